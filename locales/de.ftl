@@ -0,0 +1,105 @@
+# Deutsch
+
+## Menus and panels
+
+save-button = Speichern
+stats-title = Weltstatistik
+chronicle-title = Chronik
+achievements-title = Erfolge
+autosave-dialog-title = Automatisches Speichern schlägt fehl
+autosave-retry = Erneut versuchen
+autosave-dismiss = Schließen
+upgrade-dialog-title = Diese Welt aktualisieren?
+upgrade-now = Jetzt aktualisieren
+upgrade-later = Später
+upgrade-dialog-body = Diese Welt wurde mit einer älteren Version des Spiels gespeichert.
+upgrade-change-chunks = Chunk-Format { $from ->
+        [0] unbekannt
+       *[other] v{ $from }
+    } -> v{ $to }: { $outdated } von { $total } Chunk-Dateien werden neu geschrieben
+upgrade-change-generator = Geländegenerator { $from ->
+        [0] unbekannt
+       *[other] v{ $from }
+    } -> v{ $to }: nie gespeicherte Gebiete erzeugt die neue Version; gespeichertes Gelände bleibt erhalten
+upgrade-backup-done = Die ganze Welt wurde nach { $path } gesichert
+upgrade-backup-failed = Die Sicherung ist FEHLGESCHLAGEN ({ $error }); jetzt zu aktualisieren wird nicht empfohlen
+sign-button = Schild
+sign-dialog-title = Schild
+sign-dialog-hint = Enter speichert, Esc bricht ab. Ein leeres Schild wird entfernt
+loading-progress = Wird geladen… { $loaded }/{ $total }
+
+## Saving
+
+save-progress = Welt wird gespeichert... { $percent }%
+save-finished = Welt gespeichert ({ $chunks } Chunks, { $entities } Objekte)
+save-finished-errors = { $count ->
+    [one] Welt mit 1 Fehler gespeichert (siehe Log)
+   *[other] Welt mit { $count } Fehlern gespeichert (siehe Log)
+}
+autosave-running = Automatisches Speichern{ $dots }
+autosave-failed = Automatisches Speichern fehlgeschlagen
+autosave-dialog-body = { $count ->
+    [one] Das letzte automatische Speichern konnte nicht auf die Festplatte schreiben:
+   *[other] Die letzten { $count } automatischen Speichervorgänge konnten nicht auf die Festplatte schreiben:
+}
+
+    { $errors }
+
+    Deine letzten Änderungen sind NICHT gespeichert. Prüfe den freien Speicherplatz und die Berechtigungen des Speicherordners und versuche es dann erneut.
+autosave-unknown-error = unbekannter Fehler
+
+## Statistics
+
+stats-tiles-painted = Bemalte Kacheln: { $count }
+stats-trees-grown = Gewachsene Bäume: { $count }
+stats-snails-spawned = Erschienene Schnecken: { $count }
+stats-grass-restored = Wiederhergestelltes Gras: { $count } Kacheln
+stats-camera-traveled = Kamerastrecke: { $count } Kacheln
+stats-player-traveled = Spielerstrecke: { $count } Kacheln
+
+## World map
+
+map-title = Weltkarte ('M' zum Schließen)
+map-mode-terrain = Gelände
+map-mode-biome = Biom
+map-mode-fertility = Fruchtbarkeit
+map-mode-population = Bevölkerung
+map-scale = Chunks pro Kartenfeld: { $chunks } (Regionsdetail)
+map-detail-region = { $chunks }x{ $chunks } Chunks pro Feld
+map-detail-chunk = 1 Chunk pro Feld
+map-detail-tiles = { $tiles }x{ $tiles } Kacheln pro Feld
+map-legend = { $mode } | Kartenabdeckung: { $cells } Felder | Detail: { $detail } (Mausrad zum Zoomen) | Wegpunkte: { $waypoints } (Rechtsklick zum Setzen)
+
+## Notifications
+
+toast-guardian-cant-plant = Ein { $variant }-Wächter kann hier nicht pflanzen: { $reason }
+toast-disconnected = Verbindung zum Server getrennt: { $reason }
+toast-joined = Der Welt auf { $address } beigetreten
+toast-achievement-unlocked = Erfolg freigeschaltet: { $name }
+toast-season-arrived = { $season ->
+    [summer] Der Sommer ist da
+    [autumn] Der Herbst ist da
+    [winter] Der Winter ist da
+   *[spring] Der Frühling ist da
+}
+toast-region-protected = { $region } ist geschützt; die Kacheln dort lassen sich nicht ändern
+toast-world-upgraded = Welt aktualisiert ({ $count } Chunk-Dateien neu geschrieben)
+toast-upgrade-failed = Aktualisierung der Welt fehlgeschlagen ({ $count } Chunk-Dateien nicht neu geschrieben; siehe migration_report.txt). Beim nächsten Start wird erneut gefragt
+toast-world-exported = Welt nach { $path } exportiert
+toast-world-export-failed = Export der Welt fehlgeschlagen: { $error }
+toast-chunk-restored = Chunk ({ $x }, { $y }) war beschädigt und wurde aus einer Sicherung wiederhergestellt ({ $age })
+toast-chunk-regenerated = Chunk ({ $x }, { $y }) war beschädigt und hatte keine Sicherung; er wurde neu erzeugt (die beschädigte Datei liegt in quarantine/)
+toast-save-errors = { $count ->
+    [one] Speichern der Welt mit 1 Fehler beendet (siehe `errors`)
+   *[other] Speichern der Welt mit { $count } Fehlern beendet (siehe `errors`)
+}
+toast-autosave-recovered = Automatisches Speichern funktioniert wieder
+toast-more-errors = { $first } (und { $count } weitere, siehe `errors`)
+toast-mods-problems = Mods: { $conflicts } Konflikte, { $errors } Pakete konnten nicht geladen werden (siehe `mods`)
+toast-mods-reloaded = { $packs ->
+    [one] 1 Mod-Paket neu geladen
+   *[other] { $packs } Mod-Pakete neu geladen
+}
+toast-worldgen-reloaded = Generierungseinstellungen neu geladen, neue Chunks verwenden sie
+toast-stale-chunks = Manches Gelände hier stammt von einem älteren Weltgenerator; `regenerate stale` erzeugt die Chunks neu, die niemand bemalt hat
+toast-mods-reloaded-problems = Mod-Pakete neu geladen: { $conflicts } Konflikte, { $errors } Pakete konnten nicht geladen werden (siehe `mods`)
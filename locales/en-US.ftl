@@ -0,0 +1,105 @@
+# English (the fallback: every message must be here)
+
+## Menus and panels
+
+save-button = Save
+stats-title = World Statistics
+chronicle-title = Chronicle
+achievements-title = Achievements
+autosave-dialog-title = Autosave is failing
+autosave-retry = Retry now
+autosave-dismiss = Dismiss
+upgrade-dialog-title = Upgrade this world?
+upgrade-now = Upgrade now
+upgrade-later = Later
+upgrade-dialog-body = This world was saved by an older version of the game.
+upgrade-change-chunks = Chunk format { $from ->
+        [0] unrecorded
+       *[other] v{ $from }
+    } -> v{ $to }: { $outdated } of { $total } chunk files will be rewritten
+upgrade-change-generator = Terrain generator { $from ->
+        [0] unrecorded
+       *[other] v{ $from }
+    } -> v{ $to }: areas never saved are generated by the new version; saved terrain is kept
+upgrade-backup-done = The whole world was backed up to { $path }
+upgrade-backup-failed = The backup FAILED ({ $error }); upgrading now is not recommended
+sign-button = Sign
+sign-dialog-title = Sign
+sign-dialog-hint = Enter saves, Esc cancels. An empty sign is taken down
+loading-progress = Loading… { $loaded }/{ $total }
+
+## Saving
+
+save-progress = Saving world... { $percent }%
+save-finished = World saved ({ $chunks } chunks, { $entities } entities)
+save-finished-errors = { $count ->
+    [one] World saved with 1 error (see log)
+   *[other] World saved with { $count } errors (see log)
+}
+autosave-running = Autosaving{ $dots }
+autosave-failed = Autosave failed
+autosave-dialog-body = { $count ->
+    [one] The last autosave could not write to disk:
+   *[other] The last { $count } autosaves could not write to disk:
+}
+
+    { $errors }
+
+    Your recent edits are NOT saved. Check free disk space and permissions on the save folder, then retry.
+autosave-unknown-error = unknown error
+
+## Statistics
+
+stats-tiles-painted = Tiles painted: { $count }
+stats-trees-grown = Trees grown: { $count }
+stats-snails-spawned = Snails spawned: { $count }
+stats-grass-restored = Grass restored: { $count } tiles
+stats-camera-traveled = Camera traveled: { $count } tiles
+stats-player-traveled = Player traveled: { $count } tiles
+
+## World map
+
+map-title = World Map (Press 'M' to close)
+map-mode-terrain = Terrain
+map-mode-biome = Biome
+map-mode-fertility = Fertility
+map-mode-population = Population
+map-scale = Chunks per map tile: { $chunks } (region detail)
+map-detail-region = { $chunks }x{ $chunks } chunks per cell
+map-detail-chunk = 1 chunk per cell
+map-detail-tiles = { $tiles }x{ $tiles } tiles per cell
+map-legend = { $mode } | Map Coverage: { $cells } cells | Detail: { $detail } (scroll to zoom) | Waypoints: { $waypoints } (right-click to pin)
+
+## Notifications
+
+toast-guardian-cant-plant = A { $variant } guardian can't plant here: { $reason }
+toast-disconnected = Disconnected from the server: { $reason }
+toast-joined = Joined the world at { $address }
+toast-achievement-unlocked = Achievement unlocked: { $name }
+toast-season-arrived = { $season ->
+    [summer] Summer has arrived
+    [autumn] Autumn has arrived
+    [winter] Winter has arrived
+   *[spring] Spring has arrived
+}
+toast-region-protected = { $region } is protected; its tiles can't be changed
+toast-world-upgraded = World upgraded ({ $count } chunk files rewritten)
+toast-upgrade-failed = World upgrade failed ({ $count } chunk files couldn't be rewritten; see migration_report.txt). The world will ask again next launch
+toast-world-exported = World exported to { $path }
+toast-world-export-failed = World export failed: { $error }
+toast-chunk-restored = Chunk ({ $x }, { $y }) was corrupted and has been restored from a backup ({ $age })
+toast-chunk-regenerated = Chunk ({ $x }, { $y }) was corrupted and had no backup; it has been regenerated (damaged file kept in quarantine/)
+toast-save-errors = { $count ->
+    [one] World save finished with 1 error (see `errors`)
+   *[other] World save finished with { $count } errors (see `errors`)
+}
+toast-autosave-recovered = Autosave is working again
+toast-more-errors = { $first } (and { $count } more, see `errors`)
+toast-mods-problems = Mods: { $conflicts } conflicts, { $errors } packs failed to load (see `mods`)
+toast-mods-reloaded = { $packs ->
+    [one] Reloaded 1 mod pack
+   *[other] Reloaded { $packs } mod packs
+}
+toast-worldgen-reloaded = Reloaded generation settings, new chunks use them
+toast-stale-chunks = Some terrain here was made by an older world generator; `regenerate stale` rebuilds the chunks nobody painted
+toast-mods-reloaded-problems = Reloaded mod packs: { $conflicts } conflicts, { $errors } packs failed to load (see `mods`)
@@ -0,0 +1,359 @@
+//! Achievements: data-defined goals (`achievements.ron`, built into the game) unlocked once per
+//! profile rather than per world, so their progress lives next to the settings in
+//! `saves/achievements.ron`. Triggers are checked when the events they depend on happen; the panel
+//! (K) lists them and a toast announces each unlock
+
+use crate::config::ConfigError;
+use crate::console::console_closed;
+use crate::entities::{ForestGuardian, TreeVariant};
+use crate::error::Failures;
+use crate::locale::{Locale, LocalizedText};
+use crate::platform;
+use crate::stats::StatEvent;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Location of the achievement progress (shared by all worlds, like the settings)
+pub const ACHIEVEMENTS_PATH: &str = "saves/achievements.ron";
+
+/// Achievement definitions built into the game
+const ACHIEVEMENTS: &str = include_str!("../../achievements.ron");
+
+/// How often changed counters are written out (unlocks are written right away)
+const ACHIEVEMENTS_SAVE_INTERVAL: f32 = 30.0;
+
+/// What unlocks an achievement
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Trigger {
+    /// Trees that reached maturity, in any world
+    TreesGrown { min: u64 },
+    /// Ground tiles that turned into grass, in any world
+    GrassRestored { min: u64 },
+    /// Guardian variants with at least one guardian alive at once
+    GuardianVariants { min: u32 },
+    /// A guardian of every variant alive at once
+    EveryGuardianVariant,
+}
+
+/// One entry of `achievements.ron`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub trigger: Trigger,
+}
+
+/// The built-in achievements
+pub fn builtin() -> Result<Vec<Achievement>, ron::error::SpannedError> {
+    ron::from_str(ACHIEVEMENTS)
+}
+
+/// Per-profile progress, saved to `ACHIEVEMENTS_PATH`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementProgress {
+    pub trees_grown: u64,
+    pub grass_restored: u64,
+    /// Unlocked achievement ids, with the unix time (seconds) they were unlocked
+    pub unlocked: BTreeMap<String, u64>,
+}
+
+impl Trigger {
+    /// (current, target); `guardian_variants` is the number of variants alive right now
+    pub fn progress(&self, progress: &AchievementProgress, guardian_variants: u32) -> (u64, u64) {
+        match self {
+            Trigger::TreesGrown { min } => (progress.trees_grown, *min),
+            Trigger::GrassRestored { min } => (progress.grass_restored, *min),
+            Trigger::GuardianVariants { min } => (guardian_variants as u64, *min as u64),
+            Trigger::EveryGuardianVariant => (guardian_variants as u64, TreeVariant::ALL.len() as u64),
+        }
+    }
+}
+
+impl AchievementProgress {
+    /// Counts an event towards the counters; false if no trigger depends on it
+    pub fn record(&mut self, event: StatEvent) -> bool {
+        match event {
+            StatEvent::TreeGrown => self.trees_grown += 1,
+            StatEvent::GrassRestored => self.grass_restored += 1,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Unlocks every achievement whose trigger is now met, returning the new ones
+    pub fn unlock<'a>(&mut self, achievements: &'a [Achievement], guardian_variants: u32, now: u64) -> Vec<&'a Achievement> {
+        let mut unlocked = Vec::new();
+        for achievement in achievements {
+            if self.unlocked.contains_key(&achievement.id) {
+                continue;
+            }
+            let (current, target) = achievement.trigger.progress(self, guardian_variants);
+            if current >= target {
+                self.unlocked.insert(achievement.id.clone(), now);
+                unlocked.push(achievement);
+            }
+        }
+        unlocked
+    }
+}
+
+/// Save progress to disk as RON
+pub fn save_progress<P: AsRef<Path>>(progress: &AchievementProgress, path: P) -> Result<(), ConfigError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let text = ron::ser::to_string_pretty(progress, ron::ser::PrettyConfig::default())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Load progress from disk; a missing file is a fresh profile
+pub fn load_progress<P: AsRef<Path>>(path: P) -> Result<AchievementProgress, ConfigError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(AchievementProgress::default());
+    }
+    let text = fs::read_to_string(path)?;
+    Ok(ron::from_str(&text)?)
+}
+
+/// Definitions and this profile's progress
+#[derive(Resource, Debug, Default)]
+pub struct Achievements {
+    pub definitions: Vec<Achievement>,
+    pub progress: AchievementProgress,
+    /// Guardian variants alive at the last check
+    pub guardian_variants: u32,
+    /// Progress changed since it was last saved
+    unsaved: bool,
+}
+
+/// Marker component for the achievements panel
+#[derive(Component)]
+pub struct AchievementsPanel;
+
+/// Marker component for the achievements panel's list text
+#[derive(Component)]
+pub struct AchievementsText;
+
+/// Plugin for achievements: progress tracking, unlock toasts and the achievements panel (K)
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Achievements>()
+            .add_systems(Startup, (load_achievements, setup_achievements_panel))
+            .add_systems(Update, (
+                track_achievements,
+                save_achievements.after(track_achievements),
+                toggle_achievements_panel.run_if(console_closed),
+                update_achievements_panel.after(track_achievements).after(toggle_achievements_panel),
+            ));
+    }
+}
+
+/// Reads the definitions and the profile's progress
+fn load_achievements(mut achievements: ResMut<Achievements>, mut failures: Failures) {
+    match builtin() {
+        Ok(definitions) => achievements.definitions = definitions,
+        Err(e) => warn!("Invalid achievements file: {}", e),
+    }
+    match load_progress(ACHIEVEMENTS_PATH) {
+        Ok(progress) => achievements.progress = progress,
+        Err(e) => failures.report(format!("Failed to load achievements from {}", ACHIEVEMENTS_PATH), e),
+    }
+}
+
+/// Counts the events triggers depend on and unlocks what they complete
+fn track_achievements(
+    mut stat_events: MessageReader<StatEvent>,
+    new_guardians: Query<(), Added<ForestGuardian>>,
+    mut removed_guardians: RemovedComponents<ForestGuardian>,
+    guardians: Query<&ForestGuardian>,
+    mut achievements: ResMut<Achievements>,
+    locale: Res<Locale>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    let mut counted = false;
+    for event in stat_events.read() {
+        counted |= achievements.progress.record(*event);
+    }
+    let guardians_changed = !new_guardians.is_empty() || removed_guardians.read().count() > 0;
+    if guardians_changed {
+        achievements.guardian_variants = TreeVariant::ALL
+            .iter()
+            .filter(|variant| guardians.iter().any(|guardian| guardian.variant == **variant))
+            .count() as u32;
+    }
+    if !(counted || guardians_changed) {
+        return;
+    }
+
+    let achievements = &mut *achievements;
+    let now = platform::unix_time().as_secs();
+    let unlocked = achievements.progress.unlock(&achievements.definitions, achievements.guardian_variants, now);
+    achievements.unsaved |= counted || !unlocked.is_empty();
+    for achievement in unlocked {
+        info!("Achievement unlocked: {}", achievement.name);
+        toasts.write(Toast::info(
+            locale.format("toast-achievement-unlocked", &[("name", achievement.name.clone().into())]),
+        ));
+    }
+}
+
+/// Writes progress out: right after an unlock, every `ACHIEVEMENTS_SAVE_INTERVAL` seconds when the
+/// counters changed, and when the window closes
+fn save_achievements(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut unlocked: Local<usize>,
+    mut close_requests: MessageReader<WindowCloseRequested>,
+    mut achievements: ResMut<Achievements>,
+    mut failures: Failures,
+) {
+    *elapsed += time.delta_secs();
+    let closing = close_requests.read().count() > 0;
+    let new_unlock = achievements.progress.unlocked.len() != *unlocked;
+    if !achievements.unsaved || !(new_unlock || closing || *elapsed >= ACHIEVEMENTS_SAVE_INTERVAL) {
+        return;
+    }
+    *elapsed = 0.0;
+    *unlocked = achievements.progress.unlocked.len();
+    achievements.unsaved = false;
+    if let Err(e) = save_progress(&achievements.progress, ACHIEVEMENTS_PATH) {
+        failures.report("Failed to save achievements", e);
+    }
+}
+
+/// Spawns the hidden achievements panel (centered)
+fn setup_achievements_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            AchievementsPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Pickable::IGNORE,
+            ZIndex(900),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(440.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.08, 0.1, 0.14, 0.92)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        LocalizedText("achievements-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    panel.spawn((
+                        AchievementsText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 15.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.8, 0.85, 0.95)),
+                    ));
+                });
+        });
+}
+
+/// K toggles the achievements panel
+fn toggle_achievements_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel: Single<&mut Visibility, With<AchievementsPanel>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyK) {
+        **panel = match **panel {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Lists every achievement with its progress while the panel is open
+fn update_achievements_panel(
+    achievements: Res<Achievements>,
+    panel: Single<Ref<Visibility>, With<AchievementsPanel>>,
+    mut text: Single<&mut Text, With<AchievementsText>>,
+) {
+    if **panel == Visibility::Hidden || !(panel.is_changed() || achievements.is_changed()) {
+        return;
+    }
+
+    let lines: Vec<String> = achievements
+        .definitions
+        .iter()
+        .map(|achievement| {
+            if achievements.progress.unlocked.contains_key(&achievement.id) {
+                return format!("[x] {} - {}", achievement.name, achievement.description);
+            }
+            let (current, target) = achievement.trigger.progress(&achievements.progress, achievements.guardian_variants);
+            format!("[ ] {} - {} ({}/{})", achievement.name, achievement.description, current.min(target), target)
+        })
+        .collect();
+    text.0 = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_achievements() {
+        let definitions = builtin().unwrap();
+        let mut progress = AchievementProgress::default();
+        assert!(progress.unlock(&definitions, 0, 0).is_empty());
+
+        // The first mature tree unlocks right away, once
+        assert!(progress.record(StatEvent::TreeGrown));
+        assert!(!progress.record(StatEvent::SnailSpawned));
+        let unlocked = progress.unlock(&definitions, 0, 100);
+        assert_eq!(unlocked.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), ["first_mature_tree"]);
+        assert!(progress.unlock(&definitions, 0, 200).is_empty());
+        assert_eq!(progress.unlocked["first_mature_tree"], 100);
+
+        progress.grass_restored = 9_999;
+        assert!(progress.unlock(&definitions, 0, 0).is_empty());
+        progress.record(StatEvent::GrassRestored);
+        assert_eq!(progress.unlock(&definitions, 0, 0)[0].id, "grass_restored_10000");
+
+        // Every variant at once, not one after another
+        let all = TreeVariant::ALL.len() as u32;
+        assert!(progress.unlock(&definitions, all - 1, 0).is_empty());
+        assert_eq!(progress.unlock(&definitions, all, 0)[0].id, "guardian_council");
+
+        let path = std::env::temp_dir().join("test_achievements.ron");
+        save_progress(&progress, &path).unwrap();
+        assert_eq!(load_progress(&path).unwrap(), progress);
+        let _ = fs::remove_file(&path);
+        assert_eq!(load_progress(&path).unwrap(), AchievementProgress::default());
+    }
+}
@@ -0,0 +1,85 @@
+//! The loading screen: waits for every `SpriteAssets` image before the game starts
+
+use super::SpriteAssets;
+use crate::locale::Locale;
+use crate::schedule::GameState;
+use crate::tiles::TILE_SIZE;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+
+/// Text showing how many images have loaded
+#[derive(Component)]
+pub struct LoadingProgress;
+
+/// Pauses the world clock and covers the screen until loading is done
+pub fn setup_loading_screen(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            GlobalZIndex(100),
+            DespawnOnExit(GameState::Loading),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                LoadingProgress,
+            ));
+        });
+}
+
+/// Counts loaded (or failed) images; once all are done, turns the terrain into its array texture
+/// and starts the game
+pub fn wait_for_sprite_assets(
+    mut sprites: ResMut<SpriteAssets>,
+    assets: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    locale: Res<Locale>,
+    mut progress: Query<&mut Text, With<LoadingProgress>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let total = sprites.images().count();
+    let loaded = sprites
+        .images()
+        .filter(|handle| matches!(assets.load_state(handle.id()), LoadState::Loaded | LoadState::Failed(_)))
+        .count();
+    for mut text in progress.iter_mut() {
+        text.0 = locale.format("loading-progress", &[("loaded", loaded.into()), ("total", total.into())]);
+    }
+    if loaded < total {
+        return;
+    }
+    for handle in sprites.images() {
+        if let LoadState::Failed(error) = assets.load_state(handle.id()) {
+            warn!("Failed to load {:?}: {}", handle.path(), error);
+        }
+    }
+
+    // terrain_array.png is a column of 8x8 tiles in tile id order: reinterpret it as an array texture
+    // with one layer per tile, so tiles added to the art (decorations, say) render without code changes.
+    // Only done here, once, since reinterpreting an array texture again panics
+    if let Some(image) = images.get_mut(&sprites.terrain) {
+        let layers = (image.height() / TILE_SIZE as u32).max(1);
+        image.reinterpret_stacked_2d_as_array(layers);
+        info!("Tileset reinterpreted as {}-layer array texture", layers);
+        sprites.terrain_layers = layers;
+    }
+    info!("Loaded {} sprite images", total);
+    next_state.set(GameState::Playing);
+}
+
+pub fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
@@ -0,0 +1,186 @@
+//! Sprite sheets shared by every spawner and UI element, loaded once instead of per spawn
+
+pub mod loading;
+
+use crate::entities::EntityRegistry;
+use crate::map::{MAP_TILESET_COLS, MAP_TILESET_ROWS, MAP_TILE_SIZE};
+use crate::mods::{load_mods, RegistriesChanged};
+use crate::schedule::GameState;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// A sprite sheet and its atlas layout
+#[derive(Debug, Clone, Default)]
+pub struct SpriteSheet {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+impl SpriteSheet {
+    pub fn atlas(&self, index: usize) -> TextureAtlas {
+        TextureAtlas {
+            layout: self.layout.clone(),
+            index,
+        }
+    }
+
+    /// A world sprite showing frame `index`
+    pub fn sprite(&self, index: usize) -> Sprite {
+        Sprite::from_atlas_image(self.image.clone(), self.atlas(index))
+    }
+
+    /// A UI image showing frame `index`
+    pub fn image_node(&self, index: usize) -> ImageNode {
+        ImageNode::from_atlas_image(self.image.clone(), self.atlas(index))
+    }
+}
+
+/// Every sprite sheet the game draws from: one per registered entity type (by kind, as in
+/// `EntityRegistry`), the terrain tiles and the map tiles
+/// Sheets with the same grid share one `TextureAtlasLayout`, so spawning never adds assets
+#[derive(Resource, Debug, Default)]
+pub struct SpriteAssets {
+    entities: HashMap<String, SpriteSheet>,
+    /// Layouts by (frame size, columns, rows)
+    layouts: HashMap<(u32, u32, u32), Handle<TextureAtlasLayout>>,
+    /// The terrain array texture drawn by the tilemap chunks
+    pub terrain: Handle<Image>,
+    /// Tiles in `terrain` once it is reinterpreted as an array (0 until then)
+    pub terrain_layers: u32,
+    /// Terrain tiles for UI icons (a separate file, so it isn't reinterpreted as an array texture)
+    pub terrain_ui: SpriteSheet,
+    /// Cartographic tiles for the world map and minimap
+    pub map_tiles: SpriteSheet,
+}
+
+impl SpriteAssets {
+    pub fn load(registry: &EntityRegistry, assets: &AssetServer, layouts: &mut Assets<TextureAtlasLayout>) -> Self {
+        let mut sprites = Self {
+            terrain: assets.load("tilesets/terrain_array.png"),
+            ..default()
+        };
+        sprites.terrain_ui = SpriteSheet {
+            image: assets.load("tilesets/terrain_array_ui.png"),
+            layout: sprites.layout(8, 1, 2, layouts),
+        };
+        sprites.map_tiles = SpriteSheet {
+            image: assets.load("maps/Minifantasy_MapsLandAndSea.png"),
+            layout: sprites.layout(MAP_TILE_SIZE as u32, MAP_TILESET_COLS as u32, MAP_TILESET_ROWS as u32, layouts),
+        };
+        sprites.load_entities(registry, assets, layouts);
+        sprites
+    }
+
+    /// (Re)builds the entity sheets from the registry; sheets already loaded keep their handles
+    pub fn load_entities(
+        &mut self,
+        registry: &EntityRegistry,
+        assets: &AssetServer,
+        layouts: &mut Assets<TextureAtlasLayout>,
+    ) {
+        self.entities.clear();
+        for (kind, definition) in registry.iter() {
+            let sheet = SpriteSheet {
+                image: assets.load(definition.sprite.clone()),
+                layout: self.layout(definition.frame_size, definition.columns, definition.rows, layouts),
+            };
+            self.entities.insert(kind.to_string(), sheet);
+        }
+    }
+
+    /// The shared layout for a grid of `columns` × `rows` square frames
+    fn layout(
+        &mut self,
+        frame_size: u32,
+        columns: u32,
+        rows: u32,
+        layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> Handle<TextureAtlasLayout> {
+        self.layouts
+            .entry((frame_size, columns, rows))
+            .or_insert_with(|| {
+                layouts.add(TextureAtlasLayout::from_grid(UVec2::splat(frame_size), columns, rows, None, None))
+            })
+            .clone()
+    }
+
+    /// Every image handle, for waiting on them to load
+    pub fn images(&self) -> impl Iterator<Item = &Handle<Image>> {
+        [&self.terrain, &self.terrain_ui.image, &self.map_tiles.image]
+            .into_iter()
+            .chain(self.entities.values().map(|sheet| &sheet.image))
+    }
+
+    /// The sheet of an entity kind ("player", "guardian:oak", ...)
+    pub fn entity(&self, kind: &str) -> Option<&SpriteSheet> {
+        self.entities.get(kind)
+    }
+
+    /// Frame `index` of an entity kind's sheet; unknown kinds get an empty sprite
+    pub fn entity_sprite(&self, kind: &str, index: usize) -> Sprite {
+        match self.entity(kind) {
+            Some(sheet) => sheet.sprite(index),
+            None => {
+                warn!("No sprite sheet for entity kind '{}'", kind);
+                Sprite::default()
+            }
+        }
+    }
+}
+
+/// Plugin for `SpriteAssets`: loaded after the mod packs, refreshed when they are reloaded
+/// The game starts in `GameState::Loading` and enters `Playing` once every sheet has loaded
+pub struct SpriteAssetsPlugin;
+
+impl Plugin for SpriteAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<RegistriesChanged>()
+            .init_state::<GameState>()
+            .add_systems(PreStartup, load_sprite_assets.after(load_mods))
+            .add_systems(OnEnter(GameState::Loading), loading::setup_loading_screen)
+            .add_systems(Update, loading::wait_for_sprite_assets.run_if(in_state(GameState::Loading)))
+            .add_systems(OnExit(GameState::Loading), loading::resume_time)
+            .add_systems(Update, reload_entity_sprites);
+    }
+}
+
+pub fn load_sprite_assets(
+    mut commands: Commands,
+    registry: Res<EntityRegistry>,
+    assets: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    commands.insert_resource(SpriteAssets::load(&registry, &assets, &mut layouts));
+}
+
+/// Picks up entity types added or changed by a mod pack reload
+pub fn reload_entity_sprites(
+    mut changed: MessageReader<RegistriesChanged>,
+    mut sprites: ResMut<SpriteAssets>,
+    registry: Res<EntityRegistry>,
+    assets: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    if changed.read().count() > 0 {
+        sprites.load_entities(&registry, &assets, &mut layouts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts_are_shared() {
+        let mut layouts = Assets::<TextureAtlasLayout>::default();
+        let mut sprites = SpriteAssets::default();
+
+        let guardian = sprites.layout(32, 8, 4, &mut layouts);
+        assert_eq!(sprites.layout(32, 8, 4, &mut layouts), guardian);
+        assert_ne!(sprites.layout(32, 4, 4, &mut layouts), guardian);
+        assert_eq!(layouts.len(), 2);
+
+        assert!(sprites.entity("player").is_none());
+        assert!(sprites.entity_sprite("player", 0).texture_atlas.is_none());
+    }
+}
@@ -0,0 +1,249 @@
+use super::music_volume;
+use crate::config::GameConfig;
+use crate::debug_hud::DebugHudLines;
+use crate::environment::{DisplayHour, Weather, WorldClock, WorldWeather};
+use crate::map::{MapBiome, TerrainHistogram};
+use crate::tiles::{ChunkPos, LAYER_GROUND};
+use crate::world::WorldManager;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Seconds between soundscape samples (weather changes are picked up right away)
+const AMBIENCE_SAMPLE_INTERVAL: f32 = 3.0;
+
+/// Chunks around the camera's chunk that vote for the dominant biome (a 5x5 square)
+const AMBIENCE_RADIUS: i32 = 2;
+
+/// Seconds for a layer to fade fully in or out
+const CROSSFADE_SECONDS: f32 = 4.0;
+
+/// One looping layer of the soundscape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AmbientTrack {
+    /// Birdsong and insects (fades out at night)
+    Meadow,
+    /// Lapping water and frogs
+    Water,
+    /// A light breeze over bare ground
+    Barren,
+    /// Crickets and owls (the landscape at night)
+    Night,
+    Rain,
+    Storm,
+    Wind,
+}
+
+impl AmbientTrack {
+    pub fn path(&self) -> &'static str {
+        match self {
+            AmbientTrack::Meadow => "audio/ambience/meadow.ogg",
+            AmbientTrack::Water => "audio/ambience/water.ogg",
+            AmbientTrack::Barren => "audio/ambience/barren.ogg",
+            AmbientTrack::Night => "audio/ambience/night.ogg",
+            AmbientTrack::Rain => "audio/ambience/rain.ogg",
+            AmbientTrack::Storm => "audio/ambience/storm.ogg",
+            AmbientTrack::Wind => "audio/ambience/wind.ogg",
+        }
+    }
+}
+
+/// How much daytime is in the air: 1 from 07:00 to 18:00, 0 from 21:00 to 05:00, ramping between
+pub fn daylight(hour: f32) -> f32 {
+    let hour = hour.rem_euclid(24.0);
+    if !(5.0..21.0).contains(&hour) {
+        0.0
+    } else if hour < 7.0 {
+        (hour - 5.0) / 2.0
+    } else if hour <= 18.0 {
+        1.0
+    } else {
+        (21.0 - hour) / 3.0
+    }
+}
+
+/// Target gain (0.0 - 1.0, before the music volume) of every layer that should be playing
+/// The biome is the landscape bed, night crickets take over from the birds, and weather plays over
+/// the bed while turning it down
+pub fn ambient_mix(biome: Option<MapBiome>, hour: f32, weather: Weather) -> BTreeMap<AmbientTrack, f32> {
+    let (weather_track, bed) = match weather {
+        Weather::Clear => (None, 1.0),
+        Weather::Fog => (None, 0.6),
+        Weather::Wind => (Some((AmbientTrack::Wind, 0.8)), 0.6),
+        Weather::Rain => (Some((AmbientTrack::Rain, 1.0)), 0.4),
+        Weather::Storm => (Some((AmbientTrack::Storm, 1.0)), 0.2),
+    };
+    let day = daylight(hour);
+    let mut mix = BTreeMap::new();
+    match biome {
+        Some(MapBiome::Wetland | MapBiome::Beach) => {
+            mix.insert(AmbientTrack::Water, bed);
+        }
+        Some(MapBiome::Barren | MapBiome::Scrubland) => {
+            mix.insert(AmbientTrack::Barren, bed);
+        }
+        Some(MapBiome::Meadow) | None => {
+            mix.insert(AmbientTrack::Meadow, bed * day);
+        }
+    }
+    mix.insert(AmbientTrack::Night, bed * (1.0 - day));
+    if let Some((track, gain)) = weather_track {
+        mix.insert(track, gain);
+    }
+    mix.retain(|_, gain| *gain > 0.0);
+    mix
+}
+
+/// Biome of a chunk, classified like a map cell from its ground layer; None if it isn't cached
+pub fn chunk_biome(world: &WorldManager, pos: ChunkPos) -> Option<MapBiome> {
+    let chunk = world.get_cached_chunk(&pos)?;
+    let mut terrain = TerrainHistogram::default();
+    for &tile in chunk.layers[LAYER_GROUND].iter() {
+        terrain.add(tile, 1);
+    }
+    MapBiome::classify(&terrain)
+}
+
+/// The biome most chunks in the list have (ties go to the one listed first)
+pub fn dominant_biome(biomes: impl IntoIterator<Item = MapBiome>) -> Option<MapBiome> {
+    let mut counts: Vec<(MapBiome, u32)> = Vec::new();
+    for biome in biomes {
+        match counts.iter_mut().find(|(counted, _)| *counted == biome) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((biome, 1)),
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(biome, _)| *biome)
+}
+
+/// Moves a layer's gain towards its target at the crossfade rate
+pub fn step_gain(gain: f32, target: f32, delta_secs: f32) -> f32 {
+    let step = delta_secs / CROSSFADE_SECONDS;
+    if gain < target {
+        (gain + step).min(target)
+    } else {
+        (gain - step).max(target)
+    }
+}
+
+/// What the soundscape is made of right now (sampled every few seconds)
+#[derive(Resource, Debug, Default)]
+pub struct Soundscape {
+    pub biome: Option<MapBiome>,
+    /// Target gain of each layer; layers not listed fade out
+    pub targets: BTreeMap<AmbientTrack, f32>,
+}
+
+/// A playing layer; its sink volume is `gain` × the music volume
+#[derive(Component, Debug)]
+pub struct AmbientLoop {
+    pub track: AmbientTrack,
+    pub gain: f32,
+}
+
+/// Samples the dominant biome around the camera, the hour and the weather into the `Soundscape`
+#[allow(clippy::too_many_arguments)]
+pub fn sample_soundscape(
+    time: Res<Time>,
+    mut elapsed: Local<Option<f32>>,
+    mut last_weather: Local<Option<Weather>>,
+    world: Res<WorldManager>,
+    weather: Res<WorldWeather>,
+    clock: Res<WorldClock>,
+    display_hour: Res<DisplayHour>,
+    mut soundscape: ResMut<Soundscape>,
+    mut hud: ResMut<DebugHudLines>,
+) {
+    let elapsed = elapsed.get_or_insert(AMBIENCE_SAMPLE_INTERVAL);
+    *elapsed += time.delta_secs();
+    if *elapsed < AMBIENCE_SAMPLE_INTERVAL && *last_weather == Some(weather.weather) {
+        return;
+    }
+    *elapsed = 0.0;
+    *last_weather = Some(weather.weather);
+
+    let biome = world.camera_chunk.and_then(|center| {
+        let around = (-AMBIENCE_RADIUS..=AMBIENCE_RADIUS)
+            .flat_map(|y| (-AMBIENCE_RADIUS..=AMBIENCE_RADIUS).map(move |x| ChunkPos::new(center.x + x, center.y + y)));
+        dominant_biome(around.filter_map(|pos| chunk_biome(&world, pos)))
+    });
+    // Photo mode's time of day override is heard as well as seen
+    let hour = display_hour.0.unwrap_or_else(|| clock.hour());
+    let targets = ambient_mix(biome, hour, weather.weather);
+    if soundscape.biome != biome || soundscape.targets != targets {
+        let layers: Vec<String> = targets.iter().map(|(track, gain)| format!("{:?} {:.0}%", track, gain * 100.0)).collect();
+        hud.set("ambience", format!("Ambience: {:?} | {}", biome, layers.join(", ")));
+        *soundscape = Soundscape { biome, targets };
+    }
+}
+
+/// Crossfades the layers towards the soundscape: starts missing ones silent, fades, and stops
+/// layers once they're faded out
+pub fn mix_ambience(
+    mut commands: Commands,
+    time: Res<Time>,
+    soundscape: Res<Soundscape>,
+    config: Res<GameConfig>,
+    assets: Res<AssetServer>,
+    mut layers: Query<(Entity, &mut AmbientLoop, Option<&mut AudioSink>)>,
+) {
+    let volume = music_volume(&config.audio);
+    for (&track, _) in soundscape.targets.iter() {
+        if layers.iter().all(|(_, layer, _)| layer.track != track) {
+            commands.spawn((
+                AmbientLoop { track, gain: 0.0 },
+                AudioPlayer::new(assets.load(track.path())),
+                PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+            ));
+        }
+    }
+
+    for (entity, mut layer, sink) in layers.iter_mut() {
+        let target = soundscape.targets.get(&layer.track).copied().unwrap_or(0.0);
+        layer.gain = step_gain(layer.gain, target, time.delta_secs());
+        if layer.gain <= 0.0 && target <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // The sink appears once the sound has loaded
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(layer.gain * volume));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ambient_mix() {
+        assert_eq!(daylight(12.0), 1.0);
+        assert_eq!(daylight(2.0), 0.0);
+        assert_eq!(daylight(6.0), 0.5);
+        assert_eq!(daylight(19.5), 0.5);
+
+        // A clear meadow noon is birdsong only; midnight is crickets only
+        let noon = ambient_mix(Some(MapBiome::Meadow), 12.0, Weather::Clear);
+        assert_eq!(noon, BTreeMap::from([(AmbientTrack::Meadow, 1.0)]));
+        let midnight = ambient_mix(None, 0.0, Weather::Clear);
+        assert_eq!(midnight, BTreeMap::from([(AmbientTrack::Night, 1.0)]));
+
+        // Weather plays over a quieter bed
+        let rain = ambient_mix(Some(MapBiome::Wetland), 12.0, Weather::Rain);
+        assert_eq!(rain, BTreeMap::from([(AmbientTrack::Water, 0.4), (AmbientTrack::Rain, 1.0)]));
+
+        use MapBiome::*;
+        assert_eq!(dominant_biome([Barren, Meadow, Meadow, Wetland]), Some(Meadow));
+        assert_eq!(dominant_biome([Beach, Barren]), Some(Beach));
+        assert_eq!(dominant_biome([]), None);
+
+        assert_eq!(step_gain(0.0, 1.0, CROSSFADE_SECONDS / 2.0), 0.5);
+        assert_eq!(step_gain(0.9, 1.0, CROSSFADE_SECONDS), 1.0);
+        assert_eq!(step_gain(0.2, 0.0, CROSSFADE_SECONDS), 0.0);
+    }
+}
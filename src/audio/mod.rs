@@ -0,0 +1,225 @@
+pub mod ambience;
+pub mod stingers;
+
+pub use ambience::{AmbientLoop, AmbientTrack, Soundscape};
+pub use stingers::Stinger;
+
+use crate::config::{AudioConfig, GameConfig};
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use crate::schedule::{add_game_sets, GameSet};
+use crate::world::WorldOrigin;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+/// World sounds farther than this from the camera (in screen pixels at the current zoom) aren't played
+const HEARING_RANGE: f32 = 640.0;
+
+/// World sounds closer than this play at full volume
+const FULL_VOLUME_RANGE: f32 = 160.0;
+
+/// A one-shot sound effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// An entity placed from the toolbar
+    Place,
+    /// A tile painted
+    Paint,
+    /// A tree reaching its next growth stage
+    TreeGrowth,
+    Thunder,
+}
+
+impl Sound {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Sound::Place => "audio/sfx/place.ogg",
+            Sound::Paint => "audio/sfx/paint.ogg",
+            Sound::TreeGrowth => "audio/sfx/tree_chime.ogg",
+            Sound::Thunder => "audio/sfx/thunder.ogg",
+        }
+    }
+}
+
+/// Request to play a sound; with a position it is quieter the farther it is from the camera
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct SoundEffect {
+    pub sound: Sound,
+    pub position: Option<Vec2>,
+}
+
+impl SoundEffect {
+    /// A sound that plays at full volume wherever the camera is
+    pub fn ui(sound: Sound) -> Self {
+        Self { sound, position: None }
+    }
+
+    /// A sound coming from a world position (in pixels)
+    pub fn at(sound: Sound, position: Vec2) -> Self {
+        Self {
+            sound,
+            position: Some(position),
+        }
+    }
+}
+
+/// Plugin for sound: one-shot effects (`SoundEffect`), the ambient soundscape with its stingers and
+/// the `volume`/`mute` commands
+/// Volumes come from `GameConfig.audio`, so changes are saved with the other settings
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_sets(app);
+        app.add_message::<SoundEffect>()
+            .init_resource::<Soundscape>()
+            .add_console_command("volume", "volume [master|music|effects] [0-100] - show or set a volume")
+            .add_console_command("mute", "mute - turn all sound off or back on")
+            .add_systems(Update, handle_volume_commands.in_set(GameSet::Input))
+            // After the loader, so the camera's chunks are cached when the ambience reads their biomes
+            .add_systems(Update, (
+                play_sound_effects,
+                ambience::sample_soundscape,
+                ambience::mix_ambience.after(ambience::sample_soundscape),
+                stingers::play_stingers,
+            ).in_set(GameSet::Render));
+    }
+}
+
+/// Volume multiplier for a world sound `distance` screen pixels from the camera: full up close, fading
+/// linearly to nothing at `HEARING_RANGE`
+pub fn attenuation(distance: f32) -> f32 {
+    1.0 - ((distance - FULL_VOLUME_RANGE) / (HEARING_RANGE - FULL_VOLUME_RANGE)).clamp(0.0, 1.0)
+}
+
+/// Volume of sound effects before attenuation (0.0 - 1.0)
+pub fn effects_volume(audio: &AudioConfig) -> f32 {
+    if audio.muted {
+        0.0
+    } else {
+        audio.master_volume * audio.effects_volume
+    }
+}
+
+/// Volume of the ambience and stingers (0.0 - 1.0)
+pub fn music_volume(audio: &AudioConfig) -> f32 {
+    if audio.muted {
+        0.0
+    } else {
+        audio.master_volume * audio.music_volume
+    }
+}
+
+pub fn play_sound_effects(
+    mut commands: Commands,
+    mut effects: MessageReader<SoundEffect>,
+    config: Res<GameConfig>,
+    assets: Res<AssetServer>,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+) {
+    let base_volume = effects_volume(&config.audio);
+    if base_volume <= 0.0 {
+        effects.clear();
+        return;
+    }
+    let camera = camera_query.single().ok().map(|(transform, projection)| {
+        let scale = match projection {
+            Projection::Orthographic(ortho) => ortho.scale,
+            _ => 1.0,
+        };
+        (origin.to_world_vec2(transform.translation.truncate()), scale)
+    });
+
+    for effect in effects.read() {
+        let gain = match (effect.position, camera) {
+            (Some(position), Some((camera_position, scale))) => attenuation(position.distance(camera_position) / scale),
+            _ => 1.0,
+        };
+        if gain <= 0.0 {
+            continue;
+        }
+        commands.spawn((
+            AudioPlayer::new(assets.load(effect.sound.path())),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(base_volume * gain)),
+        ));
+    }
+}
+
+/// Console commands: `volume [master|music|effects] [0-100]` and `mute`
+pub fn handle_volume_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut config: ResMut<GameConfig>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "mute" => {
+                config.audio.muted = !config.audio.muted;
+                console.print(if config.audio.muted { "Sound off" } else { "Sound on" });
+            }
+            "volume" => {
+                let audio = &config.audio;
+                let (channel, percent) = match command.args.as_slice() {
+                    [] => {
+                        console.print(format!(
+                            "Volume: master {:.0}%, music {:.0}%, effects {:.0}%{}",
+                            audio.master_volume * 100.0,
+                            audio.music_volume * 100.0,
+                            audio.effects_volume * 100.0,
+                            if audio.muted { " (muted)" } else { "" }
+                        ));
+                        continue;
+                    }
+                    [channel, percent] => (channel.as_str(), percent.parse::<f32>().ok()),
+                    _ => ("", None),
+                };
+                let Some(percent) = percent.filter(|percent| (0.0..=100.0).contains(percent)) else {
+                    console.print("Usage: volume [master|music|effects] [0-100]");
+                    continue;
+                };
+                let audio = &mut config.audio;
+                let volume = match channel {
+                    "master" => &mut audio.master_volume,
+                    "music" => &mut audio.music_volume,
+                    "effects" => &mut audio.effects_volume,
+                    _ => {
+                        console.print("Usage: volume [master|music|effects] [0-100]");
+                        continue;
+                    }
+                };
+                *volume = percent / 100.0;
+                console.print(format!("{} volume set to {:.0}%", channel, percent));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volumes_and_stingers() {
+        assert_eq!(attenuation(0.0), 1.0);
+        assert_eq!(attenuation(FULL_VOLUME_RANGE), 1.0);
+        assert!((attenuation((FULL_VOLUME_RANGE + HEARING_RANGE) / 2.0) - 0.5).abs() < 1e-6);
+        assert_eq!(attenuation(HEARING_RANGE * 2.0), 0.0);
+
+        let mut audio = AudioConfig {
+            master_volume: 0.5,
+            music_volume: 0.5,
+            effects_volume: 1.0,
+            muted: false,
+        };
+        assert_eq!((effects_volume(&audio), music_volume(&audio)), (0.5, 0.25));
+        audio.muted = true;
+        assert_eq!((effects_volume(&audio), music_volume(&audio)), (0.0, 0.0));
+
+        // Stinger hours, across midnight too
+        assert!(stingers::crossed_hour(5.9, 6.1, 6.0));
+        assert!(!stingers::crossed_hour(6.1, 6.2, 6.0));
+        assert!(stingers::crossed_hour(23.5, 0.5, 0.0));
+        assert!(!stingers::crossed_hour(23.5, 0.5, 6.0));
+    }
+}
@@ -0,0 +1,90 @@
+use super::music_volume;
+use crate::config::GameConfig;
+use crate::environment::{SeasonChanged, WorldClock};
+use crate::world::WorldMetadata;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+/// Hours the dawn and dusk stingers play at
+const DAWN_HOUR: f32 = 6.0;
+const DUSK_HOUR: f32 = 19.0;
+
+/// Minimum seconds between stingers, so events that happen together (a new year is also a new
+/// season and a chronicle entry) play one
+const STINGER_COOLDOWN: f32 = 8.0;
+
+/// A short musical cue over the ambience, for world events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stinger {
+    Dawn,
+    Dusk,
+    /// A new season began
+    Season,
+    /// Something was written into the chronicle (milestones, objectives, ...)
+    Chronicle,
+}
+
+impl Stinger {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Stinger::Dawn => "audio/stingers/dawn.ogg",
+            Stinger::Dusk => "audio/stingers/dusk.ogg",
+            Stinger::Season => "audio/stingers/season.ogg",
+            Stinger::Chronicle => "audio/stingers/chronicle.ogg",
+        }
+    }
+}
+
+/// Whether the clock passed `hour` going from `previous` to `current` (wrapping at midnight)
+pub fn crossed_hour(previous: f32, current: f32, hour: f32) -> bool {
+    if current >= previous {
+        previous < hour && hour <= current
+    } else {
+        hour > previous || hour <= current
+    }
+}
+
+/// Plays a stinger at the music volume when the day turns, a season begins or the chronicle grows
+#[allow(clippy::too_many_arguments)]
+pub fn play_stingers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cooldown: Local<f32>,
+    mut last_hour: Local<Option<f32>>,
+    mut last_entries: Local<Option<usize>>,
+    mut season_changed: MessageReader<SeasonChanged>,
+    clock: Res<WorldClock>,
+    metadata: Res<WorldMetadata>,
+    config: Res<GameConfig>,
+    assets: Res<AssetServer>,
+) {
+    *cooldown -= time.delta_secs();
+
+    let hour = clock.hour();
+    let previous_hour = last_hour.replace(hour).unwrap_or(hour);
+    let entries = metadata.chronicle.len();
+    // The chronicle loaded with the world isn't news
+    let previous_entries = last_entries.replace(entries).unwrap_or(entries);
+
+    let stinger = if season_changed.read().count() > 0 {
+        Some(Stinger::Season)
+    } else if entries > previous_entries {
+        Some(Stinger::Chronicle)
+    } else if crossed_hour(previous_hour, hour, DAWN_HOUR) {
+        Some(Stinger::Dawn)
+    } else if crossed_hour(previous_hour, hour, DUSK_HOUR) {
+        Some(Stinger::Dusk)
+    } else {
+        None
+    };
+    let volume = music_volume(&config.audio);
+    let Some(stinger) = stinger.filter(|_| *cooldown <= 0.0 && volume > 0.0) else {
+        return;
+    };
+    *cooldown = STINGER_COOLDOWN;
+    debug!("Stinger: {:?}", stinger);
+    commands.spawn((
+        AudioPlayer::new(assets.load(stinger.path())),
+        PlaybackSettings::DESPAWN.with_volume(Volume::Linear(volume)),
+    ));
+}
@@ -0,0 +1,209 @@
+use crate::console::console_closed;
+use crate::entities::TreeSpirit;
+use crate::environment::{Season, SeasonChanged, WorldClock};
+use crate::locale::LocalizedText;
+use crate::toast::Toast;
+use crate::world::{ChronicleEntry, WorldMetadata};
+use bevy::prelude::*;
+
+/// Seconds between forest size checks
+const FOREST_CHECK_INTERVAL: f32 = 5.0;
+
+/// Tree counts worth a chronicle entry
+const FOREST_MILESTONES: [usize; 3] = [100, 500, 1000];
+
+/// Number of entries shown in the chronicle panel (newest last)
+const PANEL_ENTRIES: usize = 20;
+
+/// Plugin for the world's history log and the chronicle panel (H)
+pub struct ChroniclePlugin;
+
+impl Plugin for ChroniclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ChronicleEvent>()
+            .add_systems(Startup, setup_chronicle_panel)
+            .add_systems(Update, (
+                chronicle_new_years,
+                chronicle_forest_size,
+                record_chronicle_events.after(chronicle_new_years).after(chronicle_forest_size),
+                toggle_chronicle_panel.run_if(console_closed),
+                update_chronicle_panel.after(record_chronicle_events).after(toggle_chronicle_panel),
+            ));
+    }
+}
+
+/// Something worth writing into the world's history
+/// Milestones carry a key and are only recorded the first time
+#[derive(Message, Debug, Clone)]
+pub struct ChronicleEvent {
+    pub text: String,
+    pub milestone: Option<String>,
+}
+
+impl ChronicleEvent {
+    pub fn milestone(key: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            milestone: Some(key.into()),
+        }
+    }
+}
+
+/// Marker component for the chronicle panel
+#[derive(Component)]
+pub struct ChroniclePanel;
+
+/// Marker component for the chronicle panel's entry list
+#[derive(Component)]
+pub struct ChronicleText;
+
+/// Appends events to `WorldMetadata.chronicle` (saved with the world) and announces new ones
+fn record_chronicle_events(
+    mut events: MessageReader<ChronicleEvent>,
+    clock: Res<WorldClock>,
+    mut metadata: ResMut<WorldMetadata>,
+    mut toasts: MessageWriter<Toast>,
+) {
+    for event in events.read() {
+        let entry = ChronicleEntry {
+            day: clock.0.day,
+            text: event.text.clone(),
+            milestone: event.milestone.clone(),
+        };
+        if metadata.record_chronicle(entry) {
+            info!("Chronicle: {}", event.text);
+            toasts.write(Toast::info(event.text.clone()));
+        }
+    }
+}
+
+/// Notes the start of every year after the first
+fn chronicle_new_years(
+    mut season_changed: MessageReader<SeasonChanged>,
+    clock: Res<WorldClock>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+) {
+    for SeasonChanged(season) in season_changed.read() {
+        let year = Season::year_for_day(clock.0.day, clock.0.days_per_season);
+        if *season == Season::Spring && year > 0 {
+            chronicle.write(ChronicleEvent::milestone(
+                format!("year_{}", year + 1),
+                format!("Year {} began", year + 1),
+            ));
+        }
+    }
+}
+
+/// Notes when the forest first grows past each of `FOREST_MILESTONES` trees
+fn chronicle_forest_size(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut last_count: Local<usize>,
+    trees: Query<(), With<TreeSpirit>>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < FOREST_CHECK_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let count = trees.iter().count();
+    for milestone in FOREST_MILESTONES {
+        if *last_count < milestone && count >= milestone {
+            chronicle.write(ChronicleEvent::milestone(
+                format!("forest_{}", milestone),
+                format!("The forest reached {} trees", milestone),
+            ));
+        }
+    }
+    *last_count = count;
+}
+
+fn setup_chronicle_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            ChroniclePanel,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Pickable::IGNORE,
+            ZIndex(900),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(440.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.12, 0.1, 0.08, 0.92)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        LocalizedText("chronicle-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    panel.spawn((
+                        ChronicleText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 15.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.9, 0.85, 0.75)),
+                    ));
+                });
+        });
+}
+
+/// H toggles the chronicle panel
+fn toggle_chronicle_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut panel: Single<&mut Visibility, With<ChroniclePanel>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyH) {
+        **panel = match **panel {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Lists the most recent entries while the chronicle panel is open
+fn update_chronicle_panel(
+    metadata: Res<WorldMetadata>,
+    clock: Res<WorldClock>,
+    panel: Single<Ref<Visibility>, With<ChroniclePanel>>,
+    mut text: Single<&mut Text, With<ChronicleText>>,
+) {
+    if **panel == Visibility::Hidden || !(panel.is_changed() || metadata.is_changed()) {
+        return;
+    }
+
+    let skip = metadata.chronicle.len().saturating_sub(PANEL_ENTRIES);
+    let lines: Vec<String> = metadata.chronicle[skip..]
+        .iter()
+        .map(|entry| format!("{}: {}", Season::date_label(entry.day, clock.0.days_per_season), entry.text))
+        .collect();
+    text.0 = if lines.is_empty() {
+        "Nothing has happened yet".to_string()
+    } else {
+        lines.join("\n")
+    };
+}
@@ -0,0 +1,241 @@
+use crate::world::generator::GroundPattern;
+use crate::world::seed_code::WorldSeed;
+use bevy::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Usage text printed for `--help` and after a parse error
+pub const USAGE: &str = "\
+Usage: worldseed [options]
+
+Options:
+  --world <name|path>    open saves/<name>, or a directory if the value contains a path separator
+  --seed <n>             seed for a newly created world
+  --seed-code <code>     create a new world from a code shared with the `seedcode` console command
+  --scenario <id>        create a new world from a scenario preset (wasteland, ancient_forest, snail_infestation)
+  --world-type <type>    terrain of a newly created world: biomes (default), grass, dirt, sand, checkerboard,
+                         or debug_flat (grass with a marker at every chunk origin, for testing)
+  --window <W>x<H>       window size for this run (the saved config is left alone)
+  --headless             run the simulation without opening a window
+  --load-radius <n>      load a fixed number of chunks around the camera instead of sizing by zoom
+  --serve <addr>         share this world with other players, e.g. --serve 0.0.0.0:7878
+  --connect <addr>       join a world shared with --serve
+  --metrics <addr>       serve Prometheus metrics at http://<addr>/metrics (server builds)
+  --inspect <addr>       serve world stats, entities and console commands as JSON over WebSocket (dev-tools builds)
+  -h, --help             show this message";
+
+#[derive(Debug, PartialEq, Error)]
+pub enum CliError {
+    #[error("{0} needs a value")]
+    MissingValue(String),
+    #[error("invalid value for {option}: {value}")]
+    InvalidValue { option: String, value: String },
+    #[error("unknown option: {0}")]
+    UnknownOption(String),
+    /// Two options that can't be used together
+    #[error("{0} can't be used with {1}")]
+    Conflict(String, String),
+}
+
+/// Command-line options for one run, inserted as a resource so systems can check how the game was launched
+/// Everything is optional; anything left out falls back to the config file and built-in defaults
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct CliArgs {
+    /// World name or directory (see `world_directory`)
+    pub world: Option<String>,
+    pub seed: Option<u64>,
+    /// Seed and starting settings from `--seed-code`
+    pub seed_code: Option<WorldSeed>,
+    /// Scenario preset id (checked to exist)
+    pub scenario: Option<String>,
+    /// Ground pattern of a new world
+    pub world_type: Option<GroundPattern>,
+    /// Window width and height
+    pub window: Option<(u32, u32)>,
+    pub headless: bool,
+    pub load_radius: Option<i32>,
+    /// Address to share the world on, or of the server to join
+    pub serve: Option<String>,
+    pub connect: Option<String>,
+    /// Address for the Prometheus metrics endpoint
+    pub metrics: Option<String>,
+    /// Address for the remote inspection server
+    pub inspect: Option<String>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parse options from the process arguments (skipping the program name)
+    pub fn from_env() -> Result<Self, CliError> {
+        parse(std::env::args().skip(1))
+    }
+
+    /// Save directory for `--world`: a bare name lives under `saves/`, anything with a separator is used as is
+    /// Without `--world`, a joined world is kept under `saves/remote/<server>` so it never mixes with local saves
+    pub fn world_directory(&self) -> Option<PathBuf> {
+        if let Some(world) = &self.world {
+            return Some(if world.contains(['/', '\\']) {
+                PathBuf::from(world)
+            } else {
+                PathBuf::from("saves").join(world)
+            });
+        }
+        self.connect
+            .as_ref()
+            .map(|address| PathBuf::from("saves/remote").join(address.replace([':', '/', '\\'], "_")))
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, CliError> {
+    value.parse().map_err(|_| CliError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Parse `<W>x<H>` into a window size
+fn parse_window_size(option: &str, value: &str) -> Result<(u32, u32), CliError> {
+    let invalid = || CliError::InvalidValue {
+        option: option.to_string(),
+        value: value.to_string(),
+    };
+    let (width, height) = value.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
+
+/// Parse command-line options; values may follow as the next argument or after `=`
+pub fn parse<I, S>(args: I) -> Result<CliArgs, CliError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter().map(Into::into);
+
+    while let Some(arg) = args.next() {
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (option.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => parsed.help = true,
+            "--headless" => parsed.headless = true,
+            "--world" | "--seed" | "--seed-code" | "--scenario" | "--world-type" | "--window" | "--load-radius" | "--serve" | "--connect" | "--metrics" | "--inspect" => {
+                let value = inline_value
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError::MissingValue(option.clone()))?;
+                match option.as_str() {
+                    "--world" => parsed.world = Some(value),
+                    "--seed" => parsed.seed = Some(parse_value(&option, &value)?),
+                    "--seed-code" => {
+                        parsed.seed_code = Some(
+                            WorldSeed::decode(&value).map_err(|_| CliError::InvalidValue { option, value })?,
+                        );
+                    }
+                    "--scenario" => {
+                        if crate::scenario::builtin(&value).is_err() {
+                            return Err(CliError::InvalidValue { option, value });
+                        }
+                        parsed.scenario = Some(value);
+                    }
+                    "--world-type" => {
+                        let Some(pattern) = GroundPattern::from_name(&value) else {
+                            return Err(CliError::InvalidValue { option, value });
+                        };
+                        parsed.world_type = Some(pattern);
+                    }
+                    "--window" => parsed.window = Some(parse_window_size(&option, &value)?),
+                    "--serve" => parsed.serve = Some(value),
+                    "--connect" => parsed.connect = Some(value),
+                    "--metrics" => parsed.metrics = Some(value),
+                    "--inspect" => parsed.inspect = Some(value),
+                    _ => {
+                        let radius: i32 = parse_value(&option, &value)?;
+                        if radius < 1 {
+                            return Err(CliError::InvalidValue { option, value });
+                        }
+                        parsed.load_radius = Some(radius);
+                    }
+                }
+            }
+            _ => return Err(CliError::UnknownOption(option)),
+        }
+    }
+
+    if parsed.serve.is_some() && parsed.connect.is_some() {
+        return Err(CliError::Conflict("--serve".to_string(), "--connect".to_string()));
+    }
+    if parsed.seed.is_some() && parsed.seed_code.is_some() {
+        return Err(CliError::Conflict("--seed".to_string(), "--seed-code".to_string()));
+    }
+    // A seed code carries its own generator settings
+    if parsed.scenario.is_some() && parsed.seed_code.is_some() {
+        return Err(CliError::Conflict("--scenario".to_string(), "--seed-code".to_string()));
+    }
+    // So do seed codes and scenarios
+    if parsed.world_type.is_some() {
+        if parsed.seed_code.is_some() {
+            return Err(CliError::Conflict("--world-type".to_string(), "--seed-code".to_string()));
+        }
+        if parsed.scenario.is_some() {
+            return Err(CliError::Conflict("--world-type".to_string(), "--scenario".to_string()));
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cli_args() {
+        let args = parse([
+            "--world",
+            "meadow",
+            "--seed=42",
+            "--window",
+            "640x480",
+            "--headless",
+            "--load-radius",
+            "5",
+        ])
+        .unwrap();
+        assert_eq!(args.world_directory(), Some(PathBuf::from("saves/meadow")));
+        assert_eq!(args.seed, Some(42));
+        assert_eq!(args.window, Some((640, 480)));
+        assert!(args.headless);
+        assert_eq!(args.load_radius, Some(5));
+
+        assert_eq!(parse(Vec::<String>::new()).unwrap(), CliArgs::default());
+        let path = parse(["--world", "/tmp/bench/world"]).unwrap();
+        assert_eq!(path.world_directory(), Some(PathBuf::from("/tmp/bench/world")));
+
+        assert_eq!(parse(["--seed"]), Err(CliError::MissingValue("--seed".to_string())));
+        assert!(matches!(parse(["--window", "640"]), Err(CliError::InvalidValue { .. })));
+        assert!(matches!(parse(["--load-radius", "0"]), Err(CliError::InvalidValue { .. })));
+        assert_eq!(parse(["--fast"]), Err(CliError::UnknownOption("--fast".to_string())));
+
+        let client = parse(["--connect", "10.0.0.2:7878"]).unwrap();
+        assert_eq!(client.world_directory(), Some(PathBuf::from("saves/remote/10.0.0.2_7878")));
+        assert!(matches!(parse(["--serve=:7878", "--connect", "a:1"]), Err(CliError::Conflict(..))));
+        assert_eq!(parse(["--metrics", ":9100"]).unwrap().metrics.as_deref(), Some(":9100"));
+        assert_eq!(parse(["--inspect=127.0.0.1:9001"]).unwrap().inspect.as_deref(), Some("127.0.0.1:9001"));
+
+        let code = WorldSeed { seed: 7, ..default() }.encode();
+        assert_eq!(parse(["--seed-code", code.as_str()]).unwrap().seed_code.map(|code| code.seed), Some(7));
+        assert!(matches!(parse(["--seed-code", "WS-nope"]), Err(CliError::InvalidValue { .. })));
+        assert!(matches!(parse(["--seed=1", "--seed-code", code.as_str()]), Err(CliError::Conflict(..))));
+        assert_eq!(parse(["--scenario", "wasteland"]).unwrap().scenario.as_deref(), Some("wasteland"));
+        assert!(matches!(parse(["--scenario", "atlantis"]), Err(CliError::InvalidValue { .. })));
+        assert_eq!(parse(["--world-type", "debug_flat"]).unwrap().world_type, Some(GroundPattern::DebugFlat));
+        assert!(matches!(parse(["--world-type", "lava"]), Err(CliError::InvalidValue { .. })));
+        assert!(matches!(parse(["--world-type=grass", "--scenario", "wasteland"]), Err(CliError::Conflict(..))));
+    }
+}
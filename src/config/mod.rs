@@ -0,0 +1,323 @@
+use crate::error::Failures;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// Location of the settings file (shared by all worlds)
+pub const CONFIG_PATH: &str = "saves/settings.ron";
+
+/// Error type for settings operations
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Parse error: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Serialize error: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+/// Window and rendering settings (applied at startup)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub vsync: bool,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        Self {
+            window_width: 1280,
+            window_height: 720,
+            vsync: true,
+        }
+    }
+}
+
+/// Volume levels (0.0 - 1.0)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.7,
+            effects_volume: 0.8,
+            muted: false,
+        }
+    }
+}
+
+/// Camera controls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlsConfig {
+    /// Keyboard camera movement in pixels per second
+    pub camera_speed: f32,
+    /// Zoom change per scroll step or key press
+    pub zoom_speed: f32,
+    /// Max zoom in (smaller = more zoomed in)
+    pub zoom_min: f32,
+    /// Max zoom out (larger = more zoomed out)
+    pub zoom_max: f32,
+    /// Scroll up zooms out instead of in
+    pub invert_scroll: bool,
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        Self {
+            camera_speed: 200.0,
+            zoom_speed: 0.1,
+            zoom_min: 0.5,
+            zoom_max: 3.0,
+            invert_scroll: false,
+        }
+    }
+}
+
+/// Menus, panels and notifications
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterfaceConfig {
+    /// Language code ("en-US", "de"); see `locale::available_languages`
+    pub language: String,
+}
+
+impl Default for InterfaceConfig {
+    fn default() -> Self {
+        Self {
+            language: crate::locale::FALLBACK_LANGUAGE.to_string(),
+        }
+    }
+}
+
+/// Accessibility options (applied at runtime; see `theme`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    /// No weather particles, lightning flashes or camera glides
+    pub reduced_motion: bool,
+    /// Darker, opaque panels, brighter text and borders, and thicker selection outlines
+    pub high_contrast: bool,
+    /// Multiplies every font size (0.5 - 2.0)
+    pub font_scale: f32,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            reduced_motion: false,
+            high_contrast: false,
+            font_scale: 1.0,
+        }
+    }
+}
+
+/// Simulation and persistence tuning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplayConfig {
+    /// Chance per movement update that a snail turns its tile into dirt
+    pub snail_dirt_chance: f32,
+    /// Old versions kept per chunk file (0 disables backups)
+    pub backup_retention: usize,
+    /// Chunks kept in memory before the least recently used ones off screen are saved and dropped
+    /// (0 keeps every chunk)
+    pub chunk_cache_size: usize,
+    /// Seconds between autosaves of dirty chunks (0 disables autosave)
+    pub autosave_interval: f32,
+    /// Seconds a chunk must go unmodified before autosave writes it, so a chunk being painted isn't
+    /// re-serialized every interval (0 writes every dirty chunk); unloading and exiting always write
+    pub autosave_debounce: f32,
+    /// Creatures and trees nearest the camera that get full per-frame simulation;
+    /// the rest are advanced by the background ecosystem model (0 simulates everything fully)
+    pub full_simulation_budget: usize,
+    /// Average lightning strikes per minute during thunderstorms (0 disables lightning)
+    pub lightning_per_minute: f32,
+    /// Frame time (milliseconds) above which low-priority simulation is throttled (0 disables the governor)
+    pub frame_budget_ms: f32,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        Self {
+            snail_dirt_chance: 0.2,
+            backup_retention: crate::world::backup::DEFAULT_BACKUP_RETENTION,
+            chunk_cache_size: crate::world::manager::DEFAULT_CHUNK_CACHE_SIZE,
+            autosave_interval: 60.0,
+            autosave_debounce: 5.0,
+            full_simulation_budget: 300,
+            lightning_per_minute: 2.0,
+            frame_budget_ms: 20.0,
+        }
+    }
+}
+
+/// User settings, loaded from `CONFIG_PATH` before the app starts
+/// Systems read this resource instead of hard-coded constants
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub graphics: GraphicsConfig,
+    pub audio: AudioConfig,
+    pub controls: ControlsConfig,
+    pub gameplay: GameplayConfig,
+    pub interface: InterfaceConfig,
+    pub accessibility: AccessibilityConfig,
+}
+
+impl GameConfig {
+    /// Clamp out-of-range values, returning a description of each fix
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+        let mut clamp = |name: &str, value: &mut f32, min: f32, max: f32| {
+            let clamped = if value.is_finite() { value.clamp(min, max) } else { min };
+            if clamped != *value {
+                fixes.push(format!("{} = {} is out of range, using {}", name, value, clamped));
+                *value = clamped;
+            }
+        };
+
+        clamp("audio.master_volume", &mut self.audio.master_volume, 0.0, 1.0);
+        clamp("audio.music_volume", &mut self.audio.music_volume, 0.0, 1.0);
+        clamp("audio.effects_volume", &mut self.audio.effects_volume, 0.0, 1.0);
+        clamp("controls.camera_speed", &mut self.controls.camera_speed, 10.0, 5000.0);
+        clamp("controls.zoom_speed", &mut self.controls.zoom_speed, 0.01, 1.0);
+        clamp("controls.zoom_min", &mut self.controls.zoom_min, 0.1, 10.0);
+        let zoom_min = self.controls.zoom_min;
+        clamp("controls.zoom_max", &mut self.controls.zoom_max, zoom_min, 10.0);
+        clamp("gameplay.snail_dirt_chance", &mut self.gameplay.snail_dirt_chance, 0.0, 1.0);
+        clamp("gameplay.autosave_interval", &mut self.gameplay.autosave_interval, 0.0, 3600.0);
+        clamp("gameplay.autosave_debounce", &mut self.gameplay.autosave_debounce, 0.0, 600.0);
+        clamp("gameplay.lightning_per_minute", &mut self.gameplay.lightning_per_minute, 0.0, 60.0);
+        clamp("gameplay.frame_budget_ms", &mut self.gameplay.frame_budget_ms, 0.0, 1000.0);
+        clamp("accessibility.font_scale", &mut self.accessibility.font_scale, 0.5, 2.0);
+
+        if self.graphics.window_width < 320 || self.graphics.window_height < 240 {
+            fixes.push(format!(
+                "graphics window size {}x{} is too small, using the default",
+                self.graphics.window_width, self.graphics.window_height
+            ));
+            let defaults = GraphicsConfig::default();
+            self.graphics.window_width = defaults.window_width;
+            self.graphics.window_height = defaults.window_height;
+        }
+
+        fixes
+    }
+}
+
+/// Save settings to disk as RON
+pub fn save_config<P: AsRef<Path>>(config: &GameConfig, path: P) -> Result<(), ConfigError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let text = ron::ser::to_string_pretty(config, ron::ser::PrettyConfig::default())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Load settings from disk
+pub fn load_config<P: AsRef<Path>>(path: P) -> Result<GameConfig, ConfigError> {
+    let text = fs::read_to_string(path)?;
+    Ok(ron::from_str(&text)?)
+}
+
+/// Load and validate settings, falling back to defaults (and writing them out
+/// so the file is there to edit) when the file is missing or unreadable
+pub fn load_or_default<P: AsRef<Path>>(path: P) -> GameConfig {
+    let path = path.as_ref();
+    let mut config = if path.exists() {
+        match load_config(path) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to load settings from {:?}: {}, using defaults", path, e);
+                GameConfig::default()
+            }
+        }
+    } else {
+        let config = GameConfig::default();
+        if let Err(e) = save_config(&config, path) {
+            warn!("Failed to write default settings to {:?}: {}", path, e);
+        }
+        config
+    };
+
+    for fix in config.validate() {
+        warn!("Settings: {}", fix);
+    }
+    config
+}
+
+/// System to save settings whenever they change at runtime
+pub fn save_config_on_change(config: Res<GameConfig>, mut failures: Failures) {
+    if !config.is_changed() || config.is_added() {
+        return;
+    }
+
+    if let Err(e) = save_config(&config, CONFIG_PATH) {
+        failures.report("Failed to save settings", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_save_and_load_config() {
+        let path = env::temp_dir().join("test_settings.ron");
+
+        let mut original = GameConfig::default();
+        original.controls.camera_speed = 350.0;
+        original.gameplay.snail_dirt_chance = 0.05;
+        original.audio.muted = true;
+
+        save_config(&original, &path).expect("Failed to save settings");
+        let loaded = load_config(&path).expect("Failed to load settings");
+        assert_eq!(loaded, original);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let loaded: GameConfig =
+            ron::from_str("(gameplay: (snail_dirt_chance: 0.5))").expect("Failed to parse partial settings");
+        assert_eq!(loaded.gameplay.snail_dirt_chance, 0.5);
+        assert_eq!(loaded.gameplay.backup_retention, GameplayConfig::default().backup_retention);
+        assert_eq!(loaded.controls, ControlsConfig::default());
+    }
+
+    #[test]
+    fn test_validate_clamps_values() {
+        let mut config = GameConfig::default();
+        assert!(config.validate().is_empty());
+
+        config.gameplay.snail_dirt_chance = 3.0;
+        config.controls.zoom_min = 2.0;
+        config.controls.zoom_max = 1.0;
+        config.audio.master_volume = f32::NAN;
+        let fixes = config.validate();
+
+        assert_eq!(fixes.len(), 3);
+        assert_eq!(config.gameplay.snail_dirt_chance, 1.0);
+        assert_eq!(config.controls.zoom_max, 2.0);
+        assert_eq!(config.audio.master_volume, 0.0);
+    }
+}
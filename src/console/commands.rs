@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+/// A command typed into the console, sent to every module that handles commands
+/// Handlers match on `name` and ignore commands that aren't theirs
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+impl ConsoleCommand {
+    /// Split an input line into a command name and whitespace-separated arguments
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let name = words.next()?.to_lowercase();
+        Some(Self {
+            name,
+            args: words.collect(),
+        })
+    }
+}
+
+/// Known console commands and their usage text (shown by `help`)
+#[derive(Resource, Default)]
+pub struct ConsoleRegistry {
+    commands: Vec<(String, String)>,
+}
+
+impl ConsoleRegistry {
+    pub fn register(&mut self, name: impl Into<String>, usage: impl Into<String>) {
+        self.commands.push((name.into(), usage.into()));
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.iter().any(|(command, _)| command == name)
+    }
+
+    /// Usage lines in registration order
+    pub fn usages(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().map(|(_, usage)| usage.as_str())
+    }
+}
+
+/// Lets plugins and `main` declare console commands next to the systems handling them
+pub trait ConsoleAppExt {
+    fn add_console_command(&mut self, name: &str, usage: &str) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command(&mut self, name: &str, usage: &str) -> &mut Self {
+        self.init_resource::<ConsoleRegistry>();
+        self.world_mut()
+            .resource_mut::<ConsoleRegistry>()
+            .register(name, usage);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        let command = ConsoleCommand::parse("  Restore 3 -2   1 ").unwrap();
+        assert_eq!(command.name, "restore");
+        assert_eq!(command.args, vec!["3", "-2", "1"]);
+
+        assert_eq!(ConsoleCommand::parse("help").unwrap().args.len(), 0);
+        assert!(ConsoleCommand::parse("   ").is_none());
+    }
+}
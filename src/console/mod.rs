@@ -0,0 +1,205 @@
+mod commands;
+
+pub use commands::*;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+/// Maximum number of output lines kept in the console
+const CONSOLE_MAX_LINES: usize = 200;
+
+/// Number of output lines shown on screen
+const CONSOLE_VISIBLE_LINES: usize = 14;
+
+/// Plugin for the in-game debug console (toggle with the backquote key)
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleState>()
+            .init_resource::<TextFocus>()
+            .init_resource::<ConsoleRegistry>()
+            .add_message::<ConsoleCommand>()
+            .add_console_command("help", "help - list commands")
+            .add_console_command("clear", "clear - clear the console output")
+            .add_systems(Startup, setup_console)
+            .add_systems(Update, (
+                handle_console_input,
+                handle_builtin_commands.after(handle_console_input),
+                update_console_display,
+            ));
+    }
+}
+
+/// Console visibility, the line being typed and the output history
+#[derive(Resource, Default)]
+pub struct ConsoleState {
+    pub open: bool,
+    pub input: String,
+    pub lines: Vec<String>,
+    /// Lines printed since startup (`lines` only keeps the last `CONSOLE_MAX_LINES`)
+    pub printed: u64,
+}
+
+impl ConsoleState {
+    /// Append a line of output
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        self.printed += 1;
+        if self.lines.len() > CONSOLE_MAX_LINES {
+            let excess = self.lines.len() - CONSOLE_MAX_LINES;
+            self.lines.drain(..excess);
+        }
+    }
+}
+
+/// Set while a text field outside the console (the sign editor) takes the keyboard
+#[derive(Resource, Default)]
+pub struct TextFocus(pub bool);
+
+/// Run condition for keyboard shortcuts that shouldn't fire while typing in the console (or
+/// another text field)
+pub fn console_closed(console: Option<Res<ConsoleState>>, focus: Option<Res<TextFocus>>) -> bool {
+    console.is_none_or(|console| !console.open) && focus.is_none_or(|focus| !focus.0)
+}
+
+/// Marker component for the console root node
+#[derive(Component)]
+pub struct ConsolePanel;
+
+/// Marker component for the console output text
+#[derive(Component)]
+pub struct ConsoleOutput;
+
+/// Marker component for the console input line
+#[derive(Component)]
+pub struct ConsoleInput;
+
+/// Spawns the console panel across the top of the screen (hidden until opened)
+fn setup_console(mut commands: Commands) {
+    commands
+        .spawn((
+            ConsolePanel,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            ZIndex(1000),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ConsoleOutput,
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+            parent.spawn((
+                ConsoleInput,
+                Text::new("> "),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Toggles the console with backquote and edits the input line while it's open
+fn handle_console_input(
+    mut keyboard_inputs: MessageReader<KeyboardInput>,
+    mut console: ResMut<ConsoleState>,
+    registry: Res<ConsoleRegistry>,
+    focus: Res<TextFocus>,
+    mut commands: MessageWriter<ConsoleCommand>,
+) {
+    for input in keyboard_inputs.read() {
+        if input.state != ButtonState::Pressed || (focus.0 && !console.open) {
+            continue;
+        }
+
+        if input.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+
+        match &input.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                let Some(command) = ConsoleCommand::parse(&line) else {
+                    continue;
+                };
+                console.print(format!("> {}", line.trim()));
+                if registry.contains(&command.name) {
+                    commands.write(command);
+                } else {
+                    console.print(format!("Unknown command '{}' (type 'help')", command.name));
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+/// Handles the commands owned by the console itself
+fn handle_builtin_commands(
+    mut commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    registry: Res<ConsoleRegistry>,
+) {
+    for command in commands.read() {
+        match command.name.as_str() {
+            "help" => {
+                let usages: Vec<String> = registry.usages().map(|usage| format!("  {}", usage)).collect();
+                for usage in usages {
+                    console.print(usage);
+                }
+            }
+            "clear" => console.lines.clear(),
+            _ => {}
+        }
+    }
+}
+
+/// Shows or hides the console and redraws its text when the state changes
+fn update_console_display(
+    console: Res<ConsoleState>,
+    mut panel: Single<&mut Visibility, With<ConsolePanel>>,
+    mut output: Single<&mut Text, (With<ConsoleOutput>, Without<ConsoleInput>)>,
+    mut input: Single<&mut Text, (With<ConsoleInput>, Without<ConsoleOutput>)>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    **panel = if console.open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+
+    let start = console.lines.len().saturating_sub(CONSOLE_VISIBLE_LINES);
+    output.0 = console.lines[start..].join("\n");
+    input.0 = format!("> {}_", console.input);
+}
@@ -0,0 +1,107 @@
+use super::{DebugHud, DebugHudLines};
+use crate::console::{ConsoleCommand, ConsoleState};
+use bevy::prelude::*;
+
+/// Verbose log output, off by default: each kind floods the log (and formats a string per event)
+/// when it is on
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DebugSettings {
+    /// A line per chunk loaded, generated, saved or unloaded and per camera chunk change, plus the
+    /// chunk grid in `debug_chunks` builds
+    pub log_chunks: bool,
+    /// A line per entity placed and tile painted
+    pub log_edits: bool,
+}
+
+impl DebugSettings {
+    /// Turns one kind ("chunks", "edits" or "all") on or off; false if the kind is unknown
+    pub fn set(&mut self, kind: &str, on: bool) -> bool {
+        match kind {
+            "chunks" => self.log_chunks = on,
+            "edits" => self.log_edits = on,
+            "all" => *self = Self { log_chunks: on, log_edits: on },
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn summary(&self) -> String {
+        let state = |on: bool| if on { "on" } else { "off" };
+        format!("Verbose log: chunks {}, edits {} (F4)", state(self.log_chunks), state(self.log_edits))
+    }
+}
+
+/// F4 turns all verbose logging on or off while the debug HUD is open
+pub fn toggle_verbose_logging(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    hud: Single<&Visibility, With<DebugHud>>,
+    mut settings: ResMut<DebugSettings>,
+) {
+    if keyboard.just_pressed(KeyCode::F4) && **hud != Visibility::Hidden {
+        let on = *settings == DebugSettings::default();
+        settings.set("all", on);
+    }
+}
+
+pub fn show_debug_settings(settings: Res<DebugSettings>, mut hud: ResMut<DebugHudLines>) {
+    if settings.is_changed() {
+        hud.set("verbose", settings.summary());
+    }
+}
+
+/// Console command: `debuglog [chunks|edits|all] [on|off]`; with only a kind it toggles it
+pub fn handle_debuglog_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut settings: ResMut<DebugSettings>,
+) {
+    for command in console_commands.read() {
+        if command.name != "debuglog" {
+            continue;
+        }
+        let (kind, on) = match command.args.as_slice() {
+            [] => {
+                console.print(settings.summary());
+                continue;
+            }
+            [kind] => {
+                let on = match kind.as_str() {
+                    "chunks" => !settings.log_chunks,
+                    "edits" => !settings.log_edits,
+                    _ => *settings == DebugSettings::default(),
+                };
+                (kind.as_str(), Some(on))
+            }
+            [kind, state] => (kind.as_str(), match state.as_str() {
+                "on" => Some(true),
+                "off" => Some(false),
+                _ => None,
+            }),
+            _ => ("", None),
+        };
+        match on {
+            Some(on) if settings.set(kind, on) => console.print(settings.summary()),
+            _ => console.print("Usage: debuglog [chunks|edits|all] [on|off]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_settings() {
+        let mut settings = DebugSettings::default();
+        assert!(!settings.log_chunks && !settings.log_edits);
+
+        assert!(settings.set("chunks", true));
+        assert_eq!(settings, DebugSettings { log_chunks: true, log_edits: false });
+        assert!(settings.set("all", true));
+        assert!(settings.log_edits);
+        assert!(!settings.set("entities", false));
+        assert!(settings.set("all", false));
+        assert_eq!(settings, DebugSettings::default());
+        assert_eq!(settings.summary(), "Verbose log: chunks off, edits off (F4)");
+    }
+}
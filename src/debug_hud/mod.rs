@@ -0,0 +1,87 @@
+pub mod logging;
+
+pub use logging::DebugSettings;
+
+use crate::console::{console_closed, ConsoleAppExt};
+use crate::theme::ThemedPanel;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Plugin for the debug HUD (F3): a text block in the bottom right that any system can add lines to,
+/// and the verbose logging switches (`DebugSettings`)
+pub struct DebugHudPlugin;
+
+impl Plugin for DebugHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugHudLines>()
+            .init_resource::<DebugSettings>()
+            .add_console_command("debuglog", "debuglog [chunks|edits|all] [on|off] - show or toggle verbose logging")
+            .add_systems(Startup, setup_debug_hud)
+            .add_systems(Update, (
+                toggle_debug_hud.run_if(console_closed),
+                update_debug_hud.after(toggle_debug_hud),
+                logging::handle_debuglog_commands,
+                logging::toggle_verbose_logging.run_if(console_closed),
+                logging::show_debug_settings.before(update_debug_hud),
+            ));
+    }
+}
+
+/// Lines shown in the debug HUD, keyed (and sorted) by section name
+/// Systems overwrite their own section with `set`; writing only happens when the text changes
+#[derive(Resource, Default)]
+pub struct DebugHudLines(BTreeMap<&'static str, String>);
+
+impl DebugHudLines {
+    pub fn set(&mut self, section: &'static str, text: String) {
+        if self.0.get(section) != Some(&text) {
+            self.0.insert(section, text);
+        }
+    }
+}
+
+/// Marker component for the debug HUD text
+#[derive(Component)]
+pub struct DebugHud;
+
+fn setup_debug_hud(mut commands: Commands) {
+    commands.spawn((
+        DebugHud,
+        ThemedPanel,
+        Text::new(""),
+        TextFont {
+            font_size: 13.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 1.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(56.0),
+            right: Val::Px(10.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Pickable::IGNORE,
+        ZIndex(800),
+        Visibility::Hidden,
+    ));
+}
+
+/// F3 toggles the debug HUD
+fn toggle_debug_hud(keyboard: Res<ButtonInput<KeyCode>>, mut hud: Single<&mut Visibility, With<DebugHud>>) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        **hud = match **hud {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn update_debug_hud(lines: Res<DebugHudLines>, hud: Single<(Ref<Visibility>, &mut Text), With<DebugHud>>) {
+    let (visibility, mut text) = hud.into_inner();
+    if *visibility == Visibility::Hidden || !(lines.is_changed() || visibility.is_changed()) {
+        return;
+    }
+    text.0 = lines.0.values().cloned().collect::<Vec<_>>().join("\n");
+}
@@ -0,0 +1,83 @@
+use super::{PhotoMode, PHOTO_CAMERA_SPEED, PHOTO_ZOOM_MAX, PHOTO_ZOOM_MIN};
+use crate::config::GameConfig;
+use crate::map::MapState;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+/// Camera movement system for testing chunk loading (slower in photo mode, for framing shots)
+pub fn move_camera(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    photo: Res<PhotoMode>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if let Ok(mut transform) = camera_query.single_mut() {
+        let mut speed = config.controls.camera_speed; // pixels per second
+        if photo.active {
+            speed *= PHOTO_CAMERA_SPEED;
+        }
+        let delta = time.delta_secs();
+
+        if keyboard.pressed(KeyCode::KeyW) || keyboard.pressed(KeyCode::ArrowUp) {
+            transform.translation.y += speed * delta;
+        }
+        if keyboard.pressed(KeyCode::KeyS) || keyboard.pressed(KeyCode::ArrowDown) {
+            transform.translation.y -= speed * delta;
+        }
+        if keyboard.pressed(KeyCode::KeyA) || keyboard.pressed(KeyCode::ArrowLeft) {
+            transform.translation.x -= speed * delta;
+        }
+        if keyboard.pressed(KeyCode::KeyD) || keyboard.pressed(KeyCode::ArrowRight) {
+            transform.translation.x += speed * delta;
+        }
+    }
+}
+
+/// Camera zoom system - supports scroll wheel and keyboard (- and = keys)
+/// Input is ignored while the map is open (the map uses scroll for its own zoom)
+/// Photo mode allows zooming further in and out than the configured limits
+pub fn zoom_camera(
+    mut scroll_events: MessageReader<MouseWheel>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    map_state: Res<MapState>,
+    config: Res<GameConfig>,
+    photo: Res<PhotoMode>,
+    mut camera_query: Query<&mut Projection, With<Camera2d>>,
+) {
+    if map_state.visible {
+        scroll_events.clear();
+        return;
+    }
+
+    if let Ok(mut projection) = camera_query.single_mut() {
+        let controls = &config.controls;
+        let scroll_direction = if controls.invert_scroll { -1.0 } else { 1.0 };
+        let mut zoom_delta = 0.0;
+
+        // Handle scroll wheel input
+        for event in scroll_events.read() {
+            zoom_delta -= event.y * controls.zoom_speed * scroll_direction;
+        }
+
+        // Handle keyboard input (- to zoom out, = to zoom in)
+        if keyboard.just_pressed(KeyCode::Minus) {
+            zoom_delta += controls.zoom_speed;
+        }
+        if keyboard.just_pressed(KeyCode::Equal) {
+            zoom_delta -= controls.zoom_speed;
+        }
+
+        // Apply zoom delta and clamp to bounds
+        if zoom_delta != 0.0 {
+            let (zoom_min, zoom_max) = if photo.active {
+                (PHOTO_ZOOM_MIN, PHOTO_ZOOM_MAX)
+            } else {
+                (controls.zoom_min, controls.zoom_max)
+            };
+            if let Projection::Orthographic(ref mut ortho) = projection.as_mut() {
+                ortho.scale = (ortho.scale + zoom_delta).clamp(zoom_min, zoom_max);
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::tiles::{ChunkPos, CHUNK_PIXEL_SIZE};
+use crate::world::WorldOrigin;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Seconds for a chunk's edit heat to halve
+const HEAT_HALF_LIFE: f32 = 300.0;
+
+/// Heat at which a chunk glows fully (about this many recent tile edits)
+const FULL_GLOW_HEAT: f32 = 32.0;
+
+/// Chunks cooler than this are forgotten
+const MIN_HEAT: f32 = 0.05;
+
+/// Opacity of a fully glowing chunk
+const MAX_GLOW_ALPHA: f32 = 0.55;
+
+/// Above the tile layers, below entity sprites (1.0+)
+const HEATMAP_Z: f32 = 0.5;
+
+/// Chunks listed by `heatmap list`
+const HOTTEST_LISTED: usize = 5;
+
+/// How much and how recently a chunk was painted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkHeat {
+    /// Decaying count of tile edits
+    pub heat: f32,
+    /// `Time::elapsed_secs` of the last edit
+    pub last_edit: f32,
+}
+
+/// Where the user has painted terrain this session (locally, or on the server by any client), as a
+/// decaying per-chunk edit counter, and whether the overlay showing it is on
+#[derive(Resource, Debug, Default)]
+pub struct EditHeatmap {
+    pub chunks: HashMap<ChunkPos, ChunkHeat>,
+    pub visible: bool,
+}
+
+impl EditHeatmap {
+    /// Counts one tile edit at a world position
+    pub fn record(&mut self, world_pos: Vec2, now: f32) {
+        let chunk = ChunkPos::from_world(world_pos, CHUNK_PIXEL_SIZE);
+        let entry = self.chunks.entry(chunk).or_insert(ChunkHeat { heat: 0.0, last_edit: now });
+        entry.heat += 1.0;
+        entry.last_edit = now;
+    }
+
+    /// Cools every chunk by `delta_secs`, forgetting the cold ones
+    pub fn decay(&mut self, delta_secs: f32) {
+        let factor = 0.5_f32.powf(delta_secs / HEAT_HALF_LIFE);
+        self.chunks.retain(|_, chunk| {
+            chunk.heat *= factor;
+            chunk.heat >= MIN_HEAT
+        });
+    }
+
+    /// How strongly a chunk glows (0.0 - 1.0)
+    pub fn glow(&self, chunk: ChunkPos) -> f32 {
+        self.chunks.get(&chunk).map_or(0.0, |chunk| (chunk.heat / FULL_GLOW_HEAT).min(1.0))
+    }
+
+    /// The hottest chunks, hottest first
+    pub fn hottest(&self, count: usize) -> Vec<(ChunkPos, ChunkHeat)> {
+        let mut chunks: Vec<(ChunkPos, ChunkHeat)> = self.chunks.iter().map(|(pos, heat)| (*pos, *heat)).collect();
+        chunks.sort_by(|a, b| b.1.heat.total_cmp(&a.1.heat).then_with(|| (a.0.x, a.0.y).cmp(&(b.0.x, b.0.y))));
+        chunks.truncate(count);
+        chunks
+    }
+}
+
+/// Glow color of a chunk: from a dim amber to a bright red as it heats up
+pub fn glow_color(glow: f32) -> Color {
+    Color::hsla(40.0 * (1.0 - glow), 1.0, 0.5, glow * MAX_GLOW_ALPHA)
+}
+
+/// Sprite glowing over an edited chunk
+#[derive(Component)]
+pub struct HeatmapTile(pub ChunkPos);
+
+/// Cools the heatmap every frame
+pub fn decay_edit_heat(time: Res<Time>, mut heatmap: ResMut<EditHeatmap>) {
+    if !heatmap.chunks.is_empty() {
+        heatmap.decay(time.delta_secs());
+    }
+}
+
+/// Toggles the edit heatmap overlay with F7
+pub fn toggle_edit_heatmap(keyboard: Res<ButtonInput<KeyCode>>, mut heatmap: ResMut<EditHeatmap>) {
+    if keyboard.just_pressed(KeyCode::F7) {
+        heatmap.visible = !heatmap.visible;
+    }
+}
+
+/// Keeps one glowing sprite over every edited chunk while the overlay is on
+pub fn draw_edit_heatmap(
+    mut commands: Commands,
+    heatmap: Res<EditHeatmap>,
+    origin: Res<WorldOrigin>,
+    mut tiles: Query<(Entity, &HeatmapTile, &mut Sprite, &mut Transform)>,
+) {
+    let translation = |chunk: ChunkPos| (origin.chunk_translation(chunk) + Vec2::splat(CHUNK_PIXEL_SIZE / 2.0)).extend(HEATMAP_Z);
+    let mut drawn = Vec::new();
+    for (entity, tile, mut sprite, mut transform) in tiles.iter_mut() {
+        let glow = heatmap.glow(tile.0);
+        if !heatmap.visible || glow <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite.color = glow_color(glow);
+        transform.translation = translation(tile.0);
+        drawn.push(tile.0);
+    }
+    if !heatmap.visible {
+        return;
+    }
+
+    for &chunk in heatmap.chunks.keys().filter(|chunk| !drawn.contains(chunk)) {
+        commands.spawn((
+            HeatmapTile(chunk),
+            Sprite::from_color(glow_color(heatmap.glow(chunk)), Vec2::splat(CHUNK_PIXEL_SIZE)),
+            Transform::from_translation(translation(chunk)),
+        ));
+    }
+}
+
+/// Console command: `heatmap [on|off|clear]` toggles the overlay or forgets the edits; without
+/// arguments lists the most edited chunks
+pub fn handle_heatmap_commands(
+    time: Res<Time>,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut heatmap: ResMut<EditHeatmap>,
+) {
+    for command in console_commands.read() {
+        if command.name != "heatmap" {
+            continue;
+        }
+
+        match command.args.first().map(String::as_str) {
+            None => {
+                if heatmap.chunks.is_empty() {
+                    console.print("No recent terrain edits");
+                }
+                for (pos, chunk) in heatmap.hottest(HOTTEST_LISTED) {
+                    console.print(format!(
+                        "Chunk ({}, {}): heat {:.1}, last edited {:.0}s ago",
+                        pos.x,
+                        pos.y,
+                        chunk.heat,
+                        time.elapsed_secs() - chunk.last_edit
+                    ));
+                }
+            }
+            Some("on") => heatmap.visible = true,
+            Some("off") => heatmap.visible = false,
+            Some("clear") => {
+                heatmap.chunks.clear();
+                console.print("Edit heatmap cleared");
+            }
+            Some(_) => console.print("Usage: heatmap [on|off|clear]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_heat() {
+        let mut heatmap = EditHeatmap::default();
+        for _ in 0..16 {
+            heatmap.record(Vec2::new(10.0, 10.0), 1.0);
+        }
+        heatmap.record(Vec2::new(-10.0, 300.0), 2.0);
+        assert_eq!(heatmap.glow(ChunkPos::new(0, 0)), 0.5);
+        assert_eq!(heatmap.hottest(1), vec![(ChunkPos::new(0, 0), ChunkHeat { heat: 16.0, last_edit: 1.0 })]);
+
+        // Heat halves every half-life, and cold chunks are forgotten
+        heatmap.decay(HEAT_HALF_LIFE);
+        assert!((heatmap.glow(ChunkPos::new(0, 0)) - 0.25).abs() < 1e-5);
+        heatmap.decay(HEAT_HALF_LIFE * 5.0);
+        assert_eq!(heatmap.chunks.keys().copied().collect::<Vec<_>>(), vec![ChunkPos::new(0, 0)]);
+        assert_eq!(heatmap.glow(ChunkPos::new(-1, 1)), 0.0);
+    }
+}
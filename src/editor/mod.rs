@@ -0,0 +1,139 @@
+mod camera;
+mod heatmap;
+mod photo;
+mod session;
+mod signs;
+mod tools;
+mod tour;
+#[cfg(feature = "editor-ui")]
+mod ui;
+
+pub use camera::*;
+pub use heatmap::*;
+pub use photo::*;
+pub use session::*;
+pub use signs::*;
+pub use tools::*;
+pub use tour::*;
+#[cfg(feature = "editor-ui")]
+pub use ui::*;
+
+use crate::assets::SpriteAssets;
+use crate::console::{console_closed, ConsoleAppExt};
+use crate::debug_hud::DebugSettings;
+use crate::entities::{spawn_forest_guardian, spawn_player, spawn_snail, spawn_tree_spirit, Position, TreeVariant};
+use crate::net::is_world_authority;
+use crate::schedule::{add_game_sets, GameSet};
+use crate::world::{autosave, metadata, save, upgrade, WorldMetadata};
+use bevy::prelude::*;
+
+/// Plugin for the editor: camera controls, the entity/terrain toolbar, placing and painting
+/// with the mouse, sign posts, the edit heatmap, photo mode, the spectator tour, and restoring the camera and tools from the previous session
+/// Without the `editor-ui` feature there is no toolbar; placing and painting still work with tools
+/// restored from the session
+pub struct EditorUiPlugin;
+
+impl Plugin for EditorUiPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_sets(app);
+        app.init_resource::<PlacementMode>()
+            .init_resource::<DebugSettings>()
+            .init_resource::<PaintMode>()
+            .init_resource::<PhotoMode>()
+            .init_resource::<TourState>()
+            .init_resource::<EditHeatmap>()
+            .init_resource::<SignEditor>()
+            .add_message::<PlaceEntityRequest>()
+            .add_console_command("tour", "tour [seconds|off] - fly the camera between interesting places")
+            .add_console_command("heatmap", "heatmap [on|off|clear] - show recently painted chunks (F7), or list them")
+            // After world metadata is loaded, to know whether a scenario replaces the starting entities
+            .add_systems(Startup, setup_world.after(metadata::load_world_metadata))
+            // After world metadata is loaded and the camera exists
+            .add_systems(PostStartup, restore_session_state)
+            .add_systems(Startup, setup_sign_dialog)
+            .add_systems(Update, (
+                move_camera.run_if(console_closed),
+                zoom_camera.run_if(console_closed),
+                handle_entity_placement.run_if(autosave::autosave_dialog_closed).run_if(upgrade::upgrade_dialog_closed).run_if(photo_mode_off).run_if(sign_editor_closed),
+                // A client sends its requests to the server instead (see `net`)
+                apply_entity_placements.after(handle_entity_placement).run_if(is_world_authority),
+                handle_terrain_painting.run_if(autosave::autosave_dialog_closed).run_if(upgrade::upgrade_dialog_closed).run_if(photo_mode_off).run_if(sign_editor_closed),
+                handle_sign_editor_input.after(handle_entity_placement),
+                toggle_photo_mode.run_if(console_closed),
+                photo_mode_controls.after(toggle_photo_mode).run_if(console_closed).run_if(photo_mode_on),
+                finish_photo_capture,
+                track_newest_guardian,
+                toggle_tour.run_if(console_closed),
+                handle_tour_commands,
+                run_tour.after(move_camera).after(toggle_tour).after(handle_tour_commands).run_if(tour_active),
+                clear_tour_hud.after(run_tour),
+                toggle_edit_heatmap.run_if(console_closed),
+                handle_heatmap_commands,
+            ).in_set(GameSet::Input))
+            .add_systems(Update, (decay_edit_heat, draw_edit_heatmap).chain().in_set(GameSet::Render))
+            .add_systems(Update, (
+                update_sign_dialog,
+                sync_sign_posts,
+                update_sign_label_visibility.after(sync_sign_posts),
+            ).in_set(GameSet::Render))
+            .add_systems(Update, snapshot_session_state
+                .before(metadata::save_world_metadata)
+                .before(save::run_save_world));
+        #[cfg(feature = "editor-ui")]
+        app
+            .add_systems(Startup, setup_ui)
+            .add_systems(Update, (
+                update_button_selection,
+                update_terrain_button_selection,
+            ).in_set(GameSet::Render));
+    }
+}
+
+/// Spawns the camera and a starting set of entities around the origin
+/// Worlds created from a scenario get the scenario's entities instead (see `scenario`)
+pub fn setup_world(mut commands: Commands, sprites: Res<SpriteAssets>, mut world_metadata: ResMut<WorldMetadata>) {
+    // Spawn camera at origin
+    commands.spawn((Camera2d, Transform::from_xyz(0.0, 0.0, 999.0)));
+    if world_metadata.scenario.is_some() {
+        return;
+    }
+
+    // Spawn player character at world origin
+    spawn_player(
+        &mut commands,
+        Position::new(0.0, 0.0),
+        &sprites,
+    );
+
+    // The creatures below come back from their chunks after the first session
+    if world_metadata.starting_entities_spawned {
+        return;
+    }
+    world_metadata.starting_entities_spawned = true;
+
+    // Spawn forest guardian to the left
+    spawn_forest_guardian(
+        &mut commands,
+        Position::new(-100.0, 0.0),
+        "oak",
+        &sprites,
+    );
+
+    // Spawn snail to the right
+    spawn_snail(
+        &mut commands,
+        Position::new(100.0, 0.0),
+        &sprites,
+    );
+
+    // Spawn a test tree spirit above the player - grows every 3 seconds per stage
+    spawn_tree_spirit(
+        &mut commands,
+        Position::new(0.0, 100.0),
+        TreeVariant::Oak,
+        3.0, // 3 seconds per growth stage
+        &sprites,
+    );
+
+    info!("World setup complete with entities using position and state components");
+}
\ No newline at end of file
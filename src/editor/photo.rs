@@ -0,0 +1,310 @@
+//! Photo mode (F2): hides every UI panel, slows the camera and lifts the zoom limits, and can
+//! override the time of day the lighting shows and add a color filter. P captures the view at
+//! several times the window resolution into `screenshots/`
+
+use crate::environment::DisplayHour;
+use crate::platform;
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::{RenderTarget, ScalingMode};
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::render_resource::{TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use bevy::render::view::{ColorGrading, ColorGradingGlobal, ColorGradingSection};
+use std::path::PathBuf;
+
+/// Camera speed in photo mode, relative to `ControlsConfig.camera_speed`
+pub const PHOTO_CAMERA_SPEED: f32 = 0.25;
+
+/// Zoom range in photo mode (the normal range comes from `ControlsConfig`)
+pub const PHOTO_ZOOM_MIN: f32 = 0.1;
+pub const PHOTO_ZOOM_MAX: f32 = 12.0;
+
+/// Captures are this many times the window resolution...
+const CAPTURE_FACTOR: u32 = 4;
+
+/// ...unless that makes a side longer than this (a safe texture size on most GPUs)
+const MAX_CAPTURE_SIDE: u32 = 8192;
+
+/// Frames to wait for a capture before giving the window back anyway
+const MAX_CAPTURE_FRAMES: u32 = 10;
+
+/// Directory photos are written to
+const PHOTO_DIRECTORY: &str = "screenshots";
+
+/// Color filters, applied with the camera's color grading
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PhotoFilter {
+    #[default]
+    None,
+    Vivid,
+    Warm,
+    Cool,
+    Sepia,
+    Noir,
+}
+
+impl PhotoFilter {
+    pub const ALL: [PhotoFilter; 6] = [Self::None, Self::Vivid, Self::Warm, Self::Cool, Self::Sepia, Self::Noir];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|filter| *filter == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Vivid => "vivid",
+            Self::Warm => "warm",
+            Self::Cool => "cool",
+            Self::Sepia => "sepia",
+            Self::Noir => "noir",
+        }
+    }
+
+    /// The grading for this filter; None shows the scene as usual
+    pub fn color_grading(self) -> Option<ColorGrading> {
+        let global = |temperature: f32, post_saturation: f32| ColorGradingGlobal {
+            temperature,
+            post_saturation,
+            ..default()
+        };
+        let section = |saturation: f32, contrast: f32| ColorGradingSection {
+            saturation,
+            contrast,
+            ..default()
+        };
+        let (global, section) = match self {
+            Self::None => return None,
+            Self::Vivid => (global(0.0, 1.4), section(1.1, 1.1)),
+            Self::Warm => (global(0.35, 1.0), section(1.0, 1.0)),
+            Self::Cool => (global(-0.35, 1.0), section(1.0, 1.0)),
+            Self::Sepia => (global(0.6, 0.25), section(1.0, 1.05)),
+            Self::Noir => (global(0.0, 0.0), section(1.0, 1.25)),
+        };
+        Some(ColorGrading::with_identical_sections(global, section))
+    }
+}
+
+/// The hour shown `delta` hours from `current`, wrapped into 0-24
+pub fn shift_hour(current: f32, delta: f32) -> f32 {
+    (current + delta).rem_euclid(24.0)
+}
+
+/// Capture size for a window: `CAPTURE_FACTOR` times larger, scaled down to fit `MAX_CAPTURE_SIDE`
+pub fn capture_size(window: UVec2) -> UVec2 {
+    let longest = window.max_element().max(1);
+    let factor = CAPTURE_FACTOR.min(MAX_CAPTURE_SIDE / longest).max(1);
+    window * factor
+}
+
+/// Where the camera rendered before a capture took it over
+struct PhotoCapture {
+    target: RenderTarget,
+    scaling_mode: ScalingMode,
+    frames: u32,
+    done: bool,
+}
+
+/// Photo mode state
+#[derive(Resource, Default)]
+pub struct PhotoMode {
+    pub active: bool,
+    pub filter: PhotoFilter,
+    /// An inactive camera marked as the default UI camera, so no UI is drawn while it exists
+    ui_blocker: Option<Entity>,
+    capture: Option<PhotoCapture>,
+}
+
+/// Run condition: photo mode is off (editing input is ignored while it's on)
+pub fn photo_mode_off(photo: Option<Res<PhotoMode>>) -> bool {
+    photo.is_none_or(|photo| !photo.active)
+}
+
+/// Run condition: photo mode is on
+pub fn photo_mode_on(photo: Option<Res<PhotoMode>>) -> bool {
+    !photo_mode_off(photo)
+}
+
+/// F2 enters or leaves photo mode (Escape leaves it too)
+/// Leaving puts the UI, the time of day, the filter and the zoom limits back
+pub fn toggle_photo_mode(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut photo: ResMut<PhotoMode>,
+    mut display_hour: ResMut<DisplayHour>,
+    config: Res<crate::config::GameConfig>,
+    camera: Single<(Entity, &mut Projection), With<Camera2d>>,
+) {
+    let entering = keyboard.just_pressed(KeyCode::F2) && !photo.active;
+    let leaving = photo.active && (keyboard.just_pressed(KeyCode::F2) || keyboard.just_pressed(KeyCode::Escape));
+    if !(entering || leaving) || photo.capture.is_some() {
+        return;
+    }
+    let (camera_entity, mut projection) = camera.into_inner();
+
+    if entering {
+        photo.active = true;
+        photo.ui_blocker = Some(
+            commands
+                .spawn((
+                    Name::new("Photo mode UI blocker"),
+                    Camera {
+                        is_active: false,
+                        order: -1,
+                        ..default()
+                    },
+                    IsDefaultUiCamera,
+                ))
+                .id(),
+        );
+        info!("Photo mode on: WASD moves, scroll zooms, [ ] change the hour, 0 resets it, F cycles filters, P captures, F2/Esc leaves");
+        return;
+    }
+
+    photo.active = false;
+    if let Some(blocker) = photo.ui_blocker.take() {
+        commands.entity(blocker).despawn();
+    }
+    display_hour.0 = None;
+    photo.filter = PhotoFilter::None;
+    commands.entity(camera_entity).remove::<ColorGrading>().insert(Tonemapping::None);
+    if let Projection::Orthographic(ortho) = projection.as_mut() {
+        ortho.scale = ortho.scale.clamp(config.controls.zoom_min, config.controls.zoom_max);
+    }
+    info!("Photo mode off");
+}
+
+/// Photo mode keys: time of day, filter and capture
+#[allow(clippy::too_many_arguments)]
+pub fn photo_mode_controls(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut photo: ResMut<PhotoMode>,
+    mut display_hour: ResMut<DisplayHour>,
+    clock: Res<crate::environment::WorldClock>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window>,
+    camera: Single<(Entity, &mut Camera, &mut Projection), With<Camera2d>>,
+) {
+    if photo.capture.is_some() {
+        return;
+    }
+    let (camera_entity, mut camera, mut projection) = camera.into_inner();
+
+    let hour_step = match (keyboard.just_pressed(KeyCode::BracketLeft), keyboard.just_pressed(KeyCode::BracketRight)) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    };
+    if hour_step != 0.0 {
+        let hour = shift_hour(display_hour.0.unwrap_or_else(|| clock.hour()).round(), hour_step);
+        display_hour.0 = Some(hour);
+        info!("Photo mode: lighting at {:02}:00", hour as u32);
+    }
+    if keyboard.just_pressed(KeyCode::Digit0) && display_hour.0.take().is_some() {
+        info!("Photo mode: lighting follows the clock again");
+    }
+
+    if keyboard.just_pressed(KeyCode::KeyF) {
+        photo.filter = photo.filter.next();
+        // Color grading is applied by the tonemapping pass, which 2D cameras skip by default
+        match photo.filter.color_grading() {
+            Some(grading) => commands.entity(camera_entity).insert((grading, Tonemapping::TonyMcMapface)),
+            None => commands.entity(camera_entity).remove::<ColorGrading>().insert(Tonemapping::None),
+        };
+        info!("Photo mode: filter {}", photo.filter.label());
+    }
+
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        let Projection::Orthographic(ortho) = projection.as_mut() else {
+            return;
+        };
+        // Render the same view into a larger image: a fixed view size keeps the area, the bigger
+        // target adds the pixels
+        let size = capture_size(window.physical_size());
+        let mut image = Image::new_target_texture(size.x, size.y, TextureFormat::bevy_default());
+        image.texture_descriptor.usage |= TextureUsages::COPY_SRC;
+        image.asset_usage = RenderAssetUsages::RENDER_WORLD;
+        let handle = images.add(image);
+
+        photo.capture = Some(PhotoCapture {
+            target: std::mem::replace(&mut camera.target, RenderTarget::Image(handle.clone().into())),
+            scaling_mode: std::mem::replace(
+                &mut ortho.scaling_mode,
+                ScalingMode::Fixed {
+                    width: window.width(),
+                    height: window.height(),
+                },
+            ),
+            frames: 0,
+            done: false,
+        });
+
+        let path = PathBuf::from(PHOTO_DIRECTORY).join(format!("photo_{}.png", platform::unix_time().as_millis()));
+        if let Err(e) = std::fs::create_dir_all(PHOTO_DIRECTORY) {
+            warn!("Failed to create {}: {}", PHOTO_DIRECTORY, e);
+        }
+        info!("Photo mode: capturing {}x{} to {:?}", size.x, size.y, path);
+        commands
+            .spawn(Screenshot::image(handle))
+            .observe(save_to_disk(path))
+            .observe(mark_photo_captured);
+    }
+}
+
+fn mark_photo_captured(_captured: On<ScreenshotCaptured>, mut photo: ResMut<PhotoMode>) {
+    if let Some(capture) = photo.capture.as_mut() {
+        capture.done = true;
+    }
+}
+
+/// Gives the camera back to the window once the capture is read back (or took too long)
+pub fn finish_photo_capture(
+    mut photo: ResMut<PhotoMode>,
+    camera: Single<(&mut Camera, &mut Projection), With<Camera2d>>,
+) {
+    let Some(capture) = photo.capture.as_mut() else {
+        return;
+    };
+    capture.frames += 1;
+    if !capture.done && capture.frames < MAX_CAPTURE_FRAMES {
+        return;
+    }
+    if !capture.done {
+        warn!("Photo capture timed out");
+    }
+    let capture = photo.capture.take().expect("checked above");
+    let (mut camera, mut projection) = camera.into_inner();
+    camera.target = capture.target;
+    if let Projection::Orthographic(ortho) = projection.as_mut() {
+        ortho.scaling_mode = capture.scaling_mode;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_photo_mode_helpers() {
+        assert_eq!(capture_size(UVec2::new(1280, 720)), UVec2::new(5120, 2880));
+        // Large windows get a smaller factor, never below the window itself
+        assert_eq!(capture_size(UVec2::new(2560, 1440)), UVec2::new(7680, 4320));
+        assert_eq!(capture_size(UVec2::new(10000, 100)), UVec2::new(10000, 100));
+
+        assert_eq!(shift_hour(23.0, 1.0), 0.0);
+        assert_eq!(shift_hour(0.0, -1.0), 23.0);
+        assert_eq!(shift_hour(12.0, 1.0), 13.0);
+
+        let mut filter = PhotoFilter::None;
+        for _ in 0..PhotoFilter::ALL.len() {
+            filter = filter.next();
+        }
+        assert_eq!(filter, PhotoFilter::None);
+        assert!(PhotoFilter::None.color_grading().is_none());
+        assert_eq!(PhotoFilter::Noir.color_grading().unwrap().global.post_saturation, 0.0);
+    }
+}
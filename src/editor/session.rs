@@ -0,0 +1,90 @@
+use super::{EntityType, PaintMode, PlacementMode, TerrainType};
+use crate::config::GameConfig;
+use crate::map::{MapConfig, MapDetail, MapMode, MapState, MinimapState};
+use crate::tiles::chunk::coords;
+use crate::world::{MetadataSync, SessionState, WorldMetadata, WorldOrigin};
+use bevy::prelude::*;
+
+/// How often camera/UI session state is copied into world metadata (seconds)
+const SESSION_SNAPSHOT_INTERVAL: f32 = 5.0;
+
+/// Puts the camera, tools and panels back where the previous session left them
+#[allow(clippy::too_many_arguments)]
+pub fn restore_session_state(
+    metadata: Res<WorldMetadata>,
+    config: Res<GameConfig>,
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera2d>>,
+    mut origin: ResMut<WorldOrigin>,
+    mut placement_mode: ResMut<PlacementMode>,
+    mut paint_mode: ResMut<PaintMode>,
+    mut map_state: ResMut<MapState>,
+    mut map_config: ResMut<MapConfig>,
+    mut minimap_state: ResMut<MinimapState>,
+) {
+    let session = &metadata.session;
+
+    if let Ok((mut transform, mut projection)) = camera_query.single_mut() {
+        // Render around the saved camera position, so a far-away session starts precise
+        let camera = Vec2::from(session.camera);
+        *origin = WorldOrigin(coords::world_to_chunk(camera));
+        let render = origin.to_render(camera);
+        transform.translation.x = render.x;
+        transform.translation.y = render.y;
+        if let Projection::Orthographic(ref mut ortho) = projection.as_mut() {
+            ortho.scale = session.zoom.clamp(config.controls.zoom_min, config.controls.zoom_max);
+        }
+    }
+
+    placement_mode.selected = session.placement.as_deref().and_then(EntityType::from_str);
+    paint_mode.selected = session.paint.as_deref().and_then(TerrainType::from_str);
+
+    map_state.visible = session.map_open;
+    map_state.detail = MapDetail::parse_or_default(&session.map_detail);
+    map_state.mode = MapMode::parse_or_default(&session.map_mode);
+    map_config.show_waypoint_labels = session.waypoint_labels;
+    minimap_state.visible = session.minimap_visible;
+
+    info!(
+        "Restored session: camera ({:.0}, {:.0}) at zoom {:.1}",
+        session.camera[0], session.camera[1], session.zoom
+    );
+}
+
+/// Copies camera and UI state into world metadata periodically and when the window closes
+#[allow(clippy::too_many_arguments)]
+pub fn snapshot_session_state(
+    mut sync: MetadataSync,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    placement_mode: Res<PlacementMode>,
+    paint_mode: Res<PaintMode>,
+    map_state: Res<MapState>,
+    map_config: Res<MapConfig>,
+    minimap_state: Res<MinimapState>,
+) {
+    if !sync.due(SESSION_SNAPSHOT_INTERVAL) {
+        return;
+    }
+
+    let Ok((transform, projection)) = camera_query.single() else {
+        return;
+    };
+    let zoom = match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    };
+
+    let session = SessionState {
+        camera: origin.to_world_vec2(transform.translation.truncate()).to_array(),
+        zoom,
+        placement: placement_mode.selected.as_ref().map(|entity_type| entity_type.as_str()),
+        paint: paint_mode.selected.as_ref().map(|terrain_type| terrain_type.as_str().to_string()),
+        map_open: map_state.visible,
+        map_detail: map_state.detail.as_str().to_string(),
+        map_mode: map_state.mode.as_str().to_string(),
+        minimap_visible: minimap_state.visible,
+        waypoint_labels: map_config.show_waypoint_labels,
+    };
+
+    sync.update(|metadata| &mut metadata.session, session);
+}
@@ -0,0 +1,326 @@
+//! Sign posts: the sign tool puts one up where you click (or edits the one you click on) through a
+//! small text-input dialog, and the text is kept in `WorldMetadata.signs`. The text floats over the
+//! post when the camera is close; the map shows signs as icons (see `map::spawn_sign_icons`)
+
+use crate::chronicle::ChronicleEvent;
+use crate::console::TextFocus;
+use crate::locale::LocalizedText;
+use crate::world::{Sign, WorldMetadata, WorldOrigin, MAX_SIGN_TEXT_LENGTH};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+/// Clicks this close to a sign (pixels) edit it instead of putting up a new one
+const SIGN_PICK_RADIUS: f32 = 16.0;
+
+/// Camera distance (in pixels) within which sign texts are shown
+pub const SIGN_LABEL_DISTANCE: f32 = 320.0;
+
+/// Weathered wood
+pub const SIGN_COLOR: Color = Color::srgb(0.62, 0.45, 0.26);
+
+const SIGN_POST_SIZE: Vec2 = Vec2::new(6.0, 12.0);
+
+/// Just below entity sprites (1.0+); the label child sits at the waypoint labels' height (10.0)
+const SIGN_POST_Z: f32 = 0.9;
+
+/// The sign being written in the dialog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignTarget {
+    /// A new sign at a world position
+    New(Vec2),
+    /// `WorldMetadata.signs[index]`
+    Existing(usize),
+}
+
+/// The sign dialog: open while `target` is set, `text` is what has been typed so far
+#[derive(Resource, Debug, Default)]
+pub struct SignEditor {
+    pub target: Option<SignTarget>,
+    pub text: String,
+}
+
+impl SignEditor {
+    /// Opens the dialog for the sign at a world position, or for a new one there
+    pub fn open(&mut self, metadata: &WorldMetadata, position: Vec2) {
+        match metadata.sign_near(position, SIGN_PICK_RADIUS) {
+            Some(index) => {
+                self.target = Some(SignTarget::Existing(index));
+                self.text = metadata.signs[index].text.clone();
+            }
+            None => {
+                self.target = Some(SignTarget::New(position));
+                self.text.clear();
+            }
+        }
+    }
+
+    /// Appends typed text, up to `MAX_SIGN_TEXT_LENGTH` characters
+    pub fn type_text(&mut self, text: &str) {
+        let room = MAX_SIGN_TEXT_LENGTH.saturating_sub(self.text.chars().count());
+        self.text.extend(text.chars().take(room));
+    }
+
+    pub fn cancel(&mut self) {
+        self.target = None;
+        self.text.clear();
+    }
+
+    /// Writes the text to the sign and closes the dialog; an empty text takes the sign down (or
+    /// doesn't put it up). Returns whether a new sign was put up
+    pub fn commit(&mut self, metadata: &mut WorldMetadata) -> bool {
+        let text = self.text.trim().to_string();
+        let target = self.target;
+        self.cancel();
+        match target {
+            Some(SignTarget::New(position)) if !text.is_empty() => {
+                metadata.signs.push(Sign::new(text, position));
+                true
+            }
+            Some(SignTarget::Existing(index)) if index < metadata.signs.len() => {
+                if text.is_empty() {
+                    metadata.signs.remove(index);
+                } else {
+                    metadata.signs[index].text = text;
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Run condition for world input the sign dialog blocks
+pub fn sign_editor_closed(editor: Res<SignEditor>) -> bool {
+    editor.target.is_none()
+}
+
+/// Types into the open sign dialog: Enter saves, Escape cancels
+pub fn handle_sign_editor_input(
+    mut keyboard_inputs: MessageReader<KeyboardInput>,
+    mut editor: ResMut<SignEditor>,
+    mut metadata: ResMut<WorldMetadata>,
+    mut focus: ResMut<TextFocus>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+) {
+    if editor.target.is_none() {
+        keyboard_inputs.clear();
+    }
+    for input in keyboard_inputs.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+        match &input.logical_key {
+            Key::Enter => {
+                let text = editor.text.trim().to_string();
+                if editor.commit(&mut metadata) {
+                    info!("Put up a sign: {}", text);
+                    chronicle.write(ChronicleEvent::milestone("first_sign", format!("The first sign was put up: \"{}\"", text)));
+                }
+                break;
+            }
+            Key::Escape => {
+                editor.cancel();
+                break;
+            }
+            Key::Backspace => {
+                editor.text.pop();
+            }
+            Key::Space => editor.type_text(" "),
+            Key::Character(text) => editor.type_text(text),
+            _ => {}
+        }
+    }
+
+    let open = editor.target.is_some();
+    if focus.0 != open {
+        focus.0 = open;
+    }
+}
+
+/// Marker component for the sign dialog
+#[derive(Component)]
+pub struct SignDialog;
+
+/// Marker component for the sign dialog's text field
+#[derive(Component)]
+pub struct SignDialogText;
+
+/// Spawns the hidden sign dialog near the top of the screen
+pub fn setup_sign_dialog(mut commands: Commands) {
+    commands
+        .spawn((
+            SignDialog,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(80.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ZIndex(900),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(420.0),
+                        padding: UiRect::all(Val::Px(14.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.22, 0.16, 0.1)),
+                    BorderColor::all(SIGN_COLOR),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|dialog| {
+                    dialog.spawn((
+                        LocalizedText("sign-dialog-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    dialog.spawn((
+                        SignDialogText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 0.95, 0.85)),
+                    ));
+                    dialog.spawn((
+                        LocalizedText("sign-dialog-hint"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.75, 0.7, 0.6)),
+                    ));
+                });
+        });
+}
+
+/// Shows the dialog and the text typed so far while a sign is being written
+pub fn update_sign_dialog(
+    editor: Res<SignEditor>,
+    mut dialog: Single<&mut Visibility, With<SignDialog>>,
+    mut text: Single<&mut Text, With<SignDialogText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    **dialog = match editor.target {
+        Some(_) => Visibility::Visible,
+        None => Visibility::Hidden,
+    };
+    text.0 = format!("{}_", editor.text);
+}
+
+/// A sign post in the world (index into `WorldMetadata.signs`)
+#[derive(Component)]
+pub struct SignPost(pub usize);
+
+/// Marker component for the text floating over a sign post
+#[derive(Component)]
+pub struct SignLabel;
+
+/// Rebuilds the sign posts whenever the signs change
+pub fn sync_sign_posts(
+    mut commands: Commands,
+    metadata: Res<WorldMetadata>,
+    origin: Res<WorldOrigin>,
+    posts: Query<Entity, With<SignPost>>,
+) {
+    if !metadata.is_changed() {
+        return;
+    }
+
+    for entity in posts.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for (index, sign) in metadata.signs.iter().enumerate() {
+        let position = origin.to_render(sign.position());
+        commands
+            .spawn((
+                SignPost(index),
+                Sprite::from_color(SIGN_COLOR, SIGN_POST_SIZE),
+                Transform::from_xyz(position.x, position.y, SIGN_POST_Z),
+            ))
+            .with_children(|post| {
+                post.spawn((
+                    SignLabel,
+                    Text2d::new(sign.text.clone()),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(1.0, 0.95, 0.85)),
+                    Transform::from_xyz(0.0, SIGN_POST_SIZE.y + 4.0, 10.0 - SIGN_POST_Z),
+                    Visibility::Hidden,
+                ));
+            });
+    }
+}
+
+/// Shows sign texts only when the camera is nearby
+pub fn update_sign_label_visibility(
+    camera: Single<&Transform, With<Camera2d>>,
+    mut labels: Query<(&GlobalTransform, &mut Visibility), With<SignLabel>>,
+) {
+    let camera_pos = camera.translation.truncate();
+    for (transform, mut visibility) in labels.iter_mut() {
+        let desired = if camera_pos.distance(transform.translation().truncate()) <= SIGN_LABEL_DISTANCE {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != desired {
+            *visibility = desired;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_editor() {
+        let mut metadata = WorldMetadata::default();
+        let mut editor = SignEditor::default();
+
+        // A new sign, with the text trimmed and capped
+        editor.open(&metadata, Vec2::new(100.0, 50.0));
+        assert_eq!(editor.target, Some(SignTarget::New(Vec2::new(100.0, 50.0))));
+        editor.type_text(" Old Oak Road ");
+        assert!(editor.commit(&mut metadata));
+        assert_eq!(metadata.signs, vec![Sign::new("Old Oak Road", Vec2::new(100.0, 50.0))]);
+        assert_eq!(editor.target, None);
+
+        // Clicking next to it edits it
+        editor.open(&metadata, Vec2::new(108.0, 50.0));
+        assert_eq!(editor.target, Some(SignTarget::Existing(0)));
+        assert_eq!(editor.text, "Old Oak Road");
+        editor.type_text(&"!".repeat(MAX_SIGN_TEXT_LENGTH));
+        assert_eq!(editor.text.chars().count(), MAX_SIGN_TEXT_LENGTH);
+        assert!(!editor.commit(&mut metadata));
+        assert!(metadata.signs[0].text.ends_with('!'));
+
+        // An empty text takes it down; an empty new sign isn't put up
+        editor.open(&metadata, Vec2::new(100.0, 50.0));
+        editor.text.clear();
+        editor.commit(&mut metadata);
+        assert!(metadata.signs.is_empty());
+        editor.open(&metadata, Vec2::ZERO);
+        assert!(!editor.commit(&mut metadata));
+        assert!(metadata.signs.is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+use super::SignEditor;
+use crate::audio::{Sound, SoundEffect};
+use crate::chronicle::ChronicleEvent;
+use crate::debug_hud::DebugSettings;
+use crate::entities::{tree_tiles, GuardianLore, Position, SpawnKind, SpawnQueue, TreeSpirit, TreeVariant, GUARDIAN_FOOTPRINT};
+use crate::environment::{tree_viability, CurrentSeason};
+use crate::locale::Locale;
+use crate::stats::StatEvent;
+use crate::tiles::{TileRegistry, LAYER_GROUND, TILE_DIRT, TILE_GRASS};
+use crate::toast::Toast;
+use crate::world::{TileEdits, WorldManager, WorldMetadata, WorldOrigin};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+// Entity type identifier for buttons
+#[derive(Component, Clone, Debug, PartialEq)]
+pub enum EntityType {
+    Player,
+    ForestGuardian(String), // Variant name: "oak", "birch", etc.
+    Snail,
+    /// Puts up (or edits) a sign post through the sign dialog instead of spawning anything
+    Sign,
+}
+
+impl EntityType {
+    /// Name used when saving the selected tool
+    pub(crate) fn as_str(&self) -> String {
+        match self {
+            EntityType::Player => "player".to_string(),
+            EntityType::ForestGuardian(variant) => format!("guardian:{}", variant),
+            EntityType::Snail => "snail".to_string(),
+            EntityType::Sign => "sign".to_string(),
+        }
+    }
+
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "player" => Some(EntityType::Player),
+            "snail" => Some(EntityType::Snail),
+            "sign" => Some(EntityType::Sign),
+            _ => s
+                .strip_prefix("guardian:")
+                .map(|variant| EntityType::ForestGuardian(variant.to_string())),
+        }
+    }
+}
+
+// Terrain type identifier for terrain painting
+#[derive(Component, Clone, Debug, PartialEq)]
+pub enum TerrainType {
+    Grass,
+    Dirt,
+}
+
+impl TerrainType {
+    /// Name used when saving the selected tool
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            TerrainType::Grass => "grass",
+            TerrainType::Dirt => "dirt",
+        }
+    }
+
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "grass" => Some(TerrainType::Grass),
+            "dirt" => Some(TerrainType::Dirt),
+            _ => None,
+        }
+    }
+}
+
+// Placement mode resource - tracks which entity type is selected for placement
+#[derive(Resource, Default, Clone, Debug)]
+pub struct PlacementMode {
+    pub selected: Option<EntityType>,
+}
+
+impl PlacementMode {
+    pub fn select(&mut self, entity_type: EntityType) {
+        self.selected = Some(entity_type);
+    }
+
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    pub fn is_selected(&self, entity_type: &EntityType) -> bool {
+        if let Some(ref selected) = self.selected {
+            match (selected, entity_type) {
+                (EntityType::Player, EntityType::Player) => true,
+                (EntityType::Snail, EntityType::Snail) => true,
+                (EntityType::Sign, EntityType::Sign) => true,
+                (EntityType::ForestGuardian(a), EntityType::ForestGuardian(b)) => a == b,
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+// Paint mode resource - tracks which terrain type is selected for painting
+#[derive(Resource, Default, Clone, Debug)]
+pub struct PaintMode {
+    pub selected: Option<TerrainType>,
+}
+
+impl PaintMode {
+    pub fn select(&mut self, terrain_type: TerrainType) {
+        self.selected = Some(terrain_type);
+    }
+
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    pub fn is_selected(&self, terrain_type: &TerrainType) -> bool {
+        if let Some(ref selected) = self.selected {
+            selected == terrain_type
+        } else {
+            false
+        }
+    }
+}
+
+/// A request to place an entity, from a mouse click or (on a server) a connected client
+/// Spawned by `apply_entity_placements`, which checks where guardians can plant first
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct PlaceEntityRequest {
+    pub entity_type: EntityType,
+    pub position: Vec2,
+}
+
+/// Handles left clicks with an entity type selected by requesting a placement at the cursor
+/// (with the sign tool, by opening the sign dialog there)
+#[allow(clippy::too_many_arguments)]
+pub fn handle_entity_placement(
+    placement_mode: Res<PlacementMode>,
+    metadata: Res<WorldMetadata>,
+    mut sign_editor: ResMut<SignEditor>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    ui_query: Query<&Interaction, With<Button>>,
+    mut requests: MessageWriter<PlaceEntityRequest>,
+    mut sounds: MessageWriter<SoundEffect>,
+) {
+    // Only handle left clicks when an entity type is selected
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(ref entity_type) = placement_mode.selected else {
+        return;
+    };
+
+    // Don't spawn entities if cursor is over any UI element
+    for interaction in ui_query.iter() {
+        if *interaction == Interaction::Pressed || *interaction == Interaction::Hovered {
+            return;
+        }
+    }
+
+    // Get the primary window
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    // Get cursor position in window
+    let Some(cursor_pos) = window.cursor_position() else {
+        return;
+    };
+
+    // Get camera components
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+
+    // Convert cursor position to world position
+    let Ok(render_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) else {
+        return;
+    };
+    let world_pos = origin.to_world_vec2(render_pos);
+
+    if *entity_type == EntityType::Sign {
+        sign_editor.open(&metadata, world_pos);
+        return;
+    }
+    requests.write(PlaceEntityRequest {
+        entity_type: entity_type.clone(),
+        position: world_pos,
+    });
+    sounds.write(SoundEffect::at(Sound::Place, world_pos));
+}
+
+/// Queues requested entities on the `SpawnQueue`
+#[allow(clippy::too_many_arguments)]
+pub fn apply_entity_placements(
+    mut requests: MessageReader<PlaceEntityRequest>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut stat_events: MessageWriter<StatEvent>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+    mut toasts: MessageWriter<Toast>,
+    mut world_manager: ResMut<WorldManager>,
+    registry: Res<TileRegistry>,
+    trees: Query<&Position, With<TreeSpirit>>,
+    season: Res<CurrentSeason>,
+    locale: Res<Locale>,
+    debug: Res<DebugSettings>,
+    metadata: Res<WorldMetadata>,
+) {
+    for request in requests.read() {
+        let world_pos = request.position;
+        let position = Position::new(world_pos.x, world_pos.y);
+
+        match &request.entity_type {
+            EntityType::Player => {
+                spawn_queue.push(position, SpawnKind::Player);
+                if debug.log_edits {
+                    info!("Spawned player at ({}, {})", world_pos.x, world_pos.y);
+                }
+            }
+            EntityType::ForestGuardian(variant) => {
+                // Guardians need room to stand (on either side of a chunk border), and only plant
+                // where their trees can grow
+                let occupied = tree_tiles(trees.iter().map(Position::vec2).chain(spawn_queue.queued_trees().map(|queued| queued.vec2())));
+                let footprint = world_manager.bypass_change_detection().check_footprint(&registry, world_pos, GUARDIAN_FOOTPRINT, |tile| {
+                    occupied.contains(&tile)
+                });
+                let viability = footprint
+                    .map_err(|blocked| blocked.to_string())
+                    .and_then(|_| tree_viability(&world_manager, season.0, TreeVariant::from_str(variant), world_pos).map_err(|limit| limit.to_string()));
+                if let Err(limit) = viability {
+                    toasts.write(Toast::warning(locale.format("toast-guardian-cant-plant", &[
+                        ("variant", variant.clone().into()),
+                        ("reason", limit.into()),
+                    ])));
+                    continue;
+                }
+                spawn_queue.push(position, SpawnKind::ForestGuardian(variant.clone()));
+                // Named the way `name_guardians` will name it once spawned
+                let lore = GuardianLore::for_guardian(&metadata, &position, TreeVariant::from_str(variant));
+                chronicle.write(ChronicleEvent::milestone(
+                    "first_guardian",
+                    format!("The first forest guardian, {} ({}), was placed", lore.full_name(), variant),
+                ));
+                if debug.log_edits {
+                    info!("Spawned {} forest guardian at ({}, {})", variant, world_pos.x, world_pos.y);
+                }
+            }
+            EntityType::Snail => {
+                spawn_queue.push(position, SpawnKind::Snail);
+                stat_events.write(StatEvent::SnailSpawned);
+                chronicle.write(ChronicleEvent::milestone("first_snail", "The first snail arrived"));
+                if debug.log_edits {
+                    info!("Spawned snail at ({}, {})", world_pos.x, world_pos.y);
+                }
+            }
+            // Signs are written locally in the sign dialog, never requested
+            EntityType::Sign => {}
+        }
+    }
+}
+
+/// Handles mouse clicks to paint terrain in the world
+#[allow(clippy::too_many_arguments)]
+pub fn handle_terrain_painting(
+    paint_mode: Res<PaintMode>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    ui_query: Query<&Interaction, With<Button>>,
+    mut tile_edits: TileEdits,
+    mut heatmap: ResMut<super::EditHeatmap>,
+    time: Res<Time>,
+    mut stat_events: MessageWriter<StatEvent>,
+    mut sounds: MessageWriter<SoundEffect>,
+    debug: Res<DebugSettings>,
+) {
+    // Only handle left clicks when a terrain type is selected
+    if !mouse_button.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(ref terrain_type) = paint_mode.selected else {
+        return;
+    };
+
+    // Don't paint terrain if cursor is over any UI element
+    for interaction in ui_query.iter() {
+        if *interaction == Interaction::Pressed || *interaction == Interaction::Hovered {
+            return;
+        }
+    }
+
+    // Get the primary window
+    let Ok(window) = windows.single() else {
+        return;
+    };
+
+    // Get cursor position in window
+    let Some(cursor_pos) = window.cursor_position() else {
+        return;
+    };
+
+    // Get camera components
+    let Ok((camera, camera_transform)) = camera_query.single() else {
+        return;
+    };
+
+    // Convert cursor position to world position
+    let Ok(render_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) else {
+        return;
+    };
+    let world_pos = origin.to_world_vec2(render_pos);
+
+    // Determine which tile to paint based on terrain type
+    let tile_id = match terrain_type {
+        TerrainType::Grass => TILE_GRASS,
+        TerrainType::Dirt => TILE_DIRT,
+    };
+
+    // Request the tile modification on the ground layer
+    tile_edits.paint(world_pos, tile_id, LAYER_GROUND);
+    heatmap.record(world_pos, time.elapsed_secs());
+    stat_events.write(StatEvent::TilePainted);
+    sounds.write(SoundEffect::at(Sound::Paint, world_pos));
+    if debug.log_edits {
+        info!("Painted {:?} tile at ({}, {})", terrain_type, world_pos.x, world_pos.y);
+    }
+}
@@ -0,0 +1,431 @@
+//! Spectator tour (F6 or `tour`): the camera flies between interesting places on its own, like a
+//! screensaver. The next stop is picked a few seconds before the camera leaves, and the chunks
+//! around it are prefetched into the cache so it arrives to a loaded world
+
+use crate::config::GameConfig;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::debug_hud::DebugHudLines;
+use crate::entities::{ForestGuardian, Position, TreeSpirit};
+use crate::environment::{tile_center, Fires};
+use crate::error::Failures;
+use crate::platform;
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkPos, CHUNK_PIXEL_SIZE};
+use crate::world::model::{self, load_radius_for_viewport};
+use crate::world::{ExploredChunks, WorldManager, WorldOrigin};
+use bevy::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::PI;
+
+/// Seconds spent at each stop, unless set with `tour <seconds>`
+pub const TOUR_INTERVAL: f32 = 20.0;
+
+/// Shortest interval `tour <seconds>` accepts (a flight and the prefetch have to fit)
+const MIN_TOUR_INTERVAL: f32 = 8.0;
+
+/// Seconds before leaving that the next stop is picked and its chunks start prefetching
+const PREFETCH_LEAD: f32 = 4.0;
+
+/// Chunks prefetched per frame, so the prefetch doesn't stall a frame
+const PREFETCH_PER_FRAME: usize = 4;
+
+/// Seconds a flight between stops takes
+const FLIGHT_SECONDS: f32 = 3.0;
+
+/// How far the camera zooms out halfway through a flight (0.6 = 60% wider)
+const FLIGHT_ZOOM_OUT: f32 = 0.6;
+
+/// Stops further apart than this are cut to at the top of the zoom instead of glided to, so the
+/// camera doesn't stream every chunk in between
+const MAX_GLIDE_DISTANCE: f32 = CHUNK_PIXEL_SIZE * 12.0;
+
+/// The kinds of places the tour visits, in the order it cycles through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TourStop {
+    /// The chunk with the most tree spirits
+    DensestForest,
+    /// The most recently spawned guardian
+    NewestGuardian,
+    /// The middle of the burning tiles
+    ActiveFire,
+    /// Any chunk the player has explored
+    ExploredChunk,
+}
+
+impl TourStop {
+    pub const ALL: [TourStop; 4] = [Self::DensestForest, Self::NewestGuardian, Self::ActiveFire, Self::ExploredChunk];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::DensestForest => "densest forest",
+            Self::NewestGuardian => "newest guardian",
+            Self::ActiveFire => "active fire",
+            Self::ExploredChunk => "explored chunk",
+        }
+    }
+}
+
+/// The stop kind after `previous` (cycling) that `available` accepts; None if none are
+pub fn next_stop(previous: Option<TourStop>, available: impl Fn(TourStop) -> bool) -> Option<TourStop> {
+    let start = previous.and_then(|stop| TourStop::ALL.iter().position(|other| *other == stop)).map_or(0, |i| i + 1);
+    (0..TourStop::ALL.len())
+        .map(|offset| TourStop::ALL[(start + offset) % TourStop::ALL.len()])
+        .find(|stop| available(*stop))
+}
+
+/// The middle of the trees in the chunk with the most trees (ties go to the lowest chunk)
+pub fn densest_forest(trees: impl IntoIterator<Item = Vec2>) -> Option<Vec2> {
+    let mut chunks: HashMap<ChunkPos, (u32, Vec2)> = HashMap::new();
+    for tree in trees {
+        let (count, sum) = chunks.entry(coords::world_to_chunk(tree)).or_default();
+        *count += 1;
+        *sum += tree;
+    }
+    chunks
+        .into_iter()
+        .max_by_key(|(pos, (count, _))| (*count, Reverse((pos.y, pos.x))))
+        .map(|(_, (count, sum))| sum / count as f32)
+}
+
+/// The burning tile nearest the middle of all burning tiles (a fire front, not the ash between two)
+pub fn fire_focus(burning: &[IVec2]) -> Option<Vec2> {
+    let middle = burning.iter().map(|tile| tile_center(*tile)).sum::<Vec2>() / burning.len().max(1) as f32;
+    burning
+        .iter()
+        .map(|tile| tile_center(*tile))
+        .min_by(|a, b| a.distance_squared(middle).total_cmp(&b.distance_squared(middle)))
+}
+
+/// A flight from one stop to the next, in world pixels
+#[derive(Debug, Clone, Copy)]
+pub struct TourLeg {
+    pub from: Vec2,
+    pub to: Vec2,
+    pub elapsed: f32,
+    /// The zoom the flight started at, and returns to
+    pub base_scale: f32,
+    /// Reduced motion: cut halfway through, without gliding or zooming
+    pub still: bool,
+}
+
+impl TourLeg {
+    /// Camera position and zoom scale `t` (0-1) of the way through: an eased glide (or a cut at
+    /// the halfway point for long legs) under a zoom out and back in
+    pub fn at(&self, t: f32) -> (Vec2, f32) {
+        let t = t.clamp(0.0, 1.0);
+        let position = if self.still || self.from.distance(self.to) > MAX_GLIDE_DISTANCE {
+            if t < 0.5 { self.from } else { self.to }
+        } else {
+            self.from.lerp(self.to, t * t * (3.0 - 2.0 * t))
+        };
+        let zoom_out = if self.still { 0.0 } else { FLIGHT_ZOOM_OUT * (PI * t).sin() };
+        (position, self.base_scale * (1.0 + zoom_out))
+    }
+}
+
+/// Tour state
+#[derive(Resource)]
+pub struct TourState {
+    pub active: bool,
+    /// Seconds at each stop
+    pub interval: f32,
+    /// Seconds spent at the current stop
+    dwell: f32,
+    /// The kind of the stop last flown to
+    stop: Option<TourStop>,
+    leg: Option<TourLeg>,
+    /// Picked ahead of time so its chunks can be prefetched
+    next: Option<(TourStop, Vec2)>,
+    prefetch_queue: VecDeque<ChunkPos>,
+    /// Chunks this tour put in the cache, released again when they aren't needed
+    prefetched: HashSet<ChunkPos>,
+    /// Kept up to date while the tour is off too
+    newest_guardian: Option<Entity>,
+    rng: u64,
+}
+
+impl Default for TourState {
+    fn default() -> Self {
+        Self {
+            active: false,
+            interval: TOUR_INTERVAL,
+            dwell: 0.0,
+            stop: None,
+            leg: None,
+            next: None,
+            prefetch_queue: VecDeque::new(),
+            prefetched: HashSet::new(),
+            newest_guardian: None,
+            rng: 0,
+        }
+    }
+}
+
+impl TourState {
+    fn start(&mut self) {
+        self.active = true;
+        self.stop = None;
+        self.leg = None;
+        self.next = None;
+        // Pick the first stop right away; the camera leaves once it's prefetched
+        self.dwell = self.interval - PREFETCH_LEAD;
+        self.rng = platform::unix_time().as_nanos() as u64;
+        info!("Tour on: a new stop every {:.0}s, F6/Esc or any movement key stops it", self.interval);
+    }
+
+    /// Ends the tour and releases the chunks it prefetched; returns the zoom to restore if it
+    /// stopped mid-flight
+    fn end(&mut self, world: &mut WorldManager) -> Option<f32> {
+        self.active = false;
+        self.next = None;
+        self.prefetch_queue.clear();
+        self.release_prefetched(world);
+        info!("Tour off");
+        self.leg.take().map(|leg| leg.base_scale)
+    }
+
+    /// Drops prefetched chunks the camera didn't load
+    fn release_prefetched(&mut self, world: &mut WorldManager) {
+        let released = self.prefetched.drain().filter(|pos| model::release_prefetched(world, *pos)).count();
+        if released > 0 {
+            debug!("Tour: released {} prefetched chunks", released);
+        }
+    }
+
+    /// Random index below `len` (splitmix64)
+    fn pick(&mut self, len: usize) -> usize {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z % len.max(1) as u64) as usize
+    }
+}
+
+/// Run condition: the tour is on
+pub fn tour_active(tour: Option<Res<TourState>>) -> bool {
+    tour.is_some_and(|tour| tour.active)
+}
+
+/// Remembers the most recently spawned guardian, for the tour's guardian stop
+pub fn track_newest_guardian(mut tour: ResMut<TourState>, added: Query<Entity, Added<ForestGuardian>>) {
+    if let Some(entity) = added.iter().last() {
+        tour.newest_guardian = Some(entity);
+    }
+}
+
+/// F6 starts or stops the tour; Escape and the camera movement keys stop it
+pub fn toggle_tour(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut tour: ResMut<TourState>,
+    mut world: ResMut<WorldManager>,
+    mut projection: Single<&mut Projection, With<Camera2d>>,
+) {
+    const STOP_KEYS: [KeyCode; 9] = [
+        KeyCode::Escape,
+        KeyCode::KeyW,
+        KeyCode::KeyA,
+        KeyCode::KeyS,
+        KeyCode::KeyD,
+        KeyCode::ArrowUp,
+        KeyCode::ArrowDown,
+        KeyCode::ArrowLeft,
+        KeyCode::ArrowRight,
+    ];
+    if keyboard.just_pressed(KeyCode::F6) && !tour.active {
+        tour.start();
+    } else if tour.active && (keyboard.just_pressed(KeyCode::F6) || keyboard.any_just_pressed(STOP_KEYS)) {
+        restore_zoom(&mut projection, tour.end(&mut world));
+    }
+}
+
+fn restore_zoom(projection: &mut Projection, scale: Option<f32>) {
+    if let (Some(scale), Projection::Orthographic(ortho)) = (scale, projection) {
+        ortho.scale = scale;
+    }
+}
+
+/// Flies the camera between stops and prefetches the next stop's chunks
+#[allow(clippy::too_many_arguments)]
+pub fn run_tour(
+    time: Res<Time>,
+    mut tour: ResMut<TourState>,
+    mut world: ResMut<WorldManager>,
+    origin: Res<WorldOrigin>,
+    trees: Query<&Position, With<TreeSpirit>>,
+    guardians: Query<&Position, With<ForestGuardian>>,
+    fires: Res<Fires>,
+    explored: Res<ExploredChunks>,
+    mut failures: Failures,
+    mut hud: ResMut<DebugHudLines>,
+    config: Res<GameConfig>,
+    window: Single<&Window>,
+    camera: Single<(&mut Transform, &mut Projection), With<Camera2d>>,
+) {
+    let (mut transform, mut projection) = camera.into_inner();
+    let Projection::Orthographic(ortho) = projection.as_mut() else {
+        return;
+    };
+
+    if let Some(leg) = tour.leg.as_mut() {
+        leg.elapsed += time.delta_secs();
+        let t = leg.elapsed / FLIGHT_SECONDS;
+        let (position, scale) = leg.at(t);
+        transform.translation = origin.to_render(position).extend(transform.translation.z);
+        ortho.scale = scale;
+        if t >= 1.0 {
+            ortho.scale = leg.base_scale;
+            tour.leg = None;
+            tour.dwell = 0.0;
+        }
+    } else {
+        tour.dwell += time.delta_secs();
+    }
+
+    // Pick the next stop ahead of time and queue its chunks
+    if tour.leg.is_none() && tour.next.is_none() && tour.dwell >= tour.interval - PREFETCH_LEAD {
+        let mut targets: HashMap<TourStop, Vec2> = HashMap::new();
+        targets.extend(densest_forest(trees.iter().map(Position::vec2)).map(|at| (TourStop::DensestForest, at)));
+        let newest_guardian = tour.newest_guardian.and_then(|entity| guardians.get(entity).ok());
+        targets.extend(newest_guardian.map(|position| (TourStop::NewestGuardian, position.vec2())));
+        let burning: Vec<IVec2> = fires.burning.keys().copied().collect();
+        targets.extend(fire_focus(&burning).map(|at| (TourStop::ActiveFire, at)));
+        let index = tour.pick(explored.chunks.len());
+        let explored_chunk = explored.chunks.iter().nth(index);
+        targets.extend(explored_chunk.map(|pos| (TourStop::ExploredChunk, pos.to_world(CHUNK_PIXEL_SIZE) + CHUNK_PIXEL_SIZE / 2.0)));
+        tour.next = next_stop(tour.stop, |stop| targets.contains_key(&stop)).map(|stop| (stop, targets[&stop]));
+        match tour.next {
+            Some((stop, position)) => {
+                // The current stop's chunks are loaded now; what's left over from before wasn't needed
+                tour.release_prefetched(&mut world);
+                let radius = world
+                    .load_radius_override
+                    .unwrap_or_else(|| load_radius_for_viewport(window.size(), ortho.scale));
+                let chunks = coords::world_to_chunk(position).chunks_in_radius(radius);
+                tour.prefetch_queue = chunks.into();
+                debug!("Tour: next stop {} at ({:.0}, {:.0})", stop.label(), position.x, position.y);
+            }
+            // Nothing to show yet; look again next interval
+            None => tour.dwell = 0.0,
+        }
+    }
+
+    for _ in 0..PREFETCH_PER_FRAME {
+        let Some(pos) = tour.prefetch_queue.pop_front() else {
+            break;
+        };
+        match model::prefetch_chunk(&mut world, pos) {
+            Ok(Some(_)) => {
+                tour.prefetched.insert(pos);
+            }
+            Ok(None) => {}
+            Err(e) => failures.report(format!("Failed to prefetch chunk ({}, {}) for the tour", pos.x, pos.y), e),
+        }
+    }
+
+    if tour.leg.is_none() && tour.dwell >= tour.interval {
+        if let Some((stop, to)) = tour.next.take() {
+            let from = origin.to_world_vec2(transform.translation.truncate());
+            tour.leg = Some(TourLeg {
+                from,
+                to,
+                elapsed: 0.0,
+                base_scale: ortho.scale,
+                still: config.accessibility.reduced_motion,
+            });
+            tour.stop = Some(stop);
+            info!("Tour: flying to the {}", stop.label());
+        }
+    }
+
+    let status = match (tour.leg.is_some(), tour.stop) {
+        (true, Some(stop)) => format!("flying to the {}", stop.label()),
+        (false, Some(stop)) => format!("at the {} ({:.0}s)", stop.label(), (tour.interval - tour.dwell).max(0.0)),
+        _ => "starting".to_string(),
+    };
+    hud.set("tour", format!("Tour: {} | prefetched {} ({} queued)", status, tour.prefetched.len(), tour.prefetch_queue.len()));
+}
+
+/// Shows the tour as off in the HUD once it stops
+pub fn clear_tour_hud(tour: Res<TourState>, mut hud: ResMut<DebugHudLines>) {
+    if tour.is_changed() && !tour.active {
+        hud.set("tour", "Tour: off".to_string());
+    }
+}
+
+/// Console command: `tour [seconds|off]` starts the tour (with that many seconds per stop) or stops it
+pub fn handle_tour_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut tour: ResMut<TourState>,
+    mut world: ResMut<WorldManager>,
+    mut projection: Single<&mut Projection, With<Camera2d>>,
+) {
+    for command in console_commands.read() {
+        if command.name != "tour" {
+            continue;
+        }
+
+        match command.args.first().map(String::as_str) {
+            Some("off") => {
+                if tour.active {
+                    restore_zoom(&mut projection, tour.end(&mut world));
+                }
+                console.print("Tour stopped");
+            }
+            arg => {
+                if let Some(arg) = arg {
+                    match arg.parse::<f32>() {
+                        Ok(seconds) if seconds >= MIN_TOUR_INTERVAL => tour.interval = seconds,
+                        _ => {
+                            console.print(format!("Usage: tour [seconds|off] (at least {:.0} seconds)", MIN_TOUR_INTERVAL));
+                            continue;
+                        }
+                    }
+                }
+                if !tour.active {
+                    tour.start();
+                }
+                console.print(format!("Touring: a new stop every {:.0}s (tour off, F6 or a movement key stops it)", tour.interval));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tour_helpers() {
+        // Stops cycle, skipping kinds with nothing to show
+        assert_eq!(next_stop(None, |_| true), Some(TourStop::DensestForest));
+        assert_eq!(next_stop(Some(TourStop::ExploredChunk), |_| true), Some(TourStop::DensestForest));
+        let no_fire = |stop: TourStop| stop != TourStop::ActiveFire;
+        assert_eq!(next_stop(Some(TourStop::NewestGuardian), no_fire), Some(TourStop::ExploredChunk));
+        assert_eq!(next_stop(Some(TourStop::NewestGuardian), |_| false), None);
+
+        // Two trees in one chunk beat one in another
+        let trees = [Vec2::new(10.0, 10.0), Vec2::new(30.0, 50.0), Vec2::new(1000.0, 10.0)];
+        assert_eq!(densest_forest(trees), Some(Vec2::new(20.0, 30.0)));
+        assert_eq!(densest_forest([]), None);
+
+        assert_eq!(fire_focus(&[IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(9, 0)]), Some(tile_center(IVec2::new(1, 0))));
+        assert_eq!(fire_focus(&[]), None);
+
+        // Short legs glide and zoom out halfway; long legs cut over at the top of the zoom
+        let mut leg = TourLeg { from: Vec2::ZERO, to: Vec2::new(100.0, 0.0), elapsed: 0.0, base_scale: 1.0, still: false };
+        assert_eq!(leg.at(0.0), (Vec2::ZERO, 1.0));
+        assert_eq!(leg.at(0.5), (Vec2::new(50.0, 0.0), 1.0 + FLIGHT_ZOOM_OUT));
+        assert_eq!(leg.at(1.0).0, leg.to);
+        leg.to = Vec2::new(MAX_GLIDE_DISTANCE * 2.0, 0.0);
+        assert_eq!(leg.at(0.4).0, leg.from);
+        assert_eq!(leg.at(0.6).0, leg.to);
+        // Reduced motion always cuts, without zooming
+        leg.to = Vec2::new(100.0, 0.0);
+        leg.still = true;
+        assert_eq!(leg.at(0.4), (leg.from, 1.0));
+    }
+}
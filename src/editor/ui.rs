@@ -0,0 +1,690 @@
+use super::{EntityType, PaintMode, PlacementMode, TerrainType};
+use crate::assets::SpriteAssets;
+use crate::locale::LocalizedText;
+use crate::config::GameConfig;
+use crate::theme::{selection_outline, ThemedPanel};
+use crate::world::SaveWorldRequest;
+use bevy::picking::pointer::PointerButton;
+use bevy::prelude::*;
+
+// UI sprite vertical offsets for proper centering
+const HUMAN_SPRITE_OFFSET: f32 = 1.0;
+const FOREST_GUARDIAN_SPRITE_OFFSET: f32 = 10.0;
+const SNAIL_SPRITE_OFFSET: f32 = 10.0;
+const SNAIL_SPRITE_OFFSET_X: f32 = 10.0;
+
+// UI marker components
+#[derive(Component)]
+pub struct GuardianSubmenu;
+
+#[derive(Component)]
+pub struct GuardianButton;
+
+#[derive(Component)]
+pub struct TerrainSubmenu;
+
+#[derive(Component)]
+pub struct TerrainButton;
+
+pub fn setup_ui(
+    mut commands: Commands,
+    sprites: Res<SpriteAssets>,
+) {
+    // Root UI container on the left side
+    commands
+        .spawn((
+            ThemedPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                top: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Start,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            // Sprite sheets for UI buttons
+            let sheet = |kind: &str| sprites.entity(kind).cloned().unwrap_or_default();
+            let guardian_sheet = sheet("guardian:oak");
+            let human_sheet = sheet("player");
+            let snail_sheet = sheet("snail");
+
+            // Button 1 - With Human sprite
+            parent
+                .spawn((
+                    Button,
+                    EntityType::Player,
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        display: Display::Flex,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(0.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.3)),
+                    BorderColor::all(Color::srgb(0.4, 0.4, 0.6)),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .observe(button_interaction)
+                .with_children(|button| {
+                    button.spawn((
+                        ImageNode {
+                            image: human_sheet.image.clone(),
+                            image_mode: NodeImageMode::Stretch,
+                            texture_atlas: Some(human_sheet.atlas(0)),
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Px(64.0),
+                            height: Val::Px(64.0),
+                            margin: UiRect {
+                                top: Val::Px(HUMAN_SPRITE_OFFSET),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                });
+
+            // Button 2 - With Forest Guardian sprite (with submenu row)
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|row| {
+                    // Main guardian button
+                    row.spawn((
+                        Button,
+                        GuardianButton,
+                        EntityType::ForestGuardian("oak".to_string()),
+                        Node {
+                            width: Val::Px(64.0),
+                            height: Val::Px(64.0),
+                            display: Display::Flex,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(0.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.15, 0.3, 0.15)),
+                        BorderColor::all(Color::srgb(0.3, 0.6, 0.3)),
+                        BorderRadius::all(Val::Px(4.0)),
+                    ))
+                    .observe(button_interaction)
+                    .observe(guardian_button_right_click)
+                    .with_children(|button| {
+                        // Add guardian sprite directly
+                        button.spawn((
+                            ImageNode {
+                                image: guardian_sheet.image.clone(),
+                                image_mode: NodeImageMode::Stretch,
+                                texture_atlas: Some(guardian_sheet.atlas(0)), // First frame
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(64.0),
+                                height: Val::Px(64.0),
+                                margin: UiRect {
+                                    top: Val::Px(FOREST_GUARDIAN_SPRITE_OFFSET),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                        ));
+                    });
+
+                    // Submenu container (initially hidden)
+                    let guardians = ["oak", "birch", "hickory", "pine", "willow"];
+
+                    row.spawn((
+                        GuardianSubmenu,
+                        Node {
+                            display: Display::None, // Hidden by default
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|submenu| {
+                        for variant in guardians {
+                            let variant_sheet = sheet(&format!("guardian:{}", variant));
+
+                            submenu
+                                .spawn((
+                                    Button,
+                                    EntityType::ForestGuardian(variant.to_string()),
+                                    Node {
+                                        width: Val::Px(64.0),
+                                        height: Val::Px(64.0),
+                                        display: Display::Flex,
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        padding: UiRect::all(Val::Px(0.0)),
+                                        ..default()
+                                    },
+                                    BackgroundColor(Color::srgb(0.15, 0.3, 0.15)),
+                                    BorderColor::all(Color::srgb(0.3, 0.6, 0.3)),
+                                    BorderRadius::all(Val::Px(4.0)),
+                                ))
+                                .observe(button_interaction)
+                                .with_children(|button| {
+                                    button.spawn((
+                                        ImageNode {
+                                            image: variant_sheet.image.clone(),
+                                            image_mode: NodeImageMode::Stretch,
+                                            texture_atlas: Some(variant_sheet.atlas(0)),
+                                            ..default()
+                                        },
+                                        Node {
+                                            width: Val::Px(64.0),
+                                            height: Val::Px(64.0),
+                                            margin: UiRect {
+                                                top: Val::Px(FOREST_GUARDIAN_SPRITE_OFFSET),
+                                                ..default()
+                                            },
+                                            ..default()
+                                        },
+                                    ));
+                                });
+                        }
+                    });
+                });
+
+            // Button 3 - With Snail sprite
+            parent
+                .spawn((
+                    Button,
+                    EntityType::Snail,
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        display: Display::Flex,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(0.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.25, 0.2, 0.25)),
+                    BorderColor::all(Color::srgb(0.5, 0.4, 0.5)),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .observe(button_interaction)
+                .with_children(|button| {
+                    button.spawn((
+                        ImageNode {
+                            image: snail_sheet.image.clone(),
+                            image_mode: NodeImageMode::Stretch,
+                            texture_atlas: Some(snail_sheet.atlas(0)),
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Px(64.0),
+                            height: Val::Px(64.0),
+                            margin: UiRect {
+                                top: Val::Px(SNAIL_SPRITE_OFFSET),
+                                left: Val::Px(SNAIL_SPRITE_OFFSET_X),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ));
+                });
+
+            // Sign post button (no sprite, a wooden label)
+            parent
+                .spawn((
+                    Button,
+                    EntityType::Sign,
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(32.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.3, 0.22, 0.14)),
+                    BorderColor::all(Color::srgb(0.62, 0.45, 0.26)),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .observe(button_interaction)
+                .with_children(|button| {
+                    button.spawn((
+                        LocalizedText("sign-button"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+
+            // Button 4 - Terrain painting (with submenu row)
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|row| {
+                    // Terrain tiles for UI (separate file - won't be reinterpreted as array texture)
+                    // terrain_array_ui.png is 8x16 pixels = 2 tiles stacked vertically (8x8 each)
+                    let terrain_ui = &sprites.terrain_ui;
+
+                    // Main terrain button (starts with grass)
+                    row.spawn((
+                        Button,
+                        TerrainButton,
+                        TerrainType::Grass,
+                        Node {
+                            width: Val::Px(64.0),
+                            height: Val::Px(64.0),
+                            display: Display::Flex,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(0.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.3, 0.2)),
+                        BorderColor::all(Color::srgb(0.4, 0.6, 0.4)),
+                        BorderRadius::all(Val::Px(4.0)),
+                    ))
+                    .observe(terrain_button_interaction)
+                    .observe(terrain_button_right_click)
+                    .with_children(|button| {
+                        // Add grass tile sprite (index 0 in atlas = TILE_GRASS in world)
+                        button.spawn((
+                            ImageNode {
+                                image: terrain_ui.image.clone(),
+                                image_mode: NodeImageMode::Stretch,
+                                texture_atlas: Some(terrain_ui.atlas(0)), // First tile in atlas = grass
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(64.0),
+                                height: Val::Px(64.0),
+                                ..default()
+                            },
+                        ));
+                    });
+
+                    // Submenu container (initially hidden)
+                    row.spawn((
+                        TerrainSubmenu,
+                        Node {
+                            display: Display::None, // Hidden by default
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|submenu| {
+                        // Grass button
+                        submenu.spawn((
+                            Button,
+                            TerrainType::Grass,
+                            Node {
+                                width: Val::Px(64.0),
+                                height: Val::Px(64.0),
+                                display: Display::Flex,
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                padding: UiRect::all(Val::Px(0.0)),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.2, 0.3, 0.2)),
+                            BorderColor::all(Color::srgb(0.4, 0.6, 0.4)),
+                            BorderRadius::all(Val::Px(4.0)),
+                        ))
+                        .observe(terrain_button_interaction)
+                        .with_children(|button| {
+                            button.spawn((
+                                ImageNode {
+                                    image: terrain_ui.image.clone(),
+                                    image_mode: NodeImageMode::Stretch,
+                                    texture_atlas: Some(terrain_ui.atlas(0)), // First tile = grass
+                                    ..default()
+                                },
+                                Node {
+                                    width: Val::Px(64.0),
+                                    height: Val::Px(64.0),
+                                    ..default()
+                                },
+                            ));
+                        });
+
+                        // Dirt button
+                        submenu.spawn((
+                            Button,
+                            TerrainType::Dirt,
+                            Node {
+                                width: Val::Px(64.0),
+                                height: Val::Px(64.0),
+                                display: Display::Flex,
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                padding: UiRect::all(Val::Px(0.0)),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.2, 0.3, 0.2)),
+                            BorderColor::all(Color::srgb(0.4, 0.6, 0.4)),
+                            BorderRadius::all(Val::Px(4.0)),
+                        ))
+                        .observe(terrain_button_interaction)
+                        .with_children(|button| {
+                            button.spawn((
+                                ImageNode {
+                                    image: terrain_ui.image.clone(),
+                                    image_mode: NodeImageMode::Stretch,
+                                    texture_atlas: Some(terrain_ui.atlas(1)), // Second tile = dirt
+                                    ..default()
+                                },
+                                Node {
+                                    width: Val::Px(64.0),
+                                    height: Val::Px(64.0),
+                                    ..default()
+                                },
+                            ));
+                        });
+                    });
+                });
+
+            // Save World button (same as Ctrl+S)
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(32.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.3, 0.2)),
+                    BorderColor::all(Color::srgb(0.4, 0.6, 0.4)),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .observe(save_button_click)
+                .with_children(|button| {
+                    button.spawn((
+                        LocalizedText("save-button"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+}
+
+pub fn save_button_click(_trigger: On<Pointer<Click>>, mut requests: MessageWriter<SaveWorldRequest>) {
+    requests.write(SaveWorldRequest);
+}
+
+pub fn button_interaction(
+    trigger: On<Pointer<Click>>,
+    mut param_set: ParamSet<(
+        Query<(&EntityType, Option<&GuardianButton>), With<Button>>,
+        Query<(&mut EntityType, &Children), With<GuardianButton>>,
+    )>,
+    mut placement_mode: ResMut<PlacementMode>,
+    mut paint_mode: ResMut<PaintMode>,
+    mut submenu_query: Query<&mut Node, With<GuardianSubmenu>>,
+    mut image_query: Query<&mut ImageNode>,
+    sprites: Res<SpriteAssets>,
+) {
+    // First, get the clicked button's info
+    let button_info = param_set.p0().get(trigger.entity).ok().map(|(et, gb)| (et.clone(), gb.is_none()));
+
+    if let Some((entity_type, is_not_main_guardian)) = button_info {
+        // Check if this is a guardian variant from the submenu (not the main guardian button)
+        let is_submenu_guardian = matches!(entity_type, EntityType::ForestGuardian(_)) && is_not_main_guardian;
+
+        if is_submenu_guardian {
+            // Guardian variant selected from submenu - close menu and update main button
+            if let Ok(mut submenu_node) = submenu_query.single_mut() {
+                submenu_node.display = Display::None;
+            }
+
+            // Update the main guardian button's EntityType and icon
+            if let Ok((mut guardian_entity_type, children)) = param_set.p1().single_mut() {
+                *guardian_entity_type = entity_type.clone();
+
+                // Update the icon texture
+                if let EntityType::ForestGuardian(variant) = &entity_type {
+                    let new_texture = sprites
+                        .entity(&format!("guardian:{}", variant))
+                        .map(|sheet| sheet.image.clone())
+                        .unwrap_or_default();
+
+                    // Find and update the child ImageNode
+                    for child in children {
+                        if let Ok(mut image_node) = image_query.get_mut(*child) {
+                            image_node.image = new_texture.clone();
+                            info!("Updated guardian button icon to {} variant", variant);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Clear terrain paint mode when selecting entity
+        paint_mode.deselect();
+
+        // Toggle selection - if already selected, deselect; otherwise select
+        if placement_mode.is_selected(&entity_type) {
+            placement_mode.deselect();
+            info!("Deselected entity placement");
+        } else {
+            placement_mode.select(entity_type.clone());
+            info!("Selected entity type for placement: {:?}", entity_type);
+        }
+    }
+}
+
+pub fn guardian_button_right_click(
+    trigger: On<Pointer<Click>>,
+    mut submenu_query: Query<&mut Node, With<GuardianSubmenu>>,
+) {
+    // Only respond to right-click (Secondary button)
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+
+    // Toggle submenu visibility
+    if let Ok(mut node) = submenu_query.single_mut() {
+        node.display = if node.display == Display::None {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+pub fn terrain_button_interaction(
+    trigger: On<Pointer<Click>>,
+    mut param_set: ParamSet<(
+        Query<(&TerrainType, Option<&TerrainButton>), With<Button>>,
+        Query<(&mut TerrainType, &Children), With<TerrainButton>>,
+    )>,
+    mut paint_mode: ResMut<PaintMode>,
+    mut placement_mode: ResMut<PlacementMode>,
+    mut submenu_query: Query<&mut Node, With<TerrainSubmenu>>,
+    mut image_query: Query<&mut ImageNode>,
+) {
+    // First, get the clicked button's info
+    let button_info = param_set.p0().get(trigger.entity).ok().map(|(tt, tb)| (tt.clone(), tb.is_none()));
+
+    if let Some((terrain_type, is_not_main_terrain)) = button_info {
+        // Check if this is a terrain variant from the submenu (not the main terrain button)
+        let is_submenu_terrain = is_not_main_terrain;
+
+        if is_submenu_terrain {
+            // Terrain variant selected from submenu - close menu and update main button
+            if let Ok(mut submenu_node) = submenu_query.single_mut() {
+                submenu_node.display = Display::None;
+            }
+
+            // Update the main terrain button's TerrainType and icon
+            if let Ok((mut terrain_button_type, children)) = param_set.p1().single_mut() {
+                *terrain_button_type = terrain_type.clone();
+
+                // Update the icon texture atlas index (0-based, not tile IDs)
+                let atlas_index = match terrain_type {
+                    TerrainType::Grass => 0,  // First tile in atlas
+                    TerrainType::Dirt => 1,   // Second tile in atlas
+                };
+
+                // Find and update the child ImageNode's texture atlas index
+                for child in children {
+                    if let Ok(mut image_node) = image_query.get_mut(*child) {
+                        if let Some(ref mut atlas) = image_node.texture_atlas {
+                            atlas.index = atlas_index;
+                            info!("Updated terrain button icon to {:?} terrain", terrain_type);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Clear entity placement mode when selecting terrain
+        placement_mode.deselect();
+
+        // Toggle selection - if already selected, deselect; otherwise select
+        if paint_mode.is_selected(&terrain_type) {
+            paint_mode.deselect();
+            info!("Deselected terrain painting");
+        } else {
+            paint_mode.select(terrain_type.clone());
+            info!("Selected terrain type for painting: {:?}", terrain_type);
+        }
+    }
+}
+
+pub fn terrain_button_right_click(
+    trigger: On<Pointer<Click>>,
+    mut submenu_query: Query<&mut Node, With<TerrainSubmenu>>,
+) {
+    // Only respond to right-click (Secondary button)
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+
+    // Toggle submenu visibility
+    if let Ok(mut node) = submenu_query.single_mut() {
+        node.display = if node.display == Display::None {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+/// Updates button visual feedback based on placement mode selection (the selected button is
+/// outlined, thicker in high contrast)
+pub fn update_button_selection(
+    mut commands: Commands,
+    placement_mode: Res<PlacementMode>,
+    config: Res<GameConfig>,
+    mut buttons: Query<(Entity, &EntityType, &mut BackgroundColor, &mut BorderColor), With<Button>>,
+) {
+    // Only update if placement mode or the accessibility settings changed
+    if !placement_mode.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    for (entity, entity_type, mut bg_color, mut border_color) in buttons.iter_mut() {
+        let is_selected = placement_mode.is_selected(entity_type);
+
+        // Update colors based on entity type and selection state
+        match entity_type {
+            EntityType::Player => {
+                if is_selected {
+                    *bg_color = BackgroundColor(Color::srgb(0.3, 0.3, 0.5));
+                    *border_color = BorderColor::all(Color::srgb(0.6, 0.6, 1.0));
+                } else {
+                    *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.3));
+                    *border_color = BorderColor::all(Color::srgb(0.4, 0.4, 0.6));
+                }
+            }
+            EntityType::ForestGuardian(_) => {
+                if is_selected {
+                    *bg_color = BackgroundColor(Color::srgb(0.25, 0.5, 0.25));
+                    *border_color = BorderColor::all(Color::srgb(0.5, 1.0, 0.5));
+                } else {
+                    *bg_color = BackgroundColor(Color::srgb(0.15, 0.3, 0.15));
+                    *border_color = BorderColor::all(Color::srgb(0.3, 0.6, 0.3));
+                }
+            }
+            EntityType::Snail => {
+                if is_selected {
+                    *bg_color = BackgroundColor(Color::srgb(0.4, 0.3, 0.4));
+                    *border_color = BorderColor::all(Color::srgb(0.8, 0.6, 0.8));
+                } else {
+                    *bg_color = BackgroundColor(Color::srgb(0.25, 0.2, 0.25));
+                    *border_color = BorderColor::all(Color::srgb(0.5, 0.4, 0.5));
+                }
+            }
+            EntityType::Sign => {
+                if is_selected {
+                    *bg_color = BackgroundColor(Color::srgb(0.45, 0.33, 0.2));
+                    *border_color = BorderColor::all(Color::srgb(0.9, 0.7, 0.45));
+                } else {
+                    *bg_color = BackgroundColor(Color::srgb(0.3, 0.22, 0.14));
+                    *border_color = BorderColor::all(Color::srgb(0.62, 0.45, 0.26));
+                }
+            }
+        }
+        outline_selection(&mut commands, entity, is_selected, &config, border_color.top);
+    }
+}
+
+/// Updates terrain button visual feedback based on paint mode selection
+pub fn update_terrain_button_selection(
+    mut commands: Commands,
+    paint_mode: Res<PaintMode>,
+    config: Res<GameConfig>,
+    mut buttons: Query<(Entity, &TerrainType, &mut BackgroundColor, &mut BorderColor), With<Button>>,
+) {
+    // Only update if paint mode or the accessibility settings changed
+    if !paint_mode.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    for (entity, terrain_type, mut bg_color, mut border_color) in buttons.iter_mut() {
+        let is_selected = paint_mode.is_selected(terrain_type);
+
+        // Update colors based on selection state
+        if is_selected {
+            *bg_color = BackgroundColor(Color::srgb(0.3, 0.5, 0.3)); // Brighter when selected
+            *border_color = BorderColor::all(Color::srgb(0.6, 1.0, 0.6));
+        } else {
+            *bg_color = BackgroundColor(Color::srgb(0.2, 0.3, 0.2)); // Standard color
+            *border_color = BorderColor::all(Color::srgb(0.4, 0.6, 0.4));
+        }
+        outline_selection(&mut commands, entity, is_selected, &config, border_color.top);
+    }
+}
+
+/// Outlines a selected button in its border color; unselected buttons have no outline
+fn outline_selection(commands: &mut Commands, button: Entity, selected: bool, config: &GameConfig, color: Color) {
+    if selected {
+        commands.entity(button).insert(selection_outline(&config.accessibility, color));
+    } else {
+        commands.entity(button).remove::<Outline>();
+    }
+}
@@ -0,0 +1,92 @@
+use super::Position;
+use crate::world::WorldOrigin;
+use bevy::prelude::*;
+
+/// Entities within this many pixels (at the current zoom) outside the view are shown and animated,
+/// so they are already moving when they scroll in
+const SHOW_MARGIN: f32 = 128.0;
+
+/// Entities are only culled once this far outside the view, so ones near the edge don't flicker
+/// between the two states
+const HIDE_MARGIN: f32 = 256.0;
+
+/// Marker for entities outside the camera view: hidden, and skipped by `animate_sprite`
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Culled;
+
+/// The world-space rectangle a camera at `center` shows in a `viewport` at orthographic `scale`,
+/// grown by `margin` screen pixels on every side
+pub fn view_rect(center: Vec2, viewport: Vec2, scale: f32, margin: f32) -> Rect {
+    Rect::from_center_size(center, (viewport + Vec2::splat(margin * 2.0)) * scale)
+}
+
+/// Whether an entity at `position` should be culled, given whether it already is
+pub fn should_cull(position: Vec2, culled: bool, shown: Rect, kept: Rect) -> bool {
+    if culled {
+        !shown.contains(position)
+    } else {
+        !kept.contains(position)
+    }
+}
+
+/// Hides and stops animating sprites outside the camera view (plus a margin), and brings them back
+/// as they approach
+pub fn update_culling(
+    mut commands: Commands,
+    camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
+    window_query: Query<&Window>,
+    origin: Res<WorldOrigin>,
+    mut entities: Query<(Entity, &Position, &mut Visibility, Has<Culled>), With<Sprite>>,
+) {
+    // No window (headless) or camera: nothing to cull against
+    let (Ok((camera, projection)), Ok(window)) = (camera_query.single(), window_query.single()) else {
+        return;
+    };
+    let Projection::Orthographic(ortho) = projection else {
+        return;
+    };
+    let center = camera.translation.truncate();
+    let shown = view_rect(center, window.size(), ortho.scale, SHOW_MARGIN);
+    let kept = view_rect(center, window.size(), ortho.scale, HIDE_MARGIN);
+
+    for (entity, position, mut visibility, culled) in &mut entities {
+        let cull = should_cull(position.render_translation(origin.offset()), culled, shown, kept);
+        if cull == culled {
+            continue;
+        }
+        if cull {
+            commands.entity(entity).insert(Culled);
+            *visibility = Visibility::Hidden;
+        } else {
+            commands.entity(entity).remove::<Culled>();
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_culling() {
+        // An 800x600 window zoomed out 2x shows 1600x1200 world pixels
+        let view = view_rect(Vec2::new(100.0, 0.0), Vec2::new(800.0, 600.0), 2.0, 0.0);
+        assert_eq!(view.min, Vec2::new(-700.0, -600.0));
+        assert_eq!(view.max, Vec2::new(900.0, 600.0));
+
+        let shown = view_rect(Vec2::ZERO, Vec2::new(800.0, 600.0), 1.0, SHOW_MARGIN);
+        let kept = view_rect(Vec2::ZERO, Vec2::new(800.0, 600.0), 1.0, HIDE_MARGIN);
+        let in_view = Vec2::new(300.0, 0.0);
+        let near_edge = Vec2::new(400.0 + 200.0, 0.0);
+        let far = Vec2::new(400.0 + 300.0, 0.0);
+
+        assert!(!should_cull(in_view, false, shown, kept));
+        assert!(!should_cull(in_view, true, shown, kept));
+        // Between the margins an entity keeps whatever state it had
+        assert!(!should_cull(near_edge, false, shown, kept));
+        assert!(should_cull(near_edge, true, shown, kept));
+        assert!(should_cull(far, false, shown, kept));
+    }
+}
@@ -0,0 +1,167 @@
+//! Guardian directives: high-level goals given to a guardian (with the dev inspector's `directive`
+//! command) that steer its `RoamingBehavior` instead of letting it wander around its home
+
+use super::{ForestGuardian, Position, RoamingBehavior, Snail, WindingPath};
+use bevy::prelude::*;
+
+/// A reforested region is never roamed more tightly than this (pixels)
+const MIN_REGION_RADIUS: f32 = 16.0;
+
+/// How close a patrolling guardian gets to a waypoint before turning back (pixels)
+const PATROL_ARRIVE_DISTANCE: f32 = 8.0;
+
+/// How close a guarding guardian gets to a snail to send it away (pixels)
+const SHOO_DISTANCE: f32 = 12.0;
+
+/// A guardian's current goal (world pixels)
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum GuardianDirective {
+    /// Stay inside a marked rectangle and plant trees only there
+    Reforest { min: Vec2, max: Vec2 },
+    /// Walk back and forth between two points, heading to `to` while `outbound`
+    Patrol { from: Vec2, to: Vec2, outbound: bool },
+    /// Stay near `center` and chase away snails that come within `radius` of it
+    GuardSnails { center: Vec2, radius: f32 },
+}
+
+impl GuardianDirective {
+    /// A reforest region spanned by two corners in any order
+    pub fn reforest(a: Vec2, b: Vec2) -> Self {
+        Self::Reforest { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Reforest { min, max } => {
+                format!("reforest ({:.0}, {:.0}) - ({:.0}, {:.0})", min.x, min.y, max.x, max.y)
+            }
+            Self::Patrol { from, to, .. } => {
+                format!("patrol ({:.0}, {:.0}) <-> ({:.0}, {:.0})", from.x, from.y, to.x, to.y)
+            }
+            Self::GuardSnails { center, radius } => {
+                format!("guard ({:.0}, {:.0}) against snails within {:.0}px", center.x, center.y, radius)
+            }
+        }
+    }
+
+    /// Points the guardian's roaming at the goal; `intruder` is the snail to chase, if guarding
+    pub fn steer(&mut self, position: Vec2, roaming: &mut RoamingBehavior, intruder: Option<Vec2>) {
+        match self {
+            Self::Reforest { min, max } => {
+                let center = (*min + *max) / 2.0;
+                roaming.home = Position::from_vec2(center);
+                roaming.roam_radius = ((*max - *min) / 2.0).min_element().max(MIN_REGION_RADIUS);
+                // Head into the region when given it (the roaming picks spots inside from there)
+                let inside = |point: Vec2| point.cmpge(*min).all() && point.cmple(*max).all();
+                if !inside(position) && !inside(roaming.target.vec2()) {
+                    roaming.target = roaming.home;
+                    roaming.pause_timer = 0.0;
+                }
+            }
+            Self::Patrol { from, to, outbound } => {
+                let goal = if *outbound { *to } else { *from };
+                if position.distance(goal) < PATROL_ARRIVE_DISTANCE {
+                    *outbound = !*outbound;
+                }
+                let goal = if *outbound { *to } else { *from };
+                roaming.home = Position::from_vec2(goal);
+                roaming.roam_radius = 0.0;
+                roaming.target = roaming.home;
+                roaming.pause_timer = 0.0;
+            }
+            Self::GuardSnails { center, radius } => {
+                roaming.home = Position::from_vec2(*center);
+                roaming.roam_radius = *radius / 2.0;
+                match intruder {
+                    Some(snail) => {
+                        roaming.target = Position::from_vec2(snail);
+                        roaming.pause_timer = 0.0;
+                    }
+                    // Back to its post after a chase
+                    None if roaming.target.vec2().distance(*center) > roaming.roam_radius => {
+                        roaming.target = roaming.home;
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Where a tree the guardian plants at `spot` goes (inside the region when reforesting)
+    pub fn planting_spot(&self, spot: Vec2) -> Vec2 {
+        match self {
+            Self::Reforest { min, max } => spot.clamp(*min, *max),
+            _ => spot,
+        }
+    }
+}
+
+/// Steers guardians with a directive; guards turn snails they catch away from their post
+pub fn follow_directives(
+    mut guardians: Query<(&Position, &mut RoamingBehavior, &mut GuardianDirective), With<ForestGuardian>>,
+    mut snails: Query<(&Position, Option<&mut WindingPath>), With<Snail>>,
+) {
+    for (position, mut roaming, mut directive) in guardians.iter_mut() {
+        let intruder = match *directive {
+            GuardianDirective::GuardSnails { center, radius } => snails
+                .iter()
+                .map(|(snail, _)| snail.vec2())
+                .filter(|snail| snail.distance(center) <= radius)
+                .min_by(|a, b| a.distance_squared(center).total_cmp(&b.distance_squared(center))),
+            _ => None,
+        };
+        directive.steer(position.vec2(), &mut roaming, intruder);
+
+        let (Some(caught), GuardianDirective::GuardSnails { center, .. }) = (intruder, *directive) else {
+            continue;
+        };
+        if position.vec2().distance(caught) > SHOO_DISTANCE {
+            continue;
+        }
+        for (snail, path) in snails.iter_mut() {
+            if let (true, Some(mut path)) = (snail.vec2() == caught, path) {
+                let away = (caught - center).to_angle();
+                path.current_angle = away;
+                path.target_angle = away;
+                debug!("Guardian shooed a snail at ({:.0}, {:.0})", caught.x, caught.y);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directive_steering() {
+        let mut roaming = RoamingBehavior::new(Position::new(0.0, 0.0), 100.0, 15.0);
+
+        // Reforesting walks into the region and plants inside it
+        let mut reforest = GuardianDirective::reforest(Vec2::new(200.0, 100.0), Vec2::new(100.0, 0.0));
+        reforest.steer(Vec2::ZERO, &mut roaming, None);
+        assert_eq!(roaming.target.vec2(), Vec2::new(150.0, 50.0));
+        assert_eq!(roaming.roam_radius, 50.0);
+        assert_eq!(reforest.planting_spot(Vec2::new(0.0, 60.0)), Vec2::new(100.0, 60.0));
+
+        // Patrols turn around at each end
+        let mut patrol = GuardianDirective::Patrol { from: Vec2::ZERO, to: Vec2::new(300.0, 0.0), outbound: true };
+        patrol.steer(Vec2::new(10.0, 0.0), &mut roaming, None);
+        assert_eq!(roaming.target.vec2(), Vec2::new(300.0, 0.0));
+        patrol.steer(Vec2::new(297.0, 0.0), &mut roaming, None);
+        assert_eq!(roaming.target.vec2(), Vec2::ZERO);
+
+        // Guards chase an intruder, then go back to their post
+        let mut guard = GuardianDirective::GuardSnails { center: Vec2::ZERO, radius: 160.0 };
+        guard.steer(Vec2::ZERO, &mut roaming, Some(Vec2::new(120.0, 0.0)));
+        assert_eq!(roaming.target.vec2(), Vec2::new(120.0, 0.0));
+        guard.steer(Vec2::new(120.0, 0.0), &mut roaming, None);
+        assert_eq!(roaming.target.vec2(), Vec2::ZERO);
+
+        // Saved with the entity
+        for directive in [reforest, patrol, guard] {
+            assert_eq!(super::super::directive_from_saved(&super::super::saved_directive(&directive)), Some(directive));
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use super::{
+    heading_towards, trample, ForestGuardian, GrowingTree, Migrating, Position, Snail, TileTraffic, TreeSpirit, Velocity,
+    WindingPath,
+};
+use crate::config::GameConfig;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::environment::{tile_at, CurrentMoon, CurrentSeason, WorldWeather};
+use crate::governor::SimulationGovernor;
+use crate::stats::StatEvent;
+use crate::tiles::{TILE_DIRT, TILE_SIZE};
+use crate::world::{TileEdits, WorldManager, WorldOrigin};
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Seconds between reassigning which entities get full simulation
+const TIER_UPDATE_INTERVAL: f32 = 2.0;
+
+/// Seconds between background ecosystem ticks
+const BACKGROUND_TICK_INTERVAL: f32 = 5.0;
+
+/// Frame rate assumed when turning per-frame chances into per-tile odds
+const REFERENCE_FPS: f32 = 60.0;
+
+/// Marker for entities beyond the full simulation budget
+/// Per-frame behavior, growth and snail trails skip these; `simulate_background_ecosystem`
+/// advances them in coarse steps instead
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BackgroundSimulated;
+
+/// Entities that count against the full simulation budget (the player always gets full simulation)
+pub type TieredFilter = Or<(With<Snail>, With<TreeSpirit>, With<ForestGuardian>)>;
+
+/// Query filters for the two tiers of snails and trees
+pub type BackgroundSnailFilter = (With<Snail>, With<BackgroundSimulated>);
+pub type BackgroundTreeFilter = (With<TreeSpirit>, With<BackgroundSimulated>);
+pub type FullSnailFilter = (With<Snail>, Changed<Position>, Without<BackgroundSimulated>);
+pub type FullTreeFilter = (With<TreeSpirit>, Without<BackgroundSimulated>);
+
+/// Creatures (snails and forest guardians), counted by the map's population mode
+pub type CreatureFilter = Or<(With<Snail>, With<ForestGuardian>)>;
+
+/// How many entities are in each simulation tier, and how many are migrating (for the `ecosystem` command)
+#[derive(Resource, Default)]
+pub struct EcosystemState {
+    pub full: usize,
+    pub background: usize,
+    pub migrating: usize,
+}
+
+/// Chance that a snail crawling over a tile turns it into dirt, given the per-frame chance
+/// used by `snail_dirt_trail` and how long the snail takes to cross the tile
+pub fn tile_dirt_chance(per_frame_chance: f32, speed: f32) -> f32 {
+    if speed <= 0.0 {
+        return 0.0;
+    }
+    let frames_on_tile = TILE_SIZE / speed * REFERENCE_FPS;
+    1.0 - (1.0 - per_frame_chance.clamp(0.0, 1.0)).powf(frames_on_tile)
+}
+
+/// Points one tile apart along a straight path (excluding the start, including the end)
+pub fn points_along(from: Vec2, to: Vec2) -> Vec<Vec2> {
+    let steps = (from.distance(to) / TILE_SIZE).ceil() as usize;
+    (1..=steps).map(|step| from.lerp(to, step as f32 / steps as f32)).collect()
+}
+
+/// Gives the creatures and trees nearest the camera full simulation and moves the rest
+/// to the background model once there are more than `full_simulation_budget` of them
+/// (divided by the governor's tick multiplier when frames run over budget)
+#[allow(clippy::too_many_arguments)]
+pub fn assign_simulation_tiers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    config: Res<GameConfig>,
+    governor: Res<SimulationGovernor>,
+    camera: Single<&Transform, With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    entities: Query<(Entity, &Position, Has<BackgroundSimulated>), TieredFilter>,
+    mut velocities: Query<&mut Velocity>,
+    mut state: ResMut<EcosystemState>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < TIER_UPDATE_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+    let _span = info_span!("assign_simulation_tiers", entities = entities.iter().len()).entered();
+
+    let budget = match config.gameplay.full_simulation_budget {
+        0 => usize::MAX,
+        budget => (budget as f32 / governor.tick_multiplier()) as usize,
+    };
+    let camera_pos = origin.to_world_vec2(camera.translation.truncate());
+
+    let mut by_distance: Vec<(Entity, f32, bool)> = entities
+        .iter()
+        .map(|(entity, position, is_background)| {
+            (entity, camera_pos.distance_squared(position.vec2()), is_background)
+        })
+        .collect();
+    by_distance.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    for (rank, (entity, _, is_background)) in by_distance.iter().enumerate() {
+        let should_background = rank >= budget;
+        if should_background && !is_background {
+            commands.entity(*entity).insert(BackgroundSimulated);
+            // Background entities move in jumps, not by velocity
+            if let Ok(mut velocity) = velocities.get_mut(*entity) {
+                velocity.x = 0.0;
+                velocity.y = 0.0;
+            }
+        } else if !should_background && *is_background {
+            commands.entity(*entity).remove::<BackgroundSimulated>();
+        }
+    }
+
+    let full = by_distance.len().min(budget);
+    let background = by_distance.len() - full;
+    if state.full != full || state.background != background {
+        state.full = full;
+        state.background = background;
+    }
+}
+
+/// Coarse model for background entities: trees grow in bulk, snails jump along their
+/// winding path (or towards their migration target), dirty the tiles they would have crawled over
+/// and count them as traffic
+/// Ticks less often while the governor is throttling; each tick covers the whole elapsed time
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_background_ecosystem(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    config: Res<GameConfig>,
+    governor: Res<SimulationGovernor>,
+    season: Res<CurrentSeason>,
+    weather: Res<WorldWeather>,
+    moon: Res<CurrentMoon>,
+    world: Res<WorldManager>,
+    mut tile_edits: TileEdits,
+    mut traffic: ResMut<TileTraffic>,
+    mut snails: Query<(&mut Position, &mut WindingPath, Option<&Migrating>), BackgroundSnailFilter>,
+    mut trees: Query<(&mut GrowingTree, &mut Transform), BackgroundTreeFilter>,
+    mut stat_events: MessageWriter<StatEvent>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < BACKGROUND_TICK_INTERVAL * governor.tick_multiplier() {
+        return;
+    }
+    let tick = std::mem::take(&mut *elapsed);
+    let _span = info_span!("background_ecosystem_tick").entered();
+
+    let growth = tick * season.0.growth_multiplier() * weather.weather.growth_multiplier();
+    for (mut growing_tree, mut transform) in trees.iter_mut() {
+        if let Some(next_stage) = growing_tree.grow(growth) {
+            transform.scale = Vec3::splat(next_stage.scale());
+            if growing_tree.is_mature() {
+                stat_events.write(StatEvent::TreeGrown);
+            }
+        }
+    }
+
+    let hasher_builder = RandomState::new();
+    let activity = weather.weather.activity_multiplier() * moon.nocturnal_activity();
+    for (mut position, mut path, migration) in snails.iter_mut() {
+        let speed = path.speed * activity;
+        let hash = hasher_builder.hash_one(*position);
+        let from = position.vec2();
+
+        // Wander: one turn per tick, within the path's usual angle change (migrants head straight for their target)
+        path.current_angle = match migration {
+            Some(migration) => heading_towards(from, migration.target),
+            None => {
+                let turn = ((hash >> 40) as f32 / (1u64 << 24) as f32 - 0.5) * 2.0 * path.max_angle_change;
+                (path.current_angle + turn).rem_euclid(std::f32::consts::TAU)
+            }
+        };
+        path.target_angle = path.current_angle;
+
+        let to = from + Vec2::from_angle(path.current_angle) * speed * tick;
+        let chance = tile_dirt_chance(config.gameplay.snail_dirt_chance, speed);
+        for (step, point) in points_along(from, to).into_iter().enumerate() {
+            let roll = (hasher_builder.hash_one((hash, step)) >> 40) as f32 / (1u64 << 24) as f32;
+            if roll < chance {
+                tile_edits.set_ground(point, TILE_DIRT);
+            }
+            trample(&mut traffic, &world, &mut tile_edits, tile_at(point));
+        }
+
+        position.translate(to - from);
+    }
+}
+
+/// Console command: `ecosystem` shows how many entities are in each simulation tier and how many are migrating
+pub fn handle_ecosystem_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    config: Res<GameConfig>,
+    state: Res<EcosystemState>,
+) {
+    for command in console_commands.read() {
+        if command.name != "ecosystem" {
+            continue;
+        }
+
+        let budget = match config.gameplay.full_simulation_budget {
+            0 => "unlimited".to_string(),
+            budget => budget.to_string(),
+        };
+        console.print(format!(
+            "Full simulation: {} (budget {}), background: {}, migrating: {}",
+            state.full, budget, state.background, state.migrating
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_snail_trail() {
+        // A slow snail lingers on each tile long enough to almost always leave dirt
+        assert!(tile_dirt_chance(0.2, 2.5) > 0.99);
+        assert!(tile_dirt_chance(0.01, 2000.0) < 0.01);
+        assert_eq!(tile_dirt_chance(0.0, 2.5), 0.0);
+
+        let points = points_along(Vec2::ZERO, Vec2::new(20.0, 0.0));
+        assert_eq!(points.len(), 3);
+        assert_eq!(points.last(), Some(&Vec2::new(20.0, 0.0)));
+        assert!(points_along(Vec2::ONE, Vec2::ONE).is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Guardian names, titles and lore, generated from seeded word lists
+//! A guardian's seed comes from the world seed, its home and its variant, so the guardians a world
+//! starts with get the same names on every launch
+
+use super::{ForestGuardian, Position, RoamingBehavior, TreeVariant};
+use crate::world::metadata::DEFAULT_SEED;
+use crate::world::WorldMetadata;
+use bevy::prelude::*;
+
+/// Trees planted by one guardian that are worth a chronicle entry
+pub const GUARDIAN_TREE_MILESTONES: [u32; 3] = [10, 50, 100];
+
+const NAME_STARTS: [&str; 16] = [
+    "Bram", "Eld", "Fen", "Hol", "Ash", "Thorn", "Wil", "Mor", "Row", "Ald", "Bri", "Cal", "Dun", "Gar", "Lin", "Syl",
+];
+
+const NAME_ENDS: [&str; 14] = [
+    "wick", "ric", "wyn", "mere", "dell", "bark", "wood", "ren", "ith", "ora", "leaf", "moss", "den", "ley",
+];
+
+const TITLES: [&str; 10] = [
+    "the Patient",
+    "the Old",
+    "the Quiet",
+    "the Watchful",
+    "the Greenhanded",
+    "the Wanderer",
+    "the Deep-Rooted",
+    "the Mossbearded",
+    "the Gentle",
+    "the Unhurried",
+];
+
+/// "{name} ..." ({variant} is the guardian's tree)
+const ORIGINS: [&str; 6] = [
+    "woke beneath the first {variant} of the valley",
+    "was carried here as a {variant} seed on a northern wind",
+    "has tended {variant} groves since before the rivers had names",
+    "sprouted from a lightning-struck {variant}",
+    "followed the last {variant} out of a forest that is gone now",
+    "was sung awake by the wind in an old {variant}",
+];
+
+const TRAITS: [&str; 6] = [
+    "They hum to saplings at dusk.",
+    "They never step on a flower.",
+    "They count the rings of every tree they plant.",
+    "They distrust snails, but leave them be.",
+    "They only sleep when it rains.",
+    "They remember every tree that was ever cut.",
+];
+
+/// A guardian's generated identity, shown in the inspector and the chronicle
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct GuardianLore {
+    /// Everything below is generated from this
+    pub seed: u64,
+    pub name: String,
+    pub title: String,
+    pub lore: String,
+    /// Trees this guardian has planted since it was spawned
+    pub trees_planted: u32,
+}
+
+/// Seed for a guardian at `home`
+pub fn guardian_seed(world_seed: u64, home: Vec2, variant: TreeVariant) -> u64 {
+    let variant_index = TreeVariant::ALL.iter().position(|other| *other == variant).unwrap_or(0) as u64;
+    let position = (home.x.to_bits() as u64) << 32 | home.y.to_bits() as u64;
+    world_seed ^ position.rotate_left(17) ^ variant_index.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+impl GuardianLore {
+    pub fn generate(seed: u64, variant: TreeVariant) -> Self {
+        let mut state = seed;
+        // splitmix64
+        let mut pick = |len: usize| {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            (z % len as u64) as usize
+        };
+        let name = format!("{}{}", NAME_STARTS[pick(NAME_STARTS.len())], NAME_ENDS[pick(NAME_ENDS.len())]);
+        let title = TITLES[pick(TITLES.len())].to_string();
+        let origin = ORIGINS[pick(ORIGINS.len())].replace("{variant}", variant.as_str());
+        let trait_line = TRAITS[pick(TRAITS.len())];
+        Self {
+            seed,
+            lore: format!("{} {}. {}", name, origin, trait_line),
+            name,
+            title,
+            trees_planted: 0,
+        }
+    }
+
+    /// The lore of a guardian placed at `position` in the world with `metadata`
+    pub fn for_guardian(metadata: &WorldMetadata, position: &Position, variant: TreeVariant) -> Self {
+        let world_seed = metadata.seed.unwrap_or(DEFAULT_SEED);
+        Self::generate(guardian_seed(world_seed, position.vec2(), variant), variant)
+    }
+
+    /// "Bramwick the Patient"
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.name, self.title)
+    }
+}
+
+/// Names guardians once they're spawned (from their home, where they were placed)
+pub fn name_guardians(
+    mut commands: Commands,
+    metadata: Res<WorldMetadata>,
+    guardians: Query<(Entity, &ForestGuardian, &RoamingBehavior), Without<GuardianLore>>,
+) {
+    for (entity, guardian, roaming) in &guardians {
+        let lore = GuardianLore::for_guardian(&metadata, &roaming.home, guardian.variant);
+        debug!("Guardian named {}: {}", lore.full_name(), lore.lore);
+        commands.entity(entity).insert(lore);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guardian_lore() {
+        let seed = guardian_seed(DEFAULT_SEED, Vec2::new(-100.0, 0.0), TreeVariant::Oak);
+        let lore = GuardianLore::generate(seed, TreeVariant::Oak);
+        // Same seed, same guardian
+        assert_eq!(GuardianLore::generate(seed, TreeVariant::Oak), lore);
+        assert!(lore.lore.starts_with(&lore.name));
+        assert!(TITLES.contains(&lore.title.as_str()));
+        assert!(!lore.lore.contains("{variant}"));
+
+        // Neighbours and other worlds get other seeds
+        assert_ne!(guardian_seed(DEFAULT_SEED, Vec2::new(-99.0, 0.0), TreeVariant::Oak), seed);
+        assert_ne!(guardian_seed(DEFAULT_SEED, Vec2::new(-100.0, 0.0), TreeVariant::Birch), seed);
+        assert_ne!(guardian_seed(7, Vec2::new(-100.0, 0.0), TreeVariant::Oak), seed);
+        let names: std::collections::HashSet<String> = (0..50)
+            .map(|x| GuardianLore::generate(guardian_seed(1, Vec2::new(x as f32 * 32.0, 0.0), TreeVariant::Pine), TreeVariant::Pine).name)
+            .collect();
+        assert!(names.len() > 20);
+    }
+}
@@ -0,0 +1,140 @@
+use super::{BackgroundSimulated, EcosystemState, Position, Snail, WindingPath};
+use crate::tiles::CHUNK_PIXEL_SIZE;
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// Seconds between checks for arrivals and new migrations
+const MIGRATION_CHECK_INTERVAL: f32 = 30.0;
+
+/// Side of the square regions creatures are grouped by (4x4 chunks)
+const REGION_SIZE: f32 = CHUNK_PIXEL_SIZE * 4.0;
+
+/// A region needs at least this many settled creatures to count as a group
+const MIN_GROUP_SIZE: usize = 3;
+
+/// Chance that a group sets off at each check
+const MIGRATION_CHANCE: f32 = 0.05;
+
+/// How far groups travel (pixels); at snail speed this takes one to four in-world days
+const MIN_MIGRATION_DISTANCE: f32 = REGION_SIZE * 2.0;
+const MAX_MIGRATION_DISTANCE: f32 = REGION_SIZE * 6.0;
+
+/// Migrants count as arrived this close to their target (pixels)
+const ARRIVAL_RADIUS: f32 = 128.0;
+
+/// A creature travelling with its group towards a distant region
+/// Full-simulation migrants steer their winding path towards `target`; background migrants
+/// jump towards it in `simulate_background_ecosystem`, so groups keep travelling off screen
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Migrating {
+    pub target: Vec2,
+}
+
+/// Region a world position falls in
+pub fn region_of(position: Vec2) -> IVec2 {
+    (position / REGION_SIZE).floor().as_ivec2()
+}
+
+/// Where a group leaving `region` heads, given two random numbers in [0, 1)
+pub fn migration_target(region: IVec2, angle_roll: f32, distance_roll: f32) -> Vec2 {
+    let center = (region.as_vec2() + Vec2::splat(0.5)) * REGION_SIZE;
+    let angle = angle_roll * std::f32::consts::TAU;
+    let distance = MIN_MIGRATION_DISTANCE + distance_roll * (MAX_MIGRATION_DISTANCE - MIN_MIGRATION_DISTANCE);
+    center + Vec2::from_angle(angle) * distance
+}
+
+/// Heading (radians, matching `WindingPath` angles) from `from` towards `target`
+pub fn heading_towards(from: Vec2, target: Vec2) -> f32 {
+    let offset = target - from;
+    offset.y.atan2(offset.x).rem_euclid(std::f32::consts::TAU)
+}
+
+/// Ends migrations that arrived and occasionally sends a settled group off to a distant region
+pub fn update_migrations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    creatures: Query<(Entity, &Position, Option<&Migrating>), With<Snail>>,
+    mut state: ResMut<EcosystemState>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < MIGRATION_CHECK_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let mut groups: HashMap<IVec2, Vec<Entity>> = HashMap::new();
+    let mut migrating = 0;
+    for (entity, position, migration) in creatures.iter() {
+        let position = position.vec2();
+        match migration {
+            Some(migration) if position.distance(migration.target) <= ARRIVAL_RADIUS => {
+                commands.entity(entity).remove::<Migrating>();
+            }
+            Some(_) => migrating += 1,
+            None => groups.entry(region_of(position)).or_default().push(entity),
+        }
+    }
+
+    let hasher_builder = RandomState::new();
+    for (region, members) in groups {
+        if members.len() < MIN_GROUP_SIZE {
+            continue;
+        }
+        let hash = hasher_builder.hash_one(region);
+        let roll = |shift: u32| ((hash >> shift) & 0xFF_FFFF) as f32 / (1u64 << 24) as f32;
+        if roll(0) >= MIGRATION_CHANCE {
+            continue;
+        }
+
+        let target = migration_target(region, roll(24), roll(40));
+        info!(
+            "{} snails are migrating from region {:?} towards ({:.0}, {:.0})",
+            members.len(),
+            region,
+            target.x,
+            target.y
+        );
+        migrating += members.len();
+        for entity in members {
+            commands.entity(entity).insert(Migrating { target });
+        }
+    }
+
+    if state.migrating != migrating {
+        state.migrating = migrating;
+    }
+}
+
+/// Points full-simulation migrants' winding paths at their target
+/// (the path still wanders a little as it turns at `turn_rate`)
+pub fn steer_migrants(mut migrants: Query<(&Position, &Migrating, &mut WindingPath), Without<BackgroundSimulated>>) {
+    for (position, migration, mut path) in migrants.iter_mut() {
+        path.target_angle = heading_towards(position.vec2(), migration.target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_target_is_distant() {
+        for i in 0..16 {
+            let roll = i as f32 / 16.0;
+            let region = IVec2::new(-3, 2);
+            let target = migration_target(region, roll, 1.0 - roll);
+            let center = (region.as_vec2() + Vec2::splat(0.5)) * REGION_SIZE;
+            let distance = center.distance(target);
+            assert!((MIN_MIGRATION_DISTANCE - 0.01..=MAX_MIGRATION_DISTANCE + 0.01).contains(&distance));
+            assert_ne!(region_of(target), region);
+        }
+
+        assert_eq!(region_of(Vec2::new(-1.0, REGION_SIZE)), IVec2::new(-1, 1));
+        let heading = heading_towards(Vec2::ZERO, Vec2::new(0.0, -5.0));
+        assert!((heading - 1.5 * std::f32::consts::PI).abs() < 1e-5);
+    }
+}
@@ -1,7 +1,121 @@
+pub mod culling;
+pub mod directives;
+pub mod ecosystem;
+pub mod lore;
+pub mod migration;
+pub mod persistence;
+pub mod propagation;
+pub mod registry;
+pub mod settlers;
+pub mod spawn_queue;
 pub mod spawning;
 pub mod systems;
+pub mod trampling;
 pub mod types;
 
+pub use culling::*;
+pub use directives::*;
+pub use ecosystem::*;
+pub use lore::*;
+pub use migration::*;
+pub use persistence::*;
+pub use propagation::*;
+pub use registry::*;
+pub use settlers::*;
+pub use spawn_queue::*;
 pub use spawning::*;
 pub use systems::*;
+pub use trampling::*;
 pub use types::*;
+
+use crate::console::ConsoleAppExt;
+use crate::schedule::{add_game_sets, GameSet};
+use crate::world::loader;
+use bevy::prelude::*;
+
+/// Plugin for creatures and trees: movement and animation, AI behaviors, tree growth and spreading,
+/// the background ecosystem, migration, desire paths and culling of entities outside the view
+pub struct EntitiesPlugin;
+
+impl Plugin for EntitiesPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_sets(app);
+        app.init_resource::<EcosystemState>()
+            .init_resource::<SpawnQueue>()
+            .init_resource::<EntityRegistry>()
+            // Reflected so tools (the `dev` inspector) can read and edit them by name
+            .register_type::<Position>()
+            .register_type::<Velocity>()
+            .register_type::<Direction>()
+            .register_type::<EntityState>()
+            .register_type::<Health>()
+            .register_type::<Player>()
+            .register_type::<ForestGuardian>()
+            .register_type::<GuardianLore>()
+            .register_type::<GuardianDirective>()
+            .register_type::<Snail>()
+            .register_type::<Settler>()
+            .register_type::<TreeSpirit>()
+            .register_type::<TreeVariant>()
+            .register_type::<TreeSpawner>()
+            .register_type::<GrowthStage>()
+            .register_type::<GrowingTree>()
+            .register_type::<RoamingBehavior>()
+            .register_type::<WindingPath>()
+            .register_type::<AnimationIndices>()
+            .register_type::<AnimationTimer>()
+            .register_type::<BackgroundSimulated>()
+            .register_type::<Migrating>()
+            .register_type::<Culled>()
+            .init_resource::<TileTraffic>()
+            .add_console_command("traffic", "traffic - show how worn the tile under the camera is")
+            .add_console_command(
+                "ecosystem",
+                "ecosystem - show how many entities get full vs background simulation and how many are migrating",
+            )
+            // After world metadata is loaded
+            .add_systems(PostStartup, restore_traffic)
+            .add_systems(Update, (
+                follow_directives.before(update_roaming_behavior).before(update_winding_path),
+                update_roaming_behavior,
+                update_winding_path,
+                steer_migrants.after(update_winding_path),
+                update_migrations,
+                update_tree_spawning,
+                propagate_trees,
+                // After every system that queues spawns
+                process_spawn_queue.after(update_tree_spawning).after(propagate_trees),
+                name_guardians.after(process_spawn_queue),
+                sync_settlers,
+                // Creatures that wandered out of the loaded chunks are saved with theirs
+                stash_stray_entities,
+                // Simulation budget (background ecosystem beyond it)
+                assign_simulation_tiers,
+            ).in_set(GameSet::Ai))
+            .add_systems(Update, (
+                apply_velocity,
+                update_state_from_velocity,
+                update_direction_from_velocity,
+                simulate_background_ecosystem,
+                update_tree_growth,
+            ).in_set(GameSet::Movement))
+            // After everything that moves or spawns entities, so sprites never show a stale Transform
+            .add_systems(PostUpdate, sync_position_with_transform.before(TransformSystems::Propagate))
+            .add_systems(Update, (
+                // Tiles under the creatures, before the queued modifications are applied
+                snail_dirt_trail.before(loader::apply_tile_modifications),
+                record_traffic.before(loader::apply_tile_modifications),
+            ).in_set(GameSet::WorldApply))
+            .add_systems(Update, (
+                update_culling,
+                update_animation_from_direction,
+                animate_sprite.after(update_animation_from_direction).after(update_culling),
+            ).in_set(GameSet::Render))
+            .add_systems(Update, (
+                decay_traffic,
+                sync_traffic.after(decay_traffic),
+                handle_ecosystem_commands,
+                handle_traffic_commands,
+            ));
+    }
+}
@@ -0,0 +1,321 @@
+use bevy::math::DVec2;
+use bevy::prelude::*;
+use super::{
+    spawn_forest_guardian, spawn_grown_tree, spawn_snail, ForestGuardian, GrowingTree, GrowthStage, GuardianDirective, Health, Migrating,
+    Player, Position, RoamingBehavior, Snail, TreeSpirit, TreeVariant, Velocity, WindingPath,
+};
+use crate::assets::SpriteAssets;
+use crate::error::Failures;
+use crate::tiles::chunk::coords;
+use crate::tiles::ChunkPos;
+use crate::world::entity_serialization::{SavedComponent, SavedEntity, TAG_GROWTH, TAG_POSITION};
+use crate::world::{model, WorldManager};
+use std::collections::HashMap;
+
+/// Seconds between checks for creatures and trees that wandered out of the loaded chunks
+const STRAY_CHECK_INTERVAL: f32 = 1.0;
+
+/// Query data needed to save an entity
+pub type SaveableEntity<'a> = (
+    &'a Position,
+    Option<&'a Velocity>,
+    Option<&'a Health>,
+    Option<&'a GrowingTree>,
+    Option<&'a RoamingBehavior>,
+    Option<&'a ForestGuardian>,
+    Option<&'a GuardianDirective>,
+    Option<&'a WindingPath>,
+    Option<&'a Migrating>,
+);
+
+/// Filter matching every entity kind that gets saved
+pub type SaveableFilter = Or<(With<Player>, With<ForestGuardian>, With<Snail>, With<TreeSpirit>)>;
+
+/// Kind string stored in the entity file
+/// Takes the markers that can't be read from `SaveableEntity` as flags
+pub fn entity_kind(
+    is_player: bool,
+    is_snail: bool,
+    guardian: Option<&ForestGuardian>,
+    tree: Option<&GrowingTree>,
+) -> Option<String> {
+    if is_player {
+        Some("player".to_string())
+    } else if is_snail {
+        Some("snail".to_string())
+    } else if let Some(guardian) = guardian {
+        Some(format!("guardian:{}", guardian.variant.as_str()))
+    } else {
+        tree.map(|tree| format!("tree_spirit:{}", tree.variant.as_str()))
+    }
+}
+
+/// Convert an entity's components into a saved entity record
+pub fn to_saved_entity(kind: String, entity: SaveableEntity) -> SavedEntity {
+    let (position, velocity, health, growing_tree, roaming, _, directive, winding, migration) = entity;
+    let mut components = vec![
+        SavedComponent::Kind(kind),
+        SavedComponent::Position { x: position.dvec2().x, y: position.dvec2().y },
+    ];
+
+    if let Some(velocity) = velocity {
+        components.push(SavedComponent::Velocity { x: velocity.x, y: velocity.y });
+    }
+    if let Some(health) = health {
+        components.push(SavedComponent::Health { current: health.current, max: health.max });
+    }
+    if let Some(tree) = growing_tree {
+        components.push(SavedComponent::Growth {
+            stage: tree.stage as u8,
+            time_in_stage: tree.time_in_stage,
+            time_to_next_stage: tree.time_to_next_stage,
+        });
+    }
+    if let Some(roaming) = roaming {
+        components.push(SavedComponent::Roaming {
+            home_x: roaming.home.x(),
+            home_y: roaming.home.y(),
+            roam_radius: roaming.roam_radius,
+            speed: roaming.speed,
+        });
+    }
+
+    if let Some(directive) = directive {
+        components.push(saved_directive(directive));
+    }
+    if let Some(path) = winding {
+        components.push(SavedComponent::Winding {
+            speed: path.speed,
+            angle: path.current_angle,
+            target_angle: path.target_angle,
+            segment_length: path.segment_length,
+            distance_traveled: path.distance_traveled,
+            turn_rate: path.turn_rate,
+            min_segment: path.min_segment_length,
+            max_segment: path.max_segment_length,
+            max_angle_change: path.max_angle_change,
+        });
+    }
+    if let Some(migration) = migration {
+        components.push(SavedComponent::Migration { target_x: migration.target.x, target_y: migration.target.y });
+    }
+
+    SavedEntity::new(components)
+}
+
+/// A directive as a saved component record
+pub fn saved_directive(directive: &GuardianDirective) -> SavedComponent {
+    let (kind, params) = match *directive {
+        GuardianDirective::Reforest { min, max } => (0, [min.x, min.y, max.x, max.y, 0.0]),
+        GuardianDirective::Patrol { from, to, outbound } => (1, [from.x, from.y, to.x, to.y, outbound as u8 as f32]),
+        GuardianDirective::GuardSnails { center, radius } => (2, [center.x, center.y, radius, 0.0, 0.0]),
+    };
+    SavedComponent::Directive { kind, params }
+}
+
+/// The directive in a saved component record (None for other records and unknown kinds)
+pub fn directive_from_saved(component: &SavedComponent) -> Option<GuardianDirective> {
+    let SavedComponent::Directive { kind, params: [a, b, c, d, e] } = *component else {
+        return None;
+    };
+    match kind {
+        0 => Some(GuardianDirective::reforest(Vec2::new(a, b), Vec2::new(c, d))),
+        1 => Some(GuardianDirective::Patrol { from: Vec2::new(a, b), to: Vec2::new(c, d), outbound: e != 0.0 }),
+        2 => Some(GuardianDirective::GuardSnails { center: Vec2::new(a, b), radius: c }),
+        _ => None,
+    }
+}
+
+/// Creatures and trees saved with the chunk they stand in; the player (in the entity file) and
+/// settlers (respawned from `ChunkData.settlers`) aren't
+pub type ChunkEntityFilter = (Or<(With<ForestGuardian>, With<Snail>, With<TreeSpirit>)>, Without<Player>);
+
+/// Query data for `entities_by_chunk`
+pub type ChunkEntity<'a> = (Entity, SaveableEntity<'a>, Has<Snail>);
+
+/// Saved records of the creatures and trees out in the world, by the chunk they stand in
+pub fn entities_by_chunk(
+    entities: &Query<ChunkEntity, ChunkEntityFilter>,
+) -> HashMap<ChunkPos, Vec<(Entity, SavedEntity)>> {
+    let mut by_chunk: HashMap<ChunkPos, Vec<(Entity, SavedEntity)>> = HashMap::new();
+    for (entity, saveable, is_snail) in entities.iter() {
+        let Some(kind) = entity_kind(false, is_snail, saveable.5, saveable.3) else {
+            continue;
+        };
+        let chunk_pos = coords::world_to_chunk(saveable.0.vec2());
+        by_chunk.entry(chunk_pos).or_default().push((entity, to_saved_entity(kind, saveable)));
+    }
+    by_chunk
+}
+
+/// The entities standing in a chunk and their records, taken out of `entities_by_chunk`'s map
+pub fn take_chunk_entities(
+    by_chunk: &mut HashMap<ChunkPos, Vec<(Entity, SavedEntity)>>,
+    chunk_pos: ChunkPos,
+) -> (Vec<Entity>, Vec<SavedEntity>) {
+    by_chunk.remove(&chunk_pos).unwrap_or_default().into_iter().unzip()
+}
+
+/// Spawns a creature or tree saved with a chunk (`to_saved_entity` in reverse)
+/// None if the record has no position or a kind this version doesn't know
+pub fn spawn_saved_entity(commands: &mut Commands, saved: &SavedEntity, sprites: &SpriteAssets) -> Option<Entity> {
+    let Some(&SavedComponent::Position { x, y }) = saved.get(TAG_POSITION) else {
+        return None;
+    };
+    let position = Position::from_dvec2(DVec2::new(x, y));
+    let kind = saved.kind()?;
+    let entity = match kind.split_once(':') {
+        None if kind == "snail" => spawn_snail(commands, position, sprites),
+        Some(("guardian", variant)) => spawn_forest_guardian(commands, position, variant, sprites),
+        Some(("tree_spirit", variant)) => {
+            let variant = TreeVariant::from_str(variant);
+            let growing_tree = match saved.get(TAG_GROWTH) {
+                Some(&SavedComponent::Growth { stage, time_in_stage, time_to_next_stage }) => GrowingTree {
+                    stage: GrowthStage::from_index(stage).unwrap_or(GrowthStage::MatureTree),
+                    time_in_stage,
+                    time_to_next_stage,
+                    variant,
+                },
+                _ => GrowingTree { stage: GrowthStage::MatureTree, ..GrowingTree::new(variant) },
+            };
+            spawn_grown_tree(commands, position, growing_tree, sprites)
+        }
+        _ => return None,
+    };
+
+    let mut spawned = commands.entity(entity);
+    for component in &saved.components {
+        match *component {
+            SavedComponent::Velocity { x, y } => {
+                spawned.insert(Velocity::new(x, y));
+            }
+            SavedComponent::Health { current, max } => {
+                spawned.insert(Health { current, max });
+            }
+            SavedComponent::Roaming { home_x, home_y, roam_radius, speed } => {
+                spawned.insert(RoamingBehavior::new(Position::new(home_x, home_y), roam_radius, speed));
+            }
+            SavedComponent::Directive { .. } => {
+                if let Some(directive) = directive_from_saved(component) {
+                    spawned.insert(directive);
+                }
+            }
+            SavedComponent::Winding {
+                speed,
+                angle,
+                target_angle,
+                segment_length,
+                distance_traveled,
+                turn_rate,
+                min_segment,
+                max_segment,
+                max_angle_change,
+            } => {
+                spawned.insert(WindingPath {
+                    current_angle: angle,
+                    target_angle,
+                    speed,
+                    segment_length,
+                    distance_traveled,
+                    turn_rate,
+                    min_segment_length: min_segment,
+                    max_segment_length: max_segment,
+                    max_angle_change,
+                });
+            }
+            SavedComponent::Migration { target_x, target_y } => {
+                spawned.insert(Migrating { target: Vec2::new(target_x, target_y) });
+            }
+            _ => {}
+        }
+    }
+    Some(entity)
+}
+
+/// Gives each loaded chunk the creatures and trees standing in it now (`ChunkData.entities` is
+/// stale while a chunk is loaded), marking the ones that changed dirty
+/// Returns the records of the ones outside the loaded chunks
+pub fn stash_loaded_entities(
+    world: &mut WorldManager,
+    entities: &Query<ChunkEntity, ChunkEntityFilter>,
+) -> HashMap<ChunkPos, Vec<(Entity, SavedEntity)>> {
+    let mut by_chunk = entities_by_chunk(entities);
+    let loaded: Vec<ChunkPos> = world.active_chunks.keys().copied().collect();
+    for chunk_pos in loaded {
+        let (_, records) = take_chunk_entities(&mut by_chunk, chunk_pos);
+        if model::stash_entities(world, chunk_pos, records) {
+            world.mark_dirty(chunk_pos);
+        }
+    }
+    by_chunk
+}
+
+/// Saves every creature and tree with its chunk, for Save World and exiting: loaded chunks are
+/// refreshed (`stash_loaded_entities`), strays are moved into theirs
+pub fn stash_all_entities(
+    commands: &mut Commands,
+    world: &mut WorldManager,
+    entities: &Query<ChunkEntity, ChunkEntityFilter>,
+    failures: &mut Failures,
+) {
+    let strays = stash_loaded_entities(world, entities);
+    stash_strays(commands, world, strays, failures);
+}
+
+/// Despawns the creatures and trees standing in a chunk without saving them (its data is being
+/// replaced, and they come back from the new data)
+pub fn despawn_chunk_entities(
+    commands: &mut Commands,
+    entities: &Query<ChunkEntity, ChunkEntityFilter>,
+    chunk_pos: ChunkPos,
+) {
+    for (entity, saveable, _) in entities.iter() {
+        if coords::world_to_chunk(saveable.0.vec2()) == chunk_pos {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Despawns the creatures and trees outside the loaded chunks, saving them with the chunk they
+/// stand in (`model::stash_strays`) so they come back when it loads
+fn stash_strays(
+    commands: &mut Commands,
+    world: &mut WorldManager,
+    by_chunk: HashMap<ChunkPos, Vec<(Entity, SavedEntity)>>,
+    failures: &mut Failures,
+) {
+    for (chunk_pos, strays) in by_chunk {
+        if world.is_loaded(&chunk_pos) {
+            continue;
+        }
+        let (strays, records): (Vec<Entity>, Vec<SavedEntity>) = strays.into_iter().unzip();
+        match model::stash_strays(world, chunk_pos, records) {
+            Ok(()) => {
+                for entity in strays {
+                    commands.entity(entity).despawn();
+                }
+            }
+            Err(e) => failures.report(format!("Failed to read chunk ({}, {}) to save creatures in", chunk_pos.x, chunk_pos.y), e),
+        }
+    }
+}
+
+/// Every `STRAY_CHECK_INTERVAL` seconds, creatures and trees that wandered out of the loaded chunks
+/// are saved with the chunk they stand in and despawned (the ones in unloading chunks are saved by
+/// `loader::unload_distant_chunks`)
+pub fn stash_stray_entities(
+    mut commands: Commands,
+    mut world: ResMut<WorldManager>,
+    entities: Query<ChunkEntity, ChunkEntityFilter>,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut failures: Failures,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < STRAY_CHECK_INTERVAL || world.camera_chunk.is_none() {
+        return;
+    }
+    *elapsed = 0.0;
+    let by_chunk = entities_by_chunk(&entities);
+    stash_strays(&mut commands, &mut world, by_chunk, &mut failures);
+}
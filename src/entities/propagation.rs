@@ -0,0 +1,121 @@
+use super::{GrowingTree, Position, SpawnKind, SpawnQueue, TreeSpirit};
+use crate::environment::{tree_viability, CurrentSeason, RandomTick};
+use crate::map::{MapBiome, TerrainHistogram};
+use crate::tiles::{chunk::coords, ChunkPos, LAYER_GROUND, TILE_GRASS};
+use crate::world::WorldManager;
+use bevy::prelude::*;
+
+/// Seeds only land this close to a mature tree (pixels)
+const SEED_RADIUS: f32 = 64.0;
+
+/// No seed takes root this close to another tree (pixels)
+const MIN_TREE_SPACING: f32 = 16.0;
+
+/// Density cap: at most this many trees within `SEED_RADIUS` of the landing spot
+const MAX_NEARBY_TREES: usize = 6;
+
+/// Chance that a random tick on grass near a mature tree sprouts a seed
+/// (scaled by the season's growth multiplier, so nothing sprouts in winter)
+const SEED_CHANCE: f32 = 0.4;
+
+/// Pick the mature tree that seeds `target`, if a seed may land there
+/// `trees` yields (position, is mature, payload) for every tree; returns the payload of the
+/// nearest mature tree within `SEED_RADIUS`, or None if there is none or the spot is too crowded
+pub fn find_seed_parent<T>(target: Vec2, trees: impl IntoIterator<Item = (Vec2, bool, T)>) -> Option<T> {
+    let mut nearby = 0;
+    let mut parent: Option<(f32, T)> = None;
+    for (position, is_mature, payload) in trees {
+        let distance = position.distance(target);
+        if distance < MIN_TREE_SPACING {
+            return None;
+        }
+        if distance > SEED_RADIUS {
+            continue;
+        }
+
+        nearby += 1;
+        if nearby >= MAX_NEARBY_TREES {
+            return None;
+        }
+        if is_mature && parent.as_ref().is_none_or(|(closest, _)| distance < *closest) {
+            parent = Some((distance, payload));
+        }
+    }
+    parent.map(|(_, payload)| payload)
+}
+
+/// Biome of a loaded chunk, classified from its ground layer like the map does
+fn chunk_biome(world: &WorldManager, chunk_pos: &ChunkPos) -> Option<MapBiome> {
+    let chunk_data = world.get_cached_chunk(chunk_pos)?;
+    let mut terrain = TerrainHistogram::default();
+    for tile_id in chunk_data.layers[LAYER_GROUND].iter() {
+        terrain.add(*tile_id, 1);
+    }
+    MapBiome::classify(&terrain)
+}
+
+/// Mature trees scatter seeds: a random tick on grass near a mature tree may sprout a
+/// new tree of the same variant, as long as the spot isn't crowded, lies in the same biome
+/// and suits the variant (see `tree_viability`)
+pub fn propagate_trees(
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut ticks: MessageReader<RandomTick>,
+    season: Res<CurrentSeason>,
+    world: Res<WorldManager>,
+    trees: Query<(&Position, &GrowingTree), With<TreeSpirit>>,
+) {
+    let chance = SEED_CHANCE * season.0.growth_multiplier();
+    let _span = info_span!("propagate_trees", trees = trees.iter().len()).entered();
+
+    for tick in ticks.read() {
+        if tick.ground != TILE_GRASS || tick.roll >= chance {
+            continue;
+        }
+
+        let existing = trees.iter().map(|(position, tree)| {
+            let position = position.vec2();
+            (position, tree.is_mature(), Some((position, *tree)))
+        });
+        // Seeds still in the spawn queue aren't in the query yet
+        let queued = spawn_queue.queued_trees().map(|position| (position.vec2(), false, None));
+        let Some(Some((parent_position, parent))) = find_seed_parent(tick.position, existing.chain(queued)) else {
+            continue;
+        };
+
+        // Seeds only take root in the biome their parent grows in
+        let parent_biome = chunk_biome(&world, &coords::world_to_chunk(parent_position));
+        if parent_biome.is_none() || parent_biome != chunk_biome(&world, &tick.chunk) {
+            continue;
+        }
+        if tree_viability(&world, season.0, parent.variant, tick.position).is_err() {
+            continue;
+        }
+
+        spawn_queue.push(Position::new(tick.position.x, tick.position.y), SpawnKind::TreeSpirit {
+            variant: parent.variant,
+            growth_time: parent.time_to_next_stage,
+        });
+        debug!("{:?} tree seeded a new tree at {:?}", parent.variant, tick.position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_seed_parent() {
+        let target = Vec2::ZERO;
+        let mature = |x: f32, id: u32| (Vec2::new(x, 0.0), true, id);
+
+        // Nearest mature tree in range wins; saplings count for density but can't seed
+        let trees = vec![mature(50.0, 1), mature(30.0, 2), (Vec2::new(20.0, 0.0), false, 3), mature(100.0, 4)];
+        assert_eq!(find_seed_parent(target, trees), Some(2));
+
+        // Out of range, too close, or too crowded
+        assert_eq!(find_seed_parent(target, vec![mature(SEED_RADIUS + 1.0, 1)]), None);
+        assert_eq!(find_seed_parent(target, vec![mature(40.0, 1), mature(8.0, 2)]), None);
+        let crowded: Vec<_> = (0..MAX_NEARBY_TREES).map(|i| mature(20.0 + i as f32, i as u32)).collect();
+        assert_eq!(find_seed_parent(target, crowded), None);
+    }
+}
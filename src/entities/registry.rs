@@ -0,0 +1,80 @@
+use super::TreeVariant;
+use crate::tiles::registry::BUILTIN_SOURCE;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// An entity type and the sprite sheet it is drawn with
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityDefinition {
+    /// Sprite sheet path (an asset path for built-ins, a file path for mod packs)
+    pub sprite: String,
+    /// Size of one frame in pixels, and the sheet's columns and rows
+    pub frame_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Who registered the entity: `BUILTIN_SOURCE` or the name of a mod pack
+    pub source: String,
+}
+
+/// Entity types by name, matching the saved entity kinds ("player", "snail", "guardian:oak", "tree_spirit:oak")
+/// Starts with the built-in creatures; mod packs add theirs at startup (see `mods`)
+#[derive(Resource, Debug, Clone)]
+pub struct EntityRegistry {
+    entities: BTreeMap<String, EntityDefinition>,
+}
+
+impl EntityRegistry {
+    pub fn new() -> Self {
+        let builtin = |sprite: String, columns| EntityDefinition {
+            sprite,
+            frame_size: 32,
+            columns,
+            rows: 4,
+            source: BUILTIN_SOURCE.to_string(),
+        };
+
+        let mut entities = BTreeMap::new();
+        entities.insert("player".to_string(), builtin("characters/human_walk.png".to_string(), 4));
+        entities.insert("snail".to_string(), builtin("creatures/snail/snail_crawl.png".to_string(), 4));
+        for variant in TreeVariant::ALL {
+            let sprite = format!("creatures/forest_guardians/{}_guardian_idle.png", variant.as_str());
+            entities.insert(format!("guardian:{}", variant.as_str()), builtin(sprite, 8));
+            let sprite = format!("creatures/tree_spirits/{}_spirit_idle.png", variant.as_str());
+            entities.insert(format!("tree_spirit:{}", variant.as_str()), builtin(sprite, 8));
+        }
+        Self { entities }
+    }
+
+    /// Register an entity type; the first registration of a name wins
+    /// Returns the already-registered definition on a conflict
+    pub fn register(&mut self, name: String, definition: EntityDefinition) -> Result<(), &EntityDefinition> {
+        if self.entities.contains_key(&name) {
+            return Err(&self.entities[&name]);
+        }
+        self.entities.insert(name, definition);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntityDefinition> {
+        self.entities.get(name)
+    }
+
+    /// Registered entity types in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &EntityDefinition)> {
+        self.entities.iter().map(|(name, definition)| (name.as_str(), definition))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl Default for EntityRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,44 @@
+//! Settlement NPCs: the `ChunkSettler` records a chunk carries (see `world::structures`) are spawned
+//! as roaming `Settler`s while the chunk is loaded and despawned when it unloads. They aren't in the
+//! entity file; saving and reloading the chunk brings them back at home
+
+use super::{spawn_settler, Position, Settler};
+use crate::assets::SpriteAssets;
+use crate::tiles::{ChunkPos, CHUNK_SIZE_I32, TILE_SIZE};
+use crate::world::WorldManager;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Spawns the settlers of loaded chunks that have none out yet, and despawns settlers whose home
+/// chunk unloaded
+pub fn sync_settlers(
+    mut commands: Commands,
+    world: Res<WorldManager>,
+    settlers: Query<(Entity, &Settler)>,
+    sprites: Res<SpriteAssets>,
+) {
+    let mut housed = HashSet::new();
+    for (entity, settler) in settlers.iter() {
+        if world.is_loaded(&settler.home_chunk) {
+            housed.insert(settler.home_chunk);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for chunk_pos in world.active_chunks.keys().filter(|pos| !housed.contains(*pos)) {
+        let Some(chunk) = world.get_cached_chunk(chunk_pos) else {
+            continue;
+        };
+        for settler in &chunk.settlers {
+            let home = settler_home(*chunk_pos, settler.x, settler.y);
+            spawn_settler(&mut commands, home, *chunk_pos, settler.roam_radius as f32 * TILE_SIZE, &sprites);
+        }
+    }
+}
+
+/// World position of the middle of a chunk's local tile
+fn settler_home(chunk: ChunkPos, x: u8, y: u8) -> Position {
+    let tile = IVec2::new(chunk.x, chunk.y) * CHUNK_SIZE_I32 + IVec2::new(x as i32, y as i32);
+    Position::from_vec2((tile.as_vec2() + 0.5) * TILE_SIZE)
+}
@@ -0,0 +1,129 @@
+use super::{spawn_forest_guardian, spawn_mature_tree, spawn_player, spawn_snail, spawn_tree_spirit, Position, TreeVariant};
+use crate::assets::SpriteAssets;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Most trees and creatures spawned in one frame; the rest wait in the `SpawnQueue`
+pub const MAX_SPAWNS_PER_FRAME: usize = 8;
+
+/// What a queued spawn creates
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpawnKind {
+    Player,
+    /// Variant name: "oak", "birch", etc.
+    ForestGuardian(String),
+    Snail,
+    TreeSpirit { variant: TreeVariant, growth_time: f32 },
+    /// A fully grown tree (scenario starts)
+    MatureTree(TreeVariant),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedSpawn {
+    pub position: Position,
+    pub kind: SpawnKind,
+}
+
+/// Trees and creatures waiting to spawn, oldest first
+/// Spawners push here instead of spawning directly, so a burst (a grove of guardians planting in the
+/// same frame, a client placing many snails) is spread over several frames by `process_spawn_queue`
+#[derive(Resource, Debug, Default)]
+pub struct SpawnQueue {
+    pending: VecDeque<QueuedSpawn>,
+}
+
+impl SpawnQueue {
+    pub fn push(&mut self, position: Position, kind: SpawnKind) {
+        self.pending.push_back(QueuedSpawn { position, kind });
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Positions of the trees still waiting to spawn (they aren't in any query yet)
+    pub fn queued_trees(&self) -> impl Iterator<Item = Position> + '_ {
+        self.pending
+            .iter()
+            .filter(|spawn| matches!(spawn.kind, SpawnKind::TreeSpirit { .. } | SpawnKind::MatureTree(_)))
+            .map(|spawn| spawn.position)
+    }
+
+    /// Removes up to `budget` of the oldest spawns
+    pub fn take(&mut self, budget: usize) -> impl Iterator<Item = QueuedSpawn> + '_ {
+        let count = budget.min(self.pending.len());
+        self.pending.drain(..count)
+    }
+}
+
+/// Spawns the oldest queued trees and creatures, at most `MAX_SPAWNS_PER_FRAME` a frame
+pub fn process_spawn_queue(mut commands: Commands, mut queue: ResMut<SpawnQueue>, sprites: Res<SpriteAssets>) {
+    if queue.is_empty() {
+        return;
+    }
+    let _span = info_span!("process_spawn_queue", queued = queue.len()).entered();
+    for spawn in queue.take(MAX_SPAWNS_PER_FRAME) {
+        match spawn.kind {
+            SpawnKind::Player => {
+                spawn_player(&mut commands, spawn.position, &sprites);
+            }
+            SpawnKind::ForestGuardian(variant) => {
+                spawn_forest_guardian(&mut commands, spawn.position, &variant, &sprites);
+            }
+            SpawnKind::Snail => {
+                spawn_snail(&mut commands, spawn.position, &sprites);
+            }
+            SpawnKind::TreeSpirit { variant, growth_time } => {
+                spawn_tree_spirit(&mut commands, spawn.position, variant, growth_time, &sprites);
+            }
+            SpawnKind::MatureTree(variant) => {
+                spawn_mature_tree(&mut commands, spawn.position, variant, &sprites);
+            }
+        }
+    }
+    if !queue.is_empty() {
+        debug!("{} spawns deferred to the next frame", queue.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_queue_budget() {
+        let mut queue = SpawnQueue::default();
+        let tree = SpawnKind::TreeSpirit {
+            variant: TreeVariant::Oak,
+            growth_time: 10.0,
+        };
+        for i in 0..20 {
+            queue.push(Position::new(i as f32, 0.0), tree.clone());
+        }
+        queue.push(Position::new(-1.0, 0.0), SpawnKind::Snail);
+        assert_eq!(queue.queued_trees().count(), 20);
+
+        // Oldest first, never more than the budget
+        let batch: Vec<QueuedSpawn> = queue.take(MAX_SPAWNS_PER_FRAME).collect();
+        assert_eq!(batch.len(), MAX_SPAWNS_PER_FRAME);
+        assert_eq!(batch[0].position, Position::new(0.0, 0.0));
+        assert_eq!(queue.len(), 21 - MAX_SPAWNS_PER_FRAME);
+
+        // The rest drain over the following frames
+        let mut frames = 1;
+        while !queue.is_empty() {
+            let batch: Vec<QueuedSpawn> = queue.take(MAX_SPAWNS_PER_FRAME).collect();
+            assert!(batch.len() <= MAX_SPAWNS_PER_FRAME);
+            frames += 1;
+            if queue.is_empty() {
+                assert_eq!(batch.last().unwrap().kind, SpawnKind::Snail);
+            }
+        }
+        assert_eq!(frames, 3);
+        assert_eq!(queue.take(MAX_SPAWNS_PER_FRAME).count(), 0);
+    }
+}
@@ -1,8 +1,29 @@
-use super::{Direction, EntityBundle, ForestGuardian, Player, Position, Snail, WindingPath, RoamingBehavior, TreeSpirit, GrowingTree, TreeVariant, TreeSpawner};
+use super::{Direction, EntityBundle, ForestGuardian, Player, Position, Settler, Snail, WindingPath, RoamingBehavior, TreeSpirit, GrowingTree, GrowthStage, TreeVariant, TreeSpawner};
+use crate::assets::SpriteAssets;
+use crate::tiles::ChunkPos;
 use bevy::prelude::*;
 
+/// How far a guardian roams from its home (pixels)
+pub const GUARDIAN_ROAM_RADIUS: f32 = 100.0;
+
+/// How fast settlers stroll (pixels per second)
+pub const SETTLER_SPEED: f32 = 12.0;
+
+/// Tiles a tree and a guardian cover (see `WorldManager::check_footprint`)
+pub const TREE_FOOTPRINT: UVec2 = UVec2::splat(2);
+pub const GUARDIAN_FOOTPRINT: UVec2 = UVec2::splat(2);
+
+/// Tiles covered by trees at these positions, queued ones included
+pub fn tree_tiles(positions: impl IntoIterator<Item = Vec2>) -> std::collections::HashSet<IVec2> {
+    positions
+        .into_iter()
+        .flat_map(|position| crate::world::footprint_tiles(position, TREE_FOOTPRINT))
+        .collect()
+}
+
 /// Animation components
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct AnimationIndices {
     pub first: usize,
     pub last: usize,
@@ -14,7 +35,8 @@ impl AnimationIndices {
     }
 }
 
-#[derive(Component, Deref, DerefMut)]
+#[derive(Component, Deref, DerefMut, Reflect)]
+#[reflect(Component)]
 pub struct AnimationTimer(pub Timer);
 
 impl AnimationTimer {
@@ -27,62 +49,58 @@ impl AnimationTimer {
 pub fn spawn_player(
     commands: &mut Commands,
     position: Position,
-    assets: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &SpriteAssets,
 ) -> Entity {
-    let texture = assets.load("characters/human_walk.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-
     commands
         .spawn((
             Player,
-            EntityBundle::new(position.x, position.y, 100.0),
-            Sprite::from_atlas_image(
-                texture,
-                TextureAtlas {
-                    layout: texture_atlas_layout,
-                    index: 0,
-                },
-            ),
-            Transform::from_xyz(position.x, position.y, 1.0),
+            EntityBundle::new(position, 100.0),
+            sprites.entity_sprite("player", 0),
+            Transform::from_xyz(0.0, 0.0, 1.0),
             AnimationIndices::new(0, 3), // First row, 4 frames
             AnimationTimer::from_fps(5.0),
         ))
         .id()
 }
 
+/// Spawns a settlement NPC (with the player's sprite) roaming within `roam_radius` pixels of its home
+pub fn spawn_settler(
+    commands: &mut Commands,
+    position: Position,
+    home_chunk: ChunkPos,
+    roam_radius: f32,
+    sprites: &SpriteAssets,
+) -> Entity {
+    commands
+        .spawn((
+            Settler { home_chunk },
+            EntityBundle::new(position, 100.0),
+            RoamingBehavior::new(position, roam_radius, SETTLER_SPEED),
+            sprites.entity_sprite("player", 0),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+            AnimationIndices::new(0, 3),
+            AnimationTimer::from_fps(5.0),
+        ))
+        .id()
+}
+
 /// Spawns a forest guardian at the given position
 pub fn spawn_forest_guardian(
     commands: &mut Commands,
     position: Position,
     variant: &str, // "oak", "birch", "hickory", "pine", "willow"
-    assets: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &SpriteAssets,
 ) -> Entity {
-    let texture = assets.load(format!(
-        "creatures/forest_guardians/{}_guardian_idle.png",
-        variant
-    ));
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 8, 4, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-
     let tree_variant = TreeVariant::from_str(variant);
 
     commands
         .spawn((
             ForestGuardian::new(tree_variant),
-            EntityBundle::new(position.x, position.y, 150.0),
-            RoamingBehavior::new(position, 100.0, 15.0), // Roam within 100px at 15px/s
+            EntityBundle::new(position, 150.0),
+            RoamingBehavior::new(position, GUARDIAN_ROAM_RADIUS, 15.0), // Roam within 100px at 15px/s
             TreeSpawner::default_guardian(), // Spawn trees periodically
-            Sprite::from_atlas_image(
-                texture,
-                TextureAtlas {
-                    layout: texture_atlas_layout,
-                    index: 0,
-                },
-            ),
-            Transform::from_xyz(position.x, position.y, 1.0),
+            sprites.entity_sprite(&format!("guardian:{}", variant), 0),
+            Transform::from_xyz(0.0, 0.0, 1.0),
             AnimationIndices::new(0, 7),    // First row, 8 frames
             AnimationTimer::from_fps(6.67), // ~0.15s per frame
         ))
@@ -93,26 +111,15 @@ pub fn spawn_forest_guardian(
 pub fn spawn_snail(
     commands: &mut Commands,
     position: Position,
-    assets: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &SpriteAssets,
 ) -> Entity {
-    let texture = assets.load("creatures/snail/snail_crawl.png");
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 4, 4, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-
     commands
         .spawn((
             Snail,
-            EntityBundle::new(position.x, position.y, 500.0),
+            EntityBundle::new(position, 500.0),
             WindingPath::new(2.5), // Very slow winding movement at 2.5 px/s (8x slower)
-            Sprite::from_atlas_image(
-                texture,
-                TextureAtlas {
-                    layout: texture_atlas_layout,
-                    index: 0,
-                },
-            ),
-            Transform::from_xyz(position.x, position.y, 1.0).with_scale(Vec3::splat(4.0)), // 4x bigger
+            sprites.entity_sprite("snail", 0),
+            Transform::from_xyz(0.0, 0.0, 1.0).with_scale(Vec3::splat(4.0)), // 4x bigger
             AnimationIndices::new(0, 3),    // First row, 4 frames
             AnimationTimer::from_fps(2.0), // Slower animation at 2 FPS (~0.5s per frame)
         ))
@@ -125,37 +132,43 @@ pub fn spawn_tree_spirit(
     position: Position,
     variant: TreeVariant,
     growth_time: f32, // Time in seconds for each growth stage
-    assets: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    sprites: &SpriteAssets,
 ) -> Entity {
-    // Start with the idle animation sprite (we'll use this for all growth stages with scaling)
-    let texture = assets.load(format!(
-        "creatures/tree_spirits/{}_spirit_idle.png",
-        variant.as_str()
-    ));
+    spawn_grown_tree(commands, position, GrowingTree::with_growth_time(variant, growth_time), sprites)
+}
 
-    // Tree spirit sprite sheets are 4 rows (directions) with multiple frames per row
-    // We'll use idle animation (assume similar to guardians: multiple frames per direction)
-    let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 8, 4, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
+/// Spawns a tree spirit that is already fully grown
+pub fn spawn_mature_tree(
+    commands: &mut Commands,
+    position: Position,
+    variant: TreeVariant,
+    sprites: &SpriteAssets,
+) -> Entity {
+    let growing_tree = GrowingTree {
+        stage: GrowthStage::MatureTree,
+        ..GrowingTree::new(variant)
+    };
+    spawn_grown_tree(commands, position, growing_tree, sprites)
+}
 
-    let growing_tree = GrowingTree::with_growth_time(variant, growth_time);
-    let initial_scale = growing_tree.stage.scale();
+/// Spawns a tree spirit at any point of its growth (saved trees coming back)
+pub fn spawn_grown_tree(
+    commands: &mut Commands,
+    position: Position,
+    growing_tree: GrowingTree,
+    sprites: &SpriteAssets,
+) -> Entity {
+    let scale = growing_tree.stage.scale();
 
     commands
         .spawn((
             TreeSpirit,
             growing_tree,
-            Position::new(position.x, position.y),
-            Sprite::from_atlas_image(
-                texture,
-                TextureAtlas {
-                    layout: texture_atlas_layout,
-                    index: 0,
-                },
-            ),
-            Transform::from_xyz(position.x, position.y, 1.0)
-                .with_scale(Vec3::splat(initial_scale)),
+            position,
+            // The idle sheet for all growth stages, scaled by stage
+            sprites.entity_sprite(&format!("tree_spirit:{}", growing_tree.variant.as_str()), 0),
+            Transform::from_xyz(0.0, 0.0, 1.0)
+                .with_scale(Vec3::splat(scale)),
             AnimationIndices::new(0, 7),    // First row, 8 frames (assuming same as guardians)
             AnimationTimer::from_fps(4.0), // Slow idle animation
         ))
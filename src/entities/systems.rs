@@ -1,19 +1,55 @@
-use super::spawning::{spawn_tree_spirit, update_animation_for_direction, AnimationTimer};
+use super::spawning::{update_animation_for_direction, AnimationTimer};
 use super::{
-    AnimationIndices, Direction, EntityState, ForestGuardian, GrowingTree, Position,
-    RoamingBehavior, Snail, TreeSpawner, TreeSpirit, TreeVariant, Velocity, WindingPath,
+    AnimationIndices, BackgroundSimulated, Culled, Direction, FullSnailFilter, FullTreeFilter, EntityState, ForestGuardian, GrowingTree,
+    GuardianDirective, GuardianLore, Position, RoamingBehavior, SpawnKind, SpawnQueue, TreeSpawner, TreeSpirit, TreeVariant, Velocity, WindingPath,
+    GUARDIAN_TREE_MILESTONES, TREE_FOOTPRINT, tree_tiles,
 };
-use crate::tiles::TILE_DIRT;
-use crate::world::WorldManager;
+use crate::audio::{Sound, SoundEffect};
+use crate::chronicle::ChronicleEvent;
+use crate::config::GameConfig;
+use crate::environment::{
+    is_near_water, preferred_variant, soil_viability, temperature_at, tree_viability, CurrentMoon, CurrentSeason, WorldWeather,
+};
+use crate::stats::StatEvent;
+use crate::tiles::{TileRegistry, TILE_DIRT};
+use crate::world::{TileEdits, WorldManager, WorldOrigin};
 use bevy::prelude::*;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hash};
+
+/// Random draws for one entity in one run of a system, the same from run to run: a fixed-key
+/// hash of the entity, the system's tick and the entity's own state, stretched with splitmix64.
+/// Nothing depends on the process, the clock or the order entities are visited in
+struct EntityRng(u64);
+
+impl EntityRng {
+    fn new(entity: Entity, tick: u64, state: impl Hash) -> Self {
+        Self(BuildHasherDefault::<DefaultHasher>::default().hash_one((entity, tick, state)))
+    }
+
+    /// The next draw, in [0, 1)
+    fn draw(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
 
-/// Syncs entity Position component with Transform for rendering
+/// Syncs entity Position component with Transform for rendering (relative to the world origin);
+/// every entity when the origin moves
 pub fn sync_position_with_transform(
-    mut query: Query<(&Position, &mut Transform), Changed<Position>>,
+    origin: Res<WorldOrigin>,
+    mut query: Query<(Ref<Position>, &mut Transform)>,
 ) {
     for (position, mut transform) in &mut query {
-        transform.translation.x = position.x;
-        transform.translation.y = position.y;
+        if !position.is_changed() && !origin.is_changed() {
+            continue;
+        }
+        let translation = position.render_translation(origin.offset());
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
     }
 }
 
@@ -21,8 +57,7 @@ pub fn sync_position_with_transform(
 pub fn apply_velocity(time: Res<Time>, mut query: Query<(&mut Position, &Velocity)>) {
     let delta = time.delta_secs();
     for (mut position, velocity) in &mut query {
-        position.x += velocity.x * delta;
-        position.y += velocity.y * delta;
+        position.translate(Vec2::new(velocity.x, velocity.y) * delta);
     }
 }
 
@@ -72,12 +107,12 @@ pub fn update_animation_from_direction(
     }
 }
 
-/// Animates sprites by cycling through animation frames
+/// Animates sprites by cycling through animation frames (culled entities hold their frame), in parallel
 pub fn animate_sprite(
     time: Res<Time>,
-    mut query: Query<(&AnimationIndices, &mut AnimationTimer, &mut Sprite)>,
+    mut query: Query<(&AnimationIndices, &mut AnimationTimer, &mut Sprite), Without<Culled>>,
 ) {
-    for (indices, mut timer, mut sprite) in &mut query {
+    query.par_iter_mut().for_each(|(indices, mut timer, mut sprite)| {
         timer.tick(time.delta());
         if timer.just_finished() {
             if let Some(atlas) = &mut sprite.texture_atlas {
@@ -88,59 +123,49 @@ pub fn animate_sprite(
                 };
             }
         }
-    }
+    });
 }
 
 /// Updates velocity for entities with roaming behavior
 /// This makes entities roam randomly within a fixed radius of their home position
+/// Creatures move and wander off again more slowly in fog
+/// Runs in parallel: each entity's random draws come from its own `EntityRng`
 pub fn update_roaming_behavior(
     time: Res<Time>,
-    mut query: Query<(&Position, &mut Velocity, &mut RoamingBehavior)>,
+    weather: Res<WorldWeather>,
+    mut tick: Local<u64>,
+    mut query: Query<(Entity, &Position, &mut Velocity, &mut RoamingBehavior), Without<BackgroundSimulated>>,
 ) {
-    use std::collections::hash_map::RandomState;
     use std::f32::consts::PI;
-    use std::hash::{BuildHasher, Hash, Hasher};
-    let delta = time.delta_secs();
+    let activity = weather.weather.activity_multiplier();
+    let delta = time.delta_secs() * activity;
+    *tick += 1;
+    let tick = *tick;
 
-    for (position, mut velocity, mut roaming) in &mut query {
+    query.par_iter_mut().for_each(|(entity, position, mut velocity, mut roaming)| {
         // If we're paused, count down the pause timer
         if roaming.pause_timer > 0.0 {
             roaming.pause_timer -= delta;
             velocity.x = 0.0;
             velocity.y = 0.0;
-            continue;
+            return;
         }
 
         // Check if we've reached the target (within 5 pixels)
         if roaming.is_at_target(position, 5.0) {
-            // Generate random numbers for next target
-            let hasher_builder = RandomState::new();
-            let mut hasher = hasher_builder.build_hasher();
-            position.x.to_bits().hash(&mut hasher);
-            position.y.to_bits().hash(&mut hasher);
-            std::time::SystemTime::now().hash(&mut hasher);
-            let hash = hasher.finish();
-
-            // Random angle
-            let rand_angle = ((hash as f32) / (u64::MAX as f32)) * 2.0 * PI;
-
-            // Random distance within roam radius
-            let mut hasher2 = hasher_builder.build_hasher();
-            (hash.wrapping_add(1)).hash(&mut hasher2);
-            let hash2 = hasher2.finish();
-            let rand_distance = ((hash2 as f32) / (u64::MAX as f32)) * roaming.roam_radius;
+            let mut rng = EntityRng::new(entity, tick, position);
+
+            // Random angle, and distance within roam radius
+            let rand_angle = rng.draw() * 2.0 * PI;
+            let rand_distance = rng.draw() * roaming.roam_radius;
 
             // Calculate new target position within bounds
             let offset_x = rand_angle.cos() * rand_distance;
             let offset_y = rand_angle.sin() * rand_distance;
-            roaming.target.x = roaming.home.x + offset_x;
-            roaming.target.y = roaming.home.y + offset_y;
-
-            // Generate random pause duration
-            let mut hasher3 = hasher_builder.build_hasher();
-            (hash2.wrapping_add(1)).hash(&mut hasher3);
-            let hash3 = hasher3.finish();
-            let rand_pause = (hash3 as f32) / (u64::MAX as f32);
+            roaming.target = roaming.home.offset(Vec2::new(offset_x, offset_y));
+
+            // Random pause duration
+            let rand_pause = rng.draw();
             roaming.pause_duration = roaming.min_pause_duration
                 + rand_pause * (roaming.max_pause_duration - roaming.min_pause_duration);
             roaming.pause_timer = roaming.pause_duration;
@@ -148,12 +173,11 @@ pub fn update_roaming_behavior(
             // Stop moving while paused
             velocity.x = 0.0;
             velocity.y = 0.0;
-            continue;
+            return;
         }
 
         // Calculate direction to target
-        let dx = roaming.target.x - position.x;
-        let dy = roaming.target.y - position.y;
+        let Vec2 { x: dx, y: dy } = position.delta_to(&roaming.target);
         let distance = (dx * dx + dy * dy).sqrt();
 
         // If we're very close, just stop (handled above on next frame)
@@ -164,35 +188,38 @@ pub fn update_roaming_behavior(
             // Move towards target at roaming speed
             let dir_x = dx / distance;
             let dir_y = dy / distance;
-            velocity.x = dir_x * roaming.speed;
-            velocity.y = dir_y * roaming.speed;
+            velocity.x = dir_x * roaming.speed * activity;
+            velocity.y = dir_y * roaming.speed * activity;
         }
-    }
+    });
 }
 
 /// Updates velocity for entities with winding path behavior
 /// This creates smooth, meandering movement with long straight sections
-pub fn update_winding_path(time: Res<Time>, mut query: Query<(&mut Velocity, &mut WindingPath)>) {
-    use std::collections::hash_map::RandomState;
+/// Runs in parallel: each entity's random draws come from its own `EntityRng`
+pub fn update_winding_path(
+    time: Res<Time>,
+    weather: Res<WorldWeather>,
+    moon: Res<CurrentMoon>,
+    mut tick: Local<u64>,
+    mut query: Query<(Entity, &mut Velocity, &mut WindingPath), Without<BackgroundSimulated>>,
+) {
     use std::f32::consts::PI;
-    use std::hash::{BuildHasher, Hash, Hasher};
     let delta = time.delta_secs();
+    *tick += 1;
+    let tick = *tick;
 
-    for (mut velocity, mut path) in &mut query {
+    query.par_iter_mut().for_each(|(entity, mut velocity, mut path)| {
         // Calculate distance moved this frame
-        let speed = path.speed;
+        // Winding creatures (snails) are nocturnal: livelier on moonlit nights
+        let speed = path.speed * weather.weather.activity_multiplier() * moon.nocturnal_activity();
         let distance_this_frame = speed * delta;
         path.distance_traveled += distance_this_frame;
 
         // Check if we've reached the end of current segment
         if path.distance_traveled >= path.segment_length {
-            // Generate random numbers using hash
-            let hasher_builder = RandomState::new();
-            let mut hasher = hasher_builder.build_hasher();
-            (path.current_angle.to_bits() as u64).hash(&mut hasher);
-            path.distance_traveled.to_bits().hash(&mut hasher);
-            let hash = hasher.finish();
-            let rand1 = ((hash as f32) / (u64::MAX as f32)) - 0.5;
+            let mut rng = EntityRng::new(entity, tick, (path.current_angle.to_bits(), path.distance_traveled.to_bits()));
+            let rand1 = rng.draw() - 0.5;
 
             // Pick a new target direction with constrained angle change
             let angle_change = rand1 * 2.0 * path.max_angle_change;
@@ -201,11 +228,7 @@ pub fn update_winding_path(time: Res<Time>, mut query: Query<(&mut Velocity, &mu
             // Normalize target angle to [0, 2π]
             path.target_angle = path.target_angle.rem_euclid(2.0 * PI);
 
-            // Generate another random number for segment length
-            let mut hasher2 = hasher_builder.build_hasher();
-            (hash.wrapping_add(1)).hash(&mut hasher2);
-            let hash2 = hasher2.finish();
-            let rand2 = (hash2 as f32) / (u64::MAX as f32);
+            let rand2 = rng.draw();
 
             // Pick a new segment length
             path.segment_length = path.min_segment_length
@@ -238,30 +261,23 @@ pub fn update_winding_path(time: Res<Time>, mut query: Query<(&mut Velocity, &mu
         // Update velocity based on current angle
         velocity.x = path.current_angle.cos() * speed;
         velocity.y = path.current_angle.sin() * speed;
-    }
+    });
 }
 
-/// Makes snails turn tiles they walk over into dirt with a 20% chance
+/// Makes snails turn tiles they walk over into dirt
+/// (chance per movement update from `GameConfig.gameplay.snail_dirt_chance`)
 pub fn snail_dirt_trail(
-    mut world: ResMut<WorldManager>,
-    snail_query: Query<&Position, (With<Snail>, Changed<Position>)>,
+    mut tile_edits: TileEdits,
+    config: Res<GameConfig>,
+    mut tick: Local<u64>,
+    snail_query: Query<(Entity, &Position), FullSnailFilter>,
 ) {
-    use crate::tiles::LAYER_GROUND;
-    use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
-
-    for position in snail_query.iter() {
-        // Generate a random number using hash of position and time
-        let hasher_builder = RandomState::new();
-        let mut hasher = hasher_builder.build_hasher();
-        position.x.to_bits().hash(&mut hasher);
-        position.y.to_bits().hash(&mut hasher);
-        std::time::SystemTime::now().hash(&mut hasher);
-        let hash = hasher.finish();
-        let rand_val = (hash as f32) / (u64::MAX as f32);
-
-        if rand_val < 0.2 {
-            world.queue_tile_modification(position.x, position.y, TILE_DIRT, LAYER_GROUND);
+    *tick += 1;
+    for (entity, position) in snail_query.iter() {
+        let rand_val = EntityRng::new(entity, *tick, position).draw();
+
+        if rand_val < config.gameplay.snail_dirt_chance {
+            tile_edits.set_ground(position.vec2(), TILE_DIRT);
         }
     }
 }
@@ -269,153 +285,169 @@ pub fn snail_dirt_trail(
 /// Advances tree growth through stages over time
 pub fn update_tree_growth(
     time: Res<Time>,
-    mut tree_query: Query<(&mut GrowingTree, &mut Transform), With<TreeSpirit>>,
+    season: Res<CurrentSeason>,
+    weather: Res<WorldWeather>,
+    mut tree_query: Query<(&mut GrowingTree, &mut Transform, &Position), FullTreeFilter>,
+    mut stat_events: MessageWriter<StatEvent>,
+    mut sounds: MessageWriter<SoundEffect>,
 ) {
-    let delta = time.delta_secs();
-
-    for (mut growing_tree, mut transform) in tree_query.iter_mut() {
-        // Skip if already mature
-        if growing_tree.is_mature() {
-            continue;
-        }
+    let delta = time.delta_secs() * season.0.growth_multiplier() * weather.weather.growth_multiplier();
 
-        // Accumulate time in current stage
-        growing_tree.time_in_stage += delta;
+    for (mut growing_tree, mut transform, position) in tree_query.iter_mut() {
+        if let Some(next_stage) = growing_tree.grow(delta) {
+            // Update scale based on new stage
+            let new_scale = next_stage.scale();
+            transform.scale = Vec3::splat(new_scale);
 
-        // Check if ready to advance to next stage
-        if growing_tree.time_in_stage >= growing_tree.time_to_next_stage {
-            if let Some(next_stage) = growing_tree.stage.next() {
-                // Advance to next stage
-                growing_tree.stage = next_stage;
-                growing_tree.time_in_stage = 0.0;
-
-                // Update scale based on new stage
-                let new_scale = next_stage.scale();
-                transform.scale = Vec3::splat(new_scale);
+            info!(
+                "Tree advanced to stage {:?} with scale {:.1}",
+                next_stage, new_scale
+            );
+            sounds.write(SoundEffect::at(Sound::TreeGrowth, position.vec2()));
 
-                info!(
-                    "Tree advanced to stage {:?} with scale {:.1}",
-                    next_stage, new_scale
-                );
+            if growing_tree.is_mature() {
+                stat_events.write(StatEvent::TreeGrown);
             }
         }
     }
 }
 
-/// Spawns trees around entities with TreeSpawner component
+/// Spawns trees around entities with TreeSpawner component (inside the region of a reforest directive)
+/// Named guardians reaching one of `GUARDIAN_TREE_MILESTONES` planted trees get a chronicle entry
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn update_tree_spawning(
     time: Res<Time>,
-    mut commands: Commands,
-    assets: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    mut spawner_query: Query<(&Position, &mut TreeSpawner, Option<&ForestGuardian>)>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    season: Res<CurrentSeason>,
+    mut world: ResMut<WorldManager>,
+    registry: Res<TileRegistry>,
+    mut spawner_query: Query<(Entity, &Position, &mut TreeSpawner, Option<&ForestGuardian>, Option<&mut GuardianLore>, Option<&GuardianDirective>)>,
+    trees: Query<&Position, (With<TreeSpirit>, Without<TreeSpawner>)>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+    mut tick: Local<u64>,
 ) {
-    use std::collections::hash_map::RandomState;
     use std::f32::consts::PI;
-    use std::hash::{BuildHasher, Hash, Hasher};
 
     let delta = time.delta_secs();
+    *tick += 1;
 
-    for (position, mut spawner, guardian) in spawner_query.iter_mut() {
+    for (entity, position, mut spawner, guardian, lore, directive) in spawner_query.iter_mut() {
         // Count down spawn timer
         spawner.spawn_timer -= delta;
 
         // Check if it's time to spawn a tree
         if spawner.spawn_timer <= 0.0 {
-            // Generate random values using hash
-            let hasher_builder = RandomState::new();
-            let mut hasher = hasher_builder.build_hasher();
-            position.x.to_bits().hash(&mut hasher);
-            position.y.to_bits().hash(&mut hasher);
-            std::time::SystemTime::now().hash(&mut hasher);
-            let hash = hasher.finish();
-
-            // Random angle for tree placement
-            let rand_angle = ((hash as f32) / (u64::MAX as f32)) * 2.0 * PI;
-
-            // Random distance within spawn radius
-            let mut hasher2 = hasher_builder.build_hasher();
-            (hash.wrapping_add(1)).hash(&mut hasher2);
-            let hash2 = hasher2.finish();
-            let rand_distance = ((hash2 as f32) / (u64::MAX as f32)) * spawner.spawn_radius;
+            let mut rng = EntityRng::new(entity, *tick, position);
+
+            // Random angle, and distance within spawn radius
+            let rand_angle = rng.draw() * 2.0 * PI;
+            let rand_distance = rng.draw() * spawner.spawn_radius;
 
             // Calculate spawn position
-            let spawn_x = position.x + rand_angle.cos() * rand_distance;
-            let spawn_y = position.y + rand_angle.sin() * rand_distance;
+            let mut spawn_position = position.offset(Vec2::from_angle(rand_angle) * rand_distance);
+            if let Some(directive) = directive {
+                spawn_position = Position::from_vec2(directive.planting_spot(spawn_position.vec2()));
+            }
+            let (spawn_x, spawn_y) = (spawn_position.x(), spawn_position.y());
+
+            // Pines are favoured in cold and rocky land, willows by water and in marshes
+            let preferred = preferred_variant(
+                world.biome_at(spawn_position.vec2()),
+                temperature_at(spawn_position.vec2(), season.0),
+                is_near_water(&world, spawn_position.vec2()),
+            );
 
             // Determine tree variant based on guardian variant (if present)
             let tree_variant = if let Some(guardian) = guardian {
-                // Generate random value for variant selection
-                let mut hasher3 = hasher_builder.build_hasher();
-                (hash2.wrapping_add(1)).hash(&mut hasher3);
-                let hash3 = hasher3.finish();
-                let rand_variant = (hash3 as f32) / (u64::MAX as f32);
+                let rand_variant = rng.draw();
 
-                if rand_variant < 0.95 {
+                if let Some(preferred) = preferred.filter(|_| (0.75..0.95).contains(&rand_variant)) {
+                    // 20% chance where the land favours a variant: spawn that one
+                    preferred
+                } else if rand_variant < 0.95 {
                     // 95% chance: spawn matching variant
                     guardian.variant
                 } else {
                     // 5% chance: spawn different variant
-                    let mut hasher4 = hasher_builder.build_hasher();
-                    (hash3.wrapping_add(1)).hash(&mut hasher4);
-                    let hash4 = hasher4.finish();
-                    let rand_other = (hash4 as f32) / (u64::MAX as f32);
-                    guardian.variant.random_other(rand_other)
+                    guardian.variant.random_other(rng.draw())
                 }
             } else {
-                // No guardian component, pick fully random variant
-                let mut hasher3 = hasher_builder.build_hasher();
-                (hash2.wrapping_add(1)).hash(&mut hasher3);
-                let hash3 = hasher3.finish();
-                let variant_index = (hash3 % 5) as usize;
-                match variant_index {
-                    0 => TreeVariant::Oak,
-                    1 => TreeVariant::Birch,
-                    2 => TreeVariant::Hickory,
-                    3 => TreeVariant::Pine,
-                    _ => TreeVariant::Willow,
+                // No guardian component: half the time the variant the land favours (if any),
+                // otherwise a fully random one
+                if let Some(preferred) = preferred.filter(|_| rng.draw() < 0.5) {
+                    preferred
+                } else {
+                    let variant_index = (rng.draw() * 5.0) as usize;
+                    match variant_index {
+                        0 => TreeVariant::Oak,
+                        1 => TreeVariant::Birch,
+                        2 => TreeVariant::Hickory,
+                        3 => TreeVariant::Pine,
+                        _ => TreeVariant::Willow,
+                    }
                 }
             };
 
-            // Spawn the tree
-            spawn_tree_spirit(
-                &mut commands,
-                Position::new(spawn_x, spawn_y),
-                tree_variant,
-                spawner.tree_growth_time,
-                &assets,
-                &mut texture_atlas_layouts,
-            );
-
-            if let Some(guardian) = guardian {
-                let is_matching = tree_variant == guardian.variant;
-                info!(
-                    "{:?} guardian spawned {:?} tree at ({:.1}, {:.1}) {}",
-                    guardian.variant,
-                    tree_variant,
-                    spawn_x,
-                    spawn_y,
-                    if is_matching {
-                        "(matching)"
-                    } else {
-                        "(different!)"
-                    }
-                );
+            // The whole tree must stand on free, walkable ground, even across a chunk border
+            // (reading summaries of uncached chunks isn't a change to the world)
+            let occupied = tree_tiles(trees.iter().map(Position::vec2).chain(spawn_queue.queued_trees().map(|queued| queued.vec2())));
+            let footprint = world.bypass_change_detection().check_footprint(&registry, spawn_position.vec2(), TREE_FOOTPRINT, |tile| {
+                occupied.contains(&tile)
+            });
+            // The chosen variant may not grow here (too warm for pines, no water for willows), and no
+            // tree takes root on mostly bare ground
+            if let Err(blocked) = footprint {
+                debug!("No room for a tree at ({:.1}, {:.1}): {}", spawn_x, spawn_y, blocked);
+            } else if let Err(limit) = soil_viability(world.bypass_change_detection(), spawn_position.vec2()) {
+                debug!("No tree takes root at ({:.1}, {:.1}): {}", spawn_x, spawn_y, limit);
+            } else if let Err(limit) = tree_viability(&world, season.0, tree_variant, spawn_position.vec2()) {
+                debug!("{:?} tree can't grow at ({:.1}, {:.1}): {}", tree_variant, spawn_x, spawn_y, limit);
             } else {
-                info!(
-                    "Entity spawned {:?} tree at ({:.1}, {:.1})",
-                    tree_variant, spawn_x, spawn_y
-                );
+                // Queue the tree (spawned within the per-frame budget)
+                spawn_queue.push(spawn_position, SpawnKind::TreeSpirit {
+                    variant: tree_variant,
+                    growth_time: spawner.tree_growth_time,
+                });
+
+                if let Some(mut lore) = lore {
+                    lore.trees_planted += 1;
+                    if GUARDIAN_TREE_MILESTONES.contains(&lore.trees_planted) {
+                        chronicle.write(ChronicleEvent::milestone(
+                            format!("guardian_{:016x}_trees_{}", lore.seed, lore.trees_planted),
+                            format!("{} has planted {} trees", lore.full_name(), lore.trees_planted),
+                        ));
+                    }
+                }
+
+                if let Some(guardian) = guardian {
+                    let is_matching = tree_variant == guardian.variant;
+                    info!(
+                        "{:?} guardian spawned {:?} tree at ({:.1}, {:.1}) {}",
+                        guardian.variant,
+                        tree_variant,
+                        spawn_x,
+                        spawn_y,
+                        if is_matching {
+                            "(matching)"
+                        } else {
+                            "(different!)"
+                        }
+                    );
+                } else {
+                    info!(
+                        "Entity spawned {:?} tree at ({:.1}, {:.1})",
+                        tree_variant, spawn_x, spawn_y
+                    );
+                }
             }
 
             // Reset spawn timer with random interval
-            let mut hasher_interval = hasher_builder.build_hasher();
-            position.x.to_bits().hash(&mut hasher_interval);
-            std::time::SystemTime::now().hash(&mut hasher_interval);
-            let hash_interval = hasher_interval.finish();
-            let rand_interval = (hash_interval as f32) / (u64::MAX as f32);
-            spawner.spawn_timer = spawner.min_spawn_interval
-                + rand_interval * (spawner.max_spawn_interval - spawner.min_spawn_interval);
+            let rand_interval = rng.draw();
+            // Guardians plant faster in forests and groves, slower on rocky ground and in clearings
+            let density = world.tree_density(position.vec2());
+            spawner.spawn_timer = (spawner.min_spawn_interval
+                + rand_interval * (spawner.max_spawn_interval - spawner.min_spawn_interval))
+                / density;
         }
     }
 }
@@ -426,7 +458,59 @@ pub fn debug_entities(query: Query<(Entity, &Position, &Velocity, &Direction, &E
     for (entity, position, velocity, direction, state) in &query {
         info!(
             "Entity {:?}: pos=({:.1}, {:.1}), vel=({:.1}, {:.1}), dir={:?}, state={:?}",
-            entity, position.x, position.y, velocity.x, velocity.y, direction, state
+            entity, position.x(), position.y(), velocity.x, velocity.y, direction, state
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::tasks::{ComputeTaskPool, TaskPool};
+
+    /// A world with what the movement systems read, and a few roaming and winding creatures
+    fn movement_world() -> (World, Vec<Entity>) {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut world = World::new();
+        world.insert_resource(Time::<()>::default());
+        world.insert_resource(WorldWeather::default());
+        world.insert_resource(CurrentMoon::default());
+        let entities = (0..8)
+            .map(|i| {
+                let home = Position::new(i as f32 * 100.0, -40.0);
+                let mut path = WindingPath::new(20.0);
+                path.current_angle = 1.0;
+                path.segment_length = 0.0;
+                world.spawn((home, Velocity::new(0.0, 0.0), RoamingBehavior::new(home, 64.0, 20.0), path)).id()
+            })
+            .collect();
+        (world, entities)
+    }
+
+    fn movement_outcome() -> Vec<(Position, f32, f32, f32)> {
+        let (mut world, entities) = movement_world();
+        world.run_system_once(update_roaming_behavior).unwrap();
+        world.run_system_once(update_winding_path).unwrap();
+        entities
+            .iter()
+            .map(|entity| {
+                let roaming = world.get::<RoamingBehavior>(*entity).unwrap();
+                let path = world.get::<WindingPath>(*entity).unwrap();
+                (roaming.target, roaming.pause_duration, path.target_angle, path.segment_length)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_draws_are_reproducible() {
+        let first = movement_outcome();
+        assert_eq!(first, movement_outcome());
+
+        // Draws were made, and differ between creatures
+        let (home, target) = (Position::new(0.0, -40.0), first[0].0);
+        assert!(target != home && first[1].0 != Position::new(100.0, -40.0));
+        assert!(first.iter().all(|(_, pause, _, length)| (1.0..=4.0).contains(pause) && (150.0..=400.0).contains(length)));
+        assert!(first[0].1 != first[1].1);
+    }
+}
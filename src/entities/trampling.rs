@@ -0,0 +1,187 @@
+use super::{BackgroundSimulated, ForestGuardian, Player, Position, Snail};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::environment::{ground_tile, tile_at, tile_center};
+use crate::tiles::{TILE_DIRT, TILE_GRASS};
+use crate::world::{MetadataSync, TileEdits, WorldManager, WorldMetadata, WorldOrigin};
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// Crossings after which a grass tile wears down to dirt
+pub const WEAR_THRESHOLD: u32 = 24;
+
+/// Counts stop growing here, so a busy path recovers within a few hours of falling out of use
+const MAX_TRAFFIC: u32 = WEAR_THRESHOLD * 2;
+
+/// Seconds between each counter losing one crossing
+const TRAFFIC_DECAY_INTERVAL: f32 = 60.0;
+
+/// Upper bound on tracked tiles; new tiles are ignored beyond it
+const MAX_TRACKED_TILES: usize = 50_000;
+
+/// How often live traffic is copied into world metadata (seconds)
+const TRAFFIC_SYNC_INTERVAL: f32 = 30.0;
+
+/// Entities whose footsteps count as traffic
+pub type TrafficFilter = (
+    Or<(With<Player>, With<Snail>, With<ForestGuardian>)>,
+    Changed<Position>,
+    Without<BackgroundSimulated>,
+);
+
+/// How often each tile has been crossed recently (tile coordinates -> crossings)
+/// Tiles crossed `WEAR_THRESHOLD` times wear to dirt and don't regrow while they stay busy,
+/// so desire paths form wherever creatures keep taking the same route
+/// Copied into `WorldMetadata.traffic` periodically
+#[derive(Resource, Default, Debug)]
+pub struct TileTraffic {
+    pub counts: HashMap<IVec2, u32>,
+}
+
+impl TileTraffic {
+    /// Count one crossing of `tile`; returns true if the tile is (now) worn
+    pub fn record(&mut self, tile: IVec2) -> bool {
+        if self.counts.len() >= MAX_TRACKED_TILES && !self.counts.contains_key(&tile) {
+            return false;
+        }
+        let count = self.counts.entry(tile).or_default();
+        *count = (*count + 1).min(MAX_TRAFFIC);
+        *count >= WEAR_THRESHOLD
+    }
+
+    /// Whether a tile sees enough traffic to stay bare
+    pub fn is_worn(&self, tile: IVec2) -> bool {
+        self.counts.get(&tile).is_some_and(|count| *count >= WEAR_THRESHOLD)
+    }
+
+    /// Every counter loses one crossing; tiles nobody uses are forgotten
+    pub fn decay(&mut self) {
+        self.counts.retain(|_, count| {
+            *count -= 1;
+            *count > 0
+        });
+    }
+
+    pub fn to_metadata(&self) -> BTreeMap<(i32, i32), u32> {
+        self.counts.iter().map(|(tile, count)| ((tile.x, tile.y), *count)).collect()
+    }
+
+    pub fn from_metadata(traffic: &BTreeMap<(i32, i32), u32>) -> Self {
+        Self {
+            counts: traffic.iter().map(|(&(x, y), count)| (IVec2::new(x, y), *count)).collect(),
+        }
+    }
+}
+
+/// Count a crossing of `tile` and wear it to dirt once it's worn (only grass wears)
+pub fn trample(traffic: &mut TileTraffic, world: &WorldManager, tile_edits: &mut TileEdits, tile: IVec2) {
+    if traffic.record(tile) && ground_tile(world, tile) == Some(TILE_GRASS) {
+        tile_edits.set_ground(tile_center(tile), TILE_DIRT);
+    }
+}
+
+/// Picks up the traffic saved with the world
+pub fn restore_traffic(metadata: Res<WorldMetadata>, mut traffic: ResMut<TileTraffic>) {
+    *traffic = TileTraffic::from_metadata(&metadata.traffic);
+}
+
+/// Counts a crossing each time a walking entity (player, snail, guardian) steps onto a new tile
+/// Background creatures are counted by `simulate_background_ecosystem` as they jump
+pub fn record_traffic(
+    mut last_tiles: Local<HashMap<Entity, IVec2>>,
+    mut removed: RemovedComponents<Position>,
+    walkers: Query<(Entity, &Position), TrafficFilter>,
+    mut traffic: ResMut<TileTraffic>,
+    world: Res<WorldManager>,
+    mut tile_edits: TileEdits,
+) {
+    for entity in removed.read() {
+        last_tiles.remove(&entity);
+    }
+
+    for (entity, position) in walkers.iter() {
+        let tile = tile_at(position.vec2());
+        if last_tiles.insert(entity, tile) != Some(tile) {
+            trample(&mut traffic, &world, &mut tile_edits, tile);
+        }
+    }
+}
+
+/// Lets unused paths recover, one crossing per `TRAFFIC_DECAY_INTERVAL`
+pub fn decay_traffic(time: Res<Time>, mut elapsed: Local<f32>, mut traffic: ResMut<TileTraffic>) {
+    *elapsed += time.delta_secs();
+    if *elapsed < TRAFFIC_DECAY_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+    traffic.decay();
+}
+
+/// Copies live traffic into world metadata periodically and when the window closes
+pub fn sync_traffic(mut sync: MetadataSync, traffic: Res<TileTraffic>) {
+    if sync.due(TRAFFIC_SYNC_INTERVAL) {
+        sync.update(|metadata| &mut metadata.traffic, traffic.to_metadata());
+    }
+}
+
+/// Console command: `traffic` shows how worn the tile under the camera is
+pub fn handle_traffic_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    camera: Single<&Transform, With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    traffic: Res<TileTraffic>,
+) {
+    for command in console_commands.read() {
+        if command.name != "traffic" {
+            continue;
+        }
+
+        let tile = tile_at(origin.to_world_vec2(camera.translation.truncate()));
+        let count = traffic.counts.get(&tile).copied().unwrap_or(0);
+        let worn = traffic.counts.values().filter(|count| **count >= WEAR_THRESHOLD).count();
+        console.print(format!(
+            "Tile ({}, {}): {}/{} crossings{}. {} tiles tracked, {} worn to paths",
+            tile.x,
+            tile.y,
+            count,
+            WEAR_THRESHOLD,
+            if traffic.is_worn(tile) { " (path)" } else { "" },
+            traffic.counts.len(),
+            worn
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_traffic_wears_and_recovers() {
+        let mut traffic = TileTraffic::default();
+        let tile = IVec2::new(-4, 7);
+        for _ in 1..WEAR_THRESHOLD {
+            assert!(!traffic.record(tile));
+        }
+        assert!(traffic.record(tile));
+        assert!(traffic.is_worn(tile));
+
+        // Counts are capped, then decay until the tile is forgotten
+        for _ in 0..MAX_TRAFFIC {
+            traffic.record(tile);
+        }
+        assert_eq!(traffic.counts[&tile], MAX_TRAFFIC);
+        for _ in 0..=MAX_TRAFFIC - WEAR_THRESHOLD {
+            traffic.decay();
+        }
+        assert!(!traffic.is_worn(tile));
+        for _ in 0..WEAR_THRESHOLD {
+            traffic.decay();
+        }
+        assert!(traffic.counts.is_empty());
+
+        traffic.record(tile);
+        let restored = TileTraffic::from_metadata(&traffic.to_metadata());
+        assert_eq!(restored.counts, traffic.counts);
+    }
+}
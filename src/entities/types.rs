@@ -1,30 +1,94 @@
 use bevy::prelude::*;
+use bevy::math::DVec2;
+use crate::tiles::ChunkPos;
+
+/// Fixed-point steps per pixel in `Position`
+pub const POSITION_UNITS_PER_PIXEL: f64 = 256.0;
 
 /// World position component - tracks entity position in world space (pixels)
-#[derive(Component, Debug, Clone, Copy)]
+/// Stored as i64 fixed point (1/256 pixel) so movement and distances stay exact far from the
+/// origin; rendering uses `render_translation` relative to the `WorldOrigin`
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+#[reflect(Component)]
 pub struct Position {
-    pub x: f32,
-    pub y: f32,
+    x: i64,
+    y: i64,
 }
 
 impl Position {
     pub fn new(x: f32, y: f32) -> Self {
-        Self { x, y }
+        Self::from_dvec2(DVec2::new(x as f64, y as f64))
+    }
+
+    pub fn from_vec2(position: Vec2) -> Self {
+        Self::new(position.x, position.y)
+    }
+
+    /// From world pixels in double precision (e.g. `WorldOrigin::to_world`)
+    pub fn from_dvec2(position: DVec2) -> Self {
+        let fixed = |pixels: f64| (pixels * POSITION_UNITS_PER_PIXEL).round() as i64;
+        Self {
+            x: fixed(position.x),
+            y: fixed(position.y),
+        }
     }
 
     pub fn zero() -> Self {
-        Self { x: 0.0, y: 0.0 }
+        Self::default()
+    }
+
+    pub fn x(&self) -> f32 {
+        self.dvec2().x as f32
+    }
+
+    pub fn y(&self) -> f32 {
+        self.dvec2().y as f32
+    }
+
+    /// World pixels; sub-pixel precision is lost millions of pixels from the origin, which is fine
+    /// for tile lookups
+    pub fn vec2(&self) -> Vec2 {
+        self.dvec2().as_vec2()
+    }
+
+    pub fn dvec2(&self) -> DVec2 {
+        DVec2::new(self.x as f64, self.y as f64) / POSITION_UNITS_PER_PIXEL
+    }
+
+    /// Moves by `delta` pixels
+    pub fn translate(&mut self, delta: Vec2) {
+        *self = self.offset(delta);
+    }
+
+    /// This position moved by `delta` pixels
+    pub fn offset(&self, delta: Vec2) -> Self {
+        let step = |pixels: f32| (pixels as f64 * POSITION_UNITS_PER_PIXEL).round() as i64;
+        Self {
+            x: self.x.saturating_add(step(delta.x)),
+            y: self.y.saturating_add(step(delta.y)),
+        }
+    }
+
+    /// Pixels from this position to `other`, exact wherever the two are
+    pub fn delta_to(&self, other: &Position) -> Vec2 {
+        let axis = |from: i64, to: i64| (to.saturating_sub(from) as f64 / POSITION_UNITS_PER_PIXEL) as f32;
+        Vec2::new(axis(self.x, other.x), axis(self.y, other.y))
     }
 
     pub fn distance_to(&self, other: &Position) -> f32 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        (dx * dx + dy * dy).sqrt()
+        self.delta_to(other).length()
+    }
+
+    /// Where to draw an entity at this position when the world is rendered around `origin` (in
+    /// world pixels, a whole number of chunks)
+    pub fn render_translation(&self, origin: DVec2) -> Vec2 {
+        Position::from_dvec2(origin).delta_to(self)
     }
 }
 
 /// Velocity component - movement speed in pixels per second
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -45,7 +109,8 @@ impl Velocity {
 }
 
 /// Direction the entity is facing (for animation purposes)
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
 pub enum Direction {
     NorthEast = 0, // Row 0 in sprite sheets (up-right)
     NorthWest = 1, // Row 1 in sprite sheets (up-left)
@@ -86,7 +151,8 @@ impl Default for Direction {
 }
 
 /// Entity state machine
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
 pub enum EntityState {
     Idle,
     Moving,
@@ -101,7 +167,8 @@ impl Default for EntityState {
 }
 
 /// Health component
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct Health {
     pub current: f32,
     pub max: f32,
@@ -134,11 +201,22 @@ impl Health {
 }
 
 /// Marker component for the player character
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Player;
 
+/// A settlement NPC, drawn with the player's sprite; lives as long as its home chunk is loaded
+/// (see `settlers`)
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Settler {
+    #[reflect(ignore)]
+    pub home_chunk: ChunkPos,
+}
+
 /// Marker component for forest guardian creatures
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct ForestGuardian {
     /// The tree variant this guardian is associated with
     pub variant: TreeVariant,
@@ -151,7 +229,8 @@ impl ForestGuardian {
 }
 
 /// Component for entities that periodically spawn trees
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct TreeSpawner {
     /// Time until next tree spawn (seconds)
     pub spawn_timer: f32,
@@ -174,7 +253,7 @@ impl TreeSpawner {
         // Generate initial random spawn timer
         let hasher_builder = RandomState::new();
         let mut hasher = hasher_builder.build_hasher();
-        std::time::SystemTime::now().hash(&mut hasher);
+        crate::platform::unix_time().hash(&mut hasher);
         let hash = hasher.finish();
         let rand_val = (hash as f32) / (u64::MAX as f32);
 
@@ -194,15 +273,18 @@ impl TreeSpawner {
 }
 
 /// Marker component for snail creatures
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Snail;
 
 /// Marker component for growing tree spirits
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct TreeSpirit;
 
 /// Growth stages for trees
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
 pub enum GrowthStage {
     Seed,           // Initial planted seed (small sprite)
     Sapling,        // Young sapling (medium sprite)
@@ -221,6 +303,13 @@ impl GrowthStage {
         }
     }
 
+    /// The stage saved as `index` (`stage as u8`); None for stages this version doesn't have
+    pub fn from_index(index: u8) -> Option<GrowthStage> {
+        [GrowthStage::Seed, GrowthStage::Sapling, GrowthStage::YoungTree, GrowthStage::MatureTree]
+            .get(index as usize)
+            .copied()
+    }
+
     /// Get the next growth stage
     pub fn next(&self) -> Option<GrowthStage> {
         match self {
@@ -233,7 +322,8 @@ impl GrowthStage {
 }
 
 /// Component for trees that grow over time
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct GrowingTree {
     /// Current growth stage
     pub stage: GrowthStage,
@@ -267,10 +357,33 @@ impl GrowingTree {
     pub fn is_mature(&self) -> bool {
         self.stage == GrowthStage::MatureTree
     }
+
+    /// Add `seconds` of growth, advancing as many stages as that covers
+    /// Returns the new stage if the tree advanced
+    pub fn grow(&mut self, seconds: f32) -> Option<GrowthStage> {
+        if self.is_mature() {
+            return None;
+        }
+
+        self.time_in_stage += seconds;
+        let mut advanced = None;
+        while self.time_in_stage >= self.time_to_next_stage {
+            let Some(next_stage) = self.stage.next() else {
+                break;
+            };
+            self.stage = next_stage;
+            self.time_in_stage -= self.time_to_next_stage;
+            advanced = Some(next_stage);
+        }
+        if self.is_mature() {
+            self.time_in_stage = 0.0;
+        }
+        advanced
+    }
 }
 
 /// Tree variants available
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 pub enum TreeVariant {
     Oak,
     Birch,
@@ -280,6 +393,14 @@ pub enum TreeVariant {
 }
 
 impl TreeVariant {
+    pub const ALL: [TreeVariant; 5] = [
+        TreeVariant::Oak,
+        TreeVariant::Birch,
+        TreeVariant::Hickory,
+        TreeVariant::Pine,
+        TreeVariant::Willow,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             TreeVariant::Oak => "oak",
@@ -304,16 +425,7 @@ impl TreeVariant {
     /// Get a random variant different from this one
     pub fn random_other(&self, rand_val: f32) -> Self {
         // Get all variants except the current one
-        let others: Vec<TreeVariant> = [
-            TreeVariant::Oak,
-            TreeVariant::Birch,
-            TreeVariant::Hickory,
-            TreeVariant::Pine,
-            TreeVariant::Willow,
-        ]
-        .into_iter()
-        .filter(|v| v != self)
-        .collect();
+        let others: Vec<TreeVariant> = Self::ALL.into_iter().filter(|v| v != self).collect();
 
         // Pick one based on random value (0.0 to 1.0)
         let index = ((rand_val * others.len() as f32) as usize).min(others.len() - 1);
@@ -322,7 +434,8 @@ impl TreeVariant {
 }
 
 /// Roaming behavior - makes entities roam within a fixed radius of their spawn point
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
 pub struct RoamingBehavior {
     /// The center point to roam around (usually spawn position)
     pub home: Position,
@@ -370,7 +483,7 @@ impl RoamingBehavior {
 
         let hasher_builder = RandomState::new();
         let mut hasher = hasher_builder.build_hasher();
-        std::time::SystemTime::now().hash(&mut hasher);
+        crate::platform::unix_time().hash(&mut hasher);
         let hash = hasher.finish();
         let rand_val = (hash as f32) / (u64::MAX as f32);
 
@@ -398,7 +511,8 @@ impl RoamingBehavior {
 }
 
 /// Winding path behavior - makes entities move in long, meandering paths
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
 pub struct WindingPath {
     /// Current direction angle in radians
     pub current_angle: f32,
@@ -430,7 +544,7 @@ impl WindingPath {
         // Simple pseudo-random number generation using current time and hash
         let hasher_builder = RandomState::new();
         let mut hasher = hasher_builder.build_hasher();
-        std::time::SystemTime::now().hash(&mut hasher);
+        crate::platform::unix_time().hash(&mut hasher);
         let hash = hasher.finish();
         let initial_angle = ((hash as f32) / (u64::MAX as f32)) * 2.0 * PI;
 
@@ -461,7 +575,7 @@ impl WindingPath {
 
         let hasher_builder = RandomState::new();
         let mut hasher = hasher_builder.build_hasher();
-        std::time::SystemTime::now().hash(&mut hasher);
+        crate::platform::unix_time().hash(&mut hasher);
         let hash = hasher.finish();
         let initial_angle = ((hash as f32) / (u64::MAX as f32)) * 2.0 * PI;
 
@@ -496,9 +610,9 @@ pub struct EntityBundle {
 }
 
 impl EntityBundle {
-    pub fn new(x: f32, y: f32, max_health: f32) -> Self {
+    pub fn new(position: Position, max_health: f32) -> Self {
         Self {
-            position: Position::new(x, y),
+            position,
             velocity: Velocity::zero(),
             direction: Direction::default(),
             state: EntityState::Idle,
@@ -0,0 +1,305 @@
+use super::{Season, WorldClock, MIN_DAY_LENGTH, RANDOM_TICK_INTERVAL};
+use crate::entities::{GrowingTree, Position, TreeSpirit};
+use crate::error::Failures;
+use crate::stats::StatEvent;
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkData, ChunkPos, CHUNK_AREA, CHUNK_SIZE, LAYER_GROUND, TILE_DIRT, TILE_GRASS, TILE_SIZE};
+use crate::world::serialization::SerializationError;
+use crate::world::{ClockState, TileEdits, WorldManager};
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Magic number for chunk time files ("CTIM" in ASCII)
+const CHUNK_TIMES_MAGIC: [u8; 4] = [b'C', b'T', b'I', b'M'];
+
+/// Current chunk time file format version
+const CHUNK_TIMES_VERSION: u16 = 1;
+
+/// How often changed unload times are written to disk (seconds)
+const CHUNK_TIMES_SAVE_INTERVAL: f32 = 10.0;
+
+/// Simulated time at which each unloaded chunk stopped being simulated (`ClockState.elapsed`)
+/// Chunks still loaded when the game closes are recorded too, so catch-up spans sessions
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct ChunkUnloadTimes {
+    pub times: HashMap<ChunkPos, f64>,
+}
+
+/// Save unload times to disk (positions sorted so the file is stable)
+pub fn save_chunk_times<P: AsRef<Path>>(times: &ChunkUnloadTimes, path: P) -> Result<(), SerializationError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut entries: Vec<(ChunkPos, f64)> = times.times.iter().map(|(pos, time)| (*pos, *time)).collect();
+    entries.sort_by_key(|(pos, _)| (pos.x, pos.y));
+
+    let mut body = Vec::with_capacity(4 + entries.len() * 16);
+    body.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (pos, time) in entries {
+        body.extend_from_slice(&pos.x.to_le_bytes());
+        body.extend_from_slice(&pos.y.to_le_bytes());
+        body.extend_from_slice(&time.to_le_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(body.len() + 10);
+    bytes.extend_from_slice(&CHUNK_TIMES_MAGIC);
+    bytes.extend_from_slice(&CHUNK_TIMES_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&body);
+    bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Load unload times saved by `save_chunk_times`
+pub fn load_chunk_times<P: AsRef<Path>>(path: P) -> Result<ChunkUnloadTimes, SerializationError> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < 14 || bytes[0..4] != CHUNK_TIMES_MAGIC {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != CHUNK_TIMES_VERSION {
+        return Err(SerializationError::InvalidVersion(version));
+    }
+
+    let body = &bytes[6..bytes.len() - 4];
+    let checksum = &bytes[bytes.len() - 4..];
+    if crc32fast::hash(body) != u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) {
+        return Err(SerializationError::InvalidChecksum);
+    }
+
+    let count = u32::from_le_bytes([body[0], body[1], body[2], body[3]]) as usize;
+    if body.len() != 4 + count * 16 {
+        return Err(SerializationError::InvalidChunkSize(body.len()));
+    }
+
+    let times = body[4..]
+        .chunks_exact(16)
+        .map(|record| {
+            let pos = ChunkPos::new(
+                i32::from_le_bytes(record[0..4].try_into().unwrap()),
+                i32::from_le_bytes(record[4..8].try_into().unwrap()),
+            );
+            (pos, f64::from_le_bytes(record[8..16].try_into().unwrap()))
+        })
+        .collect();
+    Ok(ChunkUnloadTimes { times })
+}
+
+/// Expected number of successful regrowth rolls per chunk over the last `elapsed` seconds
+/// `regrow_grass` gets one random tick per chunk every `RANDOM_TICK_INTERVAL`; the season of
+/// each past day is taken into account (counting back from the clock's current day)
+pub fn regrowth_pressure(elapsed: f64, clock: &ClockState) -> f32 {
+    let day_length = clock.day_length.max(MIN_DAY_LENGTH) as f64;
+    let passes_per_day = day_length / RANDOM_TICK_INTERVAL as f64;
+
+    // Beyond this every dirt tile has regrown anyway
+    let saturated = CHUNK_AREA as f64 * 10.0;
+
+    let mut remaining = elapsed.max(0.0);
+    let mut day = clock.day;
+    let mut pressure = 0.0;
+    while remaining > 0.0 && pressure < saturated {
+        let seconds = remaining.min(day_length);
+        let chance = Season::for_day(day, clock.days_per_season).grass_regrowth_chance() as f64;
+        pressure += chance * passes_per_day * (seconds / day_length);
+        remaining -= seconds;
+        day = day.saturating_sub(1);
+    }
+    pressure.min(saturated) as f32
+}
+
+/// Seconds of tree growth over the last `elapsed` seconds: `update_tree_growth` scales time by the
+/// season's `growth_multiplier`, taken day by day like `regrowth_pressure` (weather is left out)
+pub fn tree_growth(elapsed: f64, clock: &ClockState) -> f32 {
+    let day_length = clock.day_length.max(MIN_DAY_LENGTH) as f64;
+
+    // Beyond this every tree has matured anyway
+    let saturated = 1_000_000.0;
+
+    let mut remaining = elapsed.max(0.0);
+    let mut day = clock.day;
+    let mut growth = 0.0;
+    while remaining > 0.0 && growth < saturated {
+        let seconds = remaining.min(day_length);
+        growth += Season::for_day(day, clock.days_per_season).growth_multiplier() as f64 * seconds;
+        remaining -= seconds;
+        day = day.saturating_sub(1);
+    }
+    growth.min(saturated) as f32
+}
+
+/// Ground tile indices of a chunk that would have regrown into grass under `pressure`
+/// Deterministic per chunk and `seed`, so a chunk reloaded twice with the same history catches up the same way
+pub fn fast_forward_chunk(chunk: &ChunkData, pressure: f32, seed: u64) -> Vec<usize> {
+    // Each pass picks one of CHUNK_AREA tiles, so a single tile's odds of regrowing are
+    // 1 - (1 - 1/CHUNK_AREA)^pressure
+    let chance = 1.0 - (1.0 - 1.0 / CHUNK_AREA as f32).powf(pressure);
+    if chance <= 0.0 {
+        return Vec::new();
+    }
+
+    let chunk_seed = seed ^ ((chunk.position.x as u32 as u64) << 32) ^ chunk.position.y as u32 as u64;
+    chunk.layers[LAYER_GROUND]
+        .iter()
+        .enumerate()
+        .filter(|(index, tile)| **tile == TILE_DIRT && random_unit(chunk_seed, *index as u64) < chance)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Hash of (seed, index) as a number in [0, 1) (splitmix64 finalizer)
+fn random_unit(seed: u64, index: u64) -> f32 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Startup system that restores the unload times saved with the world
+pub fn load_chunk_unload_times(world: Res<WorldManager>, mut times: ResMut<ChunkUnloadTimes>, mut failures: Failures) {
+    let path = world.get_chunk_times_path();
+    if !path.exists() {
+        return;
+    }
+
+    match load_chunk_times(&path) {
+        Ok(loaded) => {
+            info!("Loaded unload times for {} chunks", loaded.times.len());
+            *times = loaded;
+        }
+        Err(e) => failures.report(format!("Failed to load chunk unload times from {}", path.display()), e),
+    }
+}
+
+/// Records when chunks unload and fast-forwards them when they load again (grass, and the growth of
+/// the trees saved with them), so the world doesn't freeze where the camera isn't looking
+/// Writes the unload times every few seconds when they changed, and on window close
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn catch_up_reloaded_chunks(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut simulated: Local<HashSet<ChunkPos>>,
+    mut close_requests: MessageReader<WindowCloseRequested>,
+    clock: Res<WorldClock>,
+    world: Res<WorldManager>,
+    mut tile_edits: TileEdits,
+    mut trees: Query<(&Position, &mut GrowingTree, &mut Transform), With<TreeSpirit>>,
+    mut stat_events: MessageWriter<StatEvent>,
+    mut times: ResMut<ChunkUnloadTimes>,
+    mut failures: Failures,
+) {
+    let now = clock.0.elapsed;
+
+    if world.is_changed() {
+        // Chunks that went away since last frame stop being simulated now
+        for pos in simulated.iter() {
+            if !world.is_loaded(pos) {
+                times.times.insert(*pos, now);
+            }
+        }
+        simulated.retain(|pos| world.is_loaded(pos));
+
+        let loaded: Vec<ChunkPos> = world.active_chunks.keys().copied().filter(|pos| !simulated.contains(pos)).collect();
+        let mut tree_catch_up = HashMap::new();
+        for pos in loaded {
+            simulated.insert(pos);
+            let Some(unloaded_at) = times.times.remove(&pos) else {
+                continue;
+            };
+
+            let away = now - unloaded_at;
+            if away < RANDOM_TICK_INTERVAL as f64 {
+                continue;
+            }
+            tree_catch_up.insert(pos, tree_growth(away, &clock.0));
+            let Some(chunk_data) = world.get_cached_chunk(&pos) else {
+                continue;
+            };
+
+            let regrown = fast_forward_chunk(chunk_data, regrowth_pressure(away, &clock.0), unloaded_at.to_bits());
+            if regrown.is_empty() {
+                continue;
+            }
+            debug!("Chunk {:?} caught up {:.0}s: {} tiles regrew", pos, away, regrown.len());
+
+            let origin = pos.to_world(crate::tiles::CHUNK_PIXEL_SIZE);
+            let positions = regrown.into_iter().map(|index| {
+                Vec2::new(
+                    origin.x + ((index % CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+                    origin.y + ((index / CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+                )
+            });
+            tile_edits.set_ground_batch(positions, TILE_GRASS);
+        }
+
+        // Trees saved with the chunks were spawned (at the stage they were saved at) when they loaded
+        if !tree_catch_up.is_empty() {
+            for (position, mut tree, mut transform) in trees.iter_mut() {
+                let Some(&growth) = tree_catch_up.get(&coords::world_to_chunk(position.vec2())) else {
+                    continue;
+                };
+                if tree.grow(growth).is_some() {
+                    transform.scale = Vec3::splat(tree.stage.scale());
+                    if tree.is_mature() {
+                        stat_events.write(StatEvent::TreeGrown);
+                    }
+                }
+            }
+        }
+    }
+
+    *elapsed += time.delta_secs();
+    let closing = close_requests.read().count() > 0;
+    if closing {
+        // Whatever is loaded now picks up from here next session
+        for pos in simulated.iter() {
+            times.times.insert(*pos, now);
+        }
+    } else if *elapsed < CHUNK_TIMES_SAVE_INTERVAL || !times.is_changed() {
+        return;
+    }
+    *elapsed = 0.0;
+
+    if let Err(e) = save_chunk_times(&times, world.get_chunk_times_path()) {
+        failures.report("Failed to save chunk unload times", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TILE_EMPTY;
+    use std::env;
+
+    #[test]
+    fn test_fast_forward_regrows_dirt() {
+        let chunk = ChunkData::filled_layers(ChunkPos::new(3, -2), [TILE_DIRT, TILE_EMPTY, TILE_EMPTY]);
+        let spring = ClockState { day: 0, day_length: 600.0, days_per_season: 7, ..default() };
+        let winter = ClockState { day: 21, ..spring.clone() };
+
+        // Nothing regrows in winter, a few tiles in a short spring absence, nearly everything after a long one
+        assert_eq!(regrowth_pressure(600.0, &winter), 0.0);
+        let short = fast_forward_chunk(&chunk, regrowth_pressure(60.0, &spring), 7);
+        assert!(!short.is_empty() && short.len() < 64);
+        assert_eq!(short, fast_forward_chunk(&chunk, regrowth_pressure(60.0, &spring), 7));
+        let long = fast_forward_chunk(&chunk, regrowth_pressure(100.0 * 600.0, &spring), 7);
+        assert!(long.len() > CHUNK_AREA * 9 / 10);
+
+        // Trees grow faster in spring, not at all in winter; an absence spanning both counts each day
+        assert_eq!(tree_growth(600.0, &spring), 900.0);
+        assert_eq!(tree_growth(600.0, &winter), 0.0);
+        assert_eq!(tree_growth(1200.0, &winter), 300.0);
+
+        let path = env::temp_dir().join("test_chunk_times.bin");
+        let mut times = ChunkUnloadTimes::default();
+        times.times.insert(ChunkPos::new(-1, 4), 1234.5);
+        save_chunk_times(&times, &path).expect("Failed to save chunk times");
+        assert_eq!(load_chunk_times(&path).expect("Failed to load chunk times"), times);
+        let _ = fs::remove_file(path);
+    }
+}
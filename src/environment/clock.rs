@@ -0,0 +1,170 @@
+use super::EnvironmentHud;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::world::ClockState;
+use bevy::prelude::*;
+
+/// Shortest and longest allowed day length (real seconds)
+pub const MIN_DAY_LENGTH: f32 = 10.0;
+pub const MAX_DAY_LENGTH: f32 = 86_400.0;
+
+/// Sky tint keyframes: (hour, [r, g, b, alpha]), interpolated linearly
+/// Alpha is how strongly the lighting overlay darkens the world
+const SKY_KEYFRAMES: [(f32, [f32; 4]); 8] = [
+    (0.0, [0.05, 0.05, 0.2, 0.55]),
+    (5.0, [0.05, 0.05, 0.2, 0.55]),
+    (6.5, [0.9, 0.6, 0.4, 0.15]),
+    (8.0, [1.0, 1.0, 1.0, 0.0]),
+    (17.0, [1.0, 1.0, 1.0, 0.0]),
+    (19.0, [0.9, 0.45, 0.2, 0.25]),
+    (21.0, [0.05, 0.05, 0.2, 0.55]),
+    (24.0, [0.05, 0.05, 0.2, 0.55]),
+];
+
+/// In-game time of day
+/// Advances with (virtual) simulation time; copied into `WorldMetadata.clock` periodically
+#[derive(Resource, Default)]
+pub struct WorldClock(pub ClockState);
+
+/// Hour the lighting shows instead of the clock's (photo mode); the clock itself keeps running
+#[derive(Resource, Default)]
+pub struct DisplayHour(pub Option<f32>);
+
+impl WorldClock {
+    /// Advance by `seconds` of real time, rolling over into the next day
+    pub fn advance(&mut self, seconds: f32) {
+        let day_length = self.0.day_length.max(MIN_DAY_LENGTH);
+        self.0.elapsed += seconds as f64;
+        self.0.time_of_day += seconds / day_length;
+        while self.0.time_of_day >= 1.0 {
+            self.0.time_of_day -= 1.0;
+            self.0.day += 1;
+        }
+    }
+
+    /// Hour of the day (0.0 - 24.0)
+    pub fn hour(&self) -> f32 {
+        self.0.time_of_day * 24.0
+    }
+
+    /// Jump to an hour of the current day
+    pub fn set_hour(&mut self, hour: f32) {
+        self.0.time_of_day = (hour / 24.0).rem_euclid(1.0);
+    }
+
+    /// "Day 3, 18:42" (days are shown starting from 1)
+    pub fn label(&self) -> String {
+        let minutes = (self.hour() * 60.0) as u32;
+        format!("Day {}, {:02}:{:02}", self.0.day + 1, minutes / 60 % 24, minutes % 60)
+    }
+}
+
+/// Overlay color for an hour of the day
+pub fn sky_tint(hour: f32) -> Color {
+    let hour = hour.rem_euclid(24.0);
+    let next = SKY_KEYFRAMES
+        .iter()
+        .position(|(keyframe_hour, _)| *keyframe_hour > hour)
+        .unwrap_or(SKY_KEYFRAMES.len() - 1)
+        .max(1);
+    let (start_hour, start) = SKY_KEYFRAMES[next - 1];
+    let (end_hour, end) = SKY_KEYFRAMES[next];
+    let t = ((hour - start_hour) / (end_hour - start_hour)).clamp(0.0, 1.0);
+    let mix = |i: usize| start[i] + (end[i] - start[i]) * t;
+    Color::srgba(mix(0), mix(1), mix(2), mix(3))
+}
+
+/// Marker component for the clock text in the environment HUD
+#[derive(Component)]
+pub struct ClockText;
+
+/// Spawns the clock widget
+pub fn setup_clock(mut commands: Commands, hud: Single<Entity, With<EnvironmentHud>>) {
+    commands.entity(*hud).with_child((
+        ClockText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+/// Advances the world clock
+pub fn advance_clock(time: Res<Time>, mut clock: ResMut<WorldClock>) {
+    clock.advance(time.delta_secs());
+}
+
+/// Shows the day and time in the environment HUD
+pub fn update_clock_text(clock: Res<WorldClock>, mut text: Single<&mut Text, With<ClockText>>) {
+    let label = clock.label();
+    if text.0 != label {
+        text.0 = label;
+    }
+}
+
+/// Console commands: `time [hour]` shows or sets the time of day,
+/// `daylength [seconds]` shows or sets the length of a day
+pub fn handle_clock_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut clock: ResMut<WorldClock>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "time" => match command.args.first().map(|hour| hour.parse::<f32>()) {
+                None => console.print(clock.label()),
+                Some(Ok(hour)) if (0.0..24.0).contains(&hour) => {
+                    clock.set_hour(hour);
+                    console.print(format!("Time set to {}", clock.label()));
+                }
+                Some(_) => console.print("Usage: time [hour 0-24]"),
+            },
+            "daylength" => match command.args.first().map(|seconds| seconds.parse::<f32>()) {
+                None => console.print(format!("A day lasts {} seconds", clock.0.day_length)),
+                Some(Ok(seconds)) if (MIN_DAY_LENGTH..=MAX_DAY_LENGTH).contains(&seconds) => {
+                    clock.0.day_length = seconds;
+                    console.print(format!("A day now lasts {} seconds", seconds));
+                }
+                Some(_) => console.print(format!(
+                    "Usage: daylength [seconds {}-{}]",
+                    MIN_DAY_LENGTH, MAX_DAY_LENGTH
+                )),
+            },
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_rolls_over_days() {
+        let mut clock = WorldClock(ClockState {
+            day: 0,
+            time_of_day: 0.875,
+            day_length: 96.0,
+            ..default()
+        });
+        assert_eq!(clock.label(), "Day 1, 21:00");
+
+        clock.advance(48.0);
+        assert_eq!(clock.0.day, 1);
+        assert_eq!(clock.label(), "Day 2, 09:00");
+
+        clock.set_hour(-6.0);
+        assert_eq!(clock.hour(), 18.0);
+    }
+
+    #[test]
+    fn test_sky_tint() {
+        let alpha = |hour: f32| sky_tint(hour).alpha();
+        assert_eq!(alpha(12.0), 0.0);
+        assert!((alpha(0.0) - 0.55).abs() < 1e-5);
+        assert!((alpha(23.9) - 0.55).abs() < 1e-5);
+        let dusk = alpha(20.0);
+        assert!(dusk > 0.25 && dusk < 0.55);
+    }
+}
@@ -0,0 +1,219 @@
+use super::{Weather, WorldWeather};
+use crate::entities::{Position, TreeSpirit};
+use crate::tiles::{chunk::coords, CHUNK_SIZE_I32, LAYER_GROUND, TILE_DIRT, TILE_GRASS, TILE_SIZE};
+use crate::world::{TileEdits, WorldManager, WorldMetadata, WorldOrigin};
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+
+/// Seconds between fire spread steps
+const FIRE_TICK_INTERVAL: f32 = 1.0;
+
+/// Seconds a tile burns before going out
+pub const BURN_DURATION: f32 = 6.0;
+
+/// Chance per step that a burning tile ignites each neighbouring grass tile
+const SPREAD_CHANCE: f32 = 0.12;
+
+/// Spread multiplier in wind and storms
+const WIND_SPREAD_MULTIPLIER: f32 = 2.0;
+
+/// Upper bound on burning tiles, so a wildfire can't grow without limit
+const MAX_FIRES: usize = 2000;
+
+/// Tiles currently on fire (tile coordinates -> seconds left to burn)
+/// Burning tiles are scarred to dirt as they ignite; `regrow_grass` heals them afterwards
+#[derive(Resource, Default)]
+pub struct Fires {
+    pub burning: HashMap<IVec2, f32>,
+}
+
+impl Fires {
+    /// Set a tile alight; returns false if it was already burning or the fire cap is reached
+    pub fn ignite(&mut self, tile: IVec2) -> bool {
+        if self.burning.len() >= MAX_FIRES || self.burning.contains_key(&tile) {
+            return false;
+        }
+        self.burning.insert(tile, BURN_DURATION);
+        true
+    }
+}
+
+/// Tile coordinates of a world position
+pub fn tile_at(position: Vec2) -> IVec2 {
+    (position / TILE_SIZE).floor().as_ivec2()
+}
+
+/// World position of a tile's center
+pub fn tile_center(tile: IVec2) -> Vec2 {
+    (tile.as_vec2() + Vec2::splat(0.5)) * TILE_SIZE
+}
+
+/// Advance fires by `seconds`: burning tiles count down and go out, and each may ignite its
+/// four neighbours when `is_fuel` allows and `roll` comes in under `spread_chance`
+/// Returns the newly ignited tiles
+pub fn spread_fires(
+    fires: &mut Fires,
+    seconds: f32,
+    spread_chance: f32,
+    mut is_fuel: impl FnMut(IVec2) -> bool,
+    mut roll: impl FnMut(IVec2) -> f32,
+) -> Vec<IVec2> {
+    for remaining in fires.burning.values_mut() {
+        *remaining -= seconds;
+    }
+    fires.burning.retain(|_, remaining| *remaining > 0.0);
+
+    let burning: Vec<IVec2> = fires.burning.keys().copied().collect();
+    let mut ignited = Vec::new();
+    for tile in burning {
+        for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let neighbour = tile + offset;
+            if is_fuel(neighbour) && roll(neighbour) < spread_chance && fires.ignite(neighbour) {
+                ignited.push(neighbour);
+            }
+        }
+    }
+    ignited
+}
+
+/// Ground tile at tile coordinates, if its chunk is loaded
+pub fn ground_tile(world: &WorldManager, tile: IVec2) -> Option<u16> {
+    let chunk = world.get_cached_chunk(&coords::tile_to_chunk(tile))?;
+    chunk.get_tile(
+        LAYER_GROUND,
+        tile.x.rem_euclid(CHUNK_SIZE_I32) as usize,
+        tile.y.rem_euclid(CHUNK_SIZE_I32) as usize,
+    )
+}
+
+/// Whether a tile can catch fire (loaded grass)
+pub fn is_flammable(world: &WorldManager, tile: IVec2) -> bool {
+    ground_tile(world, tile) == Some(TILE_GRASS)
+}
+
+/// Set a tile alight and scar it to dirt
+pub fn ignite_tile(fires: &mut Fires, tile_edits: &mut TileEdits, tile: IVec2) -> bool {
+    if !fires.ignite(tile) {
+        return false;
+    }
+    tile_edits.set_ground(tile_center(tile), TILE_DIRT);
+    true
+}
+
+/// Marker for the sprite drawn over a burning tile
+#[derive(Component)]
+pub struct FireSprite(pub IVec2);
+
+/// Spreads fire over grass (faster in wind and storms), burns trees standing in it and
+/// puts everything out when it rains
+#[allow(clippy::too_many_arguments)]
+pub fn update_fires(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    weather: Res<WorldWeather>,
+    mut fires: ResMut<Fires>,
+    world: Res<WorldManager>,
+    metadata: Res<WorldMetadata>,
+    mut tile_edits: TileEdits,
+    trees: Query<(Entity, &Position), With<TreeSpirit>>,
+) {
+    if fires.burning.is_empty() {
+        return;
+    }
+    if weather.weather == Weather::Rain {
+        info!("Rain put out {} burning tiles", fires.burning.len());
+        fires.burning.clear();
+        return;
+    }
+
+    *elapsed += time.delta_secs();
+    if *elapsed < FIRE_TICK_INTERVAL {
+        return;
+    }
+    let step = std::mem::take(&mut *elapsed);
+
+    let spread_chance = match weather.weather {
+        Weather::Wind | Weather::Storm => SPREAD_CHANCE * WIND_SPREAD_MULTIPLIER,
+        _ => SPREAD_CHANCE,
+    };
+    let hasher_builder = RandomState::new();
+    let ignited = spread_fires(
+        &mut fires,
+        step,
+        spread_chance,
+        // Protected regions keep their grass, so they must not keep catching fire either
+        |tile| is_flammable(&world, tile) && metadata.protected_region_at(tile_center(tile)).is_none(),
+        |tile| (hasher_builder.hash_one(tile) >> 40) as f32 / (1u64 << 24) as f32,
+    );
+    tile_edits.set_ground_batch(ignited.into_iter().map(tile_center), TILE_DIRT);
+
+    for (entity, position) in trees.iter() {
+        if fires.burning.contains_key(&tile_at(position.vec2())) {
+            debug!("A tree burned at ({:.0}, {:.0})", position.x(), position.y());
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Keeps one flickering sprite on each burning tile
+pub fn update_fire_sprites(
+    mut commands: Commands,
+    time: Res<Time>,
+    fires: Res<Fires>,
+    origin: Res<WorldOrigin>,
+    mut sprites: Query<(Entity, &FireSprite, &mut Sprite)>,
+) {
+    let mut shown: HashSet<IVec2> = HashSet::new();
+    for (entity, fire, mut sprite) in sprites.iter_mut() {
+        let Some(remaining) = fires.burning.get(&fire.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let flicker = (time.elapsed_secs() * 12.0 + (fire.0.x ^ fire.0.y) as f32).sin() * 0.15;
+        sprite.color = Color::srgba(1.0, 0.45 + flicker, 0.1, (remaining / BURN_DURATION).clamp(0.3, 0.9));
+        shown.insert(fire.0);
+    }
+
+    if !fires.is_changed() {
+        return;
+    }
+    for tile in fires.burning.keys() {
+        if shown.contains(tile) {
+            continue;
+        }
+        let center = origin.to_render(tile_center(*tile));
+        commands.spawn((
+            FireSprite(*tile),
+            Sprite::from_color(Color::srgba(1.0, 0.45, 0.1, 0.9), Vec2::splat(TILE_SIZE)),
+            Transform::from_xyz(center.x, center.y, 0.5),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_spreads_and_burns_out() {
+        let mut fires = Fires::default();
+        assert!(fires.ignite(IVec2::ZERO));
+        assert!(!fires.ignite(IVec2::ZERO));
+
+        // Fuel only along the x axis; every roll succeeds
+        let fuel = |tile: IVec2| tile.y == 0 && tile.x.abs() <= 2;
+        let ignited = spread_fires(&mut fires, 1.0, 0.5, fuel, |_| 0.0);
+        assert_eq!(ignited.len(), 2);
+        assert_eq!(fires.burning[&IVec2::ZERO], BURN_DURATION - 1.0);
+        assert_eq!(fires.burning[&IVec2::X], BURN_DURATION);
+
+        // Nothing spreads on failed rolls, and everything goes out eventually
+        for _ in 0..BURN_DURATION as usize {
+            assert!(spread_fires(&mut fires, 1.0, 0.5, fuel, |_| 1.0).is_empty());
+        }
+        assert!(fires.burning.is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Night lighting: the darkness is one quad over the view drawn by `lighting.wgsl`, which samples a
+//! light map texture (one texel per tile) that the CPU stamps light sources into. Lights changing
+//! every frame only rewrite the texture, never tiles or sprites
+
+use super::{sky_tint, tile_at, CurrentMoon, DisplayHour, Fires, MoonGlow, WorldClock};
+use crate::tiles::{ChunkPos, CHUNK_LOAD_RADIUS, CHUNK_SIZE, CHUNK_SIZE_I32, TILE_SIZE};
+use crate::world::WorldOrigin;
+use bevy::asset::{embedded_asset, embedded_path, AssetPath, RenderAssetUsages};
+use bevy::image::ImageSampler;
+use bevy::math::I64Vec2;
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, Extent3d, TextureDimension, TextureFormat};
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{AlphaMode2d, Material2d, Material2dPlugin};
+
+/// Chunks the light map reaches around the camera chunk (one more than the load radius, so lights
+/// in every loaded chunk fit at the default zoom)
+const LIGHT_MAP_RADIUS: i32 = CHUNK_LOAD_RADIUS + 1;
+
+/// Light map side in tiles (texels)
+const LIGHT_MAP_TILES: usize = (2 * LIGHT_MAP_RADIUS as usize + 1) * CHUNK_SIZE;
+
+/// Light radius of a burning tile, in tiles
+const FIRE_LIGHT_RADIUS: f32 = 5.0;
+
+/// Light radius of a moon glow at full brightness, in tiles
+const GLOW_LIGHT_RADIUS: f32 = 2.0;
+
+/// Above entity sprites (1.0+) and fire, below waypoint labels (10.0)
+const LIGHTING_Z: f32 = 5.0;
+
+/// Darkness with holes where the light map is lit
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct LightingMaterial {
+    /// Color of unlit areas; alpha is how dark they are
+    #[uniform(0)]
+    pub darkness: LinearRgba,
+    /// Render-space rectangle the light map covers: min x, min y, width, height
+    #[uniform(1)]
+    pub light_rect: Vec4,
+    #[texture(2)]
+    #[sampler(3)]
+    pub light_map: Handle<Image>,
+}
+
+impl Material2d for LightingMaterial {
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Path(AssetPath::from_path_buf(embedded_path!("lighting.wgsl")).with_source("embedded"))
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Light levels (0-255) of the tiles around the camera, rebuilt each frame from the light sources
+/// and uploaded to `image` when they change
+#[derive(Resource)]
+pub struct LightMap {
+    /// Chunk at the middle of the map
+    center: ChunkPos,
+    levels: Vec<u8>,
+    pub image: Handle<Image>,
+    pub material: Handle<LightingMaterial>,
+}
+
+impl Default for LightMap {
+    fn default() -> Self {
+        Self {
+            center: ChunkPos::default(),
+            levels: vec![0; LIGHT_MAP_TILES * LIGHT_MAP_TILES],
+            image: Handle::default(),
+            material: Handle::default(),
+        }
+    }
+}
+
+impl LightMap {
+    /// Moves the map to be centered on `center` and turns every light off
+    pub fn reset(&mut self, center: ChunkPos) {
+        self.center = center;
+        self.levels.fill(0);
+    }
+
+    /// First chunk of the map (bottom left)
+    pub fn first_chunk(&self) -> ChunkPos {
+        ChunkPos::new(
+            self.center.x.saturating_sub(LIGHT_MAP_RADIUS),
+            self.center.y.saturating_sub(LIGHT_MAP_RADIUS),
+        )
+    }
+
+    /// Texel index of a tile (rows top to bottom, as in the image), if it is on the map
+    fn index(&self, tile: IVec2) -> Option<usize> {
+        let first = self.first_chunk();
+        let local = tile.as_i64vec2() - I64Vec2::new(first.x as i64, first.y as i64) * CHUNK_SIZE_I32 as i64;
+        let size = LIGHT_MAP_TILES as i64;
+        if local.x < 0 || local.y < 0 || local.x >= size || local.y >= size {
+            return None;
+        }
+        Some(((size - 1 - local.y) * size + local.x) as usize)
+    }
+
+    /// Light level of a tile (0.0 dark - 1.0 fully lit); tiles off the map are dark
+    pub fn level(&self, tile: IVec2) -> f32 {
+        self.index(tile).map_or(0.0, |index| self.levels[index] as f32 / 255.0)
+    }
+
+    /// Lights the tiles within `radius` tiles of `tile`, fading linearly from `strength` at the center;
+    /// overlapping lights keep the brightest
+    pub fn add_light(&mut self, tile: IVec2, radius: f32, strength: f32) {
+        let reach = radius.ceil() as i32;
+        for y in -reach..=reach {
+            for x in -reach..=reach {
+                let falloff = 1.0 - Vec2::new(x as f32, y as f32).length() / radius;
+                if falloff <= 0.0 {
+                    continue;
+                }
+                let Some(index) = self.index(tile + IVec2::new(x, y)) else {
+                    continue;
+                };
+                let level = (strength * falloff).clamp(0.0, 1.0) * 255.0;
+                self.levels[index] = self.levels[index].max(level as u8);
+            }
+        }
+    }
+}
+
+/// Marker component for the darkness quad
+#[derive(Component)]
+pub struct LightingOverlay;
+
+/// Plugin for the lighting shader and its material; the systems are registered by `EnvironmentPlugin`
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "lighting.wgsl");
+        app.add_plugins(Material2dPlugin::<LightingMaterial>::default())
+            .init_resource::<LightMap>();
+    }
+}
+
+/// Creates the light map texture and spawns the darkness quad
+pub fn setup_lighting(
+    mut commands: Commands,
+    mut light_map: ResMut<LightMap>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+) {
+    let mut image = Image::new(
+        Extent3d {
+            width: LIGHT_MAP_TILES as u32,
+            height: LIGHT_MAP_TILES as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        light_map.levels.clone(),
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::default(),
+    );
+    // Smooth light edges, even though the game otherwise samples nearest
+    image.sampler = ImageSampler::linear();
+    light_map.image = images.add(image);
+    light_map.material = materials.add(LightingMaterial {
+        darkness: LinearRgba::NONE,
+        light_rect: Vec4::ZERO,
+        light_map: light_map.image.clone(),
+    });
+
+    commands.spawn((
+        LightingOverlay,
+        Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
+        MeshMaterial2d(light_map.material.clone()),
+        Transform::from_xyz(0.0, 0.0, LIGHTING_Z),
+    ));
+}
+
+/// Rebuilds the light map around the camera from burning tiles and moon glows, uploading it only
+/// when a level changed
+pub fn update_light_map(
+    mut light_map: ResMut<LightMap>,
+    mut images: ResMut<Assets<Image>>,
+    origin: Res<WorldOrigin>,
+    camera: Single<&Transform, With<Camera2d>>,
+    fires: Res<Fires>,
+    glows: Query<(&Transform, &Sprite), With<MoonGlow>>,
+) {
+    let previous = light_map.levels.clone();
+    light_map.reset(origin.chunk_at(camera.translation.truncate()));
+
+    for tile in fires.burning.keys() {
+        light_map.add_light(*tile, FIRE_LIGHT_RADIUS, 1.0);
+    }
+    for (transform, sprite) in glows.iter() {
+        let position = origin.to_world_vec2(transform.translation.truncate());
+        let brightness = sprite.color.alpha();
+        light_map.add_light(tile_at(position), GLOW_LIGHT_RADIUS, brightness);
+    }
+
+    if light_map.levels == previous {
+        return;
+    }
+    if let Some(image) = images.get_mut(&light_map.image) {
+        image.data = Some(light_map.levels.clone());
+    }
+}
+
+/// The camera, as seen by a system that also moves the darkness quad
+pub type LightingCameraFilter = (With<Camera2d>, Without<LightingOverlay>);
+
+/// Colors the darkness by the time of day (darker still under a new moon), and keeps the quad
+/// covering the view and the light map lined up with the world
+#[allow(clippy::too_many_arguments)]
+pub fn update_lighting(
+    clock: Res<WorldClock>,
+    display_hour: Res<DisplayHour>,
+    moon: Res<CurrentMoon>,
+    light_map: Res<LightMap>,
+    origin: Res<WorldOrigin>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+    camera: Single<(&Transform, &Projection), LightingCameraFilter>,
+    window: Single<&Window>,
+    mut overlay: Single<&mut Transform, With<LightingOverlay>>,
+) {
+    let (camera_transform, projection) = *camera;
+    let scale = match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    };
+    // A margin so the edges never show while the camera moves
+    let size = window.size() * scale + Vec2::splat(2.0 * TILE_SIZE);
+    overlay.translation.x = camera_transform.translation.x;
+    overlay.translation.y = camera_transform.translation.y;
+    overlay.scale = size.extend(1.0);
+
+    let tint = sky_tint(display_hour.0.unwrap_or_else(|| clock.hour()));
+    let darkness = LinearRgba::from(tint.with_alpha((tint.alpha() + moon.darkness_offset()).clamp(0.0, 1.0)));
+    let min = origin.chunk_translation(light_map.first_chunk());
+    let light_rect = min.extend(LIGHT_MAP_TILES as f32 * TILE_SIZE).extend(LIGHT_MAP_TILES as f32 * TILE_SIZE);
+    // Only touch the material when it changes, so its bind group isn't rebuilt every frame
+    let unchanged = materials
+        .get(&light_map.material)
+        .is_some_and(|material| material.darkness == darkness && material.light_rect == light_rect);
+    if unchanged {
+        return;
+    }
+    if let Some(material) = materials.get_mut(&light_map.material) {
+        material.darkness = darkness;
+        material.light_rect = light_rect;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_map() {
+        let mut light_map = LightMap::default();
+        light_map.reset(ChunkPos::new(10, -2));
+        let tile = IVec2::new(10 * CHUNK_SIZE_I32 + 3, -2 * CHUNK_SIZE_I32 + 7);
+
+        light_map.add_light(tile, 4.0, 1.0);
+        assert_eq!(light_map.level(tile), 1.0);
+        assert!((light_map.level(tile + IVec2::new(2, 0)) - 0.5).abs() < 0.01);
+        assert_eq!(light_map.level(tile + IVec2::new(4, 0)), 0.0);
+
+        // The brightest overlapping light wins
+        light_map.add_light(tile, 4.0, 0.2);
+        assert_eq!(light_map.level(tile), 1.0);
+
+        // Tiles off the map are dark, and lights near the edge are clipped
+        let first = light_map.first_chunk();
+        let corner = IVec2::new(first.x, first.y) * CHUNK_SIZE_I32;
+        light_map.add_light(corner, 3.0, 1.0);
+        assert_eq!(light_map.level(corner), 1.0);
+        assert_eq!(light_map.level(corner - IVec2::ONE), 0.0);
+
+        light_map.reset(ChunkPos::new(i32::MAX, i32::MIN));
+        assert_eq!(light_map.level(tile), 0.0);
+    }
+}
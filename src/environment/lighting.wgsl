@@ -0,0 +1,19 @@
+// Darkness over the world, with holes where the light map (one texel per tile) is lit
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> darkness: vec4<f32>;
+// Render-space rectangle covered by the light map: min x, min y, width, height
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> light_rect: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var light_map: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var light_sampler: sampler;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = (mesh.world_position.xy - light_rect.xy) / light_rect.zw;
+    // Image rows run top to bottom, world y bottom to top
+    let sampled = textureSample(light_map, light_sampler, vec2(uv.x, 1.0 - uv.y)).r;
+    let on_map = all(uv >= vec2(0.0)) && all(uv <= vec2(1.0));
+    let light = select(0.0, sampled, on_map);
+    return vec4(darkness.rgb, darkness.a * (1.0 - light));
+}
@@ -0,0 +1,129 @@
+use super::{ignite_tile, is_flammable, tile_at, Fires, Weather, WorldWeather};
+use crate::audio::{Sound, SoundEffect};
+use crate::chronicle::ChronicleEvent;
+use crate::config::GameConfig;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::tiles::{CHUNK_AREA, CHUNK_PIXEL_SIZE, CHUNK_SIZE, TILE_SIZE};
+use crate::world::{TileEdits, WorldManager, WorldOrigin};
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// How long the screen flash of a strike lasts (seconds)
+const FLASH_DURATION: f32 = 0.3;
+
+/// Lightning hit the ground here (world position in pixels)
+#[derive(Message, Debug, Clone, Copy)]
+pub struct LightningStrike(pub Vec2);
+
+/// Full-screen white flash shown on each strike, fading over `FLASH_DURATION` (not with reduced motion)
+#[derive(Component, Default)]
+pub struct LightningFlash {
+    remaining: f32,
+}
+
+pub fn setup_lightning_flash(mut commands: Commands) {
+    commands.spawn((
+        LightningFlash::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        Pickable::IGNORE,
+        ZIndex(-1),
+    ));
+}
+
+/// During thunderstorms, strikes a random tile of a random loaded chunk about
+/// `lightning_per_minute` times a minute
+pub fn strike_lightning(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    weather: Res<WorldWeather>,
+    world: Res<WorldManager>,
+    mut strikes: MessageWriter<LightningStrike>,
+) {
+    if weather.weather != Weather::Storm || config.gameplay.lightning_per_minute <= 0.0 {
+        return;
+    }
+
+    let hasher_builder = RandomState::new();
+    let hash = hasher_builder.hash_one(time.elapsed_secs().to_bits());
+    let roll = (hash >> 40) as f32 / (1u64 << 24) as f32;
+    if roll >= config.gameplay.lightning_per_minute / 60.0 * time.delta_secs() {
+        return;
+    }
+
+    let loaded: Vec<_> = world.active_chunks.keys().collect();
+    if loaded.is_empty() {
+        return;
+    }
+    let chunk = loaded[(hash % loaded.len() as u64) as usize];
+    let index = ((hash >> 16) % CHUNK_AREA as u64) as usize;
+    let origin = chunk.to_world(CHUNK_PIXEL_SIZE);
+    strikes.write(LightningStrike(Vec2::new(
+        origin.x + ((index % CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+        origin.y + ((index / CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+    )));
+}
+
+/// Lightning scars the struck tile and, if it was grass, starts a fire there
+pub fn apply_lightning_strikes(
+    mut strikes: MessageReader<LightningStrike>,
+    mut fires: ResMut<Fires>,
+    world: Res<WorldManager>,
+    mut tile_edits: TileEdits,
+    mut flash: Single<&mut LightningFlash>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+    mut sounds: MessageWriter<SoundEffect>,
+) {
+    for LightningStrike(position) in strikes.read() {
+        flash.remaining = FLASH_DURATION;
+        sounds.write(SoundEffect::at(Sound::Thunder, *position));
+        let tile = tile_at(*position);
+        if is_flammable(&world, tile) && ignite_tile(&mut fires, &mut tile_edits, tile) {
+            info!("Lightning started a fire at ({:.0}, {:.0})", position.x, position.y);
+            chronicle.write(ChronicleEvent::milestone("first_wildfire", "Lightning started the first wildfire"));
+        }
+    }
+}
+
+pub fn animate_lightning_flash(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut flash: Single<(&mut LightningFlash, &mut BackgroundColor)>,
+) {
+    let (flash, color) = &mut *flash;
+    if flash.remaining <= 0.0 {
+        return;
+    }
+    if config.accessibility.reduced_motion {
+        flash.remaining = 0.0;
+        color.0 = Color::NONE;
+        return;
+    }
+    flash.remaining = (flash.remaining - time.delta_secs()).max(0.0);
+    color.0 = Color::srgba(1.0, 1.0, 1.0, 0.6 * flash.remaining / FLASH_DURATION);
+}
+
+/// Console command: `lightning` strikes the tile under the camera
+pub fn handle_lightning_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    camera: Single<&Transform, With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    mut strikes: MessageWriter<LightningStrike>,
+) {
+    for command in console_commands.read() {
+        if command.name != "lightning" {
+            continue;
+        }
+
+        let position = origin.to_world_vec2(camera.translation.truncate());
+        strikes.write(LightningStrike(position));
+        console.print(format!("Lightning strikes at ({:.0}, {:.0})", position.x, position.y));
+    }
+}
@@ -0,0 +1,169 @@
+mod catchup;
+mod clock;
+mod fire;
+mod lighting;
+mod lightning;
+mod moon;
+mod random_tick;
+mod season;
+mod temperature;
+mod weather;
+
+pub use catchup::*;
+pub use clock::*;
+pub use fire::*;
+pub use lighting::*;
+pub use lightning::*;
+pub use moon::*;
+pub use random_tick::*;
+pub use season::*;
+pub use temperature::*;
+pub use weather::*;
+
+use crate::console::ConsoleAppExt;
+use crate::net::is_world_authority;
+use crate::theme::ThemedPanel;
+use crate::world::{loader, origin, MetadataSync, WorldMetadata};
+use bevy::prelude::*;
+
+/// How often live environment state is copied into world metadata (seconds)
+const ENVIRONMENT_SYNC_INTERVAL: f32 = 10.0;
+
+/// Plugin for the world's environment: day/night clock, sky tint, moon phases, seasons, weather,
+/// temperature, lightning and fire, and offline catch-up for chunks that were unloaded
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(LightingPlugin)
+            .init_resource::<WorldClock>()
+            .init_resource::<DisplayHour>()
+            .init_resource::<CurrentSeason>()
+            .init_resource::<CurrentMoon>()
+            .init_resource::<WorldWeather>()
+            .init_resource::<ChunkUnloadTimes>()
+            .init_resource::<Fires>()
+            .add_message::<SeasonChanged>()
+            .add_message::<RandomTick>()
+            .add_message::<LightningStrike>()
+            .add_console_command("time", "time [hour] - show or set the time of day")
+            .add_console_command("daylength", "daylength [seconds] - show or set the length of a day")
+            .add_console_command("season", "season [name] - show the season or skip ahead to one")
+            .add_console_command("weather", "weather [clear|rain|fog|wind|storm] - show or set the weather")
+            .add_console_command("lightning", "lightning - strike the tile under the camera")
+            .add_console_command("temperature", "temperature - show the temperature and which trees grow at the camera")
+            .add_systems(Startup, (
+                setup_environment_hud,
+                setup_clock.after(setup_environment_hud),
+                setup_season_text.after(setup_clock),
+                setup_weather.after(setup_season_text),
+                setup_moon_text.after(setup_weather),
+                setup_lightning_flash,
+                setup_lighting,
+                load_chunk_unload_times,
+            ))
+            // After world metadata is loaded
+            .add_systems(PostStartup, restore_environment)
+            .add_systems(Update, (
+                advance_clock,
+                handle_clock_commands,
+                update_moon.after(advance_clock).after(handle_clock_commands),
+                update_lighting.after(update_moon).after(origin::shift_world_origin),
+                update_clock_text.after(advance_clock).after(handle_clock_commands),
+                handle_season_commands,
+                update_season.after(advance_clock).after(handle_season_commands),
+                update_season_text.after(update_season),
+                announce_season.after(update_season),
+                apply_season_tint.after(update_season).after(loader::apply_tile_modifications),
+                random_tick.run_if(is_world_authority),
+                regrow_grass.after(random_tick).run_if(not(in_season(Season::Winter))),
+            ))
+            .add_systems(Update, (
+                handle_weather_commands,
+                handle_temperature_commands,
+                update_weather.after(update_season).after(handle_weather_commands),
+                update_weather_display.after(update_weather),
+                animate_weather_particles.after(update_weather),
+                sync_environment.after(update_weather),
+                catch_up_reloaded_chunks
+                    .run_if(is_world_authority)
+                    .after(advance_clock)
+                    .after(loader::unload_distant_chunks)
+                    .before(loader::apply_tile_modifications),
+            ))
+            .add_systems(Update, (
+                handle_lightning_commands,
+                strike_lightning.after(update_weather).run_if(is_world_authority),
+                apply_lightning_strikes
+                    .after(strike_lightning)
+                    .after(handle_lightning_commands)
+                    .before(loader::apply_tile_modifications),
+                animate_lightning_flash.after(apply_lightning_strikes),
+                update_fires
+                    .run_if(is_world_authority)
+                    .after(apply_lightning_strikes)
+                    .before(loader::apply_tile_modifications),
+                update_fire_sprites.after(update_fires),
+                update_moon_text.after(update_moon),
+                spawn_moon_glows.after(update_moon).after(random_tick),
+                animate_moon_glows.after(spawn_moon_glows),
+                update_light_map
+                    .after(update_fires)
+                    .after(animate_moon_glows)
+                    .after(origin::shift_world_origin),
+            ));
+    }
+}
+
+/// Marker component for the environment HUD row (bottom center)
+/// Clock, season and weather widgets are spawned as its children
+#[derive(Component)]
+pub struct EnvironmentHud;
+
+fn setup_environment_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_child((
+            EnvironmentHud,
+            ThemedPanel,
+            Node {
+                column_gap: Val::Px(16.0),
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            BorderRadius::all(Val::Px(4.0)),
+            Pickable::IGNORE,
+        ));
+}
+
+/// Picks up the environment state saved with the world
+fn restore_environment(
+    metadata: Res<WorldMetadata>,
+    mut clock: ResMut<WorldClock>,
+    mut season: ResMut<CurrentSeason>,
+    mut weather: ResMut<WorldWeather>,
+) {
+    clock.0 = metadata.clock.clone();
+    *weather = WorldWeather::from_state(&metadata.weather);
+    // Set directly so loading a world doesn't announce its season as new
+    season.0 = Season::for_day(clock.0.day, clock.0.days_per_season);
+}
+
+/// Copies environment state into world metadata periodically and when the window closes
+fn sync_environment(mut sync: MetadataSync, clock: Res<WorldClock>, weather: Res<WorldWeather>) {
+    if !sync.due(ENVIRONMENT_SYNC_INTERVAL) {
+        return;
+    }
+    sync.update(|metadata| &mut metadata.clock, clock.0.clone());
+    sync.update(|metadata| &mut metadata.weather, weather.to_state());
+}
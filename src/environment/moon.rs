@@ -0,0 +1,222 @@
+use super::{EnvironmentHud, RandomTick, WorldClock};
+use crate::tiles::{TILE_GRASS, TILE_SIZE};
+use crate::world::WorldOrigin;
+use bevy::prelude::*;
+
+/// Days from one full moon to the next
+pub const LUNAR_CYCLE_DAYS: f32 = 8.0;
+
+/// Nocturnal creatures move up to this much faster under a full moon
+const FULL_MOON_ACTIVITY_BOOST: f32 = 0.5;
+
+/// Extra night darkness (overlay alpha) under a new moon, and light under a full moon
+const NEW_MOON_DARKNESS: f32 = 0.15;
+const FULL_MOON_LIGHT: f32 = 0.1;
+
+/// Glowing spots appear on grass when the moon is at least this bright
+const GLOW_MIN_ILLUMINATION: f32 = 0.75;
+
+/// How long a glowing spot lasts (seconds) and how many may exist at once
+const GLOW_DURATION: f32 = 12.0;
+const MAX_GLOWS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoonPhase {
+    #[default]
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    pub const ALL: [MoonPhase; 8] = [
+        MoonPhase::New,
+        MoonPhase::WaxingCrescent,
+        MoonPhase::FirstQuarter,
+        MoonPhase::WaxingGibbous,
+        MoonPhase::Full,
+        MoonPhase::WaningGibbous,
+        MoonPhase::LastQuarter,
+        MoonPhase::WaningCrescent,
+    ];
+
+    /// Phase at a point in the cycle (0.0 = new, 0.5 = full)
+    pub fn from_cycle(cycle: f32) -> Self {
+        let index = (cycle.rem_euclid(1.0) * Self::ALL.len() as f32).round() as usize;
+        Self::ALL[index % Self::ALL.len()]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "New moon",
+            MoonPhase::WaxingCrescent => "Waxing crescent",
+            MoonPhase::FirstQuarter => "First quarter",
+            MoonPhase::WaxingGibbous => "Waxing gibbous",
+            MoonPhase::Full => "Full moon",
+            MoonPhase::WaningGibbous => "Waning gibbous",
+            MoonPhase::LastQuarter => "Last quarter",
+            MoonPhase::WaningCrescent => "Waning crescent",
+        }
+    }
+}
+
+/// Position in the lunar cycle (0.0 = new moon, 0.5 = full moon) at a day and time of day
+pub fn lunar_cycle(day: u32, time_of_day: f32) -> f32 {
+    ((day as f32 + time_of_day) / LUNAR_CYCLE_DAYS).rem_euclid(1.0)
+}
+
+/// Lit fraction of the moon (0.0 new, 1.0 full)
+pub fn illumination(cycle: f32) -> f32 {
+    (1.0 - (cycle * std::f32::consts::TAU).cos()) / 2.0
+}
+
+/// How much of `hour` is night (0.0 by day, 1.0 from 21:00 to 05:00, ramping through dusk and dawn)
+/// Matches the sky tint keyframes
+pub fn night_amount(hour: f32) -> f32 {
+    let hour = hour.rem_euclid(24.0);
+    if !(5.0..21.0).contains(&hour) {
+        1.0
+    } else if hour < 6.5 {
+        1.0 - (hour - 5.0) / 1.5
+    } else if hour >= 19.0 {
+        (hour - 19.0) / 2.0
+    } else {
+        0.0
+    }
+}
+
+/// The moon tonight, derived from the world clock
+/// Systems that depend on the moon read this, like `CurrentSeason`
+#[derive(Resource, Default)]
+pub struct CurrentMoon {
+    pub phase: MoonPhase,
+    pub illumination: f32,
+    /// How much of the current hour is night (see `night_amount`)
+    pub night: f32,
+}
+
+impl CurrentMoon {
+    /// Speed multiplier for nocturnal creatures (snails): up to 1.5 on a full-moon night
+    pub fn nocturnal_activity(&self) -> f32 {
+        1.0 + FULL_MOON_ACTIVITY_BOOST * self.illumination * self.night
+    }
+
+    /// Added to the sky overlay alpha: darker nights under a new moon, lighter under a full one
+    pub fn darkness_offset(&self) -> f32 {
+        self.night * (NEW_MOON_DARKNESS * (1.0 - self.illumination) - FULL_MOON_LIGHT * self.illumination)
+    }
+}
+
+/// Marker component for the moon phase text in the environment HUD
+#[derive(Component)]
+pub struct MoonText;
+
+/// A glowing spot on grass on bright moonlit nights
+#[derive(Component)]
+pub struct MoonGlow {
+    remaining: f32,
+}
+
+pub fn setup_moon_text(mut commands: Commands, hud: Single<Entity, With<EnvironmentHud>>) {
+    commands.entity(*hud).with_child((
+        MoonText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.85, 0.88, 1.0)),
+    ));
+}
+
+pub fn update_moon(clock: Res<WorldClock>, mut moon: ResMut<CurrentMoon>) {
+    let cycle = lunar_cycle(clock.0.day, clock.0.time_of_day);
+    moon.phase = MoonPhase::from_cycle(cycle);
+    moon.illumination = illumination(cycle);
+    moon.night = night_amount(clock.hour());
+}
+
+pub fn update_moon_text(moon: Res<CurrentMoon>, mut text: Single<&mut Text, With<MoonText>>) {
+    let label = moon.phase.label();
+    if text.0 != label {
+        text.0 = label.to_string();
+    }
+}
+
+/// On bright moonlit nights, random ticks landing on grass light up glowing spots that fade out again
+pub fn spawn_moon_glows(
+    mut commands: Commands,
+    mut ticks: MessageReader<RandomTick>,
+    moon: Res<CurrentMoon>,
+    origin: Res<WorldOrigin>,
+    glows: Query<(), With<MoonGlow>>,
+) {
+    if moon.illumination < GLOW_MIN_ILLUMINATION || moon.night < 1.0 {
+        ticks.clear();
+        return;
+    }
+
+    let mut count = glows.iter().count();
+    for tick in ticks.read() {
+        if tick.ground != TILE_GRASS || count >= MAX_GLOWS {
+            continue;
+        }
+        count += 1;
+        let position = origin.to_render(tick.position);
+        commands.spawn((
+            MoonGlow {
+                remaining: GLOW_DURATION * (0.5 + tick.roll),
+            },
+            Sprite::from_color(Color::srgba(0.7, 1.0, 0.6, 0.0), Vec2::splat(TILE_SIZE * 0.5)),
+            Transform::from_xyz(position.x, position.y, 0.6),
+        ));
+    }
+}
+
+/// Pulses glowing spots and removes them once they fade (or the night ends)
+pub fn animate_moon_glows(
+    mut commands: Commands,
+    time: Res<Time>,
+    moon: Res<CurrentMoon>,
+    mut glows: Query<(Entity, &mut MoonGlow, &mut Sprite)>,
+) {
+    for (entity, mut glow, mut sprite) in glows.iter_mut() {
+        glow.remaining -= time.delta_secs();
+        if glow.remaining <= 0.0 || moon.night <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let pulse = 0.5 + 0.5 * (glow.remaining * 3.0).sin();
+        let fade = (glow.remaining / 2.0).min(1.0) * moon.night;
+        sprite.color = Color::srgba(0.7, 1.0, 0.6, 0.8 * pulse * fade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lunar_cycle() {
+        assert_eq!(MoonPhase::from_cycle(lunar_cycle(0, 0.0)), MoonPhase::New);
+        assert_eq!(MoonPhase::from_cycle(lunar_cycle(4, 0.0)), MoonPhase::Full);
+        assert_eq!(MoonPhase::from_cycle(lunar_cycle(8, 0.0)), MoonPhase::New);
+        assert_eq!(MoonPhase::from_cycle(lunar_cycle(2, 0.0)), MoonPhase::FirstQuarter);
+        assert!((illumination(0.5) - 1.0).abs() < 1e-6);
+        assert!(illumination(0.0).abs() < 1e-6);
+
+        // Full moon nights are lighter and livelier, new moon nights darker; days are unaffected
+        let full_night = CurrentMoon { phase: MoonPhase::Full, illumination: 1.0, night: night_amount(23.0) };
+        let new_night = CurrentMoon { phase: MoonPhase::New, illumination: 0.0, night: night_amount(2.0) };
+        let full_day = CurrentMoon { night: night_amount(12.0), ..full_night };
+        assert!(full_night.darkness_offset() < 0.0 && new_night.darkness_offset() > 0.0);
+        assert_eq!(full_night.nocturnal_activity(), 1.0 + FULL_MOON_ACTIVITY_BOOST);
+        assert_eq!(full_day.nocturnal_activity(), 1.0);
+        assert_eq!(full_day.darkness_offset(), 0.0);
+    }
+}
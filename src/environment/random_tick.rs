@@ -0,0 +1,58 @@
+use crate::governor::SimulationGovernor;
+use crate::tiles::{ChunkPos, TileId, CHUNK_PIXEL_SIZE, CHUNK_SIZE, LAYER_GROUND, TILE_SIZE};
+use crate::world::WorldManager;
+use bevy::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Seconds between random tick passes
+pub const RANDOM_TICK_INTERVAL: f32 = 5.0;
+
+/// One randomly sampled ground tile of a loaded chunk
+/// Every pass samples one tile per loaded chunk; slow world processes (grass regrowth,
+/// tree propagation) react to the ticks that land on tiles they care about
+#[derive(Message, Debug, Clone, Copy)]
+pub struct RandomTick {
+    pub chunk: ChunkPos,
+    pub ground: TileId,
+    /// World position of the tile center (pixels)
+    pub position: Vec2,
+    /// Uniform random number in [0, 1) for the consumer's chance roll
+    pub roll: f32,
+}
+
+/// Samples one ground tile per loaded chunk every `RANDOM_TICK_INTERVAL`
+/// (stretched by the governor's tick multiplier when frames run over budget)
+pub fn random_tick(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    governor: Res<SimulationGovernor>,
+    world: Res<WorldManager>,
+    mut ticks: MessageWriter<RandomTick>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < RANDOM_TICK_INTERVAL * governor.tick_multiplier() {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let hasher_builder = RandomState::new();
+    for (chunk_pos, chunk_data) in world.chunk_cache.iter() {
+        if !world.is_loaded(chunk_pos) {
+            continue;
+        }
+
+        let hash = hasher_builder.hash_one(chunk_pos);
+        let index = (hash % (CHUNK_SIZE * CHUNK_SIZE) as u64) as usize;
+        let origin = chunk_pos.to_world(CHUNK_PIXEL_SIZE);
+        ticks.write(RandomTick {
+            chunk: *chunk_pos,
+            ground: chunk_data.layers[LAYER_GROUND][index],
+            position: Vec2::new(
+                origin.x + ((index % CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+                origin.y + ((index / CHUNK_SIZE) as f32 + 0.5) * TILE_SIZE,
+            ),
+            roll: ((hash >> 32) as f32) / (u32::MAX as f32),
+        });
+    }
+}
@@ -0,0 +1,253 @@
+use super::{tile_at, EnvironmentHud, RandomTick, WorldClock};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::TileTraffic;
+use crate::locale::Locale;
+use crate::tiles::{Chunk, LAYER_GROUND, TILE_DIRT, TILE_GRASS};
+use crate::toast::Toast;
+use crate::world::TileEdits;
+use bevy::prelude::*;
+use bevy::sprite_render::TilemapChunkTileData;
+
+/// The four seasons, in calendar order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    /// Season of a (zero-based) day
+    pub fn for_day(day: u32, days_per_season: u32) -> Self {
+        Self::ALL[(day / days_per_season.max(1)) as usize % Self::ALL.len()]
+    }
+
+    /// Zero-based year of a (zero-based) day
+    pub fn year_for_day(day: u32, days_per_season: u32) -> u32 {
+        day / (days_per_season.max(1) * Self::ALL.len() as u32)
+    }
+
+    /// "Spring 3, Year 1" (days and years are shown starting from 1)
+    pub fn date_label(day: u32, days_per_season: u32) -> String {
+        format!(
+            "{} {}, Year {}",
+            Self::for_day(day, days_per_season).label(),
+            day % days_per_season.max(1) + 1,
+            Self::year_for_day(day, days_per_season) + 1
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|season| season.label().eq_ignore_ascii_case(name))
+    }
+
+    /// Multiplier applied to tree growth speed
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Season::Spring => 1.5,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.5,
+            Season::Winter => 0.0,
+        }
+    }
+
+    /// Chance that a random tick landing on dirt turns it back into grass
+    pub fn grass_regrowth_chance(&self) -> f32 {
+        match self {
+            Season::Spring => 0.5,
+            Season::Summer => 0.25,
+            Season::Autumn => 0.05,
+            Season::Winter => 0.0,
+        }
+    }
+
+    /// Added to every point of the temperature map (degrees)
+    pub fn temperature_offset(&self) -> f32 {
+        match self {
+            Season::Spring => 0.0,
+            Season::Summer => 6.0,
+            Season::Autumn => -2.0,
+            Season::Winter => -8.0,
+        }
+    }
+
+    /// Tint applied to ground tiles
+    pub fn ground_tint(&self) -> Color {
+        match self {
+            Season::Spring => Color::srgb(0.9, 1.0, 0.85),
+            Season::Summer => Color::WHITE,
+            Season::Autumn => Color::srgb(1.0, 0.8, 0.55),
+            Season::Winter => Color::srgb(0.8, 0.85, 0.95),
+        }
+    }
+}
+
+/// The current season, derived from the world clock
+/// Systems that depend on the season read this (or use `in_season` as a run condition)
+#[derive(Resource, Default)]
+pub struct CurrentSeason(pub Season);
+
+/// Sent when the calendar moves into a new season
+#[derive(Message, Debug, Clone, Copy)]
+pub struct SeasonChanged(pub Season);
+
+/// Run condition: only run during `season`
+pub fn in_season(season: Season) -> impl Fn(Res<CurrentSeason>) -> bool {
+    move |current: Res<CurrentSeason>| current.0 == season
+}
+
+/// Marker component for the season text in the environment HUD
+#[derive(Component)]
+pub struct SeasonText;
+
+pub fn setup_season_text(mut commands: Commands, hud: Single<Entity, With<EnvironmentHud>>) {
+    commands.entity(*hud).with_child((
+        SeasonText,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+/// Moves to the next season when the clock crosses into it
+pub fn update_season(
+    clock: Res<WorldClock>,
+    mut season: ResMut<CurrentSeason>,
+    mut season_changed: MessageWriter<SeasonChanged>,
+) {
+    let current = Season::for_day(clock.0.day, clock.0.days_per_season);
+    if season.0 != current {
+        season.0 = current;
+        season_changed.write(SeasonChanged(current));
+    }
+}
+
+pub fn announce_season(
+    mut season_changed: MessageReader<SeasonChanged>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    for SeasonChanged(season) in season_changed.read() {
+        let season = season.label().to_lowercase();
+        toasts.write(Toast::info(locale.format("toast-season-arrived", &[("season", season.into())])));
+    }
+}
+
+/// Shows the season, the day within it and the year
+pub fn update_season_text(
+    clock: Res<WorldClock>,
+    season: Res<CurrentSeason>,
+    mut text: Single<&mut Text, With<SeasonText>>,
+) {
+    let days_per_season = clock.0.days_per_season.max(1);
+    let label = format!(
+        "{} (day {}/{}), Year {}",
+        season.0.label(),
+        clock.0.day % days_per_season + 1,
+        days_per_season,
+        Season::year_for_day(clock.0.day, days_per_season) + 1
+    );
+    if text.0 != label {
+        text.0 = label;
+    }
+}
+
+/// Tints ground tiles for the season
+/// Retints every loaded chunk when the season changes, and newly loaded or edited chunks otherwise
+pub fn apply_season_tint(season: Res<CurrentSeason>, mut chunks: Query<(&Chunk, Mut<TilemapChunkTileData>)>) {
+    let tint = season.0.ground_tint();
+    for (chunk, mut tile_data) in chunks.iter_mut() {
+        if chunk.layer != LAYER_GROUND || !(season.is_changed() || tile_data.is_changed()) {
+            continue;
+        }
+
+        // Only write when something differs, so retinting doesn't re-trigger itself next frame
+        if tile_data.0.iter().flatten().any(|tile| tile.color != tint) {
+            for tile in tile_data.0.iter_mut().flatten() {
+                tile.color = tint;
+            }
+        }
+    }
+}
+
+/// Dirt slowly turns back into grass, fastest in spring (doesn't run in winter)
+/// Worn paths stay bare while they're still in use (see `TileTraffic`)
+pub fn regrow_grass(
+    mut ticks: MessageReader<RandomTick>,
+    season: Res<CurrentSeason>,
+    traffic: Res<TileTraffic>,
+    mut tile_edits: TileEdits,
+) {
+    let chance = season.0.grass_regrowth_chance();
+    for tick in ticks.read() {
+        if tick.ground == TILE_DIRT && tick.roll < chance && !traffic.is_worn(tile_at(tick.position)) {
+            tile_edits.set_ground(tick.position, TILE_GRASS);
+        }
+    }
+}
+
+/// Console command: `season [name]` shows the season or jumps to the start of the named one
+pub fn handle_season_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut clock: ResMut<WorldClock>,
+    season: Res<CurrentSeason>,
+) {
+    for command in console_commands.read() {
+        if command.name != "season" {
+            continue;
+        }
+
+        match command.args.first().map(|name| Season::from_name(name)) {
+            None => console.print(format!("It is {}", season.0.label())),
+            Some(Some(target)) if target == season.0 => console.print(format!("It is already {}", target.label())),
+            Some(Some(target)) => {
+                // Skip forward to the first day of the next `target` season
+                let days_per_season = clock.0.days_per_season.max(1);
+                let mut day = (clock.0.day / days_per_season + 1) * days_per_season;
+                while Season::for_day(day, days_per_season) != target {
+                    day += days_per_season;
+                }
+                clock.0.day = day;
+                console.print(format!("Skipped to {} (day {})", target.label(), day + 1));
+            }
+            Some(None) => console.print("Usage: season [spring|summer|autumn|winter]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_season_for_day() {
+        assert_eq!(Season::for_day(0, 7), Season::Spring);
+        assert_eq!(Season::for_day(6, 7), Season::Spring);
+        assert_eq!(Season::for_day(7, 7), Season::Summer);
+        assert_eq!(Season::for_day(27, 7), Season::Winter);
+        // Wraps into the next year
+        assert_eq!(Season::for_day(28, 7), Season::Spring);
+        // A zero-length season is treated as one day
+        assert_eq!(Season::for_day(2, 0), Season::Autumn);
+        assert_eq!(Season::from_name("WINTER"), Some(Season::Winter));
+        assert_eq!(Season::year_for_day(28, 7), 1);
+        assert_eq!(Season::date_label(30, 7), "Spring 3, Year 2");
+    }
+}
@@ -0,0 +1,229 @@
+use super::{CurrentSeason, Season};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::TreeVariant;
+use crate::tiles::{chunk::coords, TileId, LAYER_GROUND, TILE_CLAY, TILE_DIRT, TILE_ROCK, TILE_SAND, TILE_SIZE, TILE_STONE, TILE_WATER};
+use crate::world::{Biome, Climate, WorldManager, WorldOrigin};
+use bevy::prelude::*;
+use std::fmt;
+
+/// Pixels between two warm (or two cold) latitude bands, running east-west
+const LATITUDE_PERIOD: f32 = 8192.0;
+
+/// Size of the local warm and cold patches layered on top of latitude (pixels)
+const TEMPERATURE_NOISE_SCALE: f32 = 1024.0;
+
+/// Pines only take root at or below this temperature
+pub const COLD_TEMPERATURE: f32 = 5.0;
+
+/// Willows need water within this many tiles
+pub const WILLOW_WATER_RADIUS: i32 = 6;
+
+/// Tiles around a planting spot whose ground counts towards its soil
+pub const SOIL_RADIUS: i32 = 2;
+
+/// Trees don't take root where more of the ground around is bare (dirt, sand, rock, water or a deposit)
+pub const MAX_BARE_SHARE: f32 = 0.5;
+
+/// Why a tree variant can't grow somewhere
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrowthLimit {
+    TooWarm(f32),
+    NoWater,
+    /// Share of bare ground around the spot
+    BareGround(f32),
+}
+
+impl fmt::Display for GrowthLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrowthLimit::TooWarm(temperature) => {
+                write!(f, "too warm ({:.0}°, needs {:.0}° or colder)", temperature, COLD_TEMPERATURE)
+            }
+            GrowthLimit::NoWater => write!(f, "no water within {} tiles", WILLOW_WATER_RADIUS),
+            GrowthLimit::BareGround(share) => write!(f, "bare ground ({:.0}% of the soil around)", share * 100.0),
+        }
+    }
+}
+
+/// Temperature at a world position (degrees) for a season
+/// Latitude bands alternate warm and cold every `LATITUDE_PERIOD / 2` pixels north or south of
+/// the origin, with smooth local noise on top; the season shifts the whole map
+pub fn temperature_at(position: Vec2, season: Season) -> f32 {
+    let latitude = (position.y / LATITUDE_PERIOD * std::f32::consts::TAU).cos();
+    let local = value_noise(position / TEMPERATURE_NOISE_SCALE) * 2.0 - 1.0;
+    12.0 + 12.0 * latitude + 5.0 * local + season.temperature_offset()
+}
+
+/// Whether `variant` can grow at `temperature` (pines need the cold, willows need water)
+pub fn check_viability(variant: TreeVariant, temperature: f32, near_water: bool) -> Result<(), GrowthLimit> {
+    match variant {
+        TreeVariant::Pine if temperature > COLD_TEMPERATURE => Err(GrowthLimit::TooWarm(temperature)),
+        TreeVariant::Willow if !near_water => Err(GrowthLimit::NoWater),
+        _ => Ok(()),
+    }
+}
+
+/// Whether any loaded ground tile within `WILLOW_WATER_RADIUS` of `position` is water
+pub fn is_near_water(world: &WorldManager, position: Vec2) -> bool {
+    for dy in -WILLOW_WATER_RADIUS..=WILLOW_WATER_RADIUS {
+        for dx in -WILLOW_WATER_RADIUS..=WILLOW_WATER_RADIUS {
+            if dx * dx + dy * dy > WILLOW_WATER_RADIUS * WILLOW_WATER_RADIUS {
+                continue;
+            }
+            let sample = position + Vec2::new(dx as f32, dy as f32) * TILE_SIZE;
+            let Some(chunk) = world.get_cached_chunk(&coords::world_to_chunk(sample)) else {
+                continue;
+            };
+            let (x, y) = coords::world_to_local_tile(sample);
+            if chunk.get_tile(LAYER_GROUND, x, y) == Some(TILE_WATER) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Whether `variant` can grow at `position` this season
+pub fn tree_viability(
+    world: &WorldManager,
+    season: Season,
+    variant: TreeVariant,
+    position: Vec2,
+) -> Result<(), GrowthLimit> {
+    // Only willows care about water, and the lookup scans ~100 tiles
+    let near_water = variant == TreeVariant::Willow && is_near_water(world, position);
+    check_viability(variant, temperature_at(position, season), near_water)
+}
+
+/// Whether the ground around a spot is fertile enough for a tree (see `MAX_BARE_SHARE`)
+pub fn check_soil(ground: impl IntoIterator<Item = TileId>) -> Result<(), GrowthLimit> {
+    let (mut bare, mut total) = (0, 0);
+    for tile_id in ground {
+        total += 1;
+        if matches!(tile_id, TILE_DIRT | TILE_SAND | TILE_ROCK | TILE_WATER | TILE_STONE | TILE_CLAY) {
+            bare += 1;
+        }
+    }
+    let share = bare as f32 / total.max(1) as f32;
+    if share > MAX_BARE_SHARE {
+        Err(GrowthLimit::BareGround(share))
+    } else {
+        Ok(())
+    }
+}
+
+/// Soil within `SOIL_RADIUS` tiles of `position`, read across chunk borders like a footprint
+/// (unreadable tiles are skipped)
+pub fn soil_viability(world: &mut WorldManager, position: Vec2) -> Result<(), GrowthLimit> {
+    let center = (position / TILE_SIZE).floor().as_ivec2();
+    let mut ground = Vec::new();
+    for dy in -SOIL_RADIUS..=SOIL_RADIUS {
+        for dx in -SOIL_RADIUS..=SOIL_RADIUS {
+            ground.extend(world.ground_tile_at(center + IVec2::new(dx, dy)));
+        }
+    }
+    check_soil(ground)
+}
+
+/// Variant the land favours, if any: pines where it's cold or rocky, willows by water and in marshes
+pub fn preferred_variant(biome: Option<Biome>, temperature: f32, near_water: bool) -> Option<TreeVariant> {
+    if temperature <= COLD_TEMPERATURE || (biome == Some(Biome::Rocky) && temperature <= COLD_TEMPERATURE + 10.0) {
+        Some(TreeVariant::Pine)
+    } else if near_water || biome == Some(Biome::Marsh) {
+        Some(TreeVariant::Willow)
+    } else {
+        None
+    }
+}
+
+/// Smooth noise in [0, 1): hashed lattice values blended with smoothstep
+fn value_noise(point: Vec2) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (Vec2::splat(3.0) - 2.0 * t);
+    let corner = |dx: i32, dy: i32| lattice_value(cell.x as i32 + dx, cell.y as i32 + dy);
+
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * t.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+/// Hash of a lattice point as a number in [0, 1) (splitmix64 finalizer)
+fn lattice_value(x: i32, y: i32) -> f32 {
+    let mut z = ((x as u32 as u64) << 32 | y as u32 as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Console command: `temperature` shows the temperature at the camera, which trees can grow there,
+/// the climate and the biome
+pub fn handle_temperature_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    camera: Single<&Transform, With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    season: Res<CurrentSeason>,
+    world: Res<WorldManager>,
+    climate: Res<Climate>,
+) {
+    for command in console_commands.read() {
+        if command.name != "temperature" {
+            continue;
+        }
+
+        let position = origin.to_world_vec2(camera.translation.truncate());
+        let viable: Vec<String> = TreeVariant::ALL
+            .into_iter()
+            .filter(|variant| tree_viability(&world, season.0, *variant, position).is_ok())
+            .map(|variant| variant.as_str().to_string())
+            .collect();
+        console.print(format!(
+            "Temperature: {:.1}° ({}), trees that grow here: {}",
+            temperature_at(position, season.0),
+            season.0.label(),
+            if viable.is_empty() { "none".to_string() } else { viable.join(", ") }
+        ));
+        let sample = climate.sample(position);
+        console.print(format!("Climate: warmth {:.2}, moisture {:.2}", sample.temperature, sample.moisture));
+        if let Some(biome) = world.biome_at(position) {
+            console.print(format!("Biome: {} (tree density x{})", biome, biome.tree_density()));
+        }
+        if let Some(structure) = world.structure_at(position) {
+            console.print(format!("Structure: {} (tree density x{})", structure.kind, structure.kind.tree_density()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TILE_GRASS;
+
+    #[test]
+    fn test_temperature_gates_variants() {
+        // Warm band at the origin, cold band half a period north; winter is colder than summer
+        let origin = Vec2::ZERO;
+        let north = Vec2::new(0.0, LATITUDE_PERIOD / 2.0);
+        assert!(temperature_at(origin, Season::Summer) > temperature_at(north, Season::Summer) + 10.0);
+        assert!(temperature_at(north, Season::Winter) < temperature_at(north, Season::Summer));
+
+        assert!(check_viability(TreeVariant::Pine, temperature_at(north, Season::Winter), false).is_ok());
+        assert!(matches!(
+            check_viability(TreeVariant::Pine, temperature_at(origin, Season::Summer), false),
+            Err(GrowthLimit::TooWarm(_))
+        ));
+        assert_eq!(check_viability(TreeVariant::Willow, 20.0, false), Err(GrowthLimit::NoWater));
+        assert!(check_viability(TreeVariant::Willow, 20.0, true).is_ok());
+        assert!(check_viability(TreeVariant::Oak, -5.0, false).is_ok());
+
+        // Trees need soil: mostly grass is fine, mostly dirt or water isn't
+        assert!(check_soil([TILE_GRASS, TILE_GRASS, TILE_DIRT]).is_ok());
+        assert!(matches!(check_soil([TILE_DIRT, TILE_WATER, TILE_GRASS]), Err(GrowthLimit::BareGround(_))));
+        assert_eq!(preferred_variant(Some(Biome::Forest), -2.0, true), Some(TreeVariant::Pine));
+        assert_eq!(preferred_variant(Some(Biome::Marsh), 20.0, false), Some(TreeVariant::Willow));
+        assert_eq!(preferred_variant(Some(Biome::Plains), 20.0, true), Some(TreeVariant::Willow));
+        assert_eq!(preferred_variant(None, 20.0, false), None);
+    }
+}
@@ -0,0 +1,371 @@
+use super::{CurrentSeason, EnvironmentHud, Season};
+use crate::config::GameConfig;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::theme::KeepColors;
+use crate::world::WeatherState;
+use bevy::prelude::*;
+
+/// Shortest and longest stretch of one kind of weather (seconds)
+const MIN_WEATHER_DURATION: f32 = 60.0;
+const MAX_WEATHER_DURATION: f32 = 240.0;
+
+/// Number of rain drops / wind streaks on screen
+const PARTICLE_COUNT: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+    Wind,
+    /// Dry thunderstorm: wind and lightning, no rain to put out the fires it starts
+    Storm,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 5] = [Weather::Clear, Weather::Rain, Weather::Fog, Weather::Wind, Weather::Storm];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Fog => "fog",
+            Weather::Wind => "wind",
+            Weather::Storm => "storm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|weather| weather.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Weather::Clear => "Clear",
+            Weather::Rain => "Rain",
+            Weather::Fog => "Fog",
+            Weather::Wind => "Windy",
+            Weather::Storm => "Thunderstorm",
+        }
+    }
+
+    /// Color of the HUD icon
+    pub fn icon_color(&self) -> Color {
+        match self {
+            Weather::Clear => Color::srgb(1.0, 0.85, 0.3),
+            Weather::Rain => Color::srgb(0.3, 0.5, 1.0),
+            Weather::Fog => Color::srgb(0.7, 0.7, 0.75),
+            Weather::Wind => Color::srgb(0.6, 0.9, 0.8),
+            Weather::Storm => Color::srgb(0.7, 0.5, 1.0),
+        }
+    }
+
+    /// Multiplier applied to tree growth speed (stacks with the season's)
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Weather::Rain => 2.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Multiplier applied to how actively creatures roam
+    pub fn activity_multiplier(&self) -> f32 {
+        match self {
+            Weather::Fog => 0.4,
+            _ => 1.0,
+        }
+    }
+
+    /// Relative chance of each weather (in `ALL` order) following a change in `season`
+    fn weights(season: Season) -> [f32; 5] {
+        match season {
+            Season::Spring => [4.0, 3.0, 1.0, 2.0, 0.5],
+            Season::Summer => [6.0, 2.0, 0.5, 1.5, 1.0],
+            Season::Autumn => [3.0, 3.0, 2.0, 2.0, 0.5],
+            Season::Winter => [3.0, 1.0, 3.0, 2.0, 0.0],
+        }
+    }
+}
+
+/// Current weather, driven by a seeded RNG
+/// Copied into `WorldMetadata.weather` periodically, RNG state included
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct WorldWeather {
+    pub weather: Weather,
+    /// Seconds until the next change
+    pub remaining: f32,
+    rng_state: u64,
+}
+
+impl Default for WorldWeather {
+    fn default() -> Self {
+        Self::from_state(&WeatherState::default())
+    }
+}
+
+impl WorldWeather {
+    pub fn from_state(state: &WeatherState) -> Self {
+        Self {
+            weather: Weather::from_name(&state.kind).unwrap_or_default(),
+            remaining: state.remaining,
+            rng_state: state.rng_state,
+        }
+    }
+
+    pub fn to_state(&self) -> WeatherState {
+        WeatherState {
+            kind: self.weather.as_str().to_string(),
+            remaining: self.remaining,
+            rng_state: self.rng_state,
+        }
+    }
+
+    /// Next random number in [0, 1) (splitmix64)
+    fn next_random(&mut self) -> f32 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Switch to `weather` for a random duration
+    pub fn set(&mut self, weather: Weather) {
+        self.weather = weather;
+        self.remaining = MIN_WEATHER_DURATION + self.next_random() * (MAX_WEATHER_DURATION - MIN_WEATHER_DURATION);
+    }
+
+    /// Count down and roll new weather when the current one runs out
+    /// Returns the new weather if it changed
+    pub fn advance(&mut self, seconds: f32, season: Season) -> Option<Weather> {
+        self.remaining -= seconds;
+        if self.remaining > 0.0 {
+            return None;
+        }
+
+        let weights = Weather::weights(season);
+        let mut roll = self.next_random() * weights.iter().sum::<f32>();
+        let mut next = Weather::Clear;
+        for (weather, weight) in Weather::ALL.into_iter().zip(weights) {
+            next = weather;
+            if roll < weight {
+                break;
+            }
+            roll -= weight;
+        }
+
+        let previous = self.weather;
+        self.set(next);
+        (next != previous).then_some(next)
+    }
+}
+
+/// Marker component for the full-screen weather overlay (fog tint and particles)
+#[derive(Component)]
+pub struct WeatherOverlay;
+
+/// A rain drop or wind streak, positioned in percent of the screen
+#[derive(Component)]
+pub struct WeatherParticle {
+    x: f32,
+    y: f32,
+    /// Percent of the screen per second
+    speed: f32,
+}
+
+/// Marker components for the weather icon and label in the environment HUD
+#[derive(Component)]
+pub struct WeatherIcon;
+
+#[derive(Component)]
+pub struct WeatherText;
+
+pub fn setup_weather(mut commands: Commands, hud: Single<Entity, With<EnvironmentHud>>) {
+    commands
+        .spawn((
+            WeatherOverlay,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            Pickable::IGNORE,
+            ZIndex(-1),
+        ))
+        .with_children(|overlay| {
+            // Spread particles evenly; stagger them so they don't move in lockstep
+            for i in 0..PARTICLE_COUNT {
+                let t = i as f32 / PARTICLE_COUNT as f32;
+                overlay.spawn((
+                    WeatherParticle {
+                        x: (t * 7919.0) % 100.0,
+                        y: (t * 104_729.0) % 100.0,
+                        speed: 60.0 + (i % 7) as f32 * 8.0,
+                    },
+                    Node {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    BackgroundColor(Color::NONE),
+                    Visibility::Hidden,
+                    Pickable::IGNORE,
+                ));
+            }
+        });
+
+    commands.entity(*hud).with_children(|hud| {
+        hud.spawn(Node {
+            column_gap: Val::Px(6.0),
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                WeatherIcon,
+                Node {
+                    width: Val::Px(10.0),
+                    height: Val::Px(10.0),
+                    ..default()
+                },
+                BackgroundColor(Weather::Clear.icon_color()),
+                BorderRadius::all(Val::Px(5.0)),
+                KeepColors,
+            ));
+            row.spawn((
+                WeatherText,
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+    });
+}
+
+/// Counts down the current weather and rolls the next one
+pub fn update_weather(time: Res<Time>, season: Res<CurrentSeason>, mut weather: ResMut<WorldWeather>) {
+    if let Some(next) = weather.advance(time.delta_secs(), season.0) {
+        info!("Weather changed to {}", next.as_str());
+    }
+}
+
+/// Updates the HUD icon and the fog tint when the weather changes
+pub fn update_weather_display(
+    weather: Res<WorldWeather>,
+    mut icon: Single<&mut BackgroundColor, (With<WeatherIcon>, Without<WeatherOverlay>)>,
+    mut text: Single<&mut Text, With<WeatherText>>,
+    mut overlay: Single<&mut BackgroundColor, (With<WeatherOverlay>, Without<WeatherIcon>)>,
+) {
+    let label = weather.weather.label();
+    if text.0 == label {
+        return;
+    }
+
+    text.0 = label.to_string();
+    icon.0 = weather.weather.icon_color();
+    overlay.0 = match weather.weather {
+        Weather::Fog => Color::srgba(0.75, 0.75, 0.8, 0.35),
+        Weather::Rain => Color::srgba(0.2, 0.25, 0.35, 0.15),
+        Weather::Storm => Color::srgba(0.1, 0.08, 0.2, 0.25),
+        _ => Color::NONE,
+    };
+}
+
+/// Moves rain drops down the screen (or wind streaks across it, in wind and storms); none with
+/// reduced motion (the weather tint stays)
+pub fn animate_weather_particles(
+    time: Res<Time>,
+    weather: Res<WorldWeather>,
+    config: Res<GameConfig>,
+    mut particles: Query<(&mut WeatherParticle, &mut Node, &mut BackgroundColor, &mut Visibility)>,
+) {
+    let delta = time.delta_secs();
+    let weather = if config.accessibility.reduced_motion { Weather::Clear } else { weather.weather };
+    for (mut particle, mut node, mut color, mut visibility) in particles.iter_mut() {
+        match weather {
+            Weather::Rain => {
+                particle.y = (particle.y + particle.speed * delta) % 100.0;
+                node.width = Val::Px(1.5);
+                node.height = Val::Px(10.0);
+                color.0 = Color::srgba(0.6, 0.7, 1.0, 0.6);
+            }
+            Weather::Wind | Weather::Storm => {
+                particle.x = (particle.x + particle.speed * 1.5 * delta) % 100.0;
+                node.width = Val::Px(18.0);
+                node.height = Val::Px(1.0);
+                color.0 = Color::srgba(1.0, 1.0, 1.0, 0.25);
+            }
+            _ => {
+                if *visibility != Visibility::Hidden {
+                    *visibility = Visibility::Hidden;
+                }
+                continue;
+            }
+        }
+        node.left = Val::Percent(particle.x);
+        node.top = Val::Percent(particle.y);
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// Console command: `weather [clear|rain|fog|wind|storm]` shows or sets the weather
+pub fn handle_weather_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut weather: ResMut<WorldWeather>,
+) {
+    for command in console_commands.read() {
+        if command.name != "weather" {
+            continue;
+        }
+
+        match command.args.first().map(|name| Weather::from_name(name)) {
+            None => console.print(format!(
+                "Weather: {} ({:.0}s left)",
+                weather.weather.label(),
+                weather.remaining
+            )),
+            Some(Some(next)) => {
+                weather.set(next);
+                console.print(format!("Weather set to {} for {:.0}s", next.label(), weather.remaining));
+            }
+            Some(None) => console.print("Usage: weather [clear|rain|fog|wind|storm]"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_is_seeded() {
+        let state = WeatherState {
+            remaining: 0.0,
+            ..default()
+        };
+        let mut a = WorldWeather::from_state(&state);
+        let mut b = WorldWeather::from_state(&state);
+
+        let mut changes = 0;
+        for _ in 0..200 {
+            let changed = a.advance(MAX_WEATHER_DURATION, Season::Autumn);
+            assert_eq!(changed, b.advance(MAX_WEATHER_DURATION, Season::Autumn));
+            assert!(a.remaining >= MIN_WEATHER_DURATION - MAX_WEATHER_DURATION);
+            changes += changed.is_some() as u32;
+        }
+        assert!(changes > 50);
+
+        // Round trips through the saved state, RNG included
+        let mut restored = WorldWeather::from_state(&a.to_state());
+        assert_eq!(restored, a);
+        assert_eq!(restored.advance(1000.0, Season::Winter), a.advance(1000.0, Season::Winter));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::config::ConfigError;
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use crate::debug_hud::DebugHudLines;
+use crate::locale::Locale;
+use crate::mods::ModError;
+use crate::tiles::{ChunkPos, TileId};
+use crate::toast::Toast;
+use crate::world::archive::ArchiveError;
+use crate::world::dump::DumpError;
+use crate::world::metadata::MetadataError;
+use crate::world::serialization::SerializationError;
+use crate::world::worlds::WorldDirError;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Failures kept for the `errors` console command
+const ERROR_LOG_SIZE: usize = 50;
+
+/// Anything that can go wrong reading, writing or building the world
+/// The module error types convert into it with `?`/`into()`, so systems report them all one way (see `Failures`)
+#[derive(Debug, Error)]
+pub enum WorldError {
+    #[error("{0}")]
+    Serialization(#[from] SerializationError),
+    #[error("{0}")]
+    Metadata(#[from] MetadataError),
+    #[error("{0}")]
+    Dump(#[from] DumpError),
+    #[error("{0}")]
+    Archive(#[from] ArchiveError),
+    #[error("{0}")]
+    WorldDir(#[from] WorldDirError),
+    #[error("{0}")]
+    Config(#[from] ConfigError),
+    #[error("{0}")]
+    Mod(#[from] ModError),
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    /// A tile id no `TileRegistry` entry covers (a missing mod pack, or a damaged file)
+    #[error("chunk ({}, {}) has unknown tile id {tile}", chunk.x, chunk.y)]
+    UnknownTile { chunk: ChunkPos, tile: TileId },
+    /// The generator produced a chunk the game can't use
+    #[error("generating chunk ({}, {}): {reason}", chunk.x, chunk.y)]
+    Generation { chunk: ChunkPos, reason: String },
+}
+
+/// A failure on its way to the log, the toasts and the `errors` command
+#[derive(Message, Debug)]
+pub struct Failure {
+    /// What was being done ("Failed to save chunk (1, 2)")
+    pub context: String,
+    pub error: WorldError,
+}
+
+/// Reports failures from a system: `failures.report("Failed to save chunk", e)`
+#[derive(SystemParam)]
+pub struct Failures<'w> {
+    writer: MessageWriter<'w, Failure>,
+}
+
+impl Failures<'_> {
+    pub fn report(&mut self, context: impl Into<String>, error: impl Into<WorldError>) {
+        self.writer.write(Failure {
+            context: context.into(),
+            error: error.into(),
+        });
+    }
+}
+
+/// Recent failures, newest last
+#[derive(Resource, Default)]
+pub struct ErrorLog {
+    pub entries: VecDeque<String>,
+    /// Failures since startup (including ones dropped from `entries`)
+    pub total: usize,
+}
+
+/// Plugin for surfacing failures: logged, shown as a toast, counted in the debug HUD and listed by `errors`
+pub struct ErrorPlugin;
+
+impl Plugin for ErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<Failure>()
+            .init_resource::<ErrorLog>()
+            .add_console_command("errors", "errors - list recent failures (saving, loading, mods, ...)")
+            .add_systems(Update, (report_failures, handle_error_commands));
+    }
+}
+
+/// One toast per frame however many failures arrive, so a failing disk doesn't flood the screen
+pub fn report_failures(
+    mut failures: MessageReader<Failure>,
+    mut log: ResMut<ErrorLog>,
+    mut toasts: MessageWriter<Toast>,
+    hud: Option<ResMut<DebugHudLines>>,
+    locale: Res<Locale>,
+) {
+    let mut reported = 0;
+    let mut first = None;
+    for failure in failures.read() {
+        let line = format!("{}: {}", failure.context, failure.error);
+        error!("{}", line);
+        if log.entries.len() == ERROR_LOG_SIZE {
+            log.entries.pop_front();
+        }
+        log.entries.push_back(line.clone());
+        log.total += 1;
+        reported += 1;
+        first.get_or_insert(line);
+    }
+
+    let Some(first) = first else {
+        return;
+    };
+    if reported == 1 {
+        toasts.write(Toast::error(first));
+    } else {
+        toasts.write(Toast::error(locale.format("toast-more-errors", &[
+            ("first", first.into()),
+            ("count", (reported - 1).into()),
+        ])));
+    }
+    if let Some(mut hud) = hud {
+        hud.set("errors", format!("Errors: {}", log.total));
+    }
+}
+
+/// Console command: `errors` lists the most recent failures
+pub fn handle_error_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    log: Res<ErrorLog>,
+) {
+    for command in console_commands.read() {
+        if command.name != "errors" {
+            continue;
+        }
+        if log.entries.is_empty() {
+            console.print("No errors");
+            continue;
+        }
+        console.print(format!("{} errors since startup, most recent last:", log.total));
+        for entry in &log.entries {
+            console.print(format!("  {}", entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{ChunkData, TileRegistry, LAYER_GROUND};
+
+    #[test]
+    fn test_world_error_sources() {
+        let error: WorldError = SerializationError::InvalidChecksum.into();
+        assert_eq!(error.to_string(), "Checksum mismatch");
+        let error: WorldError = MetadataError::Io(io::Error::other("disk full")).into();
+        assert_eq!(error.to_string(), "IO error: disk full");
+
+        let mut chunk = ChunkData::empty(ChunkPos::new(3, -1));
+        let registry = TileRegistry::new();
+        assert_eq!(registry.find_unknown_tile(&chunk), None);
+        chunk.set_tile(LAYER_GROUND, 0, 0, 99);
+        let tile = registry.find_unknown_tile(&chunk).unwrap();
+        let error = WorldError::UnknownTile { chunk: chunk.position, tile };
+        assert_eq!(error.to_string(), "chunk (3, -1) has unknown tile id 99");
+    }
+}
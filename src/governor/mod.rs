@@ -0,0 +1,125 @@
+use crate::config::GameConfig;
+use crate::debug_hud::DebugHudLines;
+use bevy::prelude::*;
+
+/// Seconds between governor decisions
+const GOVERNOR_INTERVAL: f32 = 1.0;
+
+/// Weight of the newest frame in the smoothed frame time
+const SMOOTHING: f32 = 0.1;
+
+/// Throttling is eased off once frames take less than this share of the budget
+const HEADROOM: f32 = 0.75;
+
+/// Highest throttle level: low-priority ticks run 2^3 = 8 times less often
+pub const MAX_THROTTLE: u32 = 3;
+
+/// Plugin for the simulation budget governor
+pub struct GovernorPlugin;
+
+impl Plugin for GovernorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationGovernor>()
+            .add_systems(Update, (measure_frame_time, update_governor.after(measure_frame_time)));
+    }
+}
+
+/// Watches the frame time and stretches the tick intervals of low-priority simulation
+/// (random ticks, background creatures) when frames run over `GameplayConfig.frame_budget_ms`
+/// Those systems multiply their interval by `tick_multiplier`; the full simulation budget is divided by it
+#[derive(Resource, Default)]
+pub struct SimulationGovernor {
+    /// Smoothed frame time (milliseconds)
+    pub frame_ms: f32,
+    /// 0 (unthrottled) to `MAX_THROTTLE`
+    pub throttle: u32,
+}
+
+impl SimulationGovernor {
+    /// How much longer low-priority tick intervals are (1, 2, 4 or 8)
+    pub fn tick_multiplier(&self) -> f32 {
+        (1u32 << self.throttle) as f32
+    }
+}
+
+/// Throttle level after one decision: one step up when over budget, one step down with headroom
+/// to spare, unchanged in between (so the governor doesn't flip back and forth)
+pub fn next_throttle(throttle: u32, frame_ms: f32, budget_ms: f32) -> u32 {
+    if budget_ms <= 0.0 {
+        0
+    } else if frame_ms > budget_ms {
+        (throttle + 1).min(MAX_THROTTLE)
+    } else if frame_ms < budget_ms * HEADROOM {
+        throttle.saturating_sub(1)
+    } else {
+        throttle
+    }
+}
+
+fn measure_frame_time(time: Res<Time<Real>>, mut governor: ResMut<SimulationGovernor>) {
+    let frame_ms = time.delta_secs() * 1000.0;
+    governor.frame_ms = if governor.frame_ms == 0.0 {
+        frame_ms
+    } else {
+        governor.frame_ms + (frame_ms - governor.frame_ms) * SMOOTHING
+    };
+}
+
+/// Adjusts the throttle once a second and reports it in the debug HUD
+fn update_governor(
+    time: Res<Time<Real>>,
+    mut elapsed: Local<f32>,
+    config: Res<GameConfig>,
+    mut governor: ResMut<SimulationGovernor>,
+    mut hud: ResMut<DebugHudLines>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < GOVERNOR_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let budget_ms = config.gameplay.frame_budget_ms;
+    let throttle = next_throttle(governor.throttle, governor.frame_ms, budget_ms);
+    if throttle != governor.throttle {
+        info!(
+            "Frame time {:.1} ms (budget {:.1} ms): simulation throttle {} -> {}",
+            governor.frame_ms, budget_ms, governor.throttle, throttle
+        );
+        governor.throttle = throttle;
+    }
+
+    let budget = if budget_ms > 0.0 { format!("{:.1} ms", budget_ms) } else { "off".to_string() };
+    let fps = 1000.0 / governor.frame_ms.max(0.001);
+    hud.set("frame", format!("Frame: {:.1} ms ({:.0} fps), budget {}", governor.frame_ms, fps, budget));
+    hud.set(
+        "governor",
+        match governor.throttle {
+            0 => "Governor: full rate".to_string(),
+            level => format!(
+                "Governor: throttle {}/{} (random ticks and background creatures x{} slower)",
+                level,
+                MAX_THROTTLE,
+                governor.tick_multiplier()
+            ),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_throttle() {
+        // Steps up while over budget, up to the maximum
+        assert_eq!(next_throttle(0, 30.0, 20.0), 1);
+        assert_eq!(next_throttle(MAX_THROTTLE, 30.0, 20.0), MAX_THROTTLE);
+        // Holds just under budget, steps down with headroom
+        assert_eq!(next_throttle(2, 18.0, 20.0), 2);
+        assert_eq!(next_throttle(2, 10.0, 20.0), 1);
+        assert_eq!(next_throttle(0, 10.0, 20.0), 0);
+        // A zero budget disables the governor
+        assert_eq!(next_throttle(3, 100.0, 0.0), 0);
+    }
+}
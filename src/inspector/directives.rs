@@ -0,0 +1,78 @@
+//! `directive` command: gives the inspected guardian a goal (see `entities::directives`)
+//! Regions and patrol routes are marked with map waypoints
+
+use super::InspectedEntity;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::{ForestGuardian, GuardianDirective, Position, RoamingBehavior, GUARDIAN_ROAM_RADIUS};
+use crate::world::WorldMetadata;
+use bevy::prelude::*;
+
+/// Guard radius when `directive guard` doesn't give one (pixels)
+const DEFAULT_GUARD_RADIUS: f32 = 160.0;
+
+const USAGE: &str = "Usage: directive [reforest <waypoint> <waypoint> | patrol <waypoint> <waypoint> | guard [radius] | clear]";
+
+/// Console command: `directive [reforest|patrol <waypoint> <waypoint> | guard [radius] | clear]`
+/// shows or sets the inspected guardian's directive; `clear` lets it roam where it stands
+pub fn handle_directive_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    inspected: Res<InspectedEntity>,
+    metadata: Res<WorldMetadata>,
+    mut guardians: Query<(&Position, &mut RoamingBehavior, Option<&GuardianDirective>), With<ForestGuardian>>,
+) {
+    for command in console_commands.read() {
+        if command.name != "directive" {
+            continue;
+        }
+
+        let Some((entity, (position, mut roaming, current))) =
+            inspected.0.and_then(|entity| guardians.get_mut(entity).ok().map(|guardian| (entity, guardian)))
+        else {
+            console.print("No guardian selected; run `inspect` near one first");
+            continue;
+        };
+        let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+        let directive = match args.as_slice() {
+            [] => {
+                console.print(match current {
+                    Some(directive) => format!("Directive: {}", directive.label()),
+                    None => "No directive (roaming)".to_string(),
+                });
+                continue;
+            }
+            ["clear"] => {
+                commands.entity(entity).remove::<GuardianDirective>();
+                *roaming = RoamingBehavior::new(*position, GUARDIAN_ROAM_RADIUS, roaming.speed);
+                console.print("Directive cleared; roaming from here");
+                continue;
+            }
+            [kind @ ("reforest" | "patrol"), a, b] => {
+                let (Some(a), Some(b)) = (metadata.find_waypoint(a), metadata.find_waypoint(b)) else {
+                    console.print("No such waypoint (right-click the map to drop one)");
+                    continue;
+                };
+                if *kind == "reforest" {
+                    GuardianDirective::reforest(a.position(), b.position())
+                } else {
+                    GuardianDirective::Patrol { from: a.position(), to: b.position(), outbound: true }
+                }
+            }
+            ["guard"] => GuardianDirective::GuardSnails { center: position.vec2(), radius: DEFAULT_GUARD_RADIUS },
+            ["guard", radius] => match radius.parse::<f32>() {
+                Ok(radius) if radius > 0.0 => GuardianDirective::GuardSnails { center: position.vec2(), radius },
+                _ => {
+                    console.print(USAGE);
+                    continue;
+                }
+            },
+            _ => {
+                console.print(USAGE);
+                continue;
+            }
+        };
+        console.print(format!("Directive: {}", directive.label()));
+        commands.entity(entity).insert(directive);
+    }
+}
@@ -0,0 +1,160 @@
+pub mod directives;
+pub mod profiling;
+pub mod remote;
+pub mod stress;
+pub mod websocket;
+
+pub use profiling::ProfilingPlugin;
+pub use remote::{RemoteInspector, RemoteInspectorPlugin};
+
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use crate::entities::Position;
+use crate::world::WorldOrigin;
+use bevy::ecs::message::MessageCursor;
+use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy::reflect::{GetPath, TypeRegistry};
+use serde::de::DeserializeSeed;
+
+/// Only components from this crate are listed (not `Transform`, `Sprite`, ...)
+const GAMEPLAY_TYPE_PREFIX: &str = "worldseed::";
+
+/// Plugin for the dev-build entity inspector: console commands that read and live-edit reflected
+/// gameplay components (see the `register_type` calls in `EntitiesPlugin`), and give the inspected
+/// guardian a directive
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InspectedEntity>()
+            .add_console_command("inspect", "inspect - select the entity nearest the camera and list its components")
+            .add_console_command(
+                "tune",
+                "tune <Component> <field> <value> - set a field on the inspected entity (value in RON)",
+            )
+            .add_console_command(
+                "directive",
+                "directive [reforest|patrol <waypoint> <waypoint> | guard [radius] | clear] - give the inspected guardian a goal",
+            )
+            .add_systems(Update, (handle_inspector_commands, directives::handle_directive_commands));
+    }
+}
+
+/// Entity the `tune` command edits
+#[derive(Resource, Default)]
+pub struct InspectedEntity(pub Option<Entity>);
+
+/// Entity with a `Position` nearest the camera center
+fn nearest_to_camera(world: &mut World) -> Option<Entity> {
+    let center = world
+        .query_filtered::<&Transform, With<Camera2d>>()
+        .single(world)
+        .ok()?
+        .translation
+        .truncate();
+    let origin = world.get_resource::<WorldOrigin>().copied().unwrap_or_default().offset();
+    world
+        .query::<(Entity, &Position)>()
+        .iter(world)
+        .map(|(entity, position)| (entity, center.distance_squared(position.render_translation(origin))))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+/// Reflected gameplay components of an entity, one line each
+fn describe(world: &World, registry: &TypeRegistry, entity: Entity) -> Vec<String> {
+    let Ok(entity_ref) = world.get_entity(entity) else {
+        return Vec::new();
+    };
+    let mut lines: Vec<String> = registry
+        .iter()
+        .filter(|registration| registration.type_info().type_path().starts_with(GAMEPLAY_TYPE_PREFIX))
+        .filter_map(|registration| {
+            let component = registration.data::<ReflectComponent>()?.reflect(entity_ref)?;
+            Some(format!("  {:?}", component))
+        })
+        .collect();
+    lines.sort();
+    lines
+}
+
+/// Set `field` of `component_name` on `entity` to `value`, parsed as RON into the field's type
+fn tune(
+    world: &mut World,
+    registry: &TypeRegistry,
+    entity: Entity,
+    component_name: &str,
+    field: &str,
+    value: &str,
+) -> Result<(), String> {
+    let registration = registry
+        .get_with_short_type_path(component_name)
+        .ok_or_else(|| format!("Unknown type '{}'", component_name))?;
+    let reflect_component = registration
+        .data::<ReflectComponent>()
+        .ok_or_else(|| format!("'{}' is not a component", component_name))?;
+    let mut entity_mut = world.get_entity_mut(entity).map_err(|_| "The inspected entity is gone".to_string())?;
+    let mut component = reflect_component
+        .reflect_mut(&mut entity_mut)
+        .ok_or_else(|| format!("The inspected entity has no {}", component_name))?;
+
+    let target = component.reflect_path_mut(field).map_err(|e| e.to_string())?;
+    let field_type = target
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| format!("'{}' has an unregistered type", field))?;
+    let mut deserializer = ron::Deserializer::from_str(value).map_err(|e| e.to_string())?;
+    let parsed = TypedReflectDeserializer::new(field_type, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| e.to_string())?;
+    target.try_apply(parsed.as_ref()).map_err(|e| e.to_string())
+}
+
+/// Console commands: `inspect` selects the entity nearest the camera and prints its components,
+/// `tune <Component> <field> <value>` edits one of them (e.g. `tune RoamingBehavior speed 40.0`)
+/// Exclusive, since components are looked up by name at runtime
+pub fn handle_inspector_commands(world: &mut World, mut cursor: Local<MessageCursor<ConsoleCommand>>) {
+    let commands: Vec<ConsoleCommand> = cursor
+        .read(world.resource::<Messages<ConsoleCommand>>())
+        .filter(|command| command.name == "inspect" || command.name == "tune")
+        .cloned()
+        .collect();
+    if commands.is_empty() {
+        return;
+    }
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    for command in commands {
+        let mut output = Vec::new();
+        if command.name == "inspect" {
+            let selected = nearest_to_camera(world);
+            world.resource_mut::<InspectedEntity>().0 = selected;
+            match selected {
+                Some(entity) => {
+                    output.push(format!("Inspecting {}", entity));
+                    output.extend(describe(world, &registry, entity));
+                }
+                None => output.push("No entities to inspect".to_string()),
+            }
+        } else {
+            let [component_name, field, value] = command.args.as_slice() else {
+                world.resource_mut::<ConsoleState>().print("Usage: tune <Component> <field> <value>");
+                continue;
+            };
+            let Some(entity) = world.resource::<InspectedEntity>().0 else {
+                world.resource_mut::<ConsoleState>().print("Nothing selected; run `inspect` first");
+                continue;
+            };
+            match tune(world, &registry, entity, component_name, field, value) {
+                Ok(()) => output.extend(describe(world, &registry, entity)),
+                Err(e) => output.push(format!("tune failed: {}", e)),
+            }
+        }
+
+        let mut console = world.resource_mut::<ConsoleState>();
+        for line in output {
+            console.print(line);
+        }
+    }
+}
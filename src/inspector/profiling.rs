@@ -0,0 +1,53 @@
+use super::stress::{self, StressTest};
+use crate::console::ConsoleAppExt;
+use crate::debug_hud::DebugHudLines;
+use crate::world::diagnostics::{CHUNKS_LOADED, TILE_CHANGES_APPLIED};
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+/// Plugin for dev-build profiling: frame rate, frame time, entity count and the chunk streaming
+/// rates (`world::diagnostics`) in the debug HUD (F3), and the `stress` command for measuring them
+/// under load
+pub struct ProfilingPlugin;
+
+impl Plugin for ProfilingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin::default());
+        }
+        app.init_resource::<StressTest>()
+            .add_console_command(
+                "stress",
+                "stress <thousands> [snails|trees|mixed] - spawn entities and paint around the camera, then report frame times",
+            )
+            .add_systems(Update, (
+                show_diagnostics.run_if(resource_exists::<DebugHudLines>),
+                stress::handle_stress_commands,
+                stress::sample_stress_frame_times.after(stress::handle_stress_commands),
+            ));
+    }
+}
+
+/// Writes the smoothed diagnostics to the debug HUD, rounded so the line only changes when they do
+pub fn show_diagnostics(diagnostics: Res<DiagnosticsStore>, mut hud: ResMut<DebugHudLines>) {
+    let smoothed = |path: DiagnosticPath| diagnostics.get(&path).and_then(|diagnostic| diagnostic.smoothed());
+    let (Some(fps), Some(frame_time)) = (
+        smoothed(FrameTimeDiagnosticsPlugin::FPS),
+        smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME),
+    ) else {
+        return;
+    };
+    let entities = smoothed(EntityCountDiagnosticsPlugin::ENTITY_COUNT).unwrap_or(0.0);
+    let chunks = smoothed(CHUNKS_LOADED).unwrap_or(0.0);
+    let tile_changes = smoothed(TILE_CHANGES_APPLIED).unwrap_or(0.0);
+    hud.set(
+        "profiling",
+        format!(
+            "FPS: {:.0} ({:.1} ms) | Entities: {:.0} | Chunks: {:.0}/s | Tile changes: {:.0}/s",
+            fps, frame_time, entities, chunks, tile_changes
+        ),
+    );
+}
@@ -0,0 +1,477 @@
+//! Remote inspection (`--inspect <addr>`): a WebSocket server speaking JSON, so external dashboards
+//! and test harnesses can watch and poke a running (possibly headless) world
+//!
+//! Requests are objects with a `type` and an optional `id` that is echoed in the reply:
+//! `{"type": "stats"}`, `{"type": "chunks"}`, `{"type": "entities", "kind": "snail", "limit": 100}`
+//! and `{"type": "command", "line": "weather rain"}`. Console output (including what remote commands
+//! print) is pushed to every client as `{"type": "console", "lines": [...]}`
+
+use super::websocket::{self, Frame, WsError};
+use crate::console::{ConsoleCommand, ConsoleRegistry, ConsoleState};
+use crate::entities::{entity_kind, ForestGuardian, GrowingTree, GuardianLore, Player, Position, SaveableFilter, Snail};
+use crate::tiles::{TileId, LAYER_GROUND};
+use crate::world::diagnostics::{CHUNKS_LOADED, TILE_CHANGES_APPLIED};
+use crate::world::WorldManager;
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Connections beyond this many are refused
+const MAX_INSPECT_CLIENTS: usize = 8;
+
+/// A client this far behind (unsent bytes) is disconnected
+const MAX_SEND_BACKLOG: usize = 1 << 24;
+
+/// Entities listed when a request doesn't give a limit
+const DEFAULT_ENTITY_LIMIT: usize = 1000;
+
+/// Plugin for the remote inspection server; its systems only run when `main` inserted a
+/// `RemoteInspector` for `--inspect`
+pub struct RemoteInspectorPlugin;
+
+impl Plugin for RemoteInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            accept_inspector_clients,
+            serve_inspector_requests.after(accept_inspector_clients),
+            push_console_output.after(serve_inspector_requests),
+        ).run_if(resource_exists::<RemoteInspector>));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InspectRequest {
+    Stats,
+    Chunks,
+    Entities {
+        /// Only entities of this `EntityRegistry` kind ("snail", "guardian:oak", ...)
+        #[serde(default)]
+        kind: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    /// A console command line, run as if typed
+    Command { line: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestEnvelope {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    request: InspectRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsReply {
+    pub world: String,
+    pub loaded_chunks: usize,
+    pub dirty_chunks: usize,
+    pub cached_chunks: usize,
+    pub camera_chunk: Option<[i32; 2]>,
+    pub entities: usize,
+    pub uptime_secs: f64,
+    /// Smoothed diagnostics, absent until measured
+    pub fps: Option<f64>,
+    pub chunks_loaded_per_second: Option<f64>,
+    pub tile_changes_applied_per_second: Option<f64>,
+}
+
+/// A loaded chunk: whether it has unsaved changes, and its ground tile IDs with their counts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChunkSummary {
+    pub x: i32,
+    pub y: i32,
+    pub dirty: bool,
+    pub ground: BTreeMap<TileId, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntitySummary {
+    /// `Entity::to_bits`
+    pub id: u64,
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    /// Guardians' generated name and title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InspectReply {
+    Stats(StatsReply),
+    Chunks { chunks: Vec<ChunkSummary> },
+    /// `total` counts every match, `entities` stops at the limit
+    Entities { total: usize, entities: Vec<EntitySummary> },
+    Command { accepted: bool },
+    Console { lines: Vec<String> },
+    Error { message: String },
+}
+
+#[derive(Serialize)]
+struct ReplyEnvelope<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a Value>,
+    #[serde(flatten)]
+    reply: &'a InspectReply,
+}
+
+/// Parses a request message into its id (if any) and request
+pub fn parse_request(text: &str) -> Result<(Option<Value>, InspectRequest), String> {
+    serde_json::from_str::<RequestEnvelope>(text)
+        .map(|envelope| (envelope.id, envelope.request))
+        .map_err(|e| format!("Invalid request: {}", e))
+}
+
+/// JSON text of a reply, with the request's id
+pub fn encode_reply(id: Option<&Value>, reply: &InspectReply) -> String {
+    serde_json::to_string(&ReplyEnvelope { id, reply }).unwrap_or_else(|e| {
+        format!(r#"{{"type":"error","message":"Reply couldn't be encoded: {}"}}"#, e)
+    })
+}
+
+/// A connected dashboard or harness
+struct InspectClient {
+    stream: TcpStream,
+    peer: String,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    /// Past the opening handshake
+    upgraded: bool,
+    /// Closed or broken; dropped after the pending output is flushed
+    closing: bool,
+}
+
+impl InspectClient {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_else(|_| "?".to_string());
+        Ok(Self {
+            stream,
+            peer,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            upgraded: false,
+            closing: false,
+        })
+    }
+
+    /// Reads what arrived; false once the peer hung up
+    fn read(&mut self) -> io::Result<bool> {
+        let mut buffer = [0u8; 16 * 1024];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Ok(false),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Messages that completed since the last call; completes the handshake first
+    fn receive(&mut self) -> Result<Vec<Frame>, WsError> {
+        if !self.upgraded {
+            let Some(response) = websocket::accept_handshake(&mut self.incoming)? else {
+                return Ok(Vec::new());
+            };
+            self.outgoing.extend_from_slice(response.as_bytes());
+            self.upgraded = true;
+            info!("Inspector client {} connected", self.peer);
+        }
+        websocket::decode_frames(&mut self.incoming)
+    }
+
+    fn reply(&mut self, id: Option<&Value>, reply: &InspectReply) {
+        self.outgoing.extend(websocket::text_frame(&encode_reply(id, reply)));
+    }
+
+    /// Writes as much queued output as the socket takes
+    fn flush(&mut self) -> io::Result<()> {
+        if self.outgoing.len() > MAX_SEND_BACKLOG {
+            return Err(io::Error::other("client fell too far behind"));
+        }
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The remote inspection server (dev builds, `--inspect <addr>`)
+#[derive(Resource)]
+pub struct RemoteInspector {
+    listener: TcpListener,
+    clients: Vec<InspectClient>,
+    /// `ConsoleState::printed` when console output was last pushed
+    console_seen: u64,
+}
+
+impl RemoteInspector {
+    pub fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!("Remote inspection on ws://{}", listener.local_addr()?);
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            console_seen: 0,
+        })
+    }
+}
+
+pub fn accept_inspector_clients(mut inspector: ResMut<RemoteInspector>) {
+    loop {
+        match inspector.listener.accept() {
+            Ok((stream, address)) => {
+                if inspector.clients.len() >= MAX_INSPECT_CLIENTS {
+                    warn!("Refused inspector client {}: {} already connected", address, MAX_INSPECT_CLIENTS);
+                    continue;
+                }
+                match InspectClient::new(stream) {
+                    Ok(client) => inspector.clients.push(client),
+                    Err(e) => warn!("Failed to set up inspector client {}: {}", address, e),
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Failed to accept an inspector client: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// What the entity list reads of each entity (enough for `entity_kind`)
+pub type ListedEntity<'a> = (
+    Entity,
+    &'a Position,
+    Has<Player>,
+    Has<Snail>,
+    Option<&'a ForestGuardian>,
+    Option<&'a GrowingTree>,
+    Option<&'a GuardianLore>,
+);
+
+/// Reads every client's requests and answers them
+#[allow(clippy::too_many_arguments)]
+pub fn serve_inspector_requests(
+    mut inspector: ResMut<RemoteInspector>,
+    mut console: ResMut<ConsoleState>,
+    mut console_commands: MessageWriter<ConsoleCommand>,
+    registry: Res<ConsoleRegistry>,
+    world: Res<WorldManager>,
+    diagnostics: Res<DiagnosticsStore>,
+    time: Res<Time<Real>>,
+    entities: Query<ListedEntity, SaveableFilter>,
+) {
+    for client in inspector.clients.iter_mut().filter(|client| !client.closing) {
+        let frames = match client.read() {
+            Ok(true) => client.receive(),
+            Ok(false) => {
+                client.closing = true;
+                continue;
+            }
+            Err(e) => {
+                debug!("Inspector client {} read failed: {}", client.peer, e);
+                client.closing = true;
+                continue;
+            }
+        };
+        let frames = match frames {
+            Ok(frames) => frames,
+            Err(e) => {
+                warn!("Dropping inspector client {}: {}", client.peer, e);
+                if client.upgraded {
+                    client.outgoing.extend(websocket::close_frame());
+                }
+                client.closing = true;
+                continue;
+            }
+        };
+
+        for frame in frames {
+            let text = match frame {
+                Frame::Text(text) => text,
+                Frame::Ping(payload) => {
+                    client.outgoing.extend(websocket::pong_frame(&payload));
+                    continue;
+                }
+                Frame::Close => {
+                    client.outgoing.extend(websocket::close_frame());
+                    client.closing = true;
+                    break;
+                }
+                Frame::Binary(_) | Frame::Pong => continue,
+            };
+            let (id, request) = match parse_request(&text) {
+                Ok(parsed) => parsed,
+                Err(message) => {
+                    client.reply(None, &InspectReply::Error { message });
+                    continue;
+                }
+            };
+
+            let reply = match request {
+                InspectRequest::Stats => {
+                    let stats = world.stats();
+                    let smoothed = |path: DiagnosticPath| diagnostics.get(&path).and_then(|diagnostic| diagnostic.smoothed());
+                    InspectReply::Stats(StatsReply {
+                        world: world.save_directory.display().to_string(),
+                        loaded_chunks: stats.loaded_chunks,
+                        dirty_chunks: stats.dirty_chunks,
+                        cached_chunks: stats.cached_chunks,
+                        camera_chunk: stats.camera_chunk.map(|chunk| [chunk.x, chunk.y]),
+                        entities: entities.iter().len(),
+                        uptime_secs: time.elapsed_secs_f64(),
+                        fps: smoothed(FrameTimeDiagnosticsPlugin::FPS),
+                        chunks_loaded_per_second: smoothed(CHUNKS_LOADED),
+                        tile_changes_applied_per_second: smoothed(TILE_CHANGES_APPLIED),
+                    })
+                }
+                InspectRequest::Chunks => {
+                    let mut chunks: Vec<ChunkSummary> = world
+                        .active_chunks
+                        .keys()
+                        .map(|pos| {
+                            let mut ground = BTreeMap::new();
+                            if let Some(chunk) = world.get_cached_chunk(pos) {
+                                for tile in chunk.layers[LAYER_GROUND].iter() {
+                                    *ground.entry(*tile).or_insert(0) += 1;
+                                }
+                            }
+                            ChunkSummary {
+                                x: pos.x,
+                                y: pos.y,
+                                dirty: world.dirty_chunks.contains_key(pos),
+                                ground,
+                            }
+                        })
+                        .collect();
+                    chunks.sort_by_key(|chunk| (chunk.y, chunk.x));
+                    InspectReply::Chunks { chunks }
+                }
+                InspectRequest::Entities { kind, limit } => {
+                    let mut total = 0;
+                    let mut listed = Vec::new();
+                    let limit = limit.unwrap_or(DEFAULT_ENTITY_LIMIT);
+                    for (entity, position, is_player, is_snail, guardian, tree, lore) in entities.iter() {
+                        let Some(entity_kind) = entity_kind(is_player, is_snail, guardian, tree) else {
+                            continue;
+                        };
+                        if kind.as_ref().is_some_and(|kind| *kind != entity_kind) {
+                            continue;
+                        }
+                        total += 1;
+                        if listed.len() < limit {
+                            listed.push(EntitySummary {
+                                id: entity.to_bits(),
+                                kind: entity_kind,
+                                x: position.x(),
+                                y: position.y(),
+                                name: lore.map(GuardianLore::full_name),
+                            });
+                        }
+                    }
+                    InspectReply::Entities { total, entities: listed }
+                }
+                InspectRequest::Command { line } => match ConsoleCommand::parse(&line) {
+                    Some(command) if registry.contains(&command.name) => {
+                        console.print(format!("> {} (from {})", line.trim(), client.peer));
+                        console_commands.write(command);
+                        InspectReply::Command { accepted: true }
+                    }
+                    Some(command) => InspectReply::Error {
+                        message: format!("Unknown command '{}'", command.name),
+                    },
+                    None => InspectReply::Error {
+                        message: "Empty command".to_string(),
+                    },
+                },
+            };
+            client.reply(id.as_ref(), &reply);
+        }
+    }
+}
+
+/// Pushes new console lines to every client, flushes, and drops closed clients
+pub fn push_console_output(mut inspector: ResMut<RemoteInspector>, console: Res<ConsoleState>) {
+    let new_lines = (console.printed - inspector.console_seen).min(console.lines.len() as u64) as usize;
+    inspector.console_seen = console.printed;
+    if new_lines > 0 {
+        let lines = InspectReply::Console {
+            lines: console.lines[console.lines.len() - new_lines..].to_vec(),
+        };
+        for client in inspector.clients.iter_mut().filter(|client| client.upgraded && !client.closing) {
+            client.reply(None, &lines);
+        }
+    }
+
+    inspector.clients.retain_mut(|client| match client.flush() {
+        Ok(()) if client.closing => {
+            info!("Inspector client {} disconnected", client.peer);
+            false
+        }
+        Ok(()) => true,
+        Err(e) => {
+            info!("Inspector client {} disconnected: {}", client.peer, e);
+            false
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect_messages() {
+        let (id, request) = parse_request(r#"{"id": 7, "type": "entities", "kind": "snail"}"#).unwrap();
+        assert_eq!(id, Some(Value::from(7)));
+        assert_eq!(
+            request,
+            InspectRequest::Entities {
+                kind: Some("snail".to_string()),
+                limit: None
+            }
+        );
+        assert_eq!(parse_request(r#"{"type": "stats"}"#).unwrap(), (None, InspectRequest::Stats));
+        assert!(parse_request(r#"{"type": "teleport"}"#).is_err());
+        assert!(parse_request("not json").is_err());
+
+        let reply = InspectReply::Command { accepted: true };
+        assert_eq!(
+            encode_reply(Some(&Value::from("a")), &reply),
+            r#"{"id":"a","type":"command","accepted":true}"#
+        );
+        let chunks = InspectReply::Chunks {
+            chunks: vec![ChunkSummary {
+                x: 1,
+                y: -2,
+                dirty: false,
+                ground: BTreeMap::from([(1, 1000), (2, 24)]),
+            }],
+        };
+        assert_eq!(
+            encode_reply(None, &chunks),
+            r#"{"type":"chunks","chunks":[{"x":1,"y":-2,"dirty":false,"ground":{"1":1000,"2":24}}]}"#
+        );
+    }
+}
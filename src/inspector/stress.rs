@@ -0,0 +1,210 @@
+use crate::assets::SpriteAssets;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::{spawn_snail, spawn_tree_spirit, Position, TreeVariant};
+use crate::tiles::{TILE_DIRT, TILE_SIZE};
+use crate::world::{TileEdits, WorldOrigin};
+use bevy::prelude::*;
+use std::fmt;
+
+/// How long frame times are sampled after the stress load is added (seconds)
+const STRESS_DURATION: f32 = 10.0;
+
+/// Most entities one `stress` command may add, in thousands
+const MAX_STRESS_THOUSANDS: u32 = 20;
+
+/// Spacing of the spawn grid (pixels)
+const STRESS_SPACING: f32 = 2.0 * TILE_SIZE;
+
+/// Side of the square painted with dirt around the camera (tiles)
+const STRESS_PAINT_TILES: i32 = 128;
+
+/// Growth time per stage of the stress trees (seconds), so they keep growing during the run
+const STRESS_GROWTH_TIME: f32 = 3.0;
+
+/// What the `stress` command spawns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StressLoad {
+    Snails,
+    Trees,
+    /// Alternating snails and trees
+    Mixed,
+}
+
+/// A running stress test: frame times sampled since the load was added
+#[derive(Debug, Default)]
+pub struct StressRun {
+    pub elapsed: f32,
+    pub samples: Vec<f32>,
+}
+
+/// The stress test in progress, if any
+#[derive(Resource, Debug, Default)]
+pub struct StressTest(pub Option<StressRun>);
+
+/// Frame time percentiles of a run, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameTimeReport {
+    pub frames: usize,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl FrameTimeReport {
+    /// Percentiles (nearest rank) of frame times in seconds; None without samples
+    pub fn from_samples(samples: &[f32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: f32| {
+            let rank = (p * sorted.len() as f32).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1] * 1000.0
+        };
+        Some(Self {
+            frames: sorted.len(),
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1] * 1000.0,
+        })
+    }
+}
+
+impl fmt::Display for FrameTimeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} frames: p50 {:.1} ms, p90 {:.1} ms, p99 {:.1} ms, max {:.1} ms",
+            self.frames, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+/// `count` positions in a square grid `spacing` apart, centered on `center`
+pub fn grid_positions(center: Vec2, count: usize, spacing: f32) -> Vec<Vec2> {
+    let side = (count as f32).sqrt().ceil() as usize;
+    let first = center - Vec2::splat((side.saturating_sub(1)) as f32 * spacing / 2.0);
+    (0..count)
+        .map(|i| first + Vec2::new((i % side) as f32, (i / side) as f32) * spacing)
+        .collect()
+}
+
+/// `stress <thousands> [snails|trees|mixed]`: spawns the entities in a grid around the camera, paints
+/// a large square of dirt, then samples frame times for `STRESS_DURATION` seconds
+#[allow(clippy::too_many_arguments)]
+pub fn handle_stress_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut stress: ResMut<StressTest>,
+    mut commands: Commands,
+    sprites: Res<SpriteAssets>,
+    mut tile_edits: TileEdits,
+    origin: Res<WorldOrigin>,
+    camera: Single<&Transform, With<Camera2d>>,
+) {
+    for command in console_commands.read() {
+        if command.name != "stress" {
+            continue;
+        }
+        if stress.0.is_some() {
+            console.print("A stress test is already running");
+            continue;
+        }
+        let thousands = command.args.first().and_then(|arg| arg.parse::<u32>().ok());
+        let load = match command.args.get(1).map(String::as_str) {
+            None | Some("mixed") => Some(StressLoad::Mixed),
+            Some("snails") => Some(StressLoad::Snails),
+            Some("trees") => Some(StressLoad::Trees),
+            Some(_) => None,
+        };
+        let (Some(thousands @ 1..=MAX_STRESS_THOUSANDS), Some(load)) = (thousands, load) else {
+            console.print(format!("Usage: stress <thousands, 1-{}> [snails|trees|mixed]", MAX_STRESS_THOUSANDS));
+            continue;
+        };
+
+        let center = origin.to_world_vec2(camera.translation.truncate());
+        let count = thousands as usize * 1000;
+        for (i, position) in grid_positions(center, count, STRESS_SPACING).into_iter().enumerate() {
+            let position = Position::new(position.x, position.y);
+            let tree = match load {
+                StressLoad::Snails => false,
+                StressLoad::Trees => true,
+                StressLoad::Mixed => i % 2 == 1,
+            };
+            if tree {
+                let variant = TreeVariant::ALL[i % TreeVariant::ALL.len()];
+                spawn_tree_spirit(&mut commands, position, variant, STRESS_GROWTH_TIME, &sprites);
+            } else {
+                spawn_snail(&mut commands, position, &sprites);
+            }
+        }
+
+        let center_tile = (center / TILE_SIZE).floor().as_ivec2();
+        let half = STRESS_PAINT_TILES / 2;
+        tile_edits.set_ground_batch(
+            (-half..half).flat_map(|y| (-half..half).map(move |x| IVec2::new(x, y))).map(|offset| {
+                ((center_tile + offset).as_vec2() + Vec2::splat(0.5)) * TILE_SIZE
+            }),
+            TILE_DIRT,
+        );
+
+        stress.0 = Some(StressRun::default());
+        console.print(format!(
+            "Stress test: {} entities ({:?}) and {}x{} painted tiles; sampling frame times for {:.0}s",
+            count, load, STRESS_PAINT_TILES, STRESS_PAINT_TILES, STRESS_DURATION
+        ));
+        info!("Stress test started with {} entities ({:?})", count, load);
+    }
+}
+
+/// Samples frame times of the running stress test and prints the percentiles when it ends
+pub fn sample_stress_frame_times(
+    mut stress: ResMut<StressTest>,
+    mut console: ResMut<ConsoleState>,
+    time: Res<Time<Real>>,
+) {
+    let Some(run) = stress.0.as_mut() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    run.elapsed += delta;
+    run.samples.push(delta);
+    if run.elapsed < STRESS_DURATION {
+        return;
+    }
+
+    if let Some(report) = FrameTimeReport::from_samples(&run.samples) {
+        console.print(format!("Stress test done: {}", report));
+        info!("Stress test done: {}", report);
+    }
+    stress.0 = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress_helpers() {
+        let positions = grid_positions(Vec2::new(100.0, 0.0), 10, 16.0);
+        assert_eq!(positions.len(), 10);
+        // A 4x4 grid (partly filled) centered on the camera
+        assert_eq!(positions[0], Vec2::new(76.0, -24.0));
+        assert_eq!(positions[3], Vec2::new(124.0, -24.0));
+        assert_eq!(positions[4], Vec2::new(76.0, -8.0));
+        assert!(grid_positions(Vec2::ZERO, 0, 16.0).is_empty());
+
+        // 100 frames of 1..=100 ms
+        let samples: Vec<f32> = (1..=100).rev().map(|ms| ms as f32 / 1000.0).collect();
+        let report = FrameTimeReport::from_samples(&samples).unwrap();
+        assert_eq!(report.frames, 100);
+        assert!((report.p50 - 50.0).abs() < 0.01);
+        assert!((report.p90 - 90.0).abs() < 0.01);
+        assert!((report.p99 - 99.0).abs() < 0.01);
+        assert!((report.max - 100.0).abs() < 0.01);
+        assert_eq!(FrameTimeReport::from_samples(&[]), None);
+    }
+}
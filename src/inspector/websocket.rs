@@ -0,0 +1,256 @@
+//! Just enough of WebSocket (RFC 6455) for the remote inspector: the opening handshake and
+//! unfragmented frames. Clients mask their frames; the server never does
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use thiserror::Error;
+
+/// GUID appended to the client's key to form the accept key
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest request head accepted before the blank line
+pub const MAX_HANDSHAKE_SIZE: usize = 8 * 1024;
+
+/// Largest message accepted from a client
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum WsError {
+    #[error("Bad handshake: {0}")]
+    Handshake(String),
+    #[error("Bad frame: {0}")]
+    Frame(String),
+    #[error("Message too large: {0} bytes")]
+    TooLarge(usize),
+}
+
+/// A message from a client
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong,
+    Close,
+}
+
+/// SHA-1 digest (only used for the handshake, where the protocol requires it)
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let next = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, next);
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`
+pub fn accept_key(key: &str) -> String {
+    BASE64.encode(sha1(format!("{}{}", key.trim(), HANDSHAKE_GUID).as_bytes()))
+}
+
+/// Reads the opening request at the front of `buffer`: Ok(None) until the whole head has arrived,
+/// then the `101 Switching Protocols` response to send (the head is removed from the buffer)
+pub fn accept_handshake(buffer: &mut Vec<u8>) -> Result<Option<String>, WsError> {
+    let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") else {
+        if buffer.len() > MAX_HANDSHAKE_SIZE {
+            return Err(WsError::TooLarge(buffer.len()));
+        }
+        return Ok(None);
+    };
+    let head = String::from_utf8_lossy(&buffer[..end]).into_owned();
+    buffer.drain(..end + 4);
+
+    let mut lines = head.lines();
+    if !lines.next().is_some_and(|request| request.starts_with("GET ")) {
+        return Err(WsError::Handshake("not a GET request".to_string()));
+    }
+    let key = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-key"))
+        .map(|(_, value)| value.trim())
+        .ok_or_else(|| WsError::Handshake("no Sec-WebSocket-Key (not a WebSocket request)".to_string()))?;
+    Ok(Some(format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    )))
+}
+
+/// Decodes and removes every complete frame at the front of `buffer`; a partial frame stays for later
+pub fn decode_frames(buffer: &mut Vec<u8>) -> Result<Vec<Frame>, WsError> {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while let Some(&[first, second]) = buffer.get(offset..offset + 2) {
+        if first & 0x80 == 0 || first & 0x0F == 0 {
+            return Err(WsError::Frame("fragmented messages aren't supported".to_string()));
+        }
+        if second & 0x80 == 0 {
+            return Err(WsError::Frame("client frames must be masked".to_string()));
+        }
+        let (length, header) = match second & 0x7F {
+            126 => match buffer.get(offset + 2..offset + 4) {
+                Some(bytes) => (u16::from_be_bytes([bytes[0], bytes[1]]) as usize, 4),
+                None => break,
+            },
+            127 => match buffer.get(offset + 2..offset + 10) {
+                Some(bytes) => {
+                    let length = u64::from_be_bytes(bytes.try_into().unwrap_or_default());
+                    (usize::try_from(length).unwrap_or(usize::MAX), 10)
+                }
+                None => break,
+            },
+            length => (length as usize, 2),
+        };
+        if length > MAX_MESSAGE_SIZE {
+            return Err(WsError::TooLarge(length));
+        }
+        let start = offset + header + 4;
+        let (Some(mask), Some(payload)) = (buffer.get(offset + header..start), buffer.get(start..start + length)) else {
+            break;
+        };
+        let payload: Vec<u8> = payload.iter().zip(mask.iter().cycle()).map(|(byte, mask)| byte ^ mask).collect();
+
+        frames.push(match first & 0x0F {
+            OPCODE_TEXT => Frame::Text(
+                String::from_utf8(payload).map_err(|_| WsError::Frame("text isn't UTF-8".to_string()))?,
+            ),
+            OPCODE_BINARY => Frame::Binary(payload),
+            OPCODE_CLOSE => Frame::Close,
+            OPCODE_PING => Frame::Ping(payload),
+            OPCODE_PONG => Frame::Pong,
+            opcode => return Err(WsError::Frame(format!("unknown opcode {:#x}", opcode))),
+        });
+        offset = start + length;
+    }
+    buffer.drain(..offset);
+    Ok(frames)
+}
+
+/// An unmasked server frame
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+pub fn text_frame(text: &str) -> Vec<u8> {
+    encode_frame(OPCODE_TEXT, text.as_bytes())
+}
+
+pub fn pong_frame(payload: &[u8]) -> Vec<u8> {
+    encode_frame(OPCODE_PONG, payload)
+}
+
+pub fn close_frame() -> Vec<u8> {
+    encode_frame(OPCODE_CLOSE, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A client frame, masked as browsers send them
+    fn masked(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xFA, 0x21, 0x3D];
+        let mut frame = encode_frame(opcode, payload);
+        let header = frame.len() - payload.len();
+        frame[1] |= 0x80;
+        let masked: Vec<u8> = payload.iter().zip(mask.iter().cycle()).map(|(byte, mask)| byte ^ mask).collect();
+        frame.truncate(header);
+        frame.extend_from_slice(&mask);
+        frame.extend(masked);
+        frame
+    }
+
+    #[test]
+    fn test_websocket_protocol() {
+        assert_eq!(
+            sha1(b"abc").iter().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        // The example from RFC 6455
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+        let request = "GET /inspect HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nsec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        let mut buffer = request.as_bytes()[..40].to_vec();
+        assert_eq!(accept_handshake(&mut buffer), Ok(None));
+        buffer.extend_from_slice(&request.as_bytes()[40..]);
+        buffer.extend_from_slice(&masked(OPCODE_TEXT, b"hi"));
+        let response = accept_handshake(&mut buffer).unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 101"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        assert_eq!(buffer, masked(OPCODE_TEXT, b"hi"));
+        let mut plain_http = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n".to_vec();
+        assert!(matches!(accept_handshake(&mut plain_http), Err(WsError::Handshake(_))));
+
+        // Whole frames are decoded, a partial one waits for the rest
+        let long = "x".repeat(300);
+        let mut stream = masked(OPCODE_TEXT, long.as_bytes());
+        stream.extend(masked(OPCODE_PING, b"p"));
+        let close = masked(OPCODE_CLOSE, &[]);
+        stream.extend_from_slice(&close[..3]);
+        let mut buffer = stream;
+        assert_eq!(
+            decode_frames(&mut buffer),
+            Ok(vec![Frame::Text(long), Frame::Ping(b"p".to_vec())])
+        );
+        buffer.extend_from_slice(&close[3..]);
+        assert_eq!(decode_frames(&mut buffer), Ok(vec![Frame::Close]));
+        assert!(buffer.is_empty());
+
+        let mut unmasked = text_frame("hi");
+        assert!(matches!(decode_frames(&mut unmasked), Err(WsError::Frame(_))));
+        assert_eq!(text_frame("hi"), vec![0x81, 2, b'h', b'i']);
+        assert_eq!(&text_frame(&"y".repeat(200))[..4], &[0x81, 126, 0, 200]);
+    }
+}
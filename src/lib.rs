@@ -0,0 +1,62 @@
+//! Worldseed: an endless, chunk-based 2D world of growing forests and wandering creatures, built on Bevy
+//!
+//! Each area of the game is a plugin; `main.rs` assembles them into the app. The plugins and core
+//! types are re-exported here so other binaries, tools and tests can build on them.
+
+pub mod achievements;
+pub mod assets;
+pub mod audio;
+pub mod chronicle;
+pub mod cli;
+pub mod config;
+pub mod console;
+pub mod debug_hud;
+pub mod editor;
+pub mod entities;
+pub mod environment;
+pub mod error;
+pub mod governor;
+#[cfg(feature = "dev-tools")]
+pub mod inspector;
+pub mod locale;
+pub mod map;
+pub mod mods;
+pub mod net;
+pub mod platform;
+pub mod scenario;
+pub mod schedule;
+pub mod stats;
+pub mod theme;
+pub mod tiles;
+pub mod toast;
+pub mod world;
+
+// Plugins
+pub use achievements::AchievementsPlugin;
+pub use assets::SpriteAssetsPlugin;
+pub use audio::SoundPlugin;
+pub use chronicle::ChroniclePlugin;
+pub use console::ConsolePlugin;
+pub use debug_hud::DebugHudPlugin;
+pub use editor::EditorUiPlugin;
+pub use entities::EntitiesPlugin;
+pub use environment::EnvironmentPlugin;
+pub use error::ErrorPlugin;
+pub use governor::GovernorPlugin;
+pub use locale::LocalePlugin;
+pub use map::MapPlugin;
+pub use mods::ModsPlugin;
+pub use net::NetPlugin;
+pub use scenario::ScenarioPlugin;
+pub use stats::StatsPlugin;
+pub use theme::ThemePlugin;
+pub use toast::ToastPlugin;
+pub use world::{WorldLoaderPlugin, WorldPlugin};
+
+// Core types
+pub use schedule::GameSet;
+pub use config::GameConfig;
+pub use error::WorldError;
+pub use entities::{EntityRegistry, Position, TreeVariant, Velocity};
+pub use tiles::{ChunkData, ChunkPos, TileId, TileRegistry};
+pub use world::{WorldManager, WorldMetadata};
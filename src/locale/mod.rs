@@ -0,0 +1,227 @@
+use crate::config::GameConfig;
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use bevy::prelude::*;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::FluentValue as LocaleArg;
+
+/// Language used when the setting names one that isn't available; it has every message
+pub const FALLBACK_LANGUAGE: &str = "en-US";
+
+/// Translations built into the game (`locales/<language>.ftl`, Fluent syntax)
+const LOCALES: [(&str, &str); 2] = [
+    ("en-US", include_str!("../../locales/en-US.ftl")),
+    ("de", include_str!("../../locales/de.ftl")),
+];
+
+/// Codes of the built-in languages
+pub fn available_languages() -> impl Iterator<Item = &'static str> {
+    LOCALES.iter().map(|(code, _)| *code)
+}
+
+/// The built-in language closest to `requested`: an exact match, else the same language ("de-AT" -> "de")
+pub fn match_language(requested: &str) -> Option<&'static str> {
+    let requested: LanguageIdentifier = requested.parse().ok()?;
+    let exact = available_languages().find(|code| code.parse::<LanguageIdentifier>().ok().as_ref() == Some(&requested));
+    exact.or_else(|| {
+        available_languages().find(|code| {
+            code.parse::<LanguageIdentifier>()
+                .is_ok_and(|available| available.language == requested.language)
+        })
+    })
+}
+
+fn bundle(code: &str) -> FluentBundle<FluentResource> {
+    let source = LOCALES
+        .iter()
+        .find(|(available, _)| *available == code)
+        .map(|(_, source)| *source)
+        .unwrap_or_default();
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+        warn!("Locale {} has {} syntax errors, some messages are missing", code, errors.len());
+        resource
+    });
+
+    let mut bundle = FluentBundle::new_concurrent(vec![code.parse().unwrap_or_default()]);
+    // Unicode isolation marks around placeables show up as boxes in the UI font
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("Locale {} defines {} messages twice", code, errors.len());
+    }
+    bundle
+}
+
+/// User-facing text in the language from `GameConfig.interface.language`
+/// Messages missing from a translation come from `FALLBACK_LANGUAGE`; messages missing there show their id
+#[derive(Resource)]
+pub struct Locale {
+    language: &'static str,
+    bundle: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+impl Locale {
+    pub fn new(requested: &str) -> Self {
+        let language = match_language(requested).unwrap_or_else(|| {
+            warn!("No translation for language '{}', using {}", requested, FALLBACK_LANGUAGE);
+            FALLBACK_LANGUAGE
+        });
+        Self {
+            language,
+            bundle: bundle(language),
+            fallback: bundle(FALLBACK_LANGUAGE),
+        }
+    }
+
+    /// Code of the language in use
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// A message without arguments
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    /// A message with arguments: `locale.format("toast-joined", &[("address", address.into())])`
+    pub fn format(&self, id: &str, args: &[(&str, LocaleArg)]) -> String {
+        let args: FluentArgs = args.iter().map(|(name, value)| (*name, value.clone())).collect();
+        [&self.bundle, &self.fallback]
+            .into_iter()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                let text = bundle.format_pattern(pattern, Some(&args), &mut errors).into_owned();
+                for error in errors {
+                    warn!("Formatting message '{}': {}", id, error);
+                }
+                Some(text)
+            })
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+impl FromWorld for Locale {
+    fn from_world(world: &mut World) -> Self {
+        world
+            .get_resource::<GameConfig>()
+            .map(|config| Self::new(&config.interface.language))
+            .unwrap_or_else(|| Self::new(FALLBACK_LANGUAGE))
+    }
+}
+
+/// A `Text` showing a message without arguments, updated when the language changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LocalizedText(pub &'static str);
+
+/// Plugin for translations: the `Locale` resource, `LocalizedText` and the `language` command
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_console_command("language", "language [code] - show or change the interface language")
+            .add_systems(Update, (
+                handle_language_commands,
+                apply_language_setting.after(handle_language_commands),
+                refresh_localized_text.after(apply_language_setting),
+            ));
+    }
+}
+
+/// Switches `Locale` when the language setting changes
+pub fn apply_language_setting(config: Res<GameConfig>, mut locale: ResMut<Locale>) {
+    if !config.is_changed() || match_language(&config.interface.language) == Some(locale.language()) {
+        return;
+    }
+    *locale = Locale::new(&config.interface.language);
+    info!("Interface language set to {}", locale.language());
+}
+
+/// Fills in new `LocalizedText`s, and all of them after a language change
+pub fn refresh_localized_text(
+    locale: Res<Locale>,
+    mut all: Query<(&LocalizedText, &mut Text)>,
+    mut added: Query<(&LocalizedText, &mut Text), Added<LocalizedText>>,
+) {
+    if locale.is_changed() {
+        for (localized, mut text) in all.iter_mut() {
+            text.0 = locale.text(localized.0);
+        }
+    } else {
+        for (localized, mut text) in added.iter_mut() {
+            text.0 = locale.text(localized.0);
+        }
+    }
+}
+
+/// Console command: `language [code]`; the choice is saved with the settings
+pub fn handle_language_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut config: ResMut<GameConfig>,
+    locale: Res<Locale>,
+) {
+    for command in console_commands.read() {
+        if command.name != "language" {
+            continue;
+        }
+        let available = available_languages().collect::<Vec<_>>().join(", ");
+        let Some(requested) = command.args.first() else {
+            console.print(format!("Language: {} (available: {})", locale.language(), available));
+            continue;
+        };
+        match match_language(requested) {
+            Some(language) => {
+                config.interface.language = language.to_string();
+                console.print(format!("Language set to {}", language));
+            }
+            None => console.print(format!("No translation for '{}' (available: {})", requested, available)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Message ids defined in a locale file (messages start at the beginning of a line)
+    fn message_ids(source: &str) -> Vec<&str> {
+        assert!(FluentResource::try_new(source.to_string()).is_ok(), "locale file has syntax errors");
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id))
+            .collect()
+    }
+
+    #[test]
+    fn test_locales() {
+        // Every translation parses and has exactly the fallback's messages
+        let fallback = message_ids(LOCALES[0].1);
+        for (code, source) in LOCALES {
+            assert_eq!(message_ids(source), fallback, "messages of {}", code);
+        }
+
+        assert_eq!(match_language("de-AT"), Some("de"));
+        assert_eq!(match_language("en-us"), Some("en-US"));
+        assert_eq!(match_language("fr"), None);
+
+        let english = Locale::new("en-US");
+        assert_eq!(english.text("save-button"), "Save");
+        assert_eq!(english.format("toast-save-errors", &[("count", 1.into())]), "World save finished with 1 error (see `errors`)");
+        assert_eq!(english.text("no-such-message"), "no-such-message");
+        assert_eq!(
+            english.format("autosave-dialog-body", &[("count", 2.into()), ("errors", "disk full".into())]),
+            "The last 2 autosaves could not write to disk:\n\ndisk full\n\nYour recent edits are NOT saved. \
+             Check free disk space and permissions on the save folder, then retry."
+        );
+
+        assert_eq!(Locale::new("fr").language(), FALLBACK_LANGUAGE);
+        let german = Locale::new("de");
+        assert_eq!(german.format("toast-season-arrived", &[("season", "winter".into())]), "Der Winter ist da");
+        assert_eq!(german.format("toast-mods-reloaded", &[("packs", 3.into())]), "3 Mod-Pakete neu geladen");
+    }
+}
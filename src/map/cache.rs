@@ -0,0 +1,486 @@
+use bevy::prelude::*;
+use super::{map_tile_for_terrain, MapTilePos, MAP_TILE_GRASS_SPARSE, MAP_TILE_UNKNOWN};
+use crate::error::Failures;
+use crate::tiles::{ChunkPos, TileId, CHUNK_AREA, CHUNK_SIZE, CHUNK_SIZE_I32, LAYER_GROUND, TILE_EMPTY, TILE_GRASS};
+use crate::world::serialization::SerializationError;
+use crate::world::{generate_chunk, ExploredChunks, WorldManager};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Magic number for map cache files ("MAPC" in ASCII)
+const MAP_CACHE_MAGIC: [u8; 4] = [b'M', b'A', b'P', b'C'];
+
+/// Current map cache file format version (v2 stores each chunk's terrain summary after its ground)
+const MAP_CACHE_VERSION: u16 = 2;
+
+/// A chunk's ground layer and its terrain summary, recomputed only when the ground changes
+/// Map cells of a chunk or larger merge the summaries instead of counting every tile
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapChunk {
+    pub ground: Box<[TileId; CHUNK_AREA]>,
+    pub terrain: TerrainHistogram,
+}
+
+impl MapChunk {
+    pub fn new(ground: &[TileId; CHUNK_AREA]) -> Self {
+        Self {
+            ground: Box::new(*ground),
+            terrain: TerrainHistogram::of_tiles(ground),
+        }
+    }
+}
+
+/// Ground layer snapshot of every chunk seen since startup
+/// Shared by the map modal and the minimap so neither has to read chunk data directly,
+/// and kept after chunks unload so explored terrain stays on the map
+#[derive(Resource, Default)]
+pub struct MapCache {
+    pub chunks: HashMap<ChunkPos, MapChunk>,
+    /// Chunks currently loaded in the world (drawn at full brightness)
+    pub loaded: HashSet<ChunkPos>,
+}
+
+/// How much the player currently knows about an area of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum FogState {
+    /// Never seen - drawn black
+    #[default]
+    Unexplored,
+    /// Seen before but not loaded - drawn dimmed from the cache
+    Explored,
+    /// Currently loaded - drawn at full brightness
+    Visible,
+}
+
+impl FogState {
+    /// Tint applied to the map tile image
+    pub fn tint(&self) -> Color {
+        match self {
+            FogState::Unexplored => Color::BLACK,
+            FogState::Explored => Color::srgb(0.5, 0.5, 0.55),
+            FogState::Visible => Color::WHITE,
+        }
+    }
+}
+
+/// Terrain and fog summary of one map cell
+#[derive(Debug, Clone, Default)]
+pub struct MapCell {
+    pub terrain: TerrainHistogram,
+    /// Best fog state of any chunk overlapping the cell
+    pub fog: FogState,
+}
+
+impl MapCell {
+    /// Map tile index and tint to draw this cell with
+    pub fn appearance(&self) -> (usize, Color) {
+        (self.terrain.map_tile(), self.fog.tint())
+    }
+}
+
+impl MapCache {
+    /// Store a chunk's ground layer, returning true if it differs from the cached copy
+    pub fn update_chunk(&mut self, pos: ChunkPos, ground: &[TileId; CHUNK_AREA]) -> bool {
+        match self.chunks.get_mut(&pos) {
+            Some(cached) if *cached.ground == *ground => false,
+            Some(cached) => {
+                *cached.ground = *ground;
+                cached.terrain = TerrainHistogram::of_tiles(ground);
+                true
+            }
+            None => {
+                self.chunks.insert(pos, MapChunk::new(ground));
+                true
+            }
+        }
+    }
+
+    /// Fog state of a single chunk
+    pub fn fog_state(&self, pos: &ChunkPos) -> FogState {
+        if self.loaded.contains(pos) {
+            FogState::Visible
+        } else if self.chunks.contains_key(pos) {
+            FogState::Explored
+        } else {
+            FogState::Unexplored
+        }
+    }
+
+    /// Summarize the given chunks into map cells of `tiles_per_cell` world tiles
+    /// Cells of a chunk or more only read the chunks' stored summaries
+    pub fn build_cells(
+        &self,
+        tiles_per_cell: u32,
+        chunks: impl Iterator<Item = ChunkPos>,
+    ) -> HashMap<MapTilePos, MapCell> {
+        let mut cells: HashMap<MapTilePos, MapCell> = HashMap::new();
+        let cell = tiles_per_cell.max(1) as i32;
+
+        for chunk_pos in chunks {
+            let Some(chunk) = self.chunks.get(&chunk_pos) else {
+                continue;
+            };
+
+            let fog = self.fog_state(&chunk_pos);
+            let origin_x = chunk_pos.x * CHUNK_SIZE_I32;
+            let origin_y = chunk_pos.y * CHUNK_SIZE_I32;
+
+            if cell >= CHUNK_SIZE_I32 {
+                // Whole chunk falls into a single cell
+                let map_pos = MapTilePos {
+                    x: origin_x.div_euclid(cell),
+                    y: origin_y.div_euclid(cell),
+                };
+                let map_cell = cells.entry(map_pos).or_default();
+                map_cell.fog = map_cell.fog.max(fog);
+                map_cell.terrain.merge(&chunk.terrain);
+            } else {
+                // Chunk spans several cells - bucket each tile
+                for (index, tile_id) in chunk.ground.iter().enumerate() {
+                    let local_x = (index % CHUNK_SIZE) as i32;
+                    let local_y = (index / CHUNK_SIZE) as i32;
+                    let map_pos = MapTilePos {
+                        x: (origin_x + local_x).div_euclid(cell),
+                        y: (origin_y + local_y).div_euclid(cell),
+                    };
+                    let map_cell = cells.entry(map_pos).or_default();
+                    map_cell.fog = map_cell.fog.max(fog);
+                    map_cell.terrain.add(*tile_id, 1);
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+/// Count of each ground tile type within a map cell
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TerrainHistogram {
+    pub counts: HashMap<TileId, u32>,
+    pub total: u32,
+}
+
+impl TerrainHistogram {
+    pub fn add(&mut self, tile_id: TileId, count: u32) {
+        *self.counts.entry(tile_id).or_default() += count;
+        self.total += count;
+    }
+
+    /// Histogram of a set of tiles (e.g. a chunk's ground layer)
+    pub fn of_tiles(tiles: &[TileId]) -> Self {
+        let mut histogram = Self::default();
+        for tile_id in tiles {
+            histogram.add(*tile_id, 1);
+        }
+        histogram
+    }
+
+    /// Add another histogram's counts to this one
+    pub fn merge(&mut self, other: &TerrainHistogram) {
+        for (tile_id, count) in other.counts.iter() {
+            self.add(*tile_id, *count);
+        }
+    }
+
+    /// Most common non-empty tile and its share of the cell (0.0 - 1.0)
+    pub fn dominant(&self) -> Option<(TileId, f32)> {
+        self.counts
+            .iter()
+            .filter(|(tile_id, _)| **tile_id != TILE_EMPTY)
+            .max_by_key(|(tile_id, count)| (**count, std::cmp::Reverse(**tile_id)))
+            .map(|(tile_id, count)| (*tile_id, *count as f32 / self.total as f32))
+    }
+
+    /// Choose the map tile that best represents this terrain mix
+    pub fn map_tile(&self) -> usize {
+        match self.dominant() {
+            // No terrain at all - treat as unexplored
+            None => MAP_TILE_UNKNOWN,
+            // A clear majority gets its own map tile
+            Some((tile_id, share)) if share > 0.5 => map_tile_for_terrain(tile_id),
+            // Mixed terrain with grass in it reads as sparse grass
+            Some(_) if self.counts.get(&TILE_GRASS).copied().unwrap_or(0) > 0 => MAP_TILE_GRASS_SPARSE,
+            Some((tile_id, _)) => map_tile_for_terrain(tile_id),
+        }
+    }
+}
+
+/// Save the map cache (ground layers of explored chunks and their terrain summaries) to disk
+pub fn save_map_cache<P: AsRef<Path>>(cache: &MapCache, path: P) -> Result<(), SerializationError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut body = Vec::with_capacity(4 + cache.chunks.len() * (8 + CHUNK_AREA * 2 + 2));
+    body.extend_from_slice(&(cache.chunks.len() as u32).to_le_bytes());
+    for (pos, chunk) in cache.chunks.iter() {
+        body.extend_from_slice(&pos.x.to_le_bytes());
+        body.extend_from_slice(&pos.y.to_le_bytes());
+        for tile in chunk.ground.iter() {
+            body.extend_from_slice(&tile.to_le_bytes());
+        }
+        // Summary: entry count, then (tile id, count) pairs
+        body.extend_from_slice(&(chunk.terrain.counts.len() as u16).to_le_bytes());
+        for (tile_id, count) in chunk.terrain.counts.iter() {
+            body.extend_from_slice(&tile_id.to_le_bytes());
+            body.extend_from_slice(&count.to_le_bytes());
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(body.len() + 10);
+    bytes.extend_from_slice(&MAP_CACHE_MAGIC);
+    bytes.extend_from_slice(&MAP_CACHE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&body);
+    bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Load a map cache saved by `save_map_cache` (nothing is marked loaded)
+/// v1 files have no summaries; they are computed from the ground layers
+pub fn load_map_cache<P: AsRef<Path>>(path: P) -> Result<MapCache, SerializationError> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < 14 || bytes[0..4] != MAP_CACHE_MAGIC {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != 1 && version != MAP_CACHE_VERSION {
+        return Err(SerializationError::InvalidVersion(version));
+    }
+
+    let body = &bytes[6..bytes.len() - 4];
+    let checksum = &bytes[bytes.len() - 4..];
+    if crc32fast::hash(body) != u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) {
+        return Err(SerializationError::InvalidChecksum);
+    }
+
+    let mut reader = body;
+    let mut word = [0u8; 4];
+    let mut half = [0u8; 2];
+    reader.read_exact(&mut word)?;
+    let count = u32::from_le_bytes(word) as usize;
+
+    let mut cache = MapCache::default();
+    for _ in 0..count {
+        reader.read_exact(&mut word)?;
+        let x = i32::from_le_bytes(word);
+        reader.read_exact(&mut word)?;
+        let y = i32::from_le_bytes(word);
+        let mut ground = Box::new([TILE_EMPTY; CHUNK_AREA]);
+        for tile in ground.iter_mut() {
+            reader.read_exact(&mut half)?;
+            *tile = u16::from_le_bytes(half);
+        }
+
+        let terrain = if version == 1 {
+            TerrainHistogram::of_tiles(&*ground)
+        } else {
+            reader.read_exact(&mut half)?;
+            let mut terrain = TerrainHistogram::default();
+            for _ in 0..u16::from_le_bytes(half) {
+                reader.read_exact(&mut half)?;
+                reader.read_exact(&mut word)?;
+                terrain.add(u16::from_le_bytes(half), u32::from_le_bytes(word));
+            }
+            terrain
+        };
+        cache.chunks.insert(ChunkPos::new(x, y), MapChunk { ground, terrain });
+    }
+    if !reader.is_empty() {
+        return Err(SerializationError::InvalidChunkSize(body.len()));
+    }
+
+    Ok(cache)
+}
+
+/// Startup system that restores the map cache saved by the last "Save World"
+pub fn load_map_cache_from_disk(world_manager: Res<WorldManager>, mut cache: ResMut<MapCache>, mut failures: Failures) {
+    let path = world_manager.get_map_cache_path();
+    if !path.exists() {
+        return;
+    }
+
+    match load_map_cache(&path) {
+        Ok(loaded) => {
+            info!("Loaded map cache with {} explored chunks", loaded.chunks.len());
+            *cache = loaded;
+        }
+        Err(e) => failures.report(format!("Failed to load the map from {}", path.display()), e),
+    }
+}
+
+/// Fills in map cache entries for explored chunks the saved map cache doesn't cover
+/// (explored since the last "Save World"), reading their saved file or regenerating them
+pub fn restore_explored_map(
+    world_manager: Res<WorldManager>,
+    explored: Res<ExploredChunks>,
+    mut cache: ResMut<MapCache>,
+    mut failures: Failures,
+) {
+    let missing: Vec<ChunkPos> = explored
+        .chunks
+        .iter()
+        .filter(|pos| !cache.chunks.contains_key(pos))
+        .copied()
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+
+    for chunk_pos in &missing {
+        let chunk_data = match world_manager.load_stored_chunk(chunk_pos) {
+            Ok(Some(chunk_data)) => chunk_data,
+            Ok(None) => generate_chunk(*chunk_pos, &world_manager.generator),
+            Err(e) => {
+                failures.report(format!("Failed to read chunk ({}, {}) for the map", chunk_pos.x, chunk_pos.y), e);
+                generate_chunk(*chunk_pos, &world_manager.generator)
+            }
+        };
+        cache.update_chunk(*chunk_pos, &chunk_data.layers[LAYER_GROUND]);
+    }
+    info!("Restored {} explored chunks onto the map", missing.len());
+}
+
+/// Copies the ground layer of loaded chunks into the map cache when the world changes
+pub fn update_map_cache(world_manager: Res<WorldManager>, mut cache: ResMut<MapCache>) {
+    if !world_manager.is_changed() {
+        return;
+    }
+
+    let mut changed = false;
+    if cache.loaded.len() != world_manager.active_chunks.len()
+        || !world_manager.active_chunks.keys().all(|pos| cache.loaded.contains(pos))
+    {
+        cache.bypass_change_detection().loaded = world_manager.active_chunks.keys().copied().collect();
+        changed = true;
+    }
+
+    for chunk_pos in world_manager.active_chunks.keys() {
+        if let Some(chunk_data) = world_manager.chunk_cache.get(chunk_pos) {
+            changed |= cache
+                .bypass_change_detection()
+                .update_chunk(*chunk_pos, &chunk_data.layers[LAYER_GROUND]);
+        }
+    }
+
+    if changed {
+        cache.set_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::MAP_TILE_STONE;
+    use crate::tiles::{TILE_CLAY, TILE_DIRT, TILE_STONE};
+
+    #[test]
+    fn test_update_chunk_reports_changes() {
+        let mut cache = MapCache::default();
+        let pos = ChunkPos::new(0, 0);
+        let mut ground = [TILE_GRASS; CHUNK_AREA];
+
+        assert!(cache.update_chunk(pos, &ground));
+        assert!(!cache.update_chunk(pos, &ground));
+
+        ground[5] = TILE_DIRT;
+        assert!(cache.update_chunk(pos, &ground));
+
+        // The summary follows the ground
+        let terrain = &cache.chunks[&pos].terrain;
+        assert_eq!(terrain.counts[&TILE_DIRT], 1);
+        assert_eq!(terrain.counts[&TILE_GRASS], CHUNK_AREA as u32 - 1);
+        assert_eq!(terrain.total, CHUNK_AREA as u32);
+    }
+
+    #[test]
+    fn test_build_cells_negative_chunks() {
+        let mut cache = MapCache::default();
+        cache.update_chunk(ChunkPos::new(-1, -1), &[TILE_DIRT; CHUNK_AREA]);
+
+        // Chunk-sized cells: one cell per chunk
+        let cells = cache.build_cells(CHUNK_SIZE as u32, cache.chunks.keys().copied());
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[&MapTilePos { x: -1, y: -1 }].terrain.total, CHUNK_AREA as u32);
+
+        // Quarter-chunk cells: 4x4 cells per chunk, all in negative space
+        let cells = cache.build_cells(8, cache.chunks.keys().copied());
+        assert_eq!(cells.len(), 16);
+        assert!(cells.keys().all(|pos| (-4..0).contains(&pos.x) && (-4..0).contains(&pos.y)));
+    }
+
+    #[test]
+    fn test_cell_fog_uses_best_chunk_state() {
+        let mut cache = MapCache::default();
+        cache.update_chunk(ChunkPos::new(0, 0), &[TILE_GRASS; CHUNK_AREA]);
+        cache.update_chunk(ChunkPos::new(1, 0), &[TILE_GRASS; CHUNK_AREA]);
+        cache.loaded.insert(ChunkPos::new(1, 0));
+
+        assert_eq!(cache.fog_state(&ChunkPos::new(0, 0)), FogState::Explored);
+        assert_eq!(cache.fog_state(&ChunkPos::new(1, 0)), FogState::Visible);
+        assert_eq!(cache.fog_state(&ChunkPos::new(5, 5)), FogState::Unexplored);
+
+        // Two chunks per cell: the cell is visible because one chunk is loaded
+        let cells = cache.build_cells(CHUNK_SIZE as u32 * 2, cache.chunks.keys().copied());
+        assert_eq!(cells[&MapTilePos { x: 0, y: 0 }].fog, FogState::Visible);
+
+        // One chunk per cell: each cell keeps its own state
+        let cells = cache.build_cells(CHUNK_SIZE as u32, cache.chunks.keys().copied());
+        assert_eq!(cells[&MapTilePos { x: 0, y: 0 }].fog, FogState::Explored);
+    }
+
+    #[test]
+    fn test_save_and_load_map_cache() {
+        let path = std::env::temp_dir().join("test_map_cache.bin");
+        let mut original = MapCache::default();
+        let mut ground = [TILE_GRASS; CHUNK_AREA];
+        ground[17] = TILE_DIRT;
+        original.update_chunk(ChunkPos::new(-3, 7), &ground);
+        original.update_chunk(ChunkPos::new(0, 0), &[TILE_DIRT; CHUNK_AREA]);
+        original.loaded.insert(ChunkPos::new(0, 0));
+
+        save_map_cache(&original, &path).expect("Failed to save map cache");
+        let loaded = load_map_cache(&path).expect("Failed to load map cache");
+
+        assert_eq!(loaded.chunks, original.chunks);
+        assert!(loaded.loaded.is_empty());
+
+        // v1 files (ground only) still load, with the summaries computed
+        let mut body = 1u32.to_le_bytes().to_vec();
+        body.extend_from_slice(&(-3i32).to_le_bytes());
+        body.extend_from_slice(&7i32.to_le_bytes());
+        for tile in ground.iter() {
+            body.extend_from_slice(&tile.to_le_bytes());
+        }
+        let mut bytes = MAP_CACHE_MAGIC.to_vec();
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+        let loaded = load_map_cache(&path).expect("Failed to load v1 map cache");
+        assert_eq!(loaded.chunks[&ChunkPos::new(-3, 7)], original.chunks[&ChunkPos::new(-3, 7)]);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_mixed_terrain_reads_as_sparse_grass() {
+        let mut histogram = TerrainHistogram::default();
+        histogram.add(TILE_GRASS, 50);
+        histogram.add(TILE_DIRT, 50);
+        assert_eq!(histogram.map_tile(), MAP_TILE_GRASS_SPARSE);
+
+        histogram.add(TILE_DIRT, 10);
+        assert_eq!(histogram.map_tile(), map_tile_for_terrain(TILE_DIRT));
+
+        // A cell mostly covered by a deposit shows it
+        let mut outcrop = TerrainHistogram::default();
+        outcrop.add(TILE_STONE, 9);
+        outcrop.add(TILE_GRASS, 7);
+        assert_eq!(outcrop.map_tile(), MAP_TILE_STONE);
+        assert_ne!(map_tile_for_terrain(TILE_CLAY), map_tile_for_terrain(TILE_STONE));
+    }
+}
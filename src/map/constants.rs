@@ -1,6 +1,27 @@
+use crate::tiles::{TileId, TILE_CLAY, TILE_DIRT, TILE_GRASS, TILE_PATH, TILE_SAND, TILE_STONE, TILE_WATER};
+
 /// Map tile size in pixels (Minifantasy standard)
 pub const MAP_TILE_SIZE: f32 = 8.0;
 
+/// World tiles per map cell side at the most detailed map zoom level
+pub const MAP_FINE_CELL_TILES: u32 = 4;
+
+/// Largest map texture side in pixels before cells shrink to one pixel each
+pub const MAX_MAP_TEXTURE_SIZE: u32 = 4096;
+
+/// Allowed range for `MapConfig.chunks_per_map_tile`
+pub const MIN_CHUNKS_PER_MAP_TILE: u32 = 1;
+pub const MAX_CHUNKS_PER_MAP_TILE: u32 = 16;
+
+/// Minimap grid size (cells per side)
+pub const MINIMAP_CELLS: i32 = 32;
+
+/// World tiles per minimap cell side
+pub const MINIMAP_CELL_TILES: u32 = 8;
+
+/// On-screen size of a minimap cell in pixels
+pub const MINIMAP_CELL_SIZE: f32 = 4.0;
+
 /// Map tile indices from Minifantasy_MapsLandAndSea.png (216×88 pixels = 27×11 tiles)
 /// Organized in a grid layout
 
@@ -9,6 +30,8 @@ pub const MAP_TILE_GRASS_PLAIN: usize = 0;      // Bright grass
 pub const MAP_TILE_GRASS_SPARSE: usize = 1;     // Light grass
 pub const MAP_TILE_DIRT: usize = 2;             // Brown dirt
 pub const MAP_TILE_SAND: usize = 3;             // Sandy terrain
+pub const MAP_TILE_STONE: usize = 4;            // Grey stony ground
+pub const MAP_TILE_CLAY: usize = 5;             // Reddish earth
 
 // Water tiles (row 2-5)
 pub const MAP_TILE_WATER_DEEP: usize = 54;      // Row 2, col 0 - Dark water
@@ -20,3 +43,16 @@ pub const MAP_TILE_UNKNOWN: usize = 54;         // Use deep water for unexplored
 /// Tileset grid dimensions
 pub const MAP_TILESET_COLS: usize = 27;
 pub const MAP_TILESET_ROWS: usize = 11;
+
+/// Map tile used to draw a world terrain tile type
+pub fn map_tile_for_terrain(tile_id: TileId) -> usize {
+    match tile_id {
+        TILE_GRASS => MAP_TILE_GRASS_PLAIN,
+        TILE_DIRT | TILE_PATH => MAP_TILE_DIRT,
+        TILE_SAND => MAP_TILE_SAND,
+        TILE_STONE => MAP_TILE_STONE,
+        TILE_CLAY => MAP_TILE_CLAY,
+        TILE_WATER => MAP_TILE_WATER_SHALLOW,
+        _ => MAP_TILE_UNKNOWN,
+    }
+}
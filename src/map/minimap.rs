@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use super::{
+    world_to_map_tile, FogState, MapCache, MapTilePos, MAP_TILE_UNKNOWN, MINIMAP_CELLS, MINIMAP_CELL_SIZE,
+    MINIMAP_CELL_TILES,
+};
+use crate::assets::SpriteAssets;
+use crate::theme::{KeepColors, ThemedPanel};
+use crate::tiles::{ChunkPos, CHUNK_SIZE_I32};
+use crate::world::WorldOrigin;
+
+/// Size of the camera marker dot in pixels
+const MINIMAP_MARKER_SIZE: f32 = 4.0;
+
+/// Minimap visibility and the area it currently shows
+#[derive(Resource)]
+pub struct MinimapState {
+    pub visible: bool,
+    /// Bottom-left cell of the area currently drawn (None until first draw)
+    pub origin: Option<MapTilePos>,
+}
+
+impl Default for MinimapState {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            origin: None,
+        }
+    }
+}
+
+/// Marker component for the minimap root node
+#[derive(Component)]
+pub struct Minimap;
+
+/// A minimap cell, offset from the bottom-left of the minimap grid
+#[derive(Component)]
+pub struct MinimapCell {
+    pub dx: i32,
+    pub dy: i32,
+}
+
+/// Marker component for the camera position dot on the minimap
+#[derive(Component)]
+pub struct MinimapMarker;
+
+/// Sets up the corner minimap (a fixed grid of cells updated in place)
+pub fn setup_minimap(
+    mut commands: Commands,
+    sprites: Res<SpriteAssets>,
+) {
+    let grid_size = MINIMAP_CELLS as f32 * MINIMAP_CELL_SIZE;
+
+    commands
+        .spawn((
+            Minimap,
+            ThemedPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(grid_size),
+                height: Val::Px(grid_size),
+                display: Display::Grid,
+                grid_template_columns: vec![GridTrack::px(MINIMAP_CELL_SIZE); MINIMAP_CELLS as usize],
+                grid_template_rows: vec![GridTrack::px(MINIMAP_CELL_SIZE); MINIMAP_CELLS as usize],
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BorderColor::all(Color::srgb(0.8, 0.8, 0.8)),
+            BackgroundColor(Color::srgb(0.1, 0.1, 0.15)),
+            Visibility::Inherited,
+            ZIndex(500),
+        ))
+        .with_children(|grid| {
+            // Cells from top to bottom, left to right
+            for dy in (0..MINIMAP_CELLS).rev() {
+                for dx in 0..MINIMAP_CELLS {
+                    grid.spawn((
+                        MinimapCell { dx, dy },
+                        ImageNode {
+                            image: sprites.map_tiles.image.clone(),
+                            texture_atlas: Some(sprites.map_tiles.atlas(MAP_TILE_UNKNOWN)),
+                            color: FogState::Unexplored.tint(),
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Px(MINIMAP_CELL_SIZE),
+                            height: Val::Px(MINIMAP_CELL_SIZE),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+
+            // Camera marker (positioned by update_minimap)
+            grid.spawn((
+                MinimapMarker,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(MINIMAP_MARKER_SIZE),
+                    height: Val::Px(MINIMAP_MARKER_SIZE),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(1.0, 0.2, 0.2)),
+                BorderRadius::all(Val::Px(MINIMAP_MARKER_SIZE / 2.0)),
+                KeepColors,
+                ZIndex(10),
+            ));
+        });
+}
+
+/// Toggles the minimap when 'N' key is pressed
+pub fn toggle_minimap(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut minimap_state: ResMut<MinimapState>,
+    mut minimap_query: Single<&mut Visibility, With<Minimap>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyN) {
+        minimap_state.visible = !minimap_state.visible;
+    }
+
+    // Also picks up state restored from the previous session
+    if minimap_state.is_changed() {
+        let visibility = if minimap_state.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if **minimap_query != visibility {
+            **minimap_query = visibility;
+        }
+    }
+}
+
+/// Redraws minimap cells around the camera and moves the camera marker
+pub fn update_minimap(
+    mut minimap_state: ResMut<MinimapState>,
+    map_cache: Res<MapCache>,
+    camera_query: Query<&Transform, With<Camera2d>>,
+    world_origin: Res<WorldOrigin>,
+    mut cells: Query<(&MinimapCell, &mut ImageNode)>,
+    mut marker: Single<&mut Node, With<MinimapMarker>>,
+) {
+    if !minimap_state.visible {
+        return;
+    }
+
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let camera_pos = world_origin.to_world_vec2(camera_transform.translation.truncate());
+
+    // Keep the camera's cell in the middle of the grid
+    let camera_cell = world_to_map_tile(camera_pos, MINIMAP_CELL_TILES);
+    let origin = MapTilePos {
+        x: camera_cell.x - MINIMAP_CELLS / 2,
+        y: camera_cell.y - MINIMAP_CELLS / 2,
+    };
+
+    // Camera marker offset within the grid (UI y grows downwards)
+    let cell_world_size = super::map_tile_world_size(MINIMAP_CELL_TILES);
+    let grid_size = MINIMAP_CELLS as f32 * MINIMAP_CELL_SIZE;
+    let marker_x = (camera_pos.x / cell_world_size - origin.x as f32) * MINIMAP_CELL_SIZE;
+    let marker_y = (camera_pos.y / cell_world_size - origin.y as f32) * MINIMAP_CELL_SIZE;
+    marker.left = Val::Px(marker_x - MINIMAP_MARKER_SIZE / 2.0);
+    marker.top = Val::Px(grid_size - marker_y - MINIMAP_MARKER_SIZE / 2.0);
+
+    if minimap_state.origin == Some(origin) && !map_cache.is_changed() {
+        return;
+    }
+    minimap_state.origin = Some(origin);
+
+    // Only summarize the chunks under the minimap
+    let cell_tiles = MINIMAP_CELL_TILES as i32;
+    let min_chunk_x = (origin.x * cell_tiles).div_euclid(CHUNK_SIZE_I32);
+    let min_chunk_y = (origin.y * cell_tiles).div_euclid(CHUNK_SIZE_I32);
+    let max_chunk_x = ((origin.x + MINIMAP_CELLS) * cell_tiles - 1).div_euclid(CHUNK_SIZE_I32);
+    let max_chunk_y = ((origin.y + MINIMAP_CELLS) * cell_tiles - 1).div_euclid(CHUNK_SIZE_I32);
+    let chunks = (min_chunk_y..=max_chunk_y)
+        .flat_map(|y| (min_chunk_x..=max_chunk_x).map(move |x| ChunkPos::new(x, y)));
+    let map_cells = map_cache.build_cells(MINIMAP_CELL_TILES, chunks);
+
+    for (cell, mut image) in cells.iter_mut() {
+        let map_pos = MapTilePos {
+            x: origin.x + cell.dx,
+            y: origin.y + cell.dy,
+        };
+        let (index, tint) = map_cells
+            .get(&map_pos)
+            .map(|map_cell| map_cell.appearance())
+            .unwrap_or((MAP_TILE_UNKNOWN, FogState::Unexplored.tint()));
+
+        let current = image.texture_atlas.as_ref().map(|atlas| atlas.index);
+        if current != Some(index) || image.color != tint {
+            image.color = tint;
+            if let Some(atlas) = image.texture_atlas.as_mut() {
+                atlas.index = index;
+            }
+        }
+    }
+}
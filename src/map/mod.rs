@@ -1,25 +1,58 @@
+mod cache;
 mod constants;
-mod ui;
+mod minimap;
+mod modes;
 mod systems;
+mod texture;
+#[cfg(feature = "editor-ui")]
+mod ui;
+mod waypoints;
 
+pub use cache::*;
 pub use constants::*;
-pub use ui::*;
+pub use minimap::*;
+pub use modes::*;
 pub use systems::*;
+pub use texture::*;
+#[cfg(feature = "editor-ui")]
+pub use ui::*;
+pub use waypoints::*;
 
 use bevy::prelude::*;
+#[cfg(feature = "editor-ui")]
+use crate::console::console_closed;
+use crate::locale::Locale;
 
 /// Plugin for the world map system
+/// Without the `editor-ui` feature only the map cache is kept up to date (and saved), with no map or minimap
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MapConfig>()
             .init_resource::<MapState>()
-            .add_systems(Startup, setup_map_ui)
+            .init_resource::<MapCache>()
+            .init_resource::<MinimapState>()
+            .add_systems(Startup, load_map_cache_from_disk)
+            // After every Startup load (map cache, explored chunks)
+            .add_systems(PostStartup, restore_explored_map)
+            .add_systems(Update, update_map_cache);
+        #[cfg(feature = "editor-ui")]
+        app
+            .add_systems(Startup, (setup_map_ui, setup_minimap))
             .add_systems(Update, (
-                toggle_map_visibility,
-                update_map_display,
-            ));
+                toggle_map_visibility.run_if(console_closed),
+                zoom_map,
+                update_map_display.after(zoom_map).after(update_map_cache),
+                update_map_mode_tabs,
+                update_map_scale_label,
+                toggle_minimap.run_if(console_closed),
+                update_minimap.after(update_map_cache).after(toggle_minimap),
+                toggle_waypoint_labels.run_if(console_closed),
+                sync_waypoint_labels,
+                update_waypoint_label_visibility.after(sync_waypoint_labels),
+            ))
+            .add_observer(handle_map_right_click);
     }
 }
 
@@ -28,12 +61,15 @@ impl Plugin for MapPlugin {
 pub struct MapConfig {
     /// How many game world chunks are represented by one map tile
     pub chunks_per_map_tile: u32,
+    /// Whether waypoint labels float in the world when the camera is nearby
+    pub show_waypoint_labels: bool,
 }
 
 impl Default for MapConfig {
     fn default() -> Self {
         Self {
             chunks_per_map_tile: 4, // Default: 4 chunks = 1 map tile
+            show_waypoint_labels: true,
         }
     }
 }
@@ -42,6 +78,84 @@ impl Default for MapConfig {
 #[derive(Resource, Default)]
 pub struct MapState {
     pub visible: bool,
+    /// How much terrain each map cell represents
+    pub detail: MapDetail,
+    /// Which data layer the map cells show
+    pub mode: MapMode,
+}
+
+/// Map zoom levels, from most aggregated to most detailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapDetail {
+    /// One cell per `chunks_per_map_tile` × `chunks_per_map_tile` chunks
+    #[default]
+    Region,
+    /// One cell per chunk
+    Chunk,
+    /// One cell per `MAP_FINE_CELL_TILES` × `MAP_FINE_CELL_TILES` world tiles
+    Tiles,
+}
+
+impl MapDetail {
+    /// Width of a map cell in world tiles
+    pub fn tiles_per_cell(&self, chunks_per_map_tile: u32) -> u32 {
+        match self {
+            MapDetail::Region => chunks_per_map_tile * crate::tiles::CHUNK_SIZE as u32,
+            MapDetail::Chunk => crate::tiles::CHUNK_SIZE as u32,
+            MapDetail::Tiles => MAP_FINE_CELL_TILES,
+        }
+    }
+
+    /// On-screen size of a map cell in pixels
+    pub fn display_size(&self) -> f32 {
+        match self {
+            MapDetail::Region => MAP_TILE_SIZE * 4.0,
+            MapDetail::Chunk => MAP_TILE_SIZE * 2.0,
+            MapDetail::Tiles => MAP_TILE_SIZE,
+        }
+    }
+
+    /// Next more detailed level (stays at the most detailed)
+    pub fn zoom_in(&self) -> Self {
+        match self {
+            MapDetail::Region => MapDetail::Chunk,
+            MapDetail::Chunk | MapDetail::Tiles => MapDetail::Tiles,
+        }
+    }
+
+    /// Next less detailed level (stays at the least detailed)
+    pub fn zoom_out(&self) -> Self {
+        match self {
+            MapDetail::Tiles => MapDetail::Chunk,
+            MapDetail::Chunk | MapDetail::Region => MapDetail::Region,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            MapDetail::Region => "region",
+            MapDetail::Chunk => "chunk",
+            MapDetail::Tiles => "tiles",
+        }
+    }
+
+    /// The value `as_str` names; unknown names give the default
+    pub fn parse_or_default(s: &str) -> Self {
+        match s {
+            "chunk" => MapDetail::Chunk,
+            "tiles" => MapDetail::Tiles,
+            _ => MapDetail::Region, // Default to region
+        }
+    }
+
+    /// Description for the map legend
+    pub fn label(&self, locale: &Locale, chunks_per_map_tile: u32) -> String {
+        match self {
+            MapDetail::Region => locale.format("map-detail-region", &[("chunks", chunks_per_map_tile.into())]),
+            MapDetail::Chunk => locale.text("map-detail-chunk"),
+            MapDetail::Tiles => locale.format("map-detail-tiles", &[("tiles", MAP_FINE_CELL_TILES.into())]),
+        }
+    }
 }
 
 /// Marker component for the map modal root
@@ -51,3 +165,7 @@ pub struct MapModal;
 /// Marker component for the map content container
 #[derive(Component)]
 pub struct MapContent;
+
+/// Marker component for the area the map tiles are rendered into
+#[derive(Component)]
+pub struct MapDisplay;
@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use super::{FogState, MapCell, MapState, TerrainHistogram};
+use crate::config::GameConfig;
+use crate::theme::selection_outline;
+use crate::tiles::{TILE_DIRT, TILE_GRASS, TILE_SAND, TILE_WATER};
+
+/// Number of creatures in a cell that maps to the hottest population color
+const POPULATION_HEAT_MAX: f32 = 8.0;
+
+/// What the map cells show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapMode {
+    /// Cartographic terrain tiles
+    #[default]
+    Terrain,
+    /// Flat colors per biome classification
+    Biome,
+    /// Heatmap of how well plants grow on the ground
+    Fertility,
+    /// Heatmap of creatures per cell
+    Population,
+}
+
+impl MapMode {
+    pub const ALL: [MapMode; 4] = [
+        MapMode::Terrain,
+        MapMode::Biome,
+        MapMode::Fertility,
+        MapMode::Population,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MapMode::Terrain => "Terrain",
+            MapMode::Biome => "Biome",
+            MapMode::Fertility => "Fertility",
+            MapMode::Population => "Population",
+        }
+    }
+
+    /// Message id of the tab label
+    pub fn locale_id(&self) -> &'static str {
+        match self {
+            MapMode::Terrain => "map-mode-terrain",
+            MapMode::Biome => "map-mode-biome",
+            MapMode::Fertility => "map-mode-fertility",
+            MapMode::Population => "map-mode-population",
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            MapMode::Terrain => "terrain",
+            MapMode::Biome => "biome",
+            MapMode::Fertility => "fertility",
+            MapMode::Population => "population",
+        }
+    }
+
+    /// The value `as_str` names; unknown names give the default
+    pub fn parse_or_default(s: &str) -> Self {
+        match s {
+            "biome" => MapMode::Biome,
+            "fertility" => MapMode::Fertility,
+            "population" => MapMode::Population,
+            _ => MapMode::Terrain, // Default to terrain
+        }
+    }
+}
+
+/// Biome classification of a map cell, derived from its ground layer mix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapBiome {
+    Meadow,
+    Scrubland,
+    Barren,
+    Wetland,
+    Beach,
+}
+
+impl MapBiome {
+    /// Classify a cell from the share of each ground tile type
+    pub fn classify(terrain: &TerrainHistogram) -> Option<MapBiome> {
+        if terrain.total == 0 {
+            return None;
+        }
+        let share = |tile_id| {
+            terrain.counts.get(&tile_id).copied().unwrap_or(0) as f32 / terrain.total as f32
+        };
+
+        let biome = if share(TILE_WATER) > 0.5 {
+            MapBiome::Wetland
+        } else if share(TILE_SAND) > 0.5 {
+            MapBiome::Beach
+        } else if share(TILE_GRASS) >= 0.7 {
+            MapBiome::Meadow
+        } else if share(TILE_DIRT) >= 0.7 {
+            MapBiome::Barren
+        } else {
+            MapBiome::Scrubland
+        };
+        Some(biome)
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            MapBiome::Meadow => Color::srgb(0.35, 0.7, 0.3),
+            MapBiome::Scrubland => Color::srgb(0.6, 0.65, 0.35),
+            MapBiome::Barren => Color::srgb(0.55, 0.4, 0.25),
+            MapBiome::Wetland => Color::srgb(0.25, 0.45, 0.7),
+            MapBiome::Beach => Color::srgb(0.9, 0.85, 0.55),
+        }
+    }
+}
+
+/// How well plants grow on a tile type (0.0 - 1.0)
+pub fn tile_fertility(tile_id: u16) -> f32 {
+    match tile_id {
+        TILE_GRASS => 1.0,
+        TILE_DIRT => 0.4,
+        TILE_SAND => 0.1,
+        _ => 0.0,
+    }
+}
+
+/// Average fertility of a cell's ground tiles (0.0 - 1.0)
+pub fn cell_fertility(terrain: &TerrainHistogram) -> f32 {
+    if terrain.total == 0 {
+        return 0.0;
+    }
+    let weighted: f32 = terrain
+        .counts
+        .iter()
+        .map(|(tile_id, count)| tile_fertility(*tile_id) * *count as f32)
+        .sum();
+    weighted / terrain.total as f32
+}
+
+/// Blue (cold) to red (hot) heatmap color for a value in 0.0 - 1.0
+pub fn heat_color(value: f32) -> Color {
+    let t = value.clamp(0.0, 1.0);
+    if t < 0.5 {
+        let k = t * 2.0;
+        Color::srgb(0.1, 0.2 + 0.6 * k, 0.8 - 0.5 * k)
+    } else {
+        let k = (t - 0.5) * 2.0;
+        Color::srgb(0.1 + 0.85 * k, 0.8 - 0.6 * k, 0.3 - 0.2 * k)
+    }
+}
+
+/// Darken an overlay color according to fog of war
+pub fn apply_fog(color: Color, fog: FogState) -> Color {
+    let tint = fog.tint().to_srgba();
+    let base = color.to_srgba();
+    Color::srgb(base.red * tint.red, base.green * tint.green, base.blue * tint.blue)
+}
+
+/// Flat overlay color for a cell in a non-terrain map mode
+pub fn overlay_color(mode: MapMode, map_cell: &MapCell, population: u32) -> Color {
+    let color = match mode {
+        MapMode::Terrain => Color::WHITE,
+        MapMode::Biome => MapBiome::classify(&map_cell.terrain)
+            .map(|biome| biome.color())
+            .unwrap_or(Color::BLACK),
+        MapMode::Fertility => heat_color(cell_fertility(&map_cell.terrain)),
+        MapMode::Population => heat_color(population as f32 / POPULATION_HEAT_MAX),
+    };
+    apply_fog(color, map_cell.fog)
+}
+
+/// A clickable tab in the map modal that switches the map mode
+#[derive(Component)]
+pub struct MapModeTab(pub MapMode);
+
+/// Switches the map mode when a tab is clicked
+pub fn select_map_mode(
+    trigger: On<Pointer<Click>>,
+    tabs: Query<&MapModeTab>,
+    mut map_state: ResMut<MapState>,
+) {
+    if let Ok(tab) = tabs.get(trigger.entity) {
+        if map_state.mode != tab.0 {
+            map_state.mode = tab.0;
+            info!("Map mode set to {}", tab.0.label());
+        }
+    }
+}
+
+/// Highlights and outlines the tab of the active map mode
+pub fn update_map_mode_tabs(
+    mut commands: Commands,
+    map_state: Res<MapState>,
+    config: Res<GameConfig>,
+    mut tabs: Query<(Entity, &MapModeTab, &mut BackgroundColor, &mut BorderColor)>,
+) {
+    if !map_state.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    for (entity, tab, mut bg_color, mut border_color) in tabs.iter_mut() {
+        commands.entity(entity).remove::<Outline>();
+        if tab.0 == map_state.mode {
+            *bg_color = BackgroundColor(Color::srgb(0.3, 0.3, 0.45));
+            *border_color = BorderColor::all(Color::srgb(0.7, 0.7, 1.0));
+            commands.entity(entity).insert(selection_outline(&config.accessibility, border_color.top));
+        } else {
+            *bg_color = BackgroundColor(Color::srgb(0.2, 0.2, 0.28));
+            *border_color = BorderColor::all(Color::srgb(0.4, 0.4, 0.55));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biome_classification() {
+        let mut meadow = TerrainHistogram::default();
+        meadow.add(TILE_GRASS, 80);
+        meadow.add(TILE_DIRT, 20);
+        assert_eq!(MapBiome::classify(&meadow), Some(MapBiome::Meadow));
+
+        let mut mixed = TerrainHistogram::default();
+        mixed.add(TILE_GRASS, 50);
+        mixed.add(TILE_DIRT, 50);
+        assert_eq!(MapBiome::classify(&mixed), Some(MapBiome::Scrubland));
+
+        assert_eq!(MapBiome::classify(&TerrainHistogram::default()), None);
+    }
+
+    #[test]
+    fn test_cell_fertility() {
+        let mut terrain = TerrainHistogram::default();
+        terrain.add(TILE_GRASS, 50);
+        terrain.add(TILE_DIRT, 50);
+        assert!((cell_fertility(&terrain) - 0.7).abs() < 1e-6);
+        assert_eq!(cell_fertility(&TerrainHistogram::default()), 0.0);
+    }
+}
@@ -1,8 +1,12 @@
 use bevy::prelude::*;
 use bevy::input::keyboard::KeyCode;
-use super::{MapModal, MapState, MapConfig, MapContent, MAP_TILE_SIZE, MAP_TILESET_COLS, MAP_TILESET_ROWS, MAP_TILE_GRASS_PLAIN, MAP_TILE_DIRT, MAP_TILE_UNKNOWN};
-use crate::world::WorldManager;
-use crate::tiles::{ChunkPos, TILE_GRASS, TILE_DIRT, LAYER_GROUND, CHUNK_AREA};
+use bevy::input::mouse::MouseWheel;
+use bevy::ui::RelativeCursorPosition;
+use super::{MapModal, MapState, MapConfig, MapDisplay, MapCache, MapCanvas, MapMode, MapTextureGrid, TilesetPixels, overlay_color, world_to_map_tile, spawn_sign_icons, spawn_waypoint_pins, MAX_CHUNKS_PER_MAP_TILE, MIN_CHUNKS_PER_MAP_TILE};
+use crate::assets::SpriteAssets;
+use crate::entities::{CreatureFilter, Position};
+use crate::locale::Locale;
+use crate::world::WorldMetadata;
 use std::collections::HashMap;
 
 /// Toggles map visibility when 'M' key is pressed
@@ -13,115 +17,182 @@ pub fn toggle_map_visibility(
 ) {
     if keyboard.just_pressed(KeyCode::KeyM) {
         map_state.visible = !map_state.visible;
+    }
 
-        **modal_query = if map_state.visible {
+    // Also picks up state restored from the previous session
+    if map_state.is_changed() {
+        let visibility = if map_state.visible {
             Visibility::Visible
         } else {
             Visibility::Hidden
         };
+        if **modal_query != visibility {
+            **modal_query = visibility;
+        }
+    }
+}
+
+/// Changes the map detail level with the scroll wheel while the map is open
+pub fn zoom_map(
+    mut scroll_events: MessageReader<MouseWheel>,
+    mut map_state: ResMut<MapState>,
+) {
+    if !map_state.visible {
+        scroll_events.clear();
+        return;
+    }
+
+    let scroll: f32 = scroll_events.read().map(|event| event.y).sum();
+    let detail = if scroll > 0.0 {
+        map_state.detail.zoom_in()
+    } else if scroll < 0.0 {
+        map_state.detail.zoom_out()
+    } else {
+        return;
+    };
+
+    if detail != map_state.detail {
+        map_state.detail = detail;
+        info!("Map detail set to {:?}", detail);
+    }
+}
+
+/// A button that changes the map aggregation level by `delta` chunks per map tile
+#[derive(Component)]
+pub struct MapScaleButton(pub i32);
+
+/// Marker component for the text showing the current aggregation level
+#[derive(Component)]
+pub struct MapScaleLabel;
+
+/// Changes `chunks_per_map_tile` when a +/- button is clicked
+pub fn adjust_map_scale(
+    trigger: On<Pointer<Click>>,
+    buttons: Query<&MapScaleButton>,
+    mut map_config: ResMut<MapConfig>,
+) {
+    let Ok(button) = buttons.get(trigger.entity) else {
+        return;
+    };
+
+    let current = map_config.chunks_per_map_tile as i32;
+    let next = (current + button.0)
+        .clamp(MIN_CHUNKS_PER_MAP_TILE as i32, MAX_CHUNKS_PER_MAP_TILE as i32) as u32;
+    if next != map_config.chunks_per_map_tile {
+        map_config.chunks_per_map_tile = next;
+        info!("Map set to {} chunks per map tile", next);
+    }
+}
+
+/// Keeps the aggregation label in sync with `MapConfig`
+pub fn update_map_scale_label(
+    map_config: Res<MapConfig>,
+    locale: Res<Locale>,
+    mut label: Single<&mut Text, With<MapScaleLabel>>,
+) {
+    if !map_config.is_changed() && !locale.is_changed() {
+        return;
     }
+
+    label.0 = locale.format("map-scale", &[("chunks", map_config.chunks_per_map_tile.into())]);
 }
 
-/// Marker component for dynamically spawned map tiles
+/// Marker component for the image node showing the rendered map
 #[derive(Component)]
-pub struct MapTile;
+pub struct MapImage;
 
-/// Updates the map display based on loaded chunks
+/// Updates the map display from the map cache
+/// The whole map is painted into a single texture (one UI node regardless of world size)
+#[allow(clippy::too_many_arguments)]
 pub fn update_map_display(
     mut commands: Commands,
     map_state: Res<MapState>,
     map_config: Res<MapConfig>,
-    world_manager: Res<WorldManager>,
-    map_content_query: Single<Entity, With<MapContent>>,
-    existing_tiles: Query<Entity, With<MapTile>>,
-    assets: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    map_cache: Res<MapCache>,
+    metadata: Res<WorldMetadata>,
+    creatures: Query<&Position, CreatureFilter>,
+    map_display_query: Single<Entity, With<MapDisplay>>,
+    sprites: Res<SpriteAssets>,
+    locale: Res<Locale>,
+    mut images: ResMut<Assets<Image>>,
+    mut pending: Local<bool>,
 ) {
-    // Update when map is visible AND (map state changed OR world changed)
+    // Update when map is visible AND (map state or config changed OR world changed OR waypoints changed OR language changed)
+    // or a previous redraw was waiting for the tileset to load
     let should_update = map_state.visible &&
-        (map_state.is_changed() || world_manager.is_changed());
+        (*pending || map_state.is_changed() || map_config.is_changed() || map_cache.is_changed() || metadata.is_changed() || locale.is_changed());
 
     if !should_update {
         return;
     }
 
-    // Clear existing map tiles
-    for tile_entity in existing_tiles.iter() {
-        commands.entity(tile_entity).despawn();
+    // Terrain mode copies pixels from the map tileset, so wait until it is loaded
+    let tileset_handle = &sprites.map_tiles.image;
+    if map_state.mode == MapMode::Terrain && images.get(tileset_handle).is_none() {
+        *pending = true;
+        return;
+    }
+    *pending = false;
+
+    // Get the map display area and clear the previous map and legend
+    let map_content = *map_display_query;
+    commands.entity(map_content).despawn_children();
+
+    // Summarize cached terrain into map cells at the current detail level
+    let tiles_per_cell = map_state.detail.tiles_per_cell(map_config.chunks_per_map_tile);
+    let map_tiles = map_cache.build_cells(tiles_per_cell, map_cache.chunks.keys().copied());
+    let grid = MapTextureGrid::new(find_map_bounds(&map_tiles), map_state.detail.display_size());
+
+    // Population overlay counts snails and guardians (snapshot at redraw time)
+    let mut population: HashMap<MapTilePos, u32> = HashMap::new();
+    if map_state.mode == MapMode::Population {
+        for position in creatures.iter() {
+            let map_pos = world_to_map_tile(position.vec2(), tiles_per_cell);
+            *population.entry(map_pos).or_default() += 1;
+        }
     }
 
-    // Get the map content container
-    let map_content = *map_content_query;
-
-    // Load the map tileset
-    let texture = assets.load("maps/Minifantasy_MapsLandAndSea.png");
-    let layout = TextureAtlasLayout::from_grid(
-        UVec2::splat(MAP_TILE_SIZE as u32),
-        MAP_TILESET_COLS as u32,
-        MAP_TILESET_ROWS as u32,
-        None,
-        None,
-    );
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-
-    // Convert loaded chunks to map tiles
-    let map_tiles = convert_chunks_to_map_tiles(&world_manager, map_config.chunks_per_map_tile);
-
-    // Find the bounds of the map
-    let (min_x, max_x, min_y, max_y) = find_map_bounds(&map_tiles);
+    // Paint explored cells; anything left over stays black (unexplored)
+    let mut canvas = MapCanvas::new(grid);
+    if let Some(tileset) = images.get(tileset_handle).filter(|_| map_state.mode == MapMode::Terrain) {
+        let mut tileset = TilesetPixels::new(tileset);
+        for (map_pos, map_cell) in map_tiles.iter() {
+            let (tile_index, tint) = map_cell.appearance();
+            canvas.paint_tile(*map_pos, &mut tileset, tile_index, tint);
+        }
+    } else {
+        for (map_pos, map_cell) in map_tiles.iter() {
+            let count = population.get(map_pos).copied().unwrap_or(0);
+            canvas.paint_color(*map_pos, overlay_color(map_state.mode, map_cell, count));
+        }
+    }
+    let map_texture = images.add(canvas.into_image());
+    let display_size = grid.display_size();
 
-    // Render map tiles using actual sprites
     commands.entity(map_content).with_children(|parent| {
-        // Create a grid container for map tiles
         parent.spawn((
+            MapImage,
+            grid,
+            ImageNode::new(map_texture),
             Node {
-                display: Display::Grid,
-                grid_template_columns: vec![GridTrack::auto(); (max_x - min_x + 1) as usize],
-                grid_template_rows: vec![GridTrack::auto(); (max_y - min_y + 1) as usize],
-                column_gap: Val::Px(0.0),
-                row_gap: Val::Px(0.0),
+                width: Val::Px(display_size.x),
+                height: Val::Px(display_size.y),
                 ..default()
             },
-        )).with_children(|grid| {
-            // Render tiles from top to bottom, left to right
-            for y in (min_y..=max_y).rev() {
-                for x in min_x..=max_x {
-                    let map_pos = MapTilePos { x, y };
-                    let tile_index = if let Some(chunks) = map_tiles.get(&map_pos) {
-                        // Loaded chunks - analyze terrain to determine map tile
-                        determine_map_tile_from_chunks(chunks, &world_manager)
-                    } else {
-                        // Unloaded/unknown - use deep water for unexplored areas
-                        MAP_TILE_UNKNOWN
-                    };
-
-                    grid.spawn((
-                        MapTile,
-                        ImageNode {
-                            image: texture.clone(),
-                            texture_atlas: Some(TextureAtlas {
-                                layout: texture_atlas_layout.clone(),
-                                index: tile_index,
-                            }),
-                            ..default()
-                        },
-                        Node {
-                            width: Val::Px(MAP_TILE_SIZE),
-                            height: Val::Px(MAP_TILE_SIZE),
-                            ..default()
-                        },
-                    ));
-                }
-            }
+            RelativeCursorPosition::default(),
+        )).with_children(|map_image| {
+            spawn_sign_icons(map_image, &metadata, &grid, tiles_per_cell);
+            spawn_waypoint_pins(map_image, &metadata, &grid, tiles_per_cell);
         });
 
         // Add map legend/info
         parent.spawn((
-            Text::new(format!(
-                "Map Coverage: {} tiles | Chunks per tile: {} | Real-time terrain updates",
-                map_tiles.len(),
-                map_config.chunks_per_map_tile
-            )),
+            Text::new(locale.format("map-legend", &[
+                ("mode", locale.text(map_state.mode.locale_id()).into()),
+                ("cells", map_tiles.len().into()),
+                ("detail", map_state.detail.label(&locale, map_config.chunks_per_map_tile).into()),
+                ("waypoints", metadata.waypoints.len().into()),
+            ])),
             TextFont {
                 font_size: 12.0,
                 ..default()
@@ -136,36 +207,14 @@ pub fn update_map_display(
 }
 
 /// Represents a position in the map tile grid
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct MapTilePos {
-    x: i32,
-    y: i32,
-}
-
-/// Convert loaded world chunks to map tile positions
-fn convert_chunks_to_map_tiles(
-    world_manager: &WorldManager,
-    chunks_per_tile: u32,
-) -> HashMap<MapTilePos, Vec<ChunkPos>> {
-    let mut map_tiles: HashMap<MapTilePos, Vec<ChunkPos>> = HashMap::new();
-    let divisor = chunks_per_tile as i32;
-
-    for chunk_pos in world_manager.active_chunks.keys() {
-        // Convert chunk position to map tile position
-        let map_x = chunk_pos.x.div_euclid(divisor);
-        let map_y = chunk_pos.y.div_euclid(divisor);
-        let map_pos = MapTilePos { x: map_x, y: map_y };
-
-        map_tiles.entry(map_pos)
-            .or_insert_with(Vec::new)
-            .push(*chunk_pos);
-    }
-
-    map_tiles
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapTilePos {
+    pub x: i32,
+    pub y: i32,
 }
 
 /// Find the bounding box of map tiles
-fn find_map_bounds(map_tiles: &HashMap<MapTilePos, Vec<ChunkPos>>) -> (i32, i32, i32, i32) {
+fn find_map_bounds<T>(map_tiles: &HashMap<MapTilePos, T>) -> (i32, i32, i32, i32) {
     if map_tiles.is_empty() {
         return (0, 0, 0, 0);
     }
@@ -184,47 +233,3 @@ fn find_map_bounds(map_tiles: &HashMap<MapTilePos, Vec<ChunkPos>>) -> (i32, i32,
 
     (min_x, max_x, min_y, max_y)
 }
-
-/// Analyze chunks to determine which map tile to display
-/// Returns the appropriate map tile index based on terrain composition
-fn determine_map_tile_from_chunks(chunks: &[ChunkPos], world_manager: &WorldManager) -> usize {
-    let mut total_grass = 0;
-    let mut total_dirt = 0;
-    let mut total_tiles = 0;
-
-    // Analyze all chunks that contribute to this map tile
-    for chunk_pos in chunks {
-        if let Some(chunk_data) = world_manager.chunk_cache.get(chunk_pos) {
-            // Count terrain types on the ground layer
-            for tile_id in chunk_data.layers[LAYER_GROUND].iter() {
-                total_tiles += 1;
-                match *tile_id {
-                    TILE_GRASS => total_grass += 1,
-                    TILE_DIRT => total_dirt += 1,
-                    _ => {} // Ignore empty tiles
-                }
-            }
-        }
-    }
-
-    // If no tiles were analyzed, default to grass
-    if total_tiles == 0 {
-        return MAP_TILE_GRASS_PLAIN;
-    }
-
-    // Calculate percentages
-    let grass_percentage = (total_grass as f32 / total_tiles as f32) * 100.0;
-    let dirt_percentage = (total_dirt as f32 / total_tiles as f32) * 100.0;
-
-    // Determine map tile based on dominant terrain
-    // If more than 50% dirt, show as dirt
-    if dirt_percentage > 50.0 {
-        MAP_TILE_DIRT
-    } else if grass_percentage > 30.0 {
-        // More than 30% grass shows as grass
-        MAP_TILE_GRASS_PLAIN
-    } else {
-        // Mixed or sparse - default to grass
-        MAP_TILE_GRASS_PLAIN
-    }
-}
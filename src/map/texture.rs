@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use super::{MapTilePos, MAP_TILESET_COLS, MAP_TILE_SIZE, MAX_MAP_TEXTURE_SIZE};
+use std::collections::HashMap;
+
+/// Layout of the map texture, stored on the map image node so clicks can be
+/// translated back into map cells
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MapTextureGrid {
+    /// Map cell drawn in the left column
+    pub min_x: i32,
+    /// Map cell drawn in the top row
+    pub max_y: i32,
+    pub cols: u32,
+    pub rows: u32,
+    /// Texture pixels per map cell side
+    pub pixels_per_cell: u32,
+    /// On-screen size of a map cell in UI pixels
+    pub cell_display_size: f32,
+}
+
+impl MapTextureGrid {
+    /// Build a layout for the given cell bounds, shrinking to one pixel per cell
+    /// when full-resolution tiles would exceed `MAX_MAP_TEXTURE_SIZE`
+    pub fn new(bounds: (i32, i32, i32, i32), cell_display_size: f32) -> Self {
+        let (min_x, max_x, min_y, max_y) = bounds;
+        let cols = (max_x - min_x + 1).max(1) as u32;
+        let rows = (max_y - min_y + 1).max(1) as u32;
+        let tile_pixels = MAP_TILE_SIZE as u32;
+        let pixels_per_cell = if cols.max(rows) * tile_pixels <= MAX_MAP_TEXTURE_SIZE {
+            tile_pixels
+        } else {
+            1
+        };
+
+        Self {
+            min_x,
+            max_y,
+            cols,
+            rows,
+            pixels_per_cell,
+            cell_display_size,
+        }
+    }
+
+    /// Column/row of a map cell in the texture (row 0 is the top)
+    pub fn cell_offset(&self, map_pos: MapTilePos) -> Option<(u32, u32)> {
+        let col = map_pos.x - self.min_x;
+        let row = self.max_y - map_pos.y;
+        if col < 0 || row < 0 || col as u32 >= self.cols || row as u32 >= self.rows {
+            return None;
+        }
+        Some((col as u32, row as u32))
+    }
+
+    /// Map cell under a cursor position normalized to the node
+    /// ((-0.5, -0.5) is the top-left corner, (0.5, 0.5) the bottom-right)
+    pub fn cell_at_normalized(&self, normalized: Vec2) -> Option<MapTilePos> {
+        let u = normalized.x + 0.5;
+        let v = normalized.y + 0.5;
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+
+        let col = (u * self.cols as f32) as i32;
+        let row = (v * self.rows as f32) as i32;
+        Some(MapTilePos {
+            x: self.min_x + col,
+            y: self.max_y - row,
+        })
+    }
+
+    /// UI size of the whole map image
+    pub fn display_size(&self) -> Vec2 {
+        Vec2::new(
+            self.cols as f32 * self.cell_display_size,
+            self.rows as f32 * self.cell_display_size,
+        )
+    }
+}
+
+/// RGBA pixels of individual map tiles, read from the map tileset on demand
+pub struct TilesetPixels<'a> {
+    tileset: &'a Image,
+    tiles: HashMap<usize, Vec<[u8; 4]>>,
+}
+
+impl<'a> TilesetPixels<'a> {
+    pub fn new(tileset: &'a Image) -> Self {
+        Self {
+            tileset,
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Row-major pixels of a map tile (MAP_TILE_SIZE × MAP_TILE_SIZE)
+    pub fn tile(&mut self, index: usize) -> &[[u8; 4]] {
+        let tileset = self.tileset;
+        self.tiles.entry(index).or_insert_with(|| {
+            let size = MAP_TILE_SIZE as u32;
+            let origin_x = (index % MAP_TILESET_COLS) as u32 * size;
+            let origin_y = (index / MAP_TILESET_COLS) as u32 * size;
+            (0..size * size)
+                .map(|i| {
+                    tileset
+                        .get_color_at(origin_x + i % size, origin_y + i / size)
+                        .map(|color| color.to_srgba().to_u8_array())
+                        .unwrap_or([0, 0, 0, 255])
+                })
+                .collect()
+        })
+    }
+
+    /// Average color of a map tile, used when drawing one pixel per cell
+    pub fn average(&mut self, index: usize) -> [u8; 4] {
+        let pixels = self.tile(index);
+        let mut sum = [0u32; 4];
+        for pixel in pixels {
+            for (total, channel) in sum.iter_mut().zip(pixel) {
+                *total += *channel as u32;
+            }
+        }
+        let count = pixels.len().max(1) as u32;
+        sum.map(|total| (total / count) as u8)
+    }
+}
+
+/// Map texture being painted one cell at a time
+pub struct MapCanvas {
+    grid: MapTextureGrid,
+    width: u32,
+    pixels: Vec<u8>,
+}
+
+impl MapCanvas {
+    /// A canvas filled black (unexplored)
+    pub fn new(grid: MapTextureGrid) -> Self {
+        let width = grid.cols * grid.pixels_per_cell;
+        let height = grid.rows * grid.pixels_per_cell;
+        Self {
+            grid,
+            width,
+            pixels: [0, 0, 0, 255].repeat((width * height) as usize),
+        }
+    }
+
+    fn put(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        let offset = ((y * self.width + x) * 4) as usize;
+        self.pixels[offset..offset + 4].copy_from_slice(&rgba);
+    }
+
+    /// Draw a tileset tile into a cell, multiplied by `tint`
+    pub fn paint_tile(
+        &mut self,
+        map_pos: MapTilePos,
+        tileset: &mut TilesetPixels,
+        index: usize,
+        tint: Color,
+    ) {
+        let Some((col, row)) = self.grid.cell_offset(map_pos) else {
+            return;
+        };
+        let ppc = self.grid.pixels_per_cell;
+
+        if ppc == 1 {
+            let color = multiply(tileset.average(index), tint);
+            self.put(col, row, color);
+            return;
+        }
+
+        let pixels = tileset.tile(index).to_vec();
+        for (i, pixel) in pixels.iter().enumerate() {
+            let x = col * ppc + i as u32 % ppc;
+            let y = row * ppc + i as u32 / ppc;
+            self.put(x, y, multiply(*pixel, tint));
+        }
+    }
+
+    /// Fill a cell with a flat color
+    pub fn paint_color(&mut self, map_pos: MapTilePos, color: Color) {
+        let Some((col, row)) = self.grid.cell_offset(map_pos) else {
+            return;
+        };
+        let ppc = self.grid.pixels_per_cell;
+        let rgba = color.to_srgba().to_u8_array();
+        for y in row * ppc..(row + 1) * ppc {
+            for x in col * ppc..(col + 1) * ppc {
+                self.put(x, y, rgba);
+            }
+        }
+    }
+
+    /// Convert into an image asset with nearest-neighbour sampling (keeps pixel art crisp)
+    pub fn into_image(self) -> Image {
+        let height = self.grid.rows * self.grid.pixels_per_cell;
+        let mut image = Image::new(
+            Extent3d {
+                width: self.width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::nearest();
+        image
+    }
+}
+
+/// Multiply an sRGB pixel by a tint color
+fn multiply(pixel: [u8; 4], tint: Color) -> [u8; 4] {
+    let tint = tint.to_srgba();
+    [
+        (pixel[0] as f32 * tint.red) as u8,
+        (pixel[1] as f32 * tint.green) as u8,
+        (pixel[2] as f32 * tint.blue) as u8,
+        pixel[3],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_mapping_round_trip() {
+        // Cells x: -2..=1, y: -1..=2
+        let grid = MapTextureGrid::new((-2, 1, -1, 2), 16.0);
+        assert_eq!((grid.cols, grid.rows), (4, 4));
+        assert_eq!(grid.pixels_per_cell, MAP_TILE_SIZE as u32);
+
+        // Top-left corner is the west-most, north-most cell
+        assert_eq!(
+            grid.cell_at_normalized(Vec2::new(-0.49, -0.49)),
+            Some(MapTilePos { x: -2, y: 2 })
+        );
+        assert_eq!(
+            grid.cell_at_normalized(Vec2::new(0.49, 0.49)),
+            Some(MapTilePos { x: 1, y: -1 })
+        );
+        assert_eq!(grid.cell_at_normalized(Vec2::new(0.6, 0.0)), None);
+
+        assert_eq!(grid.cell_offset(MapTilePos { x: -2, y: 2 }), Some((0, 0)));
+        assert_eq!(grid.cell_offset(MapTilePos { x: 1, y: -1 }), Some((3, 3)));
+        assert_eq!(grid.cell_offset(MapTilePos { x: 2, y: 0 }), None);
+    }
+
+    #[test]
+    fn test_large_maps_use_one_pixel_per_cell() {
+        let cells = (MAX_MAP_TEXTURE_SIZE / MAP_TILE_SIZE as u32) as i32 + 1;
+        let grid = MapTextureGrid::new((0, cells - 1, 0, 0), 8.0);
+        assert_eq!(grid.pixels_per_cell, 1);
+    }
+
+    #[test]
+    fn test_paint_color_fills_cell() {
+        let grid = MapTextureGrid::new((0, 1, 0, 0), 8.0);
+        let mut canvas = MapCanvas::new(grid);
+        canvas.paint_color(MapTilePos { x: 1, y: 0 }, Color::srgb(1.0, 0.0, 0.0));
+
+        let ppc = grid.pixels_per_cell as usize;
+        let width = canvas.width as usize;
+        // First cell untouched, second cell red
+        assert_eq!(&canvas.pixels[0..4], &[0, 0, 0, 255]);
+        let offset = (ppc + (ppc - 1) * width) * 4;
+        assert_eq!(&canvas.pixels[offset..offset + 4], &[255, 0, 0, 255]);
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
-use super::{MapModal, MapContent};
+use crate::locale::LocalizedText;
+use crate::theme::ThemedPanel;
+use super::{MapModal, MapContent, MapDisplay, MapMode, MapModeTab, MapScaleButton, MapScaleLabel, adjust_map_scale, select_map_mode};
 
 /// Sets up the map modal UI (hidden by default)
 pub fn setup_map_ui(mut commands: Commands) {
@@ -7,6 +9,7 @@ pub fn setup_map_ui(mut commands: Commands) {
     commands
         .spawn((
             MapModal,
+            ThemedPanel,
             Node {
                 position_type: PositionType::Absolute,
                 width: Val::Percent(100.0),
@@ -36,21 +39,59 @@ pub fn setup_map_ui(mut commands: Commands) {
                 .with_children(|parent| {
                     // Title bar
                     parent.spawn((
-                        Text::new("World Map (Press 'M' to close)"),
+                        LocalizedText("map-title"),
+                        Text::new(""),
                         TextFont {
                             font_size: 24.0,
                             ..default()
                         },
                         TextColor(Color::WHITE),
                         Node {
-                            margin: UiRect::bottom(Val::Px(20.0)),
+                            margin: UiRect::bottom(Val::Px(10.0)),
                             ..default()
                         },
                     ));
 
+                    // Map mode tabs
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(6.0),
+                            margin: UiRect::bottom(Val::Px(10.0)),
+                            ..default()
+                        })
+                        .with_children(|tabs| {
+                            for mode in MapMode::ALL {
+                                tabs.spawn((
+                                    Button,
+                                    MapModeTab(mode),
+                                    Node {
+                                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                        border: UiRect::all(Val::Px(1.0)),
+                                        ..default()
+                                    },
+                                    BackgroundColor(Color::srgb(0.2, 0.2, 0.28)),
+                                    BorderColor::all(Color::srgb(0.4, 0.4, 0.55)),
+                                    BorderRadius::all(Val::Px(4.0)),
+                                ))
+                                .observe(select_map_mode)
+                                .with_children(|tab| {
+                                    tab.spawn((
+                                        LocalizedText(mode.locale_id()),
+                                        Text::new(""),
+                                        TextFont {
+                                            font_size: 14.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::WHITE),
+                                    ));
+                                });
+                            }
+                        });
+
                     // Map display area (will be populated dynamically by update_map_display)
                     parent.spawn((
-                        MapContent,
+                        MapDisplay,
                         Node {
                             width: Val::Percent(100.0),
                             height: Val::Percent(100.0),
@@ -62,19 +103,59 @@ pub fn setup_map_ui(mut commands: Commands) {
                         BackgroundColor(Color::srgb(0.1, 0.1, 0.15)),
                     ));
 
-                    // Config display at bottom
-                    parent.spawn((
-                        Text::new("Chunks per map tile: 4 (configurable)"),
-                        TextFont {
-                            font_size: 14.0,
-                            ..default()
-                        },
-                        TextColor(Color::srgb(0.8, 0.8, 0.8)),
-                        Node {
+                    // Aggregation controls at bottom
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.0),
                             margin: UiRect::top(Val::Px(20.0)),
                             ..default()
-                        },
-                    ));
+                        })
+                        .with_children(|controls| {
+                            spawn_scale_button(controls, "-", -1);
+                            controls.spawn((
+                                MapScaleLabel,
+                                Text::new(""),
+                                TextFont {
+                                    font_size: 14.0,
+                                    ..default()
+                                },
+                                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                            ));
+                            spawn_scale_button(controls, "+", 1);
+                        });
                 });
         });
 }
+
+/// Spawns a +/- button that changes `MapConfig.chunks_per_map_tile`
+fn spawn_scale_button(parent: &mut ChildSpawnerCommands, label: &str, delta: i32) {
+    parent
+        .spawn((
+            Button,
+            MapScaleButton(delta),
+            Node {
+                width: Val::Px(24.0),
+                height: Val::Px(24.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.28)),
+            BorderColor::all(Color::srgb(0.4, 0.4, 0.55)),
+            BorderRadius::all(Val::Px(4.0)),
+        ))
+        .observe(adjust_map_scale)
+        .with_children(|button| {
+            button.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
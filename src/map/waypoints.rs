@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+use bevy::picking::pointer::PointerButton;
+use bevy::ui::RelativeCursorPosition;
+use super::{MapConfig, MapState, MapTextureGrid, MapTilePos};
+use crate::theme::KeepColors;
+use crate::tiles::TILE_SIZE;
+use crate::world::{Waypoint, WorldMetadata, WorldOrigin};
+
+/// Colors cycled through when dropping new waypoints
+pub const WAYPOINT_COLORS: [[f32; 3]; 6] = [
+    [0.95, 0.3, 0.3],  // Red
+    [0.3, 0.6, 0.95],  // Blue
+    [0.95, 0.85, 0.3], // Yellow
+    [0.7, 0.4, 0.95],  // Purple
+    [0.3, 0.9, 0.6],   // Teal
+    [0.95, 0.6, 0.3],  // Orange
+];
+
+/// Camera distance (in pixels) within which waypoint labels are shown in the world
+pub const WAYPOINT_LABEL_DISTANCE: f32 = 600.0;
+
+/// Size of the pin marker drawn on top of a map tile
+const WAYPOINT_PIN_SIZE: f32 = 6.0;
+
+/// Size of the sign icon drawn on top of a map tile
+const SIGN_ICON_SIZE: f32 = 5.0;
+
+/// Sign icons are wooden squares (the posts' color)
+const SIGN_ICON_COLOR: Color = Color::srgb(0.62, 0.45, 0.26);
+
+/// Marker component for floating world-space waypoint labels
+#[derive(Component)]
+pub struct WaypointLabel;
+
+/// Marker component for waypoint pins drawn on the map
+#[derive(Component)]
+pub struct WaypointPin;
+
+/// Size of one map tile in world pixels
+pub fn map_tile_world_size(tiles_per_cell: u32) -> f32 {
+    TILE_SIZE * tiles_per_cell as f32
+}
+
+/// Convert a world position (pixels) to the map tile containing it
+pub fn world_to_map_tile(world_pos: Vec2, tiles_per_cell: u32) -> MapTilePos {
+    let size = map_tile_world_size(tiles_per_cell);
+    MapTilePos {
+        x: (world_pos.x / size).floor() as i32,
+        y: (world_pos.y / size).floor() as i32,
+    }
+}
+
+/// Get the world position (pixels) at the center of a map tile
+pub fn map_tile_center(map_pos: MapTilePos, tiles_per_cell: u32) -> Vec2 {
+    let size = map_tile_world_size(tiles_per_cell);
+    Vec2::new(
+        (map_pos.x as f32 + 0.5) * size,
+        (map_pos.y as f32 + 0.5) * size,
+    )
+}
+
+/// Find the waypoints that fall inside a map tile
+pub fn waypoints_in_map_tile(
+    metadata: &WorldMetadata,
+    map_pos: MapTilePos,
+    tiles_per_cell: u32,
+) -> impl Iterator<Item = (usize, &Waypoint)> {
+    metadata
+        .waypoints
+        .iter()
+        .enumerate()
+        .filter(move |(_, waypoint)| {
+            world_to_map_tile(waypoint.position(), tiles_per_cell) == map_pos
+        })
+}
+
+/// Spawns pin markers (and name labels) for waypoints, positioned over the map image
+pub fn spawn_waypoint_pins(
+    map_image: &mut ChildSpawnerCommands,
+    metadata: &WorldMetadata,
+    grid: &MapTextureGrid,
+    tiles_per_cell: u32,
+) {
+    for waypoint in metadata.waypoints.iter() {
+        let map_pos = world_to_map_tile(waypoint.position(), tiles_per_cell);
+        let Some((col, row)) = grid.cell_offset(map_pos) else {
+            continue;
+        };
+        let left = (col as f32 + 0.5) * grid.cell_display_size - WAYPOINT_PIN_SIZE / 2.0;
+        let top = (row as f32 + 0.5) * grid.cell_display_size - WAYPOINT_PIN_SIZE / 2.0;
+
+        map_image.spawn((
+            WaypointPin,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(left),
+                top: Val::Px(top),
+                width: Val::Px(WAYPOINT_PIN_SIZE),
+                height: Val::Px(WAYPOINT_PIN_SIZE),
+                ..default()
+            },
+            BackgroundColor(waypoint.bevy_color()),
+            BorderRadius::all(Val::Px(WAYPOINT_PIN_SIZE / 2.0)),
+            KeepColors,
+            ZIndex(10),
+        ));
+        map_image.spawn((
+            WaypointPin,
+            Text::new(waypoint.name.clone()),
+            TextFont {
+                font_size: 8.0,
+                ..default()
+            },
+            TextColor(waypoint.bevy_color()),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(left + WAYPOINT_PIN_SIZE + 2.0),
+                top: Val::Px(top),
+                ..default()
+            },
+            ZIndex(10),
+        ));
+    }
+}
+
+/// Spawns square icons for sign posts, positioned over the map image (texts stay in the world)
+pub fn spawn_sign_icons(
+    map_image: &mut ChildSpawnerCommands,
+    metadata: &WorldMetadata,
+    grid: &MapTextureGrid,
+    tiles_per_cell: u32,
+) {
+    for sign in metadata.signs.iter() {
+        let map_pos = world_to_map_tile(sign.position(), tiles_per_cell);
+        let Some((col, row)) = grid.cell_offset(map_pos) else {
+            continue;
+        };
+        map_image.spawn((
+            WaypointPin,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px((col as f32 + 0.5) * grid.cell_display_size - SIGN_ICON_SIZE / 2.0),
+                top: Val::Px((row as f32 + 0.5) * grid.cell_display_size - SIGN_ICON_SIZE / 2.0),
+                width: Val::Px(SIGN_ICON_SIZE),
+                height: Val::Px(SIGN_ICON_SIZE),
+                ..default()
+            },
+            BackgroundColor(SIGN_ICON_COLOR),
+            KeepColors,
+            ZIndex(9),
+        ));
+    }
+}
+
+/// Right-clicking a map cell drops a waypoint at its center, or removes the
+/// waypoint already pinned there
+pub fn handle_map_right_click(
+    trigger: On<Pointer<Click>>,
+    map_state: Res<MapState>,
+    map_config: Res<MapConfig>,
+    map_images: Query<(&MapTextureGrid, &RelativeCursorPosition)>,
+    mut metadata: ResMut<WorldMetadata>,
+) {
+    let tiles_per_cell = map_state.detail.tiles_per_cell(map_config.chunks_per_map_tile);
+
+    if trigger.event().button != PointerButton::Secondary || !map_state.visible {
+        return;
+    }
+
+    let Ok((grid, cursor)) = map_images.get(trigger.entity) else {
+        return;
+    };
+    let Some(map_pos) = cursor.normalized.and_then(|normalized| grid.cell_at_normalized(normalized)) else {
+        return;
+    };
+
+    let existing = waypoints_in_map_tile(&metadata, map_pos, tiles_per_cell)
+        .map(|(index, _)| index)
+        .next();
+
+    if let Some(index) = existing {
+        if let Some(removed) = metadata.remove_waypoint(index) {
+            info!("Removed waypoint '{}'", removed.name);
+        }
+        return;
+    }
+
+    let center = map_tile_center(map_pos, tiles_per_cell);
+    let number = metadata.waypoints.len() + 1;
+    let color = WAYPOINT_COLORS[(number - 1) % WAYPOINT_COLORS.len()];
+    let waypoint = Waypoint::new(format!("Waypoint {}", number), center.x, center.y, color);
+    info!("Dropped waypoint '{}' at ({}, {})", waypoint.name, center.x, center.y);
+    metadata.add_waypoint(waypoint);
+}
+
+/// Toggles floating waypoint labels in the world when 'L' key is pressed
+pub fn toggle_waypoint_labels(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut map_config: ResMut<MapConfig>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyL) {
+        map_config.show_waypoint_labels = !map_config.show_waypoint_labels;
+        info!("Waypoint labels {}", if map_config.show_waypoint_labels { "shown" } else { "hidden" });
+    }
+}
+
+/// Rebuilds the floating waypoint labels whenever the waypoint list changes
+pub fn sync_waypoint_labels(
+    mut commands: Commands,
+    metadata: Res<WorldMetadata>,
+    origin: Res<WorldOrigin>,
+    labels: Query<Entity, With<WaypointLabel>>,
+) {
+    if !metadata.is_changed() {
+        return;
+    }
+
+    for entity in labels.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for waypoint in metadata.waypoints.iter() {
+        let position = origin.to_render(Vec2::new(waypoint.x, waypoint.y));
+        commands.spawn((
+            WaypointLabel,
+            Text2d::new(waypoint.name.clone()),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(waypoint.bevy_color()),
+            Transform::from_xyz(position.x, position.y, 10.0),
+            Visibility::Hidden,
+        ));
+    }
+}
+
+/// Shows waypoint labels only when the camera is nearby (and labels are enabled)
+pub fn update_waypoint_label_visibility(
+    map_config: Res<MapConfig>,
+    camera_query: Query<&Transform, With<Camera2d>>,
+    mut labels: Query<(&Transform, &mut Visibility), With<WaypointLabel>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let camera_pos = camera_transform.translation.truncate();
+
+    for (transform, mut visibility) in labels.iter_mut() {
+        let in_range =
+            camera_pos.distance(transform.translation.truncate()) <= WAYPOINT_LABEL_DISTANCE;
+        let desired = if map_config.show_waypoint_labels && in_range {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != desired {
+            *visibility = desired;
+        }
+    }
+}
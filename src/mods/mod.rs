@@ -0,0 +1,141 @@
+pub mod pack;
+pub mod reload;
+
+pub use pack::{load_pack, merge_pack, scan_packs, ModError, ModPack, PackManifest, PackSummary, MANIFEST_FILE};
+pub use reload::{PackWatcher, RegistriesChanged};
+
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use crate::entities::EntityRegistry;
+use crate::locale::Locale;
+use crate::tiles::TileRegistry;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use std::path::Path;
+
+/// Directory scanned for packs; it lives under `assets/` so pack files can be loaded
+/// through the asset server (see `ModPack::asset_path`)
+pub const MODS_DIR: &str = "assets/mods";
+
+/// Plugin for mod/data packs: at startup every `assets/mods/<pack>/pack.ron` is read and its tiles and
+/// entity types are merged into `TileRegistry` and `EntityRegistry`; edited packs are merged again while running
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileRegistry>()
+            .init_resource::<EntityRegistry>()
+            .init_resource::<ModReport>()
+            .init_resource::<PackWatcher>()
+            .add_message::<RegistriesChanged>()
+            .add_console_command("mods", "mods - list loaded mod packs, conflicts and errors")
+            // Before any Startup system reads the registries
+            .add_systems(PreStartup, load_mods)
+            .add_systems(Update, (reload::watch_packs, handle_mod_commands));
+    }
+}
+
+/// What happened when the packs were loaded
+#[derive(Resource, Default, Debug)]
+pub struct ModReport {
+    pub packs: Vec<PackSummary>,
+    /// Registrations skipped because an earlier pack (or the game) already used the id or name
+    pub conflicts: Vec<String>,
+    /// Packs that couldn't be read at all
+    pub errors: Vec<String>,
+}
+
+/// Reads every pack in `MODS_DIR` into the registries at startup
+pub fn load_mods(
+    mut tiles: ResMut<TileRegistry>,
+    mut entities: ResMut<EntityRegistry>,
+    mut report: ResMut<ModReport>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    (*tiles, *entities, *report) = build_registries(Path::new(MODS_DIR));
+    if !report.conflicts.is_empty() || !report.errors.is_empty() {
+        toasts.write(Toast::warning(locale.format("toast-mods-problems", &[
+            ("conflicts", report.conflicts.len().into()),
+            ("errors", report.errors.len().into()),
+        ])));
+    }
+}
+
+/// The built-in registries with every pack in `mods_dir` merged in, in directory name order
+pub fn build_registries(mods_dir: &Path) -> (TileRegistry, EntityRegistry, ModReport) {
+    let mut tiles = TileRegistry::new();
+    let mut entities = EntityRegistry::new();
+    let mut report = ModReport::default();
+    for (directory, pack) in scan_packs(mods_dir) {
+        let pack = match pack {
+            Ok(pack) => pack,
+            Err(e) => {
+                warn!("Skipping mod pack {:?}: {}", directory, e);
+                report.errors.push(format!("{}: {}", directory.display(), e));
+                continue;
+            }
+        };
+
+        if report.packs.iter().any(|loaded| loaded.name == pack.manifest.name) {
+            report
+                .conflicts
+                .push(format!("{}: a pack with this name is already loaded, skipped", pack.manifest.name));
+            continue;
+        }
+
+        let (summary, conflicts) = merge_pack(&pack, &mut tiles, &mut entities);
+        info!(
+            "Loaded mod pack '{}' {}: {} tiles, {} entities",
+            summary.name, summary.version, summary.tiles, summary.entities
+        );
+        if !summary.scripts.is_empty() {
+            warn!("Mod pack '{}' has {} scripts, which are not run yet", summary.name, summary.scripts.len());
+        }
+        for conflict in &conflicts {
+            warn!("Mod conflict: {}", conflict);
+        }
+        report.conflicts.extend(conflicts);
+        report.packs.push(summary);
+    }
+    (tiles, entities, report)
+}
+
+/// Console command: `mods` lists the loaded packs and anything that went wrong loading them
+pub fn handle_mod_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    report: Res<ModReport>,
+    tiles: Res<TileRegistry>,
+    entities: Res<EntityRegistry>,
+) {
+    for command in console_commands.read() {
+        if command.name != "mods" {
+            continue;
+        }
+
+        console.print(format!(
+            "{} packs from {}; {} tile types, {} entity types registered",
+            report.packs.len(),
+            MODS_DIR,
+            tiles.len(),
+            entities.len()
+        ));
+        for pack in &report.packs {
+            console.print(format!(
+                "  {} {}: {} tiles, {} entities, {} tilesets, {} scripts (not run)",
+                pack.name,
+                pack.version,
+                pack.tiles,
+                pack.entities,
+                pack.tilesets.len(),
+                pack.scripts.len()
+            ));
+        }
+        for conflict in &report.conflicts {
+            console.print(format!("  Conflict: {}", conflict));
+        }
+        for error in &report.errors {
+            console.print(format!("  Error: {}", error));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::entities::{EntityDefinition, EntityRegistry};
+use crate::tiles::{TileId, TileProperties, TileRegistry};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Manifest every pack directory needs
+pub const MANIFEST_FILE: &str = "pack.ron";
+
+#[derive(Debug, Error)]
+pub enum ModError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid {MANIFEST_FILE}: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    /// A file the manifest refers to doesn't exist in the pack
+    #[error("Missing file: {}", .0.display())]
+    MissingFile(PathBuf),
+}
+
+/// A tile type added by a pack
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PackTile {
+    pub id: TileId,
+    pub name: String,
+    #[serde(default = "default_walkable")]
+    pub walkable: bool,
+}
+
+fn default_walkable() -> bool {
+    true
+}
+
+/// An entity type added by a pack, drawn from a sprite sheet inside the pack
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PackEntity {
+    pub name: String,
+    /// Sprite sheet, relative to the pack directory
+    pub sprite: String,
+    #[serde(default = "default_frame_size")]
+    pub frame_size: u32,
+    #[serde(default = "default_sheet_size")]
+    pub columns: u32,
+    #[serde(default = "default_sheet_size")]
+    pub rows: u32,
+}
+
+fn default_frame_size() -> u32 {
+    32
+}
+
+fn default_sheet_size() -> u32 {
+    4
+}
+
+/// Contents of `pack.ron`; file paths are relative to the pack directory
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub tiles: Vec<PackTile>,
+    #[serde(default)]
+    pub entities: Vec<PackEntity>,
+    #[serde(default)]
+    pub tilesets: Vec<String>,
+    #[serde(default)]
+    pub scripts: Vec<String>,
+}
+
+/// A pack read from disk
+#[derive(Debug, Clone)]
+pub struct ModPack {
+    /// Asset path of the pack directory ("mods/<directory>"), which files in the pack are loaded through
+    pub asset_dir: String,
+    pub manifest: PackManifest,
+}
+
+impl ModPack {
+    pub fn asset_path(&self, file: &str) -> String {
+        format!("{}/{}", self.asset_dir, file)
+    }
+}
+
+/// Read a pack's manifest and check that every file it lists exists
+pub fn load_pack(directory: &Path) -> Result<ModPack, ModError> {
+    let text = fs::read_to_string(directory.join(MANIFEST_FILE))?;
+    let manifest: PackManifest = ron::from_str(&text)?;
+
+    let files = manifest
+        .entities
+        .iter()
+        .map(|entity| &entity.sprite)
+        .chain(&manifest.tilesets)
+        .chain(&manifest.scripts);
+    for file in files {
+        let path = directory.join(file);
+        if !path.is_file() {
+            return Err(ModError::MissingFile(path));
+        }
+    }
+
+    let directory_name = directory.file_name().unwrap_or_default().to_string_lossy();
+    Ok(ModPack {
+        asset_dir: format!("mods/{}", directory_name),
+        manifest,
+    })
+}
+
+/// Load every pack directory under `mods_dir`, in directory name order (so merging is repeatable)
+/// A missing `mods_dir` just means no packs
+pub fn scan_packs(mods_dir: &Path) -> Vec<(PathBuf, Result<ModPack, ModError>)> {
+    let Ok(entries) = fs::read_dir(mods_dir) else {
+        return Vec::new();
+    };
+    let mut directories: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    directories.sort();
+
+    directories
+        .into_iter()
+        .map(|directory| {
+            let pack = load_pack(&directory);
+            (directory, pack)
+        })
+        .collect()
+}
+
+/// What one pack contributed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackSummary {
+    pub name: String,
+    pub version: String,
+    pub tiles: usize,
+    pub entities: usize,
+    /// Asset paths of the pack's tilesets and scripts
+    pub tilesets: Vec<String>,
+    pub scripts: Vec<String>,
+}
+
+/// Merge a pack into the registries; the first registration of a tile id, tile name or entity name wins,
+/// later ones are skipped and returned as conflict messages
+pub fn merge_pack(
+    pack: &ModPack,
+    tiles: &mut TileRegistry,
+    entities: &mut EntityRegistry,
+) -> (PackSummary, Vec<String>) {
+    let manifest = &pack.manifest;
+    let mut summary = PackSummary {
+        name: manifest.name.clone(),
+        version: manifest.version.clone(),
+        tilesets: manifest.tilesets.iter().map(|file| pack.asset_path(file)).collect(),
+        scripts: manifest.scripts.iter().map(|file| pack.asset_path(file)).collect(),
+        ..Default::default()
+    };
+    let mut conflicts = Vec::new();
+
+    for tile in &manifest.tiles {
+        let properties = TileProperties {
+            name: tile.name.clone(),
+            walkable: tile.walkable,
+            source: manifest.name.clone(),
+        };
+        match tiles.register(tile.id, properties) {
+            Ok(()) => summary.tiles += 1,
+            Err(existing) => conflicts.push(format!(
+                "{}: tile {} '{}' clashes with '{}' from {}",
+                manifest.name, tile.id, tile.name, existing.name, existing.source
+            )),
+        }
+    }
+
+    for entity in &manifest.entities {
+        let definition = EntityDefinition {
+            sprite: pack.asset_path(&entity.sprite),
+            frame_size: entity.frame_size,
+            columns: entity.columns,
+            rows: entity.rows,
+            source: manifest.name.clone(),
+        };
+        match entities.register(entity.name.clone(), definition) {
+            Ok(()) => summary.entities += 1,
+            Err(existing) => conflicts.push(format!(
+                "{}: entity '{}' is already defined by {}",
+                manifest.name, entity.name, existing.source
+            )),
+        }
+    }
+
+    (summary, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_scan_and_merge_packs() {
+        let mods_dir = env::temp_dir().join("worldseed_test_mods");
+        let _ = fs::remove_dir_all(&mods_dir);
+        fs::create_dir_all(mods_dir.join("a_desert")).unwrap();
+        fs::create_dir_all(mods_dir.join("b_clash")).unwrap();
+        fs::create_dir_all(mods_dir.join("c_broken")).unwrap();
+        fs::write(mods_dir.join("a_desert/beetle.png"), b"").unwrap();
+        fs::write(
+            mods_dir.join("a_desert").join(MANIFEST_FILE),
+            r#"(name: "Desert", version: "1.0",
+                tiles: [(id: 100, name: "red_sand")],
+                entities: [(name: "beetle", sprite: "beetle.png", columns: 6)])"#,
+        )
+        .unwrap();
+        fs::write(
+            mods_dir.join("b_clash").join(MANIFEST_FILE),
+            r#"(name: "Clash", tiles: [(id: 1, name: "moss"), (id: 101, name: "red_sand"), (id: 102, name: "ash")],
+                entities: [], scripts: [])"#,
+        )
+        .unwrap();
+        fs::write(
+            mods_dir.join("c_broken").join(MANIFEST_FILE),
+            r#"(name: "Broken", tilesets: ["missing.png"])"#,
+        )
+        .unwrap();
+
+        let packs = scan_packs(&mods_dir);
+        assert_eq!(packs.len(), 3);
+        assert!(matches!(packs[2].1, Err(ModError::MissingFile(_))));
+
+        let mut tiles = TileRegistry::new();
+        let mut entities = EntityRegistry::new();
+        let desert = packs[0].1.as_ref().unwrap();
+        let (summary, conflicts) = merge_pack(desert, &mut tiles, &mut entities);
+        assert_eq!((summary.tiles, summary.entities), (1, 1));
+        assert!(conflicts.is_empty());
+        assert_eq!(tiles.find_by_name("red_sand"), Some(100));
+        let beetle = entities.get("beetle").unwrap();
+        assert_eq!((beetle.sprite.as_str(), beetle.columns), ("mods/a_desert/beetle.png", 6));
+
+        // Built-in ids and names already taken by earlier packs are skipped and reported
+        let (summary, conflicts) = merge_pack(packs[1].1.as_ref().unwrap(), &mut tiles, &mut entities);
+        assert_eq!(summary.tiles, 1);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(tiles.get(1).unwrap().name, "grass");
+        assert!(!tiles.is_valid_tile(101));
+
+        assert!(scan_packs(&mods_dir.join("nowhere")).is_empty());
+        fs::remove_dir_all(&mods_dir).unwrap();
+    }
+}
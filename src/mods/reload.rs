@@ -0,0 +1,125 @@
+use super::pack::MANIFEST_FILE;
+use super::{build_registries, ModReport, MODS_DIR};
+use crate::entities::EntityRegistry;
+use crate::locale::Locale;
+use crate::tiles::TileRegistry;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Seconds between checks of the pack manifests
+const POLL_INTERVAL: f32 = 1.0;
+
+/// Sent after the registries were rebuilt from edited packs; systems that copied anything out of them refresh
+#[derive(Message, Debug, Clone, Copy)]
+pub struct RegistriesChanged;
+
+/// Modification times of the pack manifests when the registries were last built
+/// Bevy's own file watcher needs the `file_watcher` feature (and `notify`), so the manifests are polled instead
+#[derive(Resource)]
+pub struct PackWatcher {
+    timer: Timer,
+    stamps: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl Default for PackWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            stamps: manifest_stamps(Path::new(MODS_DIR)),
+        }
+    }
+}
+
+/// Modification time of every `pack.ron` under `mods_dir`; a pack added, removed or edited changes the map
+pub fn manifest_stamps(mods_dir: &Path) -> BTreeMap<PathBuf, SystemTime> {
+    let Ok(entries) = fs::read_dir(mods_dir) else {
+        return BTreeMap::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(MANIFEST_FILE))
+        .filter_map(|manifest| {
+            let modified = fs::metadata(&manifest).and_then(|metadata| metadata.modified()).ok()?;
+            Some((manifest, modified))
+        })
+        .collect()
+}
+
+/// Rebuilds the registries from the built-ins and every pack when a manifest changes on disk
+/// Each reload starts from scratch, so removing a tile or entity from a pack removes it from the game
+#[allow(clippy::too_many_arguments)]
+pub fn watch_packs(
+    time: Res<Time>,
+    mut watcher: ResMut<PackWatcher>,
+    mut tiles: ResMut<TileRegistry>,
+    mut entities: ResMut<EntityRegistry>,
+    mut report: ResMut<ModReport>,
+    mut changed: MessageWriter<RegistriesChanged>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let stamps = manifest_stamps(Path::new(MODS_DIR));
+    if stamps == watcher.stamps {
+        return;
+    }
+    watcher.stamps = stamps;
+
+    let (new_tiles, new_entities, new_report) = build_registries(Path::new(MODS_DIR));
+    info!(
+        "Mod packs changed on disk, reloaded {} packs: {} tile types, {} entity types",
+        new_report.packs.len(),
+        new_tiles.len(),
+        new_entities.len()
+    );
+    if new_report.conflicts.is_empty() && new_report.errors.is_empty() {
+        toasts.write(Toast::info(locale.format("toast-mods-reloaded", &[("packs", new_report.packs.len().into())])));
+    } else {
+        toasts.write(Toast::warning(locale.format("toast-mods-reloaded-problems", &[
+            ("conflicts", new_report.conflicts.len().into()),
+            ("errors", new_report.errors.len().into()),
+        ])));
+    }
+    *tiles = new_tiles;
+    *entities = new_entities;
+    *report = new_report;
+    changed.write(RegistriesChanged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_edited_pack_is_picked_up() {
+        let mods_dir = env::temp_dir().join("worldseed_test_mods_reload");
+        let _ = fs::remove_dir_all(&mods_dir);
+        assert!(manifest_stamps(&mods_dir).is_empty());
+
+        let manifest = mods_dir.join("desert").join(MANIFEST_FILE);
+        fs::create_dir_all(mods_dir.join("desert")).unwrap();
+        fs::write(&manifest, r#"(name: "Desert", tiles: [(id: 100, name: "red_sand")])"#).unwrap();
+        let before = manifest_stamps(&mods_dir);
+        assert_eq!(before.len(), 1);
+        let (tiles, _, report) = build_registries(&mods_dir);
+        assert_eq!(tiles.find_by_name("red_sand"), Some(100));
+        assert_eq!(report.packs.len(), 1);
+
+        // Rebuilding drops what the pack no longer defines
+        fs::write(&manifest, r#"(name: "Desert", tiles: [(id: 101, name: "dune")])"#).unwrap();
+        let (tiles, _, _) = build_registries(&mods_dir);
+        assert_eq!(tiles.find_by_name("red_sand"), None);
+        assert_eq!(tiles.find_by_name("dune"), Some(101));
+
+        fs::remove_dir_all(mods_dir.join("desert")).unwrap();
+        assert_ne!(manifest_stamps(&mods_dir), before);
+        fs::remove_dir_all(&mods_dir).unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+use super::protocol::{ClientMessage, Connection, EntitySnapshot, NetError, ServerMessage, TileChange, PROTOCOL_VERSION};
+use crate::assets::SpriteAssets;
+use crate::editor::PlaceEntityRequest;
+use crate::entities::{ChunkEntity, ChunkEntityFilter, Position, SaveableFilter};
+use crate::locale::Locale;
+use crate::tiles::{Chunk, ChunkPos, CHUNK_UNLOAD_RADIUS};
+use crate::toast::Toast;
+use crate::world::{loader, TileModificationEvent, WorldManager, WorldOrigin};
+use bevy::prelude::*;
+use bevy::sprite_render::TilemapChunkTileData;
+use std::collections::HashMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long to wait for the server to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The client end of a shared world: the server owns the world and the simulation; this client
+/// shows what it streams and sends placement and paint requests back
+/// While this resource exists, local simulation is off (see `is_world_authority`)
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Connection,
+    pub address: String,
+    pub client_id: Option<u32>,
+    pub connected: bool,
+    last_view: Option<ChunkPos>,
+    /// Latest entity snapshot, drawn by `sync_remote_entities`
+    entities: Option<Vec<EntitySnapshot>>,
+}
+
+impl NetClient {
+    pub fn connect(address: &str) -> Result<Self, NetError> {
+        let socket_address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| NetError::Io(std::io::Error::other(format!("Unknown address {}", address))))?;
+        let stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION })?;
+        info!("Connected to {}", address);
+        Ok(Self {
+            connection,
+            address: address.to_string(),
+            client_id: None,
+            connected: true,
+            last_view: None,
+            entities: None,
+        })
+    }
+
+    fn disconnect(&mut self, reason: &str, toasts: &mut MessageWriter<Toast>, locale: &Locale) {
+        if self.connected {
+            self.connected = false;
+            warn!("Disconnected from {}: {}", self.address, reason);
+            toasts.write(Toast::error(locale.format("toast-disconnected", &[("reason", reason.into())])));
+        }
+    }
+}
+
+/// Entity drawn from server snapshots (the id is the server's entity)
+#[derive(Component)]
+pub struct RemoteEntity(pub u64);
+
+/// The server's creatures replace the ones spawned locally at startup
+pub fn despawn_local_entities(mut commands: Commands, local: Query<Entity, (With<Position>, SaveableFilter)>) {
+    for entity in local.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Applies streamed chunks and tile changes directly (they are the server's, not requests to forward)
+pub fn receive_server_messages(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    mut world: ResMut<WorldManager>,
+    mut chunk_query: Query<(&Chunk, &mut TilemapChunkTileData)>,
+    chunk_entities: Query<ChunkEntity, ChunkEntityFilter>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    if !client.connected {
+        return;
+    }
+    let messages = match client.connection.receive::<ServerMessage>() {
+        Ok(messages) => messages,
+        Err(e) => {
+            client.disconnect(&e.to_string(), &mut toasts, &locale);
+            return;
+        }
+    };
+
+    for message in messages {
+        match message {
+            ServerMessage::Welcome { client_id } => {
+                info!("Joined {} as client {}", client.address, client_id);
+                client.client_id = Some(client_id);
+                toasts.write(Toast::info(locale.format("toast-joined", &[("address", client.address.clone().into())])));
+            }
+            ServerMessage::Refused { reason } => client.disconnect(&reason, &mut toasts, &locale),
+            ServerMessage::Chunk(snapshot) => {
+                let Some(mut chunk) = snapshot.to_chunk() else {
+                    warn!("Server sent a malformed chunk {:?}", snapshot.position);
+                    continue;
+                };
+                // The server's terrain, never regenerated as stale
+                chunk.edited = true;
+                // Drop whatever was shown before; the loader respawns the chunk from the cache,
+                // and it is saved to the local copy of the world like any changed chunk
+                let position = chunk.position;
+                loader::reload_chunk(&mut commands, &mut world, &chunk_entities, position);
+                world.cache_chunk(chunk);
+                world.mark_dirty(position);
+            }
+            ServerMessage::Tile(change) => {
+                loader::apply_tile_modification(&mut world, &mut chunk_query, &TileModificationEvent::from(change));
+            }
+            ServerMessage::Entities(snapshots) => client.entities = Some(snapshots),
+        }
+    }
+}
+
+/// Tells the server which chunks to stream whenever the camera enters a new chunk
+pub fn send_view(mut client: ResMut<NetClient>, world: Res<WorldManager>) {
+    let Some(center) = world.camera_chunk else {
+        return;
+    };
+    if !client.connected || client.client_id.is_none() || client.last_view == Some(center) {
+        return;
+    }
+    client.last_view = Some(center);
+    let radius = world.load_radius_override.unwrap_or(CHUNK_UNLOAD_RADIUS);
+    let _ = client.connection.send(&ClientMessage::View { center, radius });
+}
+
+/// Sends local tile changes (painting) and placements to the server
+/// Tile changes are also applied locally straight away; the server's answer overwrites them
+pub fn forward_requests(
+    mut client: ResMut<NetClient>,
+    mut modifications: MessageReader<TileModificationEvent>,
+    mut placements: MessageReader<PlaceEntityRequest>,
+) {
+    for modification in modifications.read() {
+        let _ = client.connection.send(&ClientMessage::SetTile(TileChange::from(modification)));
+    }
+    for request in placements.read() {
+        let _ = client.connection.send(&ClientMessage::Place {
+            entity: request.entity_type.as_str(),
+            x: request.position.x,
+            y: request.position.y,
+        });
+    }
+}
+
+pub fn flush_client(mut client: ResMut<NetClient>, mut toasts: MessageWriter<Toast>, locale: Res<Locale>) {
+    if !client.connected {
+        return;
+    }
+    if let Err(e) = client.connection.flush() {
+        client.disconnect(&e.to_string(), &mut toasts, &locale);
+    }
+}
+
+/// Spawns, moves and despawns sprites to match the latest entity snapshot
+pub fn sync_remote_entities(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    sprites: Res<SpriteAssets>,
+    origin: Res<WorldOrigin>,
+    mut remote: Query<(Entity, &RemoteEntity, &mut Transform, &mut Sprite)>,
+) {
+    let Some(snapshots) = client.entities.take() else {
+        return;
+    };
+    let mut snapshots: HashMap<u64, EntitySnapshot> =
+        snapshots.into_iter().map(|snapshot| (snapshot.id, snapshot)).collect();
+
+    for (entity, remote_entity, mut transform, mut sprite) in remote.iter_mut() {
+        let Some(snapshot) = snapshots.remove(&remote_entity.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let render = origin.to_render(Vec2::new(snapshot.x, snapshot.y));
+        transform.translation.x = render.x;
+        transform.translation.y = render.y;
+        transform.scale = Vec3::splat(snapshot.scale);
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            atlas.index = snapshot.frame as usize;
+        }
+    }
+
+    for snapshot in snapshots.into_values() {
+        let Some(sheet) = sprites.entity(&snapshot.kind) else {
+            continue;
+        };
+        let render = origin.to_render(Vec2::new(snapshot.x, snapshot.y));
+        commands.spawn((
+            RemoteEntity(snapshot.id),
+            sheet.sprite(snapshot.frame as usize),
+            Transform::from_xyz(render.x, render.y, 1.0).with_scale(Vec3::splat(snapshot.scale)),
+        ));
+    }
+}
@@ -0,0 +1,334 @@
+//! Prometheus metrics for long-running servers (`--metrics <addr>`): `GET /metrics` on a small
+//! non-blocking HTTP listener returns counters and gauges in the text exposition format, read from
+//! the same `DiagnosticsStore` values the debug HUD shows plus the world and error bookkeeping
+
+use super::NetServer;
+use crate::error::ErrorLog;
+use crate::world::diagnostics::WorldActivity;
+use crate::world::WorldManager;
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Largest request head read before answering (scrapers send a few hundred bytes)
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Scrapes being read or answered at once; more are refused until these finish
+const MAX_PENDING_SCRAPES: usize = 16;
+
+/// Plugin for the metrics endpoint; `main` adds it together with the `MetricsServer` for `--metrics`
+/// Bevy's frame time and entity count diagnostics are added if the build didn't already have them
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin::default());
+        }
+        app.add_systems(Last, serve_metrics.run_if(resource_exists::<MetricsServer>));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+/// One sample of the exposition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+    pub value: f64,
+}
+
+impl Metric {
+    pub fn gauge(name: &'static str, help: &'static str, value: f64) -> Self {
+        Self {
+            name,
+            help,
+            kind: MetricKind::Gauge,
+            value,
+        }
+    }
+
+    pub fn counter(name: &'static str, help: &'static str, value: f64) -> Self {
+        Self {
+            name,
+            help,
+            kind: MetricKind::Counter,
+            value,
+        }
+    }
+}
+
+/// Prometheus text format (version 0.0.4)
+pub fn render_metrics(metrics: &[Metric]) -> String {
+    let mut text = String::new();
+    for metric in metrics {
+        let kind = match metric.kind {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+        };
+        let _ = writeln!(text, "# HELP {} {}", metric.name, metric.help);
+        let _ = writeln!(text, "# TYPE {} {}", metric.name, kind);
+        let _ = writeln!(text, "{} {}", metric.name, metric.value);
+    }
+    text
+}
+
+/// The HTTP response to a request head: the metrics for `GET /metrics`, 404 or 405 otherwise
+pub fn http_response(request: &str, metrics: impl FnOnce() -> String) -> String {
+    let mut words = request.lines().next().unwrap_or_default().split_whitespace();
+    let (status, content_type, body) = match (words.next(), words.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", "text/plain; version=0.0.4; charset=utf-8", metrics()),
+        (Some("GET"), _) => ("404 Not Found", "text/plain; charset=utf-8", "Metrics are at /metrics\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", "Only GET is supported\n".to_string()),
+    };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// A scrape in progress: the request is read, then the response written, then the connection closed
+struct Scrape {
+    stream: TcpStream,
+    request: Vec<u8>,
+    response: Option<Vec<u8>>,
+}
+
+impl Scrape {
+    /// Reads what arrived; true once the whole request head is in (or the peer stopped sending)
+    fn read(&mut self) -> io::Result<bool> {
+        let mut buffer = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Ok(true),
+                Ok(read) => {
+                    self.request.extend_from_slice(&buffer[..read]);
+                    if self.request.windows(4).any(|window| window == b"\r\n\r\n") {
+                        return Ok(true);
+                    }
+                    if self.request.len() > MAX_REQUEST_SIZE {
+                        return Err(io::Error::other("request too large"));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Writes as much of the response as the socket takes; true once all of it is sent
+    fn write(&mut self) -> io::Result<bool> {
+        let Some(response) = self.response.as_mut() else {
+            return Ok(false);
+        };
+        while !response.is_empty() {
+            match self.stream.write(response) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    response.drain(..written);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The metrics listener (`--metrics <addr>`)
+#[derive(Resource)]
+pub struct MetricsServer {
+    listener: TcpListener,
+    scrapes: Vec<Scrape>,
+}
+
+impl MetricsServer {
+    pub fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+        Ok(Self {
+            listener,
+            scrapes: Vec::new(),
+        })
+    }
+}
+
+/// Current values of every metric
+fn collect_metrics(
+    world: &WorldManager,
+    diagnostics: &DiagnosticsStore,
+    activity: &WorldActivity,
+    errors: &ErrorLog,
+    uptime: f64,
+    net_clients: Option<usize>,
+) -> Vec<Metric> {
+    let smoothed = |path: DiagnosticPath| {
+        diagnostics
+            .get(&path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or(0.0)
+    };
+    let stats = world.stats();
+    let mut metrics = vec![
+        Metric::gauge("worldseed_uptime_seconds", "Seconds since the game started", uptime),
+        Metric::gauge("worldseed_loaded_chunks", "Chunks with tilemap entities", stats.loaded_chunks as f64),
+        Metric::gauge("worldseed_cached_chunks", "Chunks held in memory", stats.cached_chunks as f64),
+        Metric::gauge("worldseed_dirty_chunks", "Chunks with unsaved changes", stats.dirty_chunks as f64),
+        Metric::gauge(
+            "worldseed_entities",
+            "Entities in the ECS world (smoothed)",
+            smoothed(EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+        ),
+        Metric::gauge(
+            "worldseed_tick_duration_seconds",
+            "Frame (simulation tick) duration (smoothed)",
+            smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME) / 1000.0,
+        ),
+        Metric::gauge("worldseed_fps", "Frames per second (smoothed)", smoothed(FrameTimeDiagnosticsPlugin::FPS)),
+        Metric::counter(
+            "worldseed_chunks_loaded_total",
+            "Chunks loaded around the camera since startup",
+            activity.chunks_loaded_total as f64,
+        ),
+        Metric::counter("worldseed_chunk_cache_hits_total", "Chunk loads served from the cache", stats.cache_hits as f64),
+        Metric::counter(
+            "worldseed_chunk_cache_misses_total",
+            "Chunk loads that read or generated the chunk",
+            stats.cache_misses as f64,
+        ),
+        Metric::counter(
+            "worldseed_tile_changes_applied_total",
+            "Tile modifications written to chunks since startup",
+            activity.tile_changes_applied_total as f64,
+        ),
+        Metric::counter(
+            "worldseed_errors_total",
+            "Failures reported since startup (saving, loading, mods, ...)",
+            errors.total as f64,
+        ),
+    ];
+    if let Some(clients) = net_clients {
+        metrics.push(Metric::gauge("worldseed_net_clients", "Clients connected to the shared world", clients as f64));
+    }
+    metrics
+}
+
+/// Accepts scrapes and answers them; values are collected once per frame, only when asked for
+pub fn serve_metrics(
+    mut server: ResMut<MetricsServer>,
+    world: Res<WorldManager>,
+    diagnostics: Res<DiagnosticsStore>,
+    activity: Res<WorldActivity>,
+    errors: Res<ErrorLog>,
+    time: Res<Time<Real>>,
+    net: Option<Res<NetServer>>,
+) {
+    let server = &mut *server;
+    loop {
+        match server.listener.accept() {
+            Ok((stream, address)) => {
+                if server.scrapes.len() >= MAX_PENDING_SCRAPES {
+                    debug!("Refused metrics scrape from {}: too many in progress", address);
+                    continue;
+                }
+                if let Err(e) = stream.set_nonblocking(true) {
+                    debug!("Failed to set up metrics scrape from {}: {}", address, e);
+                    continue;
+                }
+                server.scrapes.push(Scrape {
+                    stream,
+                    request: Vec::new(),
+                    response: None,
+                });
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Failed to accept a metrics scrape: {}", e);
+                break;
+            }
+        }
+    }
+
+    let mut text = None;
+    server.scrapes.retain_mut(|scrape| {
+        if scrape.response.is_none() {
+            match scrape.read() {
+                Ok(true) => {
+                    let request = String::from_utf8_lossy(&scrape.request);
+                    let response = http_response(&request, || {
+                        text.get_or_insert_with(|| {
+                            render_metrics(&collect_metrics(
+                                &world,
+                                &diagnostics,
+                                &activity,
+                                &errors,
+                                time.elapsed_secs_f64(),
+                                net.as_ref().map(|net| net.clients.len()),
+                            ))
+                        })
+                        .clone()
+                    });
+                    scrape.response = Some(response.into_bytes());
+                }
+                Ok(false) => return true,
+                Err(e) => {
+                    debug!("Dropped a metrics scrape: {}", e);
+                    return false;
+                }
+            }
+        }
+        // Done (or broken) once the response is out
+        !matches!(scrape.write(), Ok(true) | Err(_))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_exposition() {
+        let metrics = [
+            Metric::gauge("worldseed_loaded_chunks", "Chunks with tilemap entities", 49.0),
+            Metric::counter("worldseed_errors_total", "Failures reported", 2.0),
+            Metric::gauge("worldseed_tick_duration_seconds", "Frame duration", 0.0165),
+        ];
+        assert_eq!(
+            render_metrics(&metrics),
+            "# HELP worldseed_loaded_chunks Chunks with tilemap entities\n\
+             # TYPE worldseed_loaded_chunks gauge\n\
+             worldseed_loaded_chunks 49\n\
+             # HELP worldseed_errors_total Failures reported\n\
+             # TYPE worldseed_errors_total counter\n\
+             worldseed_errors_total 2\n\
+             # HELP worldseed_tick_duration_seconds Frame duration\n\
+             # TYPE worldseed_tick_duration_seconds gauge\n\
+             worldseed_tick_duration_seconds 0.0165\n"
+        );
+
+        let ok = http_response("GET /metrics HTTP/1.1\r\nHost: localhost", || "a 1\n".to_string());
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.contains("Content-Length: 4\r\n"));
+        assert!(ok.ends_with("\r\n\r\na 1\n"));
+        let missing = http_response("GET / HTTP/1.1", || unreachable!());
+        assert!(missing.starts_with("HTTP/1.1 404"));
+        assert!(http_response("POST /metrics HTTP/1.1", || unreachable!()).starts_with("HTTP/1.1 405"));
+    }
+}
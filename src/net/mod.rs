@@ -0,0 +1,65 @@
+pub mod client;
+#[cfg(feature = "server")]
+pub mod metrics;
+pub mod protocol;
+#[cfg(feature = "server")]
+pub mod server;
+
+pub use client::NetClient;
+pub use protocol::NetError;
+#[cfg(feature = "server")]
+pub use metrics::{MetricsPlugin, MetricsServer};
+#[cfg(feature = "server")]
+pub use server::NetServer;
+
+use crate::schedule::{add_game_sets, GameSet};
+#[cfg(feature = "server")]
+use crate::world::loader;
+use bevy::prelude::*;
+
+/// Plugin for shared worlds over TCP (`--serve` / `--connect`)
+/// With a `NetServer` the game runs as usual and also streams the world to clients; with a
+/// `NetClient` the local simulation is off and the world shown is the server's. With neither
+/// resource nothing here runs. Hosting needs the `server` feature
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_sets(app);
+        app.configure_sets(Update, (GameSet::Ai, GameSet::Movement).run_if(is_world_authority))
+            .add_systems(PostStartup, client::despawn_local_entities.run_if(resource_exists::<NetClient>))
+            .add_systems(Update, (
+                client::receive_server_messages,
+                client::send_view.after(client::receive_server_messages),
+            ).in_set(GameSet::Input).run_if(resource_exists::<NetClient>))
+            .add_systems(Update, client::forward_requests
+                .in_set(GameSet::WorldApply)
+                .run_if(resource_exists::<NetClient>))
+            .add_systems(Update, (
+                client::sync_remote_entities,
+                client::flush_client,
+            ).in_set(GameSet::Render).run_if(resource_exists::<NetClient>));
+        #[cfg(feature = "server")]
+        app
+            .add_systems(Update, (
+                server::accept_clients,
+                server::receive_client_messages.after(server::accept_clients),
+            ).in_set(GameSet::Input).run_if(resource_exists::<NetServer>))
+            .add_systems(Update, (
+                server::stream_chunks
+                    .after(loader::unload_distant_chunks)
+                    .before(loader::apply_tile_modifications),
+                server::broadcast_tile_changes.after(loader::apply_tile_modifications),
+            ).in_set(GameSet::WorldApply).run_if(resource_exists::<NetServer>))
+            .add_systems(Update, (
+                server::broadcast_entities,
+                server::flush_clients.after(server::broadcast_entities),
+            ).in_set(GameSet::Render).run_if(resource_exists::<NetServer>));
+    }
+}
+
+/// Run condition: this game owns the world (not connected to a server)
+/// Systems that change tiles or move creatures on their own only run where this holds
+pub fn is_world_authority(client: Option<Res<NetClient>>) -> bool {
+    client.is_none()
+}
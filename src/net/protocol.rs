@@ -0,0 +1,252 @@
+use crate::tiles::{ChunkData, ChunkPos, TileId, CHUNK_AREA, NUM_LAYERS};
+use crate::world::TileModificationEvent;
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use thiserror::Error;
+
+/// Bumped whenever a message changes shape; clients with a different version are refused
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Largest accepted frame (bytes); anything bigger means a broken or hostile peer
+const MAX_FRAME_SIZE: usize = 1 << 22;
+
+/// A peer that falls this far behind (unsent bytes) is disconnected
+const MAX_SEND_BACKLOG: usize = 1 << 24;
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Encoding error: {0}")]
+    Encoding(#[from] bincode::Error),
+    #[error("Message too large: {0} bytes")]
+    FrameTooLarge(usize),
+    #[error("Connection closed")]
+    Closed,
+}
+
+/// One tile change, as sent in both directions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileChange {
+    pub world_x: f32,
+    pub world_y: f32,
+    pub tile_id: TileId,
+    pub layer: u8,
+}
+
+impl From<&TileModificationEvent> for TileChange {
+    fn from(event: &TileModificationEvent) -> Self {
+        Self {
+            world_x: event.world_x,
+            world_y: event.world_y,
+            tile_id: event.tile_id,
+            layer: event.layer as u8,
+        }
+    }
+}
+
+impl From<TileChange> for TileModificationEvent {
+    fn from(change: TileChange) -> Self {
+        TileModificationEvent::new(Vec2::new(change.world_x, change.world_y), change.tile_id, change.layer as usize)
+    }
+}
+
+/// All layers of a chunk
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkSnapshot {
+    pub position: ChunkPos,
+    /// Layer by layer, row-major
+    pub tiles: Vec<TileId>,
+}
+
+impl ChunkSnapshot {
+    pub fn from_chunk(chunk: &ChunkData) -> Self {
+        Self {
+            position: chunk.position,
+            tiles: chunk.layers.iter().flatten().copied().collect(),
+        }
+    }
+
+    /// The chunk, or None if the tile count is wrong
+    pub fn to_chunk(&self) -> Option<ChunkData> {
+        if self.tiles.len() != CHUNK_AREA * NUM_LAYERS {
+            return None;
+        }
+        let mut chunk = ChunkData::empty(self.position);
+        for (slot, tile) in chunk.layers.iter_mut().flatten().zip(&self.tiles) {
+            *slot = *tile;
+        }
+        Some(chunk)
+    }
+}
+
+/// Where an entity is and how to draw it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    /// Server-side entity id (`Entity::to_bits`)
+    pub id: u64,
+    /// `EntityRegistry` name ("snail", "guardian:oak", "tree_spirit:pine", ...)
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    /// Sprite sheet frame and scale (trees grow by scaling)
+    pub frame: u32,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Hello { version: u16 },
+    /// The chunks the client wants kept up to date (its camera chunk and a radius)
+    View { center: ChunkPos, radius: i32 },
+    /// Requests; the server decides and sends back what actually changed
+    SetTile(TileChange),
+    Place { entity: String, x: f32, y: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome { client_id: u32 },
+    Refused { reason: String },
+    Chunk(ChunkSnapshot),
+    Tile(TileChange),
+    /// Every entity in the client's view
+    Entities(Vec<EntitySnapshot>),
+}
+
+/// Length-prefixed (u32 LE) bincode frame
+pub fn encode_frame<T: Serialize>(message: &T) -> Result<Vec<u8>, NetError> {
+    let body = bincode::serialize(message)?;
+    if body.len() > MAX_FRAME_SIZE {
+        return Err(NetError::FrameTooLarge(body.len()));
+    }
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+/// Decode and remove every complete frame at the front of `buffer`; a partial frame stays for later
+pub fn decode_frames<T: DeserializeOwned>(buffer: &mut Vec<u8>) -> Result<Vec<T>, NetError> {
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = buffer.get(offset..offset + 4) {
+        let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if length > MAX_FRAME_SIZE {
+            return Err(NetError::FrameTooLarge(length));
+        }
+        let Some(body) = buffer.get(offset + 4..offset + 4 + length) else {
+            break;
+        };
+        messages.push(bincode::deserialize(body)?);
+        offset += 4 + length;
+    }
+    buffer.drain(..offset);
+    Ok(messages)
+}
+
+/// A non-blocking TCP connection exchanging framed messages
+/// `send` only queues; `flush` writes as much as the socket takes, so a frame never blocks,
+/// and fails once the peer falls `MAX_SEND_BACKLOG` bytes behind
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, NetError> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn peer(&self) -> String {
+        self.stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_else(|_| "?".to_string())
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), NetError> {
+        self.outgoing.extend(encode_frame(message)?);
+        Ok(())
+    }
+
+    /// Messages that arrived since the last call
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Result<Vec<T>, NetError> {
+        let mut buffer = [0u8; 16 * 1024];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(NetError::Closed),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        decode_frames(&mut self.incoming)
+    }
+
+    pub fn flush(&mut self) -> Result<(), NetError> {
+        if self.outgoing.len() > MAX_SEND_BACKLOG {
+            return Err(NetError::FrameTooLarge(self.outgoing.len()));
+        }
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(NetError::Closed),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{LAYER_GROUND, TILE_DIRT};
+
+    #[test]
+    fn test_frames_round_trip() {
+        let mut chunk = ChunkData::empty(ChunkPos::new(-2, 5));
+        chunk.set_tile(LAYER_GROUND, 3, 4, TILE_DIRT);
+        let messages = [
+            ServerMessage::Welcome { client_id: 7 },
+            ServerMessage::Chunk(ChunkSnapshot::from_chunk(&chunk)),
+            ServerMessage::Entities(vec![EntitySnapshot {
+                id: 42,
+                kind: "snail".to_string(),
+                x: 1.5,
+                y: -3.0,
+                frame: 2,
+                scale: 1.0,
+            }]),
+        ];
+
+        let mut stream: Vec<u8> = messages.iter().flat_map(|message| encode_frame(message).unwrap()).collect();
+        // Frames arriving in pieces are decoded once complete
+        let tail = stream.split_off(stream.len() - 5);
+        let decoded: Vec<ServerMessage> = decode_frames(&mut stream).unwrap();
+        assert_eq!(decoded, messages[..2]);
+        stream.extend(tail);
+        let decoded: Vec<ServerMessage> = decode_frames(&mut stream).unwrap();
+        assert_eq!(decoded, messages[2..]);
+        assert!(stream.is_empty());
+
+        let ServerMessage::Chunk(snapshot) = &messages[1] else { unreachable!() };
+        assert_eq!(snapshot.to_chunk().unwrap().get_tile(LAYER_GROUND, 3, 4), Some(TILE_DIRT));
+
+        let mut oversized = (MAX_FRAME_SIZE as u32 + 1).to_le_bytes().to_vec();
+        assert!(matches!(decode_frames::<ServerMessage>(&mut oversized), Err(NetError::FrameTooLarge(_))));
+    }
+}
@@ -0,0 +1,285 @@
+use super::protocol::{
+    ChunkSnapshot, ClientMessage, Connection, EntitySnapshot, NetError, ServerMessage, TileChange, PROTOCOL_VERSION,
+};
+use crate::editor::{EditHeatmap, EntityType, PlaceEntityRequest};
+use crate::entities::{entity_kind, ForestGuardian, GrowingTree, Player, Position, SaveableFilter, Snail};
+use crate::error::Failures;
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkData, ChunkPos};
+use crate::world::model::{self, ChunkSource};
+use crate::world::serialization::SerializationError;
+use crate::world::{generator, TileEdits, TileModificationEvent, WorldManager};
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::net::TcpListener;
+
+/// Largest view radius a client may ask for (chunks)
+const MAX_VIEW_RADIUS: i32 = 8;
+
+/// Chunks sent to one client per frame, so a new client doesn't stall the server
+const CHUNKS_PER_FRAME: usize = 8;
+
+/// Seconds between entity snapshots
+const ENTITY_SNAPSHOT_INTERVAL: f32 = 0.2;
+
+/// A connected client
+pub struct RemoteClient {
+    pub id: u32,
+    connection: Connection,
+    /// Said hello with a matching protocol version
+    joined: bool,
+    view: Option<(ChunkPos, i32)>,
+    /// Chunks this client has a current copy of; tile changes are only sent for these
+    sent_chunks: HashSet<ChunkPos>,
+}
+
+impl RemoteClient {
+    fn in_view(&self, pos: &ChunkPos) -> bool {
+        self.view.is_some_and(|(center, radius)| center.chebyshev_distance(pos) <= radius)
+    }
+}
+
+/// The authoritative end of a shared world: owns the `WorldManager` and the simulation, streams
+/// chunks, tile changes and entity snapshots to clients and applies their placement/paint requests
+#[derive(Resource)]
+pub struct NetServer {
+    listener: TcpListener,
+    pub clients: Vec<RemoteClient>,
+    next_id: u32,
+}
+
+impl NetServer {
+    pub fn bind(address: &str) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!("Serving the world on {}", listener.local_addr()?);
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            next_id: 1,
+        })
+    }
+}
+
+/// Chunk data for a client (see `model::read_chunk`)
+/// Chunks read here are cached (without tilemap entities) so client edits to them apply
+fn chunk_for_client(world: &mut WorldManager, pos: ChunkPos, failures: &mut Failures) -> ChunkData {
+    let chunk = match model::read_chunk(world, pos) {
+        Ok((chunk, ChunkSource::Cache)) => return chunk,
+        Ok((chunk, _)) => chunk,
+        // Not cached, so client edits can't have it saved over the newer build's file
+        Err(e @ SerializationError::InvalidVersion(_)) => {
+            let context = format!("Chunk ({}, {}) was saved by a newer version, sent a generated one", pos.x, pos.y);
+            failures.report(context, e);
+            return generator::generate_chunk(pos, &world.generator);
+        }
+        Err(e) => {
+            let context = format!("Failed to load chunk ({}, {}) for a client, sent a generated one", pos.x, pos.y);
+            failures.report(context, e);
+            generator::generate_chunk(pos, &world.generator)
+        }
+    };
+    world.cache_chunk(chunk.clone());
+    chunk
+}
+
+pub fn accept_clients(mut server: ResMut<NetServer>) {
+    loop {
+        match server.listener.accept() {
+            Ok((stream, address)) => match Connection::new(stream) {
+                Ok(connection) => {
+                    let id = server.next_id;
+                    server.next_id += 1;
+                    info!("Client {} connected from {}", id, address);
+                    server.clients.push(RemoteClient {
+                        id,
+                        connection,
+                        joined: false,
+                        view: None,
+                        sent_chunks: HashSet::new(),
+                    });
+                }
+                Err(e) => warn!("Failed to set up connection from {}: {}", address, e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Failed to accept a client: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Applies client requests: tile changes through `TileEdits` (counted in the `EditHeatmap`),
+/// placements through `PlaceEntityRequest`
+pub fn receive_client_messages(
+    time: Res<Time>,
+    mut server: ResMut<NetServer>,
+    mut tile_edits: TileEdits,
+    mut heatmap: ResMut<EditHeatmap>,
+    mut placements: MessageWriter<PlaceEntityRequest>,
+) {
+    server.clients.retain_mut(|client| {
+        let messages = match client.connection.receive::<ClientMessage>() {
+            Ok(messages) => messages,
+            Err(e) => {
+                info!("Client {} ({}) disconnected: {}", client.id, client.connection.peer(), e);
+                return false;
+            }
+        };
+
+        for message in messages {
+            match message {
+                ClientMessage::Hello { version } if version == PROTOCOL_VERSION => {
+                    client.joined = true;
+                    let _ = client.connection.send(&ServerMessage::Welcome { client_id: client.id });
+                }
+                ClientMessage::Hello { version } => {
+                    let reason =
+                        format!("Protocol version {} doesn't match the server's {}", version, PROTOCOL_VERSION);
+                    warn!("Refusing client {}: {}", client.id, reason);
+                    let _ = client.connection.send(&ServerMessage::Refused { reason });
+                    let _ = client.connection.flush();
+                    return false;
+                }
+                // Nothing else is accepted before a matching hello
+                _ if !client.joined => {}
+                ClientMessage::View { center, radius } => {
+                    client.view = Some((center, radius.clamp(0, MAX_VIEW_RADIUS)));
+                }
+                ClientMessage::SetTile(change) => {
+                    let event = TileModificationEvent::from(change);
+                    tile_edits.paint(event.position(), event.tile_id, event.layer);
+                    heatmap.record(event.position(), time.elapsed_secs());
+                }
+                ClientMessage::Place { entity, x, y } => match EntityType::from_str(&entity) {
+                    Some(entity_type) => {
+                        placements.write(PlaceEntityRequest {
+                            entity_type,
+                            position: Vec2::new(x, y),
+                        });
+                    }
+                    None => warn!("Client {} asked to place unknown entity '{}'", client.id, entity),
+                },
+            }
+        }
+        true
+    });
+}
+
+/// Sends each client the chunks in its view it doesn't have yet, and lets go of chunks
+/// that were only cached for clients once nobody views them
+pub fn stream_chunks(mut server: ResMut<NetServer>, mut world: ResMut<WorldManager>, mut failures: Failures) {
+    let mut viewed = HashSet::new();
+    for client in server.clients.iter_mut().filter(|client| client.joined) {
+        let Some((center, radius)) = client.view else {
+            continue;
+        };
+        let in_view = center.chunks_in_radius(radius);
+        viewed.extend(in_view.iter().copied());
+
+        // Out-of-view chunks go stale on the client, so they are sent again when they come back
+        client.sent_chunks.retain(|pos| center.chebyshev_distance(pos) <= radius);
+
+        let missing: Vec<ChunkPos> = in_view
+            .into_iter()
+            .filter(|pos| !client.sent_chunks.contains(pos))
+            .take(CHUNKS_PER_FRAME)
+            .collect();
+        for pos in missing {
+            let chunk = chunk_for_client(&mut world, pos, &mut failures);
+            if client.connection.send(&ServerMessage::Chunk(ChunkSnapshot::from_chunk(&chunk))).is_ok() {
+                client.sent_chunks.insert(pos);
+            }
+            world.pool.recycle_chunk(chunk);
+        }
+    }
+
+    let client_only: Vec<ChunkPos> = world
+        .chunk_cache
+        .keys()
+        .filter(|pos| !world.is_loaded(pos) && !viewed.contains(pos))
+        .copied()
+        .collect();
+    // Edited ones are saved in the background, and failures reported by `loader::poll_chunk_saves`
+    for pos in client_only {
+        model::release_chunk(&mut world, pos);
+    }
+}
+
+/// Forwards every applied tile change to the clients that have the chunk
+pub fn broadcast_tile_changes(mut server: ResMut<NetServer>, mut modifications: MessageReader<TileModificationEvent>) {
+    for modification in modifications.read() {
+        let chunk_pos = coords::world_to_chunk(modification.position());
+        let message = ServerMessage::Tile(TileChange::from(modification));
+        for client in server.clients.iter_mut().filter(|client| client.sent_chunks.contains(&chunk_pos)) {
+            let _ = client.connection.send(&message);
+        }
+    }
+}
+
+/// Sends each client the entities in its view a few times a second
+#[allow(clippy::type_complexity)]
+pub fn broadcast_entities(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut server: ResMut<NetServer>,
+    entities: Query<
+        (
+            Entity,
+            &Position,
+            Option<&Sprite>,
+            Has<Player>,
+            Has<Snail>,
+            Option<&ForestGuardian>,
+            Option<&GrowingTree>,
+        ),
+        SaveableFilter,
+    >,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < ENTITY_SNAPSHOT_INTERVAL || server.clients.is_empty() {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let snapshots: Vec<(ChunkPos, EntitySnapshot)> = entities
+        .iter()
+        .filter_map(|(entity, position, sprite, is_player, is_snail, guardian, tree)| {
+            let kind = entity_kind(is_player, is_snail, guardian, tree)?;
+            let frame = sprite.and_then(|sprite| sprite.texture_atlas.as_ref()).map_or(0, |atlas| atlas.index);
+            let chunk_pos = coords::world_to_chunk(position.vec2());
+            Some((
+                chunk_pos,
+                EntitySnapshot {
+                    id: entity.to_bits(),
+                    kind,
+                    x: position.x(),
+                    y: position.y(),
+                    frame: frame as u32,
+                    scale: tree.map_or(1.0, |tree| tree.stage.scale()),
+                },
+            ))
+        })
+        .collect();
+
+    for client in server.clients.iter_mut().filter(|client| client.view.is_some()) {
+        let visible = snapshots
+            .iter()
+            .filter(|(chunk_pos, _)| client.in_view(chunk_pos))
+            .map(|(_, snapshot)| snapshot.clone())
+            .collect();
+        let _ = client.connection.send(&ServerMessage::Entities(visible));
+    }
+}
+
+/// Writes queued messages; clients whose connection failed or fell too far behind are dropped
+pub fn flush_clients(mut server: ResMut<NetServer>) {
+    server.clients.retain_mut(|client| match client.connection.flush() {
+        Ok(()) => true,
+        Err(e) => {
+            info!("Client {} ({}) dropped: {}", client.id, client.connection.peer(), e);
+            false
+        }
+    });
+}
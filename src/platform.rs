@@ -0,0 +1,20 @@
+//! The few things that differ between native builds and the browser (`wasm32-unknown-unknown`)
+//! Chunk storage has its own backend trait (`world::storage`)
+
+use std::time::Duration;
+
+/// Time since the Unix epoch
+/// `SystemTime::now()` panics in the browser, so anything reading the wall clock goes through here
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Time since the Unix epoch
+/// `SystemTime::now()` panics in the browser, so anything reading the wall clock goes through here
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
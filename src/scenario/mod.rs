@@ -0,0 +1,449 @@
+//! Scenario presets: data-defined starting points (`scenarios/<id>.ron`, built into the game) that
+//! set a new world's generator, replace the default starting entities and give it objectives
+//! There is no world creation screen yet; a scenario is picked with `--scenario <id>` when a world is
+//! first created and remembered in `WorldMetadata.scenario`
+
+use crate::chronicle::ChronicleEvent;
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use crate::entities::{ForestGuardian, GrowingTree, Position, SpawnKind, SpawnQueue, TreeSpirit, TreeVariant};
+use crate::environment::WorldClock;
+use crate::stats::Statistics;
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkData, ChunkPos, TileId, CHUNK_SIZE_I32, LAYER_GROUND, TILE_GRASS};
+use crate::world::generator::{generate_chunk, GeneratorConfig};
+use crate::world::{metadata, WorldManager, WorldMetadata};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Scenarios built into the game: (id, RON source)
+const SCENARIOS: [(&str, &str); 3] = [
+    ("wasteland", include_str!("../../scenarios/wasteland.ron")),
+    ("ancient_forest", include_str!("../../scenarios/ancient_forest.ron")),
+    ("snail_infestation", include_str!("../../scenarios/snail_infestation.ron")),
+];
+
+/// Seconds between objective checks
+const OBJECTIVE_CHECK_INTERVAL: f32 = 5.0;
+
+/// Chronicle milestone recorded once every objective is complete
+const COMPLETE_MILESTONE: &str = "scenario_complete";
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("Unknown scenario '{0}' (see `scenarios`)")]
+    Unknown(String),
+    #[error("Invalid scenario file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Unknown entity kind '{0}'")]
+    UnknownKind(String),
+}
+
+/// Entities placed when the world starts: `count` of them, spread over a disc of `spread` pixels
+/// around (x, y)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StartingEntity {
+    /// "player", "snail", "guardian:<variant>" or "tree_spirit:<variant>" (as in saved entity files)
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub spread: f32,
+    /// Trees start fully grown
+    #[serde(default)]
+    pub mature: bool,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// What an objective asks for
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Goal {
+    /// Grass ground tiles within `radius` tiles of the start (0, 0)
+    GrassTiles { min: u32, radius: i32 },
+    /// Mature trees alive
+    MatureTrees { min: u32 },
+    /// Trees that reached maturity since the world was created
+    TreesGrown { min: u64 },
+    /// Guardian variants with at least one guardian alive
+    GuardianVariants { min: u32 },
+    /// Reach a day (counted from 1, as shown on the clock)
+    Day { day: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Objective {
+    pub text: String,
+    pub goal: Goal,
+    /// Only counts as met on or after this day (counted from 1), for "still ... on day N" goals
+    #[serde(default)]
+    pub from_day: u32,
+}
+
+/// Contents of a scenario file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub generator: GeneratorConfig,
+    #[serde(default)]
+    pub entities: Vec<StartingEntity>,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+}
+
+/// Ids of the built-in scenarios
+pub fn available_scenarios() -> impl Iterator<Item = &'static str> {
+    SCENARIOS.iter().map(|(id, _)| *id)
+}
+
+/// Parse and check a built-in scenario
+pub fn builtin(id: &str) -> Result<Scenario, ScenarioError> {
+    let (_, source) = SCENARIOS
+        .iter()
+        .find(|(available, _)| *available == id)
+        .ok_or_else(|| ScenarioError::Unknown(id.to_string()))?;
+    let scenario: Scenario = ron::from_str(source)?;
+    for entity in &scenario.entities {
+        spawn_kind(&entity.kind, entity.mature)?;
+    }
+    Ok(scenario)
+}
+
+/// The queued spawn for a starting entity kind
+fn spawn_kind(kind: &str, mature: bool) -> Result<SpawnKind, ScenarioError> {
+    let variant = |name: &str| {
+        TreeVariant::ALL
+            .into_iter()
+            .find(|variant| variant.as_str() == name)
+            .ok_or_else(|| ScenarioError::UnknownKind(kind.to_string()))
+    };
+    match kind.split_once(':') {
+        None if kind == "player" => Ok(SpawnKind::Player),
+        None if kind == "snail" => Ok(SpawnKind::Snail),
+        Some(("guardian", name)) => Ok(SpawnKind::ForestGuardian(variant(name)?.as_str().to_string())),
+        Some(("tree_spirit", name)) if mature => Ok(SpawnKind::MatureTree(variant(name)?)),
+        Some(("tree_spirit", name)) => Ok(SpawnKind::TreeSpirit {
+            variant: variant(name)?,
+            growth_time: 5.0,
+        }),
+        _ => Err(ScenarioError::UnknownKind(kind.to_string())),
+    }
+}
+
+impl Scenario {
+    /// Set up a new world's starting metadata for this scenario
+    pub fn apply(&self, id: &str, metadata: &mut WorldMetadata) {
+        metadata.scenario = Some(id.to_string());
+        metadata.generator = self.generator;
+    }
+
+    /// Every starting entity with its position
+    /// Groups are laid out on a sunflower spiral, so they spread evenly and the same way every time
+    pub fn starting_spawns(&self) -> Vec<(Position, SpawnKind)> {
+        const GOLDEN_ANGLE: f32 = 2.399_963;
+        let mut spawns = Vec::new();
+        for entity in &self.entities {
+            let Ok(kind) = spawn_kind(&entity.kind, entity.mature) else {
+                continue;
+            };
+            for i in 0..entity.count {
+                let distance = entity.spread * ((i as f32 + 0.5) / entity.count as f32).sqrt();
+                let offset = Vec2::from_angle(i as f32 * GOLDEN_ANGLE) * distance;
+                let offset = if entity.count == 1 { Vec2::ZERO } else { offset };
+                spawns.push((Position::new(entity.x + offset.x, entity.y + offset.y), kind.clone()));
+            }
+        }
+        spawns
+    }
+}
+
+/// Everything objectives are measured against, read once per check
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldReadings {
+    /// Day counted from 1
+    pub day: u32,
+    pub mature_trees: u32,
+    pub trees_grown: u64,
+    pub guardian_variants: u32,
+    /// Grass tiles by radius (only the radii the objectives ask for)
+    pub grass: BTreeMap<i32, u32>,
+}
+
+impl Goal {
+    /// (current, target)
+    pub fn progress(&self, readings: &WorldReadings) -> (u64, u64) {
+        match self {
+            Goal::GrassTiles { min, radius } => (readings.grass.get(radius).copied().unwrap_or(0) as u64, *min as u64),
+            Goal::MatureTrees { min } => (readings.mature_trees as u64, *min as u64),
+            Goal::TreesGrown { min } => (readings.trees_grown, *min),
+            Goal::GuardianVariants { min } => (readings.guardian_variants as u64, *min as u64),
+            Goal::Day { day } => (readings.day as u64, *day as u64),
+        }
+    }
+}
+
+impl Objective {
+    pub fn is_met(&self, readings: &WorldReadings) -> bool {
+        let (current, target) = self.goal.progress(readings);
+        readings.day >= self.from_day && current >= target
+    }
+}
+
+/// Tiles of `tile` on the ground layer within `radius` tiles (a square) of tile (0, 0)
+pub fn count_ground_tiles(radius: i32, tile: TileId, mut chunk_at: impl FnMut(ChunkPos) -> ChunkData) -> u32 {
+    let min = coords::tile_to_chunk(IVec2::splat(-radius));
+    let max = coords::tile_to_chunk(IVec2::splat(radius));
+    let mut count = 0;
+    for chunk_y in min.y..=max.y {
+        for chunk_x in min.x..=max.x {
+            let chunk = chunk_at(ChunkPos::new(chunk_x, chunk_y));
+            for local_y in 0..CHUNK_SIZE_I32 {
+                for local_x in 0..CHUNK_SIZE_I32 {
+                    let world_x = chunk_x * CHUNK_SIZE_I32 + local_x;
+                    let world_y = chunk_y * CHUNK_SIZE_I32 + local_y;
+                    let inside = world_x.abs() <= radius && world_y.abs() <= radius;
+                    if inside && chunk.get_tile(LAYER_GROUND, local_x as usize, local_y as usize) == Some(tile) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+    }
+    count
+}
+
+/// The scenario of the open world
+#[derive(Resource, Debug, Clone)]
+pub struct ActiveScenario {
+    pub id: String,
+    pub scenario: Scenario,
+    /// Readings from the last objective check
+    pub readings: Option<WorldReadings>,
+}
+
+impl ActiveScenario {
+    /// Chronicle milestone key of an objective
+    fn milestone(&self, index: usize) -> String {
+        format!("objective_{}_{}", self.id, index)
+    }
+
+    pub fn is_complete(&self, index: usize, metadata: &WorldMetadata) -> bool {
+        let key = self.milestone(index);
+        metadata.chronicle.iter().any(|entry| entry.milestone.as_deref() == Some(key.as_str()))
+    }
+}
+
+/// Plugin for scenario presets: starting entities, objective checks and the `scenario`/`scenarios` commands
+/// `setup_world` leaves out its default starting entities when the world has a scenario
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command("scenario", "scenario - show this world's scenario and objectives")
+            .add_console_command("scenarios", "scenarios - list the built-in scenarios (start one with --scenario <id>)")
+            .add_systems(Startup, start_scenario.after(metadata::load_world_metadata))
+            .add_systems(Update, (
+                check_objectives.run_if(resource_exists::<ActiveScenario>),
+                handle_scenario_commands.after(check_objectives),
+            ));
+    }
+}
+
+/// Activates the world's scenario and queues its starting entities (only the player once they've
+/// been spawned; the rest are saved with their chunks)
+fn start_scenario(mut commands: Commands, mut metadata: ResMut<WorldMetadata>, mut spawn_queue: ResMut<SpawnQueue>) {
+    let Some(id) = metadata.scenario.clone() else {
+        return;
+    };
+    match builtin(&id) {
+        Ok(scenario) => {
+            let spawned = metadata.starting_entities_spawned;
+            for (position, kind) in scenario.starting_spawns() {
+                if !spawned || kind == SpawnKind::Player {
+                    spawn_queue.push(position, kind);
+                }
+            }
+            metadata.starting_entities_spawned = true;
+            info!("Scenario: {}", scenario.name);
+            commands.insert_resource(ActiveScenario {
+                id,
+                scenario,
+                readings: None,
+            });
+        }
+        Err(e) => warn!("World scenario '{}' can't be started: {}", id, e),
+    }
+}
+
+/// Measures the world every few seconds and records newly met objectives in the chronicle (which
+/// announces them with a toast)
+#[allow(clippy::too_many_arguments)]
+fn check_objectives(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut active: ResMut<ActiveScenario>,
+    metadata: Res<WorldMetadata>,
+    clock: Res<WorldClock>,
+    statistics: Res<Statistics>,
+    world: Res<WorldManager>,
+    trees: Query<&GrowingTree, With<TreeSpirit>>,
+    guardians: Query<&ForestGuardian>,
+    mut chronicle: MessageWriter<ChronicleEvent>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < OBJECTIVE_CHECK_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let mut readings = WorldReadings {
+        day: clock.0.day + 1,
+        mature_trees: trees.iter().filter(|tree| tree.is_mature()).count() as u32,
+        trees_grown: statistics.0.trees_grown,
+        guardian_variants: TreeVariant::ALL
+            .iter()
+            .filter(|variant| guardians.iter().any(|guardian| guardian.variant == **variant))
+            .count() as u32,
+        grass: BTreeMap::new(),
+    };
+    for objective in &active.scenario.objectives {
+        if let Goal::GrassTiles { radius, .. } = objective.goal {
+            readings.grass.entry(radius).or_insert_with(|| {
+                count_ground_tiles(radius, TILE_GRASS, |pos| {
+                    // The in-memory copy may have unsaved edits
+                    match world.get_cached_chunk(&pos) {
+                        Some(chunk) => chunk.clone(),
+                        None => match world.load_stored_chunk(&pos) {
+                            Ok(Some(chunk)) => chunk,
+                            _ => generate_chunk(pos, &world.generator),
+                        },
+                    }
+                })
+            });
+        }
+    }
+
+    let mut remaining = 0;
+    for (index, objective) in active.scenario.objectives.iter().enumerate() {
+        if active.is_complete(index, &metadata) {
+            continue;
+        }
+        if objective.is_met(&readings) {
+            chronicle.write(ChronicleEvent::milestone(
+                active.milestone(index),
+                format!("Objective complete: {}", objective.text),
+            ));
+        } else {
+            remaining += 1;
+        }
+    }
+    let recorded = metadata
+        .chronicle
+        .iter()
+        .any(|entry| entry.milestone.as_deref() == Some(COMPLETE_MILESTONE));
+    if remaining == 0 && !active.scenario.objectives.is_empty() && !recorded {
+        chronicle.write(ChronicleEvent::milestone(
+            COMPLETE_MILESTONE,
+            format!("Scenario complete: {}", active.scenario.name),
+        ));
+    }
+    active.readings = Some(readings);
+}
+
+/// Console commands: `scenario` shows the world's objectives, `scenarios` lists the built-in ones
+fn handle_scenario_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    active: Option<Res<ActiveScenario>>,
+    metadata: Res<WorldMetadata>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "scenarios" => {
+                for id in available_scenarios() {
+                    match builtin(id) {
+                        Ok(scenario) => console.print(format!("  {} - {}: {}", id, scenario.name, scenario.description)),
+                        Err(e) => console.print(format!("  {} - {}", id, e)),
+                    }
+                }
+            }
+            "scenario" => {
+                let Some(active) = &active else {
+                    console.print("This world has no scenario (create one with --scenario <id>, see `scenarios`)");
+                    continue;
+                };
+                console.print(format!("{}: {}", active.scenario.name, active.scenario.description));
+                for (index, objective) in active.scenario.objectives.iter().enumerate() {
+                    let done = active.is_complete(index, &metadata);
+                    let progress = match &active.readings {
+                        Some(readings) if !done => {
+                            let (current, target) = objective.goal.progress(readings);
+                            format!(" ({}/{})", current.min(target), target)
+                        }
+                        _ => String::new(),
+                    };
+                    let mark = if done { "x" } else { " " };
+                    console.print(format!("  [{}] {}{}", mark, objective.text, progress));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TILE_DIRT;
+
+    #[test]
+    fn test_scenarios() {
+        // Every built-in scenario parses and only names known entity kinds
+        for id in available_scenarios() {
+            let scenario = builtin(id).unwrap_or_else(|e| panic!("{}: {}", id, e));
+            assert!(!scenario.objectives.is_empty(), "{} has no objectives", id);
+        }
+        assert!(matches!(builtin("atlantis"), Err(ScenarioError::Unknown(_))));
+        assert!(matches!(spawn_kind("guardian:palm", false), Err(ScenarioError::UnknownKind(_))));
+
+        let infestation = builtin("snail_infestation").unwrap();
+        let spawns = infestation.starting_spawns();
+        let snails: Vec<&Position> = spawns.iter().filter(|(_, kind)| *kind == SpawnKind::Snail).map(|(pos, _)| pos).collect();
+        assert_eq!(snails.len(), 60);
+        assert!(snails.iter().all(|pos| pos.vec2().length() <= 400.0));
+        assert_eq!(spawns[0], (Position::new(0.0, 0.0), SpawnKind::Player));
+
+        let mut metadata = WorldMetadata::default();
+        infestation.apply("snail_infestation", &mut metadata);
+        assert_eq!(metadata.scenario.as_deref(), Some("snail_infestation"));
+        assert_eq!(metadata.generator, infestation.generator);
+
+        // "Still ... on day 5" waits for the day
+        let objective = Objective {
+            text: String::new(),
+            goal: Goal::GrassTiles { min: 10, radius: 2 },
+            from_day: 5,
+        };
+        let mut readings = WorldReadings {
+            day: 4,
+            grass: BTreeMap::from([(2, 25)]),
+            ..default()
+        };
+        assert!(!objective.is_met(&readings));
+        readings.day = 5;
+        assert!(objective.is_met(&readings));
+
+        // A 5x5 square around the origin spans four chunks
+        let grass = count_ground_tiles(2, TILE_GRASS, |pos| ChunkData::filled(pos, TILE_GRASS));
+        assert_eq!(grass, 25);
+        let none = count_ground_tiles(2, TILE_GRASS, |pos| ChunkData::filled(pos, TILE_DIRT));
+        assert_eq!(none, 0);
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+/// Named stages of a frame, run in this order every `Update`:
+/// `Input` -> `Ai` -> `Movement` -> `WorldApply` -> `Render`
+///
+/// New systems should join the set matching what they do, rather than adding `.after()` chains
+/// to systems in other plugins. Systems that don't fit a stage (console commands, periodic
+/// syncs, UI panels) can stay outside the sets.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    /// Keyboard and mouse: camera movement, zoom, placing entities, painting terrain
+    Input,
+    /// Decisions: where creatures want to go, tree spawning and spreading, simulation tiers
+    Ai,
+    /// Positions change: velocity, facing, transform sync, background creatures jumping
+    Movement,
+    /// The world catches up: tiles entities walked over are queued, chunks stream in and out
+    /// around the (moved) camera, and queued tile modifications are applied
+    WorldApply,
+    /// Visuals that read the final state of the frame: animation rows and frames, tileset, button highlights
+    Render,
+}
+
+/// Whether the game is still preloading its assets or running
+/// The `GameSet`s only run while `Playing`; see `assets::loading`
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Playing,
+}
+
+/// Run condition: the game is `Playing`, or has no `GameState` at all (tests and tools that
+/// build a partial app)
+pub fn is_playing(state: Option<Res<State<GameState>>>) -> bool {
+    state.is_none_or(|state| *state.get() == GameState::Playing)
+}
+
+/// Orders the `GameSet`s and holds them until loading is done; added by every plugin that uses them
+pub struct GameSetsPlugin;
+
+impl Plugin for GameSetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (GameSet::Input, GameSet::Ai, GameSet::Movement, GameSet::WorldApply, GameSet::Render).chain(),
+        );
+        for set in [GameSet::Input, GameSet::Ai, GameSet::Movement, GameSet::WorldApply, GameSet::Render] {
+            app.configure_sets(Update, set.run_if(is_playing));
+        }
+    }
+}
+
+/// Adds `GameSetsPlugin` unless another plugin already did
+pub fn add_game_sets(app: &mut App) {
+    if !app.is_plugin_added::<GameSetsPlugin>() {
+        app.add_plugins(GameSetsPlugin);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::console::console_closed;
+use crate::entities::{Player, Velocity};
+use crate::locale::{Locale, LocalizedText};
+use crate::tiles::constants::TILE_SIZE;
+use crate::world::{MetadataSync, WorldMetadata, WorldOrigin, WorldStatistics};
+use bevy::prelude::*;
+
+/// How often live statistics are copied into world metadata (seconds)
+const STATS_SYNC_INTERVAL: f32 = 10.0;
+
+/// Plugin for lifetime world statistics and the stats screen (Tab)
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<StatEvent>()
+            .init_resource::<Statistics>()
+            .add_systems(Startup, setup_stats_screen)
+            // After world metadata is loaded
+            .add_systems(PostStartup, restore_statistics)
+            .add_systems(Update, (
+                record_stat_events,
+                track_distance_traveled,
+                sync_statistics.after(record_stat_events).after(track_distance_traveled),
+                toggle_stats_screen.run_if(console_closed),
+                update_stats_screen.after(sync_statistics).after(toggle_stats_screen),
+            ));
+    }
+}
+
+/// Something worth counting happened
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatEvent {
+    TilePainted,
+    TreeGrown,
+    SnailSpawned,
+    GrassRestored,
+}
+
+/// Live statistics for the current world
+/// Updated every frame; copied into `WorldMetadata.statistics` every few seconds
+/// so metadata isn't rewritten on every camera movement
+#[derive(Resource, Default)]
+pub struct Statistics(pub WorldStatistics);
+
+impl Statistics {
+    pub fn record(&mut self, event: StatEvent) {
+        match event {
+            StatEvent::TilePainted => self.0.tiles_painted += 1,
+            StatEvent::TreeGrown => self.0.trees_grown += 1,
+            StatEvent::SnailSpawned => self.0.snails_spawned += 1,
+            StatEvent::GrassRestored => self.0.grass_restored += 1,
+        }
+    }
+}
+
+/// Marker component for the stats screen
+#[derive(Component)]
+pub struct StatsScreen;
+
+/// Marker component for the stats screen's counter text
+#[derive(Component)]
+pub struct StatsText;
+
+/// Picks up the counters saved with the world
+fn restore_statistics(metadata: Res<WorldMetadata>, mut statistics: ResMut<Statistics>) {
+    statistics.0 = metadata.statistics.clone();
+}
+
+fn record_stat_events(mut events: MessageReader<StatEvent>, mut statistics: ResMut<Statistics>) {
+    for event in events.read() {
+        statistics.record(*event);
+    }
+}
+
+/// Adds up how far the camera and player have moved
+fn track_distance_traveled(
+    time: Res<Time>,
+    camera: Single<&Transform, With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+    players: Query<&Velocity, With<Player>>,
+    mut last_camera: Local<Option<Vec2>>,
+    mut statistics: ResMut<Statistics>,
+) {
+    let camera_position = origin.to_world_vec2(camera.translation.truncate());
+    let camera_moved = last_camera.map_or(0.0, |last| last.distance(camera_position));
+    *last_camera = Some(camera_position);
+
+    let player_moved: f32 = players
+        .iter()
+        .map(|velocity| Vec2::new(velocity.x, velocity.y).length() * time.delta_secs())
+        .sum();
+
+    if camera_moved > 0.0 || player_moved > 0.0 {
+        statistics.0.camera_distance += camera_moved as f64;
+        statistics.0.player_distance += player_moved as f64;
+    }
+}
+
+/// Copies live statistics into world metadata periodically and when the window closes
+fn sync_statistics(mut sync: MetadataSync, statistics: Res<Statistics>) {
+    if sync.due(STATS_SYNC_INTERVAL) {
+        sync.update(|metadata| &mut metadata.statistics, statistics.0.clone());
+    }
+}
+
+/// Spawns the hidden stats screen (centered panel)
+fn setup_stats_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            StatsScreen,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            Pickable::IGNORE,
+            ZIndex(900),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(320.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.1, 0.1, 0.12, 0.92)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        LocalizedText("stats-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    panel.spawn((
+                        StatsText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 15.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.85, 0.85, 0.85)),
+                    ));
+                });
+        });
+}
+
+/// Tab toggles the stats screen
+fn toggle_stats_screen(keyboard: Res<ButtonInput<KeyCode>>, mut screen: Single<&mut Visibility, With<StatsScreen>>) {
+    if keyboard.just_pressed(KeyCode::Tab) {
+        **screen = match **screen {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Refreshes the counters while the stats screen is open
+fn update_stats_screen(
+    statistics: Res<Statistics>,
+    screen: Single<&Visibility, With<StatsScreen>>,
+    mut text: Single<&mut Text, With<StatsText>>,
+    locale: Res<Locale>,
+) {
+    if **screen == Visibility::Hidden {
+        return;
+    }
+
+    let stats = &statistics.0;
+    let tiles = |pixels: f64| (pixels / TILE_SIZE as f64).round() as u64;
+    let lines = [
+        ("stats-tiles-painted", stats.tiles_painted),
+        ("stats-trees-grown", stats.trees_grown),
+        ("stats-snails-spawned", stats.snails_spawned),
+        ("stats-grass-restored", stats.grass_restored),
+        ("stats-camera-traveled", tiles(stats.camera_distance)),
+        ("stats-player-traveled", tiles(stats.player_distance)),
+    ];
+    text.0 = lines
+        .map(|(id, count)| locale.format(id, &[("count", count.into())]))
+        .join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_stat_events() {
+        let mut statistics = Statistics::default();
+        statistics.record(StatEvent::TilePainted);
+        statistics.record(StatEvent::TilePainted);
+        statistics.record(StatEvent::TreeGrown);
+        statistics.record(StatEvent::SnailSpawned);
+        statistics.record(StatEvent::GrassRestored);
+
+        assert_eq!(statistics.0.tiles_painted, 2);
+        assert_eq!(statistics.0.trees_grown, 1);
+        assert_eq!(statistics.0.snails_spawned, 1);
+        assert_eq!(statistics.0.grass_restored, 1);
+    }
+}
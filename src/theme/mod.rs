@@ -0,0 +1,242 @@
+//! UI theming for the accessibility options (`AccessibilityConfig`): every font is scaled by
+//! `font_scale`, and panels marked `ThemedPanel` (the sidebar, the map modal and the HUDs) switch
+//! to high contrast colors. Reduced motion is read directly by the effects it turns off
+
+use crate::config::{AccessibilityConfig, GameConfig};
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsoleState};
+use bevy::prelude::*;
+
+/// Selection outline width (pixels), normally and in high contrast
+const OUTLINE_WIDTH: f32 = 2.0;
+const HIGH_CONTRAST_OUTLINE_WIDTH: f32 = 4.0;
+
+/// Plugin for the accessibility theme and the `accessibility` command
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_console_command(
+            "accessibility",
+            "accessibility [motion|contrast on|off] [fonts 0.5-2] - show or change accessibility options",
+        )
+        .add_systems(Update, (handle_accessibility_commands, scale_fonts, apply_contrast).chain());
+    }
+}
+
+/// This node and everything under it follow the high contrast setting
+#[derive(Component)]
+pub struct ThemedPanel;
+
+/// Keeps its colors in high contrast (icons and markers whose color means something)
+#[derive(Component)]
+pub struct KeepColors;
+
+/// A font's size before scaling
+#[derive(Component)]
+pub struct BaseFontSize(pub f32);
+
+/// Colors of a themed node as spawned (or last set by its owner) and as shown, so a color set by
+/// another system is picked up as the new base
+#[derive(Component, Default)]
+pub struct ThemedColors {
+    background: Option<(Color, Color)>,
+    border: Option<(Color, Color)>,
+    text: Option<(Color, Color)>,
+}
+
+/// A high contrast panel or button background: darker and nearly opaque (transparent stays transparent)
+pub fn contrast_background(color: Color) -> Color {
+    if color.alpha() == 0.0 {
+        return color;
+    }
+    let hsla = Hsla::from(color);
+    Hsla::new(hsla.hue, hsla.saturation, hsla.lightness * 0.5, hsla.alpha.max(0.92)).into()
+}
+
+/// A high contrast text or border color: the same hue, much lighter
+pub fn contrast_foreground(color: Color) -> Color {
+    let hsla = Hsla::from(color);
+    Hsla::new(hsla.hue, hsla.saturation, hsla.lightness.max(0.9), 1.0).into()
+}
+
+/// The outline drawn around a selected button: thicker and brighter in high contrast
+pub fn selection_outline(accessibility: &AccessibilityConfig, color: Color) -> Outline {
+    if accessibility.high_contrast {
+        Outline::new(Val::Px(HIGH_CONTRAST_OUTLINE_WIDTH), Val::ZERO, contrast_foreground(color))
+    } else {
+        Outline::new(Val::Px(OUTLINE_WIDTH), Val::ZERO, color)
+    }
+}
+
+/// The shown color for `current`, remembering its base in `slot`
+fn restyle(slot: &mut Option<(Color, Color)>, current: Color, style: impl Fn(Color) -> Color) -> Color {
+    let base = match *slot {
+        Some((base, shown)) if shown == current => base,
+        _ => current,
+    };
+    let shown = style(base);
+    *slot = Some((base, shown));
+    shown
+}
+
+/// Scales every font by `font_scale`: new text when it appears, all text when the scale changes
+pub fn scale_fonts(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut last_scale: Local<Option<f32>>,
+    mut fonts: Query<(Entity, &mut TextFont, Option<&BaseFontSize>)>,
+    added: Query<(), Added<TextFont>>,
+) {
+    let scale = config.accessibility.font_scale;
+    let rescale = *last_scale != Some(scale);
+    *last_scale = Some(scale);
+    for (entity, mut font, base) in fonts.iter_mut() {
+        if !rescale && !added.contains(entity) {
+            continue;
+        }
+        let base = match base {
+            Some(base) => base.0,
+            None => {
+                commands.entity(entity).insert(BaseFontSize(font.font_size));
+                font.font_size
+            }
+        };
+        let size = base * scale;
+        if font.font_size != size {
+            font.font_size = size;
+        }
+    }
+}
+
+/// Puts themed panels into (or out of) high contrast: all of them when the setting changes, and
+/// nodes whose colors changed since
+#[allow(clippy::type_complexity)]
+pub fn apply_contrast(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut last_contrast: Local<Option<bool>>,
+    panels: Query<(), With<ThemedPanel>>,
+    parents: Query<&ChildOf>,
+    mut nodes: Query<
+        (
+            Entity,
+            Option<&mut BackgroundColor>,
+            Option<&mut BorderColor>,
+            Option<&mut TextColor>,
+            Option<&mut ThemedColors>,
+        ),
+        Without<KeepColors>,
+    >,
+    changed: Query<(), Or<(Changed<BackgroundColor>, Changed<BorderColor>, Changed<TextColor>)>>,
+) {
+    let high_contrast = config.accessibility.high_contrast;
+    let restyle_all = *last_contrast != Some(high_contrast);
+    *last_contrast = Some(high_contrast);
+
+    for (entity, background, border, text, themed) in nodes.iter_mut() {
+        // Nodes never shown in high contrast have nothing to undo
+        if (!restyle_all && !changed.contains(entity)) || (!high_contrast && themed.is_none()) {
+            continue;
+        }
+        if !panels.contains(entity) && !parents.iter_ancestors(entity).any(|ancestor| panels.contains(ancestor)) {
+            continue;
+        }
+        match themed {
+            Some(mut colors) => restyle_node(&mut colors, high_contrast, background, border, text),
+            None => {
+                let mut colors = ThemedColors::default();
+                restyle_node(&mut colors, high_contrast, background, border, text);
+                commands.entity(entity).insert(colors);
+            }
+        }
+    }
+}
+
+fn restyle_node(
+    colors: &mut ThemedColors,
+    high_contrast: bool,
+    background: Option<Mut<BackgroundColor>>,
+    border: Option<Mut<BorderColor>>,
+    text: Option<Mut<TextColor>>,
+) {
+    let background_style = |color: Color| if high_contrast { contrast_background(color) } else { color };
+    let foreground_style = |color: Color| if high_contrast { contrast_foreground(color) } else { color };
+    if let Some(mut color) = background {
+        let shown = restyle(&mut colors.background, color.0, background_style);
+        color.set_if_neq(BackgroundColor(shown));
+    }
+    if let Some(mut color) = border {
+        let shown = restyle(&mut colors.border, color.top, foreground_style);
+        color.set_if_neq(BorderColor::all(shown));
+    }
+    if let Some(mut color) = text {
+        let shown = restyle(&mut colors.text, color.0, foreground_style);
+        color.set_if_neq(TextColor(shown));
+    }
+}
+
+/// Console command: `accessibility [motion|contrast on|off] [fonts 0.5-2]`; saved with the settings
+pub fn handle_accessibility_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut config: ResMut<GameConfig>,
+) {
+    const USAGE: &str = "Usage: accessibility [motion|contrast on|off] [fonts 0.5-2]";
+    for command in console_commands.read() {
+        if command.name != "accessibility" {
+            continue;
+        }
+
+        match command.args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => {}
+            ["motion", value @ ("on" | "off")] => config.accessibility.reduced_motion = *value == "on",
+            ["contrast", value @ ("on" | "off")] => config.accessibility.high_contrast = *value == "on",
+            ["fonts", value] => match value.parse::<f32>() {
+                Ok(scale) if (0.5..=2.0).contains(&scale) => config.accessibility.font_scale = scale,
+                _ => {
+                    console.print(USAGE);
+                    continue;
+                }
+            },
+            _ => {
+                console.print(USAGE);
+                continue;
+            }
+        }
+        let accessibility = &config.accessibility;
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        console.print(format!(
+            "Reduced motion {}, high contrast {}, fonts at {:.0}%",
+            on_off(accessibility.reduced_motion),
+            on_off(accessibility.high_contrast),
+            accessibility.font_scale * 100.0
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_colors() {
+        let panel = Color::srgba(0.2, 0.2, 0.3, 0.6);
+        let dark = contrast_background(panel);
+        assert!(Hsla::from(dark).lightness < Hsla::from(panel).lightness);
+        assert!(dark.alpha() > 0.9);
+        assert_eq!(contrast_background(Color::NONE), Color::NONE);
+        assert!(Hsla::from(contrast_foreground(Color::srgb(0.8, 0.8, 0.8))).lightness >= 0.9);
+
+        // The base survives restyling, and a color set by someone else becomes the new base
+        let mut slot = None;
+        let shown = restyle(&mut slot, panel, contrast_background);
+        assert_eq!(restyle(&mut slot, shown, |color| color), panel);
+        let selected = Color::srgb(0.3, 0.3, 0.5);
+        assert_eq!(restyle(&mut slot, selected, |color| color), selected);
+
+        let mut accessibility = AccessibilityConfig::default();
+        assert_eq!(selection_outline(&accessibility, Color::WHITE).width, Val::Px(OUTLINE_WIDTH));
+        accessibility.high_contrast = true;
+        assert_eq!(selection_outline(&accessibility, Color::WHITE).width, Val::Px(HIGH_CONTRAST_OUTLINE_WIDTH));
+    }
+}
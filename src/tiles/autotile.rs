@@ -0,0 +1,108 @@
+//! Grass-to-dirt edge tiles: a dirt tile next to grass is drawn with one of the 47 "blob" edge tiles
+//! picked from its 8-neighbourhood, so painted and generated terrain has no hard seams. Only the
+//! tilemap index changes; chunk data keeps `TILE_DIRT` (see `loader::update_ground_edges`)
+
+use super::constants::{TILE_DIRT, TILE_EMPTY};
+use super::types::TileId;
+
+/// Tileset index of the first edge tile, right after the tiles with ids (`TILE_CLAY` is index 10)
+pub const GRASS_EDGE_FIRST_INDEX: u16 = 11;
+
+/// Edge tiles in the tileset: one per blob mask except the empty one
+pub const GRASS_EDGE_TILES: u16 = BLOB_MASKS.len() as u16 - 1;
+
+/// Neighbour bits, clockwise from north
+pub const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+
+const NORTH: u8 = 1;
+const NORTH_EAST: u8 = 1 << 1;
+const EAST: u8 = 1 << 2;
+const SOUTH_EAST: u8 = 1 << 3;
+const SOUTH: u8 = 1 << 4;
+const SOUTH_WEST: u8 = 1 << 5;
+const WEST: u8 = 1 << 6;
+const NORTH_WEST: u8 = 1 << 7;
+
+/// Drops corner bits whose two sides aren't both set (they look the same as no corner)
+const fn reduce(mask: u8) -> u8 {
+    let mut reduced = mask & (NORTH | EAST | SOUTH | WEST);
+    let corners = [(NORTH_EAST, NORTH | EAST), (SOUTH_EAST, SOUTH | EAST), (SOUTH_WEST, SOUTH | WEST), (NORTH_WEST, NORTH | WEST)];
+    let mut i = 0;
+    while i < corners.len() {
+        let (corner, sides) = corners[i];
+        if mask & corner != 0 && mask & sides == sides {
+            reduced |= corner;
+        }
+        i += 1;
+    }
+    reduced
+}
+
+/// The distinct reduced masks in ascending order; a mask's position is its blob index
+const BLOB_MASKS: [u8; 47] = {
+    let mut masks = [0; 47];
+    let (mut count, mut mask) = (0, 0);
+    while mask < 256 {
+        if reduce(mask as u8) == mask as u8 {
+            masks[count] = mask as u8;
+            count += 1;
+        }
+        mask += 1;
+    }
+    masks
+};
+
+/// Blob index (0-46) of an 8-neighbourhood mask; 0 means no neighbour is set
+pub fn blob_index(mask: u8) -> u16 {
+    BLOB_MASKS.binary_search(&reduce(mask)).map_or(0, |index| index as u16)
+}
+
+/// 8-neighbourhood mask of the neighbours `is_grass` accepts (bit order of `NEIGHBOR_OFFSETS`)
+pub fn grass_mask(mut is_grass: impl FnMut(i32, i32) -> bool) -> u8 {
+    NEIGHBOR_OFFSETS
+        .iter()
+        .enumerate()
+        .filter(|(_, (dx, dy))| is_grass(*dx, *dy))
+        .fold(0, |mask, (bit, _)| mask | 1 << bit)
+}
+
+/// Tileset index to draw a ground tile with, given its grass neighbours
+/// None for `TILE_EMPTY`, like `chunk::tilemap_tile`
+pub fn ground_tileset_index(tile_id: TileId, grass_neighbors: u8) -> Option<u16> {
+    match tile_id {
+        TILE_EMPTY => None,
+        TILE_DIRT if blob_index(grass_neighbors) > 0 => Some(GRASS_EDGE_FIRST_INDEX + blob_index(grass_neighbors) - 1),
+        _ => Some(tile_id - 1),
+    }
+}
+
+/// Whether a tileset with `layers` tiles has the edge tiles
+pub fn has_edge_tiles(layers: u32) -> bool {
+    layers >= (GRASS_EDGE_FIRST_INDEX + GRASS_EDGE_TILES) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::TILE_GRASS;
+
+    #[test]
+    fn test_blob_edges() {
+        assert_eq!(BLOB_MASKS[0], 0);
+        assert_eq!(blob_index(0), 0);
+        assert_eq!(blob_index(u8::MAX), 46);
+        // A lone corner looks like no neighbour at all; with both its sides it counts
+        assert_eq!(blob_index(NORTH_EAST), 0);
+        assert_eq!(blob_index(NORTH | EAST), blob_index(NORTH | EAST | SOUTH_WEST));
+        assert_ne!(blob_index(NORTH | EAST), blob_index(NORTH | EAST | NORTH_EAST));
+
+        // Dirt with grass to the north and north-east only: a northern edge
+        let mask = grass_mask(|dx, dy| dy == 1 && dx >= 0);
+        assert_eq!(mask, NORTH | NORTH_EAST);
+        assert_eq!(ground_tileset_index(TILE_DIRT, mask), Some(GRASS_EDGE_FIRST_INDEX + blob_index(NORTH) - 1));
+        assert_eq!(ground_tileset_index(TILE_DIRT, 0), Some(TILE_DIRT - 1));
+        assert_eq!(ground_tileset_index(TILE_GRASS, mask), Some(TILE_GRASS - 1));
+        assert_eq!(ground_tileset_index(TILE_EMPTY, mask), None);
+        assert!(!has_edge_tiles(2) && has_edge_tiles(57));
+    }
+}
@@ -1,4 +1,5 @@
 use super::{constants::*, types::*};
+use crate::world::entity_serialization::SavedEntity;
 use bevy::prelude::*;
 use bevy::sprite_render::{TileData, TilemapChunkTileData};
 
@@ -27,6 +28,17 @@ impl Chunk {
 #[derive(Component, Debug)]
 pub struct DirtyChunk;
 
+/// A settlement NPC living in a chunk (see `world::structures`): its home tile and how far it wanders
+/// Spawned while the chunk is loaded, so it's saved and restored with the chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkSettler {
+    /// Local tile coordinates (0-31)
+    pub x: u8,
+    pub y: u8,
+    /// Tiles from home it roams
+    pub roam_radius: u8,
+}
+
 /// Chunk data storage (separate from the visual tilemap)
 /// Now stores multiple layers of tiles
 #[derive(Debug, Clone)]
@@ -35,6 +47,18 @@ pub struct ChunkData {
     /// Array of tile layers [LAYER_GROUND, LAYER_DECORATION, LAYER_OVERLAY]
     /// Each layer is a CHUNK_AREA array of tile IDs
     pub layers: Box<[[TileId; CHUNK_AREA]; NUM_LAYERS]>,
+    /// Height of each tile (0-255, row-major like the layers), see `world::elevation`
+    pub elevation: Box<[u8; CHUNK_AREA]>,
+    /// NPCs whose home is in this chunk
+    pub settlers: Vec<ChunkSettler>,
+    /// Creatures and trees that stood in the chunk when it was last saved, spawned again when it
+    /// loads (see `entities::persistence`); stale while the chunk is loaded
+    pub entities: Vec<SavedEntity>,
+    /// `world::generator::GENERATOR_VERSION` of the generator that made the chunk (0 if unknown)
+    pub generator_version: u16,
+    /// Painted by a player since it was generated (or imported, or sent by a server); regenerating
+    /// stale chunks leaves these alone
+    pub edited: bool,
 }
 
 impl ChunkData {
@@ -43,6 +67,11 @@ impl ChunkData {
         Self {
             position,
             layers: Box::new([[tile_id; CHUNK_AREA]; NUM_LAYERS]),
+            elevation: Box::new([DEFAULT_ELEVATION; CHUNK_AREA]),
+            settlers: Vec::new(),
+            entities: Vec::new(),
+            generator_version: 0,
+            edited: false,
         }
     }
 
@@ -52,7 +81,15 @@ impl ChunkData {
         for (layer_idx, &tile_id) in layer_tiles.iter().enumerate() {
             layers[layer_idx] = [tile_id; CHUNK_AREA];
         }
-        Self { position, layers }
+        Self {
+            position,
+            layers,
+            elevation: Box::new([DEFAULT_ELEVATION; CHUNK_AREA]),
+            settlers: Vec::new(),
+            entities: Vec::new(),
+            generator_version: 0,
+            edited: false,
+        }
     }
 
     /// Create an empty chunk (all layers TILE_EMPTY)
@@ -79,23 +116,21 @@ impl ChunkData {
         true
     }
 
+    /// Elevation at local chunk coordinates (0-31, 0-31)
+    pub fn elevation_at(&self, local_x: usize, local_y: usize) -> Option<u8> {
+        if local_x >= CHUNK_SIZE || local_y >= CHUNK_SIZE {
+            return None;
+        }
+        Some(self.elevation[local_y * CHUNK_SIZE + local_x])
+    }
+
     /// Convert a specific layer of ChunkData to Bevy's TilemapChunkTileData
     pub fn layer_to_tilemap_data(&self, layer: usize) -> Vec<Option<TileData>> {
         if layer >= NUM_LAYERS {
             return vec![None; CHUNK_AREA];
         }
 
-        self.layers[layer]
-            .iter()
-            .map(|&tile_id| {
-                if tile_id == TILE_EMPTY {
-                    None
-                } else {
-                    // Subtract 1 because TILE_EMPTY is 0, but tileset indices start at 0
-                    Some(TileData::from_tileset_index((tile_id - 1) as u16))
-                }
-            })
-            .collect()
+        self.layers[layer].iter().map(|&tile_id| tilemap_tile(tile_id)).collect()
     }
 
     /// Convert ChunkData to Bevy's TilemapChunkTileData (legacy - returns ground layer)
@@ -138,6 +173,16 @@ impl ChunkData {
     }
 }
 
+/// How a tile is drawn in a `TilemapChunkTileData` (empty tiles are not drawn)
+pub fn tilemap_tile(tile_id: TileId) -> Option<TileData> {
+    if tile_id == TILE_EMPTY {
+        None
+    } else {
+        // Subtract 1 because TILE_EMPTY is 0, but tileset indices start at 0
+        Some(TileData::from_tileset_index(tile_id - 1))
+    }
+}
+
 /// Helper functions for chunk coordinate conversions
 pub mod coords {
     use super::*;
@@ -50,6 +50,32 @@ pub const TILE_GRASS: u16 = 1;
 /// Dirt tile
 pub const TILE_DIRT: u16 = 2;
 
+/// Water tile (reserved - not yet present in terrain_array.png)
+pub const TILE_WATER: u16 = 3;
+
+/// Sand tile (reserved - not yet present in terrain_array.png)
+pub const TILE_SAND: u16 = 4;
+
+/// Decoration tiles the generator scatters over `LAYER_DECORATION`
+/// (reserved - not yet present in terrain_array.png)
+pub const TILE_FLOWERS: u16 = 5;
+pub const TILE_PEBBLES: u16 = 6;
+pub const TILE_TUFT: u16 = 7;
+
+/// Rock tile: bare high ground, not walkable (reserved - not yet present in terrain_array.png)
+pub const TILE_ROCK: u16 = 8;
+
+/// Trodden path through settlements (reserved - not yet present in terrain_array.png)
+pub const TILE_PATH: u16 = 9;
+
+/// Resource deposits on the ground layer (reserved - not yet present in terrain_array.png):
+/// stone outcrops aren't walkable, clay pits are
+pub const TILE_STONE: u16 = 10;
+pub const TILE_CLAY: u16 = 11;
+
+/// Elevation (0-255) of flat worlds and of chunks saved before elevation was recorded
+pub const DEFAULT_ELEVATION: u8 = 128;
+
 /// Maximum number of tile types (u16 can hold 0-65535)
 pub const MAX_TILE_TYPES: usize = u16::MAX as usize + 1;
 
@@ -1,10 +1,11 @@
+pub mod autotile;
 pub mod chunk;
 pub mod constants;
 pub mod registry;
 pub mod types;
 
 // Re-export commonly used items
-pub use chunk::{Chunk, ChunkData, DirtyChunk};
+pub use chunk::{Chunk, ChunkData, ChunkSettler, DirtyChunk};
 pub use constants::*;
-pub use registry::TileRegistry;
+pub use registry::{TileProperties, TileRegistry};
 pub use types::{ChunkPos, TileId};
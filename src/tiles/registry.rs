@@ -1,25 +1,100 @@
+use super::constants::{
+    TILE_CLAY, TILE_DIRT, TILE_EMPTY, TILE_FLOWERS, TILE_GRASS, TILE_PEBBLES, TILE_PATH, TILE_ROCK, TILE_SAND, TILE_STONE, TILE_TUFT, TILE_WATER,
+};
+use super::chunk::ChunkData;
 use super::types::TileId;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+/// Source name of the tiles the game itself registers
+pub const BUILTIN_SOURCE: &str = "builtin";
+
+/// Properties of one tile type
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileProperties {
+    pub name: String,
+    pub walkable: bool,
+    /// Who registered the tile: `BUILTIN_SOURCE` or the name of a mod pack
+    pub source: String,
+}
 
 /// Tile registry for storing tile properties and metadata
-/// Future: Add tile properties like walkability, durability, etc.
-#[derive(Debug, Clone)]
+/// Starts with the built-in tiles; mod packs add theirs at startup (see `mods`)
+#[derive(Resource, Debug, Clone)]
 pub struct TileRegistry {
-    // Future fields:
-    // pub tiles: HashMap<TileId, TileProperties>,
+    tiles: BTreeMap<TileId, TileProperties>,
 }
 
 impl TileRegistry {
     pub fn new() -> Self {
-        Self {
-            // Initialize registry
+        let mut registry = Self { tiles: BTreeMap::new() };
+        for (id, name, walkable) in [
+            (TILE_EMPTY, "empty", true),
+            (TILE_GRASS, "grass", true),
+            (TILE_DIRT, "dirt", true),
+            (TILE_WATER, "water", false),
+            (TILE_SAND, "sand", true),
+            (TILE_FLOWERS, "flowers", true),
+            (TILE_PEBBLES, "pebbles", true),
+            (TILE_TUFT, "tuft", true),
+            (TILE_ROCK, "rock", false),
+            (TILE_PATH, "path", true),
+            (TILE_STONE, "stone", false),
+            (TILE_CLAY, "clay", true),
+        ] {
+            registry.tiles.insert(
+                id,
+                TileProperties {
+                    name: name.to_string(),
+                    walkable,
+                    source: BUILTIN_SOURCE.to_string(),
+                },
+            );
+        }
+        registry
+    }
+
+    /// Register a tile type; the first registration of an id or name wins
+    /// Returns the already-registered tile on a conflict
+    pub fn register(&mut self, id: TileId, properties: TileProperties) -> Result<(), &TileProperties> {
+        let taken = if self.is_valid_tile(id) { Some(id) } else { self.find_by_name(&properties.name) };
+        if let Some(existing_id) = taken {
+            return Err(&self.tiles[&existing_id]);
         }
+        self.tiles.insert(id, properties);
+        Ok(())
+    }
+
+    pub fn get(&self, tile_id: TileId) -> Option<&TileProperties> {
+        self.tiles.get(&tile_id)
+    }
+
+    /// Id of the tile registered under `name`
+    pub fn find_by_name(&self, name: &str) -> Option<TileId> {
+        self.tiles.iter().find(|(_, tile)| tile.name == name).map(|(id, _)| *id)
     }
 
     /// Check if a tile ID is valid
-    pub fn is_valid_tile(&self, _tile_id: TileId) -> bool {
-        // For now, all tile IDs are valid
-        // Future: Check against registered tiles
-        true
+    pub fn is_valid_tile(&self, tile_id: TileId) -> bool {
+        self.tiles.contains_key(&tile_id)
+    }
+
+    /// First tile id in `chunk` that isn't registered, if any
+    pub fn find_unknown_tile(&self, chunk: &ChunkData) -> Option<TileId> {
+        chunk.layers.iter().flatten().copied().find(|tile| !self.is_valid_tile(*tile))
+    }
+
+    /// Registered tiles in id order
+    pub fn iter(&self) -> impl Iterator<Item = (TileId, &TileProperties)> {
+        self.tiles.iter().map(|(id, tile)| (*id, tile))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
     }
 }
 
@@ -28,12 +103,3 @@ impl Default for TileRegistry {
         Self::new()
     }
 }
-
-// Future: Add TileProperties struct
-// #[derive(Debug, Clone)]
-// pub struct TileProperties {
-//     pub name: String,
-//     pub walkable: bool,
-//     pub transparent: bool,
-//     pub durability: u32,
-// }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 pub type TileId = u16;
 
 /// Chunk position in chunk coordinates (not world/tile coordinates)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChunkPos {
     pub x: i32,
     pub y: i32,
@@ -17,6 +17,7 @@ impl ChunkPos {
     }
 
     /// Convert from world position (in pixels) to chunk position
+    /// Positions beyond the chunk range clamp to its edge (`as` saturates)
     pub fn from_world(world_pos: Vec2, chunk_pixel_size: f32) -> Self {
         Self {
             x: (world_pos.x / chunk_pixel_size).floor() as i32,
@@ -45,25 +46,31 @@ impl ChunkPos {
         let mut chunks = Vec::with_capacity(((radius * 2 + 1) * (radius * 2 + 1)) as usize);
         for dy in -radius..=radius {
             for dx in -radius..=radius {
-                chunks.push(ChunkPos::new(self.x + dx, self.y + dy));
+                chunks.push(ChunkPos::new(self.x.saturating_add(dx), self.y.saturating_add(dy)));
             }
         }
         chunks
     }
 
     /// Manhattan distance between two chunk positions
+    /// Saturates at `i32::MAX` for chunks at opposite ends of the range
     pub fn manhattan_distance(&self, other: &ChunkPos) -> i32 {
-        (self.x - other.x).abs() + (self.y - other.y).abs()
+        axis_distance(self.x, other.x).saturating_add(axis_distance(self.y, other.y))
     }
 
     /// Chebyshev distance (square/max distance) between two chunk positions
     /// This represents the minimum number of moves to reach another chunk
     /// when diagonal movement is allowed (matches square radius behavior)
     pub fn chebyshev_distance(&self, other: &ChunkPos) -> i32 {
-        (self.x - other.x).abs().max((self.y - other.y).abs())
+        axis_distance(self.x, other.x).max(axis_distance(self.y, other.y))
     }
 }
 
+/// Distance between two coordinates on one axis, saturating instead of overflowing
+fn axis_distance(a: i32, b: i32) -> i32 {
+    i32::try_from(a.abs_diff(b)).unwrap_or(i32::MAX)
+}
+
 impl From<(i32, i32)> for ChunkPos {
     fn from((x, y): (i32, i32)) -> Self {
         Self::new(x, y)
@@ -118,6 +125,10 @@ mod tests {
         let a = ChunkPos::new(0, 0);
         let b = ChunkPos::new(3, 4);
         assert_eq!(a.manhattan_distance(&b), 7);
+
+        let (min, max) = (ChunkPos::new(i32::MIN, i32::MIN), ChunkPos::new(i32::MAX, i32::MAX));
+        assert_eq!(min.manhattan_distance(&max), i32::MAX);
+        assert_eq!(min.chebyshev_distance(&max), i32::MAX);
     }
 
     #[test]
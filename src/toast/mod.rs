@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+/// How long a toast stays on screen (seconds)
+const TOAST_DURATION: f32 = 6.0;
+
+/// Maximum number of toasts shown at once (oldest are dropped first)
+const MAX_TOASTS: usize = 5;
+
+/// Plugin for short on-screen notifications
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<Toast>()
+            .add_systems(Startup, setup_toast_stack)
+            .add_systems(Update, (spawn_toasts, expire_toasts.after(spawn_toasts)));
+    }
+}
+
+/// Severity of a toast (picks its color)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToastLevel {
+    fn background(&self) -> Color {
+        match self {
+            ToastLevel::Info => Color::srgba(0.15, 0.25, 0.15, 0.9),
+            ToastLevel::Warning => Color::srgba(0.4, 0.3, 0.05, 0.9),
+            ToastLevel::Error => Color::srgba(0.45, 0.1, 0.1, 0.9),
+        }
+    }
+}
+
+/// Send to show a notification at the top of the screen
+#[derive(Message, Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub level: ToastLevel,
+}
+
+impl Toast {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            level: ToastLevel::Info,
+        }
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            level: ToastLevel::Warning,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            level: ToastLevel::Error,
+        }
+    }
+}
+
+/// Marker component for the column holding active toasts
+#[derive(Component)]
+pub struct ToastStack;
+
+/// Time left before a toast is removed
+#[derive(Component)]
+pub struct ToastTimer(Timer);
+
+/// Spawns the (empty) toast column at the top center of the screen
+fn setup_toast_stack(mut commands: Commands) {
+    commands.spawn((
+        ToastStack,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        ZIndex(900),
+        Pickable::IGNORE,
+    ));
+}
+
+/// Adds a node for each new toast
+fn spawn_toasts(
+    mut commands: Commands,
+    mut toasts: MessageReader<Toast>,
+    stack: Single<Entity, With<ToastStack>>,
+) {
+    for toast in toasts.read() {
+        commands.entity(*stack).with_children(|parent| {
+            parent
+                .spawn((
+                    ToastTimer(Timer::from_seconds(TOAST_DURATION, TimerMode::Once)),
+                    Node {
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        max_width: Val::Px(520.0),
+                        ..default()
+                    },
+                    BackgroundColor(toast.level.background()),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .with_children(|toast_node| {
+                    toast_node.spawn((
+                        Text::new(toast.text.clone()),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+        });
+    }
+}
+
+/// Removes toasts whose timer ran out, and the oldest ones beyond `MAX_TOASTS`
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut ToastTimer)>,
+) {
+    let mut active = Vec::new();
+    for (entity, mut timer) in toasts.iter_mut() {
+        timer.0.tick(time.delta());
+        if timer.0.is_finished() {
+            commands.entity(entity).despawn();
+        } else {
+            active.push((entity, timer.0.remaining_secs()));
+        }
+    }
+
+    if active.len() > MAX_TOASTS {
+        active.sort_by(|a, b| a.1.total_cmp(&b.1));
+        for (entity, _) in &active[..active.len() - MAX_TOASTS] {
+            commands.entity(*entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use super::manager::WorldManager;
+use super::save::{SaveWorldJob, SaveWorldRequest};
+use super::worlds::world_dir;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::locale::Locale;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Magic number for world archives ("WARC" in ASCII)
+const MAGIC_NUMBER: [u8; 4] = [b'W', b'A', b'R', b'C'];
+
+/// Current world archive format version
+const VERSION: u16 = 1;
+
+/// Save subdirectories left out of exports (local history and debug output, not part of the world)
+const EXCLUDED_DIRS: [&str; 3] = ["backups", "quarantine", "dumps"];
+
+/// Error type for world export/import
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Not a world archive")]
+    InvalidMagicNumber,
+    #[error("Invalid archive version: {0}")]
+    InvalidVersion(u16),
+    /// An archive entry would be written outside the destination directory
+    #[error("Invalid path in archive: {0}")]
+    InvalidPath(String),
+    /// Import target already exists
+    #[error("{0:?} already exists")]
+    DestinationExists(PathBuf),
+}
+
+/// Relative paths of every file in the save directory that belongs in an export
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), io::Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if path.is_dir() {
+            if dir == root && EXCLUDED_DIRS.iter().any(|excluded| relative == Path::new(excluded)) {
+                continue;
+            }
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Archive entry names always use '/' so archives move between platforms
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Turn an entry name back into a relative path, rejecting anything that escapes the destination
+fn entry_path(name: &str) -> Result<PathBuf, ArchiveError> {
+    let path = PathBuf::from(name);
+    let is_safe = !name.is_empty()
+        && path.components().all(|component| matches!(component, Component::Normal(_)));
+    if !is_safe {
+        return Err(ArchiveError::InvalidPath(name.to_string()));
+    }
+    Ok(path)
+}
+
+/// Write a whole world directory (chunks, entities, metadata, map cache) into one gzip-compressed archive
+/// Returns the number of files written
+pub fn export_world(save_dir: &Path, archive_path: &Path) -> Result<usize, ArchiveError> {
+    let mut files = Vec::new();
+    collect_files(save_dir, save_dir, &mut files)?;
+    files.sort();
+
+    if let Some(parent) = archive_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut encoder = GzEncoder::new(File::create(archive_path)?, Compression::default());
+
+    encoder.write_all(&MAGIC_NUMBER)?;
+    encoder.write_all(&VERSION.to_le_bytes())?;
+    encoder.write_all(&(files.len() as u32).to_le_bytes())?;
+
+    for relative in &files {
+        let name = entry_name(relative);
+        let data = fs::read(save_dir.join(relative))?;
+        encoder.write_all(&(name.len() as u16).to_le_bytes())?;
+        encoder.write_all(name.as_bytes())?;
+        encoder.write_all(&(data.len() as u64).to_le_bytes())?;
+        encoder.write_all(&data)?;
+    }
+
+    encoder.finish()?.sync_all()?;
+    Ok(files.len())
+}
+
+/// Extract a world archive into `destination` (which must not exist yet)
+/// Returns the number of files extracted
+pub fn import_world(archive_path: &Path, destination: &Path) -> Result<usize, ArchiveError> {
+    if destination.exists() {
+        return Err(ArchiveError::DestinationExists(destination.to_path_buf()));
+    }
+
+    let mut decoder = GzDecoder::new(File::open(archive_path)?);
+
+    let mut magic = [0u8; 4];
+    decoder.read_exact(&mut magic)?;
+    if magic != MAGIC_NUMBER {
+        return Err(ArchiveError::InvalidMagicNumber);
+    }
+
+    let mut version_bytes = [0u8; 2];
+    decoder.read_exact(&mut version_bytes)?;
+    let version = u16::from_le_bytes(version_bytes);
+    if version != VERSION {
+        return Err(ArchiveError::InvalidVersion(version));
+    }
+
+    let mut count_bytes = [0u8; 4];
+    decoder.read_exact(&mut count_bytes)?;
+    let count = u32::from_le_bytes(count_bytes) as usize;
+
+    // Extract into a temporary sibling so a bad archive never leaves a half-imported world
+    // (the suffix is appended: `with_extension` would replace whatever follows a dot in the name)
+    let mut staging_name = destination.file_name().unwrap_or_default().to_os_string();
+    staging_name.push(".importing");
+    let staging = destination.with_file_name(staging_name);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = (|| {
+        for _ in 0..count {
+            let mut name_len = [0u8; 2];
+            decoder.read_exact(&mut name_len)?;
+            let mut name = vec![0u8; u16::from_le_bytes(name_len) as usize];
+            decoder.read_exact(&mut name)?;
+            let name = String::from_utf8_lossy(&name).into_owned();
+            let relative = entry_path(&name)?;
+
+            let mut data_len = [0u8; 8];
+            decoder.read_exact(&mut data_len)?;
+            let target = staging.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(target)?;
+            let data_len = u64::from_le_bytes(data_len);
+            // `take` stops quietly at the end of the stream, so a truncated archive is caught here
+            if io::copy(&mut (&mut decoder).take(data_len), &mut file)? != data_len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("entry {} is truncated", name)).into());
+            }
+        }
+        fs::create_dir_all(&staging)?;
+        fs::rename(&staging, destination)?;
+        Ok(count)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+/// Archive path waiting for the current "Save World" pass to finish
+#[derive(Resource, Default)]
+pub struct PendingExport(pub Option<PathBuf>);
+
+/// Console commands: `export <file>` saves the world and writes it to an archive,
+/// `import <file> [name]` extracts an archive into `saves/<name>`
+pub fn handle_archive_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut pending_export: ResMut<PendingExport>,
+    mut save_requests: MessageWriter<SaveWorldRequest>,
+    world: Res<WorldManager>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "export" => {
+                let Some(file) = command.args.first() else {
+                    console.print("Usage: export <file>");
+                    continue;
+                };
+                // Flush everything in memory first; the export runs when the save finishes
+                pending_export.0 = Some(PathBuf::from(file));
+                save_requests.write(SaveWorldRequest);
+                console.print(format!("Saving world before exporting to {}...", file));
+            }
+            "import" => {
+                let Some(file) = command.args.first() else {
+                    console.print("Usage: import <file> [name]");
+                    continue;
+                };
+                let archive_path = PathBuf::from(file);
+                let name = command.args.get(1).cloned().unwrap_or_else(|| {
+                    archive_path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| "imported".to_string())
+                });
+                let saves_root = world.save_directory.parent().unwrap_or(Path::new("saves"));
+                let destination = match world_dir(saves_root, &name) {
+                    Ok(destination) => destination,
+                    Err(e) => {
+                        console.print(format!("Import failed: {}", e));
+                        continue;
+                    }
+                };
+
+                match import_world(&archive_path, &destination) {
+                    Ok(count) => console.print(format!("Imported {} files into {:?}", count, destination)),
+                    Err(e) => console.print(format!("Import failed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Writes the pending export once the save it requested has finished
+pub fn finish_pending_export(
+    mut pending_export: ResMut<PendingExport>,
+    job: Res<SaveWorldJob>,
+    world: Res<WorldManager>,
+    mut console: ResMut<ConsoleState>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    if pending_export.0.is_none() || !job.just_finished() {
+        return;
+    }
+    let Some(archive_path) = pending_export.0.take() else {
+        return;
+    };
+
+    match export_world(&world.save_directory, &archive_path) {
+        Ok(count) => {
+            info!("Exported {} files to {:?}", count, archive_path);
+            console.print(format!("Exported {} files to {:?}", count, archive_path));
+            let path = archive_path.display().to_string();
+            toasts.write(Toast::info(locale.format("toast-world-exported", &[("path", path.into())])));
+        }
+        Err(e) => {
+            error!("Failed to export world to {:?}: {}", archive_path, e);
+            console.print(format!("Export failed: {}", e));
+            toasts.write(Toast::error(locale.format("toast-world-export-failed", &[("error", e.to_string().into())])));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_export_and_import_world() {
+        let dir = temp_dir("worldseed_test_archive");
+        let save_dir = dir.join("world");
+        fs::create_dir_all(save_dir.join("chunks")).unwrap();
+        fs::create_dir_all(save_dir.join("backups")).unwrap();
+        fs::write(save_dir.join("world.meta"), "(waypoints: [])").unwrap();
+        fs::write(save_dir.join("chunks").join("chunk_0_-1.bin"), [1u8, 2, 3, 4]).unwrap();
+        fs::write(save_dir.join("backups").join("chunk_0_-1.bin.1.bak"), [9u8]).unwrap();
+
+        let archive_path = dir.join("export.wsa");
+        assert_eq!(export_world(&save_dir, &archive_path).unwrap(), 2);
+
+        let imported = dir.join("imported");
+        assert_eq!(import_world(&archive_path, &imported).unwrap(), 2);
+        assert_eq!(fs::read(imported.join("chunks").join("chunk_0_-1.bin")).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(fs::read_to_string(imported.join("world.meta")).unwrap(), "(waypoints: [])");
+        assert!(!imported.join("backups").exists());
+
+        // Never imports over an existing world
+        assert!(matches!(
+            import_world(&archive_path, &imported),
+            Err(ArchiveError::DestinationExists(_))
+        ));
+
+        // Names with dots keep them; the staging directory is `<name>.importing`
+        let dotted = dir.join("world.v2");
+        assert_eq!(import_world(&archive_path, &dotted).unwrap(), 2);
+        assert!(dotted.join("world.meta").exists());
+        assert!(!dir.join("world.importing").exists() && !dir.join("world.v2.importing").exists());
+
+        // A truncated archive fails and leaves nothing behind
+        let bytes = {
+            let mut decoded = Vec::new();
+            GzDecoder::new(File::open(&archive_path).unwrap()).read_to_end(&mut decoded).unwrap();
+            decoded
+        };
+        let truncated_path = dir.join("truncated.wsa");
+        let mut encoder = GzEncoder::new(File::create(&truncated_path).unwrap(), Compression::default());
+        encoder.write_all(&bytes[..bytes.len() - 2]).unwrap();
+        encoder.finish().unwrap();
+        let truncated = dir.join("truncated");
+        assert!(import_world(&truncated_path, &truncated).is_err());
+        assert!(!truncated.exists() && !dir.join("truncated.importing").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_entry_paths_stay_inside_destination() {
+        assert!(entry_path("chunks/chunk_1_2.bin").is_ok());
+        assert!(entry_path("../outside.bin").is_err());
+        assert!(entry_path("/etc/passwd").is_err());
+        assert!(entry_path("chunks/../../outside.bin").is_err());
+        assert!(entry_path("").is_err());
+    }
+}
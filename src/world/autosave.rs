@@ -0,0 +1,335 @@
+use super::manager::WorldManager;
+use super::model;
+use crate::config::GameConfig;
+use crate::entities::{entities_by_chunk, stash_all_entities, take_chunk_entities, ChunkEntity, ChunkEntityFilter};
+use crate::error::Failures;
+use crate::locale::{Locale, LocalizedText};
+use crate::toast::Toast;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use std::time::Duration;
+
+/// Consecutive failed autosaves before the blocking warning dialog opens
+const AUTOSAVE_FAILURES_BEFORE_DIALOG: u32 = 3;
+
+/// How long the "Autosaving" indicator stays on screen (seconds)
+const AUTOSAVE_INDICATOR_SECONDS: f32 = 1.5;
+
+/// Autosave timer and failure tracking
+#[derive(Resource, Default)]
+pub struct AutosaveState {
+    /// Seconds since the last autosave
+    pub elapsed: f32,
+    /// Autosaves in a row that failed to write at least one chunk
+    pub consecutive_failures: u32,
+    /// Error from the most recent failure
+    pub last_error: Option<String>,
+    /// Whether the blocking failure dialog is showing
+    pub dialog_open: bool,
+    /// Seconds the indicator has been showing (None when hidden)
+    indicator_for: Option<f32>,
+    /// Set by the dialog's "Retry now" button
+    retry_requested: bool,
+}
+
+/// Run condition for world input (placing entities, painting) that the failure dialog blocks
+pub fn autosave_dialog_closed(state: Res<AutosaveState>) -> bool {
+    !state.dialog_open
+}
+
+/// Marker component for the autosave indicator text
+#[derive(Component)]
+pub struct AutosaveIndicator;
+
+/// Marker component for the blocking autosave failure dialog
+#[derive(Component)]
+pub struct AutosaveFailureDialog;
+
+/// Marker component for the failure dialog's message text
+#[derive(Component)]
+pub struct AutosaveFailureText;
+
+/// Buttons in the failure dialog
+#[derive(Component, Clone, Copy)]
+pub enum AutosaveDialogButton {
+    Retry,
+    Dismiss,
+}
+
+/// Saves dirty chunks every `GameConfig.gameplay.autosave_interval` seconds, skipping ones modified
+/// in the last `autosave_debounce` seconds (they are picked up by a later autosave, or on unload/exit)
+/// Failures are reported (see `Failures`); repeated failures open the blocking dialog
+#[allow(clippy::too_many_arguments)]
+pub fn run_autosave(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut state: ResMut<AutosaveState>,
+    mut world: ResMut<WorldManager>,
+    entities: Query<ChunkEntity, ChunkEntityFilter>,
+    mut toasts: MessageWriter<Toast>,
+    mut failures: Failures,
+    locale: Res<Locale>,
+) {
+    let interval = config.gameplay.autosave_interval;
+    if interval <= 0.0 && !state.retry_requested {
+        return;
+    }
+
+    state.elapsed += time.delta_secs();
+    if state.elapsed < interval && !state.retry_requested {
+        return;
+    }
+    state.elapsed = 0.0;
+    // A retry writes everything, like the exit flush
+    let quiet = if state.retry_requested {
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f32(config.gameplay.autosave_debounce)
+    };
+    state.retry_requested = false;
+
+    let dirty_chunks = world.get_settled_dirty_chunks(crate::platform::unix_time(), quiet);
+    if dirty_chunks.is_empty() {
+        return;
+    }
+
+    // Loaded chunks being written get the creatures standing in them now, not the ones from when
+    // they loaded (clean chunks aren't dirtied for this; unloading and Save World catch them)
+    let mut by_chunk = entities_by_chunk(&entities);
+    for &chunk_pos in &dirty_chunks {
+        if world.is_loaded(&chunk_pos) {
+            let (_, records) = take_chunk_entities(&mut by_chunk, chunk_pos);
+            model::stash_entities(&mut world, chunk_pos, records);
+        }
+    }
+
+    let mut saved = 0;
+    let mut last_error = None;
+    for chunk_pos in dirty_chunks {
+        match world.save_cached_chunk(&chunk_pos) {
+            Ok(true) => {
+                debug!("Autosaved chunk {:?}", chunk_pos);
+                world.clear_dirty(&chunk_pos);
+                saved += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                last_error = Some(e.to_string());
+                failures.report(format!("Autosave failed for chunk ({}, {})", chunk_pos.x, chunk_pos.y), e);
+            }
+        }
+    }
+
+    state.indicator_for = Some(0.0);
+
+    match last_error {
+        None => {
+            debug!("Autosaved {} chunks", saved);
+            if state.consecutive_failures > 0 {
+                toasts.write(Toast::info(locale.text("toast-autosave-recovered")));
+            }
+            state.consecutive_failures = 0;
+            state.last_error = None;
+            state.dialog_open = false;
+        }
+        Some(error) => {
+            state.consecutive_failures += 1;
+            state.last_error = Some(error);
+            if state.consecutive_failures >= AUTOSAVE_FAILURES_BEFORE_DIALOG {
+                state.dialog_open = true;
+            }
+        }
+    }
+}
+
+/// Writes every dirty chunk, settled or not, when the window closes, with the creatures and trees
+/// standing in them (`stash_all_entities`)
+pub fn save_dirty_chunks_on_exit(
+    mut commands: Commands,
+    mut close_requests: MessageReader<WindowCloseRequested>,
+    mut world: ResMut<WorldManager>,
+    entities: Query<ChunkEntity, ChunkEntityFilter>,
+    mut failures: Failures,
+) {
+    if close_requests.read().count() == 0 {
+        return;
+    }
+    stash_all_entities(&mut commands, &mut world, &entities, &mut failures);
+    // Background saves that fail leave their chunks dirty, to be saved below
+    world.finish_saves();
+    for chunk_pos in world.get_dirty_chunks() {
+        match world.save_cached_chunk(&chunk_pos) {
+            Ok(true) => world.clear_dirty(&chunk_pos),
+            Ok(false) => {}
+            Err(e) => failures.report(format!("Failed to save chunk ({}, {}) on exit", chunk_pos.x, chunk_pos.y), e),
+        }
+    }
+}
+
+/// Spawns the autosave indicator (bottom-right, above the save indicator) and the hidden failure dialog
+pub fn setup_autosave_ui(mut commands: Commands) {
+    commands.spawn((
+        AutosaveIndicator,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(32.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+
+    commands
+        .spawn((
+            AutosaveFailureDialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ZIndex(1100),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(460.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(14.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.3, 0.08, 0.08)),
+                    BorderColor::all(Color::srgb(0.8, 0.3, 0.3)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|dialog| {
+                    dialog.spawn((
+                        LocalizedText("autosave-dialog-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    dialog.spawn((
+                        AutosaveFailureText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.9, 0.85, 0.85)),
+                    ));
+                    dialog
+                        .spawn(Node {
+                            column_gap: Val::Px(10.0),
+                            justify_content: JustifyContent::End,
+                            ..default()
+                        })
+                        .with_children(|buttons| {
+                            spawn_dialog_button(buttons, "autosave-retry", AutosaveDialogButton::Retry);
+                            spawn_dialog_button(buttons, "autosave-dismiss", AutosaveDialogButton::Dismiss);
+                        });
+                });
+        });
+}
+
+fn spawn_dialog_button(parent: &mut ChildSpawnerCommands, label: &'static str, action: AutosaveDialogButton) {
+    parent
+        .spawn((
+            Button,
+            action,
+            Node {
+                padding: UiRect::axes(Val::Px(14.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.45, 0.15, 0.15)),
+            BorderRadius::all(Val::Px(4.0)),
+        ))
+        .observe(autosave_dialog_click)
+        .with_children(|button| {
+            button.spawn((
+                LocalizedText(label),
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn autosave_dialog_click(
+    trigger: On<Pointer<Click>>,
+    buttons: Query<&AutosaveDialogButton>,
+    mut state: ResMut<AutosaveState>,
+) {
+    let Ok(action) = buttons.get(trigger.entity) else {
+        return;
+    };
+
+    state.dialog_open = false;
+    match action {
+        AutosaveDialogButton::Retry => state.retry_requested = true,
+        // Ask again after another run of failures
+        AutosaveDialogButton::Dismiss => state.consecutive_failures = 0,
+    }
+}
+
+/// Animates the indicator after an autosave and syncs the failure dialog
+pub fn update_autosave_ui(
+    time: Res<Time>,
+    mut state: ResMut<AutosaveState>,
+    indicator: Single<(&mut Text, &mut Visibility), With<AutosaveIndicator>>,
+    mut dialog: Single<&mut Visibility, (With<AutosaveFailureDialog>, Without<AutosaveIndicator>)>,
+    mut dialog_text: Single<&mut Text, (With<AutosaveFailureText>, Without<AutosaveIndicator>)>,
+    locale: Res<Locale>,
+) {
+    let (mut text, mut visibility) = indicator.into_inner();
+
+    if let Some(shown_for) = state.indicator_for {
+        let shown_for = shown_for + time.delta_secs();
+        if shown_for >= AUTOSAVE_INDICATOR_SECONDS {
+            *visibility = Visibility::Hidden;
+            state.indicator_for = None;
+        } else {
+            let dots = ".".repeat(1 + (shown_for * 4.0) as usize % 3);
+            text.0 = if state.consecutive_failures == 0 {
+                locale.format("autosave-running", &[("dots", dots.into())])
+            } else {
+                locale.text("autosave-failed")
+            };
+            *visibility = Visibility::Visible;
+            state.bypass_change_detection().indicator_for = Some(shown_for);
+        }
+    }
+
+    if !state.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    **dialog = if state.dialog_open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    let errors = state.last_error.clone().unwrap_or_else(|| locale.text("autosave-unknown-error"));
+    dialog_text.0 = locale.format("autosave-dialog-body", &[
+        ("count", state.consecutive_failures.into()),
+        ("errors", errors.into()),
+    ]);
+}
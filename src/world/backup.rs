@@ -0,0 +1,357 @@
+use super::loader;
+use super::manager::WorldManager;
+use super::serialization::{self, SerializationError};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::{ChunkEntity, ChunkEntityFilter};
+use crate::platform::unix_time;
+use crate::tiles::{ChunkData, ChunkPos};
+use bevy::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Default number of old versions kept per chunk file
+pub const DEFAULT_BACKUP_RETENTION: usize = 5;
+
+/// Extension of backup files (`<chunk file>.<unix millis>.bak`)
+const BACKUP_EXTENSION: &str = "bak";
+
+/// A backup of a chunk file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkBackup {
+    /// Unix timestamp in milliseconds when the backup was taken
+    pub timestamp: u64,
+    pub path: PathBuf,
+}
+
+fn now_millis() -> u64 {
+    unix_time().as_millis() as u64
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// All backups of a chunk file, newest first
+pub fn list_backups(chunk_path: &Path, backup_dir: &Path) -> Vec<ChunkBackup> {
+    let prefix = format!("{}.", file_name(chunk_path));
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+
+    let Ok(entries) = fs::read_dir(backup_dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<ChunkBackup> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = file_name(&path);
+            let timestamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?.parse().ok()?;
+            Some(ChunkBackup { timestamp, path })
+        })
+        .collect();
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.timestamp));
+    backups
+}
+
+/// Copy the current chunk file into the backup directory and delete all but the
+/// newest `retention` backups. Returns the new backup path (None if there was no file yet)
+pub fn backup_chunk_file(
+    chunk_path: &Path,
+    backup_dir: &Path,
+    retention: usize,
+) -> Result<Option<PathBuf>, io::Error> {
+    if retention == 0 || !chunk_path.exists() {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(backup_dir)?;
+
+    // Never overwrite an existing backup (two saves within the same millisecond)
+    let mut timestamp = now_millis();
+    if let Some(newest) = list_backups(chunk_path, backup_dir).first() {
+        timestamp = timestamp.max(newest.timestamp + 1);
+    }
+
+    let backup_path = backup_dir.join(format!(
+        "{}.{}.{}",
+        file_name(chunk_path),
+        timestamp,
+        BACKUP_EXTENSION
+    ));
+    fs::copy(chunk_path, &backup_path)?;
+
+    for old in list_backups(chunk_path, backup_dir).into_iter().skip(retention) {
+        fs::remove_file(old.path)?;
+    }
+
+    Ok(Some(backup_path))
+}
+
+/// Save a chunk, first rotating the previous version of its file into the backups
+pub fn save_chunk_with_backup(
+    chunk: &ChunkData,
+    chunk_path: &Path,
+    backup_dir: &Path,
+    retention: usize,
+) -> Result<(), SerializationError> {
+    backup_chunk_file(chunk_path, backup_dir, retention)?;
+    serialization::save_chunk(chunk, chunk_path)
+}
+
+/// Replace a chunk file with one of its backups (0 = newest)
+/// The current file is backed up first so the restore itself can be undone
+pub fn restore_chunk_backup(
+    chunk_path: &Path,
+    backup_dir: &Path,
+    index: usize,
+    retention: usize,
+) -> Result<ChunkBackup, io::Error> {
+    let backup = list_backups(chunk_path, backup_dir)
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no backup #{}", index)))?;
+
+    // Keep one extra slot so the backup being restored survives the rotation
+    backup_chunk_file(chunk_path, backup_dir, retention.max(index + 2))?;
+    fs::copy(&backup.path, chunk_path)?;
+    Ok(backup)
+}
+
+/// Move a damaged file out of the way so it is never overwritten by a regenerated chunk
+pub fn quarantine_file(path: &Path, quarantine_dir: &Path) -> Result<PathBuf, io::Error> {
+    fs::create_dir_all(quarantine_dir)?;
+    let target = quarantine_dir.join(format!("{}.{}.corrupt", file_name(path), now_millis()));
+    if fs::rename(path, &target).is_err() {
+        // Rename fails across filesystems
+        fs::copy(path, &target)?;
+        fs::remove_file(path)?;
+    }
+    Ok(target)
+}
+
+/// Result of recovering a chunk whose file failed to load
+#[derive(Debug)]
+pub struct ChunkRecovery {
+    /// Where the damaged file was moved (None if it couldn't be moved)
+    pub quarantined: Option<PathBuf>,
+    /// Chunk data and the backup it came from (None if no backup was usable)
+    pub restored: Option<(ChunkData, ChunkBackup)>,
+}
+
+/// Quarantine a corrupted chunk file and restore the newest backup that loads
+/// The restored backup is copied back to `chunk_path`
+pub fn recover_corrupted_chunk(
+    chunk_pos: ChunkPos,
+    chunk_path: &Path,
+    backup_dir: &Path,
+    quarantine_dir: &Path,
+) -> ChunkRecovery {
+    let quarantined = match quarantine_file(chunk_path, quarantine_dir) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            error!("Failed to quarantine {:?}: {}", chunk_path, e);
+            None
+        }
+    };
+
+    let restored = list_backups(chunk_path, backup_dir)
+        .into_iter()
+        .find_map(|backup| match serialization::load_chunk(&backup.path) {
+            Ok(data) if data.position == chunk_pos => Some((data, backup)),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Backup {:?} is also unreadable: {}", backup.path, e);
+                None
+            }
+        });
+
+    if let Some((_, backup)) = &restored {
+        if let Err(e) = fs::copy(&backup.path, chunk_path) {
+            warn!("Failed to copy backup {:?} over {:?}: {}", backup.path, chunk_path, e);
+        }
+    }
+
+    ChunkRecovery {
+        quarantined,
+        restored,
+    }
+}
+
+/// Human-readable age of a backup
+pub fn format_age(timestamp: u64) -> String {
+    let seconds = now_millis().saturating_sub(timestamp) / 1000;
+    match seconds {
+        0..60 => format!("{}s ago", seconds),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Parse `<x> <y>` chunk coordinates from console arguments
+fn parse_chunk_args(args: &[String]) -> Option<ChunkPos> {
+    let x = args.first()?.parse().ok()?;
+    let y = args.get(1)?.parse().ok()?;
+    Some(ChunkPos::new(x, y))
+}
+
+/// Console commands: `backups <x> <y>` lists backups of a chunk,
+/// `restore <x> <y> [n]` rolls a chunk back to backup n (default newest)
+pub fn handle_backup_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut world: ResMut<WorldManager>,
+    chunk_entities: Query<ChunkEntity, ChunkEntityFilter>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "backups" => {
+                let Some(chunk_pos) = parse_chunk_args(&command.args) else {
+                    console.print("Usage: backups <x> <y>");
+                    continue;
+                };
+                let backups = list_backups(&world.get_chunk_path(&chunk_pos), &world.get_backup_dir());
+                if backups.is_empty() {
+                    console.print(format!("No backups for chunk ({}, {})", chunk_pos.x, chunk_pos.y));
+                }
+                for (index, backup) in backups.iter().enumerate() {
+                    console.print(format!("  {}: {}", index, format_age(backup.timestamp)));
+                }
+            }
+            "restore" => {
+                let Some(chunk_pos) = parse_chunk_args(&command.args) else {
+                    console.print("Usage: restore <x> <y> [n]");
+                    continue;
+                };
+                let index = command.args.get(2).and_then(|n| n.parse().ok()).unwrap_or(0);
+                let chunk_path = world.get_chunk_path(&chunk_pos);
+                // The file being written now would otherwise land on top of the restored one
+                world.wait_for_save(&chunk_pos);
+
+                match restore_chunk_backup(&chunk_path, &world.get_backup_dir(), index, world.backup_retention) {
+                    Ok(backup) => {
+                        // Drop the in-memory copy so the loader reads the restored file
+                        loader::reload_chunk(&mut commands, &mut world, &chunk_entities, chunk_pos);
+
+                        info!("Restored chunk {:?} from {:?}", chunk_pos, backup.path);
+                        console.print(format!(
+                            "Restored chunk ({}, {}) from backup {} ({})",
+                            chunk_pos.x,
+                            chunk_pos.y,
+                            index,
+                            format_age(backup.timestamp)
+                        ));
+                    }
+                    Err(e) => console.print(format!("Restore failed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{LAYER_GROUND, TILE_DIRT, TILE_GRASS};
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_backups_rotate_to_retention() {
+        let dir = temp_dir("worldseed_test_backup_rotation");
+        let chunk_path = dir.join("chunk_0_0.bin");
+        let backup_dir = dir.join("backups");
+        let chunk = ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS);
+
+        // The first save has nothing to back up
+        save_chunk_with_backup(&chunk, &chunk_path, &backup_dir, 3).unwrap();
+        assert!(list_backups(&chunk_path, &backup_dir).is_empty());
+
+        for _ in 0..5 {
+            save_chunk_with_backup(&chunk, &chunk_path, &backup_dir, 3).unwrap();
+        }
+        let backups = list_backups(&chunk_path, &backup_dir);
+        assert_eq!(backups.len(), 3);
+        assert!(backups[0].timestamp > backups[1].timestamp);
+
+        // Backups of other chunks are not counted
+        assert!(list_backups(&dir.join("chunk_0_1.bin"), &backup_dir).is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_restore_backup() {
+        let dir = temp_dir("worldseed_test_backup_restore");
+        let chunk_path = dir.join("chunk_2_-1.bin");
+        let backup_dir = dir.join("backups");
+
+        let original = ChunkData::filled(ChunkPos::new(2, -1), TILE_GRASS);
+        let edited = ChunkData::filled(ChunkPos::new(2, -1), TILE_DIRT);
+        save_chunk_with_backup(&original, &chunk_path, &backup_dir, 3).unwrap();
+        save_chunk_with_backup(&edited, &chunk_path, &backup_dir, 3).unwrap();
+
+        restore_chunk_backup(&chunk_path, &backup_dir, 0, 3).unwrap();
+        let loaded = serialization::load_chunk(&chunk_path).unwrap();
+        assert_eq!(loaded.layers[LAYER_GROUND][0], TILE_GRASS);
+
+        // The edited version was backed up by the restore and can be restored again
+        restore_chunk_backup(&chunk_path, &backup_dir, 0, 3).unwrap();
+        let loaded = serialization::load_chunk(&chunk_path).unwrap();
+        assert_eq!(loaded.layers[LAYER_GROUND][0], TILE_DIRT);
+
+        assert!(restore_chunk_backup(&chunk_path, &backup_dir, 10, 3).is_err());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_recover_corrupted_chunk() {
+        let dir = temp_dir("worldseed_test_backup_recover");
+        let chunk_pos = ChunkPos::new(1, 1);
+        let chunk_path = dir.join("chunk_1_1.bin");
+        let backup_dir = dir.join("backups");
+        let quarantine_dir = dir.join("quarantine");
+
+        let chunk = ChunkData::filled(chunk_pos, TILE_DIRT);
+        save_chunk_with_backup(&chunk, &chunk_path, &backup_dir, 3).unwrap();
+        save_chunk_with_backup(&chunk, &chunk_path, &backup_dir, 3).unwrap();
+
+        // Flip the first byte of the tile block's body so its checksum no longer matches: the body
+        // follows the 19-byte v8 header (magic, version, position, layer count, generator version
+        // and flags) and the block's encoding byte and length
+        let mut bytes = fs::read(&chunk_path).unwrap();
+        let index = 19 + 1 + 4;
+        bytes[index] ^= 0xFF;
+        fs::write(&chunk_path, &bytes).unwrap();
+        let error = serialization::load_chunk(&chunk_path).unwrap_err();
+        assert!(matches!(error, serialization::SerializationError::InvalidChecksum));
+        assert!(error.is_corruption());
+
+        let recovery = recover_corrupted_chunk(chunk_pos, &chunk_path, &backup_dir, &quarantine_dir);
+        assert!(recovery.quarantined.unwrap().exists());
+        let (restored, _) = recovery.restored.expect("backup should be restored");
+        assert_eq!(restored.layers[LAYER_GROUND][0], TILE_DIRT);
+        assert!(serialization::load_chunk(&chunk_path).is_ok());
+
+        // Without backups the file is still quarantined, and nothing is restored
+        fs::write(&chunk_path, &bytes).unwrap();
+        fs::remove_dir_all(&backup_dir).unwrap();
+        let recovery = recover_corrupted_chunk(chunk_pos, &chunk_path, &backup_dir, &quarantine_dir);
+        assert!(recovery.restored.is_none());
+        assert!(!chunk_path.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
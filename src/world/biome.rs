@@ -0,0 +1,197 @@
+//! Biomes for `GroundPattern::Biomes`: the world is split into irregular regions of forest, plains,
+//! rocky ground and marsh. Region centers sit on a jittered grid seeded by the world seed, each takes
+//! the biome its climate supports (see `climate`) and each chunk takes the biome of the nearest one,
+//! so biomes change at chunk borders. Within a biome, smooth patch noise picks the ground tiles
+
+use crate::tiles::{
+    ChunkPos, TileId, CHUNK_SIZE_I32, TILE_DIRT, TILE_EMPTY, TILE_FLOWERS, TILE_GRASS, TILE_PEBBLES, TILE_SAND, TILE_TUFT, TILE_WATER,
+};
+use super::climate::climate_at;
+use bevy::prelude::*;
+use std::fmt;
+
+/// Chunks between biome region centers
+pub const BIOME_REGION_CHUNKS: i32 = 6;
+
+/// Size of ground patches within a biome (tiles; the default of `WorldgenParams.patch_size`)
+pub const PATCH_SIZE: f32 = 5.0;
+
+/// Salt of the per-tile decoration rolls; the clumping noise uses the seed shifted by it
+const DECORATION_SALT: u64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Forest,
+    Plains,
+    Rocky,
+    Marsh,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 4] = [Self::Forest, Self::Plains, Self::Rocky, Self::Marsh];
+
+    /// Ground tile for a patch noise value in [0, 1)
+    pub fn ground_tile(&self, patch: f32) -> TileId {
+        match self {
+            Biome::Forest if patch < 0.3 => TILE_DIRT,
+            Biome::Forest => TILE_GRASS,
+            Biome::Plains if patch < 0.15 => TILE_DIRT,
+            Biome::Plains => TILE_GRASS,
+            Biome::Rocky if patch < 0.55 => TILE_SAND,
+            Biome::Rocky if patch < 0.8 => TILE_DIRT,
+            Biome::Rocky => TILE_GRASS,
+            Biome::Marsh if patch < 0.3 => TILE_WATER,
+            Biome::Marsh if patch < 0.42 => TILE_DIRT,
+            Biome::Marsh => TILE_GRASS,
+        }
+    }
+
+    /// Decoration over a `ground` tile at a world tile: flowers, pebbles and tufts, clumped by smooth
+    /// noise so they come in drifts; `TILE_EMPTY` on most tiles
+    pub fn decoration(&self, seed: u64, tile: IVec2, ground: TileId) -> TileId {
+        let (rate, grass_flowers) = match self {
+            Biome::Forest => (0.5, 0.3),
+            Biome::Plains => (0.45, 0.65),
+            Biome::Rocky => (0.35, 0.2),
+            Biome::Marsh => (0.3, 0.1),
+        };
+        let density = patch_noise(seed.wrapping_add(DECORATION_SALT), tile, PATCH_SIZE);
+        let hash = lattice_hash(seed, tile.x, tile.y, DECORATION_SALT);
+        if unit(hash) >= density * density * rate {
+            return TILE_EMPTY;
+        }
+        let pick = unit(hash.rotate_left(24));
+        match ground {
+            TILE_GRASS if *self == Biome::Rocky && pick < 0.4 => TILE_PEBBLES,
+            TILE_GRASS if pick < grass_flowers => TILE_FLOWERS,
+            TILE_GRASS => TILE_TUFT,
+            TILE_DIRT if pick < 0.7 => TILE_PEBBLES,
+            TILE_DIRT => TILE_TUFT,
+            TILE_SAND => TILE_PEBBLES,
+            _ => TILE_EMPTY,
+        }
+    }
+
+    /// How readily trees are planted here (multiplies the guardians' planting rate)
+    pub fn tree_density(&self) -> f32 {
+        match self {
+            Biome::Forest => 1.5,
+            Biome::Plains => 0.6,
+            Biome::Rocky => 0.25,
+            Biome::Marsh => 0.8,
+        }
+    }
+}
+
+impl fmt::Display for Biome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Biome::Forest => "forest",
+            Biome::Plains => "plains",
+            Biome::Rocky => "rocky",
+            Biome::Marsh => "marsh",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Well-mixed hash of a lattice point (splitmix64 finalizer)
+pub(super) fn lattice_hash(seed: u64, x: i32, y: i32, salt: u64) -> u64 {
+    let mut h = seed ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (((x as u32 as u64) << 32) | y as u32 as u64);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+/// The top 24 bits of a hash as a value in [0, 1)
+pub(super) fn unit(hash: u64) -> f32 {
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Biome of a chunk in a world generated with `seed`
+pub fn biome_at_chunk(chunk: ChunkPos, seed: u64) -> Biome {
+    let region = IVec2::new(chunk.x.div_euclid(BIOME_REGION_CHUNKS), chunk.y.div_euclid(BIOME_REGION_CHUNKS));
+    let point = Vec2::new(chunk.x as f32 + 0.5, chunk.y as f32 + 0.5);
+    let mut nearest = (f32::MAX, Vec2::ZERO);
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let cell = region + IVec2::new(dx, dy);
+            let hash = lattice_hash(seed, cell.x, cell.y, 0);
+            let jitter = Vec2::new(unit(hash), unit(hash.rotate_left(24)));
+            let center = (cell.as_vec2() + jitter) * BIOME_REGION_CHUNKS as f32;
+            let distance = center.distance_squared(point);
+            if distance < nearest.0 {
+                nearest = (distance, center);
+            }
+        }
+    }
+    climate_at(seed, (nearest.1 * CHUNK_SIZE_I32 as f32).floor().as_ivec2()).biome()
+}
+
+/// Smooth noise in [0, 1) over world tile coordinates, in patches of about `size` tiles
+pub fn patch_noise(seed: u64, tile: IVec2, size: f32) -> f32 {
+    value_noise(seed, 1, tile, size)
+}
+
+/// Smooth value noise in [0, 1) over world tile coordinates, varying over about `scale` tiles
+pub(super) fn value_noise(seed: u64, salt: u64, tile: IVec2, scale: f32) -> f32 {
+    let point = tile.as_vec2() / scale;
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (Vec2::splat(3.0) - 2.0 * t);
+    let (x, y) = (cell.x as i32, cell.y as i32);
+    let corner = |dx: i32, dy: i32| unit(lattice_hash(seed, x + dx, y + dy, salt));
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * t.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+/// World tile coordinates of a chunk's local tile
+pub fn world_tile(chunk: ChunkPos, x: usize, y: usize) -> IVec2 {
+    IVec2::new(chunk.x * CHUNK_SIZE_I32 + x as i32, chunk.y * CHUNK_SIZE_I32 + y as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biome_map() {
+        // Deterministic per seed, and every biome shows up nearby
+        let biomes: Vec<Biome> = (-30..30)
+            .flat_map(|y| (-30..30).map(move |x| ChunkPos::new(x, y)))
+            .map(|chunk| biome_at_chunk(chunk, 7))
+            .collect();
+        for biome in Biome::ALL {
+            assert!(biomes.contains(&biome), "no {} within 30 chunks", biome);
+        }
+        assert_eq!(biome_at_chunk(ChunkPos::new(-4, 9), 7), biome_at_chunk(ChunkPos::new(-4, 9), 7));
+        let other_seed = (-30..30).filter(|x| biome_at_chunk(ChunkPos::new(*x, 0), 8) != biomes[30 * 60 + (*x + 30) as usize]);
+        assert!(other_seed.count() > 0);
+
+        // Regions span several chunks: most chunks share their neighbour's biome
+        let same = (0..59).filter(|x| biomes[x + 60 * 10] == biomes[x + 1 + 60 * 10]).count();
+        assert!(same > 40, "{} of 59 neighbours match", same);
+
+        // Patch noise stays in range and is smooth from tile to tile
+        for x in -50..50 {
+            let a = patch_noise(3, IVec2::new(x, 11), PATCH_SIZE);
+            let b = patch_noise(3, IVec2::new(x + 1, 11), PATCH_SIZE);
+            assert!((0.0..1.0).contains(&a));
+            assert!((a - b).abs() < 0.5);
+        }
+        assert_eq!(Biome::Marsh.ground_tile(0.1), TILE_WATER);
+        assert_eq!(Biome::Forest.ground_tile(0.9), TILE_GRASS);
+
+        // Decorations cover some tiles, never water, and flowers only grow on grass
+        let tiles = (-50..50).flat_map(|y| (-50..50).map(move |x| IVec2::new(x, y)));
+        let decorations: Vec<(TileId, TileId)> = tiles
+            .flat_map(|tile| [TILE_GRASS, TILE_DIRT, TILE_WATER].map(|ground| (ground, Biome::Plains.decoration(3, tile, ground))))
+            .collect();
+        let decorated = decorations.iter().filter(|(_, decoration)| *decoration != TILE_EMPTY).count();
+        assert!((1_000..15_000).contains(&decorated), "{} of 30000 tiles decorated", decorated);
+        assert!(decorations.iter().all(|(ground, decoration)| *ground != TILE_WATER || *decoration == TILE_EMPTY));
+        assert!(decorations.iter().all(|(ground, decoration)| *decoration != TILE_FLOWERS || *ground == TILE_GRASS));
+        assert!(decorations.contains(&(TILE_DIRT, TILE_PEBBLES)));
+    }
+}
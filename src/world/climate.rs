@@ -0,0 +1,94 @@
+//! Climate: low-frequency temperature and moisture fields from the world seed, smooth over hundreds
+//! of tiles. Biome regions take their biome from the climate at their center (see
+//! `biome::biome_at_chunk`), and the `Climate` resource samples the fields for any other system
+
+use super::biome::{value_noise, Biome};
+use crate::tiles::TILE_SIZE;
+use bevy::prelude::*;
+
+/// Tiles across a warm or cold zone
+pub const TEMPERATURE_SCALE: f32 = 320.0;
+
+/// Tiles across a wet or dry zone
+pub const MOISTURE_SCALE: f32 = 224.0;
+
+/// Salts of the two fields (clear of the elevation octaves')
+const TEMPERATURE_SALT: u64 = 16;
+const MOISTURE_SALT: u64 = 17;
+
+/// Climate at one place; both fields are in [0, 1), from coldest and driest to warmest and wettest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimateSample {
+    pub temperature: f32,
+    pub moisture: f32,
+}
+
+impl ClimateSample {
+    /// Biome this climate supports: marsh where it's wettest, rocky ground where it's cold,
+    /// forest where it's moist and plains where it's dry
+    pub fn biome(&self) -> Biome {
+        if self.moisture >= 0.68 {
+            Biome::Marsh
+        } else if self.temperature < 0.38 {
+            Biome::Rocky
+        } else if self.moisture >= 0.42 {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        }
+    }
+}
+
+/// Climate at a world tile in a world generated with `seed`
+pub fn climate_at(seed: u64, tile: IVec2) -> ClimateSample {
+    ClimateSample {
+        temperature: value_noise(seed, TEMPERATURE_SALT, tile, TEMPERATURE_SCALE),
+        moisture: value_noise(seed, MOISTURE_SALT, tile, MOISTURE_SCALE),
+    }
+}
+
+/// The open world's climate fields (its seed is set by `metadata::load_world_metadata`)
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Climate {
+    pub seed: u64,
+}
+
+impl Climate {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Climate at a world position (pixels)
+    pub fn sample(&self, world_pos: Vec2) -> ClimateSample {
+        climate_at(self.seed, (world_pos / TILE_SIZE).floor().as_ivec2())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_climate() {
+        let climate = Climate::new(7);
+        let samples: Vec<ClimateSample> = (-40..40)
+            .flat_map(|y| (-40..40).map(move |x| Vec2::new(x as f32, y as f32) * 32.0 * TILE_SIZE))
+            .map(|position| climate.sample(position))
+            .collect();
+        for sample in &samples {
+            assert!((0.0..1.0).contains(&sample.temperature) && (0.0..1.0).contains(&sample.moisture));
+        }
+        // Every biome has a climate somewhere nearby
+        for biome in Biome::ALL {
+            assert!(samples.iter().any(|sample| sample.biome() == biome), "no climate for {}", biome);
+        }
+
+        // Deterministic per seed, and smooth: neighbouring tiles barely differ
+        let here = Vec2::new(1234.0, -567.0);
+        assert_eq!(climate.sample(here), climate_at(7, (here / TILE_SIZE).floor().as_ivec2()));
+        assert_ne!(climate.sample(here), Climate::new(8).sample(here));
+        let next = climate.sample(here + Vec2::new(TILE_SIZE, 0.0));
+        assert!((climate.sample(here).temperature - next.temperature).abs() < 0.02);
+        assert!((climate.sample(here).moisture - next.moisture).abs() < 0.02);
+    }
+}
@@ -0,0 +1,122 @@
+//! Resource deposits on biome terrain: now and then a chunk holds a small patch of stone (an
+//! outcrop, on dry ground) or clay (a pit, on moist ground). A deposit lies wholly inside its chunk
+//! and is rolled from the world seed and the chunk position alone, so each chunk's deposit is
+//! the same whenever it is generated. Deposits replace the biome's ground, never water, shores,
+//! rock or structures
+
+use super::biome::{lattice_hash, unit};
+use super::climate::climate_at;
+use crate::tiles::{ChunkPos, TileId, CHUNK_SIZE_I32, TILE_CLAY, TILE_STONE};
+use bevy::prelude::*;
+use std::fmt;
+
+/// Chance of a chunk holding a deposit
+const DEPOSIT_CHANCE: f32 = 0.12;
+
+/// Radius range of a deposit in tiles; its ragged rim reaches one tile further
+const DEPOSIT_RADIUS: (i32, i32) = (2, 3);
+
+/// Climate moisture from which deposits are clay rather than stone
+const CLAY_MOISTURE: f32 = 0.5;
+
+/// Salt of the deposit rolls (clear of the biome, structure and climate ones)
+const DEPOSIT_SALT: u64 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DepositKind {
+    Stone,
+    Clay,
+}
+
+impl DepositKind {
+    pub fn tile(&self) -> TileId {
+        match self {
+            DepositKind::Stone => TILE_STONE,
+            DepositKind::Clay => TILE_CLAY,
+        }
+    }
+}
+
+impl fmt::Display for DepositKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            DepositKind::Stone => "stone outcrop",
+            DepositKind::Clay => "clay pit",
+        })
+    }
+}
+
+/// A deposit placed in the world (center in world tile coordinates)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deposit {
+    pub kind: DepositKind,
+    pub center: IVec2,
+    pub radius: i32,
+}
+
+impl Deposit {
+    /// The deposit's tile at a world tile, or None outside it
+    pub fn ground_tile(&self, tile: IVec2, seed: u64) -> Option<TileId> {
+        let distance = (tile - self.center).length_squared();
+        let rim = lattice_hash(seed, tile.x, tile.y, DEPOSIT_SALT);
+        let inside = distance <= self.radius * self.radius
+            || (distance <= (self.radius + 1) * (self.radius + 1) && unit(rim) < 0.4);
+        inside.then(|| self.kind.tile())
+    }
+}
+
+/// The deposit in a chunk of a world generated with `seed`, if any
+pub fn deposit_in_chunk(chunk: ChunkPos, seed: u64) -> Option<Deposit> {
+    let hash = lattice_hash(seed, chunk.x, chunk.y, DEPOSIT_SALT);
+    if unit(hash) >= DEPOSIT_CHANCE {
+        return None;
+    }
+    let (min, max) = DEPOSIT_RADIUS;
+    let radius = min + (unit(hash.rotate_left(8)) * (max - min + 1) as f32) as i32;
+    // Keep the rim inside the chunk
+    let span = CHUNK_SIZE_I32 - 2 * (radius + 1);
+    let local = IVec2::new(
+        (unit(hash.rotate_left(16)) * span as f32) as i32,
+        (unit(hash.rotate_left(32)) * span as f32) as i32,
+    ) + IVec2::splat(radius + 1);
+    let center = IVec2::new(chunk.x, chunk.y) * CHUNK_SIZE_I32 + local;
+    let kind = if climate_at(seed, center).moisture >= CLAY_MOISTURE {
+        DepositKind::Clay
+    } else {
+        DepositKind::Stone
+    };
+    Some(Deposit { kind, center, radius })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposits() {
+        let chunks: Vec<ChunkPos> = (-20..20).flat_map(|y| (-20..20).map(move |x| ChunkPos::new(x, y))).collect();
+        let deposits: Vec<(ChunkPos, Deposit)> =
+            chunks.iter().filter_map(|chunk| deposit_in_chunk(*chunk, 3).map(|deposit| (*chunk, deposit))).collect();
+        // Occasional, of both kinds, and deterministic
+        assert!((80..320).contains(&deposits.len()), "{} deposits in 1600 chunks", deposits.len());
+        assert!(deposits.iter().any(|(_, deposit)| deposit.kind == DepositKind::Stone));
+        assert!(deposits.iter().any(|(_, deposit)| deposit.kind == DepositKind::Clay));
+        let (chunk, deposit) = deposits[0];
+        assert_eq!(deposit_in_chunk(chunk, 3), Some(deposit));
+
+        // Every tile of a deposit, rim included, is inside its chunk
+        for (chunk, deposit) in &deposits {
+            let origin = IVec2::new(chunk.x, chunk.y) * CHUNK_SIZE_I32;
+            for dy in -5..=5 {
+                for dx in -5..=5 {
+                    let tile = deposit.center + IVec2::new(dx, dy);
+                    if deposit.ground_tile(tile, 3).is_some() {
+                        let local = tile - origin;
+                        assert!(local.cmpge(IVec2::ZERO).all() && local.cmplt(IVec2::splat(CHUNK_SIZE_I32)).all());
+                    }
+                }
+            }
+            assert_eq!(deposit.ground_tile(deposit.center, 3), Some(deposit.kind.tile()));
+        }
+    }
+}
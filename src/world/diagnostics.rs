@@ -0,0 +1,84 @@
+//! Custom diagnostics for chunk streaming, read from the `DiagnosticsStore` like Bevy's own
+//! (the debug HUD shows them with `dev-tools`)
+
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+
+/// Chunks spawned around the camera per second
+pub const CHUNKS_LOADED: DiagnosticPath = DiagnosticPath::const_new("worldseed/chunks_loaded_per_second");
+
+/// Tile modifications written to chunks per second (no-op writes don't count)
+pub const TILE_CHANGES_APPLIED: DiagnosticPath = DiagnosticPath::const_new("worldseed/tile_changes_applied_per_second");
+
+/// Work counted by the streaming systems this frame, turned into per-second rates by
+/// `record_world_diagnostics`
+#[derive(Resource, Debug, Default)]
+pub struct WorldActivity {
+    pub chunks_loaded: u32,
+    pub tile_changes_applied: u32,
+    /// Since startup, up to the last `take_rates` (for counters like the metrics endpoint's)
+    pub chunks_loaded_total: u64,
+    pub tile_changes_applied_total: u64,
+}
+
+impl WorldActivity {
+    /// Per-second rates of this frame's counts (chunks loaded, tile changes applied), resetting
+    /// them for the next frame; None for a zero-length frame
+    pub fn take_rates(&mut self, delta_secs: f64) -> Option<(f64, f64)> {
+        let chunks_loaded = std::mem::take(&mut self.chunks_loaded);
+        let tile_changes_applied = std::mem::take(&mut self.tile_changes_applied);
+        self.chunks_loaded_total += chunks_loaded as u64;
+        self.tile_changes_applied_total += tile_changes_applied as u64;
+        if delta_secs <= 0.0 {
+            return None;
+        }
+        Some((
+            chunks_loaded as f64 / delta_secs,
+            tile_changes_applied as f64 / delta_secs,
+        ))
+    }
+}
+
+/// Registers the diagnostics and the system that records them
+pub fn add_world_diagnostics(app: &mut App) {
+    app.init_resource::<WorldActivity>()
+        .register_diagnostic(Diagnostic::new(CHUNKS_LOADED).with_suffix("/s"))
+        .register_diagnostic(Diagnostic::new(TILE_CHANGES_APPLIED).with_suffix("/s"))
+        .add_systems(Last, record_world_diagnostics);
+}
+
+/// Records this frame's streaming rates
+pub fn record_world_diagnostics(
+    mut diagnostics: Diagnostics,
+    mut activity: ResMut<WorldActivity>,
+    time: Res<Time<Real>>,
+) {
+    let Some((chunks, tile_changes)) = activity.take_rates(time.delta_secs_f64()) else {
+        return;
+    };
+    diagnostics.add_measurement(&CHUNKS_LOADED, || chunks);
+    diagnostics.add_measurement(&TILE_CHANGES_APPLIED, || tile_changes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_rates() {
+        let mut activity = WorldActivity {
+            chunks_loaded: 3,
+            tile_changes_applied: 40,
+            ..default()
+        };
+        assert_eq!(activity.take_rates(0.5), Some((6.0, 80.0)));
+        // Counts start over every frame
+        assert_eq!(activity.take_rates(0.5), Some((0.0, 0.0)));
+
+        activity.chunks_loaded = 2;
+        assert_eq!(activity.take_rates(0.0), None);
+        assert_eq!(activity.chunks_loaded, 0);
+        // Totals keep everything, zero-length frames included
+        assert_eq!((activity.chunks_loaded_total, activity.tile_changes_applied_total), (5, 40));
+    }
+}
@@ -0,0 +1,268 @@
+use super::loader;
+use super::manager::WorldManager;
+use super::{generate_chunk, model};
+use super::serialization;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::{stash_loaded_entities, ChunkEntity, ChunkEntityFilter};
+use crate::tiles::{ChunkData, ChunkPos, TileId, CHUNK_SIZE, NUM_LAYERS};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Layer names used in dumps, indexed by layer
+const LAYER_NAMES: [&str; NUM_LAYERS] = ["ground", "decoration", "overlay"];
+
+/// Error type for chunk dumps
+#[derive(Debug, Error)]
+pub enum DumpError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Parse error: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Serialize error: {0}")]
+    Serialize(#[from] ron::Error),
+    /// Wrong number of layers, rows or tiles per row
+    #[error("Invalid chunk dump: {0}")]
+    InvalidShape(String),
+}
+
+/// One layer of a dump: 32 rows of 32 space-separated tile IDs, north row first
+/// (so the text reads like the map)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerDump {
+    pub name: String,
+    pub rows: Vec<String>,
+}
+
+/// Human-readable RON form of a chunk, for bug reports and hand editing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkDump {
+    pub x: i32,
+    pub y: i32,
+    pub layers: Vec<LayerDump>,
+}
+
+impl ChunkDump {
+    pub fn from_chunk(chunk: &ChunkData) -> Self {
+        let layers = (0..NUM_LAYERS)
+            .map(|layer| LayerDump {
+                name: LAYER_NAMES[layer].to_string(),
+                rows: (0..CHUNK_SIZE)
+                    .rev()
+                    .map(|local_y| {
+                        let row = &chunk.layers[layer][local_y * CHUNK_SIZE..(local_y + 1) * CHUNK_SIZE];
+                        row.iter().map(|tile| tile.to_string()).collect::<Vec<_>>().join(" ")
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            x: chunk.position.x,
+            y: chunk.position.y,
+            layers,
+        }
+    }
+
+    pub fn to_chunk(&self) -> Result<ChunkData, DumpError> {
+        self.to_chunk_over(ChunkData::empty(ChunkPos::new(self.x, self.y)))
+    }
+
+    /// The dumped layers laid over `base` (the chunk being replaced): a dump holds only tiles, so
+    /// the base's elevation, settlers and saved creatures and trees are kept
+    pub fn to_chunk_over(&self, base: ChunkData) -> Result<ChunkData, DumpError> {
+        if self.layers.len() != NUM_LAYERS {
+            return Err(DumpError::InvalidShape(format!(
+                "expected {} layers, found {}",
+                NUM_LAYERS,
+                self.layers.len()
+            )));
+        }
+
+        // Hand-made, so never regenerated as stale
+        let mut chunk = ChunkData { position: ChunkPos::new(self.x, self.y), edited: true, ..base };
+        for (layer, dump) in self.layers.iter().enumerate() {
+            if dump.rows.len() != CHUNK_SIZE {
+                return Err(DumpError::InvalidShape(format!(
+                    "layer '{}' has {} rows, expected {}",
+                    dump.name,
+                    dump.rows.len(),
+                    CHUNK_SIZE
+                )));
+            }
+
+            for (row_index, row) in dump.rows.iter().enumerate() {
+                let tiles: Vec<TileId> = row
+                    .split_whitespace()
+                    .map(|tile| tile.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| {
+                        DumpError::InvalidShape(format!("layer '{}' row {} has a non-numeric tile", dump.name, row_index))
+                    })?;
+                if tiles.len() != CHUNK_SIZE {
+                    return Err(DumpError::InvalidShape(format!(
+                        "layer '{}' row {} has {} tiles, expected {}",
+                        dump.name,
+                        row_index,
+                        tiles.len(),
+                        CHUNK_SIZE
+                    )));
+                }
+
+                let local_y = CHUNK_SIZE - 1 - row_index;
+                chunk.layers[layer][local_y * CHUNK_SIZE..(local_y + 1) * CHUNK_SIZE].copy_from_slice(&tiles);
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+/// Write a chunk as RON
+pub fn dump_chunk<P: AsRef<Path>>(chunk: &ChunkData, path: P) -> Result<(), DumpError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let text = ron::ser::to_string_pretty(&ChunkDump::from_chunk(chunk), ron::ser::PrettyConfig::default())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Read a RON dump
+pub fn read_chunk_dump<P: AsRef<Path>>(path: P) -> Result<ChunkDump, DumpError> {
+    let text = fs::read_to_string(path)?;
+    Ok(ron::from_str(&text)?)
+}
+
+/// Read a chunk from a RON dump
+pub fn load_chunk_dump<P: AsRef<Path>>(path: P) -> Result<ChunkData, DumpError> {
+    read_chunk_dump(path)?.to_chunk()
+}
+
+/// Console commands: `dumpchunk <x> <y> [file]` writes a chunk as RON (default `dumps/chunk_x_y.ron`
+/// in the save directory), `loadchunk <file>` replaces the chunk at the dump's position
+pub fn handle_dump_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut world: ResMut<WorldManager>,
+    chunk_entities: Query<ChunkEntity, ChunkEntityFilter>,
+) {
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "dumpchunk" => {
+                let (Some(Ok(x)), Some(Ok(y))) = (
+                    command.args.first().map(|x| x.parse()),
+                    command.args.get(1).map(|y| y.parse()),
+                ) else {
+                    console.print("Usage: dumpchunk <x> <y> [file]");
+                    continue;
+                };
+                let chunk_pos = ChunkPos::new(x, y);
+                let path = command.args.get(2).map(PathBuf::from).unwrap_or_else(|| {
+                    world
+                        .save_directory
+                        .join("dumps")
+                        .join(format!("chunk_{}_{}.ron", x, y))
+                });
+
+                // Prefer the in-memory copy (it may have unsaved edits)
+                let chunk = match world.get_cached_chunk(&chunk_pos) {
+                    Some(chunk) => Ok(chunk.clone()),
+                    None => serialization::load_chunk(world.get_chunk_path(&chunk_pos)).map_err(|e| e.to_string()),
+                };
+
+                match chunk.and_then(|chunk| dump_chunk(&chunk, &path).map_err(|e| e.to_string())) {
+                    Ok(()) => console.print(format!("Dumped chunk ({}, {}) to {:?}", x, y, path)),
+                    Err(e) => console.print(format!("Dump failed: {}", e)),
+                }
+            }
+            "loadchunk" => {
+                let Some(file) = command.args.first() else {
+                    console.print("Usage: loadchunk <file>");
+                    continue;
+                };
+
+                // Laid over the chunk as it is now (creatures included), which may be unsaved
+                stash_loaded_entities(&mut world, &chunk_entities);
+                let result = read_chunk_dump(file).map_err(|e| e.to_string()).and_then(|dump| {
+                    let chunk_pos = ChunkPos::new(dump.x, dump.y);
+                    let base = match model::read_chunk(&mut world, chunk_pos) {
+                        Ok((chunk, _)) => chunk,
+                        Err(_) => generate_chunk(chunk_pos, &world.generator),
+                    };
+                    let chunk = dump.to_chunk_over(base).map_err(|e| e.to_string())?;
+                    world.replace_chunk_file(&chunk).map_err(|e| e.to_string())?;
+                    Ok(chunk_pos)
+                });
+
+                match result {
+                    Ok(chunk_pos) => {
+                        loader::reload_chunk(&mut commands, &mut world, &chunk_entities, chunk_pos);
+                        console.print(format!(
+                            "Loaded chunk ({}, {}) from {} (previous file kept as a backup)",
+                            chunk_pos.x, chunk_pos.y, file
+                        ));
+                    }
+                    Err(e) => console.print(format!("Load failed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{ChunkSettler, LAYER_DECORATION, LAYER_GROUND, TILE_DIRT, TILE_GRASS};
+    use crate::world::entity_serialization::{SavedComponent, SavedEntity};
+    use std::env;
+
+    #[test]
+    fn test_dump_round_trip() {
+        let mut chunk = ChunkData::filled_layers(ChunkPos::new(-3, 4), [TILE_GRASS, 0, 0]);
+        chunk.set_tile(LAYER_GROUND, 0, 0, TILE_DIRT);
+        chunk.set_tile(LAYER_DECORATION, 31, 31, 7);
+
+        let dump = ChunkDump::from_chunk(&chunk);
+        // North row first: local (31, 31) ends the first row, local (0, 0) starts the last one
+        assert!(dump.layers[LAYER_DECORATION].rows[0].ends_with(" 7"));
+        assert!(dump.layers[LAYER_GROUND].rows[CHUNK_SIZE - 1].starts_with("2 "));
+
+        let path = env::temp_dir().join("test_chunk_dump.ron");
+        dump_chunk(&chunk, &path).unwrap();
+        let loaded = load_chunk_dump(&path).unwrap();
+        assert_eq!(loaded.position, chunk.position);
+        assert!(loaded.layers == chunk.layers);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_malformed_dump_is_rejected() {
+        let mut dump = ChunkDump::from_chunk(&ChunkData::empty(ChunkPos::new(0, 0)));
+        dump.layers[0].rows[5].push_str(" 1");
+        assert!(matches!(dump.to_chunk(), Err(DumpError::InvalidShape(_))));
+
+        dump.layers.pop();
+        assert!(matches!(dump.to_chunk(), Err(DumpError::InvalidShape(_))));
+    }
+
+    #[test]
+    fn test_dump_keeps_what_it_does_not_hold() {
+        let mut base = ChunkData::filled(ChunkPos::new(2, 2), TILE_GRASS);
+        base.elevation[40] = 200;
+        base.settlers.push(ChunkSettler { x: 4, y: 5, roam_radius: 6 });
+        base.entities.push(SavedEntity::new(vec![SavedComponent::Kind("snail".to_string())]));
+
+        let dump = ChunkDump::from_chunk(&ChunkData::filled(ChunkPos::new(2, 2), TILE_DIRT));
+        let chunk = dump.to_chunk_over(base.clone()).unwrap();
+        assert_eq!(chunk.get_tile(LAYER_GROUND, 0, 0), Some(TILE_DIRT));
+        assert!(chunk.elevation == base.elevation);
+        assert_eq!((chunk.settlers, chunk.entities), (base.settlers, base.entities));
+        assert!(chunk.edited);
+    }
+}
@@ -0,0 +1,78 @@
+//! Elevation for `GroundPattern::Biomes`: octaves of smooth noise from the world seed give every
+//! tile a height (0-255), kept in `ChunkData.elevation` and saved with the chunk. Low ground floods
+//! (water with sand shores) and high ground is bare rock, whatever the biome; movement costs can
+//! read the same heights later. Flat patterns keep `DEFAULT_ELEVATION`. The scales and levels can
+//! be tuned in `worldgen::WORLDGEN_PATH`
+
+use super::biome::value_noise;
+use super::worldgen::WorldgenParams;
+use crate::tiles::{TileId, TILE_ROCK, TILE_SAND, TILE_WATER};
+use bevy::prelude::*;
+
+/// Below this the ground is under water (default of `WorldgenParams.water_level`, and so on)
+pub const WATER_LEVEL: u8 = 56;
+
+/// Below this (and above `WATER_LEVEL`) the ground is a sandy shore
+pub const SHORE_LEVEL: u8 = 64;
+
+/// At or above this the ground is bare rock
+pub const ROCK_LEVEL: u8 = 212;
+
+/// Salt of the broadest octave (each finer octave uses the next one)
+const ELEVATION_SALT: u64 = 5;
+
+/// Height of a world tile in a world generated with `seed`
+/// Each octave but the last keeps `1 - elevation_roughness` of the weight left and passes the rest on
+pub fn elevation_at(seed: u64, tile: IVec2, params: &WorldgenParams) -> u8 {
+    let (mut height, mut remaining, mut scale) = (0.0, 1.0, params.elevation_scale);
+    for octave in 0..params.elevation_octaves {
+        let weight = if octave + 1 == params.elevation_octaves {
+            remaining
+        } else {
+            remaining * (1.0 - params.elevation_roughness)
+        };
+        height += value_noise(seed, ELEVATION_SALT + octave as u64, tile, scale) * weight;
+        remaining -= weight;
+        scale /= params.elevation_lacunarity;
+    }
+    (height * 256.0) as u8
+}
+
+/// The ground a height forces, if any (water, shore sand or rock); None leaves it to the biome
+pub fn ground_for_elevation(elevation: u8, params: &WorldgenParams) -> Option<TileId> {
+    match elevation {
+        height if height < params.water_level => Some(TILE_WATER),
+        height if height < params.shore_level => Some(TILE_SAND),
+        height if height >= params.rock_level => Some(TILE_ROCK),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elevation() {
+        let params = WorldgenParams::default();
+        let heights: Vec<u8> = (-100..100)
+            .flat_map(|y| (-100..100).map(move |x| IVec2::new(x, y)))
+            .map(|tile| elevation_at(9, tile, &params))
+            .collect();
+        let share = |tile: TileId| {
+            heights.iter().filter(|height| ground_for_elevation(**height, &params) == Some(tile)).count() as f32 / heights.len() as f32
+        };
+        // Some lakes and rocky tops, but most ground is left to the biomes
+        for tile in [TILE_WATER, TILE_SAND, TILE_ROCK] {
+            assert!((0.005..0.12).contains(&share(tile)), "tile {} covers {}", tile, share(tile));
+        }
+
+        // Smooth from tile to tile and deterministic
+        for x in -50..50 {
+            let (a, b) = (elevation_at(9, IVec2::new(x, 3), &params), elevation_at(9, IVec2::new(x + 1, 3), &params));
+            assert!(a.abs_diff(b) < 24);
+        }
+        assert_eq!(elevation_at(9, IVec2::new(12, -40), &params), elevation_at(9, IVec2::new(12, -40), &params));
+        assert_eq!(ground_for_elevation(128, &params), None);
+    }
+}
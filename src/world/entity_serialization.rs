@@ -0,0 +1,446 @@
+use super::serialization::SerializationError;
+use bevy::log::info_span;
+use std::io::{Cursor, Read};
+
+/// Magic number for entity files ("ENTS" in ASCII)
+const MAGIC_NUMBER: [u8; 4] = [b'E', b'N', b'T', b'S'];
+
+/// Current entity file format version
+const VERSION: u16 = 1;
+
+/// Identifies a component record inside an entity file
+/// Tags are append-only: never reuse or renumber a tag once it has shipped
+pub type ComponentTag = u16;
+
+pub const TAG_KIND: ComponentTag = 1;
+pub const TAG_POSITION: ComponentTag = 2;
+pub const TAG_VELOCITY: ComponentTag = 3;
+pub const TAG_HEALTH: ComponentTag = 4;
+pub const TAG_GROWTH: ComponentTag = 5;
+pub const TAG_ROAMING: ComponentTag = 6;
+pub const TAG_DIRECTIVE: ComponentTag = 7;
+pub const TAG_WINDING: ComponentTag = 8;
+pub const TAG_MIGRATION: ComponentTag = 9;
+
+/// Registry of component tags this version understands
+/// Records with any other tag are skipped on load (and kept as `SavedComponent::Unknown`)
+pub const COMPONENT_REGISTRY: [(ComponentTag, &str); 9] = [
+    (TAG_KIND, "kind"),
+    (TAG_POSITION, "position"),
+    (TAG_VELOCITY, "velocity"),
+    (TAG_HEALTH, "health"),
+    (TAG_GROWTH, "growth"),
+    (TAG_ROAMING, "roaming"),
+    (TAG_DIRECTIVE, "directive"),
+    (TAG_WINDING, "winding"),
+    (TAG_MIGRATION, "migration"),
+];
+
+/// Look up the registered name of a component tag
+pub fn component_name(tag: ComponentTag) -> Option<&'static str> {
+    COMPONENT_REGISTRY
+        .iter()
+        .find(|(registered, _)| *registered == tag)
+        .map(|(_, name)| *name)
+}
+
+/// A single saved component
+/// Plain data only - conversion to and from ECS components happens at the call site
+#[derive(Debug, Clone, PartialEq)]
+pub enum SavedComponent {
+    /// What to spawn ("player", "snail", "guardian:<variant>", "tree_spirit:<variant>")
+    Kind(String),
+    /// World pixels; stored as f32 followed by f64, so positions far from (0, 0) keep their precision
+    /// and older versions still read the f32 pair
+    Position { x: f64, y: f64 },
+    Velocity { x: f32, y: f32 },
+    Health { current: f32, max: f32 },
+    /// Tree growth (stage index, seconds in stage, seconds per stage)
+    Growth { stage: u8, time_in_stage: f32, time_to_next_stage: f32 },
+    Roaming { home_x: f32, home_y: f32, roam_radius: f32, speed: f32 },
+    /// Guardian directive: which one (0 reforest, 1 patrol, 2 guard against snails) and its
+    /// parameters (region corners, patrol ends and direction, or post and radius)
+    Directive { kind: u8, params: [f32; 5] },
+    /// Snail wandering (`WindingPath`): heading and target heading (radians), the current segment's
+    /// length and progress, and the settings new segments are drawn from
+    Winding {
+        speed: f32,
+        angle: f32,
+        target_angle: f32,
+        segment_length: f32,
+        distance_traveled: f32,
+        turn_rate: f32,
+        min_segment: f32,
+        max_segment: f32,
+        max_angle_change: f32,
+    },
+    /// Migration (`Migrating`): the world position the creature's group is heading for
+    Migration { target_x: f32, target_y: f32 },
+    /// Component written by a newer version, preserved byte-for-byte
+    Unknown { tag: ComponentTag, data: Vec<u8> },
+}
+
+impl SavedComponent {
+    pub fn tag(&self) -> ComponentTag {
+        match self {
+            SavedComponent::Kind(_) => TAG_KIND,
+            SavedComponent::Position { .. } => TAG_POSITION,
+            SavedComponent::Velocity { .. } => TAG_VELOCITY,
+            SavedComponent::Health { .. } => TAG_HEALTH,
+            SavedComponent::Growth { .. } => TAG_GROWTH,
+            SavedComponent::Roaming { .. } => TAG_ROAMING,
+            SavedComponent::Directive { .. } => TAG_DIRECTIVE,
+            SavedComponent::Winding { .. } => TAG_WINDING,
+            SavedComponent::Migration { .. } => TAG_MIGRATION,
+            SavedComponent::Unknown { tag, .. } => *tag,
+        }
+    }
+
+    /// Encode the component payload (without tag or length)
+    fn encode_payload(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut put = |value: f32| bytes.extend_from_slice(&value.to_le_bytes());
+        match self {
+            SavedComponent::Kind(kind) => return kind.as_bytes().to_vec(),
+            SavedComponent::Position { x, y } => {
+                put(*x as f32);
+                put(*y as f32);
+                bytes.extend_from_slice(&x.to_le_bytes());
+                bytes.extend_from_slice(&y.to_le_bytes());
+            }
+            SavedComponent::Velocity { x, y } => {
+                put(*x);
+                put(*y);
+            }
+            SavedComponent::Health { current, max } => {
+                put(*current);
+                put(*max);
+            }
+            SavedComponent::Growth { stage, time_in_stage, time_to_next_stage } => {
+                put(*time_in_stage);
+                put(*time_to_next_stage);
+                bytes.push(*stage);
+            }
+            SavedComponent::Roaming { home_x, home_y, roam_radius, speed } => {
+                put(*home_x);
+                put(*home_y);
+                put(*roam_radius);
+                put(*speed);
+            }
+            SavedComponent::Directive { kind, params } => {
+                for value in params {
+                    put(*value);
+                }
+                bytes.push(*kind);
+            }
+            SavedComponent::Winding {
+                speed,
+                angle,
+                target_angle,
+                segment_length,
+                distance_traveled,
+                turn_rate,
+                min_segment,
+                max_segment,
+                max_angle_change,
+            } => {
+                for value in [
+                    speed,
+                    angle,
+                    target_angle,
+                    segment_length,
+                    distance_traveled,
+                    turn_rate,
+                    min_segment,
+                    max_segment,
+                    max_angle_change,
+                ] {
+                    put(*value);
+                }
+            }
+            SavedComponent::Migration { target_x, target_y } => {
+                put(*target_x);
+                put(*target_y);
+            }
+            SavedComponent::Unknown { data, .. } => return data.clone(),
+        }
+        bytes
+    }
+
+    /// Decode a payload for a known tag
+    /// Extra trailing bytes (fields appended by newer versions) are ignored
+    fn decode_payload(tag: ComponentTag, data: &[u8]) -> Result<Self, SerializationError> {
+        let mut reader = Cursor::new(data);
+        let mut get = || -> Result<f32, SerializationError> {
+            let mut buf = [0u8; 4];
+            reader
+                .read_exact(&mut buf)
+                .map_err(|_| SerializationError::InvalidComponent(tag))?;
+            Ok(f32::from_le_bytes(buf))
+        };
+
+        let component = match tag {
+            TAG_KIND => SavedComponent::Kind(
+                String::from_utf8(data.to_vec()).map_err(|_| SerializationError::InvalidComponent(tag))?,
+            ),
+            TAG_POSITION => {
+                let single = (get()? as f64, get()? as f64);
+                // Files from before the f64 pair was appended only have the f32 one
+                let precise = |offset: usize| data.get(offset..offset + 8)?.try_into().ok().map(f64::from_le_bytes);
+                let (x, y) = precise(8).zip(precise(16)).unwrap_or(single);
+                SavedComponent::Position { x, y }
+            }
+            TAG_VELOCITY => SavedComponent::Velocity { x: get()?, y: get()? },
+            TAG_HEALTH => SavedComponent::Health { current: get()?, max: get()? },
+            TAG_GROWTH => {
+                let time_in_stage = get()?;
+                let time_to_next_stage = get()?;
+                let stage = *data.get(8).ok_or(SerializationError::InvalidComponent(tag))?;
+                SavedComponent::Growth { stage, time_in_stage, time_to_next_stage }
+            }
+            TAG_ROAMING => SavedComponent::Roaming {
+                home_x: get()?,
+                home_y: get()?,
+                roam_radius: get()?,
+                speed: get()?,
+            },
+            TAG_DIRECTIVE => {
+                let params = [get()?, get()?, get()?, get()?, get()?];
+                let kind = *data.get(20).ok_or(SerializationError::InvalidComponent(tag))?;
+                SavedComponent::Directive { kind, params }
+            }
+            TAG_WINDING => SavedComponent::Winding {
+                speed: get()?,
+                angle: get()?,
+                target_angle: get()?,
+                segment_length: get()?,
+                distance_traveled: get()?,
+                turn_rate: get()?,
+                min_segment: get()?,
+                max_segment: get()?,
+                max_angle_change: get()?,
+            },
+            TAG_MIGRATION => SavedComponent::Migration { target_x: get()?, target_y: get()? },
+            _ => SavedComponent::Unknown { tag, data: data.to_vec() },
+        };
+        Ok(component)
+    }
+}
+
+/// A saved entity - an ordered list of components
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavedEntity {
+    pub components: Vec<SavedComponent>,
+}
+
+impl SavedEntity {
+    pub fn new(components: Vec<SavedComponent>) -> Self {
+        Self { components }
+    }
+
+    /// Find the first component with a tag
+    pub fn get(&self, tag: ComponentTag) -> Option<&SavedComponent> {
+        self.components.iter().find(|component| component.tag() == tag)
+    }
+
+    /// Kind string, if the entity has one
+    pub fn kind(&self) -> Option<&str> {
+        match self.get(TAG_KIND) {
+            Some(SavedComponent::Kind(kind)) => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+/// Encode entities into the binary entity format
+///
+/// Layout: magic, version (u16), entity count (u32), then per entity a component
+/// count (u16) and per component tag (u16), payload length (u32), payload.
+/// A CRC32 of everything after the header is appended.
+pub fn encode_entities(entities: &[SavedEntity]) -> Vec<u8> {
+    let _span = info_span!("encode_entities", count = entities.len()).entered();
+    let mut body = Vec::new();
+    body.extend_from_slice(&(entities.len() as u32).to_le_bytes());
+    for entity in entities {
+        body.extend_from_slice(&(entity.components.len() as u16).to_le_bytes());
+        for component in &entity.components {
+            let payload = component.encode_payload();
+            body.extend_from_slice(&component.tag().to_le_bytes());
+            body.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            body.extend_from_slice(&payload);
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(body.len() + 10);
+    bytes.extend_from_slice(&MAGIC_NUMBER);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&body);
+    bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+    bytes
+}
+
+/// Decode entities from the binary entity format
+pub fn decode_entities(bytes: &[u8]) -> Result<Vec<SavedEntity>, SerializationError> {
+    let _span = info_span!("decode_entities", bytes = bytes.len()).entered();
+    if bytes.len() < 10 {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    if bytes[0..4] != MAGIC_NUMBER {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != VERSION {
+        return Err(SerializationError::InvalidVersion(version));
+    }
+
+    // Verify checksum before parsing the body
+    let body = &bytes[6..bytes.len() - 4];
+    let checksum_bytes = &bytes[bytes.len() - 4..];
+    let expected_checksum = u32::from_le_bytes([
+        checksum_bytes[0],
+        checksum_bytes[1],
+        checksum_bytes[2],
+        checksum_bytes[3],
+    ]);
+    if crc32fast::hash(body) != expected_checksum {
+        return Err(SerializationError::InvalidChecksum);
+    }
+
+    let mut reader = Cursor::new(body);
+    let entity_count = read_u32(&mut reader)?;
+    let mut entities = Vec::with_capacity(entity_count.min(1 << 16) as usize);
+    for _ in 0..entity_count {
+        let component_count = read_u16(&mut reader)?;
+        let mut components = Vec::with_capacity(component_count as usize);
+        for _ in 0..component_count {
+            let tag = read_u16(&mut reader)?;
+            let length = read_u32(&mut reader)? as usize;
+            // The length comes from the file: never allocate past what the body holds
+            let remaining = body.len() - reader.position() as usize;
+            if length > remaining {
+                return Err(SerializationError::InvalidChunkSize(length));
+            }
+            let mut payload = vec![0u8; length];
+            reader.read_exact(&mut payload)?;
+            components.push(SavedComponent::decode_payload(tag, &payload)?);
+        }
+        entities.push(SavedEntity { components });
+    }
+
+    Ok(entities)
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16, SerializationError> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, SerializationError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entities() -> Vec<SavedEntity> {
+        vec![
+            SavedEntity::new(vec![
+                SavedComponent::Kind("guardian:oak".to_string()),
+                SavedComponent::Position { x: -100.0, y: 0.0 },
+                SavedComponent::Health { current: 120.0, max: 150.0 },
+                SavedComponent::Roaming { home_x: -100.0, home_y: 0.0, roam_radius: 100.0, speed: 15.0 },
+                SavedComponent::Directive { kind: 1, params: [-100.0, 0.0, 200.0, 50.0, 1.0] },
+            ]),
+            SavedEntity::new(vec![
+                SavedComponent::Kind("tree_spirit:birch".to_string()),
+                SavedComponent::Position { x: 0.0, y: 100.0 },
+                SavedComponent::Growth { stage: 2, time_in_stage: 1.5, time_to_next_stage: 3.0 },
+            ]),
+            SavedEntity::new(vec![
+                SavedComponent::Kind("snail".to_string()),
+                SavedComponent::Position { x: 100.0, y: 0.0 },
+                SavedComponent::Winding {
+                    speed: 2.5,
+                    angle: 1.0,
+                    target_angle: 1.25,
+                    segment_length: 200.0,
+                    distance_traveled: 40.0,
+                    turn_rate: 0.5,
+                    min_segment: 150.0,
+                    max_segment: 400.0,
+                    max_angle_change: 1.2,
+                },
+                SavedComponent::Migration { target_x: 2048.0, target_y: -512.0 },
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_encode_and_decode_entities() {
+        let original = sample_entities();
+
+        let loaded = decode_entities(&encode_entities(&original)).expect("Failed to decode entities");
+
+        assert_eq!(loaded, original);
+        assert_eq!(loaded[0].kind(), Some("guardian:oak"));
+    }
+
+    #[test]
+    fn test_unknown_components_are_skipped_and_preserved() {
+        let mut entities = sample_entities();
+        entities[0].components.push(SavedComponent::Unknown { tag: 999, data: vec![1, 2, 3] });
+
+        let loaded = decode_entities(&encode_entities(&entities)).expect("Failed to decode");
+        assert_eq!(component_name(999), None);
+        assert_eq!(loaded[0].get(TAG_POSITION), entities[0].get(TAG_POSITION));
+        assert_eq!(loaded[0].get(999), Some(&SavedComponent::Unknown { tag: 999, data: vec![1, 2, 3] }));
+    }
+
+    #[test]
+    fn test_known_component_ignores_appended_fields() {
+        // A newer version appended a z coordinate to position
+        let mut payload = Vec::new();
+        for value in [4.0f32, 8.0, 16.0] {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        let component = SavedComponent::decode_payload(TAG_POSITION, &payload).unwrap();
+        assert_eq!(component, SavedComponent::Position { x: 4.0, y: 8.0 });
+
+        // Positions keep full precision far from (0, 0), and older readers still find the f32 pair
+        let far = SavedComponent::Position { x: 1e12 + 0.25, y: -3.5 };
+        let payload = far.encode_payload();
+        assert_eq!(SavedComponent::decode_payload(TAG_POSITION, &payload).unwrap(), far);
+        assert_eq!(f32::from_le_bytes(payload[4..8].try_into().unwrap()), -3.5);
+
+        // Too short is an error
+        assert!(SavedComponent::decode_payload(TAG_POSITION, &payload[..6]).is_err());
+    }
+
+    #[test]
+    fn test_corrupted_entity_file_is_rejected() {
+        let mut bytes = encode_entities(&sample_entities());
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xFF;
+        assert!(matches!(decode_entities(&bytes), Err(SerializationError::InvalidChecksum)));
+
+        assert!(matches!(decode_entities(b"TILE\x01\x00...."), Err(SerializationError::InvalidMagicNumber)));
+
+        // A payload length past the end of the body (with a matching checksum) is rejected before allocating
+        let mut body = Vec::new();
+        body.extend_from_slice(&1u32.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&TAG_POSITION.to_le_bytes());
+        body.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut bytes = MAGIC_NUMBER.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        let error = decode_entities(&bytes).unwrap_err();
+        assert!(matches!(error, SerializationError::InvalidChunkSize(_)));
+        assert!(error.is_corruption());
+    }
+}
@@ -0,0 +1,143 @@
+use super::manager::WorldManager;
+use super::serialization::SerializationError;
+use crate::error::Failures;
+use crate::tiles::ChunkPos;
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Magic number for explored chunk files ("EXPL" in ASCII)
+const EXPLORED_MAGIC: [u8; 4] = [b'E', b'X', b'P', b'L'];
+
+/// Current explored chunk file format version
+const EXPLORED_VERSION: u16 = 1;
+
+/// Every chunk that has ever been loaded in this world
+/// Written to `explored.bin` as soon as it grows, so fog of war survives restarts
+/// even when the world was never saved with "Save World"
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct ExploredChunks {
+    pub chunks: HashSet<ChunkPos>,
+}
+
+/// Save the explored chunk set to disk (positions sorted so the file is stable)
+pub fn save_explored<P: AsRef<Path>>(explored: &ExploredChunks, path: P) -> Result<(), SerializationError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut positions: Vec<ChunkPos> = explored.chunks.iter().copied().collect();
+    positions.sort_by_key(|pos| (pos.x, pos.y));
+
+    let mut body = Vec::with_capacity(4 + positions.len() * 8);
+    body.extend_from_slice(&(positions.len() as u32).to_le_bytes());
+    for pos in positions {
+        body.extend_from_slice(&pos.x.to_le_bytes());
+        body.extend_from_slice(&pos.y.to_le_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(body.len() + 10);
+    bytes.extend_from_slice(&EXPLORED_MAGIC);
+    bytes.extend_from_slice(&EXPLORED_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&body);
+    bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Load an explored chunk set saved by `save_explored`
+pub fn load_explored<P: AsRef<Path>>(path: P) -> Result<ExploredChunks, SerializationError> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < 14 || bytes[0..4] != EXPLORED_MAGIC {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != EXPLORED_VERSION {
+        return Err(SerializationError::InvalidVersion(version));
+    }
+
+    let body = &bytes[6..bytes.len() - 4];
+    let checksum = &bytes[bytes.len() - 4..];
+    if crc32fast::hash(body) != u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) {
+        return Err(SerializationError::InvalidChecksum);
+    }
+
+    let count = u32::from_le_bytes([body[0], body[1], body[2], body[3]]) as usize;
+    if body.len() != 4 + count * 8 {
+        return Err(SerializationError::InvalidChunkSize(body.len()));
+    }
+
+    let chunks = body[4..]
+        .chunks_exact(8)
+        .map(|record| {
+            ChunkPos::new(
+                i32::from_le_bytes([record[0], record[1], record[2], record[3]]),
+                i32::from_le_bytes([record[4], record[5], record[6], record[7]]),
+            )
+        })
+        .collect();
+    Ok(ExploredChunks { chunks })
+}
+
+/// Startup system that restores the explored chunk set
+pub fn load_explored_chunks(world: Res<WorldManager>, mut explored: ResMut<ExploredChunks>, mut failures: Failures) {
+    let path = world.get_explored_path();
+    if !path.exists() {
+        return;
+    }
+
+    match load_explored(&path) {
+        Ok(loaded) => {
+            info!("Loaded {} explored chunks", loaded.chunks.len());
+            *explored.bypass_change_detection() = loaded;
+        }
+        Err(e) => failures.report(format!("Failed to load explored chunks from {}", path.display()), e),
+    }
+}
+
+/// Records newly loaded chunks as explored and writes the set when it grows
+pub fn track_explored_chunks(world: Res<WorldManager>, mut explored: ResMut<ExploredChunks>, mut failures: Failures) {
+    if !world.is_changed() {
+        return;
+    }
+
+    let before = explored.chunks.len();
+    let explored = explored.bypass_change_detection();
+    explored.chunks.extend(world.active_chunks.keys().copied());
+    if explored.chunks.len() == before {
+        return;
+    }
+
+    if let Err(e) = save_explored(explored, world.get_explored_path()) {
+        failures.report("Failed to save explored chunks", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_save_and_load_explored() {
+        let path = env::temp_dir().join("test_explored.bin");
+
+        let mut explored = ExploredChunks::default();
+        explored.chunks.insert(ChunkPos::new(0, 0));
+        explored.chunks.insert(ChunkPos::new(-7, 12));
+        explored.chunks.insert(ChunkPos::new(i32::MAX, i32::MIN));
+
+        save_explored(&explored, &path).expect("Failed to save explored chunks");
+        let loaded = load_explored(&path).expect("Failed to load explored chunks");
+        assert_eq!(loaded, explored);
+
+        // A flipped bit is caught by the checksum
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[10] ^= 0xFF;
+        fs::write(&path, bytes).unwrap();
+        assert!(matches!(load_explored(&path), Err(SerializationError::InvalidChecksum)));
+
+        let _ = fs::remove_file(path);
+    }
+}
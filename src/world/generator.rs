@@ -1,27 +1,165 @@
-use crate::tiles::{ChunkData, ChunkPos, TILE_GRASS, TILE_DIRT, CHUNK_SIZE, LAYER_GROUND};
+use super::biome::{biome_at_chunk, patch_noise, world_tile};
+use super::deposits::deposit_in_chunk;
+use super::elevation::{elevation_at, ground_for_elevation};
+use super::structures::structures_near_chunk;
+use super::worldgen::WorldgenParams;
+use crate::tiles::{ChunkData, ChunkPos, TileId, TILE_GRASS, TILE_DIRT, TILE_SAND, CHUNK_SIZE, CHUNK_SIZE_I32, LAYER_DECORATION, LAYER_GROUND};
+use bevy::math::IVec2;
+use bevy::log::info_span;
+use serde::{Deserialize, Serialize};
+
+/// Version of the terrain generator; bump it whenever the same settings would generate different
+/// terrain, so older worlds are offered an upgrade (see `upgrade`) and their unpainted chunks can be
+/// regenerated (`ChunkData.generator_version`, see `model::stale_chunks`)
+pub const GENERATOR_VERSION: u16 = 1;
+
+/// Ground layer of generated terrain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroundPattern {
+    /// Alternating grass and dirt (what worlds from before generator settings were made with)
+    Checkerboard,
+    Grass,
+    Dirt,
+    Sand,
+    /// Forest, plains, rocky and marsh regions, see `biome`
+    #[default]
+    Biomes,
+    /// All grass with a marker at every chunk's origin tile, for testing entities and editor tools
+    DebugFlat,
+}
+
+impl GroundPattern {
+    /// In seed code order (append new patterns)
+    pub const ALL: [GroundPattern; 6] = [Self::Checkerboard, Self::Grass, Self::Dirt, Self::Sand, Self::Biomes, Self::DebugFlat];
+
+    /// Name used by `--world-type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroundPattern::Checkerboard => "checkerboard",
+            GroundPattern::Grass => "grass",
+            GroundPattern::Dirt => "dirt",
+            GroundPattern::Sand => "sand",
+            GroundPattern::Biomes => "biomes",
+            GroundPattern::DebugFlat => "debug_flat",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pattern| pattern.name() == name)
+    }
+}
+
+/// Ground tile `GroundPattern::DebugFlat` marks each chunk's origin (local tile 0, 0) with
+pub const DEBUG_MARKER_TILE: TileId = TILE_DIRT;
+
+/// Generator settings, fixed when a world is created (`WorldMetadata.generator`, copied into
+/// `WorldManager.generator` on startup)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneratorConfig {
+    pub ground: GroundPattern,
+    /// Seed of the biome map; not saved with the settings, `load_world_metadata` copies in the world seed
+    #[serde(skip)]
+    pub seed: u64,
+    /// Noise and threshold tuning; not saved either, read from `worldgen::WORLDGEN_PATH`
+    #[serde(skip)]
+    pub params: WorldgenParams,
+}
+
+impl GeneratorConfig {
+    /// Settings of worlds created before they were recorded
+    pub fn legacy() -> Self {
+        Self {
+            ground: GroundPattern::Checkerboard,
+            ..Default::default()
+        }
+    }
+}
 
 /// Generate a new chunk at the given position
-/// Generates a checkerboard pattern of grass and dirt tiles
-pub fn generate_chunk(position: ChunkPos) -> ChunkData {
-    // Start with empty chunk
+pub fn generate_chunk(position: ChunkPos, config: &GeneratorConfig) -> ChunkData {
     let mut chunk = ChunkData::empty(position);
+    generate_into(&mut chunk, config);
+    chunk
+}
 
-    // Create checkerboard pattern on ground layer
+/// Generate the terrain of `chunk.position` into an empty chunk (e.g. a pooled buffer)
+pub fn generate_into(chunk: &mut ChunkData, config: &GeneratorConfig) {
+    let _span = info_span!("generate_chunk", x = chunk.position.x, y = chunk.position.y).entered();
+    chunk.generator_version = GENERATOR_VERSION;
+    chunk.edited = false;
+    let biome = biome_at_chunk(chunk.position, config.seed);
+    let (structures, deposit) = match config.ground {
+        GroundPattern::Biomes => (structures_near_chunk(chunk.position, config.seed), deposit_in_chunk(chunk.position, config.seed)),
+        _ => (Vec::new(), None),
+    };
     for y in 0..CHUNK_SIZE {
         for x in 0..CHUNK_SIZE {
-            // Alternate between grass and dirt based on tile coordinates
-            let tile = if (x + y) % 2 == 0 {
-                TILE_GRASS
-            } else {
-                TILE_DIRT
+            let tile = match config.ground {
+                // Alternate between grass and dirt based on tile coordinates
+                GroundPattern::Checkerboard if (x + y) % 2 == 0 => TILE_GRASS,
+                GroundPattern::Checkerboard => TILE_DIRT,
+                GroundPattern::Grass => TILE_GRASS,
+                GroundPattern::DebugFlat if x == 0 && y == 0 => DEBUG_MARKER_TILE,
+                GroundPattern::DebugFlat => TILE_GRASS,
+                GroundPattern::Dirt => TILE_DIRT,
+                GroundPattern::Sand => TILE_SAND,
+                GroundPattern::Biomes => {
+                    let tile = world_tile(chunk.position, x, y);
+                    let elevation = elevation_at(config.seed, tile, &config.params);
+                    chunk.elevation[y * CHUNK_SIZE + x] = elevation;
+                    // Structures are stamped over the terrain, the first one listed winning where
+                    // they overlap; then lakes, shores and rocky tops; then the chunk's deposit;
+                    // the biome fills the rest
+                    structures
+                        .iter()
+                        .find_map(|structure| structure.ground_tile(tile, config.seed))
+                        .or_else(|| ground_for_elevation(elevation, &config.params))
+                        .or_else(|| deposit.and_then(|deposit| deposit.ground_tile(tile, config.seed)))
+                        .unwrap_or_else(|| biome.ground_tile(patch_noise(config.seed, tile, config.params.patch_size)))
+                }
             };
             chunk.set_tile(LAYER_GROUND, x, y, tile);
+            // Biomes scatter flowers, pebbles and tufts over the ground; the flat patterns stay bare
+            if config.ground == GroundPattern::Biomes {
+                let decoration = biome.decoration(config.seed, world_tile(chunk.position, x, y), tile);
+                chunk.set_tile(LAYER_DECORATION, x, y, decoration);
+            }
         }
     }
 
-    // Decoration and overlay layers remain empty
+    // Settlements house their NPCs in the chunk holding their center
+    let origin = IVec2::new(chunk.position.x, chunk.position.y) * CHUNK_SIZE_I32;
+    for structure in &structures {
+        if (structure.center - origin).cmpge(IVec2::ZERO).all() && (structure.center - origin).cmplt(IVec2::splat(CHUNK_SIZE_I32)).all() {
+            chunk.settlers.extend(structure.settlers(config.seed));
+        }
+    }
 
-    chunk
+    // The overlay layer remains empty (biomes and structures vary tree density instead, see `WorldManager::tree_density`)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{CHUNK_AREA, LAYER_OVERLAY};
+
+    #[test]
+    fn test_debug_flat() {
+        let config = GeneratorConfig { ground: GroundPattern::DebugFlat, ..Default::default() };
+        for position in [ChunkPos::new(0, 0), ChunkPos::new(-3, 7)] {
+            let chunk = generate_chunk(position, &config);
+            assert_eq!(chunk.get_tile(LAYER_GROUND, 0, 0), Some(DEBUG_MARKER_TILE));
+            let grass = chunk.layers[LAYER_GROUND].iter().filter(|&&tile| tile == TILE_GRASS).count();
+            assert_eq!(grass, CHUNK_AREA - 1);
+            assert!(chunk.layers[LAYER_DECORATION].iter().chain(&chunk.layers[LAYER_OVERLAY]).all(|&tile| tile == 0));
+            assert!(chunk.settlers.is_empty());
+        }
+
+        for pattern in GroundPattern::ALL {
+            assert_eq!(GroundPattern::from_name(pattern.name()), Some(pattern));
+        }
+    }
 }
 
 // Future: Add more sophisticated generation
@@ -1,200 +1,447 @@
-use super::{generator, manager::WorldManager, serialization};
+use super::model::{self, ChunkSource};
+use super::origin::WorldOrigin;
+use super::diagnostics::WorldActivity;
+use super::{backup, generator, manager::{TileModificationEvent, WorldManager}, metadata::WorldMetadata, protection::TileEditBlocked, serialization};
+use crate::assets::SpriteAssets;
+use crate::debug_hud::DebugSettings;
+use crate::entities::{
+    despawn_chunk_entities, entities_by_chunk, spawn_saved_entity, take_chunk_entities, ChunkEntity, ChunkEntityFilter,
+};
+use crate::error::{Failures, WorldError};
+use crate::locale::Locale;
+use crate::mods::RegistriesChanged;
+use crate::stats::StatEvent;
+use crate::toast::Toast;
+use crate::tiles::autotile::{self, NEIGHBOR_OFFSETS};
+use crate::tiles::chunk::tilemap_tile;
 use crate::tiles::{
-    chunk::coords, Chunk, ChunkData, ChunkPos, DirtyChunk, CHUNK_LOAD_RADIUS, CHUNK_UNLOAD_RADIUS,
-    TILE_DISPLAY_SIZE,
+    Chunk, ChunkData, ChunkPos, DirtyChunk, TileId, TileRegistry, CHUNK_LOAD_RADIUS, CHUNK_SIZE, CHUNK_SIZE_I32, LAYER_GROUND,
+    TILE_DISPLAY_SIZE, TILE_GRASS,
 };
 use bevy::prelude::*;
-use bevy::sprite_render::{TilemapChunk, TilemapChunkTileData};
+use bevy::sprite_render::{TileData, TilemapChunk, TilemapChunkTileData};
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool};
+use std::collections::HashMap;
+#[cfg(feature = "debug_chunks")]
 use std::collections::HashSet;
 
 /// System to track camera position and trigger chunk loading/unloading
 pub fn update_camera_chunk(
     camera_query: Query<&Transform, With<Camera2d>>,
     mut world: ResMut<WorldManager>,
+    origin: Res<WorldOrigin>,
+    debug: Res<DebugSettings>,
 ) {
     if let Ok(camera_transform) = camera_query.single() {
-        let camera_pos = camera_transform.translation.truncate();
-        let chunk_pos = coords::world_to_chunk(camera_pos);
+        let chunk_pos = origin.chunk_at(camera_transform.translation.truncate());
 
         // Only trigger loading if camera moved to a new chunk
         if world.camera_chunk != Some(chunk_pos) {
             world.update_camera_position(chunk_pos);
-            info!("Camera moved to chunk {:?}", chunk_pos);
+            if debug.log_chunks {
+                info!("Camera moved to chunk {:?}", chunk_pos);
+            }
         }
     }
 }
 
-/// System to load chunks around the camera
+/// System to load chunks around the camera: cached chunks are spawned right away, the rest are
+/// read or generated on the `AsyncComputeTaskPool` and spawned by `poll_chunk_reads`, so a ring of
+/// new chunks after a fast camera move doesn't stall the frame
+#[allow(clippy::too_many_arguments)]
 pub fn load_chunks_around_camera(
     mut commands: Commands,
     mut world: ResMut<WorldManager>,
-    asset_server: Res<AssetServer>,
+    sprites: Res<SpriteAssets>,
     camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
     window_query: Query<&Window>,
+    origin: Res<WorldOrigin>,
+    debug: Res<DebugSettings>,
+    mut activity: ResMut<WorldActivity>,
 ) {
     let Some(camera_chunk) = world.camera_chunk else {
         return;
     };
 
     // Calculate dynamic load radius based on zoom level
-    let load_radius = calculate_load_radius(&camera_query, &window_query);
+    let load_radius = world
+        .load_radius_override
+        .unwrap_or_else(|| calculate_load_radius(&camera_query, &window_query));
 
     // Get chunks that should be loaded
-    let chunks_to_load = camera_chunk.chunks_in_radius(load_radius);
+    let chunks_to_load = model::chunks_to_load(&world, camera_chunk, load_radius);
     #[cfg(feature = "debug_chunks")]
     let has_loaded_chunks = !chunks_to_load.is_empty();
 
+    let world = world.as_mut();
+    let pool = AsyncComputeTaskPool::get();
     for chunk_pos in chunks_to_load {
-        // Skip if already loaded
-        if world.is_loaded(&chunk_pos) {
+        // Cached data was checked when it was first read
+        if let Some(chunk_data) = world.copy_cached_chunk(&chunk_pos) {
+            spawn_chunk(&mut commands, world, &sprites, &origin, chunk_data);
+            activity.chunks_loaded += 1;
+            if debug.log_chunks {
+                info!("Loaded chunk {:?} with {} layers", chunk_pos, crate::tiles::NUM_LAYERS);
+            }
+            continue;
+        }
+        // Unloaded a moment ago and still being written: the bytes in flight are the chunk
+        let unsaved = world.pending_saves.get(&chunk_pos).map(|save| serialization::decode_chunk(&save.bytes));
+        if let Some(Ok(chunk_data)) = unsaved {
+            spawn_chunk(&mut commands, world, &sprites, &origin, chunk_data);
+            activity.chunks_loaded += 1;
             continue;
         }
 
-        // Try to load from cache first
-        let chunk_data = if let Some(cached) = world.get_cached_chunk(&chunk_pos) {
-            cached.clone()
-        } else {
-            // Try to load from disk
-            let chunk_path = world.get_chunk_path(&chunk_pos);
-            if serialization::chunk_exists(&chunk_path) {
-                match serialization::load_chunk(&chunk_path) {
-                    Ok(data) => {
-                        info!("Loaded chunk {:?} from disk", chunk_pos);
-                        data
-                    }
-                    Err(e) => {
-                        warn!("Failed to load chunk {:?}: {}, generating new", chunk_pos, e);
-                        generator::generate_chunk(chunk_pos)
+        let store = world.store.clone();
+        let path = world.get_chunk_path(&chunk_pos);
+        let generator = world.generator;
+        let buffer = world.pool.take_chunk(chunk_pos);
+        let task = pool.spawn(async move {
+            let _span = info_span!("read_chunk", x = chunk_pos.x, y = chunk_pos.y).entered();
+            model::read_uncached_chunk(&*store, &path, &generator, buffer)
+        });
+        world.pending_loads.insert(chunk_pos, task);
+    }
+
+    // Print chunk grid after loading
+    #[cfg(feature = "debug_chunks")]
+    if has_loaded_chunks && debug.log_chunks {
+        let visible_chunks = calculate_visible_chunks(&camera_query, &window_query, &origin);
+        print_chunk_grid(world, camera_chunk, visible_chunks, load_radius);
+    }
+}
+
+/// Spawns the chunks whose reads finished: unknown tiles are reported, corrupted files recovered
+/// from backups, files from a newer build refused (reported and left unloaded) and other read
+/// errors replaced with a generated chunk, like `model::read_chunk`'s callers do. A chunk cached in the meantime (prefetched, say) keeps its cached data
+/// The first saved chunk from an older generator that nobody painted (`model::is_stale`) brings up
+/// a toast offering `regenerate stale`, once per session
+#[allow(clippy::too_many_arguments)]
+pub fn poll_chunk_reads(
+    mut commands: Commands,
+    mut world: ResMut<WorldManager>,
+    sprites: Res<SpriteAssets>,
+    tile_registry: Res<TileRegistry>,
+    origin: Res<WorldOrigin>,
+    mut toasts: MessageWriter<Toast>,
+    mut failures: Failures,
+    locale: Res<Locale>,
+    debug: Res<DebugSettings>,
+    mut activity: ResMut<WorldActivity>,
+    mut stale_offered: Local<bool>,
+) {
+    if world.pending_loads.is_empty() {
+        return;
+    }
+    let world = world.as_mut();
+    let mut finished = Vec::new();
+    world.pending_loads.retain(|pos, task| match block_on(future::poll_once(task)) {
+        Some(read) => {
+            finished.push((*pos, read));
+            false
+        }
+        None => true,
+    });
+
+    for (chunk_pos, read) in finished {
+        if let Some(spare) = read.spare {
+            world.pool.recycle_chunk(spare);
+        }
+        let chunk_data = match read.result {
+            Ok((data, _)) if world.chunk_cache.contains_key(&chunk_pos) => {
+                world.pool.recycle_chunk(data);
+                let cached = &world.chunk_cache[&chunk_pos];
+                world.pool.copy_of(cached)
+            }
+            Ok((data, source)) => {
+                if debug.log_chunks {
+                    match source {
+                        ChunkSource::Cache => {}
+                        ChunkSource::Disk => info!("Loaded chunk {:?} from disk", chunk_pos),
+                        ChunkSource::Generated => info!("Generating new chunk {:?}", chunk_pos),
                     }
                 }
-            } else {
-                // Generate new chunk
-                info!("Generating new chunk {:?}", chunk_pos);
-                generator::generate_chunk(chunk_pos)
+                if let Some(tile) = tile_registry.find_unknown_tile(&data) {
+                    report_unknown_tile(&mut failures, chunk_pos, tile, source);
+                }
+                if source == ChunkSource::Disk && model::is_stale(&data) && !*stale_offered {
+                    info!(
+                        "Chunk {:?} was made by generator v{}, this build has v{}",
+                        chunk_pos,
+                        data.generator_version,
+                        generator::GENERATOR_VERSION
+                    );
+                    toasts.write(Toast::info(locale.text("toast-stale-chunks")));
+                    *stale_offered = true;
+                }
+                data
+            }
+            Err(e) if e.is_corruption() => recover_chunk(world, chunk_pos, &e, &mut toasts, &locale),
+            Err(e @ serialization::SerializationError::InvalidVersion(_)) => {
+                failures.report(
+                    format!("Chunk ({}, {}) was saved by a newer version, not loading it", chunk_pos.x, chunk_pos.y),
+                    e,
+                );
+                world.refused_chunks.insert(chunk_pos);
+                continue;
+            }
+            Err(e) => {
+                failures.report(
+                    format!("Failed to load chunk ({}, {}), showing a generated one", chunk_pos.x, chunk_pos.y),
+                    e,
+                );
+                generator::generate_chunk(chunk_pos, &world.generator)
             }
         };
 
-        // Get world position for chunk
-        let world_pos = chunk_pos.to_world(crate::tiles::CHUNK_PIXEL_SIZE);
-
-        // Spawn one entity per layer
-        let mut layer_entities = [Entity::PLACEHOLDER; crate::tiles::NUM_LAYERS];
-        for layer_idx in 0..crate::tiles::NUM_LAYERS {
-            let tile_data = chunk_data.layer_to_tilemap_data(layer_idx);
-            let z_pos = crate::tiles::layer_z_position(layer_idx);
-
-            let entity = commands
-                .spawn((
-                    TilemapChunk {
-                        chunk_size: UVec2::splat(crate::tiles::CHUNK_SIZE as u32),
-                        tile_display_size: UVec2::splat(TILE_DISPLAY_SIZE),
-                        tileset: asset_server.load("tilesets/terrain_array.png"),
-                        ..default()
-                    },
-                    TilemapChunkTileData(tile_data),
-                    Transform::from_xyz(world_pos.x, world_pos.y, z_pos),
-                    Chunk::with_layer(chunk_pos, layer_idx),
-                ))
-                .id();
-
-            layer_entities[layer_idx] = entity;
+        spawn_chunk(&mut commands, world, &sprites, &origin, chunk_data);
+        activity.chunks_loaded += 1;
+        if debug.log_chunks {
+            info!("Loaded chunk {:?} with {} layers", chunk_pos, crate::tiles::NUM_LAYERS);
         }
+    }
+}
 
-        // Register in world manager
-        world.register_chunk(chunk_pos, layer_entities);
-        world.cache_chunk(chunk_data);
+/// Spawns one tilemap entity per layer for a chunk's data, registers them and caches the data
+fn spawn_chunk(commands: &mut Commands, world: &mut WorldManager, sprites: &SpriteAssets, origin: &WorldOrigin, chunk_data: ChunkData) {
+    let chunk_pos = chunk_data.position;
+    // Render position for the chunk, relative to the world origin
+    let translation = origin.chunk_translation(chunk_pos);
+
+    // Spawn one entity per layer
+    let mut layer_entities = [Entity::PLACEHOLDER; crate::tiles::NUM_LAYERS];
+    for layer_idx in 0..crate::tiles::NUM_LAYERS {
+        let tile_data = world.pool.tilemap_data(&chunk_data, layer_idx);
+        let z_pos = crate::tiles::layer_z_position(layer_idx);
+
+        let entity = commands
+            .spawn((
+                TilemapChunk {
+                    chunk_size: UVec2::splat(crate::tiles::CHUNK_SIZE as u32),
+                    tile_display_size: UVec2::splat(TILE_DISPLAY_SIZE),
+                    tileset: sprites.terrain.clone(),
+                    ..default()
+                },
+                TilemapChunkTileData(tile_data),
+                Transform::from_xyz(translation.x, translation.y, z_pos),
+                Chunk::with_layer(chunk_pos, layer_idx),
+            ))
+            .id();
+
+        layer_entities[layer_idx] = entity;
+    }
 
-        info!("Loaded chunk {:?} with {} layers", chunk_pos, crate::tiles::NUM_LAYERS);
+    // Creatures and trees saved with the chunk
+    for saved in &chunk_data.entities {
+        if spawn_saved_entity(commands, saved, sprites).is_none() {
+            warn!("Skipped an unknown entity {:?} saved in chunk {:?}", saved.kind(), chunk_pos);
+        }
     }
 
-    // Print chunk grid after loading
-    #[cfg(feature = "debug_chunks")]
-    if has_loaded_chunks {
-        let visible_chunks = calculate_visible_chunks(&camera_query, &window_query);
-        print_chunk_grid(&world, camera_chunk, visible_chunks, load_radius);
+    // Register in world manager
+    world.register_chunk(chunk_pos, layer_entities);
+    world.cache_chunk(chunk_data);
+}
+
+/// Saved chunks with unknown tiles are kept as they are (the tiles may belong to a mod pack that
+/// isn't installed); a generated one means the generator and the registry disagree
+fn report_unknown_tile(failures: &mut Failures, chunk: ChunkPos, tile: TileId, source: ChunkSource) {
+    if source == ChunkSource::Generated {
+        let reason = format!("produced unregistered tile id {}", tile);
+        failures.report("World generation failed", WorldError::Generation { chunk, reason });
+    } else {
+        let context = format!("Chunk ({}, {}) loaded with missing tiles", chunk.x, chunk.y);
+        failures.report(context, WorldError::UnknownTile { chunk, tile });
     }
 }
 
-/// System to unload chunks far from the camera
+/// Handle a chunk file that failed to decode: quarantine it, fall back to the
+/// newest readable backup, and only regenerate if there is none
+fn recover_chunk(
+    world: &WorldManager,
+    chunk_pos: ChunkPos,
+    error: &serialization::SerializationError,
+    toasts: &mut MessageWriter<Toast>,
+    locale: &Locale,
+) -> ChunkData {
+    warn!("Chunk {:?} is corrupted: {}", chunk_pos, error);
+    let recovery = backup::recover_corrupted_chunk(
+        chunk_pos,
+        &world.get_chunk_path(&chunk_pos),
+        &world.get_backup_dir(),
+        &world.get_quarantine_dir(),
+    );
+    if let Some(quarantined) = &recovery.quarantined {
+        warn!("Moved corrupted chunk file to {:?}", quarantined);
+    }
+
+    match recovery.restored {
+        Some((data, backup)) => {
+            info!("Restored chunk {:?} from {:?}", chunk_pos, backup.path);
+            toasts.write(Toast::warning(locale.format("toast-chunk-restored", &[
+                ("x", chunk_pos.x.into()),
+                ("y", chunk_pos.y.into()),
+                ("age", backup::format_age(backup.timestamp).into()),
+            ])));
+            data
+        }
+        None => {
+            warn!("No usable backup for chunk {:?}, regenerating", chunk_pos);
+            toasts.write(Toast::error(locale.format("toast-chunk-regenerated", &[
+                ("x", chunk_pos.x.into()),
+                ("y", chunk_pos.y.into()),
+            ])));
+            generator::generate_chunk(chunk_pos, &world.generator)
+        }
+    }
+}
+
+/// Drop a chunk's entities and cached data without saving, so the loader
+/// reads it from disk again next frame (used after replacing a chunk file)
+/// The creatures and trees standing in it go too; they come back with the new data
+pub fn reload_chunk(
+    commands: &mut Commands,
+    world: &mut WorldManager,
+    chunk_entities: &Query<ChunkEntity, ChunkEntityFilter>,
+    chunk_pos: ChunkPos,
+) {
+    world.pending_loads.remove(&chunk_pos);
+    world.refused_chunks.remove(&chunk_pos);
+    despawn_chunk_entities(commands, chunk_entities, chunk_pos);
+    if let Some(layer_entities) = world.unregister_chunk(&chunk_pos) {
+        for layer_entity in layer_entities {
+            commands.entity(layer_entity).despawn();
+        }
+    }
+    world.uncache_chunk(&chunk_pos);
+    world.clear_dirty(&chunk_pos);
+}
+
+/// Throw away a chunk's saved file (kept as a backup, so `restore` undoes this) and cached data and
+/// generate it again with the current generator; a loaded chunk's tilemaps are rewritten in place
+pub fn regenerate_chunk(
+    world: &mut WorldManager,
+    tilemaps: &mut Query<&mut TilemapChunkTileData>,
+    chunk_pos: ChunkPos,
+) -> std::io::Result<()> {
+    let chunk_path = world.get_chunk_path(&chunk_pos);
+    // A failed write leaves the chunk cached, and that is dropped below
+    world.wait_for_save(&chunk_pos);
+    world.store.backup(&chunk_path, &world.get_backup_dir(), world.backup_retention)?;
+    match world.store.remove(&chunk_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    world.pending_loads.remove(&chunk_pos);
+    world.refused_chunks.remove(&chunk_pos);
+    world.clear_dirty(&chunk_pos);
+    if let Some(old) = world.uncache_chunk(&chunk_pos) {
+        world.pool.recycle_chunk(old);
+    }
+    if !world.is_loaded(&chunk_pos) {
+        return Ok(());
+    }
+
+    let chunk_data = generator::generate_chunk(chunk_pos, &world.generator);
+    for layer_idx in 0..crate::tiles::NUM_LAYERS {
+        let Some(entity) = world.get_chunk_layer_entity(&chunk_pos, layer_idx) else {
+            continue;
+        };
+        if let Ok(mut tile_data) = tilemaps.get_mut(entity) {
+            let fresh = world.pool.tilemap_data(&chunk_data, layer_idx);
+            world.pool.recycle_tilemap_data(std::mem::replace(&mut tile_data.0, fresh));
+        }
+    }
+    world.cache_chunk(chunk_data);
+    Ok(())
+}
+
+/// System to unload chunks far from the camera; their buffers go back to the chunk pool and dirty
+/// ones are written on the `IoTaskPool` (`poll_chunk_saves` collects the results)
+/// Creatures and trees standing in an unloading chunk are saved with it and despawned
+#[allow(clippy::too_many_arguments)]
 pub fn unload_distant_chunks(
     mut commands: Commands,
     mut world: ResMut<WorldManager>,
-    chunk_query: Query<(Entity, &Chunk)>,
+    mut tilemaps: Query<&mut TilemapChunkTileData>,
+    entities: Query<ChunkEntity, ChunkEntityFilter>,
     camera_query: Query<(&Transform, &Projection), With<Camera2d>>,
     window_query: Query<&Window>,
+    #[cfg(feature = "debug_chunks")] origin: Res<WorldOrigin>,
+    debug: Res<DebugSettings>,
 ) {
     let Some(camera_chunk) = world.camera_chunk else {
         return;
     };
 
     // Calculate dynamic radii based on zoom level
-    let load_radius = calculate_load_radius(&camera_query, &window_query);
-    let unload_radius = calculate_unload_radius(load_radius);
+    let load_radius = world
+        .load_radius_override
+        .unwrap_or_else(|| calculate_load_radius(&camera_query, &window_query));
+    let chunks_to_unload = model::chunks_to_unload(&world, camera_chunk, model::unload_radius(load_radius));
+    // Reads the camera has left behind are cancelled
+    let unload_radius = model::unload_radius(load_radius);
+    world.pending_loads.retain(|pos, _| camera_chunk.chebyshev_distance(pos) <= unload_radius);
 
-    let mut chunks_to_unload = Vec::new();
-
-    // Find chunks outside the unload radius
-    for (entity, chunk) in chunk_query.iter() {
-        let distance = camera_chunk.chebyshev_distance(&chunk.position);
-        if distance > unload_radius {
-            chunks_to_unload.push((entity, chunk.position));
-        }
-    }
-
-    // Unload chunks
     #[cfg(feature = "debug_chunks")]
     let has_unloaded_chunks = !chunks_to_unload.is_empty();
 
-    for (entity, chunk_pos) in chunks_to_unload {
-        // Note: entity is just one layer entity, we need to despawn all layers
-        // Save if dirty
-        if world.is_dirty(&chunk_pos) {
-            if let Some(chunk_data) = world.get_cached_chunk(&chunk_pos) {
-                let chunk_path = world.get_chunk_path(&chunk_pos);
-                match serialization::save_chunk(chunk_data, &chunk_path) {
-                    Ok(_) => {
-                        info!("Saved chunk {:?} to disk", chunk_pos);
-                        world.clear_dirty(&chunk_pos);
-                    }
-                    Err(e) => {
-                        error!("Failed to save chunk {:?}: {}", chunk_pos, e);
-                    }
-                }
+    let mut by_chunk = if chunks_to_unload.is_empty() {
+        HashMap::new()
+    } else {
+        entities_by_chunk(&entities)
+    };
+    for chunk_pos in chunks_to_unload {
+        // Creatures go into the chunk before it's released (and written)
+        let (chunk_entities, records) = take_chunk_entities(&mut by_chunk, chunk_pos);
+        if world.get_cached_chunk(&chunk_pos).is_some() {
+            if model::stash_entities(&mut world, chunk_pos, records) {
+                world.mark_dirty(chunk_pos);
+            }
+            for entity in chunk_entities {
+                commands.entity(entity).despawn();
             }
         }
 
         // Despawn all layer entities
         if let Some(layer_entities) = world.unregister_chunk(&chunk_pos) {
             for layer_entity in layer_entities {
+                if let Ok(mut tile_data) = tilemaps.get_mut(layer_entity) {
+                    world.pool.recycle_tilemap_data(std::mem::take(&mut tile_data.0));
+                }
                 commands.entity(layer_entity).despawn();
             }
         }
-        world.uncache_chunk(&chunk_pos);
 
-        info!("Unloaded chunk {:?} with all layers", chunk_pos);
+        if model::release_chunk(&mut world, chunk_pos) && debug.log_chunks {
+            info!("Saving chunk {:?} in the background", chunk_pos);
+        }
+
+        if debug.log_chunks {
+            info!("Unloaded chunk {:?} with all layers", chunk_pos);
+        }
     }
 
     // Print chunk grid after unloading
     #[cfg(feature = "debug_chunks")]
-    if has_unloaded_chunks {
-        let visible_chunks = calculate_visible_chunks(&camera_query, &window_query);
+    if has_unloaded_chunks && debug.log_chunks {
+        let visible_chunks = calculate_visible_chunks(&camera_query, &window_query, &origin);
         print_chunk_grid(&world, camera_chunk, visible_chunks, load_radius);
     }
 }
 
-/// System to periodically save dirty chunks (autosave)
-pub fn autosave_dirty_chunks(world: Res<WorldManager>) {
-    for chunk_pos in world.get_dirty_chunks() {
-        if let Some(chunk_data) = world.get_cached_chunk(&chunk_pos) {
-            let chunk_path = world.get_chunk_path(&chunk_pos);
-            match serialization::save_chunk(chunk_data, &chunk_path) {
-                Ok(_) => {
-                    debug!("Autosaved chunk {:?}", chunk_pos);
-                }
-                Err(e) => {
-                    error!("Failed to autosave chunk {:?}: {}", chunk_pos, e);
-                }
-            }
+/// Collects the chunk saves started by unloading (`model::release_chunk`); a failed one is reported
+/// and its chunk stays cached and dirty for autosave to retry
+pub fn poll_chunk_saves(mut world: ResMut<WorldManager>, mut failures: Failures, debug: Res<DebugSettings>) {
+    if world.pending_saves.is_empty() {
+        return;
+    }
+    for (chunk_pos, result) in world.poll_saves() {
+        match result {
+            Ok(()) if debug.log_chunks => info!("Saved chunk {:?} to disk", chunk_pos),
+            Ok(()) => {}
+            Err(e) => failures.report(format!("Failed to save chunk ({}, {})", chunk_pos.x, chunk_pos.y), e),
         }
     }
 }
@@ -216,49 +463,198 @@ pub fn log_world_stats(world: Res<WorldManager>) {
     debug!("World stats: {}", stats);
 }
 
-/// System to apply pending tile modifications to both cache and visual tilemap
+/// Applies queued tile modifications: deduplicated per tile (the last write wins), grouped by chunk
+/// and capped at `MAX_TILE_CHANGES_PER_FRAME`, with the rest carried over to the next frame
+/// Ground tiles that became grass are counted as restored (applied changes always differ from the old tile)
+/// Changes inside protected regions are dropped here, whoever made them (see `protection`)
+#[allow(clippy::too_many_arguments)]
 pub fn apply_tile_modifications(
+    mut modifications: MessageReader<TileModificationEvent>,
+    metadata: Res<WorldMetadata>,
+    mut blocked: MessageWriter<TileEditBlocked>,
+    mut pending: ResMut<model::PendingTileChanges>,
     mut world: ResMut<WorldManager>,
     mut chunk_query: Query<(&Chunk, &mut TilemapChunkTileData)>,
+    mut activity: ResMut<WorldActivity>,
+    mut stat_events: MessageWriter<StatEvent>,
 ) {
-    use crate::tiles::chunk::coords;
-    use crate::tiles::{TILE_EMPTY, CHUNK_SIZE};
-    use bevy::sprite_render::TileData;
-
-    let modifications = world.take_tile_modifications();
-    if modifications.is_empty() {
+    for modification in modifications.read() {
+        if let Some(region) = metadata.protected_region_at(modification.position()) {
+            if modification.painted {
+                blocked.write(TileEditBlocked { position: modification.position(), region: region.name.clone() });
+            }
+            continue;
+        }
+        if modification.painted {
+            model::mark_painted(&mut world, modification);
+        }
+        pending.push(modification);
+    }
+    if pending.is_empty() {
         return;
     }
+    let _span = info_span!("apply_tile_changes", pending = pending.len()).entered();
+    let applied = model::apply_pending(&mut world, &mut pending, model::MAX_TILE_CHANGES_PER_FRAME);
+    activity.tile_changes_applied += applied.len() as u32;
+    stat_events.write_batch(
+        applied
+            .iter()
+            .filter(|update| update.layer == LAYER_GROUND && update.tile_id == TILE_GRASS)
+            .map(|_| StatEvent::GrassRestored),
+    );
+    show_tile_updates(&world, &mut chunk_query, &applied);
+}
 
-    for modification in modifications {
-        // Convert world position to chunk position
-        let chunk_pos = coords::world_to_chunk(Vec2::new(modification.world_x, modification.world_y));
+/// Apply one tile change right away to the cached chunk and its tilemap entity (chunks not in the
+/// cache are skipped)
+pub fn apply_tile_modification(
+    world: &mut WorldManager,
+    chunk_query: &mut Query<(&Chunk, &mut TilemapChunkTileData)>,
+    modification: &TileModificationEvent,
+) {
+    if let Some(update) = model::apply_modification(world, modification) {
+        show_tile_updates(world, chunk_query, &[update]);
+    }
+}
 
-        // Update the cache
-        if let Some(chunk_data) = world.chunk_cache.get_mut(&chunk_pos) {
-            let (local_x, local_y) = coords::world_to_local_tile(Vec2::new(modification.world_x, modification.world_y));
+/// Draws applied tile changes on the layer entities of spawned chunks
+/// Bevy repacks and re-uploads a layer's whole tile texture when its `TilemapChunkTileData` changes,
+/// so tiles are written without change detection and each touched layer is marked changed once:
+/// one upload per layer per frame, however many of its tiles changed, and none for untouched layers
+fn show_tile_updates(
+    world: &WorldManager,
+    chunk_query: &mut Query<(&Chunk, &mut TilemapChunkTileData)>,
+    updates: &[model::TileUpdate],
+) {
+    let mut touched = Vec::new();
+    for update in updates {
+        let Some(layers) = world.active_chunks.get(&update.chunk) else {
+            continue;
+        };
+        let Ok((_, mut tile_data)) = chunk_query.get_mut(layers[update.layer]) else {
+            continue;
+        };
+        let Some(tile) = tile_data.bypass_change_detection().0.get_mut(update.index) else {
+            continue;
+        };
+        // Keep the tile's tint, so the season doesn't have to retint the layer
+        let color = tile.map(|tile| tile.color);
+        *tile = tilemap_tile(update.tile_id).map(|new| TileData {
+            color: color.unwrap_or(new.color),
+            ..new
+        });
+        touched.push(layers[update.layer]);
+    }
 
-            if chunk_data.set_tile(modification.layer, local_x, local_y, modification.tile_id) {
-                // Mark chunk as dirty
-                world.mark_dirty(chunk_pos);
+    touched.sort_unstable();
+    touched.dedup();
+    for layer in touched {
+        if let Ok((_, mut tile_data)) = chunk_query.get_mut(layer) {
+            tile_data.set_changed();
+        }
+    }
+}
 
-                // Find and update the visual tilemap entity for this specific layer
-                for (chunk, mut tile_data) in chunk_query.iter_mut() {
-                    if chunk.position == chunk_pos && chunk.layer == modification.layer {
-                        let index = local_y * CHUNK_SIZE + local_x;
-                        if index < tile_data.0.len() {
-                            tile_data.0[index] = if modification.tile_id == TILE_EMPTY {
-                                None
-                            } else {
-                                Some(TileData::from_tileset_index((modification.tile_id - 1) as u16))
-                            };
-                        }
-                        break;
-                    }
-                }
+/// Draws dirt next to grass with edge tiles (see `tiles::autotile`) once the tileset has them
+/// Ground layers that spawned or changed are redrawn with their loaded neighbours, whose border
+/// tiles may have gained or lost a grass neighbour; like `apply_season_tint`, layers are only
+/// written when an index differs, so the redraw doesn't re-trigger itself
+pub fn update_ground_edges(
+    world: Res<WorldManager>,
+    sprites: Res<SpriteAssets>,
+    mut chunks: Query<(&Chunk, Mut<TilemapChunkTileData>)>,
+) {
+    if !autotile::has_edge_tiles(sprites.terrain_layers) {
+        return;
+    }
+    let mut stale = std::collections::HashSet::new();
+    for (chunk, tile_data) in chunks.iter() {
+        if chunk.layer == LAYER_GROUND && tile_data.is_changed() {
+            stale.insert(chunk.position);
+            stale.extend(NEIGHBOR_OFFSETS.map(|(dx, dy)| ChunkPos::new(chunk.position.x + dx, chunk.position.y + dy)));
+        }
+    }
+    if stale.is_empty() {
+        return;
+    }
+
+    for (chunk, mut tile_data) in chunks.iter_mut() {
+        if chunk.layer != LAYER_GROUND || !stale.contains(&chunk.position) {
+            continue;
+        }
+        let Some(data) = world.get_cached_chunk(&chunk.position) else {
+            continue;
+        };
+        let ground = &data.layers[LAYER_GROUND];
+        // Neighbours in unloaded chunks count as not grass
+        let is_grass = |x: i32, y: i32| {
+            if (0..CHUNK_SIZE_I32).contains(&x) && (0..CHUNK_SIZE_I32).contains(&y) {
+                return ground[y as usize * CHUNK_SIZE + x as usize] == TILE_GRASS;
+            }
+            let neighbor = ChunkPos::new(chunk.position.x + x.div_euclid(CHUNK_SIZE_I32), chunk.position.y + y.div_euclid(CHUNK_SIZE_I32));
+            let (local_x, local_y) = (x.rem_euclid(CHUNK_SIZE_I32) as usize, y.rem_euclid(CHUNK_SIZE_I32) as usize);
+            world
+                .get_cached_chunk(&neighbor)
+                .is_some_and(|data| data.get_tile(LAYER_GROUND, local_x, local_y) == Some(TILE_GRASS))
+        };
+
+        let mut changed = false;
+        for (index, tile) in tile_data.bypass_change_detection().0.iter_mut().enumerate() {
+            let Some(tile) = tile else {
+                continue;
+            };
+            let (x, y) = ((index % CHUNK_SIZE) as i32, (index / CHUNK_SIZE) as i32);
+            let mask = autotile::grass_mask(|dx, dy| is_grass(x + dx, y + dy));
+            let Some(tileset_index) = autotile::ground_tileset_index(ground[index], mask) else {
+                continue;
+            };
+            if tile.tileset_index != tileset_index {
+                tile.tileset_index = tileset_index;
+                changed = true;
             }
         }
+        if changed {
+            tile_data.set_changed();
+        }
+    }
+}
+
+/// Redraws loaded chunks from the cache after mod packs are reloaded, and reports chunks that now
+/// hold tiles no pack defines (their data is kept as it is, like when loading)
+pub fn refresh_chunks_on_registry_change(
+    mut changes: MessageReader<RegistriesChanged>,
+    world: Res<WorldManager>,
+    tile_registry: Res<TileRegistry>,
+    mut chunk_query: Query<(&Chunk, &mut TilemapChunkTileData)>,
+    mut failures: Failures,
+) {
+    if changes.read().count() == 0 {
+        return;
+    }
+    for (chunk, mut tile_data) in chunk_query.iter_mut() {
+        if let Some(data) = world.get_cached_chunk(&chunk.position) {
+            tile_data.0 = data.layer_to_tilemap_data(chunk.layer);
+        }
     }
+    for pos in world.active_chunks.keys() {
+        let unknown = world.get_cached_chunk(pos).and_then(|data| tile_registry.find_unknown_tile(data));
+        if let Some(tile) = unknown {
+            report_unknown_tile(&mut failures, *pos, tile, ChunkSource::Cache);
+        }
+    }
+}
+
+/// Camera render position, window size and zoom scale, if there is a 2D camera with an orthographic projection
+fn camera_view(
+    camera_query: &Query<(&Transform, &Projection), With<Camera2d>>,
+    window_query: &Query<&Window>,
+) -> Option<(Vec2, Vec2, f32)> {
+    let (camera_transform, projection) = camera_query.single().ok()?;
+    let window = window_query.single().ok()?;
+    let Projection::Orthographic(ortho) = projection else {
+        return None;
+    };
+    Some((camera_transform.translation.truncate(), window.size(), ortho.scale))
 }
 
 /// Calculate which chunks are visible in the camera viewport
@@ -266,49 +662,14 @@ pub fn apply_tile_modifications(
 fn calculate_visible_chunks(
     camera_query: &Query<(&Transform, &Projection), With<Camera2d>>,
     window_query: &Query<&Window>,
+    origin: &WorldOrigin,
 ) -> HashSet<ChunkPos> {
-    let mut visible_chunks = HashSet::new();
-
-    // Get camera data
-    let Ok((camera_transform, projection)) = camera_query.single() else {
-        return visible_chunks;
-    };
-
-    // Get window size
-    let Ok(window) = window_query.single() else {
-        return visible_chunks;
-    };
-
-    // Get zoom scale from projection
-    let scale = if let Projection::Orthographic(ref ortho) = projection {
-        ortho.scale
-    } else {
-        return visible_chunks;
-    };
-
-    // Calculate visible area in world coordinates
-    let camera_pos = camera_transform.translation.truncate();
-    let half_width = (window.width() / 2.0) * scale;
-    let half_height = (window.height() / 2.0) * scale;
-
-    // Calculate visible bounds
-    let min_x = camera_pos.x - half_width;
-    let max_x = camera_pos.x + half_width;
-    let min_y = camera_pos.y - half_height;
-    let max_y = camera_pos.y + half_height;
-
-    // Convert to chunk coordinates
-    let min_chunk = coords::world_to_chunk(Vec2::new(min_x, min_y));
-    let max_chunk = coords::world_to_chunk(Vec2::new(max_x, max_y));
-
-    // Collect all chunks that intersect with the visible area
-    for x in min_chunk.x..=max_chunk.x {
-        for y in min_chunk.y..=max_chunk.y {
-            visible_chunks.insert(ChunkPos::new(x, y));
-        }
-    }
-
-    visible_chunks
+    camera_view(camera_query, window_query)
+        .map(|(center, viewport, scale)| model::visible_chunks(center, viewport, scale))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|local| origin.chunk_from_render(local))
+        .collect()
 }
 
 /// Calculate the appropriate chunk load radius based on camera zoom level
@@ -317,43 +678,8 @@ fn calculate_load_radius(
     camera_query: &Query<(&Transform, &Projection), With<Camera2d>>,
     window_query: &Query<&Window>,
 ) -> i32 {
-    // Get camera data
-    let Ok((_, projection)) = camera_query.single() else {
-        return CHUNK_LOAD_RADIUS;
-    };
-
-    // Get window size
-    let Ok(window) = window_query.single() else {
-        return CHUNK_LOAD_RADIUS;
-    };
-
-    // Get zoom scale from projection
-    let scale = if let Projection::Orthographic(ref ortho) = projection {
-        ortho.scale
-    } else {
-        return CHUNK_LOAD_RADIUS;
-    };
-
-    // Calculate visible area in world coordinates
-    let half_width = (window.width() / 2.0) * scale;
-    let half_height = (window.height() / 2.0) * scale;
-
-    // Calculate how many chunks are visible in each direction
-    let chunks_horizontal = (half_width / crate::tiles::CHUNK_PIXEL_SIZE).ceil() as i32;
-    let chunks_vertical = (half_height / crate::tiles::CHUNK_PIXEL_SIZE).ceil() as i32;
-
-    // Use the larger dimension and add buffer of 2 chunks for smooth loading
-    let visible_radius = chunks_horizontal.max(chunks_vertical);
-    let load_radius = visible_radius + 2;
-
-    // Ensure minimum radius of CHUNK_LOAD_RADIUS for close zoom
-    load_radius.max(CHUNK_LOAD_RADIUS)
-}
-
-/// Calculate the unload radius based on load radius with hysteresis buffer
-/// Always maintains +2 chunk buffer above load radius to prevent thrashing
-fn calculate_unload_radius(load_radius: i32) -> i32 {
-    load_radius + 2
+    camera_view(camera_query, window_query)
+        .map_or(CHUNK_LOAD_RADIUS, |(_, viewport, scale)| model::load_radius_for_viewport(viewport, scale))
 }
 
 /// Print a visual representation of loaded chunks
@@ -431,7 +757,7 @@ fn print_chunk_grid(world: &WorldManager, camera_chunk: ChunkPos, visible_chunks
         visible_chunks.len(),
         camera_chunk,
         load_radius,
-        calculate_unload_radius(load_radius)
+        model::unload_radius(load_radius)
     ));
 
     info!("{}", grid);
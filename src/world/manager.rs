@@ -1,16 +1,114 @@
-use crate::tiles::{Chunk, ChunkData, ChunkPos};
+use crate::tiles::{Chunk, ChunkData, ChunkPos, TileId, TileRegistry, CHUNK_PIXEL_SIZE, CHUNK_SIZE, LAYER_GROUND, TILE_SIZE};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::sprite_render::{TileData, TilemapChunkTileData};
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task, TaskPool};
 use std::collections::{HashMap, HashSet};
+use std::io;
+use super::serialization::{self, SerializationError};
+use super::biome::{biome_at_chunk, Biome};
+use super::structures::{structure_at, Structure};
+use super::generator::{GeneratorConfig, GroundPattern};
+use super::worldgen::WorldgenParams;
+use super::model::UncachedRead;
+use super::pool::ChunkPool;
+use super::storage::ChunkStore;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
 
-/// Represents a pending tile modification
-#[derive(Debug, Clone)]
-pub struct TileModification {
+/// Chunks the cache holds before the least recently used unloaded ones are evicted
+pub const DEFAULT_CHUNK_CACHE_SIZE: usize = 2048;
+
+/// Ground layers of uncached chunks kept for footprint checks (the oldest are dropped past this)
+const MAX_GROUND_SUMMARIES: usize = 64;
+
+/// Why a footprint can't hold an object (tile coordinates)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum FootprintError {
+    #[error("tile {0} can't be walked on")]
+    Unwalkable(IVec2),
+    #[error("tile {0} is already taken")]
+    Occupied(IVec2),
+    #[error("the chunk of tile {0} couldn't be read")]
+    Unreadable(IVec2),
+}
+
+/// Tiles covered by an object `size` tiles across centered on a world position
+pub fn footprint_tiles(center: Vec2, size: UVec2) -> impl Iterator<Item = IVec2> {
+    let first = (center / TILE_SIZE).floor().as_ivec2() - (size / 2).as_ivec2();
+    (0..size.y as i32).flat_map(move |y| (0..size.x as i32).map(move |x| first + IVec2::new(x, y)))
+}
+
+/// Request to change one tile at a world position (in pixels)
+/// Any system can write these (directly or through `TileEdits`); `apply_tile_modifications` applies
+/// them to the chunk cache and tilemaps. Writers that want the change applied in the same frame
+/// must run before it
+#[derive(Message, Debug, Clone, Copy, PartialEq)]
+pub struct TileModificationEvent {
     pub world_x: f32,
     pub world_y: f32,
     pub tile_id: u16,
     pub layer: usize,
+    /// Painted by a user (rather than by the simulation), who is told when it's blocked
+    pub painted: bool,
+}
+
+impl TileModificationEvent {
+    pub fn new(position: Vec2, tile_id: u16, layer: usize) -> Self {
+        Self {
+            world_x: position.x,
+            world_y: position.y,
+            tile_id,
+            layer,
+            painted: false,
+        }
+    }
+
+    /// A change painted by a user
+    pub fn painted(position: Vec2, tile_id: u16, layer: usize) -> Self {
+        Self { painted: true, ..Self::new(position, tile_id, layer) }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.world_x, self.world_y)
+    }
+}
+
+/// Writer for tile modifications, with helpers for the ground layer and for batches
+#[derive(SystemParam)]
+pub struct TileEdits<'w> {
+    writer: MessageWriter<'w, TileModificationEvent>,
+}
+
+impl TileEdits<'_> {
+    pub fn set(&mut self, position: Vec2, tile_id: u16, layer: usize) {
+        self.writer.write(TileModificationEvent::new(position, tile_id, layer));
+    }
+
+    pub fn set_ground(&mut self, position: Vec2, tile_id: u16) {
+        self.set(position, tile_id, LAYER_GROUND);
+    }
+
+    /// A change painted by a user (local or a network client)
+    pub fn paint(&mut self, position: Vec2, tile_id: u16, layer: usize) {
+        self.writer.write(TileModificationEvent::painted(position, tile_id, layer));
+    }
+
+    /// Set many ground tiles to the same tile in one batch
+    pub fn set_ground_batch(&mut self, positions: impl IntoIterator<Item = Vec2>, tile_id: u16) {
+        self.writer.write_batch(
+            positions.into_iter().map(|position| TileModificationEvent::new(position, tile_id, LAYER_GROUND)),
+        );
+    }
+}
+
+/// A chunk file being written on the `IoTaskPool` (`WorldManager::save_in_background`)
+pub struct PendingSave {
+    /// The encoded chunk, also read back while the write is running
+    pub bytes: Arc<Vec<u8>>,
+    task: Task<io::Result<()>>,
 }
 
 /// World manager resource that tracks all loaded chunks and their state
@@ -20,31 +118,85 @@ pub struct WorldManager {
     /// Key: ChunkPos, Value: Array of entity IDs (one per layer)
     pub active_chunks: HashMap<ChunkPos, [Entity; crate::tiles::NUM_LAYERS]>,
 
-    /// Set of chunks that have been modified and need saving
-    pub dirty_chunks: HashSet<ChunkPos>,
+    /// Chunks that have been modified and need saving, with when they were last modified
+    /// (`platform::unix_time`), so autosave can wait for edits to settle
+    pub dirty_chunks: HashMap<ChunkPos, Duration>,
 
     /// In-memory cache of chunk data
     pub chunk_cache: HashMap<ChunkPos, ChunkData>,
 
+    /// Cached chunks kept before the least recently used ones that aren't loaded are evicted
+    /// (0 keeps everything); loaded chunks are never evicted, so the cache can exceed this
+    pub cache_capacity: usize,
+
+    /// When each cached chunk was last cached or read (`cache_clock` ticks), for eviction
+    cache_used: HashMap<ChunkPos, u64>,
+    cache_clock: u64,
+
+    /// Chunk loads served from the cache, and ones that had to read or generate the chunk
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+
     /// Directory where chunk files are saved
     pub save_directory: PathBuf,
 
     /// Current camera chunk position (for loading/unloading decisions)
     pub camera_chunk: Option<ChunkPos>,
 
-    /// Queue of pending tile modifications
-    pub pending_tile_modifications: Vec<TileModification>,
+    /// Number of old versions kept per chunk file (0 disables backups)
+    pub backup_retention: usize,
+
+    /// Fixed chunk load radius replacing the zoom-based one (`--load-radius`)
+    pub load_radius_override: Option<i32>,
+
+    /// Where chunk files are read and written (the filesystem, or localStorage on the web)
+    /// Shared with the chunk reads running on the task pool
+    pub store: Arc<dyn ChunkStore>,
+
+    /// Uncached chunks being read or generated on the `AsyncComputeTaskPool`, spawned by
+    /// `loader::poll_chunk_reads` when they finish (dropping a task cancels it)
+    pub pending_loads: HashMap<ChunkPos, Task<UncachedRead>>,
+
+    /// Chunk files of unloaded chunks being written on the `IoTaskPool`, collected by
+    /// `loader::poll_chunk_saves`; reads of these chunks use the bytes in flight
+    pub pending_saves: HashMap<ChunkPos, PendingSave>,
+
+    /// Chunks whose files were written by a newer build: left unloaded (never generated or saved
+    /// over) until the file is replaced or the chunk regenerated
+    pub refused_chunks: HashSet<ChunkPos>,
+
+    /// Buffers of unloaded chunks, reused by the chunks loaded next
+    pub pool: ChunkPool,
+
+    /// Settings chunks that were never saved are generated with (from `WorldMetadata.generator`)
+    pub generator: GeneratorConfig,
+
+    /// Ground layers of chunks outside the cache, read for footprint checks (oldest first)
+    pub ground_summaries: Vec<(ChunkPos, Vec<TileId>)>,
 }
 
 impl WorldManager {
     pub fn new(save_directory: PathBuf) -> Self {
         Self {
             active_chunks: HashMap::new(),
-            dirty_chunks: HashSet::new(),
+            dirty_chunks: HashMap::new(),
             chunk_cache: HashMap::new(),
+            cache_capacity: DEFAULT_CHUNK_CACHE_SIZE,
+            cache_used: HashMap::new(),
+            cache_clock: 0,
+            cache_hits: 0,
+            cache_misses: 0,
             save_directory,
             camera_chunk: None,
-            pending_tile_modifications: Vec::new(),
+            backup_retention: super::backup::DEFAULT_BACKUP_RETENTION,
+            load_radius_override: None,
+            store: super::storage::default_store(),
+            pending_loads: HashMap::new(),
+            pending_saves: HashMap::new(),
+            refused_chunks: HashSet::new(),
+            pool: ChunkPool::default(),
+            generator: GeneratorConfig::default(),
+            ground_summaries: Vec::new(),
         }
     }
 
@@ -73,9 +225,9 @@ impl WorldManager {
         self.active_chunks.remove(pos)
     }
 
-    /// Mark a chunk as dirty (needs saving)
+    /// Mark a chunk as dirty (needs saving), modified now
     pub fn mark_dirty(&mut self, pos: ChunkPos) {
-        self.dirty_chunks.insert(pos);
+        self.dirty_chunks.insert(pos, crate::platform::unix_time());
     }
 
     /// Clear dirty flag for a chunk (after saving)
@@ -85,17 +237,147 @@ impl WorldManager {
 
     /// Check if a chunk is dirty
     pub fn is_dirty(&self, pos: &ChunkPos) -> bool {
-        self.dirty_chunks.contains(pos)
+        self.dirty_chunks.contains_key(pos)
     }
 
     /// Get all dirty chunk positions
     pub fn get_dirty_chunks(&self) -> Vec<ChunkPos> {
-        self.dirty_chunks.iter().copied().collect()
+        self.dirty_chunks.keys().copied().collect()
+    }
+
+    /// Dirty chunks last modified at least `quiet` before `now`, i.e. not being edited right now
+    pub fn get_settled_dirty_chunks(&self, now: Duration, quiet: Duration) -> Vec<ChunkPos> {
+        self.dirty_chunks
+            .iter()
+            .filter(|(_, modified)| now.saturating_sub(**modified) >= quiet)
+            .map(|(pos, _)| *pos)
+            .collect()
     }
 
-    /// Add chunk data to cache
+    /// Add chunk data to cache, evicting the least recently used unloaded chunks past `cache_capacity`
     pub fn cache_chunk(&mut self, data: ChunkData) {
-        self.chunk_cache.insert(data.position, data);
+        let pos = data.position;
+        // The cached copy is the one that gets edited from now on
+        self.ground_summaries.retain(|(summary, _)| *summary != pos);
+        self.chunk_cache.insert(pos, data);
+        self.touch_cached_chunk(pos);
+        self.evict_chunks(pos);
+    }
+
+    /// A copy of a cached chunk for loading it, counted as a cache hit (or a miss if it isn't cached)
+    pub fn copy_cached_chunk(&mut self, pos: &ChunkPos) -> Option<ChunkData> {
+        let Some(cached) = self.chunk_cache.get(pos) else {
+            self.cache_misses += 1;
+            return None;
+        };
+        let copy = self.pool.copy_of(cached);
+        self.cache_hits += 1;
+        self.touch_cached_chunk(*pos);
+        Some(copy)
+    }
+
+    fn touch_cached_chunk(&mut self, pos: ChunkPos) {
+        self.cache_clock += 1;
+        self.cache_used.insert(pos, self.cache_clock);
+    }
+
+    /// Release least recently used chunks that aren't loaded (nor `keep`) until the cache fits
+    /// `cache_capacity`; dirty ones are saved first, like unloaded chunks (`model::release_chunk`)
+    fn evict_chunks(&mut self, keep: ChunkPos) {
+        if self.cache_capacity == 0 {
+            return;
+        }
+        while self.chunk_cache.len() > self.cache_capacity {
+            let oldest = self
+                .chunk_cache
+                .keys()
+                .filter(|pos| **pos != keep && !self.is_loaded(pos))
+                .min_by_key(|pos| self.cache_used.get(pos).copied().unwrap_or(0))
+                .copied();
+            let Some(oldest) = oldest else {
+                break;
+            };
+            super::model::release_chunk(self, oldest);
+        }
+    }
+
+    /// Biome at a world position (pixels), or None if the world isn't generated with biomes
+    pub fn biome_at(&self, position: Vec2) -> Option<Biome> {
+        (self.generator.ground == GroundPattern::Biomes)
+            .then(|| biome_at_chunk(ChunkPos::from_world(position, CHUNK_PIXEL_SIZE), self.generator.seed))
+    }
+
+    /// Structure (ruins, grove, clearing) at a world position (pixels), or None
+    pub fn structure_at(&self, position: Vec2) -> Option<Structure> {
+        let tile = (position / TILE_SIZE).floor().as_ivec2();
+        (self.generator.ground == GroundPattern::Biomes).then(|| structure_at(tile, self.generator.seed)).flatten()
+    }
+
+    /// How readily trees are planted at a world position: the biome's density times the structure's
+    /// (times the tuned `WorldgenParams.tree_density`)
+    pub fn tree_density(&self, position: Vec2) -> f32 {
+        self.generator.params.tree_density
+            * self.biome_at(position).map_or(1.0, |biome| biome.tree_density())
+            * self.structure_at(position).map_or(1.0, |structure| structure.kind.tree_density())
+    }
+
+    /// Generate chunks with new tuning from now on; ground summaries of generated chunks are dropped
+    pub fn set_worldgen_params(&mut self, params: WorldgenParams) {
+        self.generator.params = params;
+        self.ground_summaries.clear();
+    }
+
+    /// Ground tile at tile coordinates anywhere in the world: from the cache, else from a ground
+    /// summary read from the saved (or generated) chunk on demand; None if the chunk can't be read
+    pub fn ground_tile_at(&mut self, tile: IVec2) -> Option<TileId> {
+        let chunk_pos = ChunkPos::from_tile(tile, CHUNK_SIZE as i32);
+        let local = tile - IVec2::new(chunk_pos.x, chunk_pos.y) * CHUNK_SIZE as i32;
+        let index = local.y as usize * CHUNK_SIZE + local.x as usize;
+        if let Some(chunk) = self.chunk_cache.get(&chunk_pos) {
+            return chunk.get_tile(LAYER_GROUND, local.x as usize, local.y as usize);
+        }
+        if let Some((_, ground)) = self.ground_summaries.iter().find(|(pos, _)| *pos == chunk_pos) {
+            return ground.get(index).copied();
+        }
+
+        let chunk = match self.load_stored_chunk(&chunk_pos) {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => super::generator::generate_chunk(chunk_pos, &self.generator),
+            Err(e) => {
+                warn!("Failed to read chunk ({}, {}) for a footprint check: {}", chunk_pos.x, chunk_pos.y, e);
+                return None;
+            }
+        };
+        let ground = chunk.layers[LAYER_GROUND].to_vec();
+        let tile_id = ground.get(index).copied();
+        if self.ground_summaries.len() >= MAX_GROUND_SUMMARIES {
+            self.ground_summaries.remove(0);
+        }
+        self.ground_summaries.push((chunk_pos, ground));
+        tile_id
+    }
+
+    /// Checks that every tile under an object `size` tiles across centered on `center` is walkable
+    /// ground and not `occupied`, across chunk borders (chunks outside the cache are read on demand)
+    pub fn check_footprint(
+        &mut self,
+        registry: &TileRegistry,
+        center: Vec2,
+        size: UVec2,
+        occupied: impl Fn(IVec2) -> bool,
+    ) -> Result<(), FootprintError> {
+        for tile in footprint_tiles(center, size) {
+            let Some(tile_id) = self.ground_tile_at(tile) else {
+                return Err(FootprintError::Unreadable(tile));
+            };
+            if !registry.get(tile_id).is_some_and(|properties| properties.walkable) {
+                return Err(FootprintError::Unwalkable(tile));
+            }
+            if occupied(tile) {
+                return Err(FootprintError::Occupied(tile));
+            }
+        }
+        Ok(())
     }
 
     /// Get chunk data from cache
@@ -105,6 +387,7 @@ impl WorldManager {
 
     /// Remove chunk data from cache
     pub fn uncache_chunk(&mut self, pos: &ChunkPos) -> Option<ChunkData> {
+        self.cache_used.remove(pos);
         self.chunk_cache.remove(pos)
     }
 
@@ -115,25 +398,142 @@ impl WorldManager {
             .join(format!("chunk_{}_{}.bin", pos.x, pos.y))
     }
 
-    /// Update the camera's chunk position
-    pub fn update_camera_position(&mut self, chunk_pos: ChunkPos) {
-        self.camera_chunk = Some(chunk_pos);
+    /// Get the directory holding rotated chunk backups
+    pub fn get_backup_dir(&self) -> PathBuf {
+        self.save_directory.join("backups")
     }
 
-    /// Queue a tile modification at a world position (in pixels)
-    /// The modification will be applied by the apply_tile_modifications system
-    pub fn queue_tile_modification(&mut self, world_x: f32, world_y: f32, tile_id: u16, layer: usize) {
-        self.pending_tile_modifications.push(TileModification {
-            world_x,
-            world_y,
-            tile_id,
-            layer,
+    /// Get the directory corrupted chunk files are moved to
+    pub fn get_quarantine_dir(&self) -> PathBuf {
+        self.save_directory.join("quarantine")
+    }
+
+    /// Save a cached chunk to the store, rotating the previous file into the backups
+    /// Returns false if the chunk isn't cached
+    pub fn save_cached_chunk(&self, pos: &ChunkPos) -> Result<bool, SerializationError> {
+        let Some(chunk_data) = self.get_cached_chunk(pos) else {
+            return Ok(false);
+        };
+        let path = self.get_chunk_path(pos);
+        self.store.backup(&path, &self.get_backup_dir(), self.backup_retention)?;
+        self.store.write(&path, &serialization::encode_chunk(chunk_data))?;
+        Ok(true)
+    }
+
+    /// Write `chunk` over its saved file (imports), rotating the previous file into the backups
+    /// A background save of the chunk still running is waited for first, so it can't land on top
+    pub fn replace_chunk_file(&mut self, chunk: &ChunkData) -> Result<(), SerializationError> {
+        if let Some(Err(e)) = self.wait_for_save(&chunk.position) {
+            warn!("Failed to save chunk {:?}: {}", chunk.position, e);
+        }
+        let path = self.get_chunk_path(&chunk.position);
+        self.store.backup(&path, &self.get_backup_dir(), self.backup_retention)?;
+        self.store.write(&path, &serialization::encode_chunk(chunk))?;
+        Ok(())
+    }
+
+    /// Write a cached chunk on the `IoTaskPool` (rotating the backups there too) and drop it from
+    /// the cache, so unloading doesn't wait on the disk; finished writes are collected by `poll_saves`
+    /// Returns false if the chunk isn't cached
+    pub fn save_in_background(&mut self, pos: &ChunkPos) -> bool {
+        // Two writes of one file could finish in either order
+        if let Some(Err(e)) = self.wait_for_save(pos) {
+            warn!("Failed to save chunk {:?}: {}", pos, e);
+        }
+        let Some(chunk_data) = self.uncache_chunk(pos) else {
+            return false;
+        };
+        let bytes = Arc::new(serialization::encode_chunk(&chunk_data));
+        self.pool.recycle_chunk(chunk_data);
+
+        let store = self.store.clone();
+        let (path, backup_dir, retention) = (self.get_chunk_path(pos), self.get_backup_dir(), self.backup_retention);
+        let written = bytes.clone();
+        let task = IoTaskPool::get_or_init(TaskPool::new).spawn(async move {
+            store.backup(&path, &backup_dir, retention)?;
+            store.write(&path, &written)
         });
+        self.pending_saves.insert(*pos, PendingSave { bytes, task });
+        true
+    }
+
+    /// A failed write puts the chunk back in the cache (unless it was read in again meanwhile) and
+    /// marks it dirty, so autosave and Save World retry it
+    fn complete_save(&mut self, pos: ChunkPos, bytes: &[u8], result: io::Result<()>) -> Result<(), SerializationError> {
+        if result.is_err() {
+            if !self.chunk_cache.contains_key(&pos) {
+                self.cache_chunk(serialization::decode_chunk(bytes)?);
+            }
+            self.mark_dirty(pos);
+        }
+        Ok(result?)
+    }
+
+    /// Collect the background saves that have finished, with their results
+    pub fn poll_saves(&mut self) -> Vec<(ChunkPos, Result<(), SerializationError>)> {
+        let mut finished = Vec::new();
+        self.pending_saves.retain(|pos, save| match block_on(future::poll_once(&mut save.task)) {
+            Some(result) => {
+                finished.push((*pos, save.bytes.clone(), result));
+                false
+            }
+            None => true,
+        });
+        finished
+            .into_iter()
+            .map(|(pos, bytes, result)| (pos, self.complete_save(pos, &bytes, result)))
+            .collect()
+    }
+
+    /// Wait for a chunk's background save, if it has one, before touching its file
+    pub fn wait_for_save(&mut self, pos: &ChunkPos) -> Option<Result<(), SerializationError>> {
+        let PendingSave { bytes, task } = self.pending_saves.remove(pos)?;
+        Some(self.complete_save(*pos, &bytes, block_on(task)))
+    }
+
+    /// Wait for every background save (Save World, exiting), with their results
+    pub fn finish_saves(&mut self) -> Vec<(ChunkPos, Result<(), SerializationError>)> {
+        let pending: Vec<(ChunkPos, PendingSave)> = self.pending_saves.drain().collect();
+        pending
+            .into_iter()
+            .map(|(pos, PendingSave { bytes, task })| (pos, self.complete_save(pos, &bytes, block_on(task))))
+            .collect()
     }
 
-    /// Get all pending tile modifications and clear the queue
-    pub fn take_tile_modifications(&mut self) -> Vec<TileModification> {
-        std::mem::take(&mut self.pending_tile_modifications)
+    /// Read a saved chunk from the store (or from its background save); None if it was never saved
+    pub fn load_stored_chunk(&self, pos: &ChunkPos) -> Result<Option<ChunkData>, SerializationError> {
+        if let Some(save) = self.pending_saves.get(pos) {
+            return Ok(Some(serialization::decode_chunk(&save.bytes)?));
+        }
+        match self.store.read(&self.get_chunk_path(pos))? {
+            Some(bytes) => Ok(Some(serialization::decode_chunk(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the path to the world metadata file
+    pub fn get_metadata_path(&self) -> PathBuf {
+        self.save_directory.join(super::metadata::METADATA_FILE_NAME)
+    }
+
+    /// Get the path to the saved map cache
+    pub fn get_map_cache_path(&self) -> PathBuf {
+        self.save_directory.join("map_cache.bin")
+    }
+
+    /// Get the path to the explored chunk set
+    pub fn get_explored_path(&self) -> PathBuf {
+        self.save_directory.join("explored.bin")
+    }
+
+    /// Get the path to the chunk unload times used for offline catch-up
+    pub fn get_chunk_times_path(&self) -> PathBuf {
+        self.save_directory.join("chunk_times.bin")
+    }
+
+    /// Update the camera's chunk position
+    pub fn update_camera_position(&mut self, chunk_pos: ChunkPos) {
+        self.camera_chunk = Some(chunk_pos);
     }
 
     /// Get statistics about the world state
@@ -142,6 +542,10 @@ impl WorldManager {
             loaded_chunks: self.active_chunks.len(),
             dirty_chunks: self.dirty_chunks.len(),
             cached_chunks: self.chunk_cache.len(),
+            pending_chunks: self.pending_loads.len(),
+            saving_chunks: self.pending_saves.len(),
+            cache_hits: self.cache_hits,
+            cache_misses: self.cache_misses,
             camera_chunk: self.camera_chunk,
         }
     }
@@ -159,6 +563,11 @@ pub struct WorldStats {
     pub loaded_chunks: usize,
     pub dirty_chunks: usize,
     pub cached_chunks: usize,
+    pub pending_chunks: usize,
+    pub saving_chunks: usize,
+    /// Chunk loads served from the cache, and ones that weren't
+    pub cache_hits: u64,
+    pub cache_misses: u64,
     pub camera_chunk: Option<ChunkPos>,
 }
 
@@ -166,8 +575,113 @@ impl std::fmt::Display for WorldStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Loaded: {}, Dirty: {}, Cached: {}, Camera: {:?}",
-            self.loaded_chunks, self.dirty_chunks, self.cached_chunks, self.camera_chunk
+            "Loaded: {}, Dirty: {}, Cached: {} ({} hits, {} misses), Loading: {}, Saving: {}, Camera: {:?}",
+            self.loaded_chunks,
+            self.dirty_chunks,
+            self.cached_chunks,
+            self.cache_hits,
+            self.cache_misses,
+            self.pending_chunks,
+            self.saving_chunks,
+            self.camera_chunk
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{TILE_GRASS, TILE_WATER};
+
+    #[test]
+    fn test_check_footprint() {
+        let mut world = WorldManager::new(std::env::temp_dir().join("worldseed_test_footprint"));
+        // Generated chunks are all grass
+        world.generator.ground = GroundPattern::Grass;
+        let registry = TileRegistry::new();
+        let mut chunk = ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS);
+        chunk.set_tile(LAYER_GROUND, 31, 5, TILE_WATER);
+        world.cache_chunk(chunk);
+
+        // A 2x2 footprint on the border of chunk (0, 0) and the generated chunk (1, 0)
+        let border = Vec2::new(32.0 * TILE_SIZE, 10.5 * TILE_SIZE);
+        assert_eq!(footprint_tiles(border, UVec2::splat(2)).collect::<Vec<_>>(), vec![
+            IVec2::new(31, 9), IVec2::new(32, 9), IVec2::new(31, 10), IVec2::new(32, 10),
+        ]);
+        assert_eq!(world.check_footprint(&registry, border, UVec2::splat(2), |_| false), Ok(()));
+        assert_eq!(world.ground_summaries.len(), 1);
+
+        // Half over water, or over a tree
+        let shore = Vec2::new(32.0 * TILE_SIZE, 5.5 * TILE_SIZE);
+        assert_eq!(
+            world.check_footprint(&registry, shore, UVec2::splat(2), |_| false),
+            Err(FootprintError::Unwalkable(IVec2::new(31, 5)))
+        );
+        assert_eq!(
+            world.check_footprint(&registry, border, UVec2::splat(2), |tile| tile == IVec2::new(32, 10)),
+            Err(FootprintError::Occupied(IVec2::new(32, 10)))
+        );
+
+        // Caching a chunk drops its summary
+        world.cache_chunk(ChunkData::filled(ChunkPos::new(1, 0), TILE_WATER));
+        assert!(world.ground_summaries.is_empty());
+        assert_eq!(
+            world.check_footprint(&registry, border, UVec2::splat(2), |_| false),
+            Err(FootprintError::Unwalkable(IVec2::new(32, 9)))
+        );
+    }
+
+    #[test]
+    fn test_chunk_cache_eviction() {
+        let dir = std::env::temp_dir().join("worldseed_test_cache_eviction");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut world = WorldManager::new(dir.clone());
+        world.backup_retention = 0;
+        world.cache_capacity = 2;
+        let (loaded, edited, clean, newest) =
+            (ChunkPos::new(0, 0), ChunkPos::new(1, 0), ChunkPos::new(2, 0), ChunkPos::new(3, 0));
+
+        // Loaded chunks stay however old they are
+        world.register_chunk(loaded, [Entity::PLACEHOLDER; crate::tiles::NUM_LAYERS]);
+        world.cache_chunk(ChunkData::filled(loaded, TILE_GRASS));
+        world.cache_chunk(ChunkData::filled(edited, TILE_WATER));
+        world.mark_dirty(edited);
+        world.cache_chunk(ChunkData::filled(clean, TILE_GRASS));
+        assert!(world.get_cached_chunk(&loaded).is_some() && world.get_cached_chunk(&clean).is_some());
+        // The edited chunk was the least recently used; it is saved before it goes
+        assert!(world.get_cached_chunk(&edited).is_none() && !world.is_dirty(&edited));
+        assert!(world.finish_saves().iter().all(|(_, result)| result.is_ok()));
+        let saved = world.load_stored_chunk(&edited).unwrap().unwrap();
+        assert_eq!(saved.get_tile(LAYER_GROUND, 0, 0), Some(TILE_WATER));
+
+        // Once unloaded the oldest chunk can go, unless it was read since
+        world.unregister_chunk(&loaded);
+        assert!(world.copy_cached_chunk(&loaded).is_some());
+        assert!(world.copy_cached_chunk(&edited).is_none());
+        assert_eq!((world.stats().cache_hits, world.stats().cache_misses), (1, 1));
+        world.cache_chunk(ChunkData::filled(newest, TILE_GRASS));
+        assert!(world.get_cached_chunk(&clean).is_none() && world.get_cached_chunk(&loaded).is_some());
+        assert_eq!(world.chunk_cache.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replace_chunk_file_waits_for_background_save() {
+        let dir = std::env::temp_dir().join("worldseed_test_replace_chunk_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut world = WorldManager::new(dir.clone());
+        world.backup_retention = 0;
+        let pos = ChunkPos::new(0, 0);
+
+        // The unloaded chunk's write is still running when an import replaces the file
+        world.cache_chunk(ChunkData::filled(pos, TILE_GRASS));
+        assert!(world.save_in_background(&pos));
+        world.replace_chunk_file(&ChunkData::filled(pos, TILE_WATER)).unwrap();
+        assert!(world.pending_saves.is_empty());
+        let stored = world.load_stored_chunk(&pos).unwrap().unwrap();
+        assert_eq!(stored.get_tile(LAYER_GROUND, 0, 0), Some(TILE_WATER));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,568 @@
+use super::climate::Climate;
+use super::generator::GeneratorConfig;
+use super::manager::WorldManager;
+use crate::error::Failures;
+use crate::platform::unix_time;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::BuildHasher;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// File name of the world metadata file inside the save directory
+pub const METADATA_FILE_NAME: &str = "world.meta";
+
+/// Error type for world metadata operations
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Parse error: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("Serialize error: {0}")]
+    Serialize(#[from] ron::Error),
+}
+
+/// A named, colored pin placed on the world map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub name: String,
+    /// World position in pixels
+    pub x: f32,
+    pub y: f32,
+    /// sRGB color components (0.0 - 1.0)
+    pub color: [f32; 3],
+}
+
+impl Waypoint {
+    pub fn new(name: impl Into<String>, x: f32, y: f32, color: [f32; 3]) -> Self {
+        Self {
+            name: name.into(),
+            x,
+            y,
+            color,
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    pub fn bevy_color(&self) -> Color {
+        Color::srgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+/// Longest text a sign holds (characters)
+pub const MAX_SIGN_TEXT_LENGTH: usize = 80;
+
+/// A sign post put up in the world, with the user's text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sign {
+    pub text: String,
+    /// World position in pixels
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Sign {
+    pub fn new(text: impl Into<String>, position: Vec2) -> Self {
+        Self {
+            text: text.into(),
+            x: position.x,
+            y: position.y,
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+/// Versions of the chunk format and terrain generator the world was last upgraded to
+/// Worlds from before they were recorded read as 0, i.e. older than anything
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveVersions {
+    pub chunk_format: u16,
+    pub generator: u16,
+}
+
+impl SaveVersions {
+    /// The versions this build writes
+    pub fn current() -> Self {
+        Self {
+            chunk_format: super::serialization::VERSION,
+            generator: super::generator::GENERATOR_VERSION,
+        }
+    }
+
+    /// Whether anything is older than this build
+    pub fn is_outdated(&self) -> bool {
+        self.chunk_format < super::serialization::VERSION || self.generator < super::generator::GENERATOR_VERSION
+    }
+}
+
+/// A named rectangle (world pixels) whose tiles can't be changed, see `world::protection`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtectedRegion {
+    pub name: String,
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl ProtectedRegion {
+    /// A region spanned by two corners in any order
+    pub fn new(name: impl Into<String>, a: Vec2, b: Vec2) -> Self {
+        Self {
+            name: name.into(),
+            min: a.min(b).to_array(),
+            max: a.max(b).to_array(),
+        }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        position.cmpge(Vec2::from(self.min)).all() && position.cmple(Vec2::from(self.max)).all()
+    }
+}
+
+/// Camera and UI state restored when the world is reopened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Camera position in world pixels
+    pub camera: [f32; 2],
+    /// Orthographic projection scale
+    pub zoom: f32,
+    /// Selected placement tool ("player", "snail", "sign", "guardian:<variant>")
+    pub placement: Option<String>,
+    /// Selected terrain paint tool ("grass", "dirt")
+    pub paint: Option<String>,
+    pub map_open: bool,
+    pub map_detail: String,
+    pub map_mode: String,
+    pub minimap_visible: bool,
+    pub waypoint_labels: bool,
+}
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            camera: [0.0, 0.0],
+            zoom: 1.0,
+            placement: None,
+            paint: None,
+            map_open: false,
+            map_detail: String::new(),
+            map_mode: String::new(),
+            minimap_visible: true,
+            waypoint_labels: true,
+        }
+    }
+}
+
+/// What the last manual "Save World" wrote
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveSummary {
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+    pub chunks_saved: usize,
+    /// Creatures and trees saved with their chunks
+    pub entities_saved: usize,
+    pub explored_chunks: usize,
+}
+
+/// Cumulative counters for the lifetime of the world
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldStatistics {
+    /// Ground tiles painted with the terrain tools
+    pub tiles_painted: u64,
+    /// Trees that reached the mature stage
+    pub trees_grown: u64,
+    /// Snails placed with the entity tools
+    pub snails_spawned: u64,
+    /// Ground tiles that turned (back) into grass, painted or regrown
+    pub grass_restored: u64,
+    /// Camera movement in world pixels
+    pub camera_distance: f64,
+    /// Player movement in world pixels
+    pub player_distance: f64,
+}
+
+/// In-game time, persisted so the world resumes at the same hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockState {
+    /// Days elapsed since the world was created
+    pub day: u32,
+    /// Fraction of the current day (0.0 = midnight, 0.5 = noon)
+    pub time_of_day: f32,
+    /// Real seconds per in-game day
+    pub day_length: f32,
+    /// In-game days per season (a year is four seasons)
+    pub days_per_season: u32,
+    /// Total simulated seconds since the world was created (never jumps, unlike `day`)
+    pub elapsed: f64,
+}
+
+impl Default for ClockState {
+    fn default() -> Self {
+        Self {
+            day: 0,
+            // New worlds start in the morning
+            time_of_day: 0.3,
+            day_length: 600.0,
+            days_per_season: 7,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Seed of worlds from before seeds were recorded (new worlds without `--seed` get a fresh one, see
+/// `stamp_new_world`)
+pub const DEFAULT_SEED: u64 = 0x5EED_5EED_5EED_5EED;
+
+/// Current weather and the state of the weather RNG, so a reloaded world continues the same sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherState {
+    /// "clear", "rain", "fog" or "wind"
+    pub kind: String,
+    /// Seconds until the next weather change
+    pub remaining: f32,
+    pub rng_state: u64,
+}
+
+impl Default for WeatherState {
+    fn default() -> Self {
+        Self {
+            kind: "clear".to_string(),
+            remaining: 180.0,
+            rng_state: DEFAULT_SEED,
+        }
+    }
+}
+
+/// One line of the world's history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChronicleEntry {
+    /// In-world day it happened on (zero-based, see `ClockState.day`)
+    pub day: u32,
+    pub text: String,
+    /// Key of a one-time milestone ("first_guardian"), so it is only ever recorded once
+    #[serde(default)]
+    pub milestone: Option<String>,
+}
+
+/// World-level data that doesn't belong to any single chunk
+/// Saved as RON in `world.meta` alongside the chunk files
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldMetadata {
+    /// Display name (kept in sync with the directory by duplicate/rename)
+    #[serde(default)]
+    pub name: String,
+    /// Seed the world was created with (None for worlds from before seeds were recorded), see `seed_code`
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Unix time (seconds) the world was created (None for worlds from before it was recorded)
+    #[serde(default)]
+    pub created: Option<u64>,
+    /// Terrain generator settings the world was created with
+    #[serde(default = "GeneratorConfig::legacy")]
+    pub generator: GeneratorConfig,
+    /// Id of the scenario the world was created from (see `scenario`)
+    #[serde(default)]
+    pub scenario: Option<String>,
+    /// Map pins placed by the user
+    #[serde(default)]
+    pub waypoints: Vec<Waypoint>,
+    /// Where the user left off
+    #[serde(default)]
+    pub session: SessionState,
+    /// Last manual save (None until the world has been saved with Ctrl+S)
+    #[serde(default)]
+    pub last_save: Option<SaveSummary>,
+    /// Lifetime counters (kept live in `stats::Statistics`, copied here periodically)
+    #[serde(default)]
+    pub statistics: WorldStatistics,
+    /// Day/night clock (kept live in `environment::WorldClock`, copied here periodically)
+    #[serde(default)]
+    pub clock: ClockState,
+    /// Weather (kept live in `environment::WorldWeather`, copied here periodically)
+    #[serde(default)]
+    pub weather: WeatherState,
+    /// Append-only history of notable events, oldest first
+    #[serde(default)]
+    pub chronicle: Vec<ChronicleEntry>,
+    /// Recent crossings per tile ((x, y) tile coordinates), kept live in `entities::TileTraffic`
+    #[serde(default)]
+    pub traffic: BTreeMap<(i32, i32), u32>,
+    /// Sign posts put up by the user
+    #[serde(default)]
+    pub signs: Vec<Sign>,
+    /// Regions whose tiles nothing may change
+    #[serde(default)]
+    pub protected_regions: Vec<ProtectedRegion>,
+    /// Save format and generator versions, stamped on new worlds and after an upgrade (see `upgrade`)
+    #[serde(default)]
+    pub versions: SaveVersions,
+    /// The starting creatures and trees were spawned (they are saved with their chunks from then on,
+    /// so loading the world doesn't spawn them again)
+    #[serde(default)]
+    pub starting_entities_spawned: bool,
+}
+
+impl WorldMetadata {
+    /// Add a waypoint and return its index
+    pub fn add_waypoint(&mut self, waypoint: Waypoint) -> usize {
+        self.waypoints.push(waypoint);
+        self.waypoints.len() - 1
+    }
+
+    /// Remove a waypoint by index
+    pub fn remove_waypoint(&mut self, index: usize) -> Option<Waypoint> {
+        if index < self.waypoints.len() {
+            Some(self.waypoints.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// A waypoint by name ("waypoint3", case and spaces ignored) or number ("3" for "Waypoint 3")
+    pub fn find_waypoint(&self, name: &str) -> Option<&Waypoint> {
+        let squash = |text: &str| text.replace(' ', "").to_lowercase();
+        self.waypoints
+            .iter()
+            .find(|waypoint| squash(&waypoint.name) == squash(name) || waypoint.name.ends_with(&format!(" {}", name)))
+    }
+
+    /// Index of the sign nearest to a world position within `radius` pixels
+    pub fn sign_near(&self, position: Vec2, radius: f32) -> Option<usize> {
+        self.signs
+            .iter()
+            .enumerate()
+            .map(|(index, sign)| (index, sign.position().distance(position)))
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+
+    /// The protected region a world position is in, if any
+    pub fn protected_region_at(&self, position: Vec2) -> Option<&ProtectedRegion> {
+        self.protected_regions.iter().find(|region| region.contains(position))
+    }
+
+    /// Append an entry to the chronicle
+    /// Returns false (and appends nothing) if it's a milestone that was already recorded
+    pub fn record_chronicle(&mut self, entry: ChronicleEntry) -> bool {
+        if entry.milestone.is_some() && self.chronicle.iter().any(|existing| existing.milestone == entry.milestone) {
+            return false;
+        }
+        self.chronicle.push(entry);
+        true
+    }
+}
+
+/// Save world metadata to disk as RON
+pub fn save_metadata<P: AsRef<Path>>(
+    metadata: &WorldMetadata,
+    path: P,
+) -> Result<(), MetadataError> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let text = ron::ser::to_string_pretty(metadata, ron::ser::PrettyConfig::default())?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Load world metadata from disk
+pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<WorldMetadata, MetadataError> {
+    let text = fs::read_to_string(path)?;
+    Ok(ron::from_str(&text)?)
+}
+
+/// Fills in what a new world records about itself: its name (the directory's), when it was created,
+/// the current save versions, and a fresh seed unless one was given (`--seed` or a seed code)
+pub fn stamp_new_world(metadata: &mut WorldMetadata, directory: &Path, now: Duration) {
+    if metadata.name.is_empty() {
+        metadata.name = directory.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    }
+    metadata.created = Some(now.as_secs());
+    metadata.versions = SaveVersions::current();
+    if metadata.seed.is_none() {
+        let seed = RandomState::new().hash_one(now);
+        metadata.seed = Some(seed);
+        metadata.weather.rng_state = seed;
+    }
+}
+
+/// Startup system that loads world metadata from the save directory (if present), and hands the
+/// world's generator settings (with its seed, for the biome map) to the `WorldManager` and the seed
+/// to the `Climate`
+/// A new world is stamped (see `stamp_new_world`) and its metadata written straight away, so the
+/// seed its first chunks are generated with is the one it keeps
+pub fn load_world_metadata(
+    mut world: ResMut<WorldManager>,
+    mut metadata: ResMut<WorldMetadata>,
+    mut climate: ResMut<Climate>,
+    mut failures: Failures,
+) {
+    let path = world.get_metadata_path();
+    if path.exists() {
+        match load_metadata(&path) {
+            Ok(loaded) => {
+                info!("Loaded world metadata with {} waypoints", loaded.waypoints.len());
+                *metadata.bypass_change_detection() = loaded;
+            }
+            Err(e) => failures.report(format!("Failed to load world metadata from {}", path.display()), e),
+        }
+    } else if world.save_directory.join("chunks").exists() {
+        // Saved before worlds had metadata files
+        metadata.generator = GeneratorConfig::legacy();
+    } else {
+        stamp_new_world(metadata.bypass_change_detection(), &world.save_directory, unix_time());
+        info!("Created world '{}' with seed {:016x}", metadata.name, metadata.seed.unwrap_or_default());
+        if let Err(e) = save_metadata(&metadata, &path) {
+            failures.report("Failed to save world metadata", e);
+        }
+    }
+    world.generator = GeneratorConfig {
+        seed: metadata.seed.unwrap_or(DEFAULT_SEED),
+        params: world.generator.params,
+        ..metadata.generator
+    };
+    *climate = Climate::new(world.generator.seed);
+}
+
+/// System to save world metadata whenever it changes
+pub fn save_world_metadata(world: Res<WorldManager>, metadata: Res<WorldMetadata>, mut failures: Failures) {
+    if !metadata.is_changed() || metadata.is_added() {
+        return;
+    }
+
+    let path = world.get_metadata_path();
+    if let Err(e) = save_metadata(&metadata, &path) {
+        failures.report("Failed to save world metadata", e);
+    }
+}
+
+/// For systems that copy live state (clock, statistics, session...) into `WorldMetadata`
+/// every so often and when the window closes
+#[derive(SystemParam)]
+pub struct MetadataSync<'w, 's> {
+    time: Res<'w, Time>,
+    elapsed: Local<'s, f32>,
+    close_requests: MessageReader<'w, 's, WindowCloseRequested>,
+    metadata: ResMut<'w, WorldMetadata>,
+}
+
+impl MetadataSync<'_, '_> {
+    /// Whether `interval` seconds passed since the last sync or the window is closing
+    pub fn due(&mut self, interval: f32) -> bool {
+        *self.elapsed += self.time.delta_secs();
+        let closing = self.close_requests.read().count() > 0;
+        if *self.elapsed < interval && !closing {
+            return false;
+        }
+        *self.elapsed = 0.0;
+        true
+    }
+
+    /// Copy `value` into the field `field` picks out
+    /// Only touches metadata (and triggers a save) when something actually changed
+    pub fn update<T: PartialEq>(&mut self, field: impl Fn(&mut WorldMetadata) -> &mut T, value: T) {
+        if *field(self.metadata.bypass_change_detection()) != value {
+            *field(&mut self.metadata) = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::generator::GroundPattern;
+    use std::env;
+
+    #[test]
+    fn test_save_and_load_metadata() {
+        let path = env::temp_dir().join("test_world.meta");
+
+        let mut original = WorldMetadata::default();
+        original.add_waypoint(Waypoint::new("Camp", 12.5, -40.0, [1.0, 0.2, 0.2]));
+        original.add_waypoint(Waypoint::new("Grove", -300.0, 256.0, [0.2, 1.0, 0.2]));
+        original.session.camera = [512.0, -96.0];
+        original.session.zoom = 2.5;
+        original.session.placement = Some("guardian:birch".to_string());
+        original.session.map_open = true;
+        original.statistics.tiles_painted = 42;
+        original.statistics.camera_distance = 1234.5;
+        let milestone = ChronicleEntry {
+            day: 3,
+            text: "The first forest guardian was placed".to_string(),
+            milestone: Some("first_guardian".to_string()),
+        };
+        assert!(original.record_chronicle(milestone.clone()));
+        assert!(!original.record_chronicle(ChronicleEntry { day: 9, ..milestone }));
+        assert_eq!(original.chronicle.len(), 1);
+        original.traffic.insert((-12, 40), 24);
+        original.protected_regions.push(ProtectedRegion::new("Village", Vec2::new(64.0, 0.0), Vec2::new(-64.0, 128.0)));
+        assert_eq!(original.protected_region_at(Vec2::new(-10.0, 100.0)).map(|region| region.name.as_str()), Some("Village"));
+        assert!(original.protected_region_at(Vec2::new(-10.0, -1.0)).is_none());
+        original.signs.push(Sign::new("Old Oak Road", Vec2::new(40.0, 40.0)));
+        original.signs.push(Sign::new("Camp ->", Vec2::new(60.0, 40.0)));
+        assert_eq!(original.sign_near(Vec2::new(55.0, 45.0), 16.0), Some(1));
+        assert_eq!(original.sign_near(Vec2::new(0.0, 0.0), 16.0), None);
+        assert_eq!(original.find_waypoint("2").map(|waypoint| waypoint.name.as_str()), None);
+        assert_eq!(original.find_waypoint("grove").map(|waypoint| waypoint.name.as_str()), Some("Grove"));
+
+        save_metadata(&original, &path).expect("Failed to save metadata");
+        let loaded = load_metadata(&path).expect("Failed to load metadata");
+
+        assert_eq!(loaded, original);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let loaded: WorldMetadata = ron::from_str("()").expect("Failed to parse empty metadata");
+        assert!(loaded.waypoints.is_empty());
+        assert_eq!(loaded.session, SessionState::default());
+
+        // Older files with a partial session keep defaults for the rest
+        let loaded: WorldMetadata =
+            ron::from_str("(session: (zoom: 2.0))").expect("Failed to parse partial session");
+        assert_eq!(loaded.session.zoom, 2.0);
+        assert!(loaded.session.minimap_visible);
+        assert_eq!(loaded.statistics, WorldStatistics::default());
+        // Worlds from before generator settings were recorded keep the checkerboard
+        assert_eq!(loaded.generator, GeneratorConfig::legacy());
+        assert_eq!(WorldMetadata::default().generator.ground, GroundPattern::Biomes);
+    }
+
+    #[test]
+    fn test_stamp_new_world() {
+        let mut metadata = WorldMetadata::default();
+        stamp_new_world(&mut metadata, Path::new("saves/meadow"), Duration::from_secs(1_700_000_000));
+        assert_eq!(metadata.name, "meadow");
+        assert_eq!(metadata.created, Some(1_700_000_000));
+        assert!(!metadata.versions.is_outdated());
+        assert_eq!(metadata.seed.map(|seed| seed == metadata.weather.rng_state), Some(true));
+
+        // A given seed and name are kept
+        let mut metadata = WorldMetadata { name: "Meadow".to_string(), seed: Some(42), ..default() };
+        stamp_new_world(&mut metadata, Path::new("saves/meadow"), Duration::from_secs(5));
+        assert_eq!((metadata.name.as_str(), metadata.seed), ("Meadow", Some(42)));
+    }
+}
@@ -0,0 +1,196 @@
+use super::backup;
+use super::manager::WorldManager;
+use super::serialization;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::platform::unix_time;
+use bevy::prelude::*;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the migration report inside the save directory
+pub const MIGRATION_REPORT_FILE_NAME: &str = "migration_report.txt";
+
+/// Result of upgrading a directory of chunk files
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Chunk files looked at
+    pub scanned: usize,
+    /// Upgraded files and the version they were upgraded from
+    pub upgraded: Vec<(PathBuf, u16)>,
+    /// Files already in the current format
+    pub current: usize,
+    /// Files that couldn't be read or rewritten (left untouched)
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl MigrationReport {
+    /// One-line summary for the console and log
+    pub fn summary(&self) -> String {
+        format!(
+            "Scanned {} chunk files: {} upgraded to v{}, {} already current, {} failed",
+            self.scanned,
+            self.upgraded.len(),
+            serialization::VERSION,
+            self.current,
+            self.failed.len()
+        )
+    }
+
+    /// Full report written to `MIGRATION_REPORT_FILE_NAME`
+    pub fn to_text(&self) -> String {
+        let timestamp = unix_time().as_secs();
+
+        let mut text = format!("Chunk migration report (unix time {})\n{}\n", timestamp, self.summary());
+        if !self.upgraded.is_empty() {
+            text.push_str("\nUpgraded:\n");
+            for (path, version) in &self.upgraded {
+                text.push_str(&format!("  {} (v{} -> v{})\n", path.display(), version, serialization::VERSION));
+            }
+        }
+        if !self.failed.is_empty() {
+            text.push_str("\nFailed:\n");
+            for (path, error) in &self.failed {
+                text.push_str(&format!("  {}: {}\n", path.display(), error));
+            }
+        }
+        text
+    }
+}
+
+/// Rewrite one chunk file in the current format, keeping its modification time
+/// The old file goes into the backups like any other save
+fn upgrade_chunk_file(path: &Path, backup_dir: &Path, retention: usize) -> Result<(), String> {
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).map_err(|e| e.to_string())?;
+    let chunk = serialization::load_chunk(path).map_err(|e| e.to_string())?;
+    backup::save_chunk_with_backup(&chunk, path, backup_dir, retention).map_err(|e| e.to_string())?;
+    File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_modified(modified))
+        .map_err(|e| format!("upgraded, but failed to restore the timestamp: {}", e))
+}
+
+/// Upgrade every chunk file in `chunks_dir` that is older than the current format
+pub fn migrate_chunks(chunks_dir: &Path, backup_dir: &Path, retention: usize) -> Result<MigrationReport, io::Error> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(chunks_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "bin"))
+        .collect();
+    paths.sort();
+
+    let mut report = MigrationReport::default();
+    for path in paths {
+        report.scanned += 1;
+        match serialization::read_chunk_version(&path) {
+            Ok(serialization::VERSION) => report.current += 1,
+            Ok(version) if version < serialization::VERSION => {
+                match upgrade_chunk_file(&path, backup_dir, retention) {
+                    Ok(()) => report.upgraded.push((path, version)),
+                    Err(e) => report.failed.push((path, e)),
+                }
+            }
+            Ok(version) => report.failed.push((path, format!("unknown version {}", version))),
+            Err(e) => report.failed.push((path, e.to_string())),
+        }
+    }
+    Ok(report)
+}
+
+/// Console command: `migrate` upgrades every old chunk file in the world to the current
+/// format and writes `migration_report.txt` to the save directory
+pub fn handle_migration_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    world: Res<WorldManager>,
+) {
+    for command in console_commands.read() {
+        if command.name != "migrate" {
+            continue;
+        }
+
+        let chunks_dir = world.save_directory.join("chunks");
+        if !chunks_dir.exists() {
+            console.print("No saved chunks to migrate");
+            continue;
+        }
+
+        match migrate_chunks(&chunks_dir, &world.get_backup_dir(), world.backup_retention) {
+            Ok(report) => {
+                info!("{}", report.summary());
+                console.print(report.summary());
+                for (path, error) in &report.failed {
+                    console.print(format!("  {}: {}", path.display(), error));
+                }
+
+                let report_path = world.save_directory.join(MIGRATION_REPORT_FILE_NAME);
+                match fs::write(&report_path, report.to_text()) {
+                    Ok(()) => console.print(format!("Report written to {:?}", report_path)),
+                    Err(e) => console.print(format!("Failed to write report: {}", e)),
+                }
+            }
+            Err(e) => console.print(format!("Migration failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{ChunkData, ChunkPos, CHUNK_AREA, TILE_GRASS};
+    use std::env;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// Write a v1 (single layer) chunk file by hand
+    fn write_v1_chunk(path: &Path, position: ChunkPos) -> Vec<u16> {
+        let tiles: Vec<u16> = (0..CHUNK_AREA).map(|i| (i % 5) as u16).collect();
+        let body: Vec<u8> = tiles.iter().flat_map(|tile| tile.to_le_bytes()).collect();
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"TILE");
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&position.x.to_le_bytes());
+        bytes.extend_from_slice(&position.y.to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        fs::write(path, bytes).unwrap();
+        tiles
+    }
+
+    #[test]
+    fn test_migrate_v1_chunks() {
+        let dir = env::temp_dir().join("worldseed_test_migration");
+        let _ = fs::remove_dir_all(&dir);
+        let chunks_dir = dir.join("chunks");
+        let backup_dir = dir.join("backups");
+        fs::create_dir_all(&chunks_dir).unwrap();
+
+        let old_path = chunks_dir.join("chunk_2_-3.bin");
+        let tiles = write_v1_chunk(&old_path, ChunkPos::new(2, -3));
+        let modified = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        File::options().write(true).open(&old_path).unwrap().set_modified(modified).unwrap();
+
+        let current_path = chunks_dir.join("chunk_0_0.bin");
+        serialization::save_chunk(&ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS), &current_path).unwrap();
+        fs::write(chunks_dir.join("chunk_9_9.bin"), b"garbage").unwrap();
+
+        let report = migrate_chunks(&chunks_dir, &backup_dir, 5).unwrap();
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.upgraded, vec![(old_path.clone(), 1)]);
+        assert_eq!(report.current, 1);
+        assert_eq!(report.failed.len(), 1);
+
+        assert_eq!(serialization::read_chunk_version(&old_path).unwrap(), serialization::VERSION);
+        assert_eq!(fs::metadata(&old_path).unwrap().modified().unwrap(), modified);
+        let upgraded = serialization::load_chunk(&old_path).unwrap();
+        assert_eq!(upgraded.layers[0].to_vec(), tiles);
+        assert_eq!(backup::list_backups(&old_path, &backup_dir).len(), 1);
+
+        // Running again has nothing left to do
+        let report = migrate_chunks(&chunks_dir, &backup_dir, 5).unwrap();
+        assert!(report.upgraded.is_empty());
+        assert_eq!(report.current, 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,8 +1,159 @@
+pub mod archive;
+pub mod autosave;
+pub mod backup;
+pub mod biome;
+pub mod climate;
+pub mod deposits;
+pub mod diagnostics;
+pub mod dump;
+pub mod elevation;
+pub mod entity_serialization;
+pub mod explored;
 pub mod generator;
 pub mod loader;
 pub mod manager;
+pub mod metadata;
+pub mod migration;
+pub mod model;
+pub mod origin;
+pub mod pool;
+pub mod protection;
+pub mod render;
+pub mod save;
+pub mod seed_code;
 pub mod serialization;
+pub mod storage;
+pub mod structures;
+pub mod tiled;
+pub mod upgrade;
+pub mod worlds;
+pub mod worldgen;
 
 // Re-export commonly used items
+pub use explored::ExploredChunks;
+pub use biome::Biome;
+pub use climate::{Climate, ClimateSample};
 pub use generator::generate_chunk;
-pub use manager::{TileModification, WorldManager, WorldStats};
+pub use manager::{footprint_tiles, FootprintError, TileEdits, TileModificationEvent, WorldManager, WorldStats};
+pub use metadata::{ChronicleEntry, ClockState, MetadataSync, ProtectedRegion, SaveVersions, SessionState, Sign, Waypoint, WeatherState, WorldMetadata, WorldStatistics, MAX_SIGN_TEXT_LENGTH};
+pub use origin::WorldOrigin;
+pub use protection::TileEditBlocked;
+pub use save::{SaveWorldJob, SaveWorldRequest};
+pub use structures::{Structure, StructureKind};
+
+use crate::console::{console_closed, ConsoleAppExt};
+use crate::debug_hud::DebugSettings;
+use crate::mods::RegistriesChanged;
+use crate::schedule::{add_game_sets, GameSet};
+use crate::tiles::TileRegistry;
+use bevy::prelude::*;
+
+/// Plugin for the tile world: world metadata, explored chunks, manual saves, autosave, upgrading
+/// worlds from older versions and the world management console commands; adds `WorldLoaderPlugin` for chunk streaming
+/// Insert a configured `WorldManager` before adding it to override the default
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(WorldLoaderPlugin)
+            .init_resource::<WorldMetadata>()
+            .init_resource::<SaveWorldJob>()
+            .init_resource::<autosave::AutosaveState>()
+            .init_resource::<ExploredChunks>()
+            .init_resource::<archive::PendingExport>()
+            .init_resource::<upgrade::WorldUpgrade>()
+            .init_resource::<worldgen::WorldgenWatcher>()
+            .init_resource::<Climate>()
+            .add_message::<SaveWorldRequest>()
+            .add_console_command("backups", "backups <x> <y> - list backups of a chunk (newest first)")
+            .add_console_command("restore", "restore <x> <y> [n] - roll a chunk back to backup n (default 0)")
+            .add_console_command("export", "export <file> - save the world and write it to one compressed archive")
+            .add_console_command("import", "import <file> [name] - extract a world archive into saves/<name>")
+            .add_console_command("dumpchunk", "dumpchunk <x> <y> [file] - write a chunk as readable RON")
+            .add_console_command("loadchunk", "loadchunk <file> - replace a chunk with a RON dump")
+            .add_console_command("importtiled", "importtiled <file.tmx> [x y] - write a Tiled map into the world, top-left at tile (x, y)")
+            .add_console_command("renderpng", "renderpng <x1> <y1> <x2> <y2> [scale] [entities] [file] - render the tiles between two corners to a PNG")
+            .add_console_command("seedcode", "seedcode - show a code that creates this world elsewhere (--seed-code)")
+            .add_console_command("worlds", "worlds - list saved worlds")
+            .add_console_command("duplicate", "duplicate <from> <to> - copy a saved world")
+            .add_console_command("rename", "rename <from> <to> - rename a saved world (not the open one)")
+            .add_console_command("migrate", "migrate - upgrade every old-format chunk file and write a report")
+            .add_console_command("regenerate", "regenerate [x y | stale] - discard a chunk and generate it again (default: under the cursor; stale: unpainted ones from an older generator)")
+            .add_console_command("protect", "protect [add <name> <waypoint> <waypoint> | remove <name>] - list or change regions no tile changes can touch")
+            .add_systems(Startup, (
+                save::setup_save_indicator,
+                autosave::setup_autosave_ui,
+                metadata::load_world_metadata,
+                worldgen::load_worldgen_file.before(metadata::load_world_metadata),
+                explored::load_explored_chunks,
+                upgrade::setup_upgrade_dialog,
+                // Before anything is saved in the new format
+                upgrade::check_world_version.after(metadata::load_world_metadata),
+            ))
+            .add_systems(Update, (
+                metadata::save_world_metadata,
+                explored::track_explored_chunks,
+                upgrade::update_upgrade_dialog,
+                worldgen::watch_worldgen_file,
+            ))
+            .add_systems(Update, (
+                // Manual save (Ctrl+S / Save button)
+                save::request_save_shortcut.run_if(console_closed),
+                save::start_save_world.after(save::request_save_shortcut),
+                save::run_save_world.after(save::start_save_world),
+                save::update_save_indicator.after(save::run_save_world),
+                // Autosave
+                autosave::run_autosave.after(loader::apply_tile_modifications),
+                autosave::save_dirty_chunks_on_exit.after(loader::apply_tile_modifications),
+                autosave::update_autosave_ui.after(autosave::run_autosave),
+                // Console commands
+                backup::handle_backup_commands,
+                archive::handle_archive_commands,
+                dump::handle_dump_commands,
+                tiled::handle_tiled_commands,
+                render::handle_render_commands,
+                migration::handle_migration_commands,
+                worlds::handle_world_commands,
+                seed_code::handle_seed_code_commands,
+                protection::handle_protect_commands,
+                worldgen::handle_regenerate_commands,
+                protection::report_blocked_edits.after(loader::apply_tile_modifications),
+                archive::finish_pending_export
+                    .after(save::run_save_world)
+                    .before(save::update_save_indicator),
+            ));
+    }
+}
+
+/// Plugin for chunk streaming: loads and unloads chunks around the camera and applies queued
+/// tile modifications (systems that queue them must run before `loader::apply_tile_modifications`),
+/// except inside protected regions
+pub struct WorldLoaderPlugin;
+
+impl Plugin for WorldLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        add_game_sets(app);
+        diagnostics::add_world_diagnostics(app);
+        app.init_resource::<WorldManager>()
+            .init_resource::<TileRegistry>()
+            .init_resource::<model::PendingTileChanges>()
+            .init_resource::<WorldOrigin>()
+            .init_resource::<DebugSettings>()
+            // Read when applying tile changes, for the protected regions
+            .init_resource::<WorldMetadata>()
+            .add_message::<TileModificationEvent>()
+            .add_message::<TileEditBlocked>()
+            .add_message::<RegistriesChanged>()
+            .add_systems(Update, (
+                origin::shift_world_origin.before(loader::update_camera_chunk),
+                loader::update_camera_chunk,
+                loader::load_chunks_around_camera.after(loader::update_camera_chunk),
+                loader::poll_chunk_reads.after(loader::load_chunks_around_camera),
+                loader::unload_distant_chunks.after(loader::poll_chunk_reads),
+                loader::poll_chunk_saves.after(loader::unload_distant_chunks),
+                loader::apply_tile_modifications.after(loader::unload_distant_chunks),
+                loader::update_ground_edges.after(loader::apply_tile_modifications),
+            ).in_set(GameSet::WorldApply))
+            .add_systems(Update, loader::refresh_chunks_on_registry_change.in_set(GameSet::Render));
+    }
+}
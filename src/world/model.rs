@@ -0,0 +1,523 @@
+//! World logic that doesn't touch the ECS: chunk streaming math, reading chunk data, applying tile
+//! changes to the cache and what happens to a chunk's data when it unloads
+//! The loader systems gather their inputs from queries and hand them to these functions, so
+//! everything here can be tested on a plain `WorldManager`
+
+use super::entity_serialization::SavedEntity;
+use super::generator::{self, GeneratorConfig, GENERATOR_VERSION};
+use super::manager::{TileModificationEvent, WorldManager};
+use super::serialization::{self, SerializationError};
+use super::storage::ChunkStore;
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkData, ChunkPos, TileId, CHUNK_LOAD_RADIUS, CHUNK_PIXEL_SIZE, CHUNK_SIZE, NUM_LAYERS};
+use bevy::ecs::resource::Resource;
+use bevy::math::Vec2;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Most queued tile changes applied in one frame; the rest carry over to the next
+pub const MAX_TILE_CHANGES_PER_FRAME: usize = 4096;
+
+/// Chunks kept loaded beyond the visible area, so panning doesn't show unloaded edges
+const LOAD_BUFFER: i32 = 2;
+
+/// Extra radius before a chunk unloads, so chunks at the edge don't load and unload repeatedly
+const UNLOAD_HYSTERESIS: i32 = 2;
+
+/// Load radius covering a viewport of `viewport` pixels at an orthographic `scale`, plus a buffer
+/// Never smaller than `CHUNK_LOAD_RADIUS`
+pub fn load_radius_for_viewport(viewport: Vec2, scale: f32) -> i32 {
+    let half_extent = viewport / 2.0 * scale;
+    let chunks_horizontal = (half_extent.x / CHUNK_PIXEL_SIZE).ceil() as i32;
+    let chunks_vertical = (half_extent.y / CHUNK_PIXEL_SIZE).ceil() as i32;
+    (chunks_horizontal.max(chunks_vertical) + LOAD_BUFFER).max(CHUNK_LOAD_RADIUS)
+}
+
+/// Radius beyond which loaded chunks unload
+pub fn unload_radius(load_radius: i32) -> i32 {
+    load_radius + UNLOAD_HYSTERESIS
+}
+
+/// Chunks overlapping a viewport of `viewport` pixels centered on `center` at an orthographic `scale`
+pub fn visible_chunks(center: Vec2, viewport: Vec2, scale: f32) -> HashSet<ChunkPos> {
+    let half_extent = viewport / 2.0 * scale;
+    let min = coords::world_to_chunk(center - half_extent);
+    let max = coords::world_to_chunk(center + half_extent);
+    (min.x..=max.x)
+        .flat_map(|x| (min.y..=max.y).map(move |y| ChunkPos::new(x, y)))
+        .collect()
+}
+
+/// Chunks within `radius` of `center` that aren't loaded yet (or being read, or refused)
+pub fn chunks_to_load(world: &WorldManager, center: ChunkPos, radius: i32) -> Vec<ChunkPos> {
+    center
+        .chunks_in_radius(radius)
+        .into_iter()
+        .filter(|pos| !world.is_loaded(pos) && !world.pending_loads.contains_key(pos) && !world.refused_chunks.contains(pos))
+        .collect()
+}
+
+/// Loaded chunks farther than `radius` from `center`
+pub fn chunks_to_unload(world: &WorldManager, center: ChunkPos, radius: i32) -> Vec<ChunkPos> {
+    world
+        .active_chunks
+        .keys()
+        .filter(|pos| center.chebyshev_distance(pos) > radius)
+        .copied()
+        .collect()
+}
+
+/// Where `read_chunk` found a chunk's data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkSource {
+    Cache,
+    Disk,
+    Generated,
+}
+
+/// A chunk's data: the cached copy, else the saved one, else freshly generated
+/// Copies and generated chunks reuse buffers from `world.pool`
+/// Read errors are returned as they are; recovering from them is up to the caller
+pub fn read_chunk(world: &mut WorldManager, pos: ChunkPos) -> Result<(ChunkData, ChunkSource), SerializationError> {
+    if let Some(cached) = world.copy_cached_chunk(&pos) {
+        return Ok((cached, ChunkSource::Cache));
+    }
+    if let Some(save) = world.pending_saves.get(&pos) {
+        return Ok((serialization::decode_chunk(&save.bytes)?, ChunkSource::Disk));
+    }
+    let buffer = world.pool.take_chunk(pos);
+    let read = read_uncached_chunk(&*world.store, &world.get_chunk_path(&pos), &world.generator, buffer);
+    if let Some(spare) = read.spare {
+        world.pool.recycle_chunk(spare);
+    }
+    read.result
+}
+
+/// Result of `read_uncached_chunk`, with the buffer handed in if it wasn't needed
+pub struct UncachedRead {
+    pub result: Result<(ChunkData, ChunkSource), SerializationError>,
+    pub spare: Option<ChunkData>,
+}
+
+/// The saved chunk at `path`, else `generator` output written into `buffer` (an empty chunk at the
+/// right position). Needs nothing from the `WorldManager`, so the loader runs it on the task pool
+pub fn read_uncached_chunk(store: &dyn ChunkStore, path: &Path, generator: &GeneratorConfig, mut buffer: ChunkData) -> UncachedRead {
+    let stored = store
+        .read(path)
+        .map_err(SerializationError::from)
+        .and_then(|bytes| bytes.map(|bytes| serialization::decode_chunk(&bytes)).transpose());
+    match stored {
+        Ok(None) => {
+            generator::generate_into(&mut buffer, generator);
+            UncachedRead { result: Ok((buffer, ChunkSource::Generated)), spare: None }
+        }
+        Ok(Some(stored)) => UncachedRead { result: Ok((stored, ChunkSource::Disk)), spare: Some(buffer) },
+        Err(e) => UncachedRead { result: Err(e), spare: Some(buffer) },
+    }
+}
+
+/// Read a chunk into the cache ahead of the camera, so loading it later skips the disk and the
+/// generator; None if it is already cached
+pub fn prefetch_chunk(world: &mut WorldManager, pos: ChunkPos) -> Result<Option<ChunkSource>, SerializationError> {
+    if world.chunk_cache.contains_key(&pos) {
+        return Ok(None);
+    }
+    let (chunk, source) = read_chunk(world, pos)?;
+    world.cache_chunk(chunk);
+    Ok(Some(source))
+}
+
+/// Drop a prefetched chunk the camera never loaded (unloading only looks at loaded chunks);
+/// loaded and dirty chunks are kept. Returns whether it was dropped
+pub fn release_prefetched(world: &mut WorldManager, pos: ChunkPos) -> bool {
+    if world.is_loaded(&pos) || world.is_dirty(&pos) {
+        return false;
+    }
+    match world.uncache_chunk(&pos) {
+        Some(chunk) => {
+            world.pool.recycle_chunk(chunk);
+            true
+        }
+        None => false,
+    }
+}
+
+/// A tile change that went into the cache; the tilemap entity of `layer` shows `tile_id` at `index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileUpdate {
+    pub chunk: ChunkPos,
+    pub layer: usize,
+    pub index: usize,
+    pub tile_id: TileId,
+}
+
+impl TileUpdate {
+    /// The tile a modification targets; None if its layer doesn't exist
+    pub fn from_modification(modification: &TileModificationEvent) -> Option<Self> {
+        if modification.layer >= NUM_LAYERS {
+            return None;
+        }
+        let (local_x, local_y) = coords::world_to_local_tile(modification.position());
+        Some(Self {
+            chunk: coords::world_to_chunk(modification.position()),
+            layer: modification.layer,
+            index: local_y * CHUNK_SIZE + local_x,
+            tile_id: modification.tile_id,
+        })
+    }
+}
+
+/// Writes an update into its chunk's data; false if the tile already holds it, so there is nothing
+/// to save or redraw
+fn write_tile(chunk_data: &mut ChunkData, update: &TileUpdate) -> bool {
+    let (local_x, local_y) = (update.index % CHUNK_SIZE, update.index / CHUNK_SIZE);
+    chunk_data.get_tile(update.layer, local_x, local_y).is_some_and(|tile| tile != update.tile_id)
+        && chunk_data.set_tile(update.layer, local_x, local_y, update.tile_id)
+}
+
+/// Apply a tile change to the cached chunk and mark it dirty
+/// None if the chunk isn't cached, the layer doesn't exist or the tile already holds it
+pub fn apply_modification(world: &mut WorldManager, modification: &TileModificationEvent) -> Option<TileUpdate> {
+    let update = TileUpdate::from_modification(modification)?;
+    let chunk_data = world.chunk_cache.get_mut(&update.chunk)?;
+    if !write_tile(chunk_data, &update) {
+        return None;
+    }
+    world.mark_dirty(update.chunk);
+    Some(update)
+}
+
+/// Flag the cached chunk under a painted change as edited by a player, so regenerating stale chunks
+/// keeps it (the flag is saved with the chunk)
+pub fn mark_painted(world: &mut WorldManager, modification: &TileModificationEvent) {
+    if let Some(chunk_data) = world.chunk_cache.get_mut(&coords::world_to_chunk(modification.position())) {
+        chunk_data.edited = true;
+    }
+}
+
+/// Whether a chunk nobody painted was made by an older generator than this build's
+pub fn is_stale(chunk: &ChunkData) -> bool {
+    !chunk.edited && chunk.generator_version < GENERATOR_VERSION
+}
+
+/// Cached stale chunks without unsaved changes, south to north: regenerating them loses nothing a
+/// player made. Chunks saved before the generator version was recorded count as edited, so never show up
+pub fn stale_chunks(world: &WorldManager) -> Vec<ChunkPos> {
+    let mut stale: Vec<ChunkPos> = world
+        .chunk_cache
+        .values()
+        .filter(|chunk| is_stale(chunk) && !world.is_dirty(&chunk.position))
+        .map(|chunk| chunk.position)
+        .collect();
+    stale.sort_by_key(|pos| (pos.y, pos.x));
+    stale
+}
+
+/// Tile changes waiting to be applied: one per tile (the last write wins), grouped by chunk in the
+/// order the chunks were first changed
+#[derive(Resource, Debug, Default)]
+pub struct PendingTileChanges {
+    order: VecDeque<ChunkPos>,
+    /// Tile id by (layer, index) per chunk
+    chunks: HashMap<ChunkPos, HashMap<(usize, usize), TileId>>,
+    len: usize,
+}
+
+impl PendingTileChanges {
+    pub fn push(&mut self, modification: &TileModificationEvent) {
+        let Some(update) = TileUpdate::from_modification(modification) else {
+            return;
+        };
+        let tiles = self.chunks.entry(update.chunk).or_insert_with(|| {
+            self.order.push_back(update.chunk);
+            HashMap::new()
+        });
+        if tiles.insert((update.layer, update.index), update.tile_id).is_none() {
+            self.len += 1;
+        }
+    }
+
+    /// Number of tiles waiting
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Takes up to `limit` changes, oldest chunk first; a chunk that doesn't fit stays first in line
+    pub fn take(&mut self, limit: usize) -> Vec<TileUpdate> {
+        let mut updates = Vec::with_capacity(limit.min(self.len));
+        while updates.len() < limit {
+            let Some(&chunk) = self.order.front() else {
+                break;
+            };
+            let tiles = self.chunks.get_mut(&chunk).expect("queued chunk has changes");
+            let count = (limit - updates.len()).min(tiles.len());
+            let keys: Vec<_> = tiles.keys().take(count).copied().collect();
+            for (layer, index) in keys {
+                let tile_id = tiles.remove(&(layer, index)).expect("key was just listed");
+                updates.push(TileUpdate { chunk, layer, index, tile_id });
+            }
+            if tiles.is_empty() {
+                self.chunks.remove(&chunk);
+                self.order.pop_front();
+            }
+        }
+        self.len -= updates.len();
+        updates
+    }
+}
+
+/// Apply up to `limit` pending changes to the cache, marking each changed chunk dirty once
+/// Changes to chunks that are no longer cached, and ones the tile already holds, are dropped;
+/// returns the ones that applied
+pub fn apply_pending(world: &mut WorldManager, pending: &mut PendingTileChanges, limit: usize) -> Vec<TileUpdate> {
+    let mut applied = pending.take(limit);
+    applied.retain(|update| {
+        world
+            .chunk_cache
+            .get_mut(&update.chunk)
+            .is_some_and(|chunk_data| write_tile(chunk_data, update))
+    });
+    let mut changed: Vec<ChunkPos> = applied.iter().map(|update| update.chunk).collect();
+    changed.dedup();
+    for chunk in changed {
+        world.mark_dirty(chunk);
+    }
+    applied
+}
+
+/// Replace a cached chunk's saved creatures and trees (`ChunkData.entities`) with `entities`
+/// Returns whether they changed (false if the chunk isn't cached)
+pub fn stash_entities(world: &mut WorldManager, pos: ChunkPos, entities: Vec<SavedEntity>) -> bool {
+    let Some(chunk) = world.chunk_cache.get_mut(&pos) else {
+        return false;
+    };
+    if chunk.entities == entities {
+        return false;
+    }
+    chunk.entities = entities;
+    true
+}
+
+/// Add creatures and trees that wandered out of the loaded chunks to the ones saved with the
+/// (unloaded) chunk they stand in, read or generated into the cache if needed, and mark it dirty
+pub fn stash_strays(world: &mut WorldManager, pos: ChunkPos, entities: Vec<SavedEntity>) -> Result<(), SerializationError> {
+    if world.get_cached_chunk(&pos).is_none() {
+        let (chunk, _) = read_chunk(world, pos)?;
+        world.cache_chunk(chunk);
+    }
+    if let Some(chunk) = world.chunk_cache.get_mut(&pos) {
+        chunk.entities.extend(entities);
+    }
+    world.mark_dirty(pos);
+    Ok(())
+}
+
+/// Let go of an unloaded chunk's data: a dirty chunk is written on the `IoTaskPool`
+/// (`WorldManager::save_in_background`), a clean one is dropped from the cache
+/// If the save fails the data comes back cached and dirty, so autosave and Save World retry it
+/// Returns whether a save was started
+pub fn release_chunk(world: &mut WorldManager, pos: ChunkPos) -> bool {
+    if world.is_dirty(&pos) {
+        world.clear_dirty(&pos);
+        return world.save_in_background(&pos);
+    }
+    if let Some(chunk) = world.uncache_chunk(&pos) {
+        world.pool.recycle_chunk(chunk);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{LAYER_GROUND, NUM_LAYERS, TILE_DIRT, TILE_GRASS, TILE_SIZE};
+    use bevy::ecs::entity::Entity;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_streaming_math() {
+        // A small window still loads the minimum radius
+        assert_eq!(load_radius_for_viewport(Vec2::new(100.0, 100.0), 1.0), CHUNK_LOAD_RADIUS);
+        // Zoomed out far enough, the radius follows the longer side
+        let wide = load_radius_for_viewport(Vec2::new(CHUNK_PIXEL_SIZE * 40.0, 100.0), 1.0);
+        assert_eq!(wide, 20 + LOAD_BUFFER);
+        assert_eq!(unload_radius(wide), wide + UNLOAD_HYSTERESIS);
+
+        let visible = visible_chunks(Vec2::ZERO, Vec2::splat(CHUNK_PIXEL_SIZE), 1.0);
+        assert_eq!(visible.len(), 4);
+        assert!(visible.contains(&ChunkPos::new(-1, -1)) && visible.contains(&ChunkPos::new(0, 0)));
+
+        let mut world = WorldManager::new(env::temp_dir().join("worldseed_test_model_math"));
+        world.register_chunk(ChunkPos::new(0, 0), [Entity::PLACEHOLDER; NUM_LAYERS]);
+        world.register_chunk(ChunkPos::new(5, 0), [Entity::PLACEHOLDER; NUM_LAYERS]);
+        assert_eq!(chunks_to_load(&world, ChunkPos::new(0, 0), 1).len(), 8);
+        world.refused_chunks.insert(ChunkPos::new(1, 1));
+        assert_eq!(chunks_to_load(&world, ChunkPos::new(0, 0), 1).len(), 7);
+        assert_eq!(chunks_to_unload(&world, ChunkPos::new(0, 0), 4), vec![ChunkPos::new(5, 0)]);
+    }
+
+    #[test]
+    fn test_pending_tile_changes() {
+        let mut world = WorldManager::new(env::temp_dir().join("worldseed_test_model_pending"));
+        let (first, second) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+        world.cache_chunk(ChunkData::filled(first, TILE_GRASS));
+        let tile = |chunk: ChunkPos, x: f32| chunk.to_world(CHUNK_PIXEL_SIZE) + Vec2::new(x + 0.5, 0.5) * TILE_SIZE;
+
+        // Repeated writes to a tile keep the last; a layer that doesn't exist is ignored
+        let mut pending = PendingTileChanges::default();
+        for tile_id in [TILE_GRASS, TILE_DIRT] {
+            pending.push(&TileModificationEvent::new(tile(first, 0.0), tile_id, LAYER_GROUND));
+        }
+        pending.push(&TileModificationEvent::new(tile(first, 0.0), TILE_GRASS, NUM_LAYERS));
+        for x in 0..3 {
+            pending.push(&TileModificationEvent::new(tile(second, x as f32), TILE_DIRT, LAYER_GROUND));
+        }
+        pending.push(&TileModificationEvent::new(tile(first, 1.0), TILE_DIRT, LAYER_GROUND));
+        assert_eq!(pending.len(), 5);
+
+        // Grouped by chunk, oldest first, and capped with carry-over; the uncached chunk's changes drop
+        let applied = apply_pending(&mut world, &mut pending, 3);
+        assert_eq!(applied.len(), 2);
+        assert!(applied.iter().all(|update| update.chunk == first && update.tile_id == TILE_DIRT));
+        assert_eq!(world.get_cached_chunk(&first).unwrap().get_tile(LAYER_GROUND, 0, 0), Some(TILE_DIRT));
+        assert!(world.is_dirty(&first));
+        assert_eq!(pending.len(), 2);
+        assert!(apply_pending(&mut world, &mut pending, 3).is_empty());
+        assert!(pending.is_empty() && !world.is_dirty(&second));
+
+        // Writing the tile a chunk already holds changes nothing, so the chunk stays clean
+        world.clear_dirty(&first);
+        pending.push(&TileModificationEvent::new(tile(first, 0.0), TILE_DIRT, LAYER_GROUND));
+        assert!(apply_pending(&mut world, &mut pending, 3).is_empty());
+        assert!(!world.is_dirty(&first));
+    }
+
+    #[test]
+    fn test_chunk_data_lifecycle() {
+        let dir = env::temp_dir().join("worldseed_test_model_lifecycle");
+        let _ = fs::remove_dir_all(&dir);
+        let mut world = WorldManager::new(dir.clone());
+        world.backup_retention = 0;
+        let pos = ChunkPos::new(1, -1);
+
+        // Nothing saved or cached: generated
+        let (mut chunk, source) = read_chunk(&mut world, pos).unwrap();
+        assert_eq!(source, ChunkSource::Generated);
+        chunk.set_tile(LAYER_GROUND, 2, 3, TILE_GRASS);
+        world.cache_chunk(chunk);
+
+        // Changes only apply to cached chunks, and mark them dirty
+        let origin = pos.to_world(CHUNK_PIXEL_SIZE);
+        let inside = origin + Vec2::new(2.5, 3.5) * TILE_SIZE;
+        let update = apply_modification(&mut world, &TileModificationEvent::new(inside, TILE_DIRT, LAYER_GROUND));
+        assert_eq!(
+            update,
+            Some(TileUpdate { chunk: pos, layer: LAYER_GROUND, index: 3 * CHUNK_SIZE + 2, tile_id: TILE_DIRT })
+        );
+        assert!(world.is_dirty(&pos));
+        // Autosave waits until the chunk has gone unmodified for the debounce time
+        let now = crate::platform::unix_time();
+        let quiet = std::time::Duration::from_secs(5);
+        assert!(world.get_settled_dirty_chunks(now, quiet).is_empty());
+        assert_eq!(world.get_settled_dirty_chunks(now + quiet, quiet), vec![pos]);
+        let elsewhere = TileModificationEvent::new(Vec2::new(-1000.0, 1000.0), TILE_GRASS, LAYER_GROUND);
+        assert_eq!(apply_modification(&mut world, &elsewhere), None);
+        assert_eq!(read_chunk(&mut world, pos).unwrap().1, ChunkSource::Cache);
+
+        // A failed save keeps the edit cached and dirty
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("chunks"), b"not a directory").unwrap();
+        assert!(release_chunk(&mut world, pos));
+        assert!(!world.is_dirty(&pos) && world.get_cached_chunk(&pos).is_none());
+        // Read back from the bytes in flight until the write is done
+        assert_eq!(read_chunk(&mut world, pos).unwrap().0.get_tile(LAYER_GROUND, 2, 3), Some(TILE_DIRT));
+        let results = world.finish_saves();
+        assert!(matches!(results.as_slice(), [(failed, Err(_))] if *failed == pos));
+        assert!(world.is_dirty(&pos) && world.get_cached_chunk(&pos).is_some());
+
+        // Once saved it is dropped, and read back from disk
+        fs::remove_file(dir.join("chunks")).unwrap();
+        assert!(release_chunk(&mut world, pos));
+        assert!(!world.is_dirty(&pos) && world.get_cached_chunk(&pos).is_none());
+        assert!(world.finish_saves().iter().all(|(_, result)| result.is_ok()));
+        assert!(world.pending_saves.is_empty());
+        let (chunk, source) = read_chunk(&mut world, pos).unwrap();
+        assert_eq!(source, ChunkSource::Disk);
+        assert_eq!(chunk.get_tile(LAYER_GROUND, 2, 3), Some(TILE_DIRT));
+
+        // Off-thread reads hand back the buffer when the chunk comes from disk
+        let read = read_uncached_chunk(&*world.store, &world.get_chunk_path(&pos), &world.generator, ChunkData::empty(pos));
+        assert_eq!(read.result.unwrap().0.get_tile(LAYER_GROUND, 2, 3), Some(TILE_DIRT));
+        assert!(read.spare.is_some());
+        let unsaved = ChunkPos::new(-7, 2);
+        let read = read_uncached_chunk(&*world.store, &world.get_chunk_path(&unsaved), &world.generator, ChunkData::empty(unsaved));
+        assert_eq!(read.result.unwrap().1, ChunkSource::Generated);
+        assert!(read.spare.is_none());
+
+        // Prefetched chunks are cached until loaded or released
+        let ahead = ChunkPos::new(9, 9);
+        assert_eq!(prefetch_chunk(&mut world, ahead).unwrap(), Some(ChunkSource::Generated));
+        assert_eq!(prefetch_chunk(&mut world, ahead).unwrap(), None);
+        assert_eq!(read_chunk(&mut world, ahead).unwrap().1, ChunkSource::Cache);
+        world.register_chunk(ahead, [Entity::PLACEHOLDER; NUM_LAYERS]);
+        assert!(!release_prefetched(&mut world, ahead));
+        world.unregister_chunk(&ahead);
+        assert!(release_prefetched(&mut world, ahead));
+        assert!(world.get_cached_chunk(&ahead).is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_chunks() {
+        let mut world = WorldManager::new(env::temp_dir().join("worldseed_test_model_stale"));
+        let (current, old, painted, dirty) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0), ChunkPos::new(0, 1), ChunkPos::new(-1, 0));
+        world.cache_chunk(generator::generate_chunk(current, &world.generator));
+        for pos in [old, painted, dirty] {
+            world.cache_chunk(ChunkData { generator_version: GENERATOR_VERSION - 1, ..ChunkData::filled(pos, TILE_GRASS) });
+        }
+        world.mark_dirty(dirty);
+
+        // Painting flags the chunk; only the old, unpainted and saved one is stale
+        let inside = painted.to_world(CHUNK_PIXEL_SIZE) + Vec2::splat(0.5) * TILE_SIZE;
+        mark_painted(&mut world, &TileModificationEvent::painted(inside, TILE_DIRT, LAYER_GROUND));
+        assert!(world.get_cached_chunk(&painted).unwrap().edited);
+        assert!(!is_stale(world.get_cached_chunk(&current).unwrap()));
+        assert_eq!(stale_chunks(&world), vec![old]);
+    }
+
+    #[test]
+    fn test_stash_entities() {
+        use crate::world::entity_serialization::SavedComponent;
+
+        let dir = env::temp_dir().join("worldseed_test_model_stash");
+        let _ = fs::remove_dir_all(&dir);
+        let mut world = WorldManager::new(dir.clone());
+        world.backup_retention = 0;
+        let snail = |x: f64| SavedEntity::new(vec![SavedComponent::Kind("snail".to_string()), SavedComponent::Position { x, y: 0.0 }]);
+        let (loaded, away) = (ChunkPos::new(0, 0), ChunkPos::new(4, 0));
+
+        // A chunk's records are replaced, and only count as changed when they differ
+        world.cache_chunk(ChunkData::filled(loaded, TILE_GRASS));
+        assert!(stash_entities(&mut world, loaded, vec![snail(10.0)]));
+        assert!(!stash_entities(&mut world, loaded, vec![snail(10.0)]));
+        assert!(!stash_entities(&mut world, away, vec![snail(10.0)]));
+
+        // Strays are added to their chunk's records, which is read in and saved with them
+        stash_strays(&mut world, away, vec![snail(4200.0)]).unwrap();
+        stash_strays(&mut world, away, vec![snail(4300.0)]).unwrap();
+        assert!(world.is_dirty(&away));
+        assert!(release_chunk(&mut world, away));
+        assert!(world.finish_saves().iter().all(|(_, result)| result.is_ok()));
+        let (chunk, source) = read_chunk(&mut world, away).unwrap();
+        assert_eq!(source, ChunkSource::Disk);
+        assert_eq!(chunk.entities, vec![snail(4200.0), snail(4300.0)]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
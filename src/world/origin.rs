@@ -0,0 +1,112 @@
+//! Floating origin: the world is rendered around a chunk near the camera, so `Transform`s (f32) stay
+//! small and precise however far the camera travels. `Position` and world pixel coordinates are
+//! absolute; convert with `WorldOrigin` wherever they meet a `Transform`
+
+use crate::tiles::chunk::coords;
+use crate::tiles::{ChunkPos, CHUNK_PIXEL_SIZE};
+use bevy::math::DVec2;
+use bevy::prelude::*;
+
+/// Chunks the camera may move from the origin before the origin is moved to it
+const ORIGIN_SHIFT_DISTANCE: i32 = 16;
+
+/// The chunk whose bottom-left corner is at render position (0, 0)
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WorldOrigin(pub ChunkPos);
+
+impl WorldOrigin {
+    /// The origin in world pixels
+    pub fn offset(&self) -> DVec2 {
+        DVec2::new(self.0.x as f64, self.0.y as f64) * CHUNK_PIXEL_SIZE as f64
+    }
+
+    /// World pixels of a render position (a `Transform` translation)
+    pub fn to_world(&self, render: Vec2) -> DVec2 {
+        self.offset() + render.as_dvec2()
+    }
+
+    /// World pixels of a render position in single precision, for tile lookups and world-space messages
+    pub fn to_world_vec2(&self, render: Vec2) -> Vec2 {
+        self.to_world(render).as_vec2()
+    }
+
+    /// Render position of a world position in pixels
+    pub fn to_render(&self, world: Vec2) -> Vec2 {
+        (world.as_dvec2() - self.offset()).as_vec2()
+    }
+
+    /// Render position of a chunk's bottom-left corner, exact however far the chunk is
+    pub fn chunk_translation(&self, chunk: ChunkPos) -> Vec2 {
+        let axis = |chunk: i32, origin: i32| ((chunk as i64 - origin as i64) * CHUNK_PIXEL_SIZE as i64) as f32;
+        Vec2::new(axis(chunk.x, self.0.x), axis(chunk.y, self.0.y))
+    }
+
+    /// A chunk relative to the origin chunk (e.g. from `coords::world_to_chunk` of a render position)
+    /// as an absolute chunk
+    pub fn chunk_from_render(&self, local: ChunkPos) -> ChunkPos {
+        ChunkPos::new(self.0.x.saturating_add(local.x), self.0.y.saturating_add(local.y))
+    }
+
+    /// The chunk under a render position, without going through (imprecise) world pixels
+    pub fn chunk_at(&self, render: Vec2) -> ChunkPos {
+        self.chunk_from_render(coords::world_to_chunk(render))
+    }
+
+    /// The origin to move to when the camera is at `render`, if it has drifted too far
+    pub fn shifted_for(&self, render: Vec2) -> Option<WorldOrigin> {
+        let camera = self.chunk_at(render);
+        (camera.chebyshev_distance(&self.0) > ORIGIN_SHIFT_DISTANCE).then_some(WorldOrigin(camera))
+    }
+}
+
+/// Moves the origin to the camera's chunk once the camera drifts `ORIGIN_SHIFT_DISTANCE` chunks
+/// away, shifting every top-level `Transform` (camera, chunks, sprites) so nothing visibly moves
+pub fn shift_world_origin(
+    mut origin: ResMut<WorldOrigin>,
+    camera: Single<Entity, With<Camera2d>>,
+    mut transforms: Query<&mut Transform, Without<ChildOf>>,
+) {
+    let Ok(camera) = transforms.get(*camera) else {
+        return;
+    };
+    let Some(shifted) = origin.shifted_for(camera.translation.truncate()) else {
+        return;
+    };
+    // Whole chunks, so the shift is exact in f32 for any realistic camera speed
+    let shift = (shifted.offset() - origin.offset()).as_vec2();
+    for mut transform in &mut transforms {
+        transform.translation -= shift.extend(0.0);
+    }
+    info!("World origin moved to chunk {:?}", shifted.0);
+    *origin = shifted;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Position;
+
+    #[test]
+    fn test_world_origin() {
+        let origin = WorldOrigin(ChunkPos::new(1_000_000, -3));
+        let far = DVec2::new(1_000_000.0 * 256.0 + 100.25, -3.0 * 256.0 - 0.5);
+        assert_eq!(origin.to_world(Vec2::new(100.25, -0.5)), far);
+        assert_eq!(origin.chunk_at(Vec2::new(100.25, -0.5)), ChunkPos::new(1_000_000, -4));
+        assert_eq!(origin.chunk_translation(ChunkPos::new(1_000_002, -4)), Vec2::new(512.0, -256.0));
+
+        // Positions keep sub-pixel steps far from the origin, and render next to it
+        let mut position = Position::from_dvec2(far);
+        position.translate(Vec2::new(0.125, 0.0));
+        assert_eq!(position.render_translation(origin.offset()), Vec2::new(100.375, -0.5));
+        assert_eq!(Position::from_dvec2(far).distance_to(&position), 0.125);
+
+        // Shifts only once the camera is far enough, to its chunk
+        assert_eq!(origin.shifted_for(Vec2::new(256.0 * 16.0, 0.0)), None);
+        assert_eq!(
+            origin.shifted_for(Vec2::new(256.0 * 17.0 + 1.0, 0.0)),
+            Some(WorldOrigin(ChunkPos::new(1_000_017, -3)))
+        );
+        // Never overflows at the edge of the chunk range
+        assert_eq!(WorldOrigin(ChunkPos::new(i32::MAX, 0)).chunk_at(Vec2::new(1e6, 0.0)).x, i32::MAX);
+    }
+}
@@ -0,0 +1,142 @@
+//! Recycled chunk buffers, so streaming chunks in and out while panning doesn't allocate for each one
+
+use crate::tiles::chunk::tilemap_tile;
+use crate::tiles::{ChunkData, ChunkPos, TileId, CHUNK_AREA, DEFAULT_ELEVATION, NUM_LAYERS, TILE_EMPTY};
+use bevy::sprite_render::TileData;
+
+/// Most tile buffers kept for reuse; a few rings of chunks, enough for fast panning at any zoom
+const MAX_POOLED_CHUNKS: usize = 64;
+
+/// A chunk's tile layers and elevation
+type ChunkBuffers = (Box<[[TileId; CHUNK_AREA]; NUM_LAYERS]>, Box<[u8; CHUNK_AREA]>);
+
+/// Tile buffers of chunks that left the cache and tilemap vectors of despawned chunk layers,
+/// handed out again to chunks that load
+#[derive(Default)]
+pub struct ChunkPool {
+    layers: Vec<ChunkBuffers>,
+    tilemaps: Vec<Vec<Option<TileData>>>,
+}
+
+impl ChunkPool {
+    /// An empty chunk (every layer `TILE_EMPTY`, flat), reusing a pooled buffer if there is one
+    pub fn take_chunk(&mut self, position: ChunkPos) -> ChunkData {
+        match self.layers.pop() {
+            Some((mut layers, mut elevation)) => {
+                for layer in layers.iter_mut() {
+                    layer.fill(TILE_EMPTY);
+                }
+                elevation.fill(DEFAULT_ELEVATION);
+                ChunkData {
+                    position,
+                    layers,
+                    elevation,
+                    settlers: Vec::new(),
+                    entities: Vec::new(),
+                    generator_version: 0,
+                    edited: false,
+                }
+            }
+            None => ChunkData::empty(position),
+        }
+    }
+
+    /// A copy of `chunk` in a pooled buffer
+    pub fn copy_of(&mut self, chunk: &ChunkData) -> ChunkData {
+        match self.layers.pop() {
+            Some((mut layers, mut elevation)) => {
+                *layers = *chunk.layers;
+                *elevation = *chunk.elevation;
+                ChunkData {
+                    position: chunk.position,
+                    layers,
+                    elevation,
+                    settlers: chunk.settlers.clone(),
+                    entities: chunk.entities.clone(),
+                    generator_version: chunk.generator_version,
+                    edited: chunk.edited,
+                }
+            }
+            None => chunk.clone(),
+        }
+    }
+
+    /// Keeps a chunk's buffer for reuse (dropped if the pool is full)
+    pub fn recycle_chunk(&mut self, chunk: ChunkData) {
+        if self.layers.len() < MAX_POOLED_CHUNKS {
+            self.layers.push((chunk.layers, chunk.elevation));
+        }
+    }
+
+    /// `chunk.layer_to_tilemap_data(layer)`, in a pooled vector
+    pub fn tilemap_data(&mut self, chunk: &ChunkData, layer: usize) -> Vec<Option<TileData>> {
+        let Some(mut tiles) = self.tilemaps.pop() else {
+            return chunk.layer_to_tilemap_data(layer);
+        };
+        tiles.clear();
+        match chunk.layers.get(layer) {
+            Some(ids) => tiles.extend(ids.iter().map(|&tile_id| tilemap_tile(tile_id))),
+            None => tiles.resize(CHUNK_AREA, None),
+        }
+        tiles
+    }
+
+    /// Keeps a despawned layer's tile vector for reuse (dropped if the pool is full)
+    pub fn recycle_tilemap_data(&mut self, tiles: Vec<Option<TileData>>) {
+        if tiles.capacity() >= CHUNK_AREA && self.tilemaps.len() < MAX_POOLED_CHUNKS * NUM_LAYERS {
+            self.tilemaps.push(tiles);
+        }
+    }
+
+    /// Buffers waiting for reuse: (chunk buffers, tilemap vectors)
+    pub fn pooled(&self) -> (usize, usize) {
+        (self.layers.len(), self.tilemaps.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{LAYER_DECORATION, LAYER_GROUND, TILE_DIRT, TILE_GRASS};
+
+    #[test]
+    fn test_chunk_pool() {
+        let mut pool = ChunkPool::default();
+        let grass = ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS);
+        let buffer = &*grass.layers as *const _;
+        pool.recycle_chunk(grass);
+        assert_eq!(pool.pooled(), (1, 0));
+
+        // A reused buffer is cleared, and is the same allocation
+        let chunk = pool.take_chunk(ChunkPos::new(3, 4));
+        assert_eq!(&*chunk.layers as *const _, buffer);
+        assert_eq!(chunk.position, ChunkPos::new(3, 4));
+        assert!(chunk.layers.iter().flatten().all(|&tile| tile == TILE_EMPTY));
+
+        let mut dirt = ChunkData::empty(ChunkPos::new(1, 1));
+        dirt.set_tile(LAYER_GROUND, 0, 0, TILE_DIRT);
+        pool.recycle_chunk(chunk);
+        let copy = pool.copy_of(&dirt);
+        assert_eq!(&*copy.layers as *const _, buffer);
+        assert_eq!(copy.layers, dirt.layers);
+        assert_eq!(copy.position, dirt.position);
+
+        // Tilemap vectors match the unpooled conversion
+        pool.recycle_tilemap_data(grass_tiles());
+        for layer in [LAYER_GROUND, LAYER_DECORATION] {
+            assert_eq!(indices(pool.tilemap_data(&dirt, layer)), indices(dirt.layer_to_tilemap_data(layer)));
+            pool.recycle_tilemap_data(grass_tiles());
+        }
+        pool.tilemap_data(&dirt, LAYER_GROUND);
+        pool.recycle_tilemap_data(Vec::new());
+        assert_eq!(pool.pooled(), (0, 0));
+    }
+
+    fn indices(tiles: Vec<Option<TileData>>) -> Vec<Option<u16>> {
+        tiles.iter().map(|tile| tile.map(|tile| tile.tileset_index)).collect()
+    }
+
+    fn grass_tiles() -> Vec<Option<TileData>> {
+        ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS).layer_to_tilemap_data(LAYER_GROUND)
+    }
+}
@@ -0,0 +1,85 @@
+//! Protected regions: rectangles in `WorldMetadata.protected_regions` whose tiles nothing may change
+//! (painting, snail trails, trampling, fire). `loader::apply_tile_modifications` drops changes inside
+//! them; painted ones are reported with a `TileEditBlocked` so the user hears about it
+
+use super::metadata::{ProtectedRegion, WorldMetadata};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::locale::Locale;
+use crate::toast::Toast;
+use bevy::prelude::*;
+
+/// A painted tile change dropped because it was inside a protected region
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct TileEditBlocked {
+    pub position: Vec2,
+    pub region: String,
+}
+
+/// Tells the user their painting was blocked (once per region per frame)
+pub fn report_blocked_edits(
+    mut blocked: MessageReader<TileEditBlocked>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    let mut regions: Vec<&str> = Vec::new();
+    for edit in blocked.read() {
+        if regions.contains(&edit.region.as_str()) {
+            continue;
+        }
+        regions.push(&edit.region);
+        info!("Blocked a tile change at ({:.0}, {:.0}) in protected region '{}'", edit.position.x, edit.position.y, edit.region);
+        toasts.write(Toast::warning(locale.format("toast-region-protected", &[("region", edit.region.clone().into())])));
+    }
+}
+
+/// Console command: `protect [add <name> <waypoint> <waypoint> | remove <name>]`; without arguments
+/// lists the protected regions. Corners are map waypoints (by name or number)
+pub fn handle_protect_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut metadata: ResMut<WorldMetadata>,
+) {
+    const USAGE: &str = "Usage: protect [add <name> <waypoint> <waypoint> | remove <name>]";
+    for command in console_commands.read() {
+        if command.name != "protect" {
+            continue;
+        }
+
+        match command.args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => {
+                if metadata.protected_regions.is_empty() {
+                    console.print("No protected regions");
+                }
+                for region in &metadata.protected_regions {
+                    console.print(format!(
+                        "{}: ({:.0}, {:.0}) - ({:.0}, {:.0})",
+                        region.name, region.min[0], region.min[1], region.max[0], region.max[1]
+                    ));
+                }
+            }
+            ["add", name, a, b] => {
+                let (Some(a), Some(b)) = (metadata.find_waypoint(a), metadata.find_waypoint(b)) else {
+                    console.print("No such waypoint (right-click the map to drop one)");
+                    continue;
+                };
+                if metadata.protected_regions.iter().any(|region| region.name == *name) {
+                    console.print(format!("There already is a protected region called '{}'", name));
+                    continue;
+                }
+                let region = ProtectedRegion::new(*name, a.position(), b.position());
+                metadata.protected_regions.push(region);
+                console.print(format!("Protected '{}'", name));
+            }
+            ["remove", name] => {
+                let before = metadata.protected_regions.len();
+                metadata.protected_regions.retain(|region| region.name != *name);
+                if metadata.protected_regions.len() < before {
+                    console.print(format!("'{}' is no longer protected", name));
+                } else {
+                    console.print(format!("No protected region called '{}'", name));
+                }
+            }
+            _ => console.print(USAGE),
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Region renders (console `renderpng`): draws a rectangle of the world on the CPU, every tile layer
+//! and optionally the entity sprites, into a PNG at a whole-number scale. Unlike the world map (one
+//! color per tile) this keeps the full pixel art, for sharing builds
+
+use super::generator::generate_chunk;
+use super::manager::WorldManager;
+use crate::assets::SpriteAssets;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::Position;
+use crate::environment::CurrentSeason;
+use crate::tiles::{ChunkData, ChunkPos, CHUNK_SIZE_I32, LAYER_GROUND, NUM_LAYERS, TILE_EMPTY, TILE_SIZE};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Output pixels per world pixel unless the command asks otherwise
+pub const DEFAULT_RENDER_SCALE: u32 = 4;
+
+/// Largest scale the command accepts
+pub const MAX_RENDER_SCALE: u32 = 16;
+
+/// Largest render (pixels); 64 Mpx is 256 MiB of RGBA
+const MAX_RENDER_PIXELS: u64 = 64 * 1024 * 1024;
+
+/// Texels per tile side in the terrain tileset
+const TILE_TEXELS: u32 = TILE_SIZE as u32;
+
+/// Error type for region renders
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Region is {width}x{height} px at this scale, over the {MAX_RENDER_PIXELS} px limit")]
+    TooLarge { width: u64, height: u64 },
+    /// An image that isn't loaded or isn't 8-bit RGBA
+    #[error("Unsupported texture: {0}")]
+    Texture(String),
+    #[error("PNG encoding failed: {0}")]
+    Encode(String),
+}
+
+/// A rectangle of tiles, both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl TileRect {
+    /// The rectangle spanned by two corner tiles, in any order
+    pub fn from_corners(a: IVec2, b: IVec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Width and height in tiles
+    pub fn size(&self) -> (u64, u64) {
+        (
+            (self.max.x as i64 - self.min.x as i64 + 1) as u64,
+            (self.max.y as i64 - self.min.y as i64 + 1) as u64,
+        )
+    }
+
+    /// World pixel rectangle it covers
+    pub fn world_rect(&self) -> Rect {
+        Rect::from_corners(self.min.as_vec2() * TILE_SIZE, (self.max + IVec2::ONE).as_vec2() * TILE_SIZE)
+    }
+}
+
+/// 8-bit RGBA pixels, rows top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Pixels {
+    /// Fully transparent pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Pixels of a loaded RGBA8 image; the layers of an array texture are stacked top to bottom
+    pub fn from_image(image: &Image) -> Result<Self, RenderError> {
+        let format = image.texture_descriptor.format;
+        if !matches!(format, TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm) {
+            return Err(RenderError::Texture(format!("{:?} pixels", format)));
+        }
+        let size = image.texture_descriptor.size;
+        let data = image.data.clone().ok_or_else(|| RenderError::Texture("no CPU data".to_string()))?;
+        let pixels = Self {
+            width: size.width,
+            height: size.height * size.depth_or_array_layers,
+            data,
+        };
+        if pixels.data.len() != pixels.width as usize * pixels.height as usize * 4 {
+            return Err(RenderError::Texture("data doesn't match the size".to_string()));
+        }
+        Ok(pixels)
+    }
+
+    /// A copy of the pixels in `rect` (clipped to the image)
+    pub fn crop(&self, rect: URect) -> Self {
+        let max = rect.max.min(UVec2::new(self.width, self.height));
+        let min = rect.min.min(max);
+        let mut cropped = Self::new(max.x - min.x, max.y - min.y);
+        for y in 0..cropped.height {
+            let start = self.index(min.x, min.y + y);
+            let row = cropped.width as usize * 4;
+            let offset = cropped.index(0, y);
+            cropped.data[offset..offset + row].copy_from_slice(&self.data[start..start + row]);
+        }
+        cropped
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [self.data[i], self.data[i + 1], self.data[i + 2], self.data[i + 3]]
+    }
+
+    /// Draws `color` tinted by `tint` over the pixel ("over" alpha blending)
+    fn blend(&mut self, x: u32, y: u32, color: [u8; 4], tint: [f32; 4]) {
+        let alpha = color[3] as f32 / 255.0 * tint[3];
+        if alpha <= 0.0 {
+            return;
+        }
+        let i = self.index(x, y);
+        let below_alpha = self.data[i + 3] as f32 / 255.0;
+        let out_alpha = alpha + below_alpha * (1.0 - alpha);
+        for c in 0..3 {
+            let top = color[c] as f32 * tint[c];
+            let below = self.data[i + c] as f32;
+            self.data[i + c] = ((top * alpha + below * below_alpha * (1.0 - alpha)) / out_alpha).round() as u8;
+        }
+        self.data[i + 3] = (out_alpha * 255.0).round() as u8;
+    }
+}
+
+/// An entity sprite to draw over the tiles
+#[derive(Debug, Clone)]
+pub struct SpriteStamp {
+    /// World pixel position of the sprite's center
+    pub center: Vec2,
+    /// Drawn size in world pixels
+    pub size: Vec2,
+    /// The sprite's frame
+    pub frame: Pixels,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Color multiplier (sRGB + alpha)
+    pub tint: [f32; 4],
+}
+
+/// Renders the tiles of `rect` at `scale` output pixels per world pixel, then the sprites in order
+/// `tileset` holds the tiles stacked top to bottom (tile ID n is drawn from tile n - 1, IDs past the
+/// end are skipped like empty tiles); `chunk_at` provides each chunk the rectangle touches
+pub fn render_region(
+    rect: TileRect,
+    scale: u32,
+    tileset: &Pixels,
+    ground_tint: [f32; 4],
+    sprites: &[SpriteStamp],
+    mut chunk_at: impl FnMut(ChunkPos) -> ChunkData,
+) -> Result<Pixels, RenderError> {
+    let _span = info_span!("render_region").entered();
+    let (tiles_x, tiles_y) = rect.size();
+    let tile_px = TILE_TEXELS * scale;
+    let (width, height) = (tiles_x * tile_px as u64, tiles_y * tile_px as u64);
+    if width.saturating_mul(height) > MAX_RENDER_PIXELS {
+        return Err(RenderError::TooLarge { width, height });
+    }
+    let mut pixels = Pixels::new(width as u32, height as u32);
+    let tileset_tiles = tileset.height / TILE_TEXELS;
+
+    let mut chunks: HashMap<ChunkPos, ChunkData> = HashMap::new();
+    for tile_y in rect.min.y..=rect.max.y {
+        for tile_x in rect.min.x..=rect.max.x {
+            let tile = IVec2::new(tile_x, tile_y);
+            let chunk_pos = ChunkPos::from_tile(tile, CHUNK_SIZE_I32);
+            let chunk = chunks.entry(chunk_pos).or_insert_with(|| chunk_at(chunk_pos));
+            let local = tile.rem_euclid(IVec2::splat(CHUNK_SIZE_I32)).as_uvec2();
+            // Output rows run south, tiles north
+            let left = (tile_x - rect.min.x) as u32 * tile_px;
+            let top = (rect.max.y - tile_y) as u32 * tile_px;
+
+            for layer in 0..NUM_LAYERS {
+                let tile_id = chunk.get_tile(layer, local.x as usize, local.y as usize).unwrap_or(TILE_EMPTY);
+                if tile_id == TILE_EMPTY || tile_id as u32 > tileset_tiles {
+                    continue;
+                }
+                let first_row = (tile_id as u32 - 1) * TILE_TEXELS;
+                let tint = if layer == LAYER_GROUND { ground_tint } else { [1.0; 4] };
+                for y in 0..tile_px {
+                    for x in 0..tile_px {
+                        let texel = tileset.get(x / scale, first_row + y / scale);
+                        pixels.blend(left + x, top + y, texel, tint);
+                    }
+                }
+            }
+        }
+    }
+
+    let area = rect.world_rect();
+    for sprite in sprites {
+        draw_sprite(&mut pixels, area, scale, sprite);
+    }
+    Ok(pixels)
+}
+
+/// Draws a sprite (nearest texel, clipped to the render) onto a render of the world pixels `area`
+fn draw_sprite(pixels: &mut Pixels, area: Rect, scale: u32, sprite: &SpriteStamp) {
+    if sprite.frame.width == 0 || sprite.frame.height == 0 || sprite.size.min_element() <= 0.0 {
+        return;
+    }
+    let scale = scale as f32;
+    // Output pixel rectangle of the sprite
+    let left = (sprite.center.x - sprite.size.x / 2.0 - area.min.x) * scale;
+    let top = (area.max.y - sprite.center.y - sprite.size.y / 2.0) * scale;
+    let (width, height) = (sprite.size.x * scale, sprite.size.y * scale);
+
+    let x_range = left.max(0.0).floor() as u32..(left + width).min(pixels.width as f32).ceil().max(0.0) as u32;
+    let y_range = top.max(0.0).floor() as u32..(top + height).min(pixels.height as f32).ceil().max(0.0) as u32;
+    for y in y_range {
+        let v = (y as f32 + 0.5 - top) / height;
+        if !(0.0..1.0).contains(&v) {
+            continue;
+        }
+        let mut texel_y = (v * sprite.frame.height as f32) as u32;
+        if sprite.flip_y {
+            texel_y = sprite.frame.height - 1 - texel_y;
+        }
+        for x in x_range.clone() {
+            let u = (x as f32 + 0.5 - left) / width;
+            if !(0.0..1.0).contains(&u) {
+                continue;
+            }
+            let mut texel_x = (u * sprite.frame.width as f32) as u32;
+            if sprite.flip_x {
+                texel_x = sprite.frame.width - 1 - texel_x;
+            }
+            pixels.blend(x, y, sprite.frame.get(texel_x, texel_y), sprite.tint);
+        }
+    }
+}
+
+/// Writes a render as a PNG, creating missing directories
+pub fn save_png(pixels: Pixels, path: &Path) -> Result<(), RenderError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let image = Image::new(
+        Extent3d {
+            width: pixels.width,
+            height: pixels.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels.data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image
+        .try_into_dynamic()
+        .map_err(|e| RenderError::Encode(e.to_string()))?
+        .save(path)
+        .map_err(|e| RenderError::Encode(e.to_string()))
+}
+
+/// The frame an entity sprite currently shows, with its drawn size and tint
+fn sprite_stamp(
+    position: &Position,
+    transform: &Transform,
+    sprite: &Sprite,
+    images: &Assets<Image>,
+    layouts: &Assets<TextureAtlasLayout>,
+) -> Result<SpriteStamp, RenderError> {
+    let image = images
+        .get(&sprite.image)
+        .ok_or_else(|| RenderError::Texture(format!("{:?} isn't loaded", sprite.image.path())))?;
+    let pixels = Pixels::from_image(image)?;
+    let source = match (&sprite.texture_atlas, sprite.rect) {
+        (Some(atlas), _) => layouts
+            .get(&atlas.layout)
+            .and_then(|layout| layout.textures.get(atlas.index).copied())
+            .ok_or_else(|| RenderError::Texture("missing atlas frame".to_string()))?,
+        (None, Some(rect)) => rect.as_urect(),
+        (None, None) => URect::new(0, 0, pixels.width, pixels.height),
+    };
+    let frame = pixels.crop(source);
+    let size = sprite.custom_size.unwrap_or(Vec2::new(frame.width as f32, frame.height as f32));
+    Ok(SpriteStamp {
+        center: Vec2::new(position.x(), position.y()),
+        size: size * transform.scale.truncate().abs(),
+        frame,
+        flip_x: sprite.flip_x,
+        flip_y: sprite.flip_y,
+        tint: sprite.color.to_srgba().to_f32_array(),
+    })
+}
+
+/// `renderpng <x1> <y1> <x2> <y2> [scale] [entities] [file]`: renders the tiles between two corner
+/// tiles (entity sprites too with `entities`) to `saves/<world>/renders/` or `file`
+#[allow(clippy::too_many_arguments)]
+pub fn handle_render_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    world: Res<WorldManager>,
+    sprites: Res<SpriteAssets>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    season: Option<Res<CurrentSeason>>,
+    entities: Query<(&Position, &Transform, &Sprite, &InheritedVisibility)>,
+) {
+    for command in console_commands.read() {
+        if command.name != "renderpng" {
+            continue;
+        }
+        let corners: Vec<i32> = command.args.iter().take(4).map_while(|arg| arg.parse().ok()).collect();
+        let [x1, y1, x2, y2] = corners[..] else {
+            console.print(format!(
+                "Usage: renderpng <x1> <y1> <x2> <y2> [scale 1-{}] [entities] [file]",
+                MAX_RENDER_SCALE
+            ));
+            continue;
+        };
+        let rect = TileRect::from_corners(IVec2::new(x1, y1), IVec2::new(x2, y2));
+        let mut scale = DEFAULT_RENDER_SCALE;
+        let mut with_entities = false;
+        let mut file = None;
+        for arg in &command.args[4..] {
+            match arg.parse::<u32>() {
+                Ok(value) => scale = value.clamp(1, MAX_RENDER_SCALE),
+                Err(_) if arg == "entities" => with_entities = true,
+                Err(_) => file = Some(PathBuf::from(arg)),
+            }
+        }
+        let path = file.unwrap_or_else(|| {
+            world.save_directory.join("renders").join(format!(
+                "region_{}_{}_{}_{}.png",
+                rect.min.x, rect.min.y, rect.max.x, rect.max.y
+            ))
+        });
+
+        let tileset = images
+            .get(&sprites.terrain)
+            .ok_or_else(|| RenderError::Texture("terrain isn't loaded".to_string()))
+            .and_then(Pixels::from_image);
+        let tileset = match tileset {
+            Ok(tileset) => tileset,
+            Err(e) => {
+                console.print(format!("Render failed: {}", e));
+                continue;
+            }
+        };
+
+        let mut stamps = Vec::new();
+        if with_entities {
+            let area = rect.world_rect();
+            let mut visible: Vec<_> = entities
+                .iter()
+                .filter(|(position, _, _, visibility)| {
+                    // Sprites reach a few tiles past their position; the render clips the rest
+                    visibility.get() && area.inflate(4.0 * TILE_SIZE).contains(Vec2::new(position.x(), position.y()))
+                })
+                .collect();
+            visible.sort_by(|a, b| a.1.translation.z.total_cmp(&b.1.translation.z));
+            for (position, transform, sprite, _) in visible {
+                match sprite_stamp(position, transform, sprite, &images, &layouts) {
+                    Ok(stamp) => stamps.push(stamp),
+                    Err(e) => debug!("Skipping a sprite in the render: {}", e),
+                }
+            }
+        }
+
+        let ground_tint = season.as_ref().map_or([1.0; 4], |season| season.0.ground_tint().to_srgba().to_f32_array());
+        let result = render_region(rect, scale, &tileset, ground_tint, &stamps, |pos| {
+            // The in-memory copy may have unsaved edits
+            match world.get_cached_chunk(&pos) {
+                Some(chunk) => chunk.clone(),
+                None => match world.load_stored_chunk(&pos) {
+                    Ok(Some(chunk)) => chunk,
+                    _ => generate_chunk(pos, &world.generator),
+                },
+            }
+        })
+        .and_then(|pixels| {
+            let size = (pixels.width, pixels.height);
+            save_png(pixels, &path).map(|()| size)
+        });
+
+        match result {
+            Ok((width, height)) => console.print(format!(
+                "Rendered tiles ({}, {})-({}, {}) with {} sprites to {:?} ({}x{} px)",
+                rect.min.x,
+                rect.min.y,
+                rect.max.x,
+                rect.max.y,
+                stamps.len(),
+                path,
+                width,
+                height
+            )),
+            Err(e) => console.print(format!("Render failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{LAYER_DECORATION, TILE_DIRT, TILE_GRASS};
+
+    #[test]
+    fn test_render_region() {
+        // Two solid tiles: grass green, dirt brown; the decoration tile is half transparent white
+        let mut tileset = Pixels::new(TILE_TEXELS, TILE_TEXELS * 3);
+        for y in 0..tileset.height {
+            let color = match y / TILE_TEXELS {
+                0 => [0, 200, 0, 255],
+                1 => [120, 80, 40, 255],
+                _ => [255, 255, 255, 128],
+            };
+            for x in 0..tileset.width {
+                let i = tileset.index(x, y);
+                tileset.data[i..i + 4].copy_from_slice(&color);
+            }
+        }
+
+        // Tiles (-1, 0) and (0, 0) straddle a chunk border; (0, 0) is dirt with the decoration
+        let rect = TileRect::from_corners(IVec2::new(0, 0), IVec2::new(-1, 0));
+        assert_eq!(rect.size(), (2, 1));
+        let mut requested = Vec::new();
+        let pixels = render_region(rect, 2, &tileset, [1.0; 4], &[], |pos| {
+            requested.push(pos);
+            let mut chunk = ChunkData::filled_layers(pos, [TILE_GRASS, TILE_EMPTY, TILE_EMPTY]);
+            if pos == ChunkPos::new(0, 0) {
+                chunk.set_tile(LAYER_GROUND, 0, 0, TILE_DIRT);
+                chunk.set_tile(LAYER_DECORATION, 0, 0, 3);
+            }
+            chunk
+        })
+        .unwrap();
+        assert_eq!(requested, vec![ChunkPos::new(-1, 0), ChunkPos::new(0, 0)]);
+        assert_eq!((pixels.width, pixels.height), (32, 16));
+        assert_eq!(pixels.get(0, 0), [0, 200, 0, 255]);
+        assert_eq!(pixels.get(31, 15), [188, 168, 148, 255]);
+
+        // A sprite over the seam, drawn from a 2x1 frame flipped horizontally
+        let mut frame = Pixels::new(2, 1);
+        frame.data.copy_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255]);
+        let sprite = SpriteStamp {
+            center: Vec2::new(0.0, 4.0),
+            size: Vec2::new(4.0, 2.0),
+            frame,
+            flip_x: true,
+            flip_y: false,
+            tint: [1.0; 4],
+        };
+        let pixels = render_region(rect, 2, &tileset, [1.0; 4], &[sprite], |pos| ChunkData::filled(pos, TILE_GRASS)).unwrap();
+        assert_eq!(pixels.get(12, 6), [0, 0, 255, 255]);
+        assert_eq!(pixels.get(19, 9), [255, 0, 0, 255]);
+        assert_eq!(pixels.get(11, 6), [0, 200, 0, 255]);
+        assert_eq!(pixels.get(20, 10), [0, 200, 0, 255]);
+
+        let huge = TileRect::from_corners(IVec2::new(i32::MIN, 0), IVec2::new(i32::MAX, 0));
+        assert!(matches!(
+            render_region(huge, 1, &tileset, [1.0; 4], &[], ChunkData::empty),
+            Err(RenderError::TooLarge { .. })
+        ));
+    }
+}
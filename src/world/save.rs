@@ -0,0 +1,253 @@
+use super::manager::WorldManager;
+use super::metadata::{save_metadata, SaveSummary, WorldMetadata};
+use crate::entities::{stash_all_entities, ChunkEntity, ChunkEntityFilter};
+use crate::error::Failures;
+use crate::locale::Locale;
+use crate::map::{save_map_cache, MapCache};
+use crate::platform::unix_time;
+use crate::toast::Toast;
+use crate::tiles::ChunkPos;
+use bevy::prelude::*;
+
+/// Dirty chunks written per frame while a save is running
+const SAVE_CHUNKS_PER_FRAME: usize = 8;
+
+/// How long the "World saved" message stays on screen (seconds)
+const SAVE_INDICATOR_SECONDS: f32 = 3.0;
+
+/// Request a full "Save World" pass (Ctrl+S or the Save button)
+#[derive(Message, Debug, Clone, Copy, Default)]
+pub struct SaveWorldRequest;
+
+/// Steps of a full save, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveStep {
+    Chunks,
+    MapCache,
+    Metadata,
+}
+
+/// Progress of the current (or last) "Save World" pass
+/// Spread over several frames so the progress indicator can update
+#[derive(Resource, Default)]
+pub struct SaveWorldJob {
+    /// Current step, None when no save is running
+    pub step: Option<SaveStep>,
+    pending_chunks: Vec<ChunkPos>,
+    total_chunks: usize,
+    /// What the running (or last) save wrote
+    pub summary: SaveSummary,
+    /// Number of items that failed to save
+    pub errors: usize,
+    /// Seconds since the last save finished (None while running or before the first save)
+    pub finished_for: Option<f32>,
+}
+
+impl SaveWorldJob {
+    pub fn is_running(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// True only during the frame the save completed (before the indicator starts counting)
+    pub fn just_finished(&self) -> bool {
+        self.finished_for == Some(0.0)
+    }
+
+    /// Fraction of work done (0.0 - 1.0), counting each chunk and each later step as one unit
+    pub fn progress(&self) -> f32 {
+        let steps_done = match self.step {
+            None | Some(SaveStep::Chunks) | Some(SaveStep::MapCache) => 0,
+            Some(SaveStep::Metadata) => 1,
+        };
+        let chunks_done = self.total_chunks - self.pending_chunks.len();
+        let total = self.total_chunks + 2;
+        (chunks_done + steps_done) as f32 / total as f32
+    }
+}
+
+/// Marker component for the save progress text
+#[derive(Component)]
+pub struct SaveIndicator;
+
+/// Sends a save request when Ctrl+S is pressed
+pub fn request_save_shortcut(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut requests: MessageWriter<SaveWorldRequest>,
+) {
+    let ctrl = keyboard.pressed(KeyCode::ControlLeft) || keyboard.pressed(KeyCode::ControlRight);
+    if ctrl && keyboard.just_pressed(KeyCode::KeyS) {
+        requests.write(SaveWorldRequest);
+    }
+}
+
+/// Starts a save pass when requested (ignored while one is already running)
+/// Creatures and trees are put into their chunks first (`stash_all_entities`), so they are saved too
+pub fn start_save_world(
+    mut commands: Commands,
+    mut requests: MessageReader<SaveWorldRequest>,
+    mut job: ResMut<SaveWorldJob>,
+    mut world: ResMut<WorldManager>,
+    chunk_entities: Query<ChunkEntity, ChunkEntityFilter>,
+    mut failures: Failures,
+) {
+    if requests.read().count() == 0 || job.is_running() {
+        return;
+    }
+
+    stash_all_entities(&mut commands, &mut world, &chunk_entities, &mut failures);
+    let pending_chunks = world.get_dirty_chunks();
+    info!("Saving world ({} dirty chunks)", pending_chunks.len());
+    *job = SaveWorldJob {
+        step: Some(SaveStep::Chunks),
+        total_chunks: pending_chunks.len(),
+        pending_chunks,
+        summary: SaveSummary { entities_saved: chunk_entities.iter().count(), ..default() },
+        ..default()
+    };
+}
+
+/// Advances the running save by one step (or one batch of chunks) per frame
+pub fn run_save_world(
+    mut job: ResMut<SaveWorldJob>,
+    mut world: ResMut<WorldManager>,
+    map_cache: Res<MapCache>,
+    mut metadata: ResMut<WorldMetadata>,
+    mut toasts: MessageWriter<Toast>,
+    mut failures: Failures,
+    locale: Res<Locale>,
+) {
+    let Some(step) = job.step else {
+        return;
+    };
+
+    match step {
+        SaveStep::Chunks => {
+            let batch_start = job.pending_chunks.len().saturating_sub(SAVE_CHUNKS_PER_FRAME);
+            let batch: Vec<ChunkPos> = job.pending_chunks.drain(batch_start..).collect();
+            for chunk_pos in batch {
+                // The chunk may have been unloaded (and saved) since the save started
+                if !world.is_dirty(&chunk_pos) {
+                    continue;
+                }
+                match world.save_cached_chunk(&chunk_pos) {
+                    Ok(true) => {
+                        world.clear_dirty(&chunk_pos);
+                        job.summary.chunks_saved += 1;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        failures.report(format!("Failed to save chunk ({}, {})", chunk_pos.x, chunk_pos.y), e);
+                        job.errors += 1;
+                    }
+                }
+            }
+
+            if job.pending_chunks.is_empty() {
+                // Chunks unloaded meanwhile are being written in the background; wait for them, and
+                // save the ones whose write failed (cached and dirty again) like the others
+                let retries: Vec<ChunkPos> = world
+                    .finish_saves()
+                    .into_iter()
+                    .filter(|(_, result)| result.is_err())
+                    .map(|(chunk_pos, _)| chunk_pos)
+                    .collect();
+                job.total_chunks += retries.len();
+                job.pending_chunks.extend(retries);
+            }
+            if job.pending_chunks.is_empty() {
+                job.step = Some(SaveStep::MapCache);
+            }
+        }
+        SaveStep::MapCache => {
+            match save_map_cache(&map_cache, world.get_map_cache_path()) {
+                Ok(_) => job.summary.explored_chunks = map_cache.chunks.len(),
+                Err(e) => {
+                    failures.report("Failed to save the map", e);
+                    job.errors += 1;
+                }
+            }
+            job.step = Some(SaveStep::Metadata);
+        }
+        SaveStep::Metadata => {
+            job.summary.timestamp = unix_time().as_secs();
+
+            // Write directly (bypassing change detection so save_world_metadata doesn't save twice)
+            let metadata = metadata.bypass_change_detection();
+            metadata.last_save = Some(job.summary.clone());
+            if let Err(e) = save_metadata(metadata, world.get_metadata_path()) {
+                failures.report("Failed to save world metadata", e);
+                job.errors += 1;
+            }
+
+            info!(
+                "World saved: {} chunks, {} entities, {} explored chunks ({} errors)",
+                job.summary.chunks_saved, job.summary.entities_saved, job.summary.explored_chunks, job.errors
+            );
+            if job.errors > 0 {
+                toasts.write(Toast::error(locale.format("toast-save-errors", &[("count", job.errors.into())])));
+            }
+            job.step = None;
+            job.finished_for = Some(0.0);
+        }
+    }
+}
+
+/// Spawns the save progress text (hidden until a save runs)
+pub fn setup_save_indicator(mut commands: Commands) {
+    commands.spawn((
+        SaveIndicator,
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+}
+
+/// Shows save progress while running and a short confirmation afterwards
+pub fn update_save_indicator(
+    time: Res<Time>,
+    mut job: ResMut<SaveWorldJob>,
+    indicator: Single<(&mut Text, &mut Visibility), With<SaveIndicator>>,
+    locale: Res<Locale>,
+) {
+    let (mut text, mut visibility) = indicator.into_inner();
+
+    if job.is_running() {
+        let percent = (job.progress() * 100.0).round() as u32;
+        text.0 = locale.format("save-progress", &[("percent", percent.into())]);
+        *visibility = Visibility::Visible;
+        return;
+    }
+
+    let Some(finished_for) = job.finished_for else {
+        return;
+    };
+
+    if job.just_finished() {
+        text.0 = if job.errors == 0 {
+            locale.format("save-finished", &[
+                ("chunks", job.summary.chunks_saved.into()),
+                ("entities", job.summary.entities_saved.into()),
+            ])
+        } else {
+            locale.format("save-finished-errors", &[("count", job.errors.into())])
+        };
+    }
+
+    let finished_for = finished_for + time.delta_secs();
+    if finished_for >= SAVE_INDICATOR_SECONDS {
+        *visibility = Visibility::Hidden;
+        job.finished_for = None;
+    } else {
+        job.bypass_change_detection().finished_for = Some(finished_for);
+    }
+}
@@ -0,0 +1,223 @@
+//! Shareable seed codes: a world's seed, generator settings and the settings a new world starts
+//! with, packed into a short string (`WS-` plus base64) so the same world can be created on another
+//! machine with `--seed-code <code>`
+
+use super::generator::{GeneratorConfig, GroundPattern};
+use super::metadata::{WorldMetadata, DEFAULT_SEED};
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::environment::{WorldClock, MAX_DAY_LENGTH, MIN_DAY_LENGTH};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use bevy::prelude::*;
+use thiserror::Error;
+
+/// Every code starts with this
+pub const CODE_PREFIX: &str = "WS-";
+
+/// Layout of the packed bytes; bump when fields change
+/// Version 1 codes had no generator settings (they were all made with the default ones)
+const CODE_VERSION: u8 = 2;
+
+/// Terrain generator the code was made for (`generator.rs`); codes for another generator are refused,
+/// since they would create a different world
+pub const GENERATOR_VERSION: u8 = 1;
+
+/// version, generator version, seed (8), day length (4), days per season (4), [ground pattern], checksum
+fn code_bytes(version: u8) -> usize {
+    if version == 1 {
+        19
+    } else {
+        20
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SeedCodeError {
+    #[error("Seed codes start with {CODE_PREFIX}")]
+    Prefix,
+    #[error("Not a seed code (bad characters or length)")]
+    Encoding,
+    #[error("Checksum mismatch (mistyped code?)")]
+    Checksum,
+    #[error("Code is from an unsupported version of the game (format {format}, generator {generator})")]
+    Version { format: u8, generator: u8 },
+    #[error("Invalid setting in code: {0}")]
+    Setting(String),
+}
+
+/// What a new world is created from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldSeed {
+    pub seed: u64,
+    /// Real seconds per in-game day
+    pub day_length: f32,
+    pub days_per_season: u32,
+    pub generator: GeneratorConfig,
+}
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        Self::from_metadata(&WorldMetadata::default())
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0x5Au8, |sum, byte| sum.rotate_left(3) ^ byte)
+}
+
+impl WorldSeed {
+    /// The seed a world was created with, and its current day settings
+    pub fn from_metadata(metadata: &WorldMetadata) -> Self {
+        Self {
+            seed: metadata.seed.unwrap_or(DEFAULT_SEED),
+            day_length: metadata.clock.day_length,
+            days_per_season: metadata.clock.days_per_season,
+            generator: metadata.generator,
+        }
+    }
+
+    /// Set up a new world's starting metadata
+    pub fn apply(&self, metadata: &mut WorldMetadata) {
+        metadata.seed = Some(self.seed);
+        metadata.weather.rng_state = self.seed;
+        metadata.clock.day_length = self.day_length;
+        metadata.clock.days_per_season = self.days_per_season;
+        metadata.generator = self.generator;
+    }
+
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(code_bytes(CODE_VERSION));
+        bytes.extend_from_slice(&[CODE_VERSION, GENERATOR_VERSION]);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.day_length.to_le_bytes());
+        bytes.extend_from_slice(&self.days_per_season.to_le_bytes());
+        let ground = GroundPattern::ALL.iter().position(|ground| *ground == self.generator.ground).unwrap_or(0);
+        bytes.push(ground as u8);
+        bytes.push(checksum(&bytes));
+        format!("{}{}", CODE_PREFIX, BASE64.encode(bytes))
+    }
+
+    pub fn decode(code: &str) -> Result<Self, SeedCodeError> {
+        let code = code.trim();
+        let encoded = code
+            .get(..CODE_PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(CODE_PREFIX))
+            .map(|_| &code[CODE_PREFIX.len()..])
+            .ok_or(SeedCodeError::Prefix)?;
+        let bytes = BASE64.decode(encoded).map_err(|_| SeedCodeError::Encoding)?;
+        let Some((&sum, bytes)) = bytes.split_last() else {
+            return Err(SeedCodeError::Encoding);
+        };
+        if bytes.first().is_none_or(|&version| bytes.len() + 1 != code_bytes(version)) {
+            return Err(SeedCodeError::Encoding);
+        }
+        if checksum(bytes) != sum {
+            return Err(SeedCodeError::Checksum);
+        }
+        if !(1..=CODE_VERSION).contains(&bytes[0]) || bytes[1] != GENERATOR_VERSION {
+            return Err(SeedCodeError::Version {
+                format: bytes[0],
+                generator: bytes[1],
+            });
+        }
+
+        let seed = u64::from_le_bytes(bytes[2..10].try_into().unwrap_or_default());
+        let day_length = f32::from_le_bytes(bytes[10..14].try_into().unwrap_or_default());
+        let days_per_season = u32::from_le_bytes(bytes[14..18].try_into().unwrap_or_default());
+        if !(MIN_DAY_LENGTH..=MAX_DAY_LENGTH).contains(&day_length) {
+            return Err(SeedCodeError::Setting(format!("day length {}", day_length)));
+        }
+        if days_per_season == 0 {
+            return Err(SeedCodeError::Setting("0 days per season".to_string()));
+        }
+        let ground = match bytes.get(18) {
+            Some(&index) => *GroundPattern::ALL
+                .get(index as usize)
+                .ok_or_else(|| SeedCodeError::Setting(format!("ground pattern {}", index)))?,
+            None => GroundPattern::Checkerboard,
+        };
+        Ok(Self {
+            seed,
+            day_length,
+            days_per_season,
+            generator: GeneratorConfig { ground, ..default() },
+        })
+    }
+}
+
+/// Console command: `seedcode` prints the open world's code
+pub fn handle_seed_code_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    metadata: Res<WorldMetadata>,
+    clock: Option<Res<WorldClock>>,
+) {
+    for command in console_commands.read() {
+        if command.name != "seedcode" {
+            continue;
+        }
+        let mut world_seed = WorldSeed::from_metadata(&metadata);
+        // The live clock settings (changed with `daylength`) are copied into the metadata periodically
+        if let Some(clock) = &clock {
+            world_seed.day_length = clock.0.day_length;
+            world_seed.days_per_season = clock.0.days_per_season;
+        }
+        console.print(world_seed.encode());
+        if metadata.seed.is_none() {
+            console.print("(this world predates recorded seeds; the code uses the default seed)");
+        }
+        console.print("Create the same world elsewhere with: worldseed --world <name> --seed-code <code>");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_code_round_trip() {
+        let world_seed = WorldSeed {
+            seed: 0xDEAD_BEEF_1234_5678,
+            day_length: 300.0,
+            days_per_season: 3,
+            generator: GeneratorConfig {
+                ground: GroundPattern::Sand,
+                ..default()
+            },
+        };
+        let code = world_seed.encode();
+        assert!(code.starts_with(CODE_PREFIX));
+        assert_eq!(code.len(), CODE_PREFIX.len() + 27);
+        assert_eq!(WorldSeed::decode(&code), Ok(world_seed));
+        // Pasted codes may carry whitespace and a lowercase prefix
+        assert_eq!(WorldSeed::decode(&format!(" {}\n", code.replacen("WS-", "ws-", 1))), Ok(world_seed));
+
+        let mut metadata = WorldMetadata::default();
+        world_seed.apply(&mut metadata);
+        assert_eq!(metadata.weather.rng_state, world_seed.seed);
+        assert_eq!(WorldSeed::from_metadata(&metadata), world_seed);
+        assert_eq!(WorldSeed::default().seed, DEFAULT_SEED);
+
+        // Version 1 codes (no generator settings) still work
+        let mut v1 = vec![1, GENERATOR_VERSION];
+        v1.extend_from_slice(&9u64.to_le_bytes());
+        v1.extend_from_slice(&600.0f32.to_le_bytes());
+        v1.extend_from_slice(&7u32.to_le_bytes());
+        v1.push(checksum(&v1));
+        let old = WorldSeed::decode(&format!("{}{}", CODE_PREFIX, BASE64.encode(v1))).unwrap();
+        assert_eq!((old.seed, old.generator), (9, GeneratorConfig::legacy()));
+
+        // A changed character breaks the checksum (or the encoding)
+        let mut typo = code.clone().into_bytes();
+        let last = typo.len() - 5;
+        typo[last] = if typo[last] == b'A' { b'B' } else { b'A' };
+        assert!(WorldSeed::decode(&String::from_utf8(typo).unwrap()).is_err());
+        assert_eq!(WorldSeed::decode("42"), Err(SeedCodeError::Prefix));
+        assert_eq!(WorldSeed::decode("WS-abc"), Err(SeedCodeError::Encoding));
+        let no_days = WorldSeed {
+            days_per_season: 0,
+            ..world_seed
+        };
+        assert!(matches!(WorldSeed::decode(&no_days.encode()), Err(SeedCodeError::Setting(_))));
+    }
+}
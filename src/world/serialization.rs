@@ -1,87 +1,405 @@
-use crate::tiles::{ChunkData, ChunkPos, CHUNK_AREA, NUM_LAYERS};
+use crate::tiles::{ChunkData, ChunkPos, ChunkSettler, TileId, CHUNK_AREA, DEFAULT_ELEVATION, NUM_LAYERS};
+use super::entity_serialization::{decode_entities, encode_entities, SavedEntity};
+use super::storage::{ChunkStore, FsStore};
+use bevy::log::info_span;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
 use std::path::Path;
+use thiserror::Error;
 
 /// Magic number for chunk files ("TILE" in ASCII)
 const MAGIC_NUMBER: [u8; 4] = [b'T', b'I', b'L', b'E'];
 
-/// Current chunk file format version (v2 supports multiple layers)
-const VERSION: u16 = 2;
+/// Current chunk file format version (v3 adds palette + RLE encoded bodies, v4 an elevation block,
+/// v5 a settler block, v6 the generator version and flags in the header, v7 zlib compressed bodies,
+/// v8 an entity block)
+pub const VERSION: u16 = 8;
+
+/// v3 body encodings
+/// Raw: every tile of every layer as u16, like v2
+const ENCODING_RAW: u8 = 0;
+/// Palette + RLE: the distinct tile IDs, then (palette index u8, run length u16) runs
+/// over all layers in order. Only used with at most 256 distinct tiles and when smaller than raw
+const ENCODING_PALETTE_RLE: u8 = 1;
+/// v7: the raw body compressed with zlib, for noisy terrain that makes short runs
+const ENCODING_ZLIB: u8 = 2;
+
+/// Size of a raw (v2 or v3 raw) body in bytes
+const RAW_BODY_SIZE: usize = CHUNK_AREA * NUM_LAYERS * 2;
+
+/// v4 elevation block encodings: one byte per tile, or (elevation u8, run length u16) runs
+const ELEVATION_RAW: u8 = 0;
+const ELEVATION_RLE: u8 = 1;
+
+/// v5 settler block encoding: (x u8, y u8, roam radius u8) per settler
+const SETTLERS_V1: u8 = 0;
+
+/// v8 entity block encoding: empty for no entities, else an entity file (`entity_serialization`)
+const ENTITIES_V1: u8 = 0;
+
+/// Largest entity block a chunk file may hold
+const MAX_ENTITY_BYTES: usize = 1 << 20;
+
+/// v6 header flag: a player painted the chunk (`ChunkData.edited`)
+const FLAG_EDITED: u8 = 1;
+
+/// Most settlers a chunk file may hold
+const MAX_SETTLERS: usize = 256;
 
 /// Error type for serialization operations
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum SerializationError {
-    Io(io::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid magic number")]
     InvalidMagicNumber,
+    #[error("Invalid version: {0}")]
     InvalidVersion(u16),
+    #[error("Invalid chunk size: {0}")]
     InvalidChunkSize(usize),
+    #[error("Checksum mismatch")]
     InvalidChecksum,
+    /// Unknown v3 body encoding
+    #[error("Invalid chunk encoding: {0}")]
+    InvalidEncoding(u8),
+    /// A known component record had a malformed payload
+    #[error("Invalid component payload (tag {0})")]
+    InvalidComponent(u16),
 }
 
-impl From<io::Error> for SerializationError {
-    fn from(err: io::Error) -> Self {
-        SerializationError::Io(err)
+impl SerializationError {
+    /// Whether the file itself is damaged (as opposed to e.g. missing or unreadable)
+    /// A version this build doesn't know is a newer build's file, not a damaged one
+    pub fn is_corruption(&self) -> bool {
+        match self {
+            SerializationError::Io(e) => e.kind() == io::ErrorKind::UnexpectedEof,
+            SerializationError::InvalidVersion(_) => false,
+            _ => true,
+        }
     }
 }
 
-impl std::fmt::Display for SerializationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SerializationError::Io(e) => write!(f, "IO error: {}", e),
-            SerializationError::InvalidMagicNumber => write!(f, "Invalid magic number"),
-            SerializationError::InvalidVersion(v) => write!(f, "Invalid version: {}", v),
-            SerializationError::InvalidChunkSize(s) => write!(f, "Invalid chunk size: {}", s),
-            SerializationError::InvalidChecksum => write!(f, "Checksum mismatch"),
+/// All layers as little-endian u16s (layer by layer, row-major)
+fn encode_raw(chunk: &ChunkData) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(RAW_BODY_SIZE);
+    for layer in chunk.layers.iter() {
+        for &tile in layer.iter() {
+            bytes.extend_from_slice(&tile.to_le_bytes());
         }
     }
+    bytes
 }
 
-impl std::error::Error for SerializationError {}
+/// Palette + RLE encoding of all layers, or None if there are more than 256 distinct tiles
+fn encode_palette_rle(chunk: &ChunkData) -> Option<Vec<u8>> {
+    let mut palette: Vec<TileId> = Vec::new();
+    let mut runs: Vec<(u8, u16)> = Vec::new();
+
+    for &tile in chunk.layers.iter().flatten() {
+        let index = match palette.iter().position(|&entry| entry == tile) {
+            Some(index) => index,
+            None => {
+                if palette.len() == 256 {
+                    return None;
+                }
+                palette.push(tile);
+                palette.len() - 1
+            }
+        } as u8;
 
-/// Save a chunk to disk in binary format (v2 - supports multiple layers)
-pub fn save_chunk<P: AsRef<Path>>(
-    chunk: &ChunkData,
-    path: P,
-) -> Result<(), SerializationError> {
-    // Ensure directory exists
-    if let Some(parent) = path.as_ref().parent() {
-        fs::create_dir_all(parent)?;
+        match runs.last_mut() {
+            Some((last, length)) if *last == index && *length < u16::MAX => *length += 1,
+            _ => runs.push((index, 1)),
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(2 + palette.len() * 2 + runs.len() * 3);
+    bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+    for tile in &palette {
+        bytes.extend_from_slice(&tile.to_le_bytes());
+    }
+    for (index, length) in runs {
+        bytes.push(index);
+        bytes.extend_from_slice(&length.to_le_bytes());
+    }
+    Some(bytes)
+}
+
+/// The elevation block body: run-length encoded when that is smaller (smooth or flat ground)
+fn encode_elevation(elevation: &[u8; CHUNK_AREA]) -> (u8, Vec<u8>) {
+    let mut runs: Vec<(u8, u16)> = Vec::new();
+    for &height in elevation.iter() {
+        match runs.last_mut() {
+            Some((last, length)) if *last == height => *length += 1,
+            _ => runs.push((height, 1)),
+        }
+    }
+    if runs.len() * 3 >= CHUNK_AREA {
+        return (ELEVATION_RAW, elevation.to_vec());
+    }
+    let mut bytes = Vec::with_capacity(runs.len() * 3);
+    for (height, length) in runs {
+        bytes.push(height);
+        bytes.extend_from_slice(&length.to_le_bytes());
+    }
+    (ELEVATION_RLE, bytes)
+}
+
+fn decode_elevation(encoding: u8, bytes: &[u8]) -> Result<Box<[u8; CHUNK_AREA]>, SerializationError> {
+    let invalid = || SerializationError::InvalidChunkSize(bytes.len());
+    let mut elevation = Box::new([DEFAULT_ELEVATION; CHUNK_AREA]);
+    match encoding {
+        ELEVATION_RAW if bytes.len() == CHUNK_AREA => elevation.copy_from_slice(bytes),
+        ELEVATION_RAW => return Err(invalid()),
+        ELEVATION_RLE => {
+            if !bytes.len().is_multiple_of(3) {
+                return Err(invalid());
+            }
+            let mut filled = 0;
+            for run in bytes.chunks_exact(3) {
+                let length = u16::from_le_bytes([run[1], run[2]]) as usize;
+                let slots = elevation.get_mut(filled..filled + length).ok_or_else(invalid)?;
+                slots.fill(run[0]);
+                filled += length;
+            }
+            if filled != CHUNK_AREA {
+                return Err(invalid());
+            }
+        }
+        other => return Err(SerializationError::InvalidEncoding(other)),
     }
+    Ok(elevation)
+}
 
-    let mut file = File::create(path)?;
+/// Pick the smallest encoding for a chunk (palette + RLE on a tie with zlib, it's cheaper to decode)
+fn encode_body(chunk: &ChunkData) -> (u8, Vec<u8>) {
+    let raw = encode_raw(chunk);
+    let palette_rle = encode_palette_rle(chunk).map(|bytes| (ENCODING_PALETTE_RLE, bytes));
+    let zlib = (ENCODING_ZLIB, deflate(&raw));
+    palette_rle
+        .into_iter()
+        .chain([zlib])
+        .filter(|(_, bytes)| bytes.len() < RAW_BODY_SIZE)
+        .min_by_key(|(_, bytes)| bytes.len())
+        .unwrap_or((ENCODING_RAW, raw))
+}
 
-    // Write header
-    file.write_all(&MAGIC_NUMBER)?;
-    file.write_all(&VERSION.to_le_bytes())?;
+/// zlib compress a body
+fn deflate(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).expect("writing to a Vec can't fail");
+    encoder.finish().expect("writing to a Vec can't fail")
+}
 
-    // Write chunk position
-    file.write_all(&chunk.position.x.to_le_bytes())?;
-    file.write_all(&chunk.position.y.to_le_bytes())?;
+/// Decompress a zlib body that must come to exactly `length` bytes (reading no more than that,
+/// so a damaged stream can't balloon)
+fn inflate(bytes: &[u8], length: usize) -> Result<Vec<u8>, SerializationError> {
+    let mut inflated = Vec::with_capacity(length);
+    ZlibDecoder::new(bytes)
+        .take(length as u64 + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|_| SerializationError::InvalidChunkSize(bytes.len()))?;
+    if inflated.len() != length {
+        return Err(SerializationError::InvalidChunkSize(inflated.len()));
+    }
+    Ok(inflated)
+}
 
-    // Write number of layers
-    file.write_all(&(NUM_LAYERS as u16).to_le_bytes())?;
+fn decode_raw(bytes: &[u8]) -> Result<Box<[[TileId; CHUNK_AREA]; NUM_LAYERS]>, SerializationError> {
+    if bytes.len() != RAW_BODY_SIZE {
+        return Err(SerializationError::InvalidChunkSize(bytes.len()));
+    }
+
+    let mut layers = Box::new([[0u16; CHUNK_AREA]; NUM_LAYERS]);
+    for (tile, pair) in layers.iter_mut().flatten().zip(bytes.chunks_exact(2)) {
+        *tile = u16::from_le_bytes([pair[0], pair[1]]);
+    }
+    Ok(layers)
+}
 
-    // Write all layers
-    let mut all_tile_bytes = Vec::with_capacity(CHUNK_AREA * NUM_LAYERS * 2);
-    for layer_idx in 0..NUM_LAYERS {
-        for &tile in chunk.layers[layer_idx].iter() {
-            all_tile_bytes.extend_from_slice(&tile.to_le_bytes());
+fn decode_palette_rle(bytes: &[u8]) -> Result<Box<[[TileId; CHUNK_AREA]; NUM_LAYERS]>, SerializationError> {
+    let truncated = || SerializationError::InvalidChunkSize(bytes.len());
+
+    let palette_len_bytes = bytes.get(0..2).ok_or_else(truncated)?;
+    let palette_len = u16::from_le_bytes([palette_len_bytes[0], palette_len_bytes[1]]) as usize;
+    let palette_end = 2 + palette_len * 2;
+    let palette: Vec<TileId> = bytes
+        .get(2..palette_end)
+        .ok_or_else(truncated)?
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+
+    let runs = &bytes[palette_end..];
+    if !runs.len().is_multiple_of(3) {
+        return Err(truncated());
+    }
+
+    let mut tiles = Vec::with_capacity(CHUNK_AREA * NUM_LAYERS);
+    for run in runs.chunks_exact(3) {
+        let tile = *palette.get(run[0] as usize).ok_or_else(truncated)?;
+        let length = u16::from_le_bytes([run[1], run[2]]) as usize;
+        if tiles.len() + length > CHUNK_AREA * NUM_LAYERS {
+            return Err(truncated());
         }
+        tiles.extend(std::iter::repeat_n(tile, length));
+    }
+    if tiles.len() != CHUNK_AREA * NUM_LAYERS {
+        return Err(SerializationError::InvalidChunkSize(tiles.len()));
+    }
+
+    let mut layers = Box::new([[0u16; CHUNK_AREA]; NUM_LAYERS]);
+    for (slot, tile) in layers.iter_mut().flatten().zip(tiles) {
+        *slot = tile;
+    }
+    Ok(layers)
+}
+
+/// The settler block body
+fn encode_settlers(settlers: &[ChunkSettler]) -> Vec<u8> {
+    settlers.iter().take(MAX_SETTLERS).flat_map(|settler| [settler.x, settler.y, settler.roam_radius]).collect()
+}
+
+fn decode_settlers(encoding: u8, bytes: &[u8]) -> Result<Vec<ChunkSettler>, SerializationError> {
+    if encoding != SETTLERS_V1 {
+        return Err(SerializationError::InvalidEncoding(encoding));
+    }
+    if !bytes.len().is_multiple_of(3) {
+        return Err(SerializationError::InvalidChunkSize(bytes.len()));
     }
-    file.write_all(&all_tile_bytes)?;
+    Ok(bytes.chunks_exact(3).map(|settler| ChunkSettler { x: settler[0], y: settler[1], roam_radius: settler[2] }).collect())
+}
 
-    // Calculate and write checksum (CRC32)
-    let checksum = crc32fast::hash(&all_tile_bytes);
-    file.write_all(&checksum.to_le_bytes())?;
+/// The entity block body
+fn encode_chunk_entities(chunk: &ChunkData) -> Vec<u8> {
+    if chunk.entities.is_empty() {
+        return Vec::new();
+    }
+    encode_entities(&chunk.entities)
+}
+
+fn decode_chunk_entities(encoding: u8, bytes: &[u8]) -> Result<Vec<SavedEntity>, SerializationError> {
+    match encoding {
+        ENTITIES_V1 if bytes.is_empty() => Ok(Vec::new()),
+        ENTITIES_V1 => decode_entities(bytes),
+        other => Err(SerializationError::InvalidEncoding(other)),
+    }
+}
 
-    file.sync_all()?;
+/// Append a block: encoding, body length, body and the body's checksum
+fn write_block(bytes: &mut Vec<u8>, encoding: u8, body: &[u8]) {
+    bytes.push(encoding);
+    bytes.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(body);
+    bytes.extend_from_slice(&crc32fast::hash(body).to_le_bytes());
+}
+
+/// Encode a chunk in the binary chunk file format (v8 - the header ends with the generator version
+/// and flags; then a raw, palette + RLE or zlib body, whichever is smallest, then the elevation,
+/// settler and entity blocks laid out the same way: encoding, length, body, checksum)
+pub fn encode_chunk(chunk: &ChunkData) -> Vec<u8> {
+    let _span = info_span!("encode_chunk").entered();
+    let (encoding, body) = encode_body(chunk);
+    let (elevation_encoding, elevation) = encode_elevation(&chunk.elevation);
+    let settlers = encode_settlers(&chunk.settlers);
+    let entities = encode_chunk_entities(chunk);
+    let mut bytes = Vec::with_capacity(
+        4 + 2 + 8 + 2 + 3 + 4 * (1 + 4 + 4) + body.len() + elevation.len() + settlers.len() + entities.len(),
+    );
+
+    // Header
+    bytes.extend_from_slice(&MAGIC_NUMBER);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+
+    // Chunk position
+    bytes.extend_from_slice(&chunk.position.x.to_le_bytes());
+    bytes.extend_from_slice(&chunk.position.y.to_le_bytes());
+
+    // Number of layers
+    bytes.extend_from_slice(&(NUM_LAYERS as u16).to_le_bytes());
+
+    // Generator version and flags
+    bytes.extend_from_slice(&chunk.generator_version.to_le_bytes());
+    bytes.push(if chunk.edited { FLAG_EDITED } else { 0 });
+
+    // Tile block (with a CRC32 of the body), then elevation, settlers and entities
+    write_block(&mut bytes, encoding, &body);
+    write_block(&mut bytes, elevation_encoding, &elevation);
+    write_block(&mut bytes, SETTLERS_V1, &settlers);
+    write_block(&mut bytes, ENTITIES_V1, &entities);
+    bytes
+}
+
+/// Save a chunk to disk in binary format
+pub fn save_chunk<P: AsRef<Path>>(
+    chunk: &ChunkData,
+    path: P,
+) -> Result<(), SerializationError> {
+    FsStore.write(path.as_ref(), &encode_chunk(chunk))?;
     Ok(())
 }
 
-/// Load a chunk from disk (supports both v1 and v2 formats)
+/// Read a little-endian u32 CRC32 and compare it against `bytes`
+fn verify_checksum(file: &mut impl Read, bytes: &[u8]) -> Result<(), SerializationError> {
+    let mut checksum_bytes = [0u8; 4];
+    file.read_exact(&mut checksum_bytes)?;
+    let expected_checksum = u32::from_le_bytes(checksum_bytes);
+    if crc32fast::hash(bytes) != expected_checksum {
+        return Err(SerializationError::InvalidChecksum);
+    }
+    Ok(())
+}
+
+/// Read the layer count written by v2+ and check it matches `NUM_LAYERS`
+fn read_layer_count(file: &mut impl Read) -> Result<(), SerializationError> {
+    let mut num_layers_bytes = [0u8; 2];
+    file.read_exact(&mut num_layers_bytes)?;
+    let num_layers = u16::from_le_bytes(num_layers_bytes) as usize;
+    if num_layers != NUM_LAYERS {
+        return Err(SerializationError::InvalidChunkSize(num_layers));
+    }
+    Ok(())
+}
+
+/// Read the generator version and flags written by v6
+fn read_generator_header(file: &mut impl Read) -> Result<(u16, bool), SerializationError> {
+    let mut header = [0u8; 3];
+    file.read_exact(&mut header)?;
+    Ok((u16::from_le_bytes([header[0], header[1]]), header[2] & FLAG_EDITED != 0))
+}
+
+/// Read an encoding byte and a length-prefixed body of at most `max_length` bytes, then check its checksum
+fn read_block(file: &mut impl Read, max_length: usize) -> Result<(u8, Vec<u8>), SerializationError> {
+    let mut encoding = [0u8; 1];
+    file.read_exact(&mut encoding)?;
+    let mut length_bytes = [0u8; 4];
+    file.read_exact(&mut length_bytes)?;
+    let length = u32::from_le_bytes(length_bytes) as usize;
+    if length > max_length {
+        return Err(SerializationError::InvalidChunkSize(length));
+    }
+
+    let mut body = vec![0u8; length];
+    file.read_exact(&mut body)?;
+    verify_checksum(file, &body)?;
+    Ok((encoding[0], body))
+}
+
+/// Load a chunk from disk (supports v1 to v8 formats)
 pub fn load_chunk<P: AsRef<Path>>(path: P) -> Result<ChunkData, SerializationError> {
-    let mut file = File::open(path)?;
+    decode_chunk(&fs::read(path)?)
+}
+
+/// Decode a chunk file's bytes (v1 to v8); truncated data is an `UnexpectedEof` IO error, like a short file
+/// Chunks from before v4 have no elevation and come back flat (`DEFAULT_ELEVATION`), before v5 without settlers
+/// and before v8 without entities
+/// Before v6 the generator is unknown (0) and the chunk counts as edited, so it's never regenerated as stale
+pub fn decode_chunk(bytes: &[u8]) -> Result<ChunkData, SerializationError> {
+    let _span = info_span!("decode_chunk", bytes = bytes.len()).entered();
+    let mut file = bytes;
 
     // Read and verify magic number
     let mut magic = [0u8; 4];
@@ -107,15 +425,7 @@ pub fn load_chunk<P: AsRef<Path>>(path: P) -> Result<ChunkData, SerializationErr
             // Load v1 format (single layer) and convert to multi-layer
             let mut tile_bytes = vec![0u8; CHUNK_AREA * 2];
             file.read_exact(&mut tile_bytes)?;
-
-            // Read and verify checksum
-            let mut checksum_bytes = [0u8; 4];
-            file.read_exact(&mut checksum_bytes)?;
-            let expected_checksum = u32::from_le_bytes(checksum_bytes);
-            let actual_checksum = crc32fast::hash(&tile_bytes);
-            if actual_checksum != expected_checksum {
-                return Err(SerializationError::InvalidChecksum);
-            }
+            verify_checksum(&mut file, &tile_bytes)?;
 
             // Convert bytes to multi-layer format (put all tiles on ground layer)
             let mut layers = Box::new([[0u16; CHUNK_AREA]; NUM_LAYERS]);
@@ -124,50 +434,74 @@ pub fn load_chunk<P: AsRef<Path>>(path: P) -> Result<ChunkData, SerializationErr
             }
             // Other layers remain empty (0)
 
-            Ok(ChunkData { position, layers })
+            Ok(ChunkData { position, layers, edited: true, ..ChunkData::empty(position) })
         }
         2 => {
-            // Load v2 format (multiple layers)
-            let mut num_layers_bytes = [0u8; 2];
-            file.read_exact(&mut num_layers_bytes)?;
-            let num_layers = u16::from_le_bytes(num_layers_bytes) as usize;
+            // Load v2 format (multiple layers, raw)
+            read_layer_count(&mut file)?;
 
-            if num_layers != NUM_LAYERS {
-                return Err(SerializationError::InvalidChunkSize(num_layers));
-            }
-
-            // Read all layer data
-            let mut all_tile_bytes = vec![0u8; CHUNK_AREA * NUM_LAYERS * 2];
+            let mut all_tile_bytes = vec![0u8; RAW_BODY_SIZE];
             file.read_exact(&mut all_tile_bytes)?;
+            verify_checksum(&mut file, &all_tile_bytes)?;
 
-            // Read and verify checksum
-            let mut checksum_bytes = [0u8; 4];
-            file.read_exact(&mut checksum_bytes)?;
-            let expected_checksum = u32::from_le_bytes(checksum_bytes);
-            let actual_checksum = crc32fast::hash(&all_tile_bytes);
-            if actual_checksum != expected_checksum {
-                return Err(SerializationError::InvalidChecksum);
-            }
-
-            // Convert bytes to layer arrays
-            let mut layers = Box::new([[0u16; CHUNK_AREA]; NUM_LAYERS]);
-            let mut byte_idx = 0;
-            for layer_idx in 0..NUM_LAYERS {
-                for tile_idx in 0..CHUNK_AREA {
-                    layers[layer_idx][tile_idx] = u16::from_le_bytes([
-                        all_tile_bytes[byte_idx],
-                        all_tile_bytes[byte_idx + 1],
-                    ]);
-                    byte_idx += 2;
+            let layers = decode_raw(&all_tile_bytes)?;
+            Ok(ChunkData { position, layers, edited: true, ..ChunkData::empty(position) })
+        }
+        3..=8 => {
+            // Load v3-v8 format (encoding byte + length-prefixed body; v4 adds an elevation block, v5 settlers,
+            // v6 the generator version and flags, v7 zlib bodies, v8 entities)
+            read_layer_count(&mut file)?;
+            let (generator_version, edited) = match version {
+                3..=5 => (0, true),
+                _ => read_generator_header(&mut file)?,
+            };
+
+            // No encoding is ever larger than a raw body; the checksum covers the stored (compressed) bytes
+            let (encoding, body) = read_block(&mut file, RAW_BODY_SIZE)?;
+            let layers = match encoding {
+                ENCODING_RAW => decode_raw(&body)?,
+                ENCODING_PALETTE_RLE => decode_palette_rle(&body)?,
+                ENCODING_ZLIB if version >= 7 => decode_raw(&inflate(&body, RAW_BODY_SIZE)?)?,
+                other => return Err(SerializationError::InvalidEncoding(other)),
+            };
+            let elevation = match version {
+                3 => Box::new([DEFAULT_ELEVATION; CHUNK_AREA]),
+                _ => {
+                    let (encoding, body) = read_block(&mut file, CHUNK_AREA)?;
+                    decode_elevation(encoding, &body)?
                 }
-            }
-
-            Ok(ChunkData { position, layers })
+            };
+            let settlers = match version {
+                3 | 4 => Vec::new(),
+                _ => {
+                    let (encoding, body) = read_block(&mut file, MAX_SETTLERS * 3)?;
+                    decode_settlers(encoding, &body)?
+                }
+            };
+            let entities = match version {
+                3..=7 => Vec::new(),
+                _ => {
+                    let (encoding, body) = read_block(&mut file, MAX_ENTITY_BYTES)?;
+                    decode_chunk_entities(encoding, &body)?
+                }
+            };
+            Ok(ChunkData { position, layers, elevation, settlers, entities, generator_version, edited })
         }
         _ => Err(SerializationError::InvalidVersion(version)),
     }
 }
 
+/// Read just the format version of a chunk file
+pub fn read_chunk_version<P: AsRef<Path>>(path: P) -> Result<u16, SerializationError> {
+    let mut file = File::open(path)?;
+    let mut header = [0u8; 6];
+    file.read_exact(&mut header)?;
+    if header[0..4] != MAGIC_NUMBER {
+        return Err(SerializationError::InvalidMagicNumber);
+    }
+    Ok(u16::from_le_bytes([header[4], header[5]]))
+}
+
 /// Check if a chunk file exists
 pub fn chunk_exists<P: AsRef<Path>>(path: P) -> bool {
     path.as_ref().exists()
@@ -182,6 +516,7 @@ pub fn delete_chunk<P: AsRef<Path>>(path: P) -> Result<(), io::Error> {
 mod tests {
     use super::*;
     use crate::tiles::TILE_GRASS;
+    use crate::world::entity_serialization::SavedComponent;
     use std::env;
 
     #[test]
@@ -209,6 +544,138 @@ mod tests {
         let _ = fs::remove_file(chunk_path);
     }
 
+    /// Deterministic pseudo-random tiles (LCG) drawn from `tile_types` values, and elevation
+    fn random_chunk(seed: u32, tile_types: u16) -> ChunkData {
+        let mut state = seed;
+        let mut chunk = ChunkData::empty(ChunkPos::new(-7, 12));
+        for tile in chunk.layers.iter_mut().flatten() {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            *tile = ((state >> 16) as u16) % tile_types;
+        }
+        for height in chunk.elevation.iter_mut() {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            *height = (state >> 24) as u8;
+        }
+        chunk
+    }
+
+    fn assert_round_trip(chunk: &ChunkData, name: &str) -> u64 {
+        let chunk_path = env::temp_dir().join(name);
+        save_chunk(chunk, &chunk_path).expect("Failed to save chunk");
+        let loaded = load_chunk(&chunk_path).expect("Failed to load chunk");
+        assert_eq!(loaded.position, chunk.position);
+        assert!(loaded.layers == chunk.layers);
+        assert!(loaded.elevation == chunk.elevation);
+        assert_eq!(loaded.settlers, chunk.settlers);
+        assert_eq!(loaded.entities, chunk.entities);
+        assert_eq!((loaded.generator_version, loaded.edited), (chunk.generator_version, chunk.edited));
+        let size = fs::metadata(&chunk_path).unwrap().len();
+        let _ = fs::remove_file(chunk_path);
+        size
+    }
+
+    #[test]
+    fn test_uniform_chunk_uses_palette_rle() {
+        let chunk = ChunkData::filled_layers(ChunkPos::new(1, 2), [TILE_GRASS, 0, 0]);
+        assert_eq!(encode_body(&chunk).0, ENCODING_PALETTE_RLE);
+
+        let size = assert_round_trip(&chunk, "test_chunk_uniform.bin");
+        assert!(size < 100, "uniform chunk took {} bytes", size);
+    }
+
+    #[test]
+    fn test_random_chunks_round_trip() {
+        // Two tile types: runs too short for RLE, but zlib packs them
+        let few_types = random_chunk(1, 2);
+        assert_eq!(encode_body(&few_types).0, ENCODING_ZLIB);
+        assert_round_trip(&few_types, "test_chunk_random_few.bin");
+
+        // Noise over the whole tile id range doesn't compress and falls back to raw
+        let noisy = random_chunk(2, u16::MAX);
+        assert_eq!(encode_body(&noisy).0, ENCODING_RAW);
+        assert_round_trip(&noisy, "test_chunk_random_noisy.bin");
+    }
+
+    #[test]
+    fn test_generated_chunk_compresses() {
+        let config = crate::world::generator::GeneratorConfig::default();
+        let chunk = crate::world::generator::generate_chunk(ChunkPos::new(3, -2), &config);
+        let (_, body) = encode_body(&chunk);
+        assert!(body.len() < RAW_BODY_SIZE / 3, "generated chunk body took {} bytes", body.len());
+        assert_round_trip(&chunk, "test_chunk_generated.bin");
+
+        // A damaged stream is an error, not a panic or a short chunk
+        let mut damaged = deflate(&encode_raw(&chunk));
+        damaged.truncate(damaged.len() / 2);
+        assert!(inflate(&damaged, RAW_BODY_SIZE).is_err());
+        assert!(inflate(&deflate(&[0; 10]), RAW_BODY_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_elevation_block() {
+        // Flat and smooth ground run-length encodes, noise stays raw; both round-trip
+        let mut chunk = ChunkData::filled(ChunkPos::new(0, 0), TILE_GRASS);
+        assert_eq!(encode_elevation(&chunk.elevation).0, ELEVATION_RLE);
+        for (index, height) in chunk.elevation.iter_mut().enumerate() {
+            *height = (index / 40) as u8;
+        }
+        assert_eq!(encode_elevation(&chunk.elevation).0, ELEVATION_RLE);
+        // Settlers and entities ride along in their own blocks, the generator version and flags in the header
+        chunk.settlers.push(ChunkSettler { x: 3, y: 30, roam_radius: 6 });
+        chunk.entities.push(SavedEntity::new(vec![
+            SavedComponent::Kind("snail".to_string()),
+            SavedComponent::Position { x: 40.0, y: 8.5 },
+        ]));
+        chunk.generator_version = 7;
+        chunk.edited = true;
+        assert_round_trip(&chunk, "test_chunk_elevation.bin");
+        assert_eq!(encode_elevation(&random_chunk(4, 2).elevation).0, ELEVATION_RAW);
+
+        // A short run count is rejected
+        assert!(decode_elevation(ELEVATION_RLE, &[7, 10, 0]).is_err());
+        assert!(decode_settlers(SETTLERS_V1, &[1, 2]).is_err());
+        assert!(decode_chunk_entities(ENTITIES_V1, b"ENTS").is_err());
+    }
+
+    #[test]
+    fn test_load_v2_chunk() {
+        let chunk = random_chunk(3, 4);
+        let chunk_path = env::temp_dir().join("test_chunk_v2.bin");
+
+        let body = encode_raw(&chunk);
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC_NUMBER);
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&chunk.position.x.to_le_bytes());
+        bytes.extend_from_slice(&chunk.position.y.to_le_bytes());
+        bytes.extend_from_slice(&(NUM_LAYERS as u16).to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        fs::write(&chunk_path, bytes).unwrap();
+
+        let loaded = load_chunk(&chunk_path).expect("Failed to load v2 chunk");
+        assert!(loaded.layers == chunk.layers);
+        assert!(loaded.elevation.iter().all(|&height| height == DEFAULT_ELEVATION));
+        // Nothing says which generator made it or whether it was painted, so it's kept as it is
+        assert_eq!((loaded.generator_version, loaded.edited), (0, true));
+
+        let _ = fs::remove_file(chunk_path);
+    }
+
+    #[test]
+    fn test_newer_version_is_not_corruption() {
+        let chunk_path = env::temp_dir().join("test_chunk_newer.bin");
+        let mut bytes = encode_chunk(&random_chunk(1, 2));
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&chunk_path, bytes).unwrap();
+
+        let error = load_chunk(&chunk_path).unwrap_err();
+        assert!(matches!(error, SerializationError::InvalidVersion(v) if v == VERSION + 1));
+        assert!(!error.is_corruption());
+
+        let _ = fs::remove_file(chunk_path);
+    }
+
     #[test]
     fn test_chunk_exists() {
         let temp_dir = env::temp_dir();
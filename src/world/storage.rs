@@ -0,0 +1,153 @@
+//! Where chunk files are kept: the filesystem natively, the browser's localStorage on the web
+//! `WorldManager` reads and writes chunks through a `ChunkStore`; the file tools (backups, dumps,
+//! migration, archives) work on the save directory directly and are native-only in practice
+
+use super::backup;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Backend for chunk files, keyed by the path the native build would use
+pub trait ChunkStore: Send + Sync {
+    /// The stored bytes, or None if nothing is stored under `path`
+    fn read(&self, path: &Path) -> io::Result<Option<Vec<u8>>>;
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()>;
+
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    /// Keep the current version of `path` in `backup_dir` before it is overwritten
+    /// Backends without backups do nothing
+    fn backup(&self, _path: &Path, _backup_dir: &Path, _retention: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Chunk files on disk, with rotated backups (see `backup`)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FsStore;
+
+impl ChunkStore for FsStore {
+    fn read(&self, path: &Path) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(path)?;
+        io::Write::write_all(&mut file, bytes)?;
+        file.sync_all()
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn backup(&self, path: &Path, backup_dir: &Path, retention: usize) -> io::Result<()> {
+        backup::backup_chunk_file(path, backup_dir, retention).map(|_| ())
+    }
+}
+
+/// Chunk files in the browser's localStorage, hex-encoded under their path
+/// localStorage holds a few megabytes per site, which is plenty for the palette-encoded chunks of a
+/// small world; there are no backups
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalStorageStore;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageStore {
+    fn storage() -> io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "localStorage is not available"))
+    }
+
+    fn key(path: &Path) -> String {
+        format!("worldseed:{}", path.to_string_lossy())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ChunkStore for LocalStorageStore {
+    fn read(&self, path: &Path) -> io::Result<Option<Vec<u8>>> {
+        let value = Self::storage()?
+            .get_item(&Self::key(path))
+            .map_err(|_| io::Error::other("localStorage read failed"))?;
+        value.map(|text| decode_hex(&text)).transpose()
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        // Fails when the site's quota is used up
+        Self::storage()?
+            .set_item(&Self::key(path), &encode_hex(bytes))
+            .map_err(|_| io::Error::new(io::ErrorKind::StorageFull, "localStorage is full"))
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        Self::storage()?
+            .remove_item(&Self::key(path))
+            .map_err(|_| io::Error::other("localStorage remove failed"))
+    }
+}
+
+/// The store for this platform
+pub fn default_store() -> Arc<dyn ChunkStore> {
+    #[cfg(target_arch = "wasm32")]
+    return Arc::new(LocalStorageStore);
+    #[cfg(not(target_arch = "wasm32"))]
+    return Arc::new(FsStore);
+}
+
+// localStorage only holds strings
+#[cfg_attr(not(any(target_arch = "wasm32", test)), allow(dead_code))]
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg_attr(not(any(target_arch = "wasm32", test)), allow(dead_code))]
+fn decode_hex(text: &str) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "stored chunk is not valid hex");
+    if !text.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()).ok_or_else(invalid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_fs_store_and_hex() {
+        let dir = env::temp_dir().join("worldseed_test_storage");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("chunks/chunk_0_0.bin");
+        let store = FsStore;
+
+        assert_eq!(store.read(&path).unwrap(), None);
+        store.write(&path, b"TILE").unwrap();
+        assert_eq!(store.read(&path).unwrap().as_deref(), Some(&b"TILE"[..]));
+        store.backup(&path, &dir.join("backups"), 2).unwrap();
+        assert_eq!(backup::list_backups(&path, &dir.join("backups")).len(), 1);
+        store.remove(&path).unwrap();
+        assert_eq!(store.read(&path).unwrap(), None);
+
+        let bytes = [0u8, 15, 16, 255];
+        assert_eq!(encode_hex(&bytes), "000f10ff");
+        assert_eq!(decode_hex("000f10ff").unwrap(), bytes);
+        assert!(decode_hex("0g").is_err() && decode_hex("abc").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
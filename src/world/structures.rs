@@ -0,0 +1,267 @@
+//! Structures stamped over biome terrain: stone ruins, dense groves, clearings and settlements
+//! spanning several chunks. Every cell of a `STRUCTURE_CELL_CHUNKS` grid may hold one site, placed
+//! from the world seed, and every tile of a structure is a function of its world coordinates alone,
+//! so a structure straddling chunk borders comes out the same whichever chunk is generated first.
+//! A settlement's NPCs are generated into the chunk holding its center (`ChunkData.settlers`)
+
+use super::biome::{biome_at_chunk, lattice_hash, unit, Biome};
+use crate::tiles::{ChunkPos, ChunkSettler, TileId, CHUNK_SIZE_I32, TILE_DIRT, TILE_GRASS, TILE_PATH, TILE_SAND};
+use bevy::prelude::*;
+use std::fmt;
+
+/// Chunks per side of a structure cell (at most one structure each)
+pub const STRUCTURE_CELL_CHUNKS: i32 = 4;
+
+/// Chance of a cell holding a structure
+const STRUCTURE_CHANCE: f32 = 0.4;
+
+/// Chance of a cell without another structure holding a settlement
+const SETTLEMENT_CHANCE: f32 = 0.15;
+
+/// Settlers living in a settlement (fewest, most)
+const SETTLERS: (u64, u64) = (2, 4);
+
+/// Salts keeping structure rolls independent of the biome map and patch noise
+const SITE_SALT: u64 = 2;
+const RUBBLE_SALT: u64 = 3;
+const SETTLEMENT_SALT: u64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StructureKind {
+    /// Crumbling square walls (sand standing in for stone) around a dirt floor
+    Ruins,
+    /// Lush grass where guardians plant three times as fast
+    Grove,
+    /// Open grass around a bare patch, where hardly anything is planted
+    Clearing,
+    /// Paths crossing at a trodden plaza, home to a few roaming NPCs
+    Settlement,
+}
+
+impl StructureKind {
+    /// Kinds found in a biome (marshes have none)
+    fn for_biome(biome: Biome) -> &'static [StructureKind] {
+        match biome {
+            Biome::Forest => &[StructureKind::Grove, StructureKind::Clearing],
+            Biome::Plains => &[StructureKind::Ruins, StructureKind::Grove],
+            Biome::Rocky => &[StructureKind::Ruins],
+            Biome::Marsh => &[],
+        }
+    }
+
+    /// Kinds a cell without another structure may hold instead
+    fn settlement_for_biome(biome: Biome) -> &'static [StructureKind] {
+        match biome {
+            Biome::Forest | Biome::Plains => &[StructureKind::Settlement],
+            Biome::Rocky | Biome::Marsh => &[],
+        }
+    }
+
+    /// Radius range in tiles
+    fn radius(&self) -> (i32, i32) {
+        match self {
+            StructureKind::Ruins => (8, 14),
+            StructureKind::Grove => (14, 24),
+            StructureKind::Clearing => (8, 14),
+            StructureKind::Settlement => (7, 10),
+        }
+    }
+
+    /// Multiplies the biome's tree density inside the structure
+    pub fn tree_density(&self) -> f32 {
+        match self {
+            StructureKind::Ruins => 0.3,
+            StructureKind::Grove => 3.0,
+            StructureKind::Clearing => 0.1,
+            StructureKind::Settlement => 0.2,
+        }
+    }
+}
+
+impl fmt::Display for StructureKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            StructureKind::Ruins => "ruins",
+            StructureKind::Grove => "grove",
+            StructureKind::Clearing => "clearing",
+            StructureKind::Settlement => "settlement",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A placed structure, in world tile coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Structure {
+    pub kind: StructureKind,
+    pub center: IVec2,
+    pub radius: i32,
+}
+
+impl Structure {
+    /// Whether a world tile is inside (a square for ruins, a circle otherwise)
+    pub fn contains(&self, tile: IVec2) -> bool {
+        let offset = tile - self.center;
+        match self.kind {
+            StructureKind::Ruins => offset.abs().max_element() <= self.radius,
+            _ => offset.length_squared() <= self.radius * self.radius,
+        }
+    }
+
+    /// Ground tile the structure puts at a world tile, None outside it
+    pub fn ground_tile(&self, tile: IVec2, seed: u64) -> Option<TileId> {
+        if !self.contains(tile) {
+            return None;
+        }
+        let offset = tile - self.center;
+        let tile = match self.kind {
+            StructureKind::Ruins => {
+                let rubble = unit(lattice_hash(seed, tile.x, tile.y, RUBBLE_SALT));
+                let is_wall = offset.abs().max_element() >= self.radius - 1;
+                // Walls are two tiles thick with collapsed stretches; stones are scattered inside
+                if (is_wall && rubble < 0.75) || (!is_wall && rubble < 0.05) {
+                    TILE_SAND
+                } else {
+                    TILE_DIRT
+                }
+            }
+            StructureKind::Grove => TILE_GRASS,
+            StructureKind::Clearing if offset.length_squared() * 16 <= self.radius * self.radius => TILE_DIRT,
+            StructureKind::Clearing => TILE_GRASS,
+            // A plaza in the middle with paths leading out in the four directions
+            StructureKind::Settlement if offset.length_squared() <= 5 || offset.x == 0 || offset.y == 0 => TILE_PATH,
+            StructureKind::Settlement => TILE_GRASS,
+        };
+        Some(tile)
+    }
+
+    /// NPCs of a settlement, homed around the plaza in the chunk holding its center (empty for
+    /// other kinds); they roam the whole settlement
+    pub fn settlers(&self, seed: u64) -> Vec<ChunkSettler> {
+        if self.kind != StructureKind::Settlement {
+            return Vec::new();
+        }
+        let hash = lattice_hash(seed, self.center.x, self.center.y, SETTLEMENT_SALT);
+        let count = SETTLERS.0 + hash % (SETTLERS.1 - SETTLERS.0 + 1);
+        let local = self.center.rem_euclid(IVec2::splat(CHUNK_SIZE_I32));
+        (0..count)
+            .map(|index| {
+                let angle = unit(hash.rotate_left(8 * index as u32 + 8)) * std::f32::consts::TAU;
+                let home = (local.as_vec2() + Vec2::from_angle(angle) * 2.0).round().as_ivec2().clamp(IVec2::ZERO, IVec2::splat(CHUNK_SIZE_I32 - 1));
+                ChunkSettler { x: home.x as u8, y: home.y as u8, roam_radius: self.radius as u8 }
+            })
+            .collect()
+    }
+}
+
+/// The structure sited in a cell of the structure grid, if any
+/// Settlements only go in cells the other kinds left empty, so adding them moved no other structure
+pub fn structure_in_cell(cell: IVec2, seed: u64) -> Option<Structure> {
+    let hash = lattice_hash(seed, cell.x, cell.y, SITE_SALT);
+    if unit(hash) < STRUCTURE_CHANCE {
+        return site(cell, seed, hash, StructureKind::for_biome);
+    }
+    let hash = lattice_hash(seed, cell.x, cell.y, SETTLEMENT_SALT);
+    if unit(hash) < SETTLEMENT_CHANCE {
+        return site(cell, seed, hash, StructureKind::settlement_for_biome);
+    }
+    None
+}
+
+/// A structure of one of the `kinds` the biome at its center allows, placed in a cell by `hash`
+fn site(cell: IVec2, seed: u64, hash: u64, kinds: fn(Biome) -> &'static [StructureKind]) -> Option<Structure> {
+    let cell_tiles = STRUCTURE_CELL_CHUNKS * CHUNK_SIZE_I32;
+    let center = cell * cell_tiles
+        + IVec2::new(
+            (unit(hash.rotate_left(16)) * cell_tiles as f32) as i32,
+            (unit(hash.rotate_left(32)) * cell_tiles as f32) as i32,
+        );
+    let biome = biome_at_chunk(ChunkPos::new(center.x.div_euclid(CHUNK_SIZE_I32), center.y.div_euclid(CHUNK_SIZE_I32)), seed);
+    let kinds = kinds(biome);
+    let kind = *kinds.get((hash.rotate_left(48) % kinds.len().max(1) as u64) as usize)?;
+    let (min, max) = kind.radius();
+    let radius = min + (unit(hash.rotate_left(8)) * (max - min + 1) as f32) as i32;
+    Some(Structure { kind, center, radius })
+}
+
+/// Structures overlapping a chunk (a structure is smaller than a cell, so only the neighbouring
+/// cells can reach in)
+pub fn structures_near_chunk(chunk: ChunkPos, seed: u64) -> Vec<Structure> {
+    let min = IVec2::new(chunk.x, chunk.y) * CHUNK_SIZE_I32;
+    let max = min + IVec2::splat(CHUNK_SIZE_I32 - 1);
+    let cell = IVec2::new(chunk.x.div_euclid(STRUCTURE_CELL_CHUNKS), chunk.y.div_euclid(STRUCTURE_CELL_CHUNKS));
+    let mut structures = Vec::new();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let Some(structure) = structure_in_cell(cell + IVec2::new(dx, dy), seed) else {
+                continue;
+            };
+            let reaches = (structure.center - max).cmple(IVec2::splat(structure.radius)).all()
+                && (min - structure.center).cmple(IVec2::splat(structure.radius)).all();
+            if reaches {
+                structures.push(structure);
+            }
+        }
+    }
+    structures
+}
+
+/// The structure covering a world tile, if any
+pub fn structure_at(tile: IVec2, seed: u64) -> Option<Structure> {
+    let chunk = ChunkPos::new(tile.x.div_euclid(CHUNK_SIZE_I32), tile.y.div_euclid(CHUNK_SIZE_I32));
+    structures_near_chunk(chunk, seed).into_iter().find(|structure| structure.contains(tile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{CHUNK_SIZE, LAYER_GROUND};
+    use crate::world::biome::world_tile;
+    use crate::world::generator::{generate_chunk, GeneratorConfig};
+
+    #[test]
+    fn test_structures_straddle_chunk_borders() {
+        let seed = 11;
+        let config = GeneratorConfig { seed, ..default() };
+        let structures: Vec<Structure> = (-8..8)
+            .flat_map(|y| (-8..8).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| structure_in_cell(cell, seed))
+            .collect();
+        assert!(structures.len() > 10);
+        assert!(structures.iter().any(|structure| structure.kind == StructureKind::Ruins));
+        assert_eq!(structure_in_cell(IVec2::new(3, -2), seed), structure_in_cell(IVec2::new(3, -2), seed));
+
+        // Every chunk a structure overlaps is stamped with the same structure tiles
+        let straddling = structures.iter().filter(|structure| {
+            let radius = IVec2::splat(structure.radius);
+            (structure.center - radius).div_euclid(IVec2::splat(CHUNK_SIZE_I32)) != (structure.center + radius).div_euclid(IVec2::splat(CHUNK_SIZE_I32))
+        });
+        for structure in straddling.take(6) {
+            let radius = IVec2::splat(structure.radius);
+            let min = (structure.center - radius).div_euclid(IVec2::splat(CHUNK_SIZE_I32));
+            let max = (structure.center + radius).div_euclid(IVec2::splat(CHUNK_SIZE_I32));
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let chunk = generate_chunk(ChunkPos::new(x, y), &config);
+                    for (tx, ty) in (0..CHUNK_SIZE).flat_map(|ty| (0..CHUNK_SIZE).map(move |tx| (tx, ty))) {
+                        let tile = world_tile(chunk.position, tx, ty);
+                        // Overlapping structures: the first one listed for the chunk wins
+                        if structure_at(tile, seed) != Some(*structure) {
+                            continue;
+                        }
+                        assert_eq!(chunk.get_tile(LAYER_GROUND, tx, ty), structure.ground_tile(tile, seed));
+                    }
+                }
+            }
+        }
+
+        // Settlements house their NPCs in the chunk holding the center, and only there
+        let settlement = structures.iter().find(|structure| structure.kind == StructureKind::Settlement).expect("no settlement");
+        assert_eq!(settlement.ground_tile(settlement.center, seed), Some(TILE_PATH));
+        let home = ChunkPos::new(settlement.center.x.div_euclid(CHUNK_SIZE_I32), settlement.center.y.div_euclid(CHUNK_SIZE_I32));
+        let settlers = generate_chunk(home, &config).settlers;
+        assert!((2..=4).contains(&settlers.len()));
+        assert_eq!(settlers, settlement.settlers(seed));
+        assert!(generate_chunk(ChunkPos::new(home.x + 1, home.y), &config).settlers.is_empty());
+    }
+}
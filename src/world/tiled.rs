@@ -0,0 +1,456 @@
+//! Tiled map import: turns a `.tmx` export into chunk files, so regions can be hand-authored in Tiled
+//! Supports orthogonal maps (finite or infinite) with CSV or Base64 layer data (uncompressed, zlib
+//! or gzip). Tile layers named ground/decoration/overlay go to that layer, other layers fill the
+//! layers in order. Each tileset tile names the registry tile it stands for with a `tile` property,
+//! or its class (type)
+
+use super::loader;
+use super::manager::WorldManager;
+use super::generate_chunk;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::{stash_loaded_entities, ChunkEntity, ChunkEntityFilter};
+use crate::tiles::{
+    ChunkData, ChunkPos, TileId, TileRegistry, CHUNK_SIZE_I32, LAYER_DECORATION, LAYER_GROUND,
+    LAYER_OVERLAY, NUM_LAYERS,
+};
+use base64::Engine;
+use bevy::prelude::*;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The top bits of a Tiled GID flag flipped/rotated tiles; the rest is the tile
+const GID_MASK: u32 = 0x0FFF_FFFF;
+
+/// Error type for Tiled imports
+#[derive(Debug, Error)]
+pub enum TiledError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("XML error: {0}")]
+    Xml(#[from] quick_xml::Error),
+    #[error("Base64 error: {0}")]
+    Base64(#[from] base64::DecodeError),
+    /// A feature of the file this importer doesn't handle
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+    /// Missing or malformed attributes, data of the wrong size
+    #[error("Invalid map: {0}")]
+    Invalid(String),
+    /// A placed tile with no tileset entry naming a registry tile
+    #[error("Tile GID {0} has no `tile` property or class naming a registered tile")]
+    UnmappedTile(u32),
+    #[error("Tile '{0}' is not registered")]
+    UnknownTile(String),
+}
+
+/// A tileset: registry tile names by local tile id
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    pub names: HashMap<u32, String>,
+}
+
+/// A tile layer: the GIDs of its placed tiles by Tiled tile coordinates (x right, y down)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TiledLayer {
+    pub name: String,
+    pub tiles: Vec<(IVec2, u32)>,
+}
+
+/// The parts of a Tiled map the importer uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TiledMap {
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Name of the tileset tile a GID refers to
+    fn tile_name(&self, gid: u32) -> Option<&str> {
+        let tileset = self.tilesets.iter().filter(|tileset| tileset.first_gid <= gid).max_by_key(|tileset| tileset.first_gid)?;
+        tileset.names.get(&(gid - tileset.first_gid)).map(String::as_str)
+    }
+}
+
+/// Value of an attribute, if present
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, TiledError> {
+    match element.try_get_attribute(name).map_err(quick_xml::Error::from)? {
+        Some(value) => Ok(Some(value.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+/// A required numeric attribute
+fn number<T: FromStr>(element: &BytesStart, name: &str) -> Result<T, TiledError> {
+    attribute(element, name)?.and_then(|value| value.parse().ok()).ok_or_else(|| {
+        TiledError::Invalid(format!(
+            "<{}> needs a numeric {}",
+            String::from_utf8_lossy(element.name().as_ref()),
+            name
+        ))
+    })
+}
+
+/// Reads a tileset's tiles, up to its closing tag
+fn read_tileset(reader: &mut Reader<&[u8]>, first_gid: u32) -> Result<TiledTileset, TiledError> {
+    let mut tileset = TiledTileset {
+        first_gid,
+        ..default()
+    };
+    let mut current_tile = None;
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) | Event::Empty(element) => match element.name().as_ref() {
+                b"tile" => {
+                    let id: u32 = number(&element, "id")?;
+                    current_tile = Some(id);
+                    // `class` since Tiled 1.9, `type` before
+                    if let Some(class) = attribute(&element, "class")?.or(attribute(&element, "type")?) {
+                        tileset.names.insert(id, class);
+                    }
+                }
+                b"property" => {
+                    if let (Some(id), Some("tile")) = (current_tile, attribute(&element, "name")?.as_deref()) {
+                        if let Some(value) = attribute(&element, "value")? {
+                            tileset.names.insert(id, value);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::End(element) => match element.name().as_ref() {
+                b"tile" => current_tile = None,
+                b"tileset" => return Ok(tileset),
+                _ => {}
+            },
+            Event::Eof => return Ok(tileset),
+            _ => {}
+        }
+    }
+}
+
+/// Parses an external tileset (`.tsx`)
+pub fn parse_tsx(xml: &str, first_gid: u32) -> Result<TiledTileset, TiledError> {
+    read_tileset(&mut Reader::from_str(xml), first_gid)
+}
+
+/// Decodes the text of a `<data>` or `<chunk>` element into GIDs
+fn decode_data(text: &str, encoding: Option<&str>, compression: Option<&str>) -> Result<Vec<u32>, TiledError> {
+    match encoding {
+        Some("csv") => text
+            .split(',')
+            .map(|value| value.trim().parse::<u32>())
+            .collect::<Result<_, _>>()
+            .map_err(|e| TiledError::Invalid(format!("CSV layer data: {}", e))),
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(text.trim())?;
+            let mut raw = Vec::new();
+            match compression {
+                None | Some("") => raw = bytes,
+                Some("zlib") => {
+                    ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut raw)?;
+                }
+                Some("gzip") => {
+                    GzDecoder::new(bytes.as_slice()).read_to_end(&mut raw)?;
+                }
+                Some(other) => {
+                    return Err(TiledError::Unsupported(format!("{} compression (use zlib, gzip or none)", other)))
+                }
+            }
+            if raw.len() % 4 != 0 {
+                return Err(TiledError::Invalid(format!("{} bytes of Base64 layer data", raw.len())));
+            }
+            Ok(raw.chunks_exact(4).map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]])).collect())
+        }
+        _ => Err(TiledError::Unsupported("XML tile data (export layers as CSV or Base64)".to_string())),
+    }
+}
+
+/// Parses a Tiled map (`.tmx`); external tilesets are read relative to `dir`
+pub fn parse_tmx(xml: &str, dir: &Path) -> Result<TiledMap, TiledError> {
+    let mut reader = Reader::from_str(xml);
+    let mut map = TiledMap::default();
+    let mut layer: Option<TiledLayer> = None;
+    let mut layer_width = 0;
+    // Encoding and compression of the current <data>
+    let mut data: Option<(Option<String>, Option<String>)> = None;
+    // Offset and width of the block of tiles being read: the layer, or one chunk of an infinite map
+    let mut block = (IVec2::ZERO, 0);
+    let mut text = String::new();
+
+    loop {
+        let event = reader.read_event()?;
+        let is_empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(element) | Event::Empty(element) => match element.name().as_ref() {
+                b"map" => {
+                    let orientation = attribute(&element, "orientation")?;
+                    if orientation.as_deref().is_some_and(|orientation| orientation != "orthogonal") {
+                        return Err(TiledError::Unsupported(format!("{} maps", orientation.unwrap_or_default())));
+                    }
+                }
+                b"tileset" => {
+                    let first_gid = number(&element, "firstgid")?;
+                    let tileset = match attribute(&element, "source")? {
+                        Some(source) => parse_tsx(&fs::read_to_string(dir.join(source))?, first_gid)?,
+                        None if is_empty => TiledTileset {
+                            first_gid,
+                            ..default()
+                        },
+                        None => read_tileset(&mut reader, first_gid)?,
+                    };
+                    map.tilesets.push(tileset);
+                }
+                b"layer" => {
+                    layer_width = number(&element, "width")?;
+                    layer = Some(TiledLayer {
+                        name: attribute(&element, "name")?.unwrap_or_default(),
+                        tiles: Vec::new(),
+                    });
+                }
+                b"data" if layer.is_some() => {
+                    data = Some((attribute(&element, "encoding")?, attribute(&element, "compression")?));
+                    block = (IVec2::ZERO, layer_width);
+                    text.clear();
+                }
+                b"chunk" if data.is_some() => {
+                    block = (IVec2::new(number(&element, "x")?, number(&element, "y")?), number(&element, "width")?);
+                    text.clear();
+                }
+                _ => {}
+            },
+            Event::Text(content) if data.is_some() => text.push_str(&content.unescape()?),
+            Event::End(element) => match element.name().as_ref() {
+                // A finite map's tiles end with <data>, an infinite map's with each <chunk>
+                name @ (b"data" | b"chunk") if data.is_some() => {
+                    if name == b"data" && text.trim().is_empty() {
+                        data = None;
+                        continue;
+                    }
+                    let (encoding, compression) = data.as_ref().expect("inside <data>");
+                    let gids = decode_data(&text, encoding.as_deref(), compression.as_deref())?;
+                    let (offset, width) = block;
+                    if width <= 0 {
+                        return Err(TiledError::Invalid("tile block without a width".to_string()));
+                    }
+                    let tiles = &mut layer.as_mut().expect("<data> inside <layer>").tiles;
+                    for (index, gid) in gids.into_iter().enumerate() {
+                        let gid = gid & GID_MASK;
+                        if gid != 0 {
+                            let index = index as i32;
+                            tiles.push((offset + IVec2::new(index % width, index / width), gid));
+                        }
+                    }
+                    text.clear();
+                    if name == b"data" {
+                        data = None;
+                    }
+                }
+                b"layer" => map.layers.extend(layer.take()),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(map)
+}
+
+/// Game layer a Tiled layer goes to: by name, else by its position among the tile layers
+fn target_layer(name: &str, position: usize) -> Result<usize, TiledError> {
+    match name.to_lowercase().as_str() {
+        "ground" => Ok(LAYER_GROUND),
+        "decoration" => Ok(LAYER_DECORATION),
+        "overlay" => Ok(LAYER_OVERLAY),
+        _ if position < NUM_LAYERS => Ok(position),
+        _ => Err(TiledError::Unsupported(format!(
+            "more than {} tile layers (layer '{}' should be named ground, decoration or overlay)",
+            NUM_LAYERS, name
+        ))),
+    }
+}
+
+/// Writes a parsed map into chunks, with its top-left tile at world tile `origin` (Tiled rows run
+/// south). Tiles the map leaves empty keep what `base` (the current chunk) has there
+pub fn import_map(
+    map: &TiledMap,
+    registry: &TileRegistry,
+    origin: IVec2,
+    mut base: impl FnMut(ChunkPos) -> ChunkData,
+) -> Result<Vec<ChunkData>, TiledError> {
+    let mut tile_ids: HashMap<u32, TileId> = HashMap::new();
+    let mut chunks: HashMap<ChunkPos, ChunkData> = HashMap::new();
+    for (position, layer) in map.layers.iter().enumerate() {
+        let layer_index = target_layer(&layer.name, position)?;
+        for (tiled_pos, gid) in layer.tiles.iter() {
+            let tile_id = match tile_ids.get(gid) {
+                Some(tile_id) => *tile_id,
+                None => {
+                    let name = map.tile_name(*gid).ok_or(TiledError::UnmappedTile(*gid))?;
+                    let tile_id = registry.find_by_name(name).ok_or_else(|| TiledError::UnknownTile(name.to_string()))?;
+                    tile_ids.insert(*gid, tile_id);
+                    tile_id
+                }
+            };
+
+            let world_tile = origin + IVec2::new(tiled_pos.x, -tiled_pos.y);
+            let chunk_pos = ChunkPos::new(world_tile.x.div_euclid(CHUNK_SIZE_I32), world_tile.y.div_euclid(CHUNK_SIZE_I32));
+            let chunk = chunks.entry(chunk_pos).or_insert_with(|| base(chunk_pos));
+            chunk.edited = true;
+            chunk.set_tile(
+                layer_index,
+                world_tile.x.rem_euclid(CHUNK_SIZE_I32) as usize,
+                world_tile.y.rem_euclid(CHUNK_SIZE_I32) as usize,
+                tile_id,
+            );
+        }
+    }
+    let mut chunks: Vec<ChunkData> = chunks.into_values().collect();
+    chunks.sort_by_key(|chunk| (chunk.position.y, chunk.position.x));
+    Ok(chunks)
+}
+
+/// `importtiled <file.tmx> [x y]`: imports a Tiled map with its top-left tile at world tile (x, y)
+/// (default 0 0). Replaced chunk files are kept as backups, and loaded chunks are reloaded
+pub fn handle_tiled_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut world: ResMut<WorldManager>,
+    registry: Res<TileRegistry>,
+    chunk_entities: Query<ChunkEntity, ChunkEntityFilter>,
+) {
+    for command in console_commands.read() {
+        if command.name != "importtiled" {
+            continue;
+        }
+        let origin = match command.args.get(1..3).map(|args| (args[0].parse(), args[1].parse())) {
+            None => Some(IVec2::ZERO),
+            Some((Ok(x), Ok(y))) => Some(IVec2::new(x, y)),
+            Some(_) => None,
+        };
+        let (Some(file), Some(origin), 1 | 3) = (command.args.first(), origin, command.args.len()) else {
+            console.print("Usage: importtiled <file.tmx> [x y]");
+            continue;
+        };
+
+        // The import starts from the loaded chunks as they are now, creatures included
+        stash_loaded_entities(&mut world, &chunk_entities);
+        let path = Path::new(file);
+        let result = fs::read_to_string(path)
+            .map_err(TiledError::from)
+            .and_then(|xml| parse_tmx(&xml, path.parent().unwrap_or(Path::new("."))))
+            .and_then(|map| {
+                import_map(&map, &registry, origin, |pos| {
+                    // The in-memory copy may have unsaved edits
+                    match world.get_cached_chunk(&pos) {
+                        Some(chunk) => chunk.clone(),
+                        None => match world.load_stored_chunk(&pos) {
+                            Ok(Some(chunk)) => chunk,
+                            _ => generate_chunk(pos, &world.generator),
+                        },
+                    }
+                })
+            });
+        let chunks = match result {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                console.print(format!("Import failed: {}", e));
+                continue;
+            }
+        };
+
+        let mut imported = 0;
+        for chunk in &chunks {
+            let chunk_pos = chunk.position;
+            match world.replace_chunk_file(chunk) {
+                Ok(()) => {
+                    loader::reload_chunk(&mut commands, &mut world, &chunk_entities, chunk_pos);
+                    imported += 1;
+                }
+                Err(e) => console.print(format!("Failed to write chunk ({}, {}): {}", chunk_pos.x, chunk_pos.y, e)),
+            }
+        }
+        console.print(format!(
+            "Imported {} into {} of {} chunks at tile ({}, {}) (previous files kept as backups)",
+            file,
+            imported,
+            chunks.len(),
+            origin.x,
+            origin.y
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::{CHUNK_SIZE, TILE_DIRT, TILE_GRASS, TILE_WATER};
+    use std::io::Write;
+
+    #[test]
+    fn test_import_tiled_map() {
+        // A 3x2 map: CSV ground with a flipped tile, zlib Base64 decoration
+        let mut decoration = Vec::new();
+        for gid in [0u32, 0, 3, 0, 0, 0] {
+            decoration.extend_from_slice(&gid.to_le_bytes());
+        }
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&decoration).unwrap();
+        let decoration = base64::engine::general_purpose::STANDARD.encode(encoder.finish().unwrap());
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="8" tileheight="8" infinite="0">
+ <tileset firstgid="1" name="terrain" tilewidth="8" tileheight="8" tilecount="3">
+  <tile id="0" type="grass"/>
+  <tile id="1"><properties><property name="tile" value="dirt"/></properties></tile>
+  <tile id="2" class="water"/>
+ </tileset>
+ <layer id="1" name="Decoration" width="3" height="2">
+  <data encoding="base64" compression="zlib">{}</data>
+ </layer>
+ <layer id="2" name="Ground" width="3" height="2">
+  <data encoding="csv">
+1,2,1,
+0,2147483650,0
+</data>
+ </layer>
+</map>"#,
+            decoration
+        );
+
+        let map = parse_tmx(&xml, Path::new(".")).unwrap();
+        assert_eq!(map.layers.len(), 2);
+        assert_eq!(map.tile_name(2), Some("dirt"));
+
+        // Top-left at world tile (-1, 0): the top row lands in chunks (-1, 0) and (0, 0), the second
+        // row (south) in chunk (0, -1); its first tile is empty, so chunk (-1, -1) isn't touched
+        let registry = TileRegistry::new();
+        let chunks = import_map(&map, &registry, IVec2::new(-1, 0), |pos| ChunkData::filled_layers(pos, [TILE_WATER, 0, 0])).unwrap();
+        let chunk = |x, y| chunks.iter().find(|chunk| chunk.position == ChunkPos::new(x, y)).unwrap();
+        assert_eq!(chunks.len(), 3);
+        let last = CHUNK_SIZE - 1;
+        assert_eq!(chunk(-1, 0).get_tile(LAYER_GROUND, last, 0), Some(TILE_GRASS));
+        assert_eq!(chunk(0, 0).get_tile(LAYER_GROUND, 0, 0), Some(TILE_DIRT));
+        assert_eq!(chunk(0, 0).get_tile(LAYER_GROUND, 1, 0), Some(TILE_GRASS));
+        assert_eq!(chunk(0, 0).get_tile(LAYER_DECORATION, 1, 0), Some(TILE_WATER));
+        // Empty map tiles keep the base chunk's tile; flip flags are ignored
+        assert_eq!(chunk(0, -1).get_tile(LAYER_GROUND, 0, last), Some(TILE_DIRT));
+        assert_eq!(chunk(0, -1).get_tile(LAYER_GROUND, 1, last), Some(TILE_WATER));
+
+        // Tiles without a registry name are reported
+        let mut unnamed = map.clone();
+        unnamed.tilesets[0].names.remove(&2);
+        assert!(matches!(
+            import_map(&unnamed, &registry, IVec2::ZERO, ChunkData::empty),
+            Err(TiledError::UnmappedTile(3))
+        ));
+    }
+}
@@ -0,0 +1,347 @@
+//! Upgrading worlds saved by an older version: on startup, a world whose `WorldMetadata.versions`
+//! are older than this build is first archived whole to `backups/` (before anything is migrated),
+//! then a dialog lists what will change. "Upgrade now" rewrites old chunk files (see `migration`) and
+//! stamps the current versions; "Later" keeps playing (chunks are still upgraded as they're saved)
+//! and asks again next launch
+
+use super::archive;
+use super::manager::WorldManager;
+use super::metadata::{SaveVersions, WorldMetadata};
+use super::migration::{self, MIGRATION_REPORT_FILE_NAME};
+use super::serialization;
+use crate::locale::{Locale, LocaleArg, LocalizedText};
+use crate::platform::unix_time;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An upgrade waiting for the user's answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradePlan {
+    /// Versions the world was saved with
+    pub from: SaveVersions,
+    /// Chunk files in an older format, out of all chunk files
+    pub outdated_chunks: usize,
+    pub total_chunks: usize,
+    /// The archive written before upgrading, or why it couldn't be
+    pub backup: Result<PathBuf, String>,
+}
+
+/// One line of the upgrade dialog: a message id and its arguments, formatted with the `Locale`
+#[derive(Debug, Clone)]
+pub struct UpgradeChange {
+    pub id: &'static str,
+    pub args: Vec<(&'static str, LocaleArg<'static>)>,
+}
+
+impl UpgradeChange {
+    pub fn text(&self, locale: &Locale) -> String {
+        locale.format(self.id, &self.args)
+    }
+}
+
+impl UpgradePlan {
+    /// What upgrading changes, one line each (shown in the dialog)
+    /// Unrecorded versions are passed as 0, which the messages spell out
+    pub fn changes(&self) -> Vec<UpgradeChange> {
+        let current = SaveVersions::current();
+        let mut lines = Vec::new();
+        if self.from.chunk_format < current.chunk_format {
+            lines.push(UpgradeChange {
+                id: "upgrade-change-chunks",
+                args: vec![
+                    ("from", self.from.chunk_format.into()),
+                    ("to", current.chunk_format.into()),
+                    ("outdated", self.outdated_chunks.into()),
+                    ("total", self.total_chunks.into()),
+                ],
+            });
+        }
+        if self.from.generator < current.generator {
+            lines.push(UpgradeChange {
+                id: "upgrade-change-generator",
+                args: vec![("from", self.from.generator.into()), ("to", current.generator.into())],
+            });
+        }
+        lines.push(match &self.backup {
+            Ok(path) => UpgradeChange {
+                id: "upgrade-backup-done",
+                args: vec![("path", path.display().to_string().into())],
+            },
+            Err(e) => UpgradeChange {
+                id: "upgrade-backup-failed",
+                args: vec![("error", e.clone().into())],
+            },
+        });
+        lines
+    }
+}
+
+/// The pending upgrade, if the world is outdated and the dialog hasn't been answered
+#[derive(Resource, Debug, Default)]
+pub struct WorldUpgrade {
+    pub plan: Option<UpgradePlan>,
+}
+
+/// Run condition for world input the upgrade dialog blocks
+pub fn upgrade_dialog_closed(upgrade: Res<WorldUpgrade>) -> bool {
+    upgrade.plan.is_none()
+}
+
+/// Chunk files in an older format than this build's, and all chunk files (unreadable ones count as neither)
+pub fn count_outdated_chunks(chunks_dir: &Path) -> (usize, usize) {
+    let Ok(entries) = fs::read_dir(chunks_dir) else {
+        return (0, 0);
+    };
+    let versions: Vec<u16> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "bin"))
+        .filter_map(|path| serialization::read_chunk_version(path).ok())
+        .collect();
+    let outdated = versions.iter().filter(|version| **version < serialization::VERSION).count();
+    (outdated, versions.len())
+}
+
+/// Checks the loaded world's versions (new worlds were stamped with the current ones by
+/// `load_world_metadata`): outdated worlds are archived and get the upgrade dialog
+pub fn check_world_version(
+    world: Res<WorldManager>,
+    metadata: Res<WorldMetadata>,
+    locale: Res<Locale>,
+    mut upgrade: ResMut<WorldUpgrade>,
+) {
+    let chunks_dir = world.save_directory.join("chunks");
+    if !metadata.versions.is_outdated() {
+        return;
+    }
+
+    let from = metadata.versions;
+    // One archive per old version, so later launches don't replace the most original copy
+    let archive_path = world
+        .get_backup_dir()
+        .join(format!("before_upgrade_v{}_g{}.warc", from.chunk_format, from.generator));
+    let backup = if archive_path.exists() {
+        Ok(archive_path)
+    } else {
+        match archive::export_world(&world.save_directory, &archive_path) {
+            Ok(count) => {
+                info!("Backed up {} files to {:?} before upgrading", count, archive_path);
+                Ok(archive_path)
+            }
+            Err(e) => {
+                warn!("Failed to back up the world before upgrading: {}", e);
+                Err(e.to_string())
+            }
+        }
+    };
+    let (outdated_chunks, total_chunks) = count_outdated_chunks(&chunks_dir);
+    let plan = UpgradePlan { from, outdated_chunks, total_chunks, backup };
+    for line in plan.changes() {
+        info!("World upgrade: {}", line.text(&locale));
+    }
+    upgrade.plan = Some(plan);
+}
+
+/// Marker component for the upgrade dialog
+#[derive(Component)]
+pub struct UpgradeDialog;
+
+/// Marker component for the upgrade dialog's list of changes
+#[derive(Component)]
+pub struct UpgradeDialogText;
+
+/// Buttons in the upgrade dialog
+#[derive(Component, Clone, Copy)]
+pub enum UpgradeDialogButton {
+    Upgrade,
+    Later,
+}
+
+/// Spawns the hidden upgrade dialog
+pub fn setup_upgrade_dialog(mut commands: Commands) {
+    commands
+        .spawn((
+            UpgradeDialog,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ZIndex(1100),
+            Visibility::Hidden,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(520.0),
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(14.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.1, 0.14, 0.24)),
+                    BorderColor::all(Color::srgb(0.4, 0.55, 0.85)),
+                    BorderRadius::all(Val::Px(6.0)),
+                ))
+                .with_children(|dialog| {
+                    dialog.spawn((
+                        LocalizedText("upgrade-dialog-title"),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                    dialog.spawn((
+                        UpgradeDialogText,
+                        Text::new(""),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.85, 0.88, 0.95)),
+                    ));
+                    dialog
+                        .spawn(Node {
+                            column_gap: Val::Px(10.0),
+                            justify_content: JustifyContent::End,
+                            ..default()
+                        })
+                        .with_children(|buttons| {
+                            spawn_dialog_button(buttons, "upgrade-later", UpgradeDialogButton::Later);
+                            spawn_dialog_button(buttons, "upgrade-now", UpgradeDialogButton::Upgrade);
+                        });
+                });
+        });
+}
+
+fn spawn_dialog_button(parent: &mut ChildSpawnerCommands, label: &'static str, action: UpgradeDialogButton) {
+    parent
+        .spawn((
+            Button,
+            action,
+            Node {
+                padding: UiRect::axes(Val::Px(14.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.28, 0.45)),
+            BorderRadius::all(Val::Px(4.0)),
+        ))
+        .observe(upgrade_dialog_click)
+        .with_children(|button| {
+            button.spawn((
+                LocalizedText(label),
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn upgrade_dialog_click(
+    trigger: On<Pointer<Click>>,
+    buttons: Query<&UpgradeDialogButton>,
+    mut upgrade: ResMut<WorldUpgrade>,
+    mut metadata: ResMut<WorldMetadata>,
+    world: Res<WorldManager>,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    let Ok(action) = buttons.get(trigger.entity) else {
+        return;
+    };
+    upgrade.plan = None;
+    if let UpgradeDialogButton::Later = action {
+        return;
+    }
+
+    let chunks_dir = world.save_directory.join("chunks");
+    let upgraded = if chunks_dir.exists() {
+        match migration::migrate_chunks(&chunks_dir, &world.get_backup_dir(), world.backup_retention) {
+            Ok(report) => {
+                info!("{}", report.summary());
+                let report_path = world.save_directory.join(MIGRATION_REPORT_FILE_NAME);
+                if let Err(e) = fs::write(&report_path, format!("Upgrade at unix time {}\n{}", unix_time().as_secs(), report.to_text())) {
+                    warn!("Failed to write {:?}: {}", report_path, e);
+                }
+                if !report.failed.is_empty() {
+                    toasts.write(Toast::warning(locale.format("toast-upgrade-failed", &[("count", report.failed.len().into())])));
+                    return;
+                }
+                report.upgraded.len()
+            }
+            Err(e) => {
+                warn!("World upgrade failed: {}", e);
+                toasts.write(Toast::error(locale.format("toast-upgrade-failed", &[("count", 0.into())])));
+                return;
+            }
+        }
+    } else {
+        0
+    };
+    metadata.versions = SaveVersions::current();
+    toasts.write(Toast::info(locale.format("toast-world-upgraded", &[("count", upgraded.into())])));
+}
+
+/// Shows the dialog while an upgrade is waiting for an answer
+pub fn update_upgrade_dialog(
+    upgrade: Res<WorldUpgrade>,
+    mut dialog: Single<&mut Visibility, With<UpgradeDialog>>,
+    mut text: Single<&mut Text, With<UpgradeDialogText>>,
+    locale: Res<Locale>,
+) {
+    if !upgrade.is_changed() && !locale.is_changed() {
+        return;
+    }
+    **dialog = match upgrade.plan {
+        Some(_) => Visibility::Visible,
+        None => Visibility::Hidden,
+    };
+    if let Some(plan) = &upgrade.plan {
+        let changes = plan.changes().iter().map(|line| format!("- {}", line.text(&locale))).collect::<Vec<_>>();
+        text.0 = format!("{}\n\n{}", locale.text("upgrade-dialog-body"), changes.join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_plan() {
+        assert!(SaveVersions::default().is_outdated());
+        assert!(!SaveVersions::current().is_outdated());
+
+        let plan = UpgradePlan {
+            from: SaveVersions { chunk_format: 2, generator: 0 },
+            outdated_chunks: 3,
+            total_chunks: 10,
+            backup: Ok(PathBuf::from("backups/before_upgrade_v2_g0.warc")),
+        };
+        let english = Locale::new("en-US");
+        let changes: Vec<String> = plan.changes().iter().map(|line| line.text(&english)).collect();
+        assert_eq!(changes.len(), 3);
+        assert!(changes[0].starts_with(&format!("Chunk format v2 -> v{}: 3 of 10", serialization::VERSION)));
+        assert!(changes[1].starts_with("Terrain generator unrecorded -> v1"));
+
+        // Only the generator is outdated, and the backup failed
+        let plan = UpgradePlan {
+            from: SaveVersions { generator: 0, ..SaveVersions::current() },
+            backup: Err("disk full".to_string()),
+            ..plan
+        };
+        let changes: Vec<String> = plan.changes().iter().map(|line| line.text(&english)).collect();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[1].contains("FAILED (disk full)"));
+    }
+}
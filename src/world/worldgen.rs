@@ -0,0 +1,292 @@
+//! Generation tuning read from `WORLDGEN_PATH`: the patch and elevation noise, the elevation
+//! thresholds and tree density. The file is polled and re-read when it changes, so chunks generated
+//! afterwards use the new numbers without recompiling; chunks already generated (or saved) keep
+//! their terrain (`regenerate` throws a chunk away to see the change). The numbers aren't saved with
+//! worlds, and the defaults are the stock generator
+
+use super::biome::PATCH_SIZE;
+use super::elevation::{ROCK_LEVEL, SHORE_LEVEL, WATER_LEVEL};
+use super::{loader, model, WorldManager, WorldOrigin};
+use crate::config::ConfigError;
+use crate::console::{ConsoleCommand, ConsoleState};
+use crate::entities::Settler;
+use crate::error::Failures;
+use crate::locale::Locale;
+use crate::tiles::{ChunkPos, CHUNK_PIXEL_SIZE};
+use crate::toast::Toast;
+use bevy::prelude::*;
+use bevy::sprite_render::TilemapChunkTileData;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Location of the tuning file (optional; without it the defaults are used)
+pub const WORLDGEN_PATH: &str = "assets/worldgen.ron";
+
+/// Seconds between checks of the tuning file
+const POLL_INTERVAL: f32 = 1.0;
+
+/// Numbers `GroundPattern::Biomes` generates with (`GeneratorConfig.params`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldgenParams {
+    /// Tiles across a ground patch within a biome
+    pub patch_size: f32,
+    /// Tiles across the broadest elevation octave (hills and valleys)
+    pub elevation_scale: f32,
+    /// Octaves of elevation noise, each `elevation_lacunarity` times finer than the last
+    pub elevation_octaves: u32,
+    pub elevation_lacunarity: f32,
+    /// Share of each octave's weight passed on to the finer ones
+    pub elevation_roughness: f32,
+    /// Heights below `water_level` flood, below `shore_level` are sand and from `rock_level` up are rock
+    pub water_level: u8,
+    pub shore_level: u8,
+    pub rock_level: u8,
+    /// Multiplies how readily trees are planted everywhere
+    pub tree_density: f32,
+}
+
+impl Default for WorldgenParams {
+    fn default() -> Self {
+        Self {
+            patch_size: PATCH_SIZE,
+            elevation_scale: 40.0,
+            elevation_octaves: 2,
+            // The detail octave is 9 tiles across
+            elevation_lacunarity: 40.0 / 9.0,
+            elevation_roughness: 0.25,
+            water_level: WATER_LEVEL,
+            shore_level: SHORE_LEVEL,
+            rock_level: ROCK_LEVEL,
+            tree_density: 1.0,
+        }
+    }
+}
+
+impl WorldgenParams {
+    /// Clamp out-of-range values, returning a description of each fix
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut fixes = Vec::new();
+        let mut clamp = |name: &str, value: &mut f32, min: f32, max: f32| {
+            let clamped = if value.is_finite() { value.clamp(min, max) } else { min };
+            if clamped != *value {
+                fixes.push(format!("{} = {} is out of range, using {}", name, value, clamped));
+                *value = clamped;
+            }
+        };
+        clamp("patch_size", &mut self.patch_size, 1.0, 1000.0);
+        clamp("elevation_scale", &mut self.elevation_scale, 1.0, 10000.0);
+        clamp("elevation_lacunarity", &mut self.elevation_lacunarity, 1.0, 16.0);
+        clamp("elevation_roughness", &mut self.elevation_roughness, 0.0, 1.0);
+        clamp("tree_density", &mut self.tree_density, 0.0, 100.0);
+
+        if !(1..=8).contains(&self.elevation_octaves) {
+            fixes.push(format!("elevation_octaves = {} is out of range, using 2", self.elevation_octaves));
+            self.elevation_octaves = 2;
+        }
+        if self.water_level > self.shore_level || self.shore_level > self.rock_level {
+            fixes.push(format!(
+                "levels must rise from water ({}) to shore ({}) to rock ({}), using the defaults",
+                self.water_level, self.shore_level, self.rock_level
+            ));
+            (self.water_level, self.shore_level, self.rock_level) = (WATER_LEVEL, SHORE_LEVEL, ROCK_LEVEL);
+        }
+        fixes
+    }
+}
+
+/// Load and validate tuning from disk (fixes are logged)
+pub fn load_worldgen_params<P: AsRef<Path>>(path: P) -> Result<WorldgenParams, ConfigError> {
+    let text = fs::read_to_string(path)?;
+    let mut params: WorldgenParams = ron::from_str(&text)?;
+    for fix in params.validate() {
+        warn!("{}: {}", WORLDGEN_PATH, fix);
+    }
+    Ok(params)
+}
+
+/// Modification time of the tuning file (None if it doesn't exist)
+fn file_stamp(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Modification time of the tuning file when it was last read
+/// Polled like the mod pack manifests (see `mods::PackWatcher`)
+#[derive(Resource)]
+pub struct WorldgenWatcher {
+    timer: Timer,
+    stamp: Option<SystemTime>,
+}
+
+impl Default for WorldgenWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            stamp: None,
+        }
+    }
+}
+
+/// Startup: reads the tuning file if there is one, before the first chunk is generated
+pub fn load_worldgen_file(mut world: ResMut<WorldManager>, mut watcher: ResMut<WorldgenWatcher>, mut failures: Failures) {
+    let path = Path::new(WORLDGEN_PATH);
+    watcher.stamp = file_stamp(path);
+    if watcher.stamp.is_none() {
+        return;
+    }
+    match load_worldgen_params(path) {
+        Ok(params) => {
+            info!("Loaded generation settings from {}", WORLDGEN_PATH);
+            world.set_worldgen_params(params);
+        }
+        Err(e) => failures.report(format!("Failed to load {}", WORLDGEN_PATH), e),
+    }
+}
+
+/// Re-reads the tuning file when it changes on disk (deleting it restores the defaults); a file
+/// that fails to parse keeps the previous numbers
+pub fn watch_worldgen_file(
+    time: Res<Time>,
+    mut watcher: ResMut<WorldgenWatcher>,
+    mut world: ResMut<WorldManager>,
+    mut failures: Failures,
+    mut toasts: MessageWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let path = Path::new(WORLDGEN_PATH);
+    let stamp = file_stamp(path);
+    if stamp == watcher.stamp {
+        return;
+    }
+    watcher.stamp = stamp;
+
+    let params = match stamp.map(|_| load_worldgen_params(path)) {
+        Some(Ok(params)) => params,
+        Some(Err(e)) => return failures.report(format!("Failed to reload {}", WORLDGEN_PATH), e),
+        None => WorldgenParams::default(),
+    };
+    info!("Generation settings changed on disk: {:?}", params);
+    world.set_worldgen_params(params);
+    toasts.write(Toast::info(locale.text("toast-worldgen-reloaded")));
+}
+
+/// Chunk under the mouse cursor, or at the middle of the screen when the cursor is outside the window
+fn chunk_under_cursor(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    origin: &WorldOrigin,
+) -> Option<ChunkPos> {
+    let (camera, camera_transform) = camera_query.single().ok()?;
+    let render_pos = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+        .unwrap_or_else(|| camera_transform.translation().truncate());
+    Some(ChunkPos::from_world(origin.to_world_vec2(render_pos), CHUNK_PIXEL_SIZE))
+}
+
+/// Console command: `regenerate [x y]` discards a chunk's saved and cached data and runs the
+/// generator for it again (default: the chunk under the cursor), for iterating on generator changes;
+/// `regenerate stale` does that for every cached chunk in `model::stale_chunks`
+#[allow(clippy::too_many_arguments)]
+pub fn handle_regenerate_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    mut world: ResMut<WorldManager>,
+    mut tilemaps: Query<&mut TilemapChunkTileData>,
+    settlers: Query<(Entity, &Settler)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    origin: Res<WorldOrigin>,
+) {
+    for command in console_commands.read().filter(|command| command.name == "regenerate") {
+        let stale = command.args.len() == 1 && command.args[0] == "stale";
+        let chunks = match command.args.as_slice() {
+            _ if stale => model::stale_chunks(&world),
+            [] => chunk_under_cursor(&windows, &camera_query, &origin).into_iter().collect(),
+            [x, y] => x.parse().ok().zip(y.parse().ok()).map(|(x, y)| ChunkPos::new(x, y)).into_iter().collect(),
+            _ => Vec::new(),
+        };
+        if chunks.is_empty() {
+            console.print(match stale {
+                true => "No loaded chunks are stale (made by an older generator and never painted)",
+                false => "Usage: regenerate [x y | stale]",
+            });
+            continue;
+        }
+
+        let mut regenerated = 0;
+        for chunk_pos in chunks {
+            if let Err(e) = loader::regenerate_chunk(&mut world, &mut tilemaps, chunk_pos) {
+                console.print(format!("Regenerating chunk ({}, {}) failed: {}", chunk_pos.x, chunk_pos.y, e));
+                continue;
+            }
+            // `entities::sync_settlers` houses the new chunk's settlers
+            for (entity, settler) in settlers.iter() {
+                if settler.home_chunk == chunk_pos {
+                    commands.entity(entity).despawn();
+                }
+            }
+            info!("Regenerated chunk {:?}", chunk_pos);
+            if !stale {
+                console.print(format!(
+                    "Regenerated chunk ({}, {}) (the previous file is kept as a backup)",
+                    chunk_pos.x, chunk_pos.y
+                ));
+            }
+            regenerated += 1;
+        }
+        if stale {
+            console.print(format!("Regenerated {} stale chunks (the previous files are kept as backups)", regenerated));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::biome::value_noise;
+    use crate::world::elevation::elevation_at;
+    use std::env;
+
+    #[test]
+    fn test_worldgen_params() {
+        // Partial files keep the defaults for everything they leave out
+        let path = env::temp_dir().join("worldgen_test_params.ron");
+        fs::write(&path, "(elevation_octaves: 3, water_level: 60)").unwrap();
+        let params = load_worldgen_params(&path).unwrap();
+        assert_eq!((params.elevation_octaves, params.water_level), (3, 60));
+        assert_eq!(params.patch_size, WorldgenParams::default().patch_size);
+        fs::remove_file(&path).unwrap();
+
+        // The defaults generate the same heights as the fixed two octaves did
+        let defaults = WorldgenParams::default();
+        for x in -200..200 {
+            let tile = IVec2::new(x * 7, x * -3);
+            let broad = value_noise(4, 5, tile, 40.0);
+            let detail = value_noise(4, 6, tile, 9.0);
+            assert_eq!(elevation_at(4, tile, &defaults), ((broad * 0.75 + detail * 0.25) * 256.0) as u8);
+        }
+        assert_ne!(
+            (0..100).map(|x| elevation_at(4, IVec2::new(x, 0), &params)).collect::<Vec<_>>(),
+            (0..100).map(|x| elevation_at(4, IVec2::new(x, 0), &defaults)).collect::<Vec<_>>()
+        );
+
+        let mut broken = WorldgenParams {
+            elevation_octaves: 0,
+            shore_level: 10,
+            tree_density: f32::NAN,
+            ..default()
+        };
+        assert_eq!(broken.validate().len(), 3);
+        assert_eq!((broken.elevation_octaves, broken.shore_level, broken.tree_density), (2, SHORE_LEVEL, 0.0));
+    }
+}
@@ -0,0 +1,224 @@
+use super::generator::GeneratorConfig;
+use super::manager::WorldManager;
+use super::metadata::{self, MetadataError, WorldMetadata, METADATA_FILE_NAME};
+use crate::console::{ConsoleCommand, ConsoleState};
+use bevy::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// Error type for duplicating and renaming worlds
+#[derive(Debug, Error)]
+pub enum WorldDirError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Metadata error: {0}")]
+    Metadata(#[from] MetadataError),
+    /// Names must be a single plain directory name
+    #[error("Invalid world name: {0:?}")]
+    InvalidName(String),
+    #[error("{0:?} does not exist")]
+    NotFound(PathBuf),
+    #[error("{0:?} already exists")]
+    DestinationExists(PathBuf),
+    /// The world currently open can't be renamed out from under the game
+    #[error("World '{0}' is currently open")]
+    WorldInUse(String),
+}
+
+/// Resolve a world name to its directory under `saves_root`
+/// Staging directory names (`.copying`, `.importing`) are refused
+pub(super) fn world_dir(saves_root: &Path, name: &str) -> Result<PathBuf, WorldDirError> {
+    let mut components = Path::new(name).components();
+    let is_plain = matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+    if !is_plain || name.ends_with(".copying") || name.ends_with(".importing") {
+        return Err(WorldDirError::InvalidName(name.to_string()));
+    }
+    Ok(saves_root.join(name))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), io::Error> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Set the display name stored in a world's metadata (creating the file if the world has none)
+fn set_world_name(world_dir: &Path, name: &str) -> Result<(), WorldDirError> {
+    let path = world_dir.join(METADATA_FILE_NAME);
+    let mut world_metadata = if path.exists() {
+        metadata::load_metadata(&path)?
+    } else {
+        // Saved before worlds had metadata files
+        WorldMetadata {
+            generator: GeneratorConfig::legacy(),
+            ..default()
+        }
+    };
+    world_metadata.name = name.to_string();
+    metadata::save_metadata(&world_metadata, &path)?;
+    Ok(())
+}
+
+/// Copy a world to a new name
+/// Copies into a `<name>.copying` staging directory first, then renames it into place,
+/// so an interrupted copy never looks like a real world
+pub fn duplicate_world(saves_root: &Path, from: &str, to: &str) -> Result<PathBuf, WorldDirError> {
+    let source = world_dir(saves_root, from)?;
+    let destination = world_dir(saves_root, to)?;
+    if !source.is_dir() {
+        return Err(WorldDirError::NotFound(source));
+    }
+    if destination.exists() {
+        return Err(WorldDirError::DestinationExists(destination));
+    }
+
+    let staging = saves_root.join(format!("{}.copying", to));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = copy_dir(&source, &staging)
+        .map_err(WorldDirError::from)
+        .and_then(|_| set_world_name(&staging, to))
+        .and_then(|_| fs::rename(&staging, &destination).map_err(WorldDirError::from));
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result.map(|_| destination)
+}
+
+/// Rename a world's directory (a single atomic rename) and update its metadata
+pub fn rename_world(saves_root: &Path, from: &str, to: &str) -> Result<PathBuf, WorldDirError> {
+    let source = world_dir(saves_root, from)?;
+    let destination = world_dir(saves_root, to)?;
+    if !source.is_dir() {
+        return Err(WorldDirError::NotFound(source));
+    }
+    if destination.exists() {
+        return Err(WorldDirError::DestinationExists(destination));
+    }
+
+    fs::rename(&source, &destination)?;
+    set_world_name(&destination, to)?;
+    Ok(destination)
+}
+
+/// Names of every world directory under `saves_root`, sorted
+pub fn list_worlds(saves_root: &Path) -> Result<Vec<String>, io::Error> {
+    let mut names: Vec<String> = fs::read_dir(saves_root)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.ends_with(".copying") && !name.ends_with(".importing"))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Console commands (there is no world selection menu yet):
+/// `worlds` lists saved worlds, `duplicate <from> <to>` copies one, `rename <from> <to>` renames one
+pub fn handle_world_commands(
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<ConsoleState>,
+    world: Res<WorldManager>,
+) {
+    let saves_root = world.save_directory.parent().unwrap_or(Path::new("saves"));
+    let current = world
+        .save_directory
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    for command in console_commands.read() {
+        match command.name.as_str() {
+            "worlds" => match list_worlds(saves_root) {
+                Ok(names) if names.is_empty() => console.print("No saved worlds"),
+                Ok(names) => {
+                    for name in names {
+                        let marker = if name == current { " (open)" } else { "" };
+                        console.print(format!("  {}{}", name, marker));
+                    }
+                }
+                Err(e) => console.print(format!("Failed to list worlds: {}", e)),
+            },
+            "duplicate" => {
+                let (Some(from), Some(to)) = (command.args.first(), command.args.get(1)) else {
+                    console.print("Usage: duplicate <from> <to>");
+                    continue;
+                };
+                match duplicate_world(saves_root, from, to) {
+                    Ok(path) => {
+                        console.print(format!("Copied '{}' to {:?}", from, path));
+                        if *from == current {
+                            console.print("Only saved data was copied; press Ctrl+S first to include unsaved edits");
+                        }
+                    }
+                    Err(e) => console.print(format!("Duplicate failed: {}", e)),
+                }
+            }
+            "rename" => {
+                let (Some(from), Some(to)) = (command.args.first(), command.args.get(1)) else {
+                    console.print("Usage: rename <from> <to>");
+                    continue;
+                };
+                let result = if *from == current {
+                    Err(WorldDirError::WorldInUse(from.clone()))
+                } else {
+                    rename_world(saves_root, from, to)
+                };
+                match result {
+                    Ok(path) => console.print(format!("Renamed '{}' to {:?}", from, path)),
+                    Err(e) => console.print(format!("Rename failed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_duplicate_and_rename_world() {
+        let root = env::temp_dir().join("worldseed_test_worlds");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sandbox").join("chunks")).unwrap();
+        fs::write(root.join("sandbox").join("chunks").join("chunk_0_0.bin"), [1u8, 2, 3]).unwrap();
+
+        let copy = duplicate_world(&root, "sandbox", "experiment").unwrap();
+        assert_eq!(fs::read(copy.join("chunks").join("chunk_0_0.bin")).unwrap(), vec![1, 2, 3]);
+        assert_eq!(metadata::load_metadata(copy.join(METADATA_FILE_NAME)).unwrap().name, "experiment");
+        assert!(root.join("sandbox").join("chunks").join("chunk_0_0.bin").exists());
+
+        let renamed = rename_world(&root, "experiment", "broken").unwrap();
+        assert!(!copy.exists());
+        assert_eq!(metadata::load_metadata(renamed.join(METADATA_FILE_NAME)).unwrap().name, "broken");
+        assert_eq!(list_worlds(&root).unwrap(), vec!["broken", "sandbox"]);
+
+        assert!(matches!(
+            duplicate_world(&root, "sandbox", "broken"),
+            Err(WorldDirError::DestinationExists(_))
+        ));
+        assert!(matches!(rename_world(&root, "missing", "other"), Err(WorldDirError::NotFound(_))));
+        assert!(matches!(
+            duplicate_world(&root, "sandbox", "../escape"),
+            Err(WorldDirError::InvalidName(_))
+        ));
+        assert!(matches!(world_dir(&root, "other.importing"), Err(WorldDirError::InvalidName(_))));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}